@@ -0,0 +1,372 @@
+//! Match analytics viewer (`launcher analytics --telemetry FILE`): loads the telemetry the
+//! server saves with `--match-records` and draws death, damage and traffic heatmaps and
+//! the players' paths over the generated level. Files of several matches on the same seed
+//! add up; the panel shows the generator settings they were played on.
+
+use std::path::PathBuf;
+
+use bevy::input::mouse::MouseWheel;
+use bevy::log::LogPlugin;
+use bevy::prelude::{
+    App, AssetPlugin, Assets, ButtonInput, Camera3d, Color, Commands, Component, DefaultPlugins,
+    Gizmos, Isometry3d, KeyCode, Mesh, MessageReader, Name, Plugin, PluginGroup, Quat, Query, Res,
+    ResMut, Resource, Result, StandardMaterial, Startup, Time, Transform, Update, Vec2, Vec3, With,
+    default, warn,
+};
+use bevy::window::{Window, WindowPlugin};
+use bevy_inspector_egui::bevy_egui::{EguiContexts, EguiPlugin, EguiPrimaryContextPass, egui};
+use shared::level::generation::{LevelConfig, build_level_visuals, generate_level};
+use shared::match_analytics::{HEATMAP_CELL_SIZE, Heatmap, MatchHeatmaps};
+use shared::match_recap::MatchTelemetry;
+
+/// Cells listed per layer in the panel.
+const HOTSPOTS_LISTED: usize = 5;
+/// Where the camera starts, from the middle of the level.
+const CAMERA_OFFSET: Vec3 = Vec3::new(0.0, 120.0, 60.0);
+/// Meters per second the camera pans at its starting height.
+const PAN_SPEED: f32 = 40.0;
+const ZOOM_STEP: f32 = 0.1;
+const CAMERA_HEIGHT_RANGE: (f32, f32) = (10.0, 400.0);
+/// Heat is drawn this far above the floor, so it is not hidden in it.
+const DRAW_LIFT: f32 = 0.15;
+
+const DEATH_COLOR: Color = Color::srgb(1.0, 0.15, 0.1);
+const DAMAGE_COLOR: Color = Color::srgb(1.0, 0.65, 0.1);
+const TRAFFIC_COLOR: Color = Color::srgb(0.2, 0.6, 1.0);
+const TRACK_COLOR: Color = Color::srgba(0.9, 0.9, 0.9, 0.35);
+
+/// Telemetry files to show.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct AnalyticsSettings {
+    pub telemetry: Vec<PathBuf>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Layer {
+    Deaths,
+    Damage,
+    Traffic,
+    Tracks,
+}
+
+impl Layer {
+    const ALL: [Layer; 4] = [Layer::Deaths, Layer::Damage, Layer::Traffic, Layer::Tracks];
+
+    fn label(self) -> &'static str {
+        match self {
+            Layer::Deaths => "Deaths",
+            Layer::Damage => "Damage",
+            Layer::Traffic => "Traffic",
+            Layer::Tracks => "Paths",
+        }
+    }
+
+    fn key(self) -> KeyCode {
+        match self {
+            Layer::Deaths => KeyCode::Digit1,
+            Layer::Damage => KeyCode::Digit2,
+            Layer::Traffic => KeyCode::Digit3,
+            Layer::Tracks => KeyCode::Digit4,
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Layer::Deaths => DEATH_COLOR,
+            Layer::Damage => DAMAGE_COLOR,
+            Layer::Traffic => TRAFFIC_COLOR,
+            Layer::Tracks => TRACK_COLOR,
+        }
+    }
+}
+
+/// The loaded matches and what is shown of them.
+#[derive(Resource)]
+struct AnalyticsView {
+    seed: Option<u64>,
+    matches: Vec<MatchTelemetry>,
+    skipped: Vec<String>,
+    heatmaps: MatchHeatmaps,
+    visible: Vec<Layer>,
+}
+
+impl AnalyticsView {
+    fn heatmap(&self, layer: Layer) -> Option<&Heatmap> {
+        match layer {
+            Layer::Deaths => Some(&self.heatmaps.deaths),
+            Layer::Damage => Some(&self.heatmaps.damage),
+            Layer::Traffic => Some(&self.heatmaps.traffic),
+            Layer::Tracks => None,
+        }
+    }
+
+    fn toggle(&mut self, layer: Layer) {
+        if let Some(index) = self.visible.iter().position(|shown| *shown == layer) {
+            self.visible.remove(index);
+        } else {
+            self.visible.push(layer);
+        }
+    }
+}
+
+#[derive(Component)]
+struct AnalyticsCamera;
+
+/// A windowed app with the level of the telemetry's seed and the viewer, no networking.
+pub fn create_analytics_app(asset_path: String, telemetry: Vec<PathBuf>) -> App {
+    let mut app = App::new();
+    app.add_plugins(
+        DefaultPlugins
+            .set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: "Game Test - Match analytics".to_string(),
+                    resolution: (1280, 720).into(),
+                    ..default()
+                }),
+                ..default()
+            })
+            .set(AssetPlugin {
+                file_path: asset_path,
+                ..Default::default()
+            })
+            .disable::<LogPlugin>(),
+    );
+    app.add_plugins(EguiPlugin::default());
+    app.insert_resource(AnalyticsSettings { telemetry });
+    app.add_plugins(ClientAnalyticsPlugin);
+    app
+}
+
+pub struct ClientAnalyticsPlugin;
+
+impl Plugin for ClientAnalyticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AnalyticsSettings>();
+        app.add_systems(Startup, load_analytics);
+        app.add_systems(
+            Update,
+            (
+                toggle_analytics_layers,
+                move_analytics_camera,
+                draw_analytics,
+            ),
+        );
+        app.add_systems(EguiPrimaryContextPass, analytics_window);
+    }
+}
+
+/// Loads the telemetry, keeping the matches played on the first file's seed, and builds
+/// that level.
+fn load_analytics(
+    mut commands: Commands,
+    settings: Res<AnalyticsSettings>,
+    meshes: ResMut<Assets<Mesh>>,
+    materials: Option<ResMut<Assets<StandardMaterial>>>,
+) {
+    let mut seed = None;
+    let mut matches = Vec::new();
+    let mut skipped = Vec::new();
+    for path in &settings.telemetry {
+        match MatchTelemetry::load(path) {
+            Ok(telemetry) if seed.is_none_or(|seed| seed == telemetry.seed) => {
+                seed = Some(telemetry.seed);
+                matches.push(telemetry);
+            }
+            Ok(telemetry) => skipped.push(format!(
+                "{}: seed {}, not {}",
+                path.display(),
+                telemetry.seed,
+                seed.unwrap_or_default()
+            )),
+            Err(e) => skipped.push(format!("{}: {}", path.display(), e)),
+        }
+    }
+    for reason in &skipped {
+        warn!("Telemetry skipped: {}", reason);
+    }
+
+    let heatmaps = MatchHeatmaps::from_telemetry(&matches, HEATMAP_CELL_SIZE);
+    let mut focus = Vec3::ZERO;
+    if let Some(seed) = seed {
+        let level_graph = generate_level(LevelConfig::for_seed(seed));
+        // Look at the middle of the zones.
+        focus = level_graph
+            .zones
+            .values()
+            .map(|zone| zone.position)
+            .sum::<Vec3>()
+            / level_graph.zones.len().max(1) as f32;
+        build_level_visuals(commands.reborrow(), meshes, materials, &level_graph);
+    }
+
+    commands.spawn((
+        AnalyticsCamera,
+        Camera3d::default(),
+        Transform::from_translation(focus + CAMERA_OFFSET).looking_at(focus, Vec3::Y),
+        Name::new("AnalyticsCamera"),
+    ));
+    commands.insert_resource(AnalyticsView {
+        seed,
+        matches,
+        skipped,
+        heatmaps,
+        visible: vec![Layer::Deaths, Layer::Traffic],
+    });
+}
+
+fn toggle_analytics_layers(keys: Res<ButtonInput<KeyCode>>, view: Option<ResMut<AnalyticsView>>) {
+    let Some(mut view) = view else {
+        return;
+    };
+    for layer in Layer::ALL {
+        if keys.just_pressed(layer.key()) {
+            view.toggle(layer);
+        }
+    }
+}
+
+/// WASD or the arrows pan over the floor, the mouse wheel moves closer or farther.
+fn move_analytics_camera(
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut wheel: MessageReader<MouseWheel>,
+    mut camera: Query<&mut Transform, With<AnalyticsCamera>>,
+) {
+    let Ok(mut transform) = camera.single_mut() else {
+        return;
+    };
+    let pressed = |keys_for: [KeyCode; 2]| keys_for.iter().any(|key| keys.pressed(*key));
+    let mut pan = Vec2::ZERO;
+    if pressed([KeyCode::KeyW, KeyCode::ArrowUp]) {
+        pan.y -= 1.0;
+    }
+    if pressed([KeyCode::KeyS, KeyCode::ArrowDown]) {
+        pan.y += 1.0;
+    }
+    if pressed([KeyCode::KeyA, KeyCode::ArrowLeft]) {
+        pan.x -= 1.0;
+    }
+    if pressed([KeyCode::KeyD, KeyCode::ArrowRight]) {
+        pan.x += 1.0;
+    }
+    // Farther away, the same key press covers more ground.
+    let speed = PAN_SPEED * transform.translation.y / CAMERA_OFFSET.y;
+    let pan = pan.normalize_or_zero() * speed * time.delta_secs();
+    transform.translation += Vec3::new(pan.x, 0.0, pan.y);
+
+    let scroll: f32 = wheel.read().map(|event| event.y).sum();
+    if scroll != 0.0 {
+        let forward = transform.forward().as_vec3();
+        let height = transform.translation.y;
+        let target = (height * (1.0 - scroll * ZOOM_STEP))
+            .clamp(CAMERA_HEIGHT_RANGE.0, CAMERA_HEIGHT_RANGE.1);
+        // Along the view direction, so the point under the middle of the screen stays.
+        transform.translation += forward * (height - target) / -forward.y;
+    }
+}
+
+fn draw_analytics(view: Option<Res<AnalyticsView>>, mut gizmos: Gizmos) {
+    let Some(view) = view else {
+        return;
+    };
+    let flat = Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2);
+    for layer in &view.visible {
+        if let Some(heatmap) = view.heatmap(*layer) {
+            let max = heatmap.max();
+            if max <= 0.0 {
+                continue;
+            }
+            for cell in heatmap.cells() {
+                let heat = cell.value / max;
+                let size = heatmap.cell_size * (0.3 + 0.65 * heat.sqrt());
+                gizmos.rect(
+                    Isometry3d::new(cell.center + Vec3::Y * DRAW_LIFT, flat),
+                    Vec2::splat(size),
+                    layer.color().with_alpha(0.25 + 0.75 * heat),
+                );
+            }
+        } else {
+            let paths = view
+                .matches
+                .iter()
+                .flat_map(|telemetry| &telemetry.tracks)
+                .flat_map(|track| &track.segments);
+            for segment in paths {
+                gizmos.linestrip(
+                    segment.iter().map(|point| *point + Vec3::Y * DRAW_LIFT),
+                    layer.color(),
+                );
+            }
+        }
+    }
+}
+
+fn analytics_window(mut contexts: EguiContexts, view: Option<ResMut<AnalyticsView>>) -> Result {
+    let Some(mut view) = view else {
+        return Ok(());
+    };
+    egui::Window::new("Match analytics")
+        .default_pos((12.0, 12.0))
+        .show(contexts.ctx_mut()?, |ui| {
+            let Some(seed) = view.seed else {
+                ui.label("No telemetry loaded. Record some with `server --match-records DIR`.");
+                return;
+            };
+            let config = LevelConfig::for_seed(seed);
+            ui.label(format!(
+                "{} {} on seed {}",
+                view.matches.len(),
+                if view.matches.len() == 1 {
+                    "match"
+                } else {
+                    "matches"
+                },
+                seed
+            ));
+            ui.label(format!(
+                "Generator: {} zones, {:.0} m apart, depth {}",
+                config.target_zone_count, config.min_zone_spacing, config.max_depth
+            ));
+            let kills: usize = view
+                .matches
+                .iter()
+                .map(|telemetry| telemetry.kills.len())
+                .sum();
+            let hits: usize = view
+                .matches
+                .iter()
+                .map(|telemetry| telemetry.damage.len())
+                .sum();
+            ui.label(format!("{} kills, {} hits", kills, hits));
+            for reason in &view.skipped {
+                ui.colored_label(egui::Color32::YELLOW, format!("Skipped {}", reason));
+            }
+
+            ui.separator();
+            for (index, layer) in Layer::ALL.into_iter().enumerate() {
+                let mut shown = view.visible.contains(&layer);
+                if ui
+                    .checkbox(&mut shown, format!("{} ({})", layer.label(), index + 1))
+                    .changed()
+                {
+                    view.toggle(layer);
+                }
+            }
+
+            for layer in Layer::ALL {
+                let Some(heatmap) = view.heatmap(layer).filter(|heatmap| !heatmap.is_empty())
+                else {
+                    continue;
+                };
+                ui.separator();
+                ui.strong(format!("{} hotspots", layer.label()));
+                for cell in heatmap.hottest(HOTSPOTS_LISTED) {
+                    ui.label(format!(
+                        "({:.0}, {:.0}, {:.0}): {:.0}",
+                        cell.center.x, cell.center.y, cell.center.z, cell.value
+                    ));
+                }
+            }
+        });
+    Ok(())
+}
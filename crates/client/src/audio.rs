@@ -0,0 +1,603 @@
+use avian3d::prelude::{LinearVelocity, Position};
+use bevy::audio::{
+    AudioPlayer, AudioSink, AudioSinkPlayback, PlaybackSettings, SpatialAudioSink, SpatialListener,
+    Volume,
+};
+use bevy::prelude::{
+    Added, App, AssetServer, Camera3d, Changed, Commands, Component, Entity, IntoScheduleConfigs,
+    Local, MessageReader, Name, OnEnter, OnExit, Plugin, Query, Res, ResMut, Resource, Time,
+    Transform, Update, Vec3, With, Without, warn,
+};
+use serde::{Deserialize, Serialize};
+use shared::components::health::Health;
+use shared::components::weapons::Gun;
+use shared::inputs::movement::{GroundState, WALK_SPEED};
+use shared::protocol::{CharacterMarker, PlayerId};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::crosshair::{HitFeedback, HitFlash};
+use crate::{ClientGameState, LocalPlayerId, config_file};
+
+/// Seconds the music stays ducked after the last combat event.
+const COMBAT_HOLD_SECS: f32 = 4.0;
+/// How fast (per second) the music fades toward its ducked or normal level.
+const DUCK_FADE_RATE: f32 = 3.0;
+/// Ground covered between two footsteps: two steps a second at walking speed.
+const STRIDE_LENGTH: f32 = WALK_SPEED * 0.5;
+/// Slower than this a character is standing still.
+const MIN_FOOTSTEP_SPEED: f32 = 0.5;
+const AUDIO_SETTINGS_FILE: &str = "audio_settings.ron";
+
+/// Mixer bus an audio entity is routed through.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AudioBus {
+    Music,
+    Sfx,
+    Voice,
+    /// Menu and HUD sounds: mixed with the SFX volume but never spatialized.
+    Ui,
+}
+
+/// Faders shown in the settings UI, `None` being the master fader.
+pub const MIXER_FADERS: [Option<AudioBus>; 4] = [
+    None,
+    Some(AudioBus::Music),
+    Some(AudioBus::Sfx),
+    Some(AudioBus::Voice),
+];
+
+pub fn fader_label(fader: Option<AudioBus>) -> &'static str {
+    match fader {
+        None => "Master",
+        Some(AudioBus::Music) => "Music",
+        Some(AudioBus::Sfx) => "SFX",
+        Some(AudioBus::Voice) => "Voice",
+        Some(AudioBus::Ui) => "UI",
+    }
+}
+
+/// Volumes of the master and per-bus faders, all in 0.0..=1.0. Changed by the settings UI
+/// and applied to every playing sound routed through [`MixedAudio`].
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AudioMixer {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+    pub voice: f32,
+    /// Music volume multiplier while in combat.
+    pub combat_duck: f32,
+}
+
+impl Default for AudioMixer {
+    fn default() -> Self {
+        Self {
+            master: 0.8,
+            music: 0.6,
+            sfx: 0.8,
+            voice: 1.0,
+            combat_duck: 0.35,
+        }
+    }
+}
+
+impl AudioMixer {
+    pub fn bus_volume(&self, bus: AudioBus) -> f32 {
+        let bus_volume = match bus {
+            AudioBus::Music => self.music,
+            AudioBus::Sfx | AudioBus::Ui => self.sfx,
+            AudioBus::Voice => self.voice,
+        };
+        (self.master * bus_volume).clamp(0.0, 1.0)
+    }
+
+    /// Value of the master fader for `None`, otherwise of the fader of `bus`.
+    pub fn fader(&self, fader: Option<AudioBus>) -> f32 {
+        match fader {
+            None => self.master,
+            Some(AudioBus::Music) => self.music,
+            Some(AudioBus::Sfx | AudioBus::Ui) => self.sfx,
+            Some(AudioBus::Voice) => self.voice,
+        }
+    }
+
+    fn fader_mut(&mut self, fader: Option<AudioBus>) -> &mut f32 {
+        match fader {
+            None => &mut self.master,
+            Some(AudioBus::Music) => &mut self.music,
+            Some(AudioBus::Sfx | AudioBus::Ui) => &mut self.sfx,
+            Some(AudioBus::Voice) => &mut self.voice,
+        }
+    }
+
+    /// Move a fader by `delta`, keeping it within 0.0..=1.0.
+    pub fn adjust(&mut self, fader: Option<AudioBus>, delta: f32) {
+        self.set(fader, self.fader(fader) + delta);
+    }
+
+    /// Set a fader, keeping it within 0.0..=1.0.
+    pub fn set(&mut self, fader: Option<AudioBus>, volume: f32) {
+        *self.fader_mut(fader) = volume.clamp(0.0, 1.0);
+    }
+
+    /// Final volume of a sound on `bus` with its own `volume`, given the current ducking
+    /// factor (1.0 = not ducked).
+    pub fn mix(&self, bus: AudioBus, volume: f32, duck: f32) -> f32 {
+        let duck = if bus == AudioBus::Music { duck } else { 1.0 };
+        self.bus_volume(bus) * volume * duck
+    }
+
+    pub fn to_ron(&self) -> Result<String, String> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())
+    }
+
+    /// Faders from a settings file, missing or non-finite ones keeping their default and all
+    /// of them kept within 0.0..=1.0.
+    pub fn from_ron(text: &str) -> Result<Self, String> {
+        let mut mixer: Self = ron::from_str(text).map_err(|e| e.to_string())?;
+        let defaults = Self::default();
+        let finite_or = |value: f32, default: f32| if value.is_finite() { value } else { default };
+        for fader in MIXER_FADERS {
+            mixer.set(fader, finite_or(mixer.fader(fader), defaults.fader(fader)));
+        }
+        mixer.combat_duck = finite_or(mixer.combat_duck, defaults.combat_duck).clamp(0.0, 1.0);
+        Ok(mixer)
+    }
+}
+
+/// Where the mixer faders are saved; `None` keeps them in memory only.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct AudioSettingsPath(pub Option<PathBuf>);
+
+/// Paces a character's footsteps from the ground it covers.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FootstepCadence {
+    distance: f32,
+    airborne: bool,
+}
+
+impl FootstepCadence {
+    /// Move at `speed` for `delta_secs`. True when a foot lands, landing from a jump or a
+    /// fall included.
+    pub fn advance(&mut self, speed: f32, grounded: bool, delta_secs: f32) -> bool {
+        let landed = grounded && self.airborne;
+        self.airborne = !grounded;
+        if !grounded || speed < MIN_FOOTSTEP_SPEED {
+            self.distance = 0.0;
+            return landed;
+        }
+
+        self.distance += speed * delta_secs;
+        if self.distance >= STRIDE_LENGTH {
+            self.distance %= STRIDE_LENGTH;
+            return true;
+        }
+        landed
+    }
+}
+
+/// Runtime music ducking, driven by combat events.
+#[derive(Resource, Debug)]
+pub struct MusicDucking {
+    /// Current music multiplier, fading between 1.0 and [`AudioMixer::combat_duck`].
+    pub level: f32,
+    combat_remaining: f32,
+}
+
+impl Default for MusicDucking {
+    fn default() -> Self {
+        Self {
+            level: 1.0,
+            combat_remaining: 0.0,
+        }
+    }
+}
+
+impl MusicDucking {
+    pub fn trigger_combat(&mut self) {
+        self.combat_remaining = COMBAT_HOLD_SECS;
+    }
+
+    pub fn in_combat(&self) -> bool {
+        self.combat_remaining > 0.0
+    }
+
+    pub fn tick(&mut self, delta_secs: f32, combat_duck: f32) {
+        self.combat_remaining = (self.combat_remaining - delta_secs).max(0.0);
+        let target = if self.in_combat() { combat_duck } else { 1.0 };
+        let step = DUCK_FADE_RATE * delta_secs;
+        self.level += (target - self.level).clamp(-step, step);
+    }
+}
+
+/// Routes an audio entity through the mixer. `volume` is the sound's own gain before the
+/// bus and master faders are applied.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct MixedAudio {
+    pub bus: AudioBus,
+    pub volume: f32,
+}
+
+#[derive(Component)]
+struct GameMusic;
+
+/// Spawn a one-shot sound on `bus`. Sounds with a `position` are spatialized, except on the
+/// UI bus.
+pub fn play_sound(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    mixer: &AudioMixer,
+    path: &'static str,
+    bus: AudioBus,
+    position: Option<Vec3>,
+) {
+    let spatial = position.is_some() && bus != AudioBus::Ui;
+    commands.spawn((
+        Name::new(format!("Sound {}", path)),
+        AudioPlayer::new(asset_server.load(path)),
+        PlaybackSettings::DESPAWN
+            .with_volume(Volume::Linear(mixer.mix(bus, 1.0, 1.0)))
+            .with_spatial(spatial),
+        MixedAudio { bus, volume: 1.0 },
+        Transform::from_translation(position.unwrap_or_default()),
+    ));
+}
+
+pub struct ClientAudioPlugin;
+
+impl Plugin for ClientAudioPlugin {
+    fn build(&self, app: &mut App) {
+        let settings_path = config_file(AUDIO_SETTINGS_FILE);
+        let mixer = settings_path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .map_or_else(AudioMixer::default, |text| {
+                AudioMixer::from_ron(&text).unwrap_or_else(|e| {
+                    warn!("Invalid audio settings, using defaults: {}", e);
+                    AudioMixer::default()
+                })
+            });
+        app.insert_resource(mixer);
+        app.insert_resource(AudioSettingsPath(settings_path));
+        app.init_resource::<MusicDucking>();
+        app.add_systems(OnEnter(ClientGameState::Playing), start_game_music);
+        app.add_systems(OnExit(ClientGameState::Playing), stop_game_music);
+        app.add_systems(
+            Update,
+            (
+                attach_spatial_listener,
+                play_weapon_sounds,
+                play_footsteps,
+                play_damage_sounds,
+                play_hit_sounds,
+                update_music_ducking,
+                apply_mixer_volumes,
+                save_audio_settings,
+            )
+                .chain(),
+        );
+    }
+}
+
+fn start_game_music(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mixer: Res<AudioMixer>,
+    existing: Query<(), With<GameMusic>>,
+) {
+    if !existing.is_empty() {
+        return;
+    }
+
+    commands.spawn((
+        Name::new("GameMusic"),
+        GameMusic,
+        AudioPlayer::new(asset_server.load("audio/ambient_hum.mp3")),
+        PlaybackSettings::LOOP.with_volume(Volume::Linear(mixer.mix(AudioBus::Music, 1.0, 1.0))),
+        MixedAudio {
+            bus: AudioBus::Music,
+            volume: 1.0,
+        },
+    ));
+}
+
+fn stop_game_music(mut commands: Commands, music: Query<Entity, With<GameMusic>>) {
+    for entity in &music {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn attach_spatial_listener(
+    mut commands: Commands,
+    cameras: Query<Entity, (With<Camera3d>, Without<SpatialListener>)>,
+) {
+    for camera in &cameras {
+        commands.entity(camera).insert(SpatialListener::new(0.3));
+    }
+}
+
+/// Gunshots and reloads of every armed character, heard from where they happen.
+fn play_weapon_sounds(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mixer: Res<AudioMixer>,
+    mut ducking: ResMut<MusicDucking>,
+    mut previous: Local<HashMap<Entity, (u32, f32, bool)>>,
+    guns: Query<(Entity, &Gun, &Position), Changed<Gun>>,
+    armed: Query<(), With<Gun>>,
+) {
+    previous.retain(|entity, _| armed.contains(*entity));
+    for (entity, gun, position) in &guns {
+        // Heat weapons never spend ammo: a shot shows as a rise in heat, venting as a reload.
+        let heat = gun.heat.as_ref().map_or(0.0, |heat| heat.level);
+        let reloading_or_venting = gun.is_reloading || gun.is_venting();
+        let Some((ammo, previous_heat, reloading)) =
+            previous.insert(entity, (gun.ammo_in_magazine, heat, reloading_or_venting))
+        else {
+            continue;
+        };
+
+        if gun.ammo_in_magazine < ammo || heat > previous_heat {
+            ducking.trigger_combat();
+            play_sound(
+                &mut commands,
+                &asset_server,
+                &mixer,
+                "audio/weapon_fire.mp3",
+                AudioBus::Sfx,
+                Some(position.0),
+            );
+        }
+        if reloading_or_venting && !reloading {
+            play_sound(
+                &mut commands,
+                &asset_server,
+                &mixer,
+                "audio/weapon_reload.mp3",
+                AudioBus::Sfx,
+                Some(position.0),
+            );
+        }
+    }
+}
+
+/// Footsteps of every character walking on the ground, paced by its speed.
+fn play_footsteps(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mixer: Res<AudioMixer>,
+    time: Res<Time>,
+    mut cadences: Local<HashMap<Entity, FootstepCadence>>,
+    characters: Query<
+        (Entity, &Position, &LinearVelocity, Option<&GroundState>),
+        With<CharacterMarker>,
+    >,
+) {
+    let delta_secs = time.delta_secs();
+    cadences.retain(|entity, _| characters.contains(*entity));
+    for (entity, position, velocity, ground) in &characters {
+        let grounded = ground.is_none_or(|ground| ground.is_grounded);
+        let speed = velocity.0.with_y(0.0).length();
+        if cadences
+            .entry(entity)
+            .or_default()
+            .advance(speed, grounded, delta_secs)
+        {
+            play_sound(
+                &mut commands,
+                &asset_server,
+                &mixer,
+                "audio/footstep_concrete.mp3",
+                AudioBus::Sfx,
+                Some(position.0),
+            );
+        }
+    }
+}
+
+fn play_damage_sounds(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mixer: Res<AudioMixer>,
+    mut ducking: ResMut<MusicDucking>,
+    mut previous_health: Local<Option<f32>>,
+    local_player_id: Res<LocalPlayerId>,
+    players: Query<(&PlayerId, &Health), Changed<Health>>,
+) {
+    let Some((_, health)) = players
+        .iter()
+        .find(|(player_id, _)| player_id.0.to_bits() == local_player_id.0)
+    else {
+        return;
+    };
+
+    if previous_health.is_some_and(|previous| health.current < previous) {
+        ducking.trigger_combat();
+        // The hurt sound belongs to the local player, so it is not spatialized.
+        play_sound(
+            &mut commands,
+            &asset_server,
+            &mixer,
+            "audio/damage_taken.wav",
+            AudioBus::Voice,
+            None,
+        );
+    }
+    *previous_health = Some(health.current);
+}
+
+/// Hit marker and kill confirmation sounds for the local player's hits, one per frame with
+/// kills taking precedence.
+fn play_hit_sounds(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mixer: Res<AudioMixer>,
+    mut feedback: MessageReader<HitFeedback>,
+) {
+    let mut strongest = None;
+    for HitFeedback(flash) in feedback.read() {
+        if strongest != Some(HitFlash::Kill) {
+            strongest = Some(*flash);
+        }
+    }
+    let Some(flash) = strongest else {
+        return;
+    };
+
+    let path = match flash {
+        HitFlash::Hit => "audio/hit_marker.wav",
+        HitFlash::Kill => "audio/kill_confirmed.wav",
+    };
+    play_sound(
+        &mut commands,
+        &asset_server,
+        &mixer,
+        path,
+        AudioBus::Ui,
+        None,
+    );
+}
+
+fn update_music_ducking(
+    time: Res<Time>,
+    mixer: Res<AudioMixer>,
+    mut ducking: ResMut<MusicDucking>,
+) {
+    // Only touch the resource while fading so volumes are not re-applied every frame.
+    if ducking.in_combat() || ducking.level != 1.0 {
+        ducking.tick(time.delta_secs(), mixer.combat_duck);
+    }
+}
+
+fn apply_mixer_volumes(
+    mixer: Res<AudioMixer>,
+    ducking: Res<MusicDucking>,
+    mut sinks: Query<(&MixedAudio, &mut AudioSink)>,
+    mut spatial_sinks: Query<(&MixedAudio, &mut SpatialAudioSink)>,
+    new_sinks: Query<(), Added<AudioSink>>,
+    new_spatial_sinks: Query<(), Added<SpatialAudioSink>>,
+) {
+    let mixer_changed = mixer.is_changed() || ducking.is_changed();
+    if !mixer_changed && new_sinks.is_empty() && new_spatial_sinks.is_empty() {
+        return;
+    }
+
+    for (mixed, mut sink) in &mut sinks {
+        sink.set_volume(Volume::Linear(mixer.mix(
+            mixed.bus,
+            mixed.volume,
+            ducking.level,
+        )));
+    }
+    for (mixed, mut sink) in &mut spatial_sinks {
+        sink.set_volume(Volume::Linear(mixer.mix(
+            mixed.bus,
+            mixed.volume,
+            ducking.level,
+        )));
+    }
+}
+
+/// Write the faders back whenever the settings UI moves one.
+fn save_audio_settings(mixer: Res<AudioMixer>, settings_path: Res<AudioSettingsPath>) {
+    if !mixer.is_changed() || mixer.is_added() {
+        return;
+    }
+    let Some(path) = &settings_path.0 else {
+        return;
+    };
+    let written = mixer.to_ron().and_then(|text| {
+        path.parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(path, text))
+            .map_err(|e| e.to_string())
+    });
+    if let Err(e) = written {
+        warn!("Failed to save audio settings to {}: {}", path.display(), e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AudioBus, AudioMixer, FootstepCadence, MusicDucking, STRIDE_LENGTH};
+    use shared::inputs::movement::WALK_SPEED;
+
+    #[test]
+    fn bus_volume_scales_with_master_and_ui_follows_sfx() {
+        let mixer = AudioMixer {
+            master: 0.5,
+            music: 0.4,
+            sfx: 0.8,
+            voice: 1.0,
+            combat_duck: 0.25,
+        };
+
+        assert!((mixer.bus_volume(AudioBus::Music) - 0.2).abs() < 1e-6);
+        assert_eq!(
+            mixer.bus_volume(AudioBus::Ui),
+            mixer.bus_volume(AudioBus::Sfx)
+        );
+        assert!((mixer.mix(AudioBus::Music, 1.0, 0.25) - 0.05).abs() < 1e-6);
+        assert!((mixer.mix(AudioBus::Sfx, 0.5, 0.25) - 0.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn adjusting_faders_clamps_to_unit_range() {
+        let mut mixer = AudioMixer::default();
+        mixer.adjust(None, 5.0);
+        mixer.adjust(Some(AudioBus::Ui), -5.0);
+
+        assert_eq!(mixer.master, 1.0);
+        assert_eq!(mixer.sfx, 0.0);
+        assert_eq!(mixer.bus_volume(AudioBus::Sfx), 0.0);
+    }
+
+    #[test]
+    fn music_ducks_during_combat_and_recovers() {
+        let mut ducking = MusicDucking::default();
+        ducking.trigger_combat();
+        for _ in 0..60 {
+            ducking.tick(1.0 / 60.0, 0.35);
+        }
+        assert!(ducking.in_combat());
+        assert!((ducking.level - 0.35).abs() < 1e-4);
+
+        for _ in 0..(10 * 60) {
+            ducking.tick(1.0 / 60.0, 0.35);
+        }
+        assert!(!ducking.in_combat());
+        assert!((ducking.level - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn mixer_settings_round_trip_and_clamp_faders() {
+        let mut mixer = AudioMixer::default();
+        mixer.adjust(None, -0.3);
+        mixer.adjust(Some(AudioBus::Music), -0.6);
+        assert_eq!(AudioMixer::from_ron(&mixer.to_ron().unwrap()), Ok(mixer));
+
+        let parsed = AudioMixer::from_ron("(sfx: 2.5)").unwrap();
+        assert_eq!(parsed.sfx, 1.0);
+        assert_eq!(parsed.voice, AudioMixer::default().voice);
+
+        let parsed = AudioMixer::from_ron("(master: NaN, music: inf, combat_duck: -inf)").unwrap();
+        assert_eq!(parsed, AudioMixer::default());
+
+        assert!(AudioMixer::from_ron("(voice: loud)").is_err());
+        assert!(AudioMixer::from_ron("(bass: 0.1)").is_err());
+    }
+
+    #[test]
+    fn footsteps_follow_ground_speed_and_landings() {
+        let mut cadence = FootstepCadence::default();
+        let step_secs = STRIDE_LENGTH / WALK_SPEED;
+        assert!(!cadence.advance(WALK_SPEED, true, step_secs * 0.5));
+        assert!(cadence.advance(WALK_SPEED, true, step_secs * 0.6));
+
+        assert!(!cadence.advance(0.0, true, 10.0));
+        assert!(!cadence.advance(WALK_SPEED, false, 10.0));
+        assert!(cadence.advance(0.0, true, 0.01));
+    }
+}
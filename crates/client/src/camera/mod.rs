@@ -1,3 +1,6 @@
+pub mod spectator;
+pub mod third_person;
+
 use bevy::input::common_conditions::input_toggle_active;
 use bevy::prelude::{
     Add, App, Camera, Camera2d, Camera3d, ClearColorConfig, Commands, Component, Entity,
@@ -16,6 +19,11 @@ use shared::inputs::input::PLAYER_CAPSULE_HEIGHT;
 use shared::protocol::PlayerId;
 
 use crate::ClientGameState;
+use crate::camera::spectator::ClientSpectatorPlugin;
+use crate::camera::third_person::{ClientThirdPersonPlugin, SpringArm};
+
+/// Height of the first-person camera above the player's origin.
+pub const FIRST_PERSON_CAMERA_HEIGHT: f32 = PLAYER_CAPSULE_HEIGHT + 0.6;
 
 #[derive(Component, Default)]
 pub struct PlayerCamera;
@@ -35,6 +43,7 @@ impl Plugin for ClientCameraPlugin {
         // so tests exercise the same gameplay wiring as runtime.
         app.add_systems(OnExit(ClientGameState::Playing), despawn_player_cameras);
         app.add_observer(spawn_camera_when_local_player_id_added);
+        app.add_plugins((ClientSpectatorPlugin, ClientThirdPersonPlugin));
 
         if !is_headless {
             app.insert_resource(EguiGlobalSettings {
@@ -70,7 +79,11 @@ fn despawn_player_cameras(mut commands: Commands, camera_query: Query<Entity, Wi
     }
 }
 
-fn spawn_local_player_camera(commands: &mut Commands, player_entity: Entity, local_player_id: u64) {
+pub(crate) fn spawn_local_player_camera(
+    commands: &mut Commands,
+    player_entity: Entity,
+    local_player_id: u64,
+) {
     let camera_entity = commands
         .spawn((
             PlayerCamera,
@@ -81,7 +94,8 @@ fn spawn_local_player_camera(commands: &mut Commands, player_entity: Entity, loc
                 ..default()
             },
             Camera3d::default(),
-            Transform::from_xyz(0.0, PLAYER_CAPSULE_HEIGHT + 0.6, 0.0),
+            Transform::from_xyz(0.0, FIRST_PERSON_CAMERA_HEIGHT, 0.0),
+            SpringArm::default(),
             Name::new(format!("Client_{}_Camera", local_player_id)),
         ))
         .id();
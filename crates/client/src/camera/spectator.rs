@@ -0,0 +1,326 @@
+use avian3d::prelude::{Position, Rotation};
+use bevy::input::mouse::MouseMotion;
+use bevy::math::EulerRot;
+use bevy::prelude::{
+    App, ButtonInput, Camera, Camera3d, Commands, CommandsStatesExt, Component, Entity,
+    IntoScheduleConfigs, KeyCode, Local, MessageReader, MouseButton, Name, OnEnter, OnExit, Plugin,
+    Quat, Query, Res, ResMut, Resource, State, SystemCondition, Time, Transform, Update, Vec3,
+    With, default, in_state,
+};
+use lightyear::prelude::{Client, Connected, MessageSender};
+use shared::components::health::Health;
+use shared::game_math::yaw_of;
+use shared::protocol::{LobbyControlChannel, PlayerId, SpectateRequest};
+
+use crate::{ClientGameState, Headless, LocalPlayerId};
+
+/// How long a client may stay in `Playing` without a local player before it starts
+/// spectating. Covers the gap between a death and the server respawning the player.
+const SPECTATE_GRACE_SECS: f32 = 2.0;
+const FOLLOW_DISTANCE: f32 = 4.0;
+const FOLLOW_HEIGHT: f32 = 2.5;
+
+#[derive(Component)]
+pub struct SpectatorCamera {
+    movement_speed: f32,
+    fast_multiplier: f32,
+    look_sensitivity: f32,
+}
+
+impl Default for SpectatorCamera {
+    fn default() -> Self {
+        Self {
+            movement_speed: 16.0,
+            fast_multiplier: 3.0,
+            look_sensitivity: 0.0018,
+        }
+    }
+}
+
+/// Player followed by the spectator camera (`PlayerId` bits), or `None` for free-fly.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq)]
+pub struct SpectatorTarget(pub Option<u64>);
+
+/// Set while the local player asked to spectate, so the client does not switch back to
+/// `Playing` before the server removed its player entity.
+#[derive(Resource, Debug, Default)]
+pub struct SpectatorRequested(pub bool);
+
+pub struct ClientSpectatorPlugin;
+
+impl Plugin for ClientSpectatorPlugin {
+    fn build(&self, app: &mut App) {
+        fn is_not_headless(headless: Option<Res<Headless>>) -> bool {
+            !headless.map(|h| h.0).unwrap_or(false)
+        }
+
+        app.init_resource::<SpectatorTarget>();
+        app.init_resource::<SpectatorRequested>();
+        app.add_systems(OnEnter(ClientGameState::Spectating), spawn_spectator_camera);
+        app.add_systems(
+            OnExit(ClientGameState::Spectating),
+            despawn_spectator_camera,
+        );
+        app.add_systems(
+            Update,
+            enter_spectating_without_local_player.run_if(in_state(ClientGameState::Playing)),
+        );
+        app.add_systems(
+            Update,
+            return_to_playing_when_local_player_spawns
+                .run_if(in_state(ClientGameState::Spectating)),
+        );
+        app.add_systems(
+            Update,
+            toggle_spectating_hotkey
+                .run_if(
+                    in_state(ClientGameState::Playing).or(in_state(ClientGameState::Spectating)),
+                )
+                .run_if(is_not_headless),
+        );
+        app.add_systems(
+            Update,
+            (cycle_spectator_target, update_spectator_camera)
+                .chain()
+                .run_if(in_state(ClientGameState::Spectating))
+                .run_if(is_not_headless),
+        );
+    }
+}
+
+/// Next player to follow when stepping `step` positions from `current` through the sorted
+/// list of live players. Wraps around; starts from the first (or last) player when nothing
+/// is followed yet.
+pub fn cycle_follow_target(current: Option<u64>, players: &[u64], step: isize) -> Option<u64> {
+    if players.is_empty() {
+        return None;
+    }
+
+    let count = players.len() as isize;
+    let index = match current.and_then(|id| players.iter().position(|&player| player == id)) {
+        Some(index) => index as isize + step,
+        None if step >= 0 => 0,
+        None => count - 1,
+    };
+
+    Some(players[index.rem_euclid(count) as usize])
+}
+
+fn has_local_player(players: &Query<&PlayerId>, local_player_id: &LocalPlayerId) -> bool {
+    players
+        .iter()
+        .any(|player_id| player_id.0.to_bits() == local_player_id.0)
+}
+
+fn enter_spectating_without_local_player(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut missing_for: Local<f32>,
+    players: Query<&PlayerId>,
+    local_player_id: Res<LocalPlayerId>,
+) {
+    if has_local_player(&players, &local_player_id) {
+        *missing_for = 0.0;
+        return;
+    }
+
+    *missing_for += time.delta_secs();
+    if *missing_for >= SPECTATE_GRACE_SECS {
+        *missing_for = 0.0;
+        bevy::log::info!("👁️ No local player in the match, switching to spectator mode");
+        commands.set_state(ClientGameState::Spectating);
+    }
+}
+
+fn return_to_playing_when_local_player_spawns(
+    mut commands: Commands,
+    requested: Res<SpectatorRequested>,
+    players: Query<&PlayerId>,
+    local_player_id: Res<LocalPlayerId>,
+) {
+    if !requested.0 && has_local_player(&players, &local_player_id) {
+        bevy::log::info!("🎮 Local player spawned, leaving spectator mode");
+        commands.set_state(ClientGameState::Playing);
+    }
+}
+
+/// The server only lets players start spectating once their player is dead, so the hotkey
+/// does nothing while it is alive.
+fn toggle_spectating_hotkey(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    state: Res<State<ClientGameState>>,
+    mut requested: ResMut<SpectatorRequested>,
+    players: Query<(&PlayerId, &Health)>,
+    local_player_id: Res<LocalPlayerId>,
+    mut sender_q: Query<&mut MessageSender<SpectateRequest>, (With<Client>, With<Connected>)>,
+) {
+    if !keys.just_pressed(KeyCode::F4) {
+        return;
+    }
+
+    let spectate = state.get() == &ClientGameState::Playing;
+    let alive = players
+        .iter()
+        .any(|(player_id, health)| player_id.0.to_bits() == local_player_id.0 && !health.is_dead);
+    if spectate && alive {
+        return;
+    }
+
+    requested.0 = spectate;
+    if let Some(mut sender) = sender_q.iter_mut().next() {
+        sender.send::<LobbyControlChannel>(SpectateRequest {
+            spectate: requested.0,
+        });
+    }
+
+    if requested.0 {
+        commands.set_state(ClientGameState::Spectating);
+    }
+}
+
+fn spawn_spectator_camera(mut commands: Commands, mut target: ResMut<SpectatorTarget>) {
+    target.0 = None;
+    commands.spawn((
+        SpectatorCamera::default(),
+        Camera {
+            // Same order as the player camera, which never coexists with this one.
+            order: 10,
+            ..default()
+        },
+        Camera3d::default(),
+        Transform::from_xyz(-20.0, 20.0, 20.0).looking_at(Vec3::ZERO, Vec3::Y),
+        Name::new("SpectatorCamera"),
+    ));
+}
+
+fn despawn_spectator_camera(
+    mut commands: Commands,
+    camera_query: Query<Entity, With<SpectatorCamera>>,
+) {
+    for camera in &camera_query {
+        commands.entity(camera).despawn();
+    }
+}
+
+/// `E`/`Q` follow the next/previous player, `F` goes back to free-fly.
+fn cycle_spectator_target(
+    keys: Res<ButtonInput<KeyCode>>,
+    players: Query<&PlayerId>,
+    mut target: ResMut<SpectatorTarget>,
+) {
+    let step = if keys.just_pressed(KeyCode::KeyE) {
+        1
+    } else if keys.just_pressed(KeyCode::KeyQ) {
+        -1
+    } else {
+        if keys.just_pressed(KeyCode::KeyF) {
+            target.0 = None;
+        }
+        return;
+    };
+
+    let mut player_ids: Vec<u64> = players
+        .iter()
+        .map(|player_id| player_id.0.to_bits())
+        .collect();
+    player_ids.sort_unstable();
+    player_ids.dedup();
+    target.0 = cycle_follow_target(target.0, &player_ids, step);
+}
+
+fn update_spectator_camera(
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    mut mouse_motion: MessageReader<MouseMotion>,
+    mut target: ResMut<SpectatorTarget>,
+    players: Query<(&PlayerId, &Position, &Rotation)>,
+    mut camera_query: Query<(&mut Transform, &SpectatorCamera)>,
+) {
+    let Ok((mut transform, spectator_camera)) = camera_query.single_mut() else {
+        return;
+    };
+
+    if let Some(target_id) = target.0 {
+        let Some((_, position, rotation)) = players
+            .iter()
+            .find(|(player_id, ..)| player_id.0.to_bits() == target_id)
+        else {
+            // The followed player left or died; fall back to free-fly from here.
+            target.0 = None;
+            return;
+        };
+
+        let behind = Quat::from_rotation_y(yaw_of(rotation.0)) * Vec3::Z * FOLLOW_DISTANCE;
+        let focus = position.0 + Vec3::Y * (FOLLOW_HEIGHT * 0.5);
+        transform.translation = position.0 + behind + Vec3::Y * FOLLOW_HEIGHT;
+        transform.look_at(focus, Vec3::Y);
+        return;
+    }
+
+    let mut speed = spectator_camera.movement_speed;
+    if keys.pressed(KeyCode::ShiftLeft) || keys.pressed(KeyCode::ShiftRight) {
+        speed *= spectator_camera.fast_multiplier;
+    }
+
+    if mouse_buttons.pressed(MouseButton::Right) {
+        let (mut yaw, mut pitch, _) = transform.rotation.to_euler(EulerRot::YXZ);
+        for event in mouse_motion.read() {
+            yaw -= event.delta.x * spectator_camera.look_sensitivity;
+            pitch -= event.delta.y * spectator_camera.look_sensitivity;
+        }
+        pitch = pitch.clamp(-1.54, 1.54);
+        transform.rotation = Quat::from_euler(EulerRot::YXZ, yaw, pitch, 0.0);
+    } else {
+        mouse_motion.clear();
+    }
+
+    let forward = transform.forward();
+    let right = transform.right();
+    let mut direction = Vec3::ZERO;
+    if keys.pressed(KeyCode::KeyW) {
+        direction += *forward;
+    }
+    if keys.pressed(KeyCode::KeyS) {
+        direction -= *forward;
+    }
+    if keys.pressed(KeyCode::KeyD) {
+        direction += *right;
+    }
+    if keys.pressed(KeyCode::KeyA) {
+        direction -= *right;
+    }
+    if keys.pressed(KeyCode::Space) {
+        direction += Vec3::Y;
+    }
+    if keys.pressed(KeyCode::ControlLeft) || keys.pressed(KeyCode::ControlRight) {
+        direction -= Vec3::Y;
+    }
+
+    if direction.length_squared() > 0.0 {
+        transform.translation += direction.normalize() * speed * time.delta_secs();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::cycle_follow_target;
+
+    #[test]
+    fn cycling_wraps_around_players() {
+        let players = [1, 4, 7];
+
+        assert_eq!(cycle_follow_target(None, &players, 1), Some(1));
+        assert_eq!(cycle_follow_target(None, &players, -1), Some(7));
+        assert_eq!(cycle_follow_target(Some(4), &players, 1), Some(7));
+        assert_eq!(cycle_follow_target(Some(7), &players, 1), Some(1));
+        assert_eq!(cycle_follow_target(Some(1), &players, -1), Some(7));
+    }
+
+    #[test]
+    fn cycling_restarts_when_target_left_and_handles_empty_match() {
+        assert_eq!(cycle_follow_target(Some(9), &[2, 3], 1), Some(2));
+        assert_eq!(cycle_follow_target(Some(2), &[], 1), None);
+    }
+}
@@ -0,0 +1,199 @@
+use avian3d::prelude::{
+    Collider, Position, Rotation, ShapeCastConfig, SpatialQuery, SpatialQueryFilter,
+};
+use bevy::prelude::{
+    App, ButtonInput, ChildOf, Component, Dir3, IntoScheduleConfigs, KeyCode, Plugin, Quat, Query,
+    Res, ResMut, Resource, Time, Transform, Update, Vec3, With, in_state,
+};
+use leafwing_input_manager::prelude::ActionState;
+use shared::inputs::input::PlayerAction;
+
+use crate::camera::{FIRST_PERSON_CAMERA_HEIGHT, PlayerCamera};
+use crate::{ClientGameState, Headless};
+
+pub const CAMERA_VIEW_KEY: KeyCode = KeyCode::F9;
+pub const SHOULDER_SWAP_KEY: KeyCode = KeyCode::KeyX;
+
+/// Height the server shoots from; the third-person camera aims where this eye looks.
+const EYE_HEIGHT: f32 = 1.5;
+const ARM_LENGTH: f32 = 3.0;
+const AIM_ARM_LENGTH: f32 = 1.4;
+const SHOULDER_OFFSET: f32 = 0.6;
+const AIM_SHOULDER_OFFSET: f32 = 0.45;
+const ARM_RAISE: f32 = 0.25;
+/// Radius of the camera in the collision cast, so the near plane stays out of walls.
+const CAMERA_RADIUS: f32 = 0.2;
+/// How fast the arm eases toward its length, per second. Walls pull it in at once.
+const ARM_SPEED: f32 = 8.0;
+/// Aim point distance when the eye looks at nothing closer.
+const AIM_DISTANCE: f32 = 60.0;
+/// The camera turns toward the aim point; closer than this it would swing too much.
+const MIN_AIM_DISTANCE: f32 = 3.0;
+
+/// Optional over-the-shoulder camera, toggled with F9, shoulder swapped with X. It is only
+/// a view: shots still leave from the character's eye along its look direction, as the
+/// server computes them, and the camera turns so the crosshair sits on that line.
+pub struct ClientThirdPersonPlugin;
+
+impl Plugin for ClientThirdPersonPlugin {
+    fn build(&self, app: &mut App) {
+        fn is_not_headless(headless: Option<Res<Headless>>) -> bool {
+            !headless.map(|h| h.0).unwrap_or(false)
+        }
+
+        app.init_resource::<CameraView>();
+        app.add_systems(
+            Update,
+            (toggle_camera_view, update_camera_rig)
+                .chain()
+                .run_if(in_state(ClientGameState::Playing))
+                .run_if(is_not_headless),
+        );
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Shoulder {
+    #[default]
+    Right,
+    Left,
+}
+
+impl Shoulder {
+    fn side(self) -> f32 {
+        match self {
+            Shoulder::Right => 1.0,
+            Shoulder::Left => -1.0,
+        }
+    }
+
+    fn swapped(self) -> Self {
+        match self {
+            Shoulder::Right => Shoulder::Left,
+            Shoulder::Left => Shoulder::Right,
+        }
+    }
+}
+
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CameraView {
+    pub third_person: bool,
+    pub shoulder: Shoulder,
+}
+
+/// Current length of the third-person camera arm, eased between frames.
+#[derive(Component, Debug, Default)]
+pub struct SpringArm {
+    length: f32,
+}
+
+fn toggle_camera_view(keys: Res<ButtonInput<KeyCode>>, mut view: ResMut<CameraView>) {
+    if keys.just_pressed(CAMERA_VIEW_KEY) {
+        view.third_person = !view.third_person;
+    }
+    if keys.just_pressed(SHOULDER_SWAP_KEY) && view.third_person {
+        view.shoulder = view.shoulder.swapped();
+    }
+}
+
+/// Camera position relative to the eye, in the character's frame (looking down -Z),
+/// before collision.
+pub fn arm_offset(shoulder: Shoulder, aiming: bool) -> Vec3 {
+    let (length, side) = if aiming {
+        (AIM_ARM_LENGTH, AIM_SHOULDER_OFFSET)
+    } else {
+        (ARM_LENGTH, SHOULDER_OFFSET)
+    };
+    Vec3::new(side * shoulder.side(), ARM_RAISE, length)
+}
+
+/// Eases the arm from `current` toward `desired`, but never past `blocked`, the distance
+/// at which the camera would touch a wall.
+pub fn spring_arm_length(current: f32, desired: f32, blocked: Option<f32>, delta_secs: f32) -> f32 {
+    let eased = current + (desired - current) * (ARM_SPEED * delta_secs).min(1.0);
+    blocked.map_or(eased, |blocked| eased.min(blocked)).max(0.0)
+}
+
+fn update_camera_rig(
+    time: Res<Time>,
+    view: Res<CameraView>,
+    spatial_query: SpatialQuery,
+    mut cameras: Query<(&mut Transform, &mut SpringArm, &ChildOf), With<PlayerCamera>>,
+    players: Query<(&Position, &Rotation, Option<&ActionState<PlayerAction>>)>,
+) {
+    for (mut transform, mut arm, child_of) in cameras.iter_mut() {
+        if !view.third_person {
+            arm.length = 0.0;
+            let first_person = Transform::from_xyz(0.0, FIRST_PERSON_CAMERA_HEIGHT, 0.0);
+            if *transform != first_person {
+                *transform = first_person;
+            }
+            continue;
+        }
+        let player = child_of.parent();
+        let Ok((position, rotation, action_state)) = players.get(player) else {
+            continue;
+        };
+        let aiming = action_state
+            .is_some_and(|actions| !actions.disabled() && actions.pressed(&PlayerAction::Aim));
+
+        // Work in the character's frame, where the camera lives as a child, and go to
+        // the world only for the casts.
+        let eye = Vec3::Y * EYE_HEIGHT;
+        let eye_world = position.0 + rotation.0 * eye;
+        let filter = SpatialQueryFilter::default().with_excluded_entities([player]);
+
+        let offset = arm_offset(view.shoulder, aiming);
+        let (direction, desired) = match Dir3::new(offset) {
+            Ok(direction) => (direction, offset.length()),
+            Err(_) => continue,
+        };
+        let blocked = spatial_query
+            .cast_shape(
+                &Collider::sphere(CAMERA_RADIUS),
+                eye_world,
+                Quat::IDENTITY,
+                Dir3::new(rotation.0 * *direction).unwrap_or(Dir3::Z),
+                &ShapeCastConfig::from_max_distance(desired),
+                &filter,
+            )
+            .map(|hit| hit.distance);
+        arm.length = spring_arm_length(arm.length, desired, blocked, time.delta_secs());
+        let camera = eye + *direction * arm.length;
+
+        let aim_distance = Dir3::new(rotation.0 * Vec3::NEG_Z)
+            .ok()
+            .and_then(|forward| {
+                spatial_query.cast_ray(eye_world, forward, AIM_DISTANCE, true, &filter)
+            })
+            .map_or(AIM_DISTANCE, |hit| hit.distance)
+            .max(MIN_AIM_DISTANCE);
+        let aim_point = eye + Vec3::NEG_Z * aim_distance;
+
+        *transform = Transform::from_translation(camera).looking_at(aim_point, Vec3::Y);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ARM_LENGTH, Shoulder, arm_offset, spring_arm_length};
+
+    #[test]
+    fn arm_sits_behind_the_chosen_shoulder_and_tightens_while_aiming() {
+        let right = arm_offset(Shoulder::Right, false);
+        let left = arm_offset(Shoulder::Left, false);
+        assert!(right.x > 0.0 && left.x < 0.0);
+        assert_eq!(right.z, ARM_LENGTH);
+        assert!(arm_offset(Shoulder::Right, true).length() < right.length());
+    }
+
+    #[test]
+    fn walls_pull_the_arm_in_at_once_and_it_eases_back_out() {
+        let pulled = spring_arm_length(3.0, 3.0, Some(0.8), 0.016);
+        assert_eq!(pulled, 0.8);
+
+        let easing = spring_arm_length(pulled, 3.0, None, 0.016);
+        assert!(easing > pulled && easing < 3.0);
+        assert_eq!(spring_arm_length(easing, 3.0, None, 1.0), 3.0);
+    }
+}
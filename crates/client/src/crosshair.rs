@@ -0,0 +1,384 @@
+use avian3d::prelude::{
+    LinearVelocity, Position, Rotation, SpatialQueryFilter, SpatialQueryPipeline,
+};
+use bevy::prelude::{
+    App, BackgroundColor, ChildSpawnerCommands, Color, Component, Dir3, Entity,
+    IntoScheduleConfigs, Message, MessageWriter, Name, Node, Plugin, PositionType, Query, Res,
+    ResMut, Resource, Text, TextColor, TextFont, Time, Update, Val, Vec3, Visibility, With,
+    Without,
+};
+use leafwing_input_manager::prelude::ActionState;
+use lightyear::prelude::{Client, MessageReceiver};
+use shared::components::health::Health;
+use shared::components::pickup::Pickup;
+use shared::components::team::{Team, is_friendly_fire};
+use shared::components::weapons::{Gun, MAX_RECOIL};
+use shared::inputs::input::PlayerAction;
+use shared::protocol::{CharacterMarker, HitConfirmation, PlayerId};
+
+use crate::LocalPlayerId;
+use crate::hud::{HudAnchor, HudAppExt, HudSystems};
+
+/// Same eye height the gun raycast shoots from.
+const EYE_HEIGHT: f32 = 1.5;
+/// Range used to look for enemies when the local player has no gun.
+const DEFAULT_TARGET_RANGE: f32 = 100.0;
+/// Pickups closer than this and near the crosshair show the interaction dot.
+pub const USE_RANGE: f32 = 3.0;
+const USE_MAX_ANGLE_RADIANS: f32 = 0.25;
+
+/// Gap between the crosshair arms at rest, in pixels.
+pub const CROSSHAIR_BASE_GAP: f32 = 6.0;
+pub const CROSSHAIR_MAX_GAP: f32 = 40.0;
+/// Extra gap per m/s of horizontal speed.
+const MOVEMENT_SPREAD_PER_SPEED: f32 = 2.0;
+/// Extra gap right after a shot, shrinking as the gun cools down.
+const RECOIL_SPREAD: f32 = 10.0;
+/// Aiming tightens the crosshair.
+const AIM_SPREAD_SCALE: f32 = 0.5;
+
+const ARM_LENGTH: f32 = 10.0;
+const ARM_THICKNESS: f32 = 2.0;
+const DOT_SIZE: f32 = 4.0;
+const HIT_FLASH_SECS: f32 = 0.15;
+const KILL_FLASH_SECS: f32 = 0.4;
+
+const NEUTRAL_COLOR: Color = Color::WHITE;
+const ENEMY_COLOR: Color = Color::srgb(0.95, 0.2, 0.2);
+const TEAMMATE_COLOR: Color = Color::srgb(0.3, 0.6, 1.0);
+const USABLE_COLOR: Color = Color::srgb(0.3, 0.9, 0.4);
+const HIT_COLOR: Color = Color::WHITE;
+const KILL_COLOR: Color = Color::srgb(1.0, 0.3, 0.1);
+
+pub struct ClientCrosshairPlugin;
+
+impl Plugin for ClientCrosshairPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CrosshairState>();
+        app.add_message::<HitFeedback>();
+        app.add_hud_widget("Crosshair", HudAnchor::Center, 0, spawn_crosshair);
+        app.add_systems(
+            Update,
+            (
+                receive_hit_confirmations,
+                update_crosshair_state,
+                update_crosshair,
+            )
+                .chain()
+                .in_set(HudSystems),
+        );
+    }
+}
+
+/// What the crosshair is resting on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CrosshairTarget {
+    #[default]
+    Nothing,
+    Enemy,
+    Teammate,
+    Usable,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HitFlash {
+    Hit,
+    Kill,
+}
+
+/// Every confirmed hit, for feedback outside the crosshair (hit sounds).
+#[derive(Message, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HitFeedback(pub HitFlash);
+
+impl HitFlash {
+    fn duration(&self) -> f32 {
+        match self {
+            HitFlash::Hit => HIT_FLASH_SECS,
+            HitFlash::Kill => KILL_FLASH_SECS,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct CrosshairState {
+    target: CrosshairTarget,
+    gap: f32,
+    /// Latest confirmation and when it arrived, in elapsed seconds.
+    flash: Option<(HitFlash, f32)>,
+}
+
+/// One of the four arms, pointing away from the center along `direction`.
+#[derive(Component)]
+struct CrosshairArm {
+    direction: (f32, f32),
+}
+
+#[derive(Component)]
+struct CrosshairDot;
+
+#[derive(Component)]
+struct HitMarker;
+
+/// Gap between the crosshair arms: wider when moving or with the gun's recoil (0.0 to 1.0
+/// of `MAX_RECOIL`), tighter when aiming.
+pub fn crosshair_gap(horizontal_speed: f32, recoil: f32, aiming: bool) -> f32 {
+    let gap = CROSSHAIR_BASE_GAP
+        + horizontal_speed.max(0.0) * MOVEMENT_SPREAD_PER_SPEED
+        + recoil.clamp(0.0, 1.0) * RECOIL_SPREAD;
+    let gap = if aiming { gap * AIM_SPREAD_SCALE } else { gap };
+    gap.clamp(CROSSHAIR_BASE_GAP * AIM_SPREAD_SCALE, CROSSHAIR_MAX_GAP)
+}
+
+/// Classify the character under the crosshair. Characters without a team, like bots,
+/// always read as enemies.
+pub fn character_target(local_team: Option<Team>, target_team: Option<Team>) -> CrosshairTarget {
+    if is_friendly_fire(local_team, target_team) {
+        CrosshairTarget::Teammate
+    } else {
+        CrosshairTarget::Enemy
+    }
+}
+
+/// Flash still showing at `now`, if any.
+pub fn active_flash(flash: Option<(HitFlash, f32)>, now: f32) -> Option<HitFlash> {
+    flash
+        .filter(|(kind, started)| now - started <= kind.duration())
+        .map(|(kind, _)| kind)
+}
+
+fn crosshair_color(target: CrosshairTarget) -> Color {
+    match target {
+        CrosshairTarget::Enemy => ENEMY_COLOR,
+        CrosshairTarget::Teammate => TEAMMATE_COLOR,
+        CrosshairTarget::Usable => USABLE_COLOR,
+        CrosshairTarget::Nothing => NEUTRAL_COLOR,
+    }
+}
+
+fn spawn_crosshair(parent: &mut ChildSpawnerCommands) {
+    parent
+        .spawn((
+            Name::new("Crosshair"),
+            Node {
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            },
+        ))
+        .with_children(|parent| {
+            for direction in [(0.0, -1.0), (0.0, 1.0), (-1.0, 0.0), (1.0, 0.0)] {
+                parent.spawn((
+                    CrosshairArm { direction },
+                    Node {
+                        position_type: PositionType::Absolute,
+                        ..Default::default()
+                    },
+                    BackgroundColor(NEUTRAL_COLOR),
+                ));
+            }
+
+            parent.spawn((
+                CrosshairDot,
+                Node {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(-DOT_SIZE * 0.5),
+                    top: Val::Px(-DOT_SIZE * 0.5),
+                    width: Val::Px(DOT_SIZE),
+                    height: Val::Px(DOT_SIZE),
+                    ..Default::default()
+                },
+                BackgroundColor(USABLE_COLOR),
+                Visibility::Hidden,
+            ));
+
+            parent.spawn((
+                HitMarker,
+                Text::new("X"),
+                TextFont {
+                    font_size: 28.0,
+                    ..Default::default()
+                },
+                TextColor(HIT_COLOR),
+                Node {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(-9.0),
+                    top: Val::Px(-17.0),
+                    ..Default::default()
+                },
+                Visibility::Hidden,
+            ));
+        });
+}
+
+fn receive_hit_confirmations(
+    time: Res<Time>,
+    mut receiver_q: Query<&mut MessageReceiver<HitConfirmation>, With<Client>>,
+    mut state: ResMut<CrosshairState>,
+    mut feedback: MessageWriter<HitFeedback>,
+) {
+    let now = time.elapsed_secs();
+    for mut receiver in receiver_q.iter_mut() {
+        for confirmation in receiver.receive() {
+            let flash = if confirmation.kill {
+                HitFlash::Kill
+            } else {
+                HitFlash::Hit
+            };
+            feedback.write(HitFeedback(flash));
+            // Keep showing a kill over the hit confirmations that arrive with it.
+            if flash == HitFlash::Hit && active_flash(state.flash, now) == Some(HitFlash::Kill) {
+                continue;
+            }
+            state.flash = Some((flash, now));
+        }
+    }
+}
+
+/// Raycast along the local player's aim against the character hitboxes (interpolated
+/// colliders for remote players) and look for pickups in reach.
+fn update_crosshair_state(
+    local_player_id: Res<LocalPlayerId>,
+    local_query: Query<(
+        Entity,
+        &PlayerId,
+        &Position,
+        &Rotation,
+        Option<&LinearVelocity>,
+        Option<&Gun>,
+        Option<&ActionState<PlayerAction>>,
+        Option<&Team>,
+    )>,
+    spatial_query: Res<SpatialQueryPipeline>,
+    characters: Query<(&Health, Option<&Team>), With<CharacterMarker>>,
+    pickups: Query<(&Pickup, &Position)>,
+    mut state: ResMut<CrosshairState>,
+) {
+    let Some((entity, _, position, rotation, velocity, gun, action_state, team)) = local_query
+        .iter()
+        .find(|(_, player_id, ..)| player_id.0.to_bits() == local_player_id.0)
+    else {
+        state.target = CrosshairTarget::Nothing;
+        state.gap = CROSSHAIR_BASE_GAP;
+        return;
+    };
+
+    let origin = position.0 + Vec3::Y * EYE_HEIGHT;
+    let direction = Dir3::new(rotation.0 * Vec3::NEG_Z).unwrap_or(Dir3::NEG_Z);
+    let range = gun.map_or(DEFAULT_TARGET_RANGE, |gun| gun.range);
+    let filter = SpatialQueryFilter::default().with_excluded_entities([entity]);
+
+    let hovered = spatial_query
+        .cast_ray(origin, direction, range, false, &filter)
+        .and_then(|hit| characters.get(hit.entity).ok())
+        .filter(|(health, _)| !health.is_dead)
+        .map(|(_, target_team)| character_target(team.copied(), target_team.copied()));
+
+    let usable = pickups.iter().any(|(pickup, pickup_position)| {
+        let to_pickup = pickup_position.0 - origin;
+        pickup.available
+            && to_pickup.length() <= USE_RANGE
+            && direction.angle_between(to_pickup) <= USE_MAX_ANGLE_RADIANS
+    });
+
+    state.target = match hovered {
+        Some(target) => target,
+        None if usable => CrosshairTarget::Usable,
+        None => CrosshairTarget::Nothing,
+    };
+
+    let horizontal_speed = velocity.map_or(0.0, |velocity| velocity.0.with_y(0.0).length());
+    let recoil = gun.map_or(0.0, |gun| gun.recoil / MAX_RECOIL);
+    let aiming = action_state.is_some_and(|actions| actions.pressed(&PlayerAction::Aim));
+    state.gap = crosshair_gap(horizontal_speed, recoil, aiming);
+}
+
+fn update_crosshair(
+    time: Res<Time>,
+    state: Res<CrosshairState>,
+    mut arms: Query<(&CrosshairArm, &mut Node, &mut BackgroundColor)>,
+    mut dots: Query<&mut Visibility, (With<CrosshairDot>, Without<HitMarker>)>,
+    mut markers: Query<(&mut Visibility, &mut TextColor), With<HitMarker>>,
+) {
+    let color = crosshair_color(state.target);
+    for (arm, mut node, mut background) in arms.iter_mut() {
+        let (x, y) = arm.direction;
+        let (width, height) = if x == 0.0 {
+            (ARM_THICKNESS, ARM_LENGTH)
+        } else {
+            (ARM_LENGTH, ARM_THICKNESS)
+        };
+        let offset = state.gap + ARM_LENGTH * 0.5;
+        node.width = Val::Px(width);
+        node.height = Val::Px(height);
+        node.left = Val::Px(x * offset - width * 0.5);
+        node.top = Val::Px(y * offset - height * 0.5);
+        background.0 = color;
+    }
+
+    let dot_visibility = if state.target == CrosshairTarget::Usable {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    for mut visibility in dots.iter_mut() {
+        *visibility = dot_visibility;
+    }
+
+    let flash = active_flash(state.flash, time.elapsed_secs());
+    for (mut visibility, mut text_color) in markers.iter_mut() {
+        *visibility = if flash.is_some() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        text_color.0 = match flash {
+            Some(HitFlash::Kill) => KILL_COLOR,
+            _ => HIT_COLOR,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CROSSHAIR_BASE_GAP, CROSSHAIR_MAX_GAP, CrosshairTarget, HitFlash, active_flash,
+        character_target, crosshair_gap,
+    };
+    use shared::components::team::Team;
+
+    #[test]
+    fn gap_widens_when_moving_or_firing_and_tightens_when_aiming() {
+        let resting = crosshair_gap(0.0, 0.0, false);
+        assert_eq!(resting, CROSSHAIR_BASE_GAP);
+        assert!(crosshair_gap(5.0, 0.0, false) > resting);
+        assert!(crosshair_gap(0.0, 1.0, false) > resting);
+        assert!(crosshair_gap(5.0, 1.0, true) < crosshair_gap(5.0, 1.0, false));
+        assert_eq!(crosshair_gap(100.0, 1.0, false), CROSSHAIR_MAX_GAP);
+    }
+
+    #[test]
+    fn teammates_and_enemies_are_told_apart() {
+        assert_eq!(
+            character_target(Some(Team::Red), Some(Team::Red)),
+            CrosshairTarget::Teammate
+        );
+        assert_eq!(
+            character_target(Some(Team::Red), Some(Team::Blue)),
+            CrosshairTarget::Enemy
+        );
+        assert_eq!(character_target(None, None), CrosshairTarget::Enemy);
+    }
+
+    #[test]
+    fn flashes_expire_and_kills_last_longer() {
+        assert_eq!(
+            active_flash(Some((HitFlash::Hit, 1.0)), 1.1),
+            Some(HitFlash::Hit)
+        );
+        assert_eq!(active_flash(Some((HitFlash::Hit, 1.0)), 1.3), None);
+        assert_eq!(
+            active_flash(Some((HitFlash::Kill, 1.0)), 1.3),
+            Some(HitFlash::Kill)
+        );
+        assert_eq!(active_flash(None, 1.0), None);
+    }
+}
@@ -0,0 +1,88 @@
+use bevy::diagnostic::DiagnosticsStore;
+use bevy::prelude::{
+    App, BackgroundColor, ButtonInput, Color, Commands, Component, IntoScheduleConfigs, KeyCode,
+    Name, Node, Plugin, PositionType, Query, Res, ResMut, Resource, Startup, Text, TextFont, Time,
+    Timer, TimerMode, UiRect, Update, Val, Visibility, With, default,
+};
+use shared::cpu_profile::{cpu_breakdown, format_cpu_breakdown};
+
+const REFRESH_SECS: f32 = 0.25;
+
+/// Per-module CPU breakdown overlay, toggled with F8. In local host mode it includes the
+/// server's modules, which share the app.
+pub struct ClientCpuProfilePlugin;
+
+impl Plugin for ClientCpuProfilePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CpuOverlayState>();
+        app.add_systems(Startup, spawn_cpu_overlay);
+        app.add_systems(Update, (toggle_cpu_overlay, update_cpu_overlay).chain());
+    }
+}
+
+#[derive(Resource, Debug)]
+pub struct CpuOverlayState {
+    pub visible: bool,
+    refresh: Timer,
+}
+
+impl Default for CpuOverlayState {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            refresh: Timer::from_seconds(REFRESH_SECS, TimerMode::Repeating),
+        }
+    }
+}
+
+#[derive(Component)]
+struct CpuOverlayText;
+
+fn spawn_cpu_overlay(mut commands: Commands) {
+    commands.spawn((
+        Name::new("CpuProfileOverlay"),
+        CpuOverlayText,
+        Text::new(""),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        Visibility::Hidden,
+        BackgroundColor(Color::srgba(0.05, 0.05, 0.08, 0.75)),
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(16.0),
+            top: Val::Px(16.0),
+            padding: UiRect::all(Val::Px(8.0)),
+            ..default()
+        },
+    ));
+}
+
+fn toggle_cpu_overlay(keys: Res<ButtonInput<KeyCode>>, mut state: ResMut<CpuOverlayState>) {
+    if keys.just_pressed(KeyCode::F8) {
+        state.visible = !state.visible;
+    }
+}
+
+fn update_cpu_overlay(
+    time: Res<Time>,
+    store: Option<Res<DiagnosticsStore>>,
+    mut state: ResMut<CpuOverlayState>,
+    mut overlay: Query<(&mut Text, &mut Visibility), With<CpuOverlayText>>,
+) {
+    let Ok((mut text, mut visibility)) = overlay.single_mut() else {
+        return;
+    };
+    if !state.visible {
+        *visibility = Visibility::Hidden;
+        return;
+    }
+    *visibility = Visibility::Visible;
+
+    if !state.refresh.tick(time.delta()).just_finished() && !text.0.is_empty() {
+        return;
+    }
+    let stats = store.as_deref().map(cpu_breakdown).unwrap_or_default();
+    text.0 = format_cpu_breakdown(&stats);
+}
@@ -0,0 +1,192 @@
+use avian3d::prelude::{Position, SpatialQuery, SpatialQueryFilter};
+use bevy::prelude::{
+    App, ButtonInput, Color, Dir3, Entity, Gizmos, IntoScheduleConfigs, KeyCode, Plugin, Query,
+    Res, ResMut, Resource, Result, Update, Vec3, With, Without, in_state,
+};
+use bevy_inspector_egui::bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use lightyear::prelude::{Controlled, Predicted};
+use shared::balance::{BalanceConfig, BalanceConfigPath};
+use shared::hearing::SoundKind;
+use shared::inputs::input::PLAYER_CAPSULE_HEIGHT;
+use shared::protocol::{CharacterMarker, PlayerId};
+
+use crate::ClientGameState;
+
+/// Obstacles counted on a path before it reads as fully blocked.
+const MAX_DRAWN_OCCLUDERS: u32 = 4;
+/// Audible radii drawn around the player: through no wall, one wall, two walls.
+const DRAWN_OCCLUSION_LEVELS: usize = 3;
+
+const HEARD_COLOR: Color = Color::srgb(0.3, 1.0, 0.4);
+const BLOCKED_COLOR: Color = Color::srgb(1.0, 0.25, 0.2);
+const OUT_OF_RANGE_COLOR: Color = Color::srgba(0.6, 0.6, 0.6, 0.4);
+const OCCLUDER_COLOR: Color = Color::srgb(1.0, 0.8, 0.2);
+
+/// Hearing tuning tool, toggled with F7: how far the local player's sounds carry, which
+/// bots would hear them and which walls are in the way, with sliders over the hearing
+/// balance. In host mode the sliders retune the running server's bots live; "Save" writes
+/// the whole balance config to its file.
+pub struct ClientHearingTunerPlugin;
+
+impl Plugin for ClientHearingTunerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HearingTunerState>();
+        app.init_resource::<BalanceConfigPath>();
+        app.add_systems(Update, toggle_hearing_tuner);
+        app.add_systems(
+            Update,
+            draw_hearing_ranges
+                .run_if(in_state(ClientGameState::Playing))
+                .run_if(hearing_tuner_visible),
+        );
+        app.add_systems(
+            EguiPrimaryContextPass,
+            hearing_tuner_window.run_if(hearing_tuner_visible),
+        );
+    }
+}
+
+#[derive(Resource, Debug)]
+pub struct HearingTunerState {
+    pub visible: bool,
+    /// Sound whose ranges and paths are drawn.
+    pub sound: SoundKind,
+    /// Outcome of the last save, shown under the button.
+    status: Option<String>,
+}
+
+impl Default for HearingTunerState {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            sound: SoundKind::Footstep,
+            status: None,
+        }
+    }
+}
+
+fn hearing_tuner_visible(state: Res<HearingTunerState>) -> bool {
+    state.visible
+}
+
+fn toggle_hearing_tuner(keys: Res<ButtonInput<KeyCode>>, mut state: ResMut<HearingTunerState>) {
+    if keys.just_pressed(KeyCode::F7) {
+        state.visible = !state.visible;
+    }
+}
+
+/// Rings of how far the selected sound carries through 0, 1, 2... walls, and a path to
+/// every bot: green if it hears the sound, red if walls swallow it, grey if out of range.
+/// Walls on a path are marked where the path crosses them.
+fn draw_hearing_ranges(
+    mut gizmos: Gizmos,
+    state: Res<HearingTunerState>,
+    balance: Res<BalanceConfig>,
+    spatial_query: SpatialQuery,
+    local_player: Query<(Entity, &Position), (With<PlayerId>, With<Predicted>, With<Controlled>)>,
+    bots: Query<(Entity, &Position), (With<CharacterMarker>, Without<PlayerId>)>,
+) {
+    let Ok((player, player_position)) = local_player.single() else {
+        return;
+    };
+    let hearing = &balance.hearing;
+    let eye = Vec3::Y * (PLAYER_CAPSULE_HEIGHT * 0.5);
+    let source = player_position.0 + eye;
+
+    for occluders in 0..DRAWN_OCCLUSION_LEVELS {
+        let radius = hearing.audible_radius(state.sound, occluders);
+        if radius > 0.0 {
+            let fade = 1.0 - occluders as f32 / DRAWN_OCCLUSION_LEVELS as f32;
+            gizmos.sphere(source, radius, HEARD_COLOR.with_alpha(0.6 * fade));
+        }
+    }
+
+    for (bot, bot_position) in bots.iter() {
+        let listener = bot_position.0 + eye;
+        let distance = source.distance(listener);
+        if !hearing.is_heard(state.sound, distance, 0) {
+            gizmos.line(source, listener, OUT_OF_RANGE_COLOR);
+            continue;
+        }
+        let Ok(direction) = Dir3::new(listener - source) else {
+            continue;
+        };
+        let filter = SpatialQueryFilter::default().with_excluded_entities([player, bot]);
+        let hits = spatial_query.ray_hits(
+            source,
+            direction,
+            distance,
+            MAX_DRAWN_OCCLUDERS,
+            true,
+            &filter,
+        );
+        for hit in &hits {
+            gizmos.sphere(source + direction * hit.distance, 0.25, OCCLUDER_COLOR);
+        }
+        let color = if hearing.is_heard(state.sound, distance, hits.len()) {
+            HEARD_COLOR
+        } else {
+            BLOCKED_COLOR
+        };
+        gizmos.line(source, listener, color);
+    }
+}
+
+fn hearing_tuner_window(
+    mut contexts: EguiContexts,
+    mut state: ResMut<HearingTunerState>,
+    mut balance: ResMut<BalanceConfig>,
+    path: Res<BalanceConfigPath>,
+) -> Result {
+    let mut hearing = balance.hearing.clone();
+    let mut sound = state.sound;
+    let mut save = false;
+
+    egui::Window::new("Bot hearing").show(contexts.ctx_mut()?, |ui| {
+        ui.horizontal(|ui| {
+            for kind in SoundKind::ALL {
+                ui.radio_value(&mut sound, kind, kind.label());
+            }
+        });
+        ui.separator();
+        ui.add(egui::Slider::new(&mut hearing.footstep_range, 1.0..=60.0).text("Footstep range"));
+        ui.add(egui::Slider::new(&mut hearing.sprint_range, 1.0..=100.0).text("Sprint range"));
+        ui.add(egui::Slider::new(&mut hearing.gunshot_range, 1.0..=200.0).text("Gunshot range"));
+        ui.add(
+            egui::Slider::new(&mut hearing.occlusion_transmission, 0.0..=1.0)
+                .text("Through each wall"),
+        );
+        ui.add(egui::Slider::new(&mut hearing.threshold, 0.0..=1.0).text("Bot threshold"));
+        ui.separator();
+        for occluders in 0..DRAWN_OCCLUSION_LEVELS {
+            ui.label(format!(
+                "Heard within {:.1} m through {} walls",
+                hearing.audible_radius(sound, occluders),
+                occluders
+            ));
+        }
+        ui.separator();
+        save = ui.button(format!("Save to {}", path.0.display())).clicked();
+        if let Some(status) = &state.status {
+            ui.label(status);
+        }
+    });
+
+    if state.sound != sound {
+        state.sound = sound;
+    }
+    if hearing != balance.hearing {
+        balance.hearing = hearing;
+    }
+    if save {
+        let status = match balance
+            .to_ron()
+            .and_then(|text| std::fs::write(&path.0, text).map_err(|e| e.to_string()))
+        {
+            Ok(()) => format!("Saved {}", path.0.display()),
+            Err(e) => format!("Failed to save {}: {}", path.0.display(), e),
+        };
+        state.status = Some(status);
+    }
+    Ok(())
+}
@@ -0,0 +1,512 @@
+use avian3d::prelude::{SpatialQuery, SpatialQueryFilter};
+use bevy::prelude::{
+    App, ButtonInput, Color, Entity, Gizmos, GlobalTransform, IntoScheduleConfigs, KeyCode,
+    MessageWriter, Plugin, Query, Res, ResMut, Resource, Result, Transform, Update, Vec3, With,
+    in_state, warn,
+};
+use bevy_inspector_egui::bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use lightyear::prelude::{Controlled, Predicted};
+use shared::NetworkMode;
+use shared::level::file::{LevelBlock, LevelFile, LevelLight, LoadLevelFile};
+use shared::protocol::PlayerId;
+use std::path::Path;
+
+use crate::ClientGameState;
+use crate::camera::PlayerCamera;
+
+const DEFAULT_LEVEL_PATH: &str = "levels/edited.ron";
+/// How far ahead of the camera things are placed when the crosshair hits nothing closer.
+const PLACE_DISTANCE: f32 = 10.0;
+/// Placed things snap to this grid, in meters.
+const GRID: f32 = 0.5;
+const DEFAULT_BLOCK_SIZE: Vec3 = Vec3::new(2.0, 2.0, 2.0);
+/// Distance moved by the arrow and page keys.
+const NUDGE: f32 = 0.5;
+
+const BLOCK_COLOR: Color = Color::srgb(0.7, 0.7, 0.75);
+const SPAWN_COLOR: Color = Color::srgb(0.3, 1.0, 0.4);
+const ROUTE_COLOR: Color = Color::srgb(1.0, 0.45, 0.2);
+const SELECTED_COLOR: Color = Color::srgb(1.0, 0.9, 0.1);
+
+/// Level editor, host only, toggled with F11. Places, moves and deletes blocks, spawn
+/// points, patrol routes and lights where the crosshair points (Insert places, Delete
+/// removes the selection, arrows and Page Up/Down move it), then exports the level to a RON
+/// file and loads it on the in-process server for playtesting.
+pub struct ClientLevelEditorPlugin;
+
+impl Plugin for ClientLevelEditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LevelEditorState>();
+        app.add_message::<LoadLevelFile>();
+        app.add_systems(Update, toggle_level_editor);
+        app.add_systems(
+            Update,
+            (level_editor_shortcuts, draw_level_editor)
+                .chain()
+                .run_if(in_state(ClientGameState::Playing))
+                .run_if(level_editor_visible),
+        );
+        app.add_systems(
+            EguiPrimaryContextPass,
+            level_editor_window.run_if(level_editor_visible),
+        );
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EditorTool {
+    Block,
+    SpawnPoint,
+    PatrolRoute,
+    Light,
+}
+
+impl EditorTool {
+    const ALL: [EditorTool; 4] = [
+        EditorTool::Block,
+        EditorTool::SpawnPoint,
+        EditorTool::PatrolRoute,
+        EditorTool::Light,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            EditorTool::Block => "Block",
+            EditorTool::SpawnPoint => "Spawn point",
+            EditorTool::PatrolRoute => "Patrol route",
+            EditorTool::Light => "Light",
+        }
+    }
+}
+
+/// Something in the edited level, by index. Patrol route points are selected one at a
+/// time; placing with the route tool appends to the selected route.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EditorItem {
+    Block(usize),
+    SpawnPoint(usize),
+    RoutePoint(usize, usize),
+    Light(usize),
+}
+
+#[derive(Resource, Debug)]
+pub struct LevelEditorState {
+    pub visible: bool,
+    pub tool: EditorTool,
+    pub level: LevelFile,
+    pub selected: Option<EditorItem>,
+    pub path: String,
+    /// Set by the window's button, handled with the keyboard shortcuts.
+    place_requested: bool,
+    /// Outcome of the last export or import, shown in the window.
+    status: Option<String>,
+}
+
+impl Default for LevelEditorState {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            tool: EditorTool::Block,
+            level: LevelFile::default(),
+            selected: None,
+            path: DEFAULT_LEVEL_PATH.to_string(),
+            place_requested: false,
+            status: None,
+        }
+    }
+}
+
+impl LevelEditorState {
+    /// Adds a thing of the current tool's kind at `point` and selects it.
+    pub fn place(&mut self, point: Vec3) {
+        let level = &mut self.level;
+        let selected = match self.tool {
+            EditorTool::Block => {
+                level.blocks.push(LevelBlock {
+                    center: point + Vec3::Y * DEFAULT_BLOCK_SIZE.y / 2.0,
+                    size: DEFAULT_BLOCK_SIZE,
+                });
+                EditorItem::Block(level.blocks.len() - 1)
+            }
+            EditorTool::SpawnPoint => {
+                level.spawn_points.push(point + Vec3::Y);
+                EditorItem::SpawnPoint(level.spawn_points.len() - 1)
+            }
+            EditorTool::PatrolRoute => {
+                let route = match self.selected {
+                    Some(EditorItem::RoutePoint(route, _)) if route < level.patrol_routes.len() => {
+                        route
+                    }
+                    _ => {
+                        level.patrol_routes.push(Vec::new());
+                        level.patrol_routes.len() - 1
+                    }
+                };
+                level.patrol_routes[route].push(point + Vec3::Y);
+                EditorItem::RoutePoint(route, level.patrol_routes[route].len() - 1)
+            }
+            EditorTool::Light => {
+                level.lights.push(LevelLight::new(point + Vec3::Y * 3.0));
+                EditorItem::Light(level.lights.len() - 1)
+            }
+        };
+        self.selected = Some(selected);
+    }
+
+    pub fn selected_position_mut(&mut self) -> Option<&mut Vec3> {
+        let level = &mut self.level;
+        match self.selected? {
+            EditorItem::Block(index) => level.blocks.get_mut(index).map(|block| &mut block.center),
+            EditorItem::SpawnPoint(index) => level.spawn_points.get_mut(index),
+            EditorItem::RoutePoint(route, point) => level
+                .patrol_routes
+                .get_mut(route)
+                .and_then(|route| route.get_mut(point)),
+            EditorItem::Light(index) => {
+                level.lights.get_mut(index).map(|light| &mut light.position)
+            }
+        }
+    }
+
+    /// Removes the selection. Routes left without points go with it.
+    pub fn delete_selected(&mut self) {
+        let level = &mut self.level;
+        match self.selected.take() {
+            Some(EditorItem::Block(index)) if index < level.blocks.len() => {
+                level.blocks.remove(index);
+            }
+            Some(EditorItem::SpawnPoint(index)) if index < level.spawn_points.len() => {
+                level.spawn_points.remove(index);
+            }
+            Some(EditorItem::RoutePoint(route, point)) if route < level.patrol_routes.len() => {
+                if point < level.patrol_routes[route].len() {
+                    level.patrol_routes[route].remove(point);
+                }
+                if level.patrol_routes[route].is_empty() {
+                    level.patrol_routes.remove(route);
+                }
+            }
+            Some(EditorItem::Light(index)) if index < level.lights.len() => {
+                level.lights.remove(index);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn level_editor_visible(state: Res<LevelEditorState>) -> bool {
+    state.visible
+}
+
+fn toggle_level_editor(
+    keys: Res<ButtonInput<KeyCode>>,
+    network_mode: Option<Res<NetworkMode>>,
+    mut state: ResMut<LevelEditorState>,
+) {
+    if !keys.just_pressed(KeyCode::F11) {
+        return;
+    }
+    // Edits go straight into the server's world, which only the host shares.
+    if network_mode.is_none_or(|mode| *mode != NetworkMode::Local) {
+        warn!("The level editor is only available when hosting");
+        return;
+    }
+
+    state.visible = !state.visible;
+    if state.visible && state.level == LevelFile::default() {
+        let path = state.path.clone();
+        if let Ok(level) = LevelFile::load(Path::new(&path)) {
+            state.level = level;
+            state.status = Some(format!("Loaded {}", path));
+        }
+    }
+}
+
+fn snap(point: Vec3) -> Vec3 {
+    (point / GRID).round() * GRID
+}
+
+fn level_editor_shortcuts(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut state: ResMut<LevelEditorState>,
+    spatial_query: SpatialQuery,
+    camera: Query<&GlobalTransform, With<PlayerCamera>>,
+    local_player: Query<Entity, (With<PlayerId>, With<Predicted>, With<Controlled>)>,
+) {
+    if state.place_requested || keys.just_pressed(KeyCode::Insert) {
+        state.place_requested = false;
+        if let Ok(camera) = camera.single() {
+            let origin = camera.translation();
+            let direction = camera.forward();
+            let filter = SpatialQueryFilter::default().with_excluded_entities(&local_player);
+            let distance = spatial_query
+                .cast_ray(origin, direction, PLACE_DISTANCE, true, &filter)
+                .map_or(PLACE_DISTANCE, |hit| hit.distance);
+            state.place(snap(origin + direction * distance));
+        }
+    }
+    if keys.just_pressed(KeyCode::Delete) {
+        state.delete_selected();
+    }
+
+    let nudges = [
+        (KeyCode::ArrowLeft, Vec3::NEG_X),
+        (KeyCode::ArrowRight, Vec3::X),
+        (KeyCode::ArrowUp, Vec3::NEG_Z),
+        (KeyCode::ArrowDown, Vec3::Z),
+        (KeyCode::PageUp, Vec3::Y),
+        (KeyCode::PageDown, Vec3::NEG_Y),
+    ];
+    let nudge: Vec3 = nudges
+        .iter()
+        .filter(|(key, _)| keys.just_pressed(*key))
+        .map(|(_, direction)| *direction * NUDGE)
+        .sum();
+    if nudge != Vec3::ZERO
+        && let Some(position) = state.selected_position_mut()
+    {
+        *position += nudge;
+    }
+}
+
+/// Everything in the edited level, the selection highlighted with its axes drawn.
+fn draw_level_editor(mut gizmos: Gizmos, state: Res<LevelEditorState>) {
+    let level = &state.level;
+    let highlight = |item: EditorItem, color: Color| {
+        if state.selected == Some(item) {
+            SELECTED_COLOR
+        } else {
+            color
+        }
+    };
+
+    for (index, block) in level.blocks.iter().enumerate() {
+        gizmos.cube(
+            Transform::from_translation(block.center).with_scale(block.size),
+            highlight(EditorItem::Block(index), BLOCK_COLOR),
+        );
+    }
+    for (index, spawn_point) in level.spawn_points.iter().enumerate() {
+        let color = highlight(EditorItem::SpawnPoint(index), SPAWN_COLOR);
+        gizmos.sphere(*spawn_point, 0.5, color);
+        gizmos.arrow(*spawn_point, *spawn_point + Vec3::NEG_Z, color);
+    }
+    for (route_index, route) in level.patrol_routes.iter().enumerate() {
+        gizmos.linestrip(route.iter().copied(), ROUTE_COLOR);
+        for (index, point) in route.iter().enumerate() {
+            let color = highlight(EditorItem::RoutePoint(route_index, index), ROUTE_COLOR);
+            gizmos.sphere(*point, 0.3, color);
+        }
+    }
+    for (index, light) in level.lights.iter().enumerate() {
+        let [red, green, blue] = light.color;
+        let color = highlight(
+            EditorItem::Light(index),
+            Color::linear_rgb(red, green, blue),
+        );
+        gizmos.sphere(light.position, 0.4, color);
+        gizmos.sphere(light.position, light.range, color.with_alpha(0.15));
+    }
+
+    let selected = match state.selected {
+        Some(EditorItem::Block(index)) => level.blocks.get(index).map(|block| block.center),
+        Some(EditorItem::SpawnPoint(index)) => level.spawn_points.get(index).copied(),
+        Some(EditorItem::RoutePoint(route, point)) => level
+            .patrol_routes
+            .get(route)
+            .and_then(|route| route.get(point))
+            .copied(),
+        Some(EditorItem::Light(index)) => level.lights.get(index).map(|light| light.position),
+        None => None,
+    };
+    if let Some(position) = selected {
+        gizmos.arrow(
+            position,
+            position + Vec3::X * 1.5,
+            Color::srgb(1.0, 0.2, 0.2),
+        );
+        gizmos.arrow(
+            position,
+            position + Vec3::Y * 1.5,
+            Color::srgb(0.2, 1.0, 0.2),
+        );
+        gizmos.arrow(
+            position,
+            position + Vec3::Z * 1.5,
+            Color::srgb(0.2, 0.4, 1.0),
+        );
+    }
+}
+
+fn drag_vec3(ui: &mut egui::Ui, label: &str, value: &mut Vec3, speed: f32) {
+    ui.horizontal(|ui| {
+        ui.label(label);
+        ui.add(egui::DragValue::new(&mut value.x).speed(speed).prefix("x "));
+        ui.add(egui::DragValue::new(&mut value.y).speed(speed).prefix("y "));
+        ui.add(egui::DragValue::new(&mut value.z).speed(speed).prefix("z "));
+    });
+}
+
+fn level_editor_window(
+    mut contexts: EguiContexts,
+    mut state: ResMut<LevelEditorState>,
+    mut load_requests: MessageWriter<LoadLevelFile>,
+) -> Result {
+    let mut export = false;
+    let mut import = false;
+
+    egui::Window::new("Level editor").show(contexts.ctx_mut()?, |ui| {
+        let state = &mut *state;
+        ui.horizontal(|ui| {
+            for tool in EditorTool::ALL {
+                ui.radio_value(&mut state.tool, tool, tool.label());
+            }
+        });
+        ui.horizontal(|ui| {
+            state.place_requested |= ui.button("Place at crosshair (Insert)").clicked();
+            if ui.button("Delete selected (Delete)").clicked() {
+                state.delete_selected();
+            }
+            if ui.button("New route").clicked() {
+                state.tool = EditorTool::PatrolRoute;
+                state.selected = None;
+            }
+        });
+        ui.separator();
+
+        let level = &state.level;
+        let items = (0..level.blocks.len())
+            .map(|index| (EditorItem::Block(index), format!("Block {}", index + 1)))
+            .chain((0..level.spawn_points.len()).map(|index| {
+                (
+                    EditorItem::SpawnPoint(index),
+                    format!("Spawn point {}", index + 1),
+                )
+            }))
+            .chain(
+                level
+                    .patrol_routes
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(route, points)| {
+                        (0..points.len()).map(move |point| {
+                            (
+                                EditorItem::RoutePoint(route, point),
+                                format!("Route {} point {}", route + 1, point + 1),
+                            )
+                        })
+                    }),
+            )
+            .chain(
+                (0..level.lights.len())
+                    .map(|index| (EditorItem::Light(index), format!("Light {}", index + 1))),
+            )
+            .collect::<Vec<_>>();
+        egui::ScrollArea::vertical()
+            .max_height(200.0)
+            .show(ui, |ui| {
+                for (item, label) in items {
+                    if ui
+                        .selectable_label(state.selected == Some(item), label)
+                        .clicked()
+                    {
+                        state.selected = Some(item);
+                    }
+                }
+            });
+
+        if let Some(position) = state.selected_position_mut() {
+            ui.separator();
+            drag_vec3(ui, "Position", position, 0.1);
+        }
+        match state.selected {
+            Some(EditorItem::Block(index)) => {
+                if let Some(block) = state.level.blocks.get_mut(index) {
+                    drag_vec3(ui, "Size", &mut block.size, 0.1);
+                    block.size = block.size.max(Vec3::splat(0.1));
+                }
+            }
+            Some(EditorItem::Light(index)) => {
+                if let Some(light) = state.level.lights.get_mut(index) {
+                    ui.color_edit_button_rgb(&mut light.color);
+                    ui.add(
+                        egui::Slider::new(&mut light.intensity, 0.0..=200000.0).text("Intensity"),
+                    );
+                    ui.add(egui::Slider::new(&mut light.range, 1.0..=60.0).text("Range"));
+                }
+            }
+            _ => {}
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("File");
+            ui.text_edit_singleline(&mut state.path);
+        });
+        ui.horizontal(|ui| {
+            export = ui.button("Export and playtest").clicked();
+            import = ui.button("Load file").clicked();
+        });
+        if let Some(status) = &state.status {
+            ui.label(status);
+        }
+    });
+
+    let path = state.path.clone();
+    if export {
+        let status = match state.level.save(Path::new(&path)) {
+            Ok(()) => {
+                load_requests.write(LoadLevelFile(state.level.clone()));
+                format!("Saved {} and loaded it on the server", path)
+            }
+            Err(e) => format!("Failed to save {}: {}", path, e),
+        };
+        state.status = Some(status);
+    }
+    if import {
+        let status = match LevelFile::load(Path::new(&path)) {
+            Ok(level) => {
+                state.level = level;
+                state.selected = None;
+                format!("Loaded {}", path)
+            }
+            Err(e) => format!("Failed to load {}: {}", path, e),
+        };
+        state.status = Some(status);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EditorItem, EditorTool, LevelEditorState};
+    use bevy::prelude::Vec3;
+
+    #[test]
+    fn placing_moving_and_deleting_edit_the_level() {
+        let mut state = LevelEditorState {
+            tool: EditorTool::PatrolRoute,
+            ..Default::default()
+        };
+        state.place(Vec3::ZERO);
+        state.place(Vec3::new(4.0, 0.0, 0.0));
+        assert_eq!(state.level.patrol_routes.len(), 1);
+        assert_eq!(state.level.patrol_routes[0].len(), 2);
+        assert_eq!(state.selected, Some(EditorItem::RoutePoint(0, 1)));
+
+        *state.selected_position_mut().unwrap() += Vec3::Z;
+        assert_eq!(state.level.patrol_routes[0][1], Vec3::new(4.0, 1.0, 1.0));
+
+        state.tool = EditorTool::Block;
+        state.place(Vec3::ZERO);
+        assert_eq!(state.level.blocks[0].center, Vec3::new(0.0, 1.0, 0.0));
+        state.delete_selected();
+        assert!(state.level.blocks.is_empty());
+
+        state.selected = Some(EditorItem::RoutePoint(0, 0));
+        state.delete_selected();
+        state.selected = Some(EditorItem::RoutePoint(0, 0));
+        state.delete_selected();
+        assert!(state.level.patrol_routes.is_empty());
+    }
+}
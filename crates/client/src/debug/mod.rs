@@ -1,3 +1,9 @@
+pub mod cpu_profile;
+pub mod hearing;
+pub mod level_editor;
+pub mod net_labels;
+pub mod netgraph;
+
 use crate::ClientGameState;
 use crate::camera::PlayerCamera;
 
@@ -9,7 +15,7 @@ use leafwing_input_manager::prelude::ActionState;
 
 use lightyear::prelude::{Controlled, Predicted};
 use shared::{
-    components::health::Health,
+    components::{health::Health, shield::Shield},
     inputs::input::PlayerAction,
     navigation::{PatrolRoute, PatrolState, SimpleNavigationAgent},
     protocol::{CharacterMarker, PlayerId},
@@ -79,7 +85,7 @@ fn toggle_debug_view(
     mut debug_view_state: ResMut<DebugViewState>,
     mut fps_overlay_config: ResMut<FpsOverlayConfig>,
 ) {
-    if keys.just_pressed(KeyCode::KeyH) || keys.just_pressed(KeyCode::F3) {
+    if keys.just_pressed(KeyCode::KeyH) {
         debug_view_state.enabled = !debug_view_state.enabled;
         fps_overlay_config.enabled = debug_view_state.enabled;
     }
@@ -234,13 +240,16 @@ fn debug_navigation_paths(
 }
 
 fn debug_npc_health_gizmos(
-    npc_query: Query<(&Position, &Health), (With<CharacterMarker>, Without<PlayerId>)>,
+    npc_query: Query<
+        (&Position, &Health, Option<&Shield>),
+        (With<CharacterMarker>, Without<PlayerId>),
+    >,
     camera_query: Query<&GlobalTransform, With<PlayerCamera>>,
     mut gizmos: Gizmos,
 ) {
     let camera_transform = camera_query.single().ok();
 
-    for (position, health) in &npc_query {
+    for (position, health, shield) in &npc_query {
         let health_ratio = health.percentage();
         let center = position.0 + Vec3::Y * 2.5;
 
@@ -280,5 +289,15 @@ fn debug_npc_health_gizmos(
                 health_color,
             );
         }
+
+        if let Some(shield) = shield {
+            let shield_offset = up_axis * 0.12;
+            let shield_right = left + right_axis * (bar_width * shield.percentage());
+            gizmos.line(
+                left + shield_offset,
+                shield_right + shield_offset,
+                Color::srgb(0.2, 0.6, 1.0),
+            );
+        }
     }
 }
@@ -0,0 +1,245 @@
+use avian3d::prelude::Position;
+use bevy::prelude::{
+    App, ButtonInput, Camera, Color, Commands, Component, Entity, GlobalTransform, Has,
+    IntoScheduleConfigs, KeyCode, Name, Node, Or, Plugin, PositionType, Query, Ref, Res, ResMut,
+    Resource, Text, TextColor, TextFont, Update, Val, Vec3, Visibility, With, default,
+};
+use lightyear::prelude::{
+    Client, Confirmed, InputTimeline, Interpolated, NetworkTimeline, Predicted, Replicated, Tick,
+};
+use shared::protocol::PlayerId;
+
+use crate::camera::PlayerCamera;
+
+/// Labels float this far above the entity's origin.
+const LABEL_HEIGHT: f32 = 2.2;
+
+/// Floating replication labels over every replicated entity, toggled with F5: entity id,
+/// prediction status, ticks since the last confirmed update and owning peer.
+pub struct ClientNetLabelsPlugin;
+
+impl Plugin for ClientNetLabelsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NetLabelsState>();
+        app.add_systems(
+            Update,
+            (
+                toggle_net_labels,
+                track_confirmed_updates,
+                sync_net_labels,
+                update_net_labels,
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct NetLabelsState {
+    pub visible: bool,
+}
+
+/// How the client holds a replicated entity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReplicationStatus {
+    Confirmed,
+    Predicted,
+    Interpolated,
+}
+
+impl ReplicationStatus {
+    fn label(self) -> &'static str {
+        match self {
+            ReplicationStatus::Confirmed => "confirmed",
+            ReplicationStatus::Predicted => "predicted",
+            ReplicationStatus::Interpolated => "interpolated",
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            ReplicationStatus::Confirmed => Color::srgb(0.9, 0.9, 0.9),
+            ReplicationStatus::Predicted => Color::srgb(0.3, 1.0, 0.4),
+            ReplicationStatus::Interpolated => Color::srgb(0.4, 0.7, 1.0),
+        }
+    }
+}
+
+/// Tick at which the server's position for this entity last changed.
+#[derive(Component, Clone, Copy, Debug)]
+struct LastConfirmedUpdate(Tick);
+
+#[derive(Component)]
+struct NetLabel {
+    target: Entity,
+}
+
+type ReplicatedFilter = Or<(With<Replicated>, With<Predicted>, With<Interpolated>)>;
+
+/// Label text: entity id and status, then the update age and the owner.
+pub fn format_net_label(
+    entity: Entity,
+    status: ReplicationStatus,
+    ticks_since_update: Option<i32>,
+    owner: Option<u64>,
+) -> String {
+    format!(
+        "{} {}\nupdated {} | owner {}",
+        entity,
+        status.label(),
+        ticks_since_update.map_or_else(|| "never".to_string(), |age| format!("{} ticks ago", age)),
+        owner.map_or_else(|| "server".to_string(), |peer| peer.to_string()),
+    )
+}
+
+fn toggle_net_labels(keys: Res<ButtonInput<KeyCode>>, mut state: ResMut<NetLabelsState>) {
+    if keys.just_pressed(KeyCode::F5) {
+        state.visible = !state.visible;
+    }
+}
+
+fn current_tick(timelines: &Query<&InputTimeline, With<Client>>) -> Option<Tick> {
+    timelines.single().ok().map(|timeline| timeline.tick())
+}
+
+/// Predicted and interpolated entities receive the server's position in `Confirmed`; the
+/// others straight in `Position`.
+fn track_confirmed_updates(
+    mut commands: Commands,
+    timelines: Query<&InputTimeline, With<Client>>,
+    entities: Query<(Entity, Ref<Position>, Option<Ref<Confirmed<Position>>>), ReplicatedFilter>,
+) {
+    let Some(now) = current_tick(&timelines) else {
+        return;
+    };
+    for (entity, position, confirmed) in entities.iter() {
+        let updated = match confirmed {
+            Some(confirmed) => confirmed.is_changed(),
+            None => position.is_changed(),
+        };
+        if updated {
+            commands.entity(entity).insert(LastConfirmedUpdate(now));
+        }
+    }
+}
+
+/// One label per replicated entity while the labels are on, none otherwise.
+fn sync_net_labels(
+    mut commands: Commands,
+    state: Res<NetLabelsState>,
+    labels: Query<(Entity, &NetLabel)>,
+    entities: Query<Entity, (With<Position>, ReplicatedFilter)>,
+) {
+    for (label, net_label) in labels.iter() {
+        if !state.visible || !entities.contains(net_label.target) {
+            commands.entity(label).despawn();
+        }
+    }
+    if !state.visible {
+        return;
+    }
+
+    for entity in entities.iter() {
+        if labels.iter().any(|(_, label)| label.target == entity) {
+            continue;
+        }
+        commands.spawn((
+            Name::new("NetLabel"),
+            NetLabel { target: entity },
+            Text::new(""),
+            TextFont {
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            Node {
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            Visibility::Hidden,
+        ));
+    }
+}
+
+fn update_net_labels(
+    state: Res<NetLabelsState>,
+    timelines: Query<&InputTimeline, With<Client>>,
+    camera: Query<(&Camera, &GlobalTransform), With<PlayerCamera>>,
+    entities: Query<(
+        &Position,
+        Has<Predicted>,
+        Has<Interpolated>,
+        Option<&LastConfirmedUpdate>,
+        Option<&PlayerId>,
+    )>,
+    mut labels: Query<(
+        &NetLabel,
+        &mut Text,
+        &mut TextColor,
+        &mut Node,
+        &mut Visibility,
+    )>,
+) {
+    if !state.visible {
+        return;
+    }
+    let Ok((camera, camera_transform)) = camera.single() else {
+        return;
+    };
+    let now = current_tick(&timelines);
+
+    for (label, mut text, mut color, mut node, mut visibility) in labels.iter_mut() {
+        let Ok((position, predicted, interpolated, last_update, owner)) =
+            entities.get(label.target)
+        else {
+            continue;
+        };
+        let Ok(screen) =
+            camera.world_to_viewport(camera_transform, position.0 + Vec3::Y * LABEL_HEIGHT)
+        else {
+            // Behind the camera.
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        let status = if predicted {
+            ReplicationStatus::Predicted
+        } else if interpolated {
+            ReplicationStatus::Interpolated
+        } else {
+            ReplicationStatus::Confirmed
+        };
+        let age = now
+            .zip(last_update)
+            .map(|(now, last_update)| i32::from(now - last_update.0).max(0));
+        text.0 = format_net_label(
+            label.target,
+            status,
+            age,
+            owner.map(|owner| owner.0.to_bits()),
+        );
+        color.0 = status.color();
+        node.left = Val::Px(screen.x);
+        node.top = Val::Px(screen.y);
+        *visibility = Visibility::Visible;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ReplicationStatus, format_net_label};
+    use bevy::prelude::World;
+
+    #[test]
+    fn labels_show_status_age_and_owner() {
+        let entity = World::new().spawn_empty().id();
+        assert_eq!(
+            format_net_label(entity, ReplicationStatus::Predicted, Some(3), Some(2)),
+            format!("{} predicted\nupdated 3 ticks ago | owner 2", entity)
+        );
+        assert_eq!(
+            format_net_label(entity, ReplicationStatus::Interpolated, None, None),
+            format!("{} interpolated\nupdated never | owner server", entity)
+        );
+    }
+}
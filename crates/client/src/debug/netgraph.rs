@@ -0,0 +1,386 @@
+use bevy::prelude::{
+    Add, App, BackgroundColor, ButtonInput, Color, Commands, Component, IntoScheduleConfigs,
+    KeyCode, Name, Node, On, Plugin, PositionType, PostUpdate, PreUpdate, Query, Res, ResMut,
+    Resource, Startup, Text, TextFont, Time, Timer, TimerMode, UiRect, Update, Val, Visibility,
+    With, default,
+};
+use lightyear::prelude::{
+    Client, Connected, InputTimeline, InterpolationTimeline, Link, LinkSystems, MessageReceiver,
+    NetworkTimeline, PingManager, TransportSystems,
+};
+use shared::protocol::NetProbe;
+use std::collections::VecDeque;
+
+/// Probes considered when computing packet loss (~5 s at 20 probes per second).
+const LOSS_WINDOW: u32 = 100;
+/// Samples kept for the graphs.
+const HISTORY_LEN: usize = 60;
+const SAMPLE_INTERVAL_SECS: f32 = 0.1;
+const SPARKLINE_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Network diagnostics overlay, toggled with F3.
+pub struct ClientNetgraphPlugin;
+
+impl Plugin for ClientNetgraphPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NetgraphState>();
+        app.init_resource::<PacketLossTracker>();
+        app.init_resource::<LinkTraffic>();
+        app.init_resource::<NetDiagnostics>();
+        app.add_systems(Startup, spawn_netgraph_overlay);
+        app.add_observer(reset_packet_loss_on_connect);
+        // Count packets while they sit in the link buffers, between the IO and transport
+        // layers.
+        app.add_systems(
+            PreUpdate,
+            count_received_packets
+                .after(LinkSystems::Receive)
+                .before(TransportSystems::Receive),
+        );
+        app.add_systems(
+            PostUpdate,
+            count_sent_packets
+                .after(TransportSystems::Send)
+                .before(LinkSystems::Send),
+        );
+        app.add_systems(
+            Update,
+            (
+                toggle_netgraph,
+                receive_net_probes,
+                sample_net_diagnostics,
+                update_netgraph_overlay,
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct NetgraphState {
+    pub visible: bool,
+}
+
+/// Packet loss measured from gaps in the server's [`NetProbe`] sequence.
+///
+/// A probe from further back than the loss window means the server started counting again
+/// (a restart or a new session), so the tracker starts over from it.
+#[derive(Resource, Debug, Default)]
+pub struct PacketLossTracker {
+    received: VecDeque<u32>,
+    first: Option<u32>,
+    highest: u32,
+}
+
+impl PacketLossTracker {
+    pub fn record(&mut self, sequence: u32) {
+        if self.first.is_some() && sequence.saturating_add(LOSS_WINDOW) <= self.highest {
+            *self = Self::default();
+        }
+
+        let first = *self.first.get_or_insert(sequence);
+        if sequence < first || self.received.contains(&sequence) {
+            return;
+        }
+
+        self.highest = self.highest.max(sequence);
+        self.received.push_back(sequence);
+        let oldest = self.window_start();
+        self.received.retain(|&received| received >= oldest);
+    }
+
+    fn window_start(&self) -> u32 {
+        let first = self.first.unwrap_or_default();
+        self.highest.saturating_sub(LOSS_WINDOW - 1).max(first)
+    }
+
+    /// Fraction (0.0..=1.0) of probes missing from the recent window.
+    pub fn loss(&self) -> f32 {
+        if self.first.is_none() {
+            return 0.0;
+        }
+
+        let expected = self.highest - self.window_start() + 1;
+        1.0 - self.received.len() as f32 / expected as f32
+    }
+}
+
+/// Packets seen in the link buffers since the last sample.
+#[derive(Resource, Debug, Default)]
+struct LinkTraffic {
+    received: u32,
+    sent: u32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct NetSample {
+    pub rtt_ms: f32,
+    pub jitter_ms: f32,
+    pub packet_loss: f32,
+    /// How far remote (interpolated) entities lag behind the locally predicted timeline.
+    pub interpolation_delay_ms: f32,
+    pub packets_in_per_sec: f32,
+    pub packets_out_per_sec: f32,
+}
+
+#[derive(Resource, Debug)]
+pub struct NetDiagnostics {
+    pub history: VecDeque<NetSample>,
+    timer: Timer,
+}
+
+impl Default for NetDiagnostics {
+    fn default() -> Self {
+        Self {
+            history: VecDeque::with_capacity(HISTORY_LEN),
+            timer: Timer::from_seconds(SAMPLE_INTERVAL_SECS, TimerMode::Repeating),
+        }
+    }
+}
+
+impl NetDiagnostics {
+    pub fn push(&mut self, sample: NetSample) {
+        if self.history.len() == HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.history.push_back(sample);
+    }
+
+    pub fn latest(&self) -> Option<&NetSample> {
+        self.history.back()
+    }
+}
+
+#[derive(Component)]
+struct NetgraphText;
+
+/// One character per value, scaled so `max` maps to a full block.
+pub fn sparkline(values: impl Iterator<Item = f32>, max: f32) -> String {
+    let top = (SPARKLINE_LEVELS.len() - 1) as f32;
+    values
+        .map(|value| {
+            let level = if max > 0.0 {
+                (value / max).clamp(0.0, 1.0) * top
+            } else {
+                0.0
+            };
+            SPARKLINE_LEVELS[level.round() as usize]
+        })
+        .collect()
+}
+
+pub fn format_netgraph(diagnostics: &NetDiagnostics) -> String {
+    let Some(latest) = diagnostics.latest() else {
+        return "netgraph: waiting for samples".to_string();
+    };
+
+    let rtt_max = diagnostics
+        .history
+        .iter()
+        .map(|sample| sample.rtt_ms)
+        .fold(1.0, f32::max);
+    let loss_max = diagnostics
+        .history
+        .iter()
+        .map(|sample| sample.packet_loss)
+        .fold(0.05, f32::max);
+
+    [
+        format!(
+            "rtt {:>5.1} ms  jitter {:>4.1} ms",
+            latest.rtt_ms, latest.jitter_ms
+        ),
+        sparkline(diagnostics.history.iter().map(|s| s.rtt_ms), rtt_max),
+        format!("loss {:>5.1} %", latest.packet_loss * 100.0),
+        sparkline(diagnostics.history.iter().map(|s| s.packet_loss), loss_max),
+        format!("interp delay {:>5.1} ms", latest.interpolation_delay_ms),
+        format!(
+            "link in {:>4.0} pkt/s  out {:>4.0} pkt/s",
+            latest.packets_in_per_sec, latest.packets_out_per_sec
+        ),
+    ]
+    .join("\n")
+}
+
+fn spawn_netgraph_overlay(mut commands: Commands) {
+    commands.spawn((
+        Name::new("NetgraphOverlay"),
+        NetgraphText,
+        Text::new(""),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        Visibility::Hidden,
+        BackgroundColor(Color::srgba(0.05, 0.05, 0.08, 0.75)),
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(16.0),
+            bottom: Val::Px(16.0),
+            padding: UiRect::all(Val::Px(8.0)),
+            ..default()
+        },
+    ));
+}
+
+fn toggle_netgraph(keys: Res<ButtonInput<KeyCode>>, mut state: ResMut<NetgraphState>) {
+    if keys.just_pressed(KeyCode::F3) {
+        state.visible = !state.visible;
+    }
+}
+
+fn count_received_packets(links: Query<&Link, With<Client>>, mut traffic: ResMut<LinkTraffic>) {
+    for link in &links {
+        traffic.received += link.recv.len() as u32;
+    }
+}
+
+fn count_sent_packets(links: Query<&Link, With<Client>>, mut traffic: ResMut<LinkTraffic>) {
+    for link in &links {
+        traffic.sent += link.send.len() as u32;
+    }
+}
+
+fn reset_packet_loss_on_connect(
+    _trigger: On<Add, Connected>,
+    mut tracker: ResMut<PacketLossTracker>,
+) {
+    *tracker = PacketLossTracker::default();
+}
+
+fn receive_net_probes(
+    mut receivers: Query<&mut MessageReceiver<NetProbe>, With<Client>>,
+    mut tracker: ResMut<PacketLossTracker>,
+) {
+    for mut receiver in receivers.iter_mut() {
+        for probe in receiver.receive() {
+            tracker.record(probe.sequence);
+        }
+    }
+}
+
+fn sample_net_diagnostics(
+    time: Res<Time>,
+    clients: Query<
+        (
+            &PingManager,
+            Option<&InputTimeline>,
+            Option<&InterpolationTimeline>,
+        ),
+        With<Client>,
+    >,
+    tracker: Res<PacketLossTracker>,
+    mut traffic: ResMut<LinkTraffic>,
+    mut diagnostics: ResMut<NetDiagnostics>,
+) {
+    diagnostics.timer.tick(time.delta());
+    if !diagnostics.timer.just_finished() {
+        return;
+    }
+
+    let Ok((ping, input_timeline, interpolation_timeline)) = clients.single() else {
+        return;
+    };
+
+    let tick_ms = 1000.0 / shared::FIXED_TIMESTEP_HZ as f32;
+    let interpolation_delay_ms = match (input_timeline, interpolation_timeline) {
+        (Some(input), Some(interpolation)) => {
+            i32::from(input.tick() - interpolation.tick()).max(0) as f32 * tick_ms
+        }
+        _ => 0.0,
+    };
+
+    let elapsed = SAMPLE_INTERVAL_SECS;
+    diagnostics.push(NetSample {
+        rtt_ms: ping.rtt().as_secs_f32() * 1000.0,
+        jitter_ms: ping.jitter().as_secs_f32() * 1000.0,
+        packet_loss: tracker.loss(),
+        interpolation_delay_ms,
+        packets_in_per_sec: traffic.received as f32 / elapsed,
+        packets_out_per_sec: traffic.sent as f32 / elapsed,
+    });
+    *traffic = LinkTraffic::default();
+}
+
+fn update_netgraph_overlay(
+    state: Res<NetgraphState>,
+    diagnostics: Res<NetDiagnostics>,
+    mut overlay: Query<(&mut Text, &mut Visibility), With<NetgraphText>>,
+) {
+    let Ok((mut text, mut visibility)) = overlay.single_mut() else {
+        return;
+    };
+
+    visibility.set_if_neq(if state.visible {
+        Visibility::Visible
+    } else {
+        Visibility::Hidden
+    });
+    if state.visible && diagnostics.is_changed() {
+        **text = format_netgraph(&diagnostics);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LOSS_WINDOW, NetDiagnostics, NetSample, PacketLossTracker, sparkline};
+
+    #[test]
+    fn packet_loss_counts_sequence_gaps() {
+        let mut tracker = PacketLossTracker::default();
+        assert_eq!(tracker.loss(), 0.0);
+
+        for sequence in (10..20).filter(|sequence| sequence % 5 != 0) {
+            tracker.record(sequence);
+        }
+        // 11..=19 expected, 15 missing.
+        assert!((tracker.loss() - 1.0 / 9.0).abs() < 1e-6);
+
+        // Duplicates and probes from before the first one do not count.
+        tracker.record(12);
+        tracker.record(3);
+        assert!((tracker.loss() - 1.0 / 9.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn packet_loss_forgets_old_gaps() {
+        let mut tracker = PacketLossTracker::default();
+        tracker.record(0);
+        for sequence in 5..(5 + LOSS_WINDOW) {
+            tracker.record(sequence);
+        }
+        assert_eq!(tracker.loss(), 0.0);
+    }
+
+    #[test]
+    fn packet_loss_restarts_when_the_sequence_jumps_back() {
+        let mut tracker = PacketLossTracker::default();
+        for sequence in (500..600).filter(|sequence| sequence % 2 == 0) {
+            tracker.record(sequence);
+        }
+        assert!(tracker.loss() > 0.4);
+
+        // The server restarted: its probes count up from zero again.
+        for sequence in 0..10 {
+            tracker.record(sequence);
+        }
+        assert_eq!(tracker.loss(), 0.0);
+        tracker.record(11);
+        assert!((tracker.loss() - 1.0 / 12.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn sparkline_scales_to_max_and_history_is_bounded() {
+        assert_eq!(sparkline([0.0, 5.0, 10.0, 20.0].into_iter(), 10.0), "▁▅██");
+
+        let mut diagnostics = NetDiagnostics::default();
+        for index in 0..200 {
+            diagnostics.push(NetSample {
+                rtt_ms: index as f32,
+                ..Default::default()
+            });
+        }
+        assert_eq!(diagnostics.history.len(), super::HISTORY_LEN);
+        assert_eq!(diagnostics.latest().map(|s| s.rtt_ms), Some(199.0));
+    }
+}
@@ -0,0 +1,134 @@
+//! Demonstration capture: with [`DemoCaptureSettings::path`] set, every fixed tick the
+//! local player is playing, their observation and the action they took are appended to a
+//! demonstration file (see `shared::demo`) for pretraining policies on human play.
+
+use std::path::PathBuf;
+
+use avian3d::prelude::{LinearVelocity, Position, Rotation, SpatialQueryPipeline};
+use bevy::prelude::{
+    App, Entity, FixedUpdate, Last, Plugin, Query, Res, ResMut, Resource, Startup, State, With,
+    info, warn,
+};
+use leafwing_input_manager::prelude::ActionState;
+use lightyear::prelude::{Controlled, Predicted};
+use shared::components::health::Health;
+use shared::demo::{DemoFrame, DemoWriter};
+use shared::inputs::input::PlayerAction;
+use shared::observation::{LAST_ACTION_SIZE, action_values, observation_buffer, ray_distances};
+use shared::protocol::PlayerId;
+
+use crate::ClientGameState;
+
+/// Where demonstrations are recorded. Nothing is recorded without a path.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct DemoCaptureSettings {
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Resource, Default)]
+struct DemoCapture {
+    writer: Option<DemoWriter>,
+    /// Fixed ticks since the recording started, recorded or not.
+    tick: u32,
+    /// The action of the previous recorded tick, part of the next observation.
+    last_action: Option<[f32; LAST_ACTION_SIZE]>,
+}
+
+pub struct ClientDemoCapturePlugin;
+
+impl Plugin for ClientDemoCapturePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DemoCaptureSettings>();
+        app.init_resource::<DemoCapture>();
+        app.add_systems(Startup, open_demo_file);
+        app.add_systems(FixedUpdate, capture_demo_frame);
+        app.add_systems(Last, flush_demo_file);
+    }
+}
+
+fn open_demo_file(settings: Res<DemoCaptureSettings>, mut capture: ResMut<DemoCapture>) {
+    let Some(path) = &settings.path else {
+        return;
+    };
+    match DemoWriter::create(path) {
+        Ok(writer) => {
+            info!("Recording demonstrations to {}", path.display());
+            capture.writer = Some(writer);
+        }
+        Err(e) => warn!("Demonstrations not recorded to {}: {}", path.display(), e),
+    }
+}
+
+fn capture_demo_frame(
+    mut capture: ResMut<DemoCapture>,
+    state: Res<State<ClientGameState>>,
+    spatial_query: Res<SpatialQueryPipeline>,
+    local_player: Query<
+        (
+            Entity,
+            &Position,
+            &Rotation,
+            &LinearVelocity,
+            Option<&Health>,
+            &ActionState<PlayerAction>,
+        ),
+        (With<PlayerId>, With<Predicted>, With<Controlled>),
+    >,
+) {
+    if capture.writer.is_none() {
+        return;
+    }
+    let tick = capture.tick;
+    capture.tick = capture.tick.wrapping_add(1);
+
+    let player = local_player
+        .single()
+        .ok()
+        .filter(|_| *state.get() == ClientGameState::Playing);
+    let Some((entity, position, rotation, velocity, health, action_state)) = player else {
+        // The next recorded tick starts a new segment, with no action before it.
+        capture.last_action = None;
+        return;
+    };
+
+    let action = action_values(action_state);
+    let frame = DemoFrame {
+        tick,
+        observation: observation_buffer(
+            position.0,
+            velocity.0,
+            health.map_or(0.0, |health| health.current),
+            health.map_or(0.0, |health| health.max),
+            &ray_distances(&spatial_query, entity, position.0, rotation.0),
+            &capture.last_action.unwrap_or_default(),
+        ),
+        action,
+    };
+    capture.last_action = Some(action);
+
+    let Some(writer) = &mut capture.writer else {
+        return;
+    };
+    if let Err(e) = writer.write_frame(&frame) {
+        warn!(
+            "Stopped recording demonstrations to {}: {}",
+            writer.path().display(),
+            e
+        );
+        capture.writer = None;
+    }
+}
+
+/// Keeps the file usable if the game is killed rather than closed.
+fn flush_demo_file(mut capture: ResMut<DemoCapture>) {
+    if let Some(writer) = &mut capture.writer
+        && let Err(e) = writer.flush()
+    {
+        warn!(
+            "Stopped recording demonstrations to {}: {}",
+            writer.path().display(),
+            e
+        );
+        capture.writer = None;
+    }
+}
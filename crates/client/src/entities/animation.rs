@@ -0,0 +1,452 @@
+//! Skinned characters. Remote characters swap their capsule for `scenes/character.glb`
+//! once it has loaded, animated from replicated state: the legs follow a locomotion state
+//! machine (idle, walk, run, jump, fall) and, for armed characters, the spine and arms hold
+//! the weapon up and kick back on every shot.
+//!
+//! The model needs clips named `Idle`, `Walk`, `Run`, `Jump`, `Fall`, `Aim` and `Shoot`,
+//! and a bone named [`UPPER_BODY_ROOT_BONE`]: it and everything under it is upper body.
+//! Without the file characters keep their capsule.
+
+use avian3d::prelude::LinearVelocity;
+use bevy::animation::graph::{
+    AnimationGraph, AnimationGraphHandle, AnimationMask, AnimationNodeIndex,
+};
+use bevy::animation::{AnimationPlayer, AnimationTargetId};
+use bevy::asset::LoadState;
+use bevy::gltf::{Gltf, GltfAssetLabel};
+use bevy::prelude::{
+    App, AssetServer, Assets, ChildOf, Children, Commands, Component, Entity, Handle,
+    IntoScheduleConfigs, MeshMaterial3d, Name, Plugin, Quat, Query, Res, ResMut, Resource, Scene,
+    SceneRoot, StandardMaterial, Startup, Time, Transform, Update, Vec3, With, Without, warn,
+};
+use lightyear::prelude::Interpolated;
+use shared::components::weapons::Gun;
+use shared::inputs::input::{PLAYER_CAPSULE_HEIGHT, PLAYER_CAPSULE_RADIUS};
+use shared::inputs::movement::{GroundState, WALK_SPEED};
+use shared::protocol::CharacterMarker;
+
+pub const CHARACTER_MODEL_PATH: &str = "scenes/character.glb";
+/// First bone of the upper body.
+pub const UPPER_BODY_ROOT_BONE: &str = "Spine";
+
+const UPPER_BODY_GROUP: u32 = 0;
+const LOWER_BODY_GROUP: u32 = 1;
+/// Slower than this on the ground is standing still.
+const IDLE_SPEED: f32 = 0.5;
+/// Faster than this on the ground is running rather than walking.
+const RUN_THRESHOLD_SPEED: f32 = WALK_SPEED * 1.15;
+/// Rising faster than this in the air plays the jump clip, otherwise the fall clip.
+const JUMP_RISE_SPEED: f32 = 1.0;
+/// Ground speeds at which the walk and run clips play at their authored rate.
+const WALK_CLIP_SPEED: f32 = WALK_SPEED * 0.5;
+const RUN_CLIP_SPEED: f32 = WALK_SPEED * 1.5;
+/// Time to cross-fade from one locomotion clip to the next.
+const LOCOMOTION_BLEND_SECS: f32 = 0.15;
+/// How long the shoot clip replaces the aim pose after a shot.
+const SHOOT_POSE_SECS: f32 = 0.25;
+/// Weight of the aim layer against the locomotion clips on the upper body: the weapon
+/// stays up while the torso keeps a little of the walk's sway.
+const UPPER_BODY_WEIGHT: f32 = 4.0;
+/// The model's feet are at its origin; characters are centred on their capsule.
+const MODEL_OFFSET: Vec3 = Vec3::new(
+    0.0,
+    -(PLAYER_CAPSULE_HEIGHT * 0.5 + PLAYER_CAPSULE_RADIUS),
+    0.0,
+);
+
+/// What the legs are doing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LocomotionState {
+    #[default]
+    Idle,
+    Walk,
+    Run,
+    Jump,
+    Fall,
+}
+
+impl LocomotionState {
+    const ALL: [LocomotionState; 5] = [
+        LocomotionState::Idle,
+        LocomotionState::Walk,
+        LocomotionState::Run,
+        LocomotionState::Jump,
+        LocomotionState::Fall,
+    ];
+
+    /// Locomotion from the replicated velocity and ground contact.
+    pub fn from_motion(velocity: Vec3, grounded: bool) -> Self {
+        if !grounded {
+            return if velocity.y > JUMP_RISE_SPEED {
+                LocomotionState::Jump
+            } else {
+                LocomotionState::Fall
+            };
+        }
+        let speed = velocity.with_y(0.0).length();
+        if speed < IDLE_SPEED {
+            LocomotionState::Idle
+        } else if speed < RUN_THRESHOLD_SPEED {
+            LocomotionState::Walk
+        } else {
+            LocomotionState::Run
+        }
+    }
+
+    /// Playback rate that keeps the feet at the pace of the ground speed.
+    pub fn clip_speed(self, velocity: Vec3) -> f32 {
+        let speed = velocity.with_y(0.0).length();
+        match self {
+            LocomotionState::Walk => (speed / WALK_CLIP_SPEED).clamp(0.5, 2.0),
+            LocomotionState::Run => (speed / RUN_CLIP_SPEED).clamp(0.5, 2.0),
+            _ => 1.0,
+        }
+    }
+}
+
+/// The character model and, once it has loaded, its animation graph.
+#[derive(Resource, Default)]
+pub struct CharacterAnimations {
+    gltf: Handle<Gltf>,
+    scene: Handle<Scene>,
+    graph: Option<CharacterGraph>,
+    load_failed: bool,
+}
+
+struct CharacterGraph {
+    handle: Handle<AnimationGraph>,
+    locomotion: [AnimationNodeIndex; 5],
+    aim: AnimationNodeIndex,
+    shoot: AnimationNodeIndex,
+    /// Bones are sorted into mask groups from the first rig that spawns.
+    masked: bool,
+}
+
+impl CharacterGraph {
+    fn locomotion_node(&self, state: LocomotionState) -> AnimationNodeIndex {
+        self.locomotion[state as usize]
+    }
+}
+
+/// Animation state of a replicated character.
+#[derive(Component, Debug, Default)]
+pub struct CharacterAnimator {
+    pub locomotion: LocomotionState,
+    /// The model's `AnimationPlayer`, once its scene has spawned.
+    player: Option<Entity>,
+    shoot_secs_left: f32,
+    last_gun_state: Option<(u32, f32)>,
+}
+
+impl CharacterAnimator {
+    /// Whether `gun` fired since the last call: less ammo, or more heat.
+    fn fired(&mut self, gun: &Gun) -> bool {
+        let heat = gun.heat.as_ref().map_or(0.0, |heat| heat.level);
+        let previous = self.last_gun_state.replace((gun.ammo_in_magazine, heat));
+        previous.is_some_and(|(ammo, previous_heat)| {
+            gun.ammo_in_magazine < ammo || heat > previous_heat
+        })
+    }
+}
+
+/// Step `weight` toward `target` by at most `step`.
+fn blend_toward(weight: f32, target: f32, step: f32) -> f32 {
+    weight + (target - weight).clamp(-step, step)
+}
+
+pub struct ClientCharacterAnimationPlugin;
+
+impl Plugin for ClientCharacterAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CharacterAnimations>();
+        app.add_systems(Startup, load_character_model);
+        app.add_systems(
+            Update,
+            (
+                build_character_graph,
+                spawn_character_models,
+                attach_animation_graphs,
+                animate_characters,
+            )
+                .chain(),
+        );
+    }
+}
+
+fn load_character_model(
+    asset_server: Res<AssetServer>,
+    mut animations: ResMut<CharacterAnimations>,
+) {
+    animations.gltf = asset_server.load(CHARACTER_MODEL_PATH);
+    animations.scene = asset_server.load(GltfAssetLabel::Scene(0).from_asset(CHARACTER_MODEL_PATH));
+}
+
+/// Locomotion clips drive the whole body. The aim and shoot clips are masked to the upper
+/// body, where they outweigh locomotion.
+fn build_character_graph(
+    asset_server: Res<AssetServer>,
+    gltfs: Res<Assets<Gltf>>,
+    mut graphs: ResMut<Assets<AnimationGraph>>,
+    mut animations: ResMut<CharacterAnimations>,
+) {
+    if animations.graph.is_some() || animations.load_failed {
+        return;
+    }
+    if let LoadState::Failed(error) = asset_server.load_state(&animations.gltf) {
+        warn!(
+            "No character model at {}, characters stay capsules: {}",
+            CHARACTER_MODEL_PATH, error
+        );
+        animations.load_failed = true;
+        return;
+    }
+    let Some(gltf) = gltfs.get(&animations.gltf) else {
+        return;
+    };
+
+    let clip = |name: &str| match gltf.named_animations.get(name) {
+        Some(clip) => clip.clone(),
+        None => {
+            warn!("Character model has no {} clip", name);
+            Handle::default()
+        }
+    };
+    let upper_body_only: AnimationMask = 1 << LOWER_BODY_GROUP;
+
+    let mut graph = AnimationGraph::new();
+    let root = graph.root;
+    let locomotion =
+        ["Idle", "Walk", "Run", "Jump", "Fall"].map(|name| graph.add_clip(clip(name), 1.0, root));
+    let aim = graph.add_clip_with_mask(clip("Aim"), upper_body_only, 1.0, root);
+    let shoot = graph.add_clip_with_mask(clip("Shoot"), upper_body_only, 1.0, root);
+
+    animations.graph = Some(CharacterGraph {
+        handle: graphs.add(graph),
+        locomotion,
+        aim,
+        shoot,
+        masked: false,
+    });
+}
+
+fn spawn_character_models(
+    mut commands: Commands,
+    animations: Res<CharacterAnimations>,
+    characters: Query<
+        Entity,
+        (
+            With<CharacterMarker>,
+            With<Interpolated>,
+            Without<CharacterAnimator>,
+        ),
+    >,
+) {
+    if animations.graph.is_none() {
+        return;
+    }
+    for character in characters.iter() {
+        // glTF models face +Z, characters look down -Z.
+        let model = commands
+            .spawn((
+                Name::new("CharacterModel"),
+                SceneRoot(animations.scene.clone()),
+                Transform::from_translation(MODEL_OFFSET)
+                    .with_rotation(Quat::from_rotation_y(std::f32::consts::PI)),
+            ))
+            .id();
+        commands
+            .entity(character)
+            .add_child(model)
+            .insert(CharacterAnimator::default());
+    }
+}
+
+/// Hook each spawned model's `AnimationPlayer` to the shared graph, start every clip and
+/// hide the capsule it replaces.
+fn attach_animation_graphs(
+    mut commands: Commands,
+    mut animations: ResMut<CharacterAnimations>,
+    mut graphs: ResMut<Assets<AnimationGraph>>,
+    mut players: Query<(Entity, &mut AnimationPlayer), Without<AnimationGraphHandle>>,
+    mut animators: Query<&mut CharacterAnimator>,
+    parents: Query<&ChildOf>,
+    children: Query<&Children>,
+    names: Query<&Name>,
+) {
+    let Some(graph) = animations.graph.as_mut() else {
+        return;
+    };
+    for (player_entity, mut player) in players.iter_mut() {
+        let Some(character) = parents
+            .iter_ancestors(player_entity)
+            .find(|&ancestor| animators.contains(ancestor))
+        else {
+            continue;
+        };
+
+        if !graph.masked
+            && let Some(animation_graph) = graphs.get_mut(&graph.handle)
+        {
+            assign_mask_groups(animation_graph, player_entity, &children, &names);
+            graph.masked = true;
+        }
+
+        for node in graph.locomotion {
+            player.play(node).repeat().set_weight(0.0);
+        }
+        player
+            .play(graph.locomotion_node(LocomotionState::Idle))
+            .set_weight(1.0);
+        player.play(graph.aim).repeat().set_weight(0.0);
+        player.play(graph.shoot).set_weight(0.0);
+        commands
+            .entity(player_entity)
+            .insert(AnimationGraphHandle(graph.handle.clone()));
+
+        if let Ok(mut animator) = animators.get_mut(character) {
+            animator.player = Some(player_entity);
+        }
+        commands
+            .entity(character)
+            .remove::<MeshMaterial3d<StandardMaterial>>();
+    }
+}
+
+/// Put [`UPPER_BODY_ROOT_BONE`] and its descendants in the upper body group, every other
+/// node of the rig in the lower body group. Target ids are the name paths from the
+/// `AnimationPlayer` down, like the glTF loader builds them.
+fn assign_mask_groups(
+    graph: &mut AnimationGraph,
+    player: Entity,
+    children: &Query<&Children>,
+    names: &Query<&Name>,
+) {
+    let Ok(root_name) = names.get(player) else {
+        return;
+    };
+    let mut stack = vec![(player, vec![root_name.clone()], false)];
+    while let Some((entity, path, upper_body)) = stack.pop() {
+        let group = if upper_body {
+            UPPER_BODY_GROUP
+        } else {
+            LOWER_BODY_GROUP
+        };
+        graph.add_target_to_mask_group(AnimationTargetId::from_names(path.iter()), group);
+
+        let Ok(entity_children) = children.get(entity) else {
+            continue;
+        };
+        for &child in entity_children.iter() {
+            let Ok(name) = names.get(child) else {
+                continue;
+            };
+            let mut child_path = path.clone();
+            child_path.push(name.clone());
+            stack.push((
+                child,
+                child_path,
+                upper_body || name.as_str() == UPPER_BODY_ROOT_BONE,
+            ));
+        }
+    }
+}
+
+/// Cross-fade locomotion clips toward the current state and layer the aim or shoot pose
+/// over the upper body of armed characters.
+fn animate_characters(
+    time: Res<Time>,
+    animations: Res<CharacterAnimations>,
+    mut characters: Query<(
+        &mut CharacterAnimator,
+        &LinearVelocity,
+        Option<&GroundState>,
+        Option<&Gun>,
+    )>,
+    mut players: Query<&mut AnimationPlayer>,
+) {
+    let Some(graph) = animations.graph.as_ref() else {
+        return;
+    };
+    let delta_secs = time.delta_secs();
+    let blend_step = (delta_secs / LOCOMOTION_BLEND_SECS).min(1.0);
+
+    for (mut animator, velocity, ground, gun) in characters.iter_mut() {
+        let Some(mut player) = animator
+            .player
+            .and_then(|player| players.get_mut(player).ok())
+        else {
+            continue;
+        };
+
+        let grounded = ground.is_none_or(|ground| ground.is_grounded);
+        let state = LocomotionState::from_motion(velocity.0, grounded);
+        if state != animator.locomotion && state == LocomotionState::Jump {
+            player.play(graph.locomotion_node(state)).replay();
+        }
+        animator.locomotion = state;
+        for candidate in LocomotionState::ALL {
+            let Some(active) = player.animation_mut(graph.locomotion_node(candidate)) else {
+                continue;
+            };
+            let target = if candidate == state { 1.0 } else { 0.0 };
+            active.set_weight(blend_toward(active.weight(), target, blend_step));
+            if candidate == state {
+                active.set_speed(state.clip_speed(velocity.0));
+            }
+        }
+
+        if gun.is_some_and(|gun| animator.fired(gun)) {
+            animator.shoot_secs_left = SHOOT_POSE_SECS;
+            player.play(graph.shoot).replay();
+        }
+        animator.shoot_secs_left = (animator.shoot_secs_left - delta_secs).max(0.0);
+        let upper_body_weight = if gun.is_some() {
+            UPPER_BODY_WEIGHT
+        } else {
+            0.0
+        };
+        let shooting = animator.shoot_secs_left > 0.0;
+        for (node, on) in [(graph.aim, !shooting), (graph.shoot, shooting)] {
+            if let Some(active) = player.animation_mut(node) {
+                active.set_weight(if on { upper_body_weight } else { 0.0 });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LocomotionState, RUN_THRESHOLD_SPEED, blend_toward};
+    use bevy::prelude::Vec3;
+
+    #[test]
+    fn locomotion_follows_ground_speed_and_vertical_motion() {
+        let walking = Vec3::new(RUN_THRESHOLD_SPEED * 0.5, 0.0, 0.0);
+        let running = Vec3::new(0.0, 0.0, RUN_THRESHOLD_SPEED * 1.5);
+        assert_eq!(
+            LocomotionState::from_motion(Vec3::new(0.1, 0.0, 0.1), true),
+            LocomotionState::Idle
+        );
+        assert_eq!(
+            LocomotionState::from_motion(walking, true),
+            LocomotionState::Walk
+        );
+        assert_eq!(
+            LocomotionState::from_motion(running, true),
+            LocomotionState::Run
+        );
+        assert_eq!(
+            LocomotionState::from_motion(running.with_y(5.0), false),
+            LocomotionState::Jump
+        );
+        assert_eq!(
+            LocomotionState::from_motion(walking.with_y(-2.0), false),
+            LocomotionState::Fall
+        );
+
+        assert!(LocomotionState::Walk.clip_speed(walking) > 1.0);
+        assert_eq!(LocomotionState::Idle.clip_speed(walking), 1.0);
+        assert_eq!(blend_toward(0.25, 1.0, 0.5), 0.75);
+        assert_eq!(blend_toward(0.25, 0.0, 0.5), 0.0);
+    }
+}
@@ -0,0 +1,242 @@
+use avian3d::prelude::{Position, Rotation};
+use bevy::app::{RunFixedMainLoop, RunFixedMainLoopSystems};
+use bevy::prelude::{
+    App, ButtonInput, Color, Commands, Component, Entity, First, Gizmos, IntoScheduleConfigs,
+    KeyCode, Plugin, PostUpdate, Quat, Query, Res, ResMut, Resource, Time, Transform,
+    TransformSystems, Update, Vec3, With, Without,
+};
+use lightyear::prelude::Predicted;
+
+/// Offsets smaller than this are dropped instead of decayed forever.
+const MIN_OFFSET: f32 = 1e-4;
+/// Correction size drawn fully red by the debug view.
+const DEBUG_MAX_ERROR: f32 = 1.0;
+
+/// How predicted entities recover from rollback corrections. Instead of snapping to the
+/// corrected state, the rendered transform keeps where it was and blends toward the physics
+/// state, each error shrinking to 1/e of its size every window.
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct CorrectionSmoothingSettings {
+    pub enabled: bool,
+    pub position_window_secs: f32,
+    pub rotation_window_secs: f32,
+    /// Corrections farther than this (teleports, respawns) snap.
+    pub max_position_error: f32,
+}
+
+impl Default for CorrectionSmoothingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            position_window_secs: 0.1,
+            rotation_window_secs: 0.08,
+            max_position_error: 3.0,
+        }
+    }
+}
+
+/// Draws each predicted entity's remaining correction while on, toggled with F6.
+#[derive(Resource, Debug, Default)]
+pub struct CorrectionDebugState {
+    pub visible: bool,
+}
+
+/// Rendered offset of a predicted entity from its physics state, left by corrections not
+/// yet blended out. Rendered rotation is `rotation * Rotation`.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct CorrectionOffset {
+    pub position: Vec3,
+    pub rotation: Quat,
+    /// Physics state at the start of the frame, before any rollback.
+    before_rollback: Option<(Vec3, Quat)>,
+}
+
+impl Default for CorrectionOffset {
+    fn default() -> Self {
+        Self {
+            position: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+            before_rollback: None,
+        }
+    }
+}
+
+impl CorrectionOffset {
+    /// Take in the jump from `before` to `after` so the rendered transform does not move.
+    pub fn absorb(
+        &mut self,
+        before: (Vec3, Quat),
+        after: (Vec3, Quat),
+        settings: &CorrectionSmoothingSettings,
+    ) {
+        let error = before.0 - after.0;
+        if !settings.enabled || (self.position + error).length() > settings.max_position_error {
+            *self = Self::default();
+            return;
+        }
+        self.position += error;
+        self.rotation = (self.rotation * before.1 * after.1.inverse()).normalize();
+    }
+
+    /// Blend the offset out over `delta_secs`.
+    pub fn decay(&mut self, delta_secs: f32, settings: &CorrectionSmoothingSettings) {
+        self.position *= decay_factor(delta_secs, settings.position_window_secs);
+        self.rotation = Quat::IDENTITY.slerp(
+            self.rotation,
+            decay_factor(delta_secs, settings.rotation_window_secs),
+        );
+        if self.position.length() < MIN_OFFSET {
+            self.position = Vec3::ZERO;
+        }
+        if self.rotation.angle_between(Quat::IDENTITY) < MIN_OFFSET {
+            self.rotation = Quat::IDENTITY;
+        }
+    }
+}
+
+/// Share of an error left after `delta_secs` of exponential blending.
+fn decay_factor(delta_secs: f32, window_secs: f32) -> f32 {
+    if window_secs <= 0.0 {
+        0.0
+    } else {
+        (-delta_secs / window_secs).exp()
+    }
+}
+
+/// Rollbacks happen in `PreUpdate`: the physics state before and after it tells the size
+/// of the correction, which is then rendered away gradually.
+pub struct CorrectionSmoothingPlugin;
+
+impl Plugin for CorrectionSmoothingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CorrectionSmoothingSettings>();
+        app.init_resource::<CorrectionDebugState>();
+        app.add_systems(
+            First,
+            (attach_correction_offsets, record_pre_rollback_state).chain(),
+        );
+        app.add_systems(
+            RunFixedMainLoop,
+            absorb_corrections.in_set(RunFixedMainLoopSystems::BeforeFixedMainLoop),
+        );
+        app.add_systems(
+            PostUpdate,
+            apply_correction_offsets.before(TransformSystems::Propagate),
+        );
+        app.add_systems(
+            Update,
+            (toggle_correction_debug, draw_correction_debug).chain(),
+        );
+    }
+}
+
+fn attach_correction_offsets(
+    mut commands: Commands,
+    predicted: Query<Entity, (With<Predicted>, With<Position>, Without<CorrectionOffset>)>,
+) {
+    for entity in predicted.iter() {
+        commands.entity(entity).insert(CorrectionOffset::default());
+    }
+}
+
+fn record_pre_rollback_state(mut entities: Query<(&Position, &Rotation, &mut CorrectionOffset)>) {
+    for (position, rotation, mut offset) in entities.iter_mut() {
+        offset.before_rollback = Some((position.0, rotation.0));
+    }
+}
+
+fn absorb_corrections(
+    settings: Res<CorrectionSmoothingSettings>,
+    mut entities: Query<(&Position, &Rotation, &mut CorrectionOffset)>,
+) {
+    for (position, rotation, mut offset) in entities.iter_mut() {
+        let Some(before) = offset.before_rollback.take() else {
+            continue;
+        };
+        let after = (position.0, rotation.0);
+        if before != after {
+            offset.absorb(before, after, &settings);
+        }
+    }
+}
+
+fn apply_correction_offsets(
+    time: Res<Time>,
+    settings: Res<CorrectionSmoothingSettings>,
+    mut entities: Query<(&Position, &Rotation, &mut CorrectionOffset, &mut Transform)>,
+) {
+    for (position, rotation, mut offset, mut transform) in entities.iter_mut() {
+        offset.decay(time.delta_secs(), &settings);
+        transform.translation = position.0 + offset.position;
+        transform.rotation = offset.rotation * rotation.0;
+    }
+}
+
+fn toggle_correction_debug(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut state: ResMut<CorrectionDebugState>,
+) {
+    if keys.just_pressed(KeyCode::F6) {
+        state.visible = !state.visible;
+    }
+}
+
+/// A line from the physics state to the rendered one, green for small corrections through
+/// red for large ones.
+fn draw_correction_debug(
+    state: Res<CorrectionDebugState>,
+    entities: Query<(&Position, &CorrectionOffset)>,
+    mut gizmos: Gizmos,
+) {
+    if !state.visible {
+        return;
+    }
+    for (position, offset) in entities.iter() {
+        let error = offset.position.length();
+        if error == 0.0 {
+            continue;
+        }
+        let severity = (error / DEBUG_MAX_ERROR).min(1.0);
+        let color = Color::srgb(severity, 1.0 - severity, 0.0);
+        gizmos.line(position.0, position.0 + offset.position, color);
+        gizmos.sphere(position.0 + offset.position, 0.05 + error * 0.1, color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CorrectionOffset, CorrectionSmoothingSettings};
+    use bevy::prelude::{Quat, Vec3};
+
+    #[test]
+    fn corrections_keep_the_rendered_state_then_blend_out() {
+        let settings = CorrectionSmoothingSettings::default();
+        let before = (Vec3::new(1.0, 0.0, 0.0), Quat::from_rotation_y(0.2));
+        let after = (Vec3::new(1.5, 0.0, 0.0), Quat::IDENTITY);
+        let mut offset = CorrectionOffset::default();
+        offset.absorb(before, after, &settings);
+
+        assert!((after.0 + offset.position).distance(before.0) < 1e-5);
+        assert!((offset.rotation * after.1).angle_between(before.1) < 1e-4);
+
+        offset.decay(settings.position_window_secs, &settings);
+        assert!((offset.position.length() - 0.5 / std::f32::consts::E).abs() < 1e-4);
+        for _ in 0..100 {
+            offset.decay(0.05, &settings);
+        }
+        assert_eq!(offset.position, Vec3::ZERO);
+        assert_eq!(offset.rotation, Quat::IDENTITY);
+    }
+
+    #[test]
+    fn teleports_snap_instead_of_sliding() {
+        let settings = CorrectionSmoothingSettings::default();
+        let mut offset = CorrectionOffset::default();
+        offset.absorb(
+            (Vec3::ZERO, Quat::IDENTITY),
+            (Vec3::new(20.0, 0.0, 0.0), Quat::IDENTITY),
+            &settings,
+        );
+        assert_eq!(offset, CorrectionOffset::default());
+    }
+}
@@ -0,0 +1,278 @@
+pub mod animation;
+pub mod correction;
+pub mod nameplates;
+pub mod provisional;
+
+use crate::entities::correction::CorrectionSmoothingPlugin;
+use crate::entities::nameplates::NameplatePlugin;
+use crate::entities::provisional::ProvisionalPlayerPlugin;
+use crate::inputs::input_map::get_player_input_map;
+
+use avian3d::prelude::{Collider, RigidBody, Rotation};
+use bevy::app::{PostUpdate, Update};
+use bevy::prelude::{
+    AlphaMode, App, Assets, Capsule3d, Color, Commands, Component, Cuboid, Entity,
+    IntoScheduleConfigs, Mesh, Mesh3d, MeshMaterial3d, Plugin, Query, Res, ResMut,
+    StandardMaterial, Time, Transform, TransformSystems, Vec2, With, Without, default,
+};
+use leafwing_input_manager::prelude::ActionState;
+
+use shared::components::destructible::{DESTRUCTIBLE_COLOR, DebrisPiece, Destructible};
+use shared::components::team::Team;
+use shared::components::world_items::{LOOT_SIZE, WorldItem, WorldItemKind};
+use shared::entities::{NpcPhysicsBundle, PlayerPhysicsBundle};
+use shared::level::interactive::{BUTTON_COLOR, BUTTON_SIZE, DOOR_COLOR, Door, LevelButton};
+use shared::level::platforms::{MovingPlatform, PLATFORM_COLOR, PLATFORM_SIZE};
+
+use shared::inputs::input::PlayerAction;
+
+use crate::LocalPlayerId;
+use crate::settings::GameSettings;
+use lightyear::prelude::{Controlled, Interpolated, Predicted};
+use shared::inputs::input::{PLAYER_CAPSULE_HEIGHT, PLAYER_CAPSULE_RADIUS};
+use shared::inputs::look::{
+    LOOK_EXTRAPOLATION_HORIZON_SECS, LookVelocity, apply_look_offset, smooth_look_offset,
+};
+
+use shared::protocol::{CharacterMarker, PlayerColor, PlayerId};
+
+pub struct ClientEntitiesPlugin;
+
+impl Plugin for ClientEntitiesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(CorrectionSmoothingPlugin);
+        app.add_plugins(NameplatePlugin);
+        app.add_plugins(ProvisionalPlayerPlugin);
+        app.add_systems(Update, handle_interpolated_npcs_setup);
+        app.add_systems(Update, handle_local_player_setup);
+        app.add_systems(Update, handle_interpolated_players_setup);
+        app.add_systems(
+            Update,
+            (
+                handle_destructible_setup,
+                handle_platform_setup,
+                handle_door_setup,
+                handle_button_setup,
+                handle_debris_setup,
+                handle_world_item_setup,
+            ),
+        );
+        app.add_systems(
+            PostUpdate,
+            extrapolate_remote_player_look.before(TransformSystems::Propagate),
+        );
+    }
+}
+
+/// Visual-only yaw/pitch offset extrapolated ahead of a remote player's interpolated
+/// rotation.
+#[derive(Component, Default)]
+struct RemoteLookOffset(Vec2);
+
+fn handle_local_player_setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    player_query: Query<
+        (Entity, &PlayerColor, &PlayerId, Option<&Team>),
+        (
+            With<Predicted>,
+            With<Controlled>,
+            With<PlayerId>,
+            Without<Mesh3d>,
+        ),
+    >,
+    local_player_id: Res<LocalPlayerId>,
+    settings: Option<Res<GameSettings>>,
+) {
+    for (entity, color, player_id, team) in player_query.iter() {
+        if player_id.0.to_bits() == local_player_id.0 {
+            let input_map = settings
+                .as_ref()
+                .map_or_else(get_player_input_map, |settings| settings.input_map());
+            let mut action_state = ActionState::<PlayerAction>::default();
+            action_state.enable();
+            commands.entity(entity).insert((
+                Mesh3d(meshes.add(Capsule3d::new(PLAYER_CAPSULE_RADIUS, PLAYER_CAPSULE_HEIGHT))),
+                MeshMaterial3d(materials.add(player_material_color(color, team))),
+                input_map,
+                action_state,
+                PlayerPhysicsBundle::default(),
+            ));
+        }
+    }
+}
+
+fn handle_interpolated_players_setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    player_query: Query<
+        (Entity, &PlayerColor, Option<&Team>),
+        (With<Interpolated>, With<CharacterMarker>, Without<Mesh3d>),
+    >,
+) {
+    for (entity, color, team) in player_query.iter() {
+        commands.entity(entity).insert((
+            Mesh3d(meshes.add(Capsule3d::new(PLAYER_CAPSULE_RADIUS, PLAYER_CAPSULE_HEIGHT))),
+            MeshMaterial3d(materials.add(player_material_color(color, team))),
+            PlayerPhysicsBundle::default(),
+            RemoteLookOffset::default(),
+        ));
+    }
+}
+
+/// Players on a team wear its color so teammates are easy to tell apart from enemies.
+fn player_material_color(color: &PlayerColor, team: Option<&Team>) -> Color {
+    team.map(Team::color).unwrap_or(color.0)
+}
+
+/// Rotate remote player models slightly ahead of their interpolated aim using the
+/// replicated look velocity. Only the rendered `Transform` is touched; `Rotation` stays the
+/// interpolated value, and the offset eases back to zero when the player stops turning.
+fn extrapolate_remote_player_look(
+    time: Res<Time>,
+    mut player_query: Query<
+        (
+            &Rotation,
+            &LookVelocity,
+            &mut RemoteLookOffset,
+            &mut Transform,
+        ),
+        (With<Interpolated>, With<PlayerId>),
+    >,
+) {
+    let delta_secs = time.delta_secs();
+    for (rotation, look_velocity, mut offset, mut transform) in player_query.iter_mut() {
+        let target = look_velocity.extrapolated_offset(LOOK_EXTRAPOLATION_HORIZON_SECS);
+        offset.0 = smooth_look_offset(offset.0, target, delta_secs);
+        transform.rotation = apply_look_offset(rotation.0, offset.0);
+    }
+}
+
+fn handle_interpolated_npcs_setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    npc_query: Query<Entity, (With<CharacterMarker>, Without<PlayerId>, Without<Mesh3d>)>,
+) {
+    for entity in npc_query.iter() {
+        let color = Color::srgb(0.5, 0.5, 0.5);
+        commands.entity(entity).insert((
+            Mesh3d(meshes.add(Capsule3d::new(PLAYER_CAPSULE_RADIUS, PLAYER_CAPSULE_HEIGHT))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: color,
+                unlit: false,
+                ..default()
+            })),
+            NpcPhysicsBundle::default(),
+        ));
+    }
+}
+
+fn handle_destructible_setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    prop_query: Query<(Entity, &Destructible), Without<Mesh3d>>,
+) {
+    for (entity, destructible) in prop_query.iter() {
+        let size = destructible.size;
+        commands.entity(entity).insert((
+            Mesh3d(meshes.add(Cuboid::from_size(size))),
+            MeshMaterial3d(materials.add(DESTRUCTIBLE_COLOR)),
+            RigidBody::Static,
+            Collider::cuboid(size.x, size.y, size.z),
+        ));
+    }
+}
+
+/// Platforms are predicted so the local player can stand on them; the shared platform
+/// systems move them from their replicated phase.
+fn handle_platform_setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    platform_query: Query<Entity, (With<MovingPlatform>, Without<Mesh3d>)>,
+) {
+    for entity in platform_query.iter() {
+        commands.entity(entity).insert((
+            Mesh3d(meshes.add(Cuboid::from_size(PLATFORM_SIZE))),
+            MeshMaterial3d(materials.add(PLATFORM_COLOR)),
+            RigidBody::Kinematic,
+            Collider::cuboid(PLATFORM_SIZE.x, PLATFORM_SIZE.y, PLATFORM_SIZE.z),
+        ));
+    }
+}
+
+/// Doors are predicted like platforms, so the local player's `Use` opens them at once.
+fn handle_door_setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    door_query: Query<(Entity, &Door), Without<Mesh3d>>,
+) {
+    for (entity, door) in door_query.iter() {
+        let size = door.size;
+        commands.entity(entity).insert((
+            Mesh3d(meshes.add(Cuboid::from_size(size))),
+            MeshMaterial3d(materials.add(DOOR_COLOR)),
+            RigidBody::Kinematic,
+            Collider::cuboid(size.x, size.y, size.z),
+        ));
+    }
+}
+
+fn handle_button_setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    button_query: Query<Entity, (With<LevelButton>, Without<Mesh3d>)>,
+) {
+    for entity in button_query.iter() {
+        commands.entity(entity).insert((
+            Mesh3d(meshes.add(Cuboid::from_size(BUTTON_SIZE))),
+            MeshMaterial3d(materials.add(BUTTON_COLOR)),
+        ));
+    }
+}
+
+/// Server-simulated debris only needs a mesh, its motion comes from replication.
+fn handle_debris_setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    debris_query: Query<(Entity, &DebrisPiece), Without<Mesh3d>>,
+) {
+    for (entity, piece) in debris_query.iter() {
+        commands.entity(entity).insert((
+            Mesh3d(meshes.add(Cuboid::from_length(piece.size))),
+            MeshMaterial3d(materials.add(DESTRUCTIBLE_COLOR)),
+        ));
+    }
+}
+
+/// Corpses and dropped weapons get their own material so they can fade out one by one.
+fn handle_world_item_setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    item_query: Query<(Entity, &WorldItem), Without<Mesh3d>>,
+) {
+    for (entity, item) in item_query.iter() {
+        let mesh = match item.kind {
+            WorldItemKind::Corpse => {
+                meshes.add(Capsule3d::new(PLAYER_CAPSULE_RADIUS, PLAYER_CAPSULE_HEIGHT))
+            }
+            WorldItemKind::Loot => meshes.add(Cuboid::from_size(LOOT_SIZE)),
+        };
+        commands.entity(entity).insert((
+            Mesh3d(mesh),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: item.color,
+                alpha_mode: AlphaMode::Blend,
+                ..default()
+            })),
+        ));
+    }
+}
@@ -0,0 +1,254 @@
+use avian3d::prelude::{Position, SpatialQueryFilter, SpatialQueryPipeline};
+use bevy::prelude::{
+    AlignItems, App, BackgroundColor, Camera, Color, Commands, Component, ComputedNode, Dir3,
+    Entity, FlexDirection, GlobalTransform, IntoScheduleConfigs, Name, Node, Plugin, PositionType,
+    Query, Res, Text, TextColor, TextFont, Update, Val, Vec3, Visibility, With, Without, default,
+};
+use lightyear::prelude::Interpolated;
+use shared::components::health::Health;
+use shared::components::team::Team;
+use shared::protocol::{CharacterMarker, PlayerId};
+
+use crate::Headless;
+use crate::camera::PlayerCamera;
+
+/// Nameplates float this far above the character's origin.
+const NAMEPLATE_HEIGHT: f32 = 1.6;
+/// Nameplates start fading out at this distance...
+const FADE_START_DISTANCE: f32 = 20.0;
+/// ...and are gone at this one.
+const FADE_END_DISTANCE: f32 = 40.0;
+const BAR_WIDTH_PX: f32 = 56.0;
+const BAR_HEIGHT_PX: f32 = 5.0;
+const BAR_TRACK_COLOR: Color = Color::srgba(0.05, 0.05, 0.05, 0.7);
+
+/// Name and a small health bar over every remote player. They are UI nodes placed at the
+/// player's projected position, so they always face the camera; players behind level
+/// geometry lose theirs, and they fade out with distance.
+pub struct NameplatePlugin;
+
+impl Plugin for NameplatePlugin {
+    fn build(&self, app: &mut App) {
+        fn is_not_headless(headless: Option<Res<Headless>>) -> bool {
+            !headless.map(|h| h.0).unwrap_or(false)
+        }
+
+        app.add_systems(
+            Update,
+            (sync_nameplates, place_nameplates, style_nameplates)
+                .chain()
+                .run_if(is_not_headless),
+        );
+    }
+}
+
+/// Root of a player's nameplate, with its parts.
+#[derive(Component)]
+struct Nameplate {
+    target: Entity,
+    label: Entity,
+    track: Entity,
+    fill: Entity,
+}
+
+/// Opacity the nameplate is drawn with this frame.
+#[derive(Component, Default)]
+struct NameplateFade(f32);
+
+type RemotePlayerFilter = (With<Interpolated>, With<CharacterMarker>, With<PlayerId>);
+
+/// Opacity of a nameplate `distance` away from the camera.
+pub fn nameplate_alpha(distance: f32) -> f32 {
+    1.0 - ((distance - FADE_START_DISTANCE) / (FADE_END_DISTANCE - FADE_START_DISTANCE))
+        .clamp(0.0, 1.0)
+}
+
+/// Health bar fill color, from green when healthy to red when nearly dead.
+pub fn health_bar_color(fraction: f32) -> Color {
+    let fraction = fraction.clamp(0.0, 1.0);
+    Color::srgb(
+        0.9 * (1.0 - fraction) + 0.2 * fraction,
+        0.2 + 0.65 * fraction,
+        0.2,
+    )
+}
+
+/// One nameplate per remote player.
+fn sync_nameplates(
+    mut commands: Commands,
+    nameplates: Query<(Entity, &Nameplate)>,
+    players: Query<Entity, RemotePlayerFilter>,
+) {
+    for (root, nameplate) in nameplates.iter() {
+        if !players.contains(nameplate.target) {
+            commands.entity(root).despawn();
+        }
+    }
+
+    for player in players.iter() {
+        if nameplates
+            .iter()
+            .any(|(_, nameplate)| nameplate.target == player)
+        {
+            continue;
+        }
+        let label = commands
+            .spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ))
+            .id();
+        let fill = commands
+            .spawn((
+                Node {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    ..default()
+                },
+                BackgroundColor(health_bar_color(1.0)),
+            ))
+            .id();
+        let track = commands
+            .spawn((
+                Node {
+                    width: Val::Px(BAR_WIDTH_PX),
+                    height: Val::Px(BAR_HEIGHT_PX),
+                    ..default()
+                },
+                BackgroundColor(BAR_TRACK_COLOR),
+            ))
+            .add_child(fill)
+            .id();
+        commands
+            .spawn((
+                Name::new("Nameplate"),
+                Nameplate {
+                    target: player,
+                    label,
+                    track,
+                    fill,
+                },
+                NameplateFade::default(),
+                Node {
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                Visibility::Hidden,
+            ))
+            .add_children(&[label, track]);
+    }
+}
+
+/// Puts each nameplate over its player, hiding the ones behind the camera, out of range,
+/// dead or behind something solid.
+fn place_nameplates(
+    camera: Query<(&Camera, &GlobalTransform), With<PlayerCamera>>,
+    spatial_query: Res<SpatialQueryPipeline>,
+    characters: Query<Entity, With<CharacterMarker>>,
+    players: Query<(&Position, Option<&Health>), RemotePlayerFilter>,
+    mut nameplates: Query<(
+        &Nameplate,
+        &mut NameplateFade,
+        &mut Node,
+        &ComputedNode,
+        &mut Visibility,
+    )>,
+) {
+    let Ok((camera, camera_transform)) = camera.single() else {
+        return;
+    };
+    let eye = camera_transform.translation();
+    // Only the level and props block the view, not the characters themselves.
+    let filter = SpatialQueryFilter::default().with_excluded_entities(characters.iter());
+
+    for (nameplate, mut fade, mut node, computed, mut visibility) in nameplates.iter_mut() {
+        let Ok((position, health)) = players.get(nameplate.target) else {
+            continue;
+        };
+        let anchor = position.0 + Vec3::Y * NAMEPLATE_HEIGHT;
+        let distance = eye.distance(anchor);
+        let alpha = nameplate_alpha(distance);
+        let occluded = || {
+            Dir3::new(anchor - eye).is_ok_and(|direction| {
+                spatial_query
+                    .cast_ray(eye, direction, distance, true, &filter)
+                    .is_some()
+            })
+        };
+        let screen = camera.world_to_viewport(camera_transform, anchor).ok();
+        let Some(screen) = screen
+            .filter(|_| alpha > 0.0 && !health.is_some_and(|health| health.is_dead) && !occluded())
+        else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        // Centred over the player, with last frame's layout of the nameplate.
+        let size = computed.size() * computed.inverse_scale_factor();
+        node.left = Val::Px(screen.x - size.x * 0.5);
+        node.top = Val::Px(screen.y - size.y);
+        fade.0 = alpha;
+        *visibility = Visibility::Visible;
+    }
+}
+
+/// Name, team color and health of each visible nameplate.
+fn style_nameplates(
+    nameplates: Query<(&Nameplate, &NameplateFade, &Visibility)>,
+    players: Query<(Option<&Name>, Option<&Team>, Option<&Health>), RemotePlayerFilter>,
+    mut labels: Query<(&mut Text, &mut TextColor)>,
+    mut bars: Query<(&mut BackgroundColor, &mut Node), Without<Nameplate>>,
+) {
+    for (nameplate, fade, visibility) in nameplates.iter() {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+        let Ok((name, team, health)) = players.get(nameplate.target) else {
+            continue;
+        };
+
+        if let Ok((mut text, mut color)) = labels.get_mut(nameplate.label) {
+            let label = name.map_or("", |name| name.as_str());
+            if text.0 != label {
+                text.0 = label.to_string();
+            }
+            color.0 = team.map_or(Color::WHITE, Team::color).with_alpha(fade.0);
+        }
+
+        if let Ok((mut track_color, _)) = bars.get_mut(nameplate.track) {
+            track_color.0 = BAR_TRACK_COLOR.with_alpha(BAR_TRACK_COLOR.alpha() * fade.0);
+        }
+        let fraction = health.map_or(1.0, |health| {
+            (health.current / health.max.max(f32::EPSILON)).clamp(0.0, 1.0)
+        });
+        if let Ok((mut fill_color, mut fill_node)) = bars.get_mut(nameplate.fill) {
+            fill_color.0 = health_bar_color(fraction).with_alpha(fade.0);
+            fill_node.width = Val::Percent(fraction * 100.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FADE_END_DISTANCE, FADE_START_DISTANCE, health_bar_color, nameplate_alpha};
+    use bevy::prelude::Color;
+
+    #[test]
+    fn nameplates_fade_out_between_the_fade_distances() {
+        assert_eq!(nameplate_alpha(5.0), 1.0);
+        assert_eq!(nameplate_alpha(FADE_START_DISTANCE), 1.0);
+        let halfway = (FADE_START_DISTANCE + FADE_END_DISTANCE) * 0.5;
+        assert!((nameplate_alpha(halfway) - 0.5).abs() < 1e-5);
+        assert_eq!(nameplate_alpha(FADE_END_DISTANCE + 1.0), 0.0);
+
+        assert_ne!(health_bar_color(1.0), health_bar_color(0.1));
+        assert_eq!(health_bar_color(2.0), health_bar_color(1.0));
+        assert_eq!(health_bar_color(0.0), Color::srgb(0.9, 0.2, 0.2));
+    }
+}
@@ -0,0 +1,185 @@
+use avian3d::prelude::{Position, Rotation};
+use bevy::prelude::{
+    App, Assets, Capsule3d, Children, Commands, Component, Entity, IntoScheduleConfigs, Mesh,
+    Mesh3d, MeshMaterial3d, Name, OnExit, Plugin, Query, Res, ResMut, StandardMaterial, Transform,
+    Update, With, in_state,
+};
+use lightyear::prelude::{Controlled, Predicted};
+use shared::NetworkMode;
+use shared::components::profile::player_color;
+use shared::inputs::input::{PLAYER_CAPSULE_HEIGHT, PLAYER_CAPSULE_RADIUS};
+use shared::level::transition::lobby_spawn_point;
+use shared::protocol::{LobbyState, PlayerColor, PlayerId};
+
+use super::player_material_color;
+use crate::camera::{PlayerCamera, spawn_local_player_camera};
+use crate::{ClientGameState, LocalPlayerId};
+
+/// Shows the local player, with its camera, where the server is about to spawn it, so the
+/// level is not viewed from nowhere until the player entity is replicated. The spawn point
+/// comes from the lobby, like on the server. When the predicted player arrives it takes
+/// over the camera and the stand-in goes away.
+pub struct ProvisionalPlayerPlugin;
+
+impl Plugin for ProvisionalPlayerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (spawn_provisional_player, adopt_provisional_player)
+                .chain()
+                .run_if(in_state(ClientGameState::Playing)),
+        );
+        app.add_systems(
+            OnExit(ClientGameState::Playing),
+            despawn_provisional_players,
+        );
+    }
+}
+
+/// Local stand-in for the player entity the server has not replicated yet.
+#[derive(Component)]
+pub struct ProvisionalPlayer;
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_provisional_player(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    network_mode: Res<NetworkMode>,
+    local_player_id: Res<LocalPlayerId>,
+    lobby: Query<&LobbyState>,
+    players: Query<&PlayerId>,
+    provisional: Query<(), With<ProvisionalPlayer>>,
+    cameras: Query<(), With<PlayerCamera>>,
+) {
+    // In local host mode the server's player entity is in this world from the start.
+    if *network_mode == NetworkMode::Local || !provisional.is_empty() || !cameras.is_empty() {
+        return;
+    }
+    if players
+        .iter()
+        .any(|player_id| player_id.0.to_bits() == local_player_id.0)
+    {
+        return;
+    }
+    let Ok(lobby) = lobby.single() else {
+        return;
+    };
+    if !lobby.players.contains(&local_player_id.0) {
+        return;
+    }
+
+    let (position, team) = lobby_spawn_point(lobby, local_player_id.0);
+    let color = PlayerColor(player_color(&lobby.profiles, local_player_id.0));
+    let stand_in = commands
+        .spawn((
+            Name::new("ProvisionalPlayer"),
+            ProvisionalPlayer,
+            Position::new(position),
+            Rotation::default(),
+            Transform::from_translation(position),
+            Mesh3d(meshes.add(Capsule3d::new(PLAYER_CAPSULE_RADIUS, PLAYER_CAPSULE_HEIGHT))),
+            MeshMaterial3d(materials.add(player_material_color(&color, team.as_ref()))),
+        ))
+        .id();
+    spawn_local_player_camera(&mut commands, stand_in, local_player_id.0);
+}
+
+/// Hands the stand-in's camera to the predicted local player once it exists.
+fn adopt_provisional_player(
+    mut commands: Commands,
+    local_player_id: Res<LocalPlayerId>,
+    provisional: Query<(Entity, Option<&Children>), With<ProvisionalPlayer>>,
+    players: Query<(Entity, &PlayerId), (With<Predicted>, With<Controlled>)>,
+    cameras: Query<(), With<PlayerCamera>>,
+) {
+    let Some(player) = players
+        .iter()
+        .find(|(_, player_id)| player_id.0.to_bits() == local_player_id.0)
+        .map(|(entity, _)| entity)
+    else {
+        return;
+    };
+
+    for (stand_in, children) in provisional.iter() {
+        if let Some(children) = children {
+            for child in children.iter() {
+                if cameras.contains(child) {
+                    commands.entity(player).add_child(child);
+                }
+            }
+        }
+        commands.entity(stand_in).despawn();
+    }
+}
+
+fn despawn_provisional_players(
+    mut commands: Commands,
+    provisional: Query<Entity, With<ProvisionalPlayer>>,
+) {
+    for stand_in in provisional.iter() {
+        commands.entity(stand_in).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ProvisionalPlayer, ProvisionalPlayerPlugin};
+    use crate::camera::{ClientCameraPlugin, PlayerCamera};
+    use crate::{ClientGameState, Headless, LocalPlayerId};
+    use bevy::prelude::{
+        App, Assets, ChildOf, Entity, Mesh, MinimalPlugins, StandardMaterial, With,
+    };
+    use bevy::state::app::AppExtStates;
+    use lightyear::prelude::{Controlled, PeerId, Predicted};
+    use shared::NetworkMode;
+    use shared::protocol::{LobbyState, PlayerId};
+
+    #[test]
+    fn stand_in_holds_the_camera_until_the_predicted_player_arrives() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(bevy::state::app::StatesPlugin);
+        app.init_state::<ClientGameState>();
+        app.insert_state(ClientGameState::Playing);
+        app.insert_resource(Headless(true));
+        app.insert_resource(LocalPlayerId(1));
+        app.insert_resource(NetworkMode::Udp);
+        app.init_resource::<Assets<Mesh>>();
+        app.init_resource::<Assets<StandardMaterial>>();
+        app.add_plugins((ClientCameraPlugin, ProvisionalPlayerPlugin));
+
+        app.world_mut().spawn(LobbyState {
+            players: vec![1, 2],
+            host_id: 1,
+            teams: Default::default(),
+            profiles: Default::default(),
+        });
+        app.update();
+        app.update();
+
+        let stand_in = app
+            .world_mut()
+            .query_filtered::<Entity, With<ProvisionalPlayer>>()
+            .single(app.world())
+            .expect("a stand-in while the player is not replicated");
+        let camera_parent = |app: &mut App| {
+            app.world_mut()
+                .query_filtered::<&ChildOf, With<PlayerCamera>>()
+                .single(app.world())
+                .map(ChildOf::parent)
+                .expect("exactly one player camera")
+        };
+        assert_eq!(camera_parent(&mut app), stand_in);
+
+        let player = app
+            .world_mut()
+            .spawn((PlayerId(PeerId::Netcode(1)), Predicted, Controlled))
+            .id();
+        app.update();
+        app.update();
+
+        assert_eq!(camera_parent(&mut app), player);
+        assert!(app.world().get_entity(stand_in).is_err());
+    }
+}
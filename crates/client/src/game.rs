@@ -1,25 +1,144 @@
 use bevy::prelude::{
-    App, Assets, Commands, Mesh, Plugin, Query, Res, ResMut, Single, StandardMaterial, Update,
+    AlignItems, App, Assets, BackgroundColor, Color, Commands, Component, Entity, GlobalZIndex,
+    IntoScheduleConfigs, JustifyContent, Mesh, Name, Node, Plugin, Query, Res, ResMut, Resource,
+    Single, StandardMaterial, Text, TextFont, Time, Timer, TimerMode, Update, Val, With,
+    resource_exists,
 };
 use bevy::state::commands::CommandsStatesExt;
 use shared::{GymMode, NetworkMode};
 use shared::gym::setup_gym_level;
-use shared::level::generation::{LevelConfig, build_level_physics, generate_level};
+use shared::level::generation::{
+    LevelConfig, LevelGeometry, LevelGraph, build_level_physics, generate_level,
+};
+use shared::level::preload::LevelPreloader;
 use shared::level::visuals::build_level_visuals;
 
-use crate::ClientGameState;
+use crate::{ClientGameState, Headless};
 use lightyear::prelude::{Confirmed, MessageReceiver};
 
-use shared::protocol::{LevelSeed, StartLoadingGameEvent};
+use shared::protocol::{LevelSeed, LevelTransitionEvent, StartLoadingGameEvent};
+
+/// Minimum time the loading overlay stays up during a level transition.
+const LEVEL_TRANSITION_OVERLAY_SECS: f32 = 0.75;
 
 pub struct ClientGameCyclePlugin;
 
 impl Plugin for ClientGameCyclePlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Update, handle_world_creation);
+        app.add_systems(
+            Update,
+            (
+                receive_level_transition,
+                apply_level_transition.run_if(resource_exists::<PendingLevelTransition>),
+            )
+                .chain(),
+        );
     }
 }
 
+/// Level transition announced by the server. The level is rebuilt on the frame after the
+/// overlay is spawned so the overlay is on screen while the geometry is regenerated.
+#[derive(Resource)]
+struct PendingLevelTransition {
+    seed: u64,
+    built: bool,
+    overlay_timer: Timer,
+}
+
+#[derive(Component)]
+struct LevelTransitionOverlay;
+
+fn build_procedural_level(
+    mut commands: Commands,
+    meshes: ResMut<Assets<Mesh>>,
+    materials: Option<ResMut<Assets<StandardMaterial>>>,
+    level_graph: &LevelGraph,
+) {
+    build_level_physics(commands.reborrow(), level_graph);
+    build_level_visuals(commands.reborrow(), meshes, materials, level_graph);
+}
+
+fn receive_level_transition(
+    mut receiver: Single<&mut MessageReceiver<LevelTransitionEvent>>,
+    mut commands: Commands,
+    headless: Option<Res<Headless>>,
+) {
+    let Some(event) = receiver.receive().last() else {
+        return;
+    };
+
+    bevy::log::info!(
+        "🚪 Client received LevelTransitionEvent with seed: {}",
+        event.seed
+    );
+    commands.insert_resource(PendingLevelTransition {
+        seed: event.seed,
+        built: false,
+        overlay_timer: Timer::from_seconds(LEVEL_TRANSITION_OVERLAY_SECS, TimerMode::Once),
+    });
+
+    if headless.map(|h| h.0).unwrap_or(false) {
+        return;
+    }
+
+    commands
+        .spawn((
+            Name::new("LevelTransitionOverlay"),
+            LevelTransitionOverlay,
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..Default::default()
+            },
+            BackgroundColor(Color::BLACK),
+            GlobalZIndex(i32::MAX),
+        ))
+        .with_child((
+            Text::new("Loading next level..."),
+            TextFont {
+                font_size: 36.0,
+                ..Default::default()
+            },
+        ));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn apply_level_transition(
+    mut commands: Commands,
+    mut pending: ResMut<PendingLevelTransition>,
+    time: Res<Time>,
+    network_mode: Res<NetworkMode>,
+    geometry_query: Query<Entity, With<LevelGeometry>>,
+    overlay_query: Query<Entity, With<LevelTransitionOverlay>>,
+    meshes: ResMut<Assets<Mesh>>,
+    materials: Option<ResMut<Assets<StandardMaterial>>>,
+) {
+    if !pending.built {
+        // In local host mode the server world is shared and already holds the new level.
+        if *network_mode != NetworkMode::Local {
+            for entity in geometry_query.iter() {
+                commands.entity(entity).despawn();
+            }
+            let level_graph = generate_level(LevelConfig::for_seed(pending.seed));
+            build_procedural_level(commands.reborrow(), meshes, materials, &level_graph);
+        }
+        pending.built = true;
+        bevy::log::info!("✅ Client level transition loaded seed: {}", pending.seed);
+    }
+
+    if !pending.overlay_timer.tick(time.delta()).is_finished() {
+        return;
+    }
+
+    for entity in overlay_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    commands.remove_resource::<PendingLevelTransition>();
+}
+
 #[allow(clippy::too_many_arguments)]
 fn handle_world_creation(
     mut receiver: Single<&mut MessageReceiver<StartLoadingGameEvent>>,
@@ -30,6 +149,7 @@ fn handle_world_creation(
     confirmed_level_seed_query: Query<&Confirmed<LevelSeed>>,
     meshes: ResMut<Assets<Mesh>>,
     materials: Option<ResMut<Assets<StandardMaterial>>>,
+    preloader: Option<ResMut<LevelPreloader>>,
     state: Res<bevy::prelude::State<ClientGameState>>,
 ) {
     let has_level_seed = level_seed_query.iter().next().is_some()
@@ -77,17 +197,12 @@ fn handle_world_creation(
             })
         {
             bevy::log::info!("🌱 Client generating level with seed: {}", seed);
-
-            let config = LevelConfig {
-                seed,
-                target_zone_count: 12,
-                min_zone_spacing: 35.0,
-                max_depth: 8,
-            };
-
-            let level_graph = generate_level(config);
-            build_level_physics(commands.reborrow(), &level_graph);
-            build_level_visuals(commands.reborrow(), meshes, materials, &level_graph);
+            // Usually generated in the background during the previous post-game.
+            let level_graph = preloader.map_or_else(
+                || generate_level(LevelConfig::for_seed(seed)),
+                |mut preloader| preloader.take(seed),
+            );
+            build_procedural_level(commands.reborrow(), meshes, materials, &level_graph);
         } else {
             bevy::log::info!(
                 "⏳ Client waiting for LevelSeed replication before generating procedural level"
@@ -0,0 +1,479 @@
+use avian3d::prelude::{Collider, Position, Rotation};
+use bevy::asset::RenderAssetUsages;
+use bevy::image::{Image, ImageSampler};
+use bevy::prelude::{
+    Added, App, Assets, BackgroundColor, ButtonInput, ChildSpawnerCommands, Color, Commands,
+    Component, Entity, GlobalTransform, Handle, ImageNode, IntoScheduleConfigs, KeyCode, Name,
+    Node, Overflow, PositionType, Query, RemovedComponents, Res, ResMut, Resource, Rot2, Update,
+    Val, Vec2, Vec3, Visibility, With, resource_exists,
+};
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::ui::UiTransform;
+use shared::components::team::Team;
+use shared::level::generation::LevelGeometry;
+use shared::protocol::{CharacterMarker, PlayerId};
+
+use crate::LocalPlayerId;
+use crate::camera::PlayerCamera;
+use crate::hud::{HudAnchor, HudAppExt, HudSystems};
+
+/// Side of the minimap on screen.
+const MINIMAP_SIZE_PX: f32 = 200.0;
+/// Texture resolution for small levels; large ones get coarser texels instead of a bigger
+/// texture than [`MAX_TEXTURE_SIZE`].
+const METERS_PER_TEXEL: f32 = 0.5;
+const MAX_TEXTURE_SIZE: u32 = 512;
+/// Empty border around the level, so walls on its edge are not cut.
+const BAKE_MARGIN: f32 = 2.0;
+/// Colliders taller than this are drawn as walls, flatter ones as floor.
+const WALL_MIN_HEIGHT: f32 = 2.0;
+/// Colliders topping out below this are under the playable floors, like the safety floor,
+/// and are left off the map.
+const SUBFLOOR_TOP: f32 = -0.5;
+/// Screen pixels per meter, and its bounds and step for the zoom keys.
+const DEFAULT_ZOOM: f32 = 2.0;
+const MIN_ZOOM: f32 = 0.5;
+const MAX_ZOOM: f32 = 8.0;
+const ZOOM_STEP: f32 = 1.25;
+const BLIP_SIZE_PX: f32 = 8.0;
+
+/// Top-down map of the level in the top left corner, baked from the level colliders
+/// whenever the level is (re)built. North is up; the local player is an arrow in the
+/// middle, teammates are blips. Numpad +/- zoom.
+pub(super) fn add_minimap(app: &mut App) {
+    app.init_resource::<MinimapZoom>();
+    app.add_hud_widget("Minimap", HudAnchor::TopLeft, 0, spawn_minimap);
+    app.add_systems(
+        Update,
+        (
+            bake_minimap_texture,
+            zoom_minimap,
+            (
+                update_minimap_view,
+                sync_minimap_blips,
+                update_minimap_blips,
+            )
+                .chain()
+                .run_if(resource_exists::<MinimapTexture>),
+        )
+            .chain()
+            .in_set(HudSystems),
+    );
+}
+
+/// What a texel of the baked map shows.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MinimapCell {
+    #[default]
+    Void,
+    Floor,
+    Wall,
+}
+
+impl MinimapCell {
+    fn rgba(self) -> [u8; 4] {
+        match self {
+            MinimapCell::Void => [0, 0, 0, 0],
+            MinimapCell::Floor => [70, 74, 82, 220],
+            MinimapCell::Wall => [200, 205, 215, 255],
+        }
+    }
+}
+
+/// Top-down raster of the level: row 0 is the northmost (lowest Z) and column 0 the
+/// westmost (lowest X).
+#[derive(Clone, Debug, PartialEq)]
+pub struct MinimapBake {
+    /// World XZ of the north-west corner of the first texel.
+    pub min: Vec2,
+    pub meters_per_texel: f32,
+    pub width: u32,
+    pub height: u32,
+    pub cells: Vec<MinimapCell>,
+}
+
+impl MinimapBake {
+    pub fn cell_at(&self, world: Vec2) -> MinimapCell {
+        let texel = ((world - self.min) / self.meters_per_texel).floor();
+        if texel.x < 0.0
+            || texel.y < 0.0
+            || texel.x >= self.width as f32
+            || texel.y >= self.height as f32
+        {
+            return MinimapCell::Void;
+        }
+        self.cells[texel.y as usize * self.width as usize + texel.x as usize]
+    }
+
+    /// Size of the baked area in meters.
+    pub fn extent(&self) -> Vec2 {
+        Vec2::new(self.width as f32, self.height as f32) * self.meters_per_texel
+    }
+}
+
+/// Rasterizes the footprints of `colliders` (shape, position, rotation) seen from above.
+/// Walls are drawn over floors. `None` when nothing is on the map.
+pub fn bake_minimap<'a>(
+    colliders: impl IntoIterator<Item = (&'a Collider, Vec3, Rotation)>,
+) -> Option<MinimapBake> {
+    let shapes: Vec<_> = colliders
+        .into_iter()
+        .filter_map(|(collider, position, rotation)| {
+            let aabb = collider.aabb(position, rotation);
+            if aabb.max.y < SUBFLOOR_TOP {
+                return None;
+            }
+            let cell = if aabb.max.y - aabb.min.y > WALL_MIN_HEIGHT {
+                MinimapCell::Wall
+            } else {
+                MinimapCell::Floor
+            };
+            Some((collider, position, rotation, aabb, cell))
+        })
+        .collect();
+
+    let (mut min, mut max) = shapes.iter().fold(
+        (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+        |(min, max), (_, _, _, aabb, _)| {
+            (
+                min.min(Vec2::new(aabb.min.x, aabb.min.z)),
+                max.max(Vec2::new(aabb.max.x, aabb.max.z)),
+            )
+        },
+    );
+    if !min.is_finite() || !max.is_finite() {
+        return None;
+    }
+    min -= Vec2::splat(BAKE_MARGIN);
+    max += Vec2::splat(BAKE_MARGIN);
+
+    let extent = max - min;
+    let meters_per_texel = METERS_PER_TEXEL.max(extent.max_element() / MAX_TEXTURE_SIZE as f32);
+    let width = (extent.x / meters_per_texel).ceil() as u32;
+    let height = (extent.y / meters_per_texel).ceil() as u32;
+    let mut cells = vec![MinimapCell::Void; width as usize * height as usize];
+
+    for layer in [MinimapCell::Floor, MinimapCell::Wall] {
+        for (collider, position, rotation, aabb, _) in
+            shapes.iter().filter(|(.., cell)| *cell == layer)
+        {
+            // Sample through the middle of the collider, over the texels its AABB covers.
+            let probe_y = (aabb.min.y + aabb.max.y) * 0.5;
+            let first = ((Vec2::new(aabb.min.x, aabb.min.z) - min) / meters_per_texel).floor();
+            let last = ((Vec2::new(aabb.max.x, aabb.max.z) - min) / meters_per_texel).ceil();
+            for row in first.y as u32..(last.y as u32).min(height) {
+                for column in first.x as u32..(last.x as u32).min(width) {
+                    let texel_center =
+                        min + (Vec2::new(column as f32, row as f32) + 0.5) * meters_per_texel;
+                    let point = Vec3::new(texel_center.x, probe_y, texel_center.y);
+                    if collider.contains_point(*position, *rotation, point) {
+                        cells[row as usize * width as usize + column as usize] = layer;
+                    }
+                }
+            }
+        }
+    }
+
+    Some(MinimapBake {
+        min,
+        meters_per_texel,
+        width,
+        height,
+        cells,
+    })
+}
+
+fn minimap_image(bake: &MinimapBake) -> Image {
+    let data = bake.cells.iter().flat_map(|cell| cell.rgba()).collect();
+    let mut image = Image::new(
+        Extent3d {
+            width: bake.width,
+            height: bake.height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    image.sampler = ImageSampler::nearest();
+    image
+}
+
+/// Baked map of the current level.
+#[derive(Resource)]
+struct MinimapTexture {
+    image: Handle<Image>,
+    min: Vec2,
+    extent: Vec2,
+}
+
+/// Screen pixels per meter.
+#[derive(Resource)]
+struct MinimapZoom(f32);
+
+impl Default for MinimapZoom {
+    fn default() -> Self {
+        Self(DEFAULT_ZOOM)
+    }
+}
+
+#[derive(Component)]
+struct MinimapRoot;
+
+#[derive(Component)]
+struct MinimapImage;
+
+#[derive(Component)]
+struct MinimapPlayerArrow;
+
+#[derive(Component)]
+struct MinimapBlip {
+    target: Entity,
+}
+
+fn spawn_minimap(parent: &mut ChildSpawnerCommands) {
+    parent
+        .spawn((
+            Name::new("Minimap"),
+            MinimapRoot,
+            Node {
+                width: Val::Px(MINIMAP_SIZE_PX),
+                height: Val::Px(MINIMAP_SIZE_PX),
+                overflow: Overflow::clip(),
+                ..Default::default()
+            },
+            BackgroundColor(Color::srgba(0.05, 0.05, 0.08, 0.75)),
+        ))
+        .with_children(|map| {
+            map.spawn((
+                MinimapImage,
+                ImageNode::default(),
+                Node {
+                    position_type: PositionType::Absolute,
+                    ..Default::default()
+                },
+                Visibility::Hidden,
+            ));
+            // A dot with a nose, turned with the camera.
+            map.spawn((
+                MinimapPlayerArrow,
+                Node {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(MINIMAP_SIZE_PX * 0.5 - 5.0),
+                    top: Val::Px(MINIMAP_SIZE_PX * 0.5 - 5.0),
+                    width: Val::Px(10.0),
+                    height: Val::Px(10.0),
+                    ..Default::default()
+                },
+                BackgroundColor(Color::WHITE),
+                UiTransform::default(),
+            ))
+            .with_child((
+                Node {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(3.0),
+                    top: Val::Px(-7.0),
+                    width: Val::Px(4.0),
+                    height: Val::Px(8.0),
+                    ..Default::default()
+                },
+                BackgroundColor(Color::WHITE),
+            ));
+        });
+}
+
+/// Re-bakes the map whenever level colliders come or go.
+fn bake_minimap_texture(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    added: Query<(), (With<LevelGeometry>, Added<Collider>)>,
+    mut removed: RemovedComponents<LevelGeometry>,
+    level: Query<(&Collider, &Position, &Rotation), With<LevelGeometry>>,
+) {
+    let level_changed = removed.read().count() > 0 || !added.is_empty();
+    if !level_changed {
+        return;
+    }
+
+    let bake = bake_minimap(
+        level
+            .iter()
+            .map(|(collider, position, rotation)| (collider, position.0, *rotation)),
+    );
+    match bake {
+        Some(bake) => {
+            commands.insert_resource(MinimapTexture {
+                image: images.add(minimap_image(&bake)),
+                min: bake.min,
+                extent: bake.extent(),
+            });
+        }
+        None => commands.remove_resource::<MinimapTexture>(),
+    }
+}
+
+fn zoom_minimap(keys: Res<ButtonInput<KeyCode>>, mut zoom: ResMut<MinimapZoom>) {
+    if keys.just_pressed(KeyCode::NumpadAdd) {
+        zoom.0 = (zoom.0 * ZOOM_STEP).min(MAX_ZOOM);
+    }
+    if keys.just_pressed(KeyCode::NumpadSubtract) {
+        zoom.0 = (zoom.0 / ZOOM_STEP).max(MIN_ZOOM);
+    }
+}
+
+/// Position of the local player's character.
+fn local_player_position(
+    local_player_id: &LocalPlayerId,
+    characters: &Query<(Entity, &PlayerId, &Position, Option<&Team>), With<CharacterMarker>>,
+) -> Option<(Entity, Vec3, Option<Team>)> {
+    characters
+        .iter()
+        .find(|(_, player_id, ..)| player_id.0.to_bits() == local_player_id.0)
+        .map(|(entity, _, position, team)| (entity, position.0, team.copied()))
+}
+
+/// Scrolls the map under the local player and turns their arrow with the camera.
+fn update_minimap_view(
+    texture: Res<MinimapTexture>,
+    zoom: Res<MinimapZoom>,
+    local_player_id: Res<LocalPlayerId>,
+    characters: Query<(Entity, &PlayerId, &Position, Option<&Team>), With<CharacterMarker>>,
+    camera: Query<&GlobalTransform, With<PlayerCamera>>,
+    mut map_image: Query<(&mut ImageNode, &mut Node, &mut Visibility), With<MinimapImage>>,
+    mut arrow: Query<&mut UiTransform, With<MinimapPlayerArrow>>,
+) {
+    let Ok((mut image, mut node, mut visibility)) = map_image.single_mut() else {
+        return;
+    };
+    if image.image != texture.image {
+        image.image = texture.image.clone();
+    }
+    let Some((_, player, _)) = local_player_position(&local_player_id, &characters) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    *visibility = Visibility::Inherited;
+
+    let offset = (Vec2::new(player.x, player.z) - texture.min) * zoom.0;
+    node.left = Val::Px(MINIMAP_SIZE_PX * 0.5 - offset.x);
+    node.top = Val::Px(MINIMAP_SIZE_PX * 0.5 - offset.y);
+    node.width = Val::Px(texture.extent.x * zoom.0);
+    node.height = Val::Px(texture.extent.y * zoom.0);
+
+    if let Ok(camera) = camera.single()
+        && let Ok(mut arrow) = arrow.single_mut()
+    {
+        let forward = camera.forward();
+        // Clockwise from north, which is also clockwise on screen.
+        arrow.rotation = Rot2::radians(forward.x.atan2(-forward.z));
+    }
+}
+
+/// One blip per teammate of the local player.
+fn sync_minimap_blips(
+    mut commands: Commands,
+    local_player_id: Res<LocalPlayerId>,
+    characters: Query<(Entity, &PlayerId, &Position, Option<&Team>), With<CharacterMarker>>,
+    blips: Query<(Entity, &MinimapBlip)>,
+    root: Query<Entity, With<MinimapRoot>>,
+) {
+    let Ok(root) = root.single() else {
+        return;
+    };
+    let local = local_player_position(&local_player_id, &characters);
+    let local_team = local.and_then(|(_, _, team)| team);
+    let is_teammate = |entity: Entity, team: Option<&Team>| {
+        local.is_some_and(|(local, ..)| local != entity)
+            && local_team.is_some()
+            && team.copied() == local_team
+    };
+
+    for (blip, MinimapBlip { target }) in blips.iter() {
+        let still_teammate = characters
+            .get(*target)
+            .is_ok_and(|(entity, _, _, team)| is_teammate(entity, team));
+        if !still_teammate {
+            commands.entity(blip).despawn();
+        }
+    }
+
+    for (entity, _, _, team) in characters.iter() {
+        if !is_teammate(entity, team) || blips.iter().any(|(_, blip)| blip.target == entity) {
+            continue;
+        }
+        let color = team.map_or(Color::WHITE, Team::color);
+        commands.entity(root).with_child((
+            MinimapBlip { target: entity },
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Px(BLIP_SIZE_PX),
+                height: Val::Px(BLIP_SIZE_PX),
+                ..Default::default()
+            },
+            BackgroundColor(color),
+        ));
+    }
+}
+
+/// Places teammate blips relative to the local player, in the middle of the map.
+fn update_minimap_blips(
+    zoom: Res<MinimapZoom>,
+    local_player_id: Res<LocalPlayerId>,
+    characters: Query<(Entity, &PlayerId, &Position, Option<&Team>), With<CharacterMarker>>,
+    mut blips: Query<(&MinimapBlip, &mut Node)>,
+) {
+    let Some((_, player, _)) = local_player_position(&local_player_id, &characters) else {
+        return;
+    };
+    for (blip, mut node) in blips.iter_mut() {
+        let Ok((_, _, position, _)) = characters.get(blip.target) else {
+            continue;
+        };
+        let offset = Vec2::new(position.x - player.x, position.z - player.z) * zoom.0;
+        node.left = Val::Px(MINIMAP_SIZE_PX * 0.5 + offset.x - BLIP_SIZE_PX * 0.5);
+        node.top = Val::Px(MINIMAP_SIZE_PX * 0.5 + offset.y - BLIP_SIZE_PX * 0.5);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BAKE_MARGIN, MinimapCell, bake_minimap};
+    use avian3d::prelude::{Collider, Rotation};
+    use bevy::prelude::{Quat, Vec2, Vec3};
+
+    #[test]
+    fn level_colliders_bake_into_floor_and_walls() {
+        let floor = Collider::cuboid(20.0, 1.0, 20.0);
+        let wall = Collider::cuboid(1.0, 10.0, 20.0);
+        let safety_floor = Collider::cuboid(100.0, 6.0, 100.0);
+        let bake = bake_minimap([
+            (&floor, Vec3::new(0.0, -0.5, 0.0), Rotation::default()),
+            (&wall, Vec3::new(9.5, 5.0, 0.0), Rotation::default()),
+            (
+                &safety_floor,
+                Vec3::new(0.0, -4.0, 0.0),
+                Rotation::default(),
+            ),
+        ])
+        .expect("something to bake");
+
+        // The safety floor under the level does not widen the map.
+        assert_eq!(bake.min, Vec2::splat(-10.0 - BAKE_MARGIN));
+        assert_eq!(bake.cell_at(Vec2::new(0.0, 0.0)), MinimapCell::Floor);
+        assert_eq!(bake.cell_at(Vec2::new(9.6, 3.0)), MinimapCell::Wall);
+        assert_eq!(bake.cell_at(Vec2::new(-11.0, 0.0)), MinimapCell::Void);
+        assert_eq!(bake.cell_at(Vec2::new(500.0, 0.0)), MinimapCell::Void);
+
+        // Rotated colliders keep their footprint.
+        let turned = bake_minimap([(
+            &wall,
+            Vec3::new(0.0, 5.0, 0.0),
+            Rotation::from(Quat::from_rotation_y(std::f32::consts::FRAC_PI_2)),
+        )])
+        .unwrap();
+        assert_eq!(turned.cell_at(Vec2::new(8.0, 0.0)), MinimapCell::Wall);
+        assert_eq!(turned.cell_at(Vec2::new(0.0, 8.0)), MinimapCell::Void);
+        assert!(bake_minimap([]).is_none());
+    }
+}
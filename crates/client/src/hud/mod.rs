@@ -0,0 +1,278 @@
+pub mod minimap;
+pub mod widgets;
+
+use bevy::prelude::{
+    AlignItems, App, ChildSpawnerCommands, Commands, Component, Entity, FlexDirection,
+    IntoScheduleConfigs, Name, Node, OnEnter, OnExit, Plugin, PositionType, Query, Res, Resource,
+    SystemSet, Update, Val, With, in_state,
+};
+
+use crate::{ClientGameState, Headless};
+
+/// Gameplay HUD, spawned while playing. It only lays out anchors: the widgets in them
+/// (vitals, ammo, crosshair...) are registered with [`HudAppExt::add_hud_widget`] by the
+/// plugins that own them, and update themselves in [`HudSystems`].
+pub struct ClientHudPlugin;
+
+impl Plugin for ClientHudPlugin {
+    fn build(&self, app: &mut App) {
+        fn is_not_headless(headless: Option<Res<Headless>>) -> bool {
+            !headless.map(|h| h.0).unwrap_or(false)
+        }
+
+        app.init_resource::<HudWidgets>();
+        app.configure_sets(
+            Update,
+            HudSystems
+                .run_if(in_state(ClientGameState::Playing))
+                .run_if(is_not_headless),
+        );
+        app.add_systems(
+            OnEnter(ClientGameState::Playing),
+            spawn_hud.run_if(is_not_headless),
+        );
+        app.add_systems(
+            OnExit(ClientGameState::Playing),
+            despawn_hud.run_if(is_not_headless),
+        );
+        widgets::add_builtin_widgets(app);
+        minimap::add_minimap(app);
+    }
+}
+
+/// Systems updating HUD widgets; they only run while the HUD is up.
+#[derive(SystemSet, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct HudSystems;
+
+/// Screen region a widget is laid out in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HudAnchor {
+    /// Stacked downwards from the top left corner.
+    TopLeft,
+    TopCenter,
+    /// Zero-sized point at the middle of the screen; widgets position themselves around it.
+    Center,
+    /// Stacked upwards from the bottom left corner.
+    BottomLeft,
+    /// Stacked upwards from the bottom right corner.
+    BottomRight,
+}
+
+impl HudAnchor {
+    pub const ALL: [HudAnchor; 5] = [
+        HudAnchor::TopLeft,
+        HudAnchor::TopCenter,
+        HudAnchor::Center,
+        HudAnchor::BottomLeft,
+        HudAnchor::BottomRight,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            HudAnchor::TopLeft => "HudTopLeft",
+            HudAnchor::TopCenter => "HudTopCenter",
+            HudAnchor::Center => "HudCenter",
+            HudAnchor::BottomLeft => "HudBottomLeft",
+            HudAnchor::BottomRight => "HudBottomRight",
+        }
+    }
+
+    fn node(self) -> Node {
+        let stack = Node {
+            position_type: PositionType::Absolute,
+            flex_direction: FlexDirection::ColumnReverse,
+            row_gap: Val::Px(6.0),
+            ..Default::default()
+        };
+        match self {
+            HudAnchor::TopLeft => Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(24.0),
+                top: Val::Px(24.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Start,
+                row_gap: Val::Px(6.0),
+                ..Default::default()
+            },
+            HudAnchor::TopCenter => Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(16.0),
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(4.0),
+                ..Default::default()
+            },
+            HudAnchor::Center => Node {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(50.0),
+                top: Val::Percent(50.0),
+                ..Default::default()
+            },
+            HudAnchor::BottomLeft => Node {
+                left: Val::Px(24.0),
+                bottom: Val::Px(24.0),
+                align_items: AlignItems::Start,
+                ..stack
+            },
+            HudAnchor::BottomRight => Node {
+                right: Val::Px(24.0),
+                bottom: Val::Px(24.0),
+                align_items: AlignItems::End,
+                ..stack
+            },
+        }
+    }
+}
+
+/// Spawns a widget's entities into its anchor.
+pub type HudWidgetSpawner = fn(&mut ChildSpawnerCommands);
+
+#[derive(Clone, Debug)]
+pub struct HudWidget {
+    pub name: &'static str,
+    pub anchor: HudAnchor,
+    /// Position within the anchor, lowest first: top down at the top, bottom up at the
+    /// bottom.
+    pub order: i32,
+    pub spawn: HudWidgetSpawner,
+}
+
+/// Every widget spawned with the HUD.
+#[derive(Resource, Debug, Default)]
+pub struct HudWidgets {
+    widgets: Vec<HudWidget>,
+}
+
+impl HudWidgets {
+    /// Adds `widget`, replacing any registered under the same name.
+    pub fn register(&mut self, widget: HudWidget) {
+        self.widgets.retain(|existing| existing.name != widget.name);
+        self.widgets.push(widget);
+    }
+
+    /// Widgets in `anchor`, in order; ties keep registration order.
+    pub fn at(&self, anchor: HudAnchor) -> Vec<&HudWidget> {
+        let mut widgets: Vec<&HudWidget> = self
+            .widgets
+            .iter()
+            .filter(|widget| widget.anchor == anchor)
+            .collect();
+        widgets.sort_by_key(|widget| widget.order);
+        widgets
+    }
+}
+
+pub trait HudAppExt {
+    /// Registers a HUD widget. Works whether or not `ClientHudPlugin` was added yet.
+    fn add_hud_widget(
+        &mut self,
+        name: &'static str,
+        anchor: HudAnchor,
+        order: i32,
+        spawn: HudWidgetSpawner,
+    ) -> &mut Self;
+}
+
+impl HudAppExt for App {
+    fn add_hud_widget(
+        &mut self,
+        name: &'static str,
+        anchor: HudAnchor,
+        order: i32,
+        spawn: HudWidgetSpawner,
+    ) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_init::<HudWidgets>()
+            .register(HudWidget {
+                name,
+                anchor,
+                order,
+                spawn,
+            });
+        self
+    }
+}
+
+#[derive(Component)]
+struct HudRoot;
+
+fn spawn_hud(mut commands: Commands, widgets: Res<HudWidgets>) {
+    commands
+        .spawn((
+            Name::new("GameHud"),
+            HudRoot,
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            },
+        ))
+        .with_children(|root| {
+            for anchor in HudAnchor::ALL {
+                root.spawn((Name::new(anchor.label()), anchor.node()))
+                    .with_children(|slot| {
+                        for widget in widgets.at(anchor) {
+                            (widget.spawn)(slot);
+                        }
+                    });
+            }
+        });
+}
+
+fn despawn_hud(mut commands: Commands, hud_query: Query<Entity, With<HudRoot>>) {
+    for hud in &hud_query {
+        commands.entity(hud).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HudAnchor, HudAppExt, HudWidgets, spawn_hud};
+    use bevy::prelude::{App, ChildSpawnerCommands, Name, Startup};
+
+    fn spawn_nothing(_: &mut ChildSpawnerCommands) {}
+
+    fn spawn_marker(parent: &mut ChildSpawnerCommands) {
+        parent.spawn(Name::new("Marker"));
+    }
+
+    #[test]
+    fn widgets_are_grouped_by_anchor_in_order() {
+        let mut app = App::new();
+        app.add_hud_widget("Ammo", HudAnchor::BottomRight, 0, spawn_nothing)
+            .add_hud_widget("Stamina", HudAnchor::BottomLeft, 20, spawn_nothing)
+            .add_hud_widget("Health", HudAnchor::BottomLeft, 0, spawn_nothing)
+            .add_hud_widget("Shield", HudAnchor::BottomLeft, 10, spawn_nothing)
+            // Re-registering replaces the earlier widget.
+            .add_hud_widget("Stamina", HudAnchor::BottomLeft, 5, spawn_nothing);
+
+        let widgets = app.world().resource::<HudWidgets>();
+        let names: Vec<&str> = widgets
+            .at(HudAnchor::BottomLeft)
+            .iter()
+            .map(|widget| widget.name)
+            .collect();
+        assert_eq!(names, ["Health", "Stamina", "Shield"]);
+        assert!(widgets.at(HudAnchor::Center).is_empty());
+    }
+
+    #[test]
+    fn hud_spawns_registered_widgets() {
+        let mut app = App::new();
+        app.add_hud_widget("Marker", HudAnchor::Center, 0, spawn_marker);
+        app.add_systems(Startup, spawn_hud);
+        app.update();
+
+        let names: Vec<String> = app
+            .world_mut()
+            .query::<&Name>()
+            .iter(app.world())
+            .map(|name| name.to_string())
+            .collect();
+        assert!(names.contains(&"GameHud".to_string()));
+        assert!(names.contains(&"HudCenter".to_string()));
+        assert!(names.contains(&"Marker".to_string()));
+    }
+}
@@ -0,0 +1,404 @@
+use bevy::prelude::{
+    App, BackgroundColor, ChildSpawnerCommands, Color, Component, IntoScheduleConfigs, Name, Node,
+    PositionType, Query, Res, Text, TextFont, Update, Val, With,
+};
+use shared::components::health::Health;
+use shared::components::match_timer::{MatchTimer, format_match_clock};
+use shared::components::shield::Shield;
+use shared::components::stamina::Stamina;
+use shared::components::weapons::Gun;
+use shared::protocol::PlayerId;
+
+use crate::LocalPlayerId;
+use crate::hud::{HudAnchor, HudAppExt, HudSystems};
+
+const BAR_WIDTH: f32 = 220.0;
+const BAR_HEIGHT: f32 = 18.0;
+const BAR_BACKGROUND: Color = Color::srgba(0.05, 0.05, 0.08, 0.75);
+
+/// Widgets every match shows: the clock, health, shield and stamina bars, and ammo.
+pub(super) fn add_builtin_widgets(app: &mut App) {
+    app.add_hud_widget("MatchClock", HudAnchor::TopCenter, 0, spawn_match_clock)
+        .add_hud_widget("HealthBar", HudAnchor::BottomLeft, 0, spawn_health_bar)
+        .add_hud_widget("ShieldBar", HudAnchor::BottomLeft, 10, spawn_shield_bar)
+        .add_hud_widget("StaminaBar", HudAnchor::BottomLeft, 20, spawn_stamina_bar)
+        .add_hud_widget("Ammo", HudAnchor::BottomRight, 0, spawn_ammo);
+    app.add_systems(
+        Update,
+        (update_ammo_text, update_vital_bars, update_match_clock_text).in_set(HudSystems),
+    );
+}
+
+/// Local player resource shown as a bar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Vital {
+    Health,
+    Shield,
+    Stamina,
+}
+
+impl Vital {
+    fn name(self) -> &'static str {
+        match self {
+            Vital::Health => "HealthBar",
+            Vital::Shield => "ShieldBar",
+            Vital::Stamina => "StaminaBar",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Vital::Health => "HP",
+            Vital::Shield => "Shield",
+            Vital::Stamina => "Stamina",
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Vital::Health => Color::srgb(0.85, 0.2, 0.2),
+            Vital::Shield => Color::srgb(0.3, 0.6, 1.0),
+            Vital::Stamina => Color::srgb(0.95, 0.8, 0.25),
+        }
+    }
+}
+
+/// Fill fraction and current value of `vital`, `None` when the player does not have it.
+pub fn vital_reading(
+    vital: Vital,
+    health: &Health,
+    shield: Option<&Shield>,
+    stamina: Option<&Stamina>,
+) -> Option<(f32, f32)> {
+    match vital {
+        Vital::Health => Some((health.percentage(), health.current)),
+        Vital::Shield => shield.map(|shield| (shield.percentage(), shield.current)),
+        Vital::Stamina => stamina.map(|stamina| (stamina.percentage(), stamina.current)),
+    }
+}
+
+pub fn format_vital(vital: Vital, current: Option<f32>) -> String {
+    match current {
+        Some(current) => format!("{} {:.0}", vital.label(), current),
+        None => format!("{} --", vital.label()),
+    }
+}
+
+#[derive(Component)]
+struct AmmoText;
+
+#[derive(Component)]
+struct VitalBarFill(Vital);
+
+#[derive(Component)]
+struct VitalBarText(Vital);
+
+#[derive(Component)]
+struct MatchClockText;
+
+fn spawn_match_clock(parent: &mut ChildSpawnerCommands) {
+    parent.spawn((
+        Name::new("MatchClockText"),
+        MatchClockText,
+        Text::new("--:--"),
+        TextFont {
+            font_size: 24.0,
+            ..Default::default()
+        },
+    ));
+}
+
+fn spawn_ammo(parent: &mut ChildSpawnerCommands) {
+    parent.spawn((
+        Name::new("AmmoText"),
+        AmmoText,
+        Text::new("Ammo: -- / --"),
+        TextFont {
+            font_size: 22.0,
+            ..Default::default()
+        },
+    ));
+}
+
+fn spawn_health_bar(parent: &mut ChildSpawnerCommands) {
+    spawn_vital_bar(parent, Vital::Health);
+}
+
+fn spawn_shield_bar(parent: &mut ChildSpawnerCommands) {
+    spawn_vital_bar(parent, Vital::Shield);
+}
+
+fn spawn_stamina_bar(parent: &mut ChildSpawnerCommands) {
+    spawn_vital_bar(parent, Vital::Stamina);
+}
+
+fn spawn_vital_bar(parent: &mut ChildSpawnerCommands, vital: Vital) {
+    parent
+        .spawn((
+            Name::new(vital.name()),
+            Node {
+                width: Val::Px(BAR_WIDTH),
+                height: Val::Px(BAR_HEIGHT),
+                ..Default::default()
+            },
+            BackgroundColor(BAR_BACKGROUND),
+        ))
+        .with_children(|bar| {
+            bar.spawn((
+                VitalBarFill(vital),
+                Node {
+                    width: Val::Percent(0.0),
+                    height: Val::Percent(100.0),
+                    ..Default::default()
+                },
+                BackgroundColor(vital.color()),
+            ));
+            bar.spawn((
+                VitalBarText(vital),
+                Text::new(format_vital(vital, None)),
+                TextFont {
+                    font_size: 14.0,
+                    ..Default::default()
+                },
+                Node {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(6.0),
+                    ..Default::default()
+                },
+            ));
+        });
+}
+
+fn update_ammo_text(
+    mut ammo_text_query: Query<&mut Text, With<AmmoText>>,
+    local_player_id: Res<LocalPlayerId>,
+    player_gun_query: Query<(&PlayerId, &Gun), With<PlayerId>>,
+) {
+    let Ok(mut text) = ammo_text_query.single_mut() else {
+        return;
+    };
+
+    let local_gun = player_gun_query.iter().find_map(|(player_id, gun)| {
+        if player_id.0.to_bits() == local_player_id.0 {
+            Some(gun)
+        } else {
+            None
+        }
+    });
+
+    if let Some(heat) = local_gun.and_then(|gun| gun.heat.as_ref()) {
+        **text = if heat.venting {
+            "Heat: OVERHEATED (Venting...)".to_string()
+        } else {
+            format!("Heat: {:.0}%", heat.level * 100.0)
+        };
+    } else if let Some(gun) = local_gun {
+        let status = if gun.is_reloading {
+            " (Reloading...)"
+        } else {
+            ""
+        };
+        **text = format!(
+            "Ammo: {} / {}{}",
+            gun.ammo_in_magazine, gun.magazine_size, status
+        );
+    } else {
+        **text = "Ammo: -- / --".to_string();
+    }
+}
+
+fn update_vital_bars(
+    local_player_id: Res<LocalPlayerId>,
+    player_query: Query<(&PlayerId, &Health, Option<&Shield>, Option<&Stamina>)>,
+    mut fills: Query<(&VitalBarFill, &mut Node)>,
+    mut texts: Query<(&VitalBarText, &mut Text)>,
+) {
+    let local_vitals = player_query
+        .iter()
+        .find(|(player_id, ..)| player_id.0.to_bits() == local_player_id.0);
+    let reading = |vital| {
+        local_vitals
+            .and_then(|(_, health, shield, stamina)| vital_reading(vital, health, shield, stamina))
+    };
+
+    for (fill, mut node) in fills.iter_mut() {
+        let width = Val::Percent(reading(fill.0).map_or(0.0, |(fraction, _)| fraction * 100.0));
+        if node.width != width {
+            node.width = width;
+        }
+    }
+    for (vital_text, mut text) in texts.iter_mut() {
+        let content = format_vital(
+            vital_text.0,
+            reading(vital_text.0).map(|(_, current)| current),
+        );
+        if **text != content {
+            **text = content;
+        }
+    }
+}
+
+fn update_match_clock_text(
+    mut clock_text_query: Query<&mut Text, With<MatchClockText>>,
+    timer_query: Query<&MatchTimer>,
+) {
+    let Ok(mut text) = clock_text_query.single_mut() else {
+        return;
+    };
+
+    let content = timer_query.iter().next().map_or_else(
+        || "--:--".to_string(),
+        |timer| format_match_clock(timer.remaining_secs),
+    );
+    if **text != content {
+        **text = content;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AmmoText, Vital, VitalBarFill, VitalBarText, update_ammo_text, update_vital_bars};
+    use crate::LocalPlayerId;
+    use bevy::prelude::{App, Entity, MinimalPlugins, Node, Text, Update, Val, With};
+    use lightyear::prelude::PeerId;
+    use shared::components::health::Health;
+    use shared::components::shield::Shield;
+    use shared::components::weapons::{Gun, WeaponHeat};
+    use shared::protocol::PlayerId;
+
+    #[test]
+    fn ammo_text_uses_local_player_gun_values() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.insert_resource(LocalPlayerId(1));
+        app.add_systems(Update, update_ammo_text);
+
+        app.world_mut()
+            .spawn((AmmoText, Text::new("Ammo: -- / --")));
+
+        app.world_mut().spawn((
+            PlayerId(PeerId::Netcode(1)),
+            Gun {
+                ammo_in_magazine: 5,
+                magazine_size: 8,
+                ..Gun::default()
+            },
+        ));
+        app.world_mut().spawn((
+            PlayerId(PeerId::Netcode(2)),
+            Gun {
+                ammo_in_magazine: 1,
+                magazine_size: 8,
+                ..Gun::default()
+            },
+        ));
+
+        app.update();
+
+        let text = app
+            .world_mut()
+            .query_filtered::<&Text, With<AmmoText>>()
+            .single(app.world())
+            .expect("Ammo text entity should exist");
+
+        assert_eq!(text.as_str(), "Ammo: 5 / 8");
+    }
+
+    #[test]
+    fn ammo_text_shows_heat_for_heat_weapons() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.insert_resource(LocalPlayerId(1));
+        app.add_systems(Update, update_ammo_text);
+
+        app.world_mut()
+            .spawn((AmmoText, Text::new("Ammo: -- / --")));
+        let mut heat = WeaponHeat::new(0.1, 0.35, 1.6);
+        heat.level = 0.42;
+        app.world_mut()
+            .spawn((PlayerId(PeerId::Netcode(1)), Gun::default().with_heat(heat)));
+
+        app.update();
+
+        let text = app
+            .world_mut()
+            .query_filtered::<&Text, With<AmmoText>>()
+            .single(app.world())
+            .expect("Ammo text entity should exist");
+
+        assert_eq!(text.as_str(), "Heat: 42%");
+    }
+
+    #[test]
+    fn ammo_text_stays_placeholder_without_local_player_gun() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.insert_resource(LocalPlayerId(1));
+        app.add_systems(Update, update_ammo_text);
+
+        app.world_mut()
+            .spawn((AmmoText, Text::new("Ammo: -- / --")));
+        app.world_mut().spawn((
+            PlayerId(PeerId::Netcode(2)),
+            Gun {
+                ammo_in_magazine: 3,
+                magazine_size: 8,
+                ..Gun::default()
+            },
+        ));
+
+        app.update();
+
+        let text = app
+            .world_mut()
+            .query_filtered::<&Text, With<AmmoText>>()
+            .single(app.world())
+            .expect("Ammo text entity should exist");
+
+        assert_eq!(text.as_str(), "Ammo: -- / --");
+    }
+
+    #[test]
+    fn vital_bars_follow_local_health_shield_and_stamina() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.insert_resource(LocalPlayerId(1));
+        app.add_systems(Update, update_vital_bars);
+
+        let spawn_bar = |app: &mut App, vital: Vital| -> (Entity, Entity) {
+            let fill = app
+                .world_mut()
+                .spawn((VitalBarFill(vital), Node::default()))
+                .id();
+            let text = app
+                .world_mut()
+                .spawn((VitalBarText(vital), Text::new("")))
+                .id();
+            (fill, text)
+        };
+        let (_, health_text) = spawn_bar(&mut app, Vital::Health);
+        let (shield_fill, shield_text) = spawn_bar(&mut app, Vital::Shield);
+        let (stamina_fill, stamina_text) = spawn_bar(&mut app, Vital::Stamina);
+
+        let mut shield = Shield::new(50.0, 0.6, 10.0, 4.0);
+        shield.current = 20.0;
+        app.world_mut()
+            .spawn((PlayerId(PeerId::Netcode(1)), Health::basic(), shield));
+
+        app.update();
+
+        let world = app.world();
+        let text = |entity: Entity| world.get::<Text>(entity).unwrap().as_str().to_string();
+        assert_eq!(text(health_text), "HP 100");
+        assert_eq!(text(shield_text), "Shield 20");
+        assert_eq!(text(stamina_text), "Stamina --");
+        assert_eq!(
+            world.get::<Node>(shield_fill).unwrap().width,
+            Val::Percent(40.0)
+        );
+        assert_eq!(
+            world.get::<Node>(stamina_fill).unwrap().width,
+            Val::Percent(0.0)
+        );
+    }
+}
@@ -0,0 +1,261 @@
+use std::ops::RangeInclusive;
+
+use bevy::input::gamepad::Gamepad;
+use bevy::input::mouse::MouseMotion;
+use bevy::prelude::{
+    App, ButtonInput, Fixed, GamepadButton, IntoScheduleConfigs, KeyCode, MessageReader,
+    MouseButton, Plugin, PreUpdate, Query, Res, ResMut, Resource, Time, Vec2, With,
+};
+use leafwing_input_manager::plugin::InputManagerSystem;
+use leafwing_input_manager::prelude::{ActionState, InputMap};
+use serde::{Deserialize, Serialize};
+use shared::inputs::input::PlayerAction;
+use shared::inputs::look::MOUSE_SENSIVITY;
+
+use crate::settings::{GameSettings, SENSITIVITY_RANGE};
+
+/// Left stick deflection ignored for movement.
+pub const MOVE_STICK_DEADZONE: f32 = 0.15;
+/// Turn rate, in radians per second, with the right stick fully deflected at sensitivity 1.
+const LOOK_STICK_RATE: f32 = 3.5;
+/// Stick deflection that counts as picking up the gamepad.
+const ACTIVE_STICK_THRESHOLD: f32 = 0.4;
+/// Mouse motion, in pixels per frame, that counts as going back to the mouse.
+const ACTIVE_MOUSE_THRESHOLD: f32 = 4.0;
+
+pub const DEADZONE_RANGE: RangeInclusive<f32> = 0.0..=0.5;
+pub const OUTER_DEADZONE_RANGE: RangeInclusive<f32> = 0.5..=1.0;
+pub const EXPONENT_RANGE: RangeInclusive<f32> = 1.0..=4.0;
+
+/// Fixed gamepad layout for the button actions, with the prompt shown for each.
+const GAMEPAD_BUTTONS: [(PlayerAction, GamepadButton, &str); 10] = [
+    (PlayerAction::Jump, GamepadButton::South, "A"),
+    (PlayerAction::Sprint, GamepadButton::LeftThumb, "L3"),
+    (PlayerAction::Shoot, GamepadButton::RightTrigger2, "RT"),
+    (PlayerAction::Aim, GamepadButton::LeftTrigger2, "LT"),
+    (PlayerAction::Reload, GamepadButton::West, "X"),
+    (
+        PlayerAction::ToggleFlashlight,
+        GamepadButton::DPadUp,
+        "D-pad up",
+    ),
+    (PlayerAction::Throw, GamepadButton::RightTrigger, "RB"),
+    (PlayerAction::SwitchWeapon, GamepadButton::North, "Y"),
+    (PlayerAction::Use, GamepadButton::East, "B"),
+    (PlayerAction::ToggleCrouch, GamepadButton::RightThumb, "R3"),
+];
+
+/// Gamepad support on top of the keyboard and mouse bindings: the left stick and buttons
+/// are in the player's input map, the right stick is shaped by [`StickCurve`] and turned
+/// into the same look deltas the mouse produces, so the server sees no difference.
+pub struct ClientGamepadPlugin;
+
+impl Plugin for ClientGamepadPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActiveInputDevice>();
+        app.add_systems(
+            PreUpdate,
+            (
+                detect_active_device,
+                apply_gamepad_look.in_set(InputManagerSystem::ManualControl),
+            ),
+        );
+    }
+}
+
+/// Gamepad button bound to `action`, if any.
+pub fn gamepad_button(action: PlayerAction) -> Option<GamepadButton> {
+    GAMEPAD_BUTTONS
+        .iter()
+        .find(|(bound, ..)| *bound == action)
+        .map(|(_, button, _)| *button)
+}
+
+/// Prompt for `action` on a gamepad.
+pub fn gamepad_label(action: PlayerAction) -> &'static str {
+    match action {
+        PlayerAction::Move => "Left stick",
+        PlayerAction::Look => "Right stick",
+        _ => GAMEPAD_BUTTONS
+            .iter()
+            .find(|(bound, ..)| *bound == action)
+            .map_or("?", |(.., label)| label),
+    }
+}
+
+/// Response of the right stick: no input inside the inner deadzone, full input past the
+/// outer one, and `exponent` bending the curve in between for finer aim near the centre.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StickCurve {
+    pub inner_deadzone: f32,
+    pub outer_deadzone: f32,
+    pub exponent: f32,
+    pub sensitivity: f32,
+}
+
+impl Default for StickCurve {
+    fn default() -> Self {
+        Self {
+            inner_deadzone: 0.12,
+            outer_deadzone: 0.95,
+            exponent: 2.0,
+            sensitivity: 1.0,
+        }
+    }
+}
+
+impl StickCurve {
+    /// `Ok` if every value is in its range, otherwise why not.
+    pub fn validate(&self) -> Result<(), String> {
+        let values = [
+            ("Stick deadzone", self.inner_deadzone, DEADZONE_RANGE),
+            (
+                "Stick outer deadzone",
+                self.outer_deadzone,
+                OUTER_DEADZONE_RANGE,
+            ),
+            ("Stick curve", self.exponent, EXPONENT_RANGE),
+            ("Stick sensitivity", self.sensitivity, SENSITIVITY_RANGE),
+        ];
+        for (label, value, range) in values {
+            if !value.is_finite() || !range.contains(&value) {
+                return Err(format!(
+                    "{} must be between {} and {}",
+                    label,
+                    range.start(),
+                    range.end()
+                ));
+            }
+        }
+        if self.inner_deadzone >= self.outer_deadzone {
+            return Err("Stick deadzone must be below the outer deadzone".to_string());
+        }
+        Ok(())
+    }
+
+    /// `stick` after the deadzones and curve, in the same direction, at most 1 long.
+    pub fn shape(&self, stick: Vec2) -> Vec2 {
+        let deflection = stick.length();
+        if deflection <= self.inner_deadzone {
+            return Vec2::ZERO;
+        }
+        let range = (self.outer_deadzone - self.inner_deadzone).max(f32::EPSILON);
+        let scaled = ((deflection - self.inner_deadzone) / range).clamp(0.0, 1.0);
+        stick / deflection * scaled.powf(self.exponent)
+    }
+
+    /// Look input, in mouse units, for one fixed tick of `tick_secs` with the stick at
+    /// `stick`.
+    pub fn look_delta(&self, stick: Vec2, tick_secs: f32) -> Vec2 {
+        let shaped = self.shape(stick);
+        // Mouse y grows downward, the stick's upward.
+        Vec2::new(shaped.x, -shaped.y) * LOOK_STICK_RATE * self.sensitivity * tick_secs
+            / MOUSE_SENSIVITY
+    }
+}
+
+/// Device the player used last, for input prompts.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ActiveInputDevice {
+    #[default]
+    KeyboardMouse,
+    Gamepad,
+}
+
+fn detect_active_device(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    mut mouse_motion: MessageReader<MouseMotion>,
+    gamepads: Query<&Gamepad>,
+    mut device: ResMut<ActiveInputDevice>,
+) {
+    let mouse_moved = mouse_motion
+        .read()
+        .any(|motion| motion.delta.length() > ACTIVE_MOUSE_THRESHOLD);
+    let used_gamepad = gamepads.iter().any(|gamepad| {
+        gamepad.get_just_pressed().next().is_some()
+            || gamepad.left_stick().length() > ACTIVE_STICK_THRESHOLD
+            || gamepad.right_stick().length() > ACTIVE_STICK_THRESHOLD
+    });
+
+    let active = if used_gamepad {
+        ActiveInputDevice::Gamepad
+    } else if mouse_moved
+        || keys.get_just_pressed().next().is_some()
+        || mouse_buttons.get_just_pressed().next().is_some()
+    {
+        ActiveInputDevice::KeyboardMouse
+    } else {
+        return;
+    };
+    if *device != active {
+        *device = active;
+    }
+}
+
+/// Adds the right stick to the local player's look input, after leafwing filled in the
+/// mouse motion.
+fn apply_gamepad_look(
+    fixed_time: Res<Time<Fixed>>,
+    settings: Option<Res<GameSettings>>,
+    gamepads: Query<&Gamepad>,
+    mut players: Query<&mut ActionState<PlayerAction>, With<InputMap<PlayerAction>>>,
+) {
+    let curve = settings.map_or_else(StickCurve::default, |settings| settings.look_stick);
+    let Some(stick) = gamepads
+        .iter()
+        .map(|gamepad| gamepad.right_stick())
+        .find(|stick| curve.shape(*stick) != Vec2::ZERO)
+    else {
+        return;
+    };
+    let delta = curve.look_delta(stick, fixed_time.timestep().as_secs_f32());
+
+    for mut action_state in players.iter_mut() {
+        if action_state.disabled() {
+            continue;
+        }
+        let look = action_state.axis_pair(&PlayerAction::Look) + delta;
+        action_state.set_axis_pair(&PlayerAction::Look, look);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{StickCurve, gamepad_button, gamepad_label};
+    use bevy::prelude::{GamepadButton, Vec2};
+    use shared::inputs::input::PlayerAction;
+
+    #[test]
+    fn stick_curve_applies_deadzones_and_exponent() {
+        let curve = StickCurve {
+            inner_deadzone: 0.2,
+            outer_deadzone: 0.8,
+            exponent: 2.0,
+            sensitivity: 1.0,
+        };
+        assert_eq!(curve.shape(Vec2::new(0.15, 0.0)), Vec2::ZERO);
+        assert_eq!(curve.shape(Vec2::new(0.0, 0.9)), Vec2::Y);
+
+        // Halfway between the deadzones: a quarter with the squared curve.
+        let half = curve.shape(Vec2::new(-0.5, 0.0));
+        assert!((half.x + 0.25).abs() < 1e-5);
+        assert_eq!(half.y, 0.0);
+
+        // Pushing up looks up, which is negative mouse y.
+        let look = curve.look_delta(Vec2::Y, 1.0 / 64.0);
+        assert!(look.y < 0.0 && look.x == 0.0);
+    }
+
+    #[test]
+    fn gamepad_layout_covers_every_button_action() {
+        assert_eq!(
+            gamepad_button(PlayerAction::Jump),
+            Some(GamepadButton::South)
+        );
+        assert_eq!(gamepad_label(PlayerAction::Shoot), "RT");
+        assert_eq!(gamepad_label(PlayerAction::Move), "Left stick");
+        assert_eq!(gamepad_button(PlayerAction::Look), None);
+    }
+}
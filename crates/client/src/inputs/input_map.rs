@@ -1,18 +1,344 @@
 use bevy::prelude::{KeyCode, MouseButton};
 
-use leafwing_input_manager::prelude::{InputMap, MouseMove, VirtualDPad};
+use leafwing_input_manager::prelude::{
+    GamepadStick, InputMap, MouseMove, VirtualDPad, WithDualAxisProcessingPipelineExt,
+};
 
 use shared::inputs::input::PlayerAction;
 
+use crate::inputs::gamepad::{MOVE_STICK_DEADZONE, gamepad_button};
+
 pub fn get_player_input_map() -> InputMap<PlayerAction> {
-    InputMap::<PlayerAction>::default()
-        .with(PlayerAction::Jump, KeyCode::Space)
-        .with(PlayerAction::Shoot, MouseButton::Left)
-        .with(PlayerAction::Aim, MouseButton::Right)
-        .with(PlayerAction::Reload, KeyCode::KeyR)
-        .with(PlayerAction::Sprint, KeyCode::ShiftLeft)
-        .with(PlayerAction::ToggleFlashlight, KeyCode::KeyF)
-        .with_dual_axis(PlayerAction::Move, VirtualDPad::wasd())
-        .with_dual_axis(PlayerAction::Move, VirtualDPad::arrow_keys())
-        .with_dual_axis(PlayerAction::Look, MouseMove::default())
+    KeyBindings::default().input_map(1.0)
+}
+
+/// Keys that can be bound. Escape, Tab, function keys, push-to-talk, the shoulder swap and
+/// the debug view key are left out: the client uses them outside of the player actions.
+pub const BINDABLE_KEYS: &[KeyCode] = &[
+    KeyCode::KeyA,
+    KeyCode::KeyB,
+    KeyCode::KeyC,
+    KeyCode::KeyD,
+    KeyCode::KeyE,
+    KeyCode::KeyF,
+    KeyCode::KeyG,
+    KeyCode::KeyI,
+    KeyCode::KeyJ,
+    KeyCode::KeyK,
+    KeyCode::KeyL,
+    KeyCode::KeyM,
+    KeyCode::KeyN,
+    KeyCode::KeyO,
+    KeyCode::KeyP,
+    KeyCode::KeyQ,
+    KeyCode::KeyR,
+    KeyCode::KeyS,
+    KeyCode::KeyT,
+    KeyCode::KeyU,
+    KeyCode::KeyW,
+    KeyCode::KeyY,
+    KeyCode::KeyZ,
+    KeyCode::Digit0,
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+    KeyCode::Space,
+    KeyCode::ShiftLeft,
+    KeyCode::ShiftRight,
+    KeyCode::ControlLeft,
+    KeyCode::ControlRight,
+    KeyCode::AltLeft,
+    KeyCode::AltRight,
+    KeyCode::CapsLock,
+    KeyCode::Enter,
+    KeyCode::Backspace,
+    KeyCode::Backquote,
+    KeyCode::Minus,
+    KeyCode::Equal,
+    KeyCode::BracketLeft,
+    KeyCode::BracketRight,
+    KeyCode::Backslash,
+    KeyCode::Semicolon,
+    KeyCode::Quote,
+    KeyCode::Comma,
+    KeyCode::Period,
+    KeyCode::Slash,
+    KeyCode::Insert,
+    KeyCode::Delete,
+    KeyCode::Home,
+    KeyCode::End,
+    KeyCode::PageUp,
+    KeyCode::PageDown,
+    KeyCode::Numpad0,
+    KeyCode::Numpad1,
+    KeyCode::Numpad2,
+    KeyCode::Numpad3,
+    KeyCode::Numpad4,
+    KeyCode::Numpad5,
+    KeyCode::Numpad6,
+    KeyCode::Numpad7,
+    KeyCode::Numpad8,
+    KeyCode::Numpad9,
+];
+
+pub const BINDABLE_MOUSE_BUTTONS: [MouseButton; 5] = [
+    MouseButton::Left,
+    MouseButton::Right,
+    MouseButton::Middle,
+    MouseButton::Back,
+    MouseButton::Forward,
+];
+
+/// A key or mouse button a control is bound to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+impl Binding {
+    pub fn is_bindable(self) -> bool {
+        match self {
+            Binding::Key(key) => BINDABLE_KEYS.contains(&key),
+            Binding::Mouse(button) => BINDABLE_MOUSE_BUTTONS.contains(&button),
+        }
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            Binding::Key(key) => {
+                let name = format!("{:?}", key);
+                name.strip_prefix("Key")
+                    .or_else(|| name.strip_prefix("Digit"))
+                    .unwrap_or(&name)
+                    .to_string()
+            }
+            Binding::Mouse(button) => format!("Mouse {:?}", button),
+        }
+    }
+
+    /// Name in the settings file, e.g. `KeyW` or `MouseLeft`.
+    pub fn key(self) -> String {
+        match self {
+            Binding::Key(key) => format!("{:?}", key),
+            Binding::Mouse(button) => format!("Mouse{:?}", button),
+        }
+    }
+
+    pub fn from_key(name: &str) -> Option<Self> {
+        BINDABLE_KEYS
+            .iter()
+            .copied()
+            .map(Binding::Key)
+            .chain(BINDABLE_MOUSE_BUTTONS.into_iter().map(Binding::Mouse))
+            .find(|binding| binding.key() == name)
+    }
+}
+
+/// Something the player can rebind: a button action, or one direction of movement. The
+/// `Move` and `Look` axes are not controls themselves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Control {
+    MoveForward,
+    MoveBack,
+    MoveLeft,
+    MoveRight,
+    Action(PlayerAction),
+}
+
+impl Control {
+    pub const ALL: [Control; 15] = [
+        Control::MoveForward,
+        Control::MoveBack,
+        Control::MoveLeft,
+        Control::MoveRight,
+        Control::Action(PlayerAction::Jump),
+        Control::Action(PlayerAction::Sprint),
+        Control::Action(PlayerAction::Shoot),
+        Control::Action(PlayerAction::Aim),
+        Control::Action(PlayerAction::Reload),
+        Control::Action(PlayerAction::ToggleFlashlight),
+        Control::Action(PlayerAction::Throw),
+        Control::Action(PlayerAction::SwitchWeapon),
+        Control::Action(PlayerAction::Use),
+        Control::Action(PlayerAction::Crouch),
+        Control::Action(PlayerAction::ToggleCrouch),
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Control::MoveForward => "Move forward",
+            Control::MoveBack => "Move back",
+            Control::MoveLeft => "Move left",
+            Control::MoveRight => "Move right",
+            Control::Action(PlayerAction::Jump) => "Jump",
+            Control::Action(PlayerAction::Sprint) => "Sprint",
+            Control::Action(PlayerAction::Shoot) => "Shoot",
+            Control::Action(PlayerAction::Aim) => "Aim",
+            Control::Action(PlayerAction::Reload) => "Reload",
+            Control::Action(PlayerAction::ToggleFlashlight) => "Flashlight",
+            Control::Action(PlayerAction::Throw) => "Throw grenade",
+            Control::Action(PlayerAction::SwitchWeapon) => "Switch weapon",
+            Control::Action(PlayerAction::Use) => "Use",
+            Control::Action(PlayerAction::Crouch) => "Crouch (hold)",
+            Control::Action(PlayerAction::ToggleCrouch) => "Crouch (toggle)",
+            Control::Action(PlayerAction::Move | PlayerAction::Look) => "Axis",
+        }
+    }
+
+    /// Name in the settings file.
+    pub fn key(self) -> &'static str {
+        match self {
+            Control::MoveForward => "move_forward",
+            Control::MoveBack => "move_back",
+            Control::MoveLeft => "move_left",
+            Control::MoveRight => "move_right",
+            Control::Action(PlayerAction::Jump) => "jump",
+            Control::Action(PlayerAction::Sprint) => "sprint",
+            Control::Action(PlayerAction::Shoot) => "shoot",
+            Control::Action(PlayerAction::Aim) => "aim",
+            Control::Action(PlayerAction::Reload) => "reload",
+            Control::Action(PlayerAction::ToggleFlashlight) => "flashlight",
+            Control::Action(PlayerAction::Throw) => "throw",
+            Control::Action(PlayerAction::SwitchWeapon) => "switch_weapon",
+            Control::Action(PlayerAction::Use) => "use",
+            Control::Action(PlayerAction::Crouch) => "crouch",
+            Control::Action(PlayerAction::ToggleCrouch) => "toggle_crouch",
+            Control::Action(PlayerAction::Move | PlayerAction::Look) => "axis",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|control| control.key() == key)
+    }
+
+    /// Whether `binding` works for this control: movement is a d-pad of keys.
+    pub fn accepts(self, binding: Binding) -> bool {
+        match self {
+            Control::Action(_) => true,
+            _ => matches!(binding, Binding::Key(_)),
+        }
+    }
+}
+
+/// What every [`Control`] is bound to. The arrow keys always move, the mouse always looks
+/// and the gamepad layout is fixed, on top of these.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyBindings {
+    bindings: [Binding; Control::ALL.len()],
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        // In `Control::ALL` order.
+        Self {
+            bindings: [
+                Binding::Key(KeyCode::KeyW),
+                Binding::Key(KeyCode::KeyS),
+                Binding::Key(KeyCode::KeyA),
+                Binding::Key(KeyCode::KeyD),
+                Binding::Key(KeyCode::Space),
+                Binding::Key(KeyCode::ShiftLeft),
+                Binding::Mouse(MouseButton::Left),
+                Binding::Mouse(MouseButton::Right),
+                Binding::Key(KeyCode::KeyR),
+                Binding::Key(KeyCode::KeyF),
+                Binding::Key(KeyCode::KeyG),
+                Binding::Key(KeyCode::KeyQ),
+                Binding::Key(KeyCode::KeyE),
+                Binding::Key(KeyCode::ControlLeft),
+                Binding::Key(KeyCode::KeyC),
+            ],
+        }
+    }
+}
+
+impl KeyBindings {
+    fn index(control: Control) -> usize {
+        Control::ALL
+            .iter()
+            .position(|candidate| *candidate == control)
+            .expect("every control is listed in Control::ALL")
+    }
+
+    pub fn get(&self, control: Control) -> Binding {
+        self.bindings[Self::index(control)]
+    }
+
+    /// Control other than `control` already bound to `binding`.
+    pub fn bound_to(&self, binding: Binding, control: Control) -> Option<Control> {
+        Control::ALL
+            .into_iter()
+            .find(|other| *other != control && self.get(*other) == binding)
+    }
+
+    /// Binding `control` to `binding` swaps it with the control that had it, which only
+    /// works when that control accepts the binding it gets in return.
+    pub fn check(&self, control: Control, binding: Binding) -> Result<(), String> {
+        match self.bound_to(binding, control) {
+            Some(other) if !other.accepts(self.get(control)) => Err(format!(
+                "{} is bound to {}, which cannot take {}",
+                binding.label(),
+                other.label(),
+                self.get(control).label()
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Binds `control`, swapping bindings with the control that had `binding`.
+    pub fn set(&mut self, control: Control, binding: Binding) {
+        if let Some(other) = self.bound_to(binding, control) {
+            self.bindings[Self::index(other)] = self.get(control);
+        }
+        self.bindings[Self::index(control)] = binding;
+    }
+
+    /// Input map for these bindings, with mouse look scaled by `sensitivity`. Keyboard and
+    /// mouse inputs come first, so prompts built from the map show them. The right stick
+    /// is not in the map: it goes through the look curve in [`crate::inputs::gamepad`].
+    pub fn input_map(&self, sensitivity: f32) -> InputMap<PlayerAction> {
+        let mut input_map = InputMap::<PlayerAction>::default();
+        for control in Control::ALL {
+            let Control::Action(action) = control else {
+                continue;
+            };
+            match self.get(control) {
+                Binding::Key(key) => input_map.insert(action, key),
+                Binding::Mouse(button) => input_map.insert(action, button),
+            };
+            if let Some(button) = gamepad_button(action) {
+                input_map.insert(action, button);
+            }
+        }
+        let key = |control| match self.get(control) {
+            Binding::Key(key) => key,
+            Binding::Mouse(_) => unreachable!("movement is only bound to keys"),
+        };
+        input_map
+            .with_dual_axis(
+                PlayerAction::Move,
+                VirtualDPad::new(
+                    key(Control::MoveForward),
+                    key(Control::MoveBack),
+                    key(Control::MoveLeft),
+                    key(Control::MoveRight),
+                ),
+            )
+            .with_dual_axis(PlayerAction::Move, VirtualDPad::arrow_keys())
+            .with_dual_axis(
+                PlayerAction::Move,
+                GamepadStick::LEFT.with_circle_deadzone(MOVE_STICK_DEADZONE),
+            )
+            .with_dual_axis(
+                PlayerAction::Look,
+                MouseMove::default().sensitivity(sensitivity),
+            )
+    }
 }
@@ -5,10 +5,12 @@ use bevy::prelude::{
 };
 use bevy::window::WindowFocused;
 use bevy::window::{CursorGrabMode, CursorOptions, PrimaryWindow};
+use bevy_inspector_egui::bevy_egui::input::EguiWantsInput;
 use leafwing_input_manager::prelude::{ActionState, InputMap};
 
 use crate::ClientGameState;
 use crate::inputs::input_map::get_player_input_map;
+use crate::settings::GameSettings;
 use shared::inputs::input::PlayerAction;
 
 pub struct ClientWindowPlugin;
@@ -41,15 +43,34 @@ pub fn is_cursor_locked(cursor_options_query: &Query<&CursorOptions, With<Primar
 fn capture_cursor_for_gameplay(
     mut cursor_options_query: Query<&mut CursorOptions, With<PrimaryWindow>>,
     mut player_inputs: Query<(&mut ActionState<PlayerAction>, &mut InputMap<PlayerAction>)>,
+    settings: Option<Res<GameSettings>>,
 ) {
-    apply_capture_state(&mut cursor_options_query, &mut player_inputs, true);
+    let input_map = player_input_map(settings);
+    apply_capture_state(
+        &mut cursor_options_query,
+        &mut player_inputs,
+        true,
+        &input_map,
+    );
 }
 
 fn release_cursor_after_gameplay(
     mut cursor_options_query: Query<&mut CursorOptions, With<PrimaryWindow>>,
     mut player_inputs: Query<(&mut ActionState<PlayerAction>, &mut InputMap<PlayerAction>)>,
+    settings: Option<Res<GameSettings>>,
 ) {
-    apply_capture_state(&mut cursor_options_query, &mut player_inputs, false);
+    let input_map = player_input_map(settings);
+    apply_capture_state(
+        &mut cursor_options_query,
+        &mut player_inputs,
+        false,
+        &input_map,
+    );
+}
+
+/// Input map the players' maps are rebuilt with: their bindings and mouse sensitivity.
+fn player_input_map(settings: Option<Res<GameSettings>>) -> InputMap<PlayerAction> {
+    settings.map_or_else(get_player_input_map, |settings| settings.input_map())
 }
 
 fn set_cursor_capture_state(cursor_options: &mut CursorOptions, captured: bool) {
@@ -66,26 +87,27 @@ fn apply_capture_state(
     cursor_options_query: &mut Query<&mut CursorOptions, With<PrimaryWindow>>,
     player_inputs: &mut Query<(&mut ActionState<PlayerAction>, &mut InputMap<PlayerAction>)>,
     captured: bool,
+    input_map: &InputMap<PlayerAction>,
 ) {
     if let Ok(mut cursor_options) = cursor_options_query.single_mut() {
         set_cursor_capture_state(&mut cursor_options, captured);
     }
 
-    set_player_input_state(player_inputs, captured);
+    set_player_input_state(player_inputs, captured, input_map);
 }
 
 fn set_player_input_state(
     player_inputs: &mut Query<(&mut ActionState<PlayerAction>, &mut InputMap<PlayerAction>)>,
     captured: bool,
+    input_map: &InputMap<PlayerAction>,
 ) {
-    for (mut action_state, mut input_map) in player_inputs.iter_mut() {
+    for (mut action_state, mut player_input_map) in player_inputs.iter_mut() {
         if captured {
             action_state.enable();
-            *input_map = get_player_input_map();
         } else {
             action_state.disable();
-            *input_map = get_player_input_map();
         }
+        *player_input_map = input_map.clone();
 
         action_state.reset_all();
     }
@@ -96,11 +118,17 @@ fn handle_cursor_capture_hotkeys(
     mouse_buttons: Res<ButtonInput<MouseButton>>,
     mut cursor_options_query: Query<&mut CursorOptions, With<PrimaryWindow>>,
     mut player_inputs: Query<(&mut ActionState<PlayerAction>, &mut InputMap<PlayerAction>)>,
+    settings: Option<Res<GameSettings>>,
+    egui_input: Option<Res<EguiWantsInput>>,
 ) {
+    let input_map = player_input_map(settings);
     let mut should_capture = false;
     let should_release = keys.just_pressed(KeyCode::Escape);
+    // Clicks on menus (settings, pause) are for the menu, not the game.
+    let clicked_menu = egui_input.is_some_and(|input| input.wants_any_pointer_input());
 
     if !should_release
+        && !clicked_menu
         && mouse_buttons.just_pressed(MouseButton::Left)
         && let Ok(cursor_options) = cursor_options_query.single_mut()
     {
@@ -108,9 +136,19 @@ fn handle_cursor_capture_hotkeys(
     }
 
     if should_release {
-        apply_capture_state(&mut cursor_options_query, &mut player_inputs, false);
+        apply_capture_state(
+            &mut cursor_options_query,
+            &mut player_inputs,
+            false,
+            &input_map,
+        );
     } else if should_capture {
-        apply_capture_state(&mut cursor_options_query, &mut player_inputs, true);
+        apply_capture_state(
+            &mut cursor_options_query,
+            &mut player_inputs,
+            true,
+            &input_map,
+        );
     }
 }
 
@@ -118,15 +156,22 @@ fn handle_focus(
     mut cursor_options_query: Query<&mut CursorOptions, With<PrimaryWindow>>,
     mut focus_events: MessageReader<WindowFocused>,
     mut player_inputs: Query<(&mut ActionState<PlayerAction>, &mut InputMap<PlayerAction>)>,
+    settings: Option<Res<GameSettings>>,
 ) {
+    let input_map = player_input_map(settings);
     for event in focus_events.read() {
         if event.focused {
             let captured = cursor_options_query
                 .single_mut()
                 .is_ok_and(|cursor_options| cursor_options.grab_mode == CursorGrabMode::Locked);
-            set_player_input_state(&mut player_inputs, captured);
+            set_player_input_state(&mut player_inputs, captured, &input_map);
         } else {
-            apply_capture_state(&mut cursor_options_query, &mut player_inputs, false);
+            apply_capture_state(
+                &mut cursor_options_query,
+                &mut player_inputs,
+                false,
+                &input_map,
+            );
         }
     }
 }
@@ -134,6 +179,7 @@ fn handle_focus(
 #[cfg(test)]
 mod tests {
     use super::{set_cursor_capture_state, set_player_input_state};
+    use crate::inputs::input_map::get_player_input_map;
     use bevy::prelude::{App, MinimalPlugins, Update};
     use bevy::window::{CursorGrabMode, CursorOptions};
     use leafwing_input_manager::prelude::{ActionState, InputMap};
@@ -145,7 +191,7 @@ mod tests {
             &mut InputMap<PlayerAction>,
         )>,
     ) {
-        set_player_input_state(&mut player_inputs, true);
+        set_player_input_state(&mut player_inputs, true, &get_player_input_map());
     }
 
     fn disable_inputs(
@@ -154,7 +200,7 @@ mod tests {
             &mut InputMap<PlayerAction>,
         )>,
     ) {
-        set_player_input_state(&mut player_inputs, false);
+        set_player_input_state(&mut player_inputs, false, &get_player_input_map());
     }
 
     #[test]
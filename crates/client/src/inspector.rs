@@ -0,0 +1,259 @@
+use bevy::prelude::{
+    App, Entity, IntoScheduleConfigs, Local, Plugin, Query, Res, ResMut, Resource, Update, With,
+    info,
+};
+use lightyear::prelude::{Client, Connected, MessageReceiver, MessageSender};
+use shared::protocol::{
+    DebugChannel, EntitySnapshot, EntitySnapshotEntry, EntitySnapshotSubscribe,
+};
+
+/// Client id used by the inspector when none is given, kept away from regular player ids.
+pub const DEFAULT_INSPECTOR_CLIENT_ID: u64 = 900;
+
+/// Filters forwarded to the server when the inspector subscribes to entity snapshots.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct InspectorFilter(pub EntitySnapshotSubscribe);
+
+/// Last snapshot printed by the inspector, used to only print what changed.
+#[derive(Resource, Default)]
+pub struct LastEntitySnapshot(pub Option<EntitySnapshot>);
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SnapshotChange {
+    Added(EntitySnapshotEntry),
+    Removed(u64),
+    ComponentAdded {
+        entity: u64,
+        component: String,
+        value: String,
+    },
+    ComponentRemoved {
+        entity: u64,
+        component: String,
+    },
+    ComponentChanged {
+        entity: u64,
+        component: String,
+        old: String,
+        new: String,
+    },
+}
+
+impl std::fmt::Display for SnapshotChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnapshotChange::Added(entry) => {
+                write!(f, "+ {}", entry.entity)?;
+                for (component, value) in &entry.components {
+                    write!(f, "\n    {}: {}", component, value)?;
+                }
+                Ok(())
+            }
+            SnapshotChange::Removed(entity) => write!(f, "- {}", entity),
+            SnapshotChange::ComponentAdded {
+                entity,
+                component,
+                value,
+            } => write!(f, "~ {} +{}: {}", entity, component, value),
+            SnapshotChange::ComponentRemoved { entity, component } => {
+                write!(f, "~ {} -{}", entity, component)
+            }
+            SnapshotChange::ComponentChanged {
+                entity,
+                component,
+                old,
+                new,
+            } => write!(f, "~ {} {}: {} -> {}", entity, component, old, new),
+        }
+    }
+}
+
+/// Compute the changes between two snapshots. Without a previous snapshot every entity is
+/// reported as added.
+pub fn diff_snapshots(
+    previous: Option<&EntitySnapshot>,
+    next: &EntitySnapshot,
+) -> Vec<SnapshotChange> {
+    let empty = EntitySnapshot::default();
+    let previous = previous.unwrap_or(&empty);
+    let mut changes = Vec::new();
+
+    for entry in &next.entities {
+        let Some(old_entry) = previous
+            .entities
+            .iter()
+            .find(|old| old.entity == entry.entity)
+        else {
+            changes.push(SnapshotChange::Added(entry.clone()));
+            continue;
+        };
+
+        for (component, value) in &entry.components {
+            match old_entry
+                .components
+                .iter()
+                .find(|(old_component, _)| old_component == component)
+            {
+                None => changes.push(SnapshotChange::ComponentAdded {
+                    entity: entry.entity,
+                    component: component.clone(),
+                    value: value.clone(),
+                }),
+                Some((_, old_value)) if old_value != value => {
+                    changes.push(SnapshotChange::ComponentChanged {
+                        entity: entry.entity,
+                        component: component.clone(),
+                        old: old_value.clone(),
+                        new: value.clone(),
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+
+        for (old_component, _) in &old_entry.components {
+            if !entry
+                .components
+                .iter()
+                .any(|(component, _)| component == old_component)
+            {
+                changes.push(SnapshotChange::ComponentRemoved {
+                    entity: entry.entity,
+                    component: old_component.clone(),
+                });
+            }
+        }
+    }
+
+    for old_entry in &previous.entities {
+        if !next
+            .entities
+            .iter()
+            .any(|entry| entry.entity == old_entry.entity)
+        {
+            changes.push(SnapshotChange::Removed(old_entry.entity));
+        }
+    }
+
+    changes
+}
+
+/// Read-only replication inspector: subscribes to server entity snapshots over the debug
+/// channel and prints live diffs to stdout instead of spawning a player.
+pub struct ClientInspectorPlugin;
+
+impl Plugin for ClientInspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InspectorFilter>();
+        app.init_resource::<LastEntitySnapshot>();
+        app.add_systems(
+            Update,
+            (subscribe_to_snapshots, print_snapshot_diffs).chain(),
+        );
+    }
+}
+
+/// Sends the subscription once per connected client entity, so a reconnect resubscribes.
+fn subscribe_to_snapshots(
+    filter: Res<InspectorFilter>,
+    mut sender_q: Query<
+        (Entity, &mut MessageSender<EntitySnapshotSubscribe>),
+        (With<Client>, With<Connected>),
+    >,
+    mut subscribed: Local<Option<Entity>>,
+) {
+    for (client_entity, mut sender) in sender_q.iter_mut() {
+        if *subscribed != Some(client_entity) {
+            info!("🔍 Subscribing to entity snapshots with {:?}", filter.0);
+            sender.send::<DebugChannel>(filter.0.clone());
+            *subscribed = Some(client_entity);
+        }
+    }
+}
+
+fn print_snapshot_diffs(
+    mut receiver_q: Query<&mut MessageReceiver<EntitySnapshot>, With<Client>>,
+    mut last_snapshot: ResMut<LastEntitySnapshot>,
+) {
+    for mut receiver in receiver_q.iter_mut() {
+        for snapshot in receiver.receive() {
+            for change in diff_snapshots(last_snapshot.0.as_ref(), &snapshot) {
+                info!("{}", change);
+            }
+            last_snapshot.0 = Some(snapshot);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SnapshotChange, diff_snapshots};
+    use shared::protocol::{EntitySnapshot, EntitySnapshotEntry};
+
+    fn snapshot(entries: &[(u64, &[(&str, &str)])]) -> EntitySnapshot {
+        EntitySnapshot {
+            entities: entries
+                .iter()
+                .map(|(entity, components)| EntitySnapshotEntry {
+                    entity: *entity,
+                    components: components
+                        .iter()
+                        .map(|(name, value)| (name.to_string(), value.to_string()))
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn first_snapshot_reports_every_entity_as_added() {
+        let next = snapshot(&[(1, &[("Health", "100")]), (2, &[("Name", "Npc")])]);
+        let changes = diff_snapshots(None, &next);
+
+        assert_eq!(changes.len(), 2);
+        assert!(
+            changes
+                .iter()
+                .all(|change| matches!(change, SnapshotChange::Added(_)))
+        );
+    }
+
+    #[test]
+    fn diff_reports_component_changes_and_removed_entities() {
+        let previous = snapshot(&[
+            (1, &[("Health", "100"), ("Gun", "8")]),
+            (2, &[("Name", "Npc")]),
+        ]);
+        let next = snapshot(&[(1, &[("Health", "75"), ("Position", "[0, 1, 0]")])]);
+
+        let changes = diff_snapshots(Some(&previous), &next);
+
+        assert_eq!(
+            changes,
+            vec![
+                SnapshotChange::ComponentChanged {
+                    entity: 1,
+                    component: "Health".to_string(),
+                    old: "100".to_string(),
+                    new: "75".to_string(),
+                },
+                SnapshotChange::ComponentAdded {
+                    entity: 1,
+                    component: "Position".to_string(),
+                    value: "[0, 1, 0]".to_string(),
+                },
+                SnapshotChange::ComponentRemoved {
+                    entity: 1,
+                    component: "Gun".to_string(),
+                },
+                SnapshotChange::Removed(2),
+            ]
+        );
+    }
+
+    #[test]
+    fn identical_snapshots_produce_no_changes() {
+        let previous = snapshot(&[(1, &[("Health", "100")])]);
+        assert!(diff_snapshots(Some(&previous), &previous.clone()).is_empty());
+    }
+}
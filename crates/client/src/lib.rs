@@ -1,25 +1,60 @@
+pub mod analytics;
+pub mod audio;
 pub mod camera;
+pub mod crosshair;
 pub mod debug;
+pub mod demo;
 pub mod entities;
 pub mod local_menu;
 
 pub mod game;
 pub mod hud;
 pub mod inputs;
+pub mod inspector;
+pub mod loadout;
 pub mod lobby;
+pub mod match_lifecycle;
 pub mod network;
+pub mod onboarding;
+pub mod placeholder;
+pub mod profile;
+pub mod resolution;
+pub mod resync;
+pub mod scoreboard;
+pub mod session;
+pub mod settings;
 pub mod vfx;
+pub mod voice;
 
+use crate::audio::ClientAudioPlugin;
 use crate::camera::ClientCameraPlugin;
+use crate::crosshair::ClientCrosshairPlugin;
 use crate::debug::ClientDebugPlugin;
+use crate::debug::cpu_profile::ClientCpuProfilePlugin;
+use crate::debug::hearing::ClientHearingTunerPlugin;
+use crate::debug::net_labels::ClientNetLabelsPlugin;
+use crate::debug::netgraph::ClientNetgraphPlugin;
+use crate::demo::ClientDemoCapturePlugin;
 use crate::entities::ClientEntitiesPlugin;
+use crate::entities::animation::ClientCharacterAnimationPlugin;
 use crate::game::ClientGameCyclePlugin;
 use crate::hud::ClientHudPlugin;
 use crate::inputs::ClientInputPlugin;
+use crate::loadout::ClientLoadoutPlugin;
 use crate::lobby::ClientLobbyPlugin;
+use crate::match_lifecycle::ClientMatchLifecyclePlugin;
 use crate::network::ClientNetworkPlugin;
+use crate::onboarding::ClientOnboardingPlugin;
+use crate::placeholder::ClientPlaceholderPlugin;
+use crate::profile::ClientProfilePlugin;
+use crate::resolution::ClientResolutionPlugin;
+use crate::resync::ClientResyncPlugin;
+use crate::scoreboard::ClientScoreboardPlugin;
+use crate::session::ClientSessionPlugin;
+use crate::settings::ClientSettingsPlugin;
 
 use crate::vfx::ClientVFXPlugin;
+use crate::voice::ClientVoicePlugin;
 use bevy::diagnostic::FrameTimeDiagnosticsPlugin;
 use bevy::log::LogPlugin;
 use bevy::prelude::{
@@ -35,8 +70,11 @@ use bevy::state::app::AppExtStates;
 use bevy::window::{PresentMode, Window, WindowPlugin};
 
 use lightyear::prelude::client::ClientPlugins;
+use shared::composition::{ComposeApp, check_composition};
+use shared::cpu_profile::CpuProfilePlugin;
 use shared::debug::{client_debug_gizmos_enabled, debug_println};
 
+use std::path::PathBuf;
 use std::time::Duration;
 
 #[derive(Resource)]
@@ -48,6 +86,30 @@ pub struct LocalPlayerId(pub u64);
 #[derive(Resource)]
 pub struct Headless(pub bool);
 
+/// `<config dir>/yolo-game/<name>`, in the platform's config directory: `%APPDATA%` on
+/// Windows, `~/Library/Application Support` on macOS, and `$XDG_CONFIG_HOME` falling back
+/// to `~/.config` elsewhere.
+pub fn config_file(name: &str) -> Option<PathBuf> {
+    Some(config_dir()?.join("yolo-game").join(name))
+}
+
+#[cfg(target_os = "windows")]
+fn config_dir() -> Option<PathBuf> {
+    std::env::var_os("APPDATA").map(PathBuf::from)
+}
+
+#[cfg(target_os = "macos")]
+fn config_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn config_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+}
+
 #[derive(States, Debug, Default, Clone, PartialEq, Eq, Hash)]
 pub enum ClientGameState {
     LocalMenu,
@@ -57,6 +119,9 @@ pub enum ClientGameState {
     Loading,
     Spawning,
     Playing,
+    Spectating,
+    /// Final scores, until the server sends everyone back to the lobby.
+    PostGame,
 }
 
 use shared::NetworkMode;
@@ -69,7 +134,7 @@ pub fn create_client_app(
 ) -> App {
     let mut client_app = App::new();
     let client_id = if client_id == 0 { 1 } else { client_id };
-    client_app.insert_resource(Headless(headless));
+    client_app.compose_resource(Headless(headless));
 
     if headless {
         // Add AssetPlugin first to enable asset initialization
@@ -131,37 +196,66 @@ pub fn create_client_app(
         );
     }
 
-    client_app.insert_resource(network_mode);
+    client_app.compose_resource(network_mode);
     client_app.add_plugins(ClientPlugins {
         tick_duration: Duration::from_secs_f64(1.0 / shared::FIXED_TIMESTEP_HZ),
     });
-    client_app.add_plugins(shared::SharedPlugin);
-
-    client_app.insert_resource(LocalPlayerId(client_id));
-    client_app.add_plugins(ClientNetworkPlugin);
-    client_app.add_plugins(ClientInputPlugin);
-    client_app.add_plugins(ClientCameraPlugin);
+    client_app.compose_plugin(shared::SharedPlugin);
 
-    client_app.add_plugins(ClientEntitiesPlugin);
-    client_app.add_plugins(ClientLobbyPlugin);
-    client_app.add_plugins(ClientGameCyclePlugin);
-    client_app.add_plugins(ClientHudPlugin);
+    client_app.compose_resource(LocalPlayerId(client_id));
+    client_app.compose_plugin(CpuProfilePlugin);
+    compose_client_plugins(&mut client_app, headless);
 
-    client_app.init_state::<ClientGameState>();
-    client_app.insert_state(ClientGameState::LocalMenu);
+    client_app.compose_state(ClientGameState::LocalMenu);
 
     if !headless {
         if client_debug_gizmos_enabled() {
             client_app.add_plugins(FrameTimeDiagnosticsPlugin::default());
-            client_app.add_plugins(ClientDebugPlugin);
+            client_app.compose_plugin(ClientDebugPlugin);
         }
-        client_app.add_plugins(ClientVFXPlugin);
         client_app.add_systems(Startup, log_active_render_adapter);
     }
+    check_composition(&client_app);
 
     client_app
 }
 
+/// Gameplay plugins of the client, shared by the client and the host app so the host
+/// player gets the same HUD, scoreboard and menus. Headless apps skip the ones that draw
+/// or play sound.
+pub fn compose_client_plugins(app: &mut App, headless: bool) {
+    app.compose_plugin(ClientNetworkPlugin);
+    app.compose_plugin(ClientInputPlugin);
+    app.compose_plugin(ClientCameraPlugin);
+    app.compose_plugin(ClientEntitiesPlugin);
+    app.compose_plugin(ClientLobbyPlugin);
+    app.compose_plugin(ClientGameCyclePlugin);
+    app.compose_plugin(ClientHudPlugin);
+    app.compose_plugin(ClientCrosshairPlugin);
+    app.compose_plugin(ClientOnboardingPlugin);
+    app.compose_plugin(ClientScoreboardPlugin);
+    app.compose_plugin(ClientLoadoutPlugin);
+    app.compose_plugin(ClientMatchLifecyclePlugin);
+    app.compose_plugin(ClientSessionPlugin);
+    app.compose_plugin(ClientProfilePlugin);
+    app.compose_plugin(ClientResyncPlugin);
+    app.compose_plugin(ClientSettingsPlugin);
+    app.compose_plugin(ClientDemoCapturePlugin);
+
+    if !headless {
+        app.compose_plugin(ClientVFXPlugin);
+        app.compose_plugin(ClientCharacterAnimationPlugin);
+        app.compose_plugin(ClientPlaceholderPlugin);
+        app.compose_plugin(ClientAudioPlugin);
+        app.compose_plugin(ClientNetgraphPlugin);
+        app.compose_plugin(ClientCpuProfilePlugin);
+        app.compose_plugin(ClientNetLabelsPlugin);
+        app.compose_plugin(ClientHearingTunerPlugin);
+        app.compose_plugin(ClientResolutionPlugin);
+        app.compose_plugin(ClientVoicePlugin);
+    }
+}
+
 fn log_active_render_adapter(adapter_info: Option<bevy::prelude::Res<RenderAdapterInfo>>) {
     if let Some(adapter_info) = adapter_info {
         let info = &adapter_info.0;
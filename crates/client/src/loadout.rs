@@ -0,0 +1,131 @@
+use bevy::prelude::{
+    Added, App, Camera, ChildOf, DetectChanges, IntoScheduleConfigs, PerspectiveProjection, Plugin,
+    Projection, Query, Res, Resource, Time, Update, With, in_state,
+};
+use leafwing_input_manager::prelude::ActionState;
+use lightyear::prelude::{Client, Connected, Controlled, MessageSender, Predicted};
+use shared::components::attachments::{Attachment, DEFAULT_AIM_ZOOM, WeaponAttachments};
+use shared::components::loadout::Loadout;
+use shared::inputs::input::PlayerAction;
+use shared::protocol::{EquipAttachmentsRequest, LoadoutChannel, PlayerId, SubmitLoadoutRequest};
+
+use crate::ClientGameState;
+use crate::camera::PlayerCamera;
+use crate::camera::third_person::CameraView;
+use crate::settings::GameSettings;
+
+const AIM_ZOOM_SPEED: f32 = 12.0;
+/// Aim zoom in third person, where optics do not apply: the arm already moves in.
+const THIRD_PERSON_AIM_ZOOM: f32 = 1.2;
+
+/// Attachments the local player wants on their weapon, picked in the lobby. Changing this
+/// resource, or spawning a character, sends an `EquipAttachmentsRequest` to the server,
+/// which validates and replicates the result.
+#[derive(Resource, Clone, Debug, Default, PartialEq)]
+pub struct SelectedAttachments(pub Vec<Attachment>);
+
+/// Loadout the local player wants to spawn with, picked in the lobby. Valid choices are
+/// sent to the server whenever they change and again after connecting.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub struct SelectedLoadout(pub Loadout);
+
+pub struct ClientLoadoutPlugin;
+
+impl Plugin for ClientLoadoutPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SelectedAttachments>();
+        app.init_resource::<SelectedLoadout>();
+        app.add_systems(Update, (send_selected_loadout, send_selected_attachments));
+        app.add_systems(
+            Update,
+            apply_aim_zoom.run_if(in_state(ClientGameState::Playing)),
+        );
+    }
+}
+
+/// The server equips the character in hand, so the choice is sent again once the local
+/// character exists.
+fn send_selected_attachments(
+    selected: Res<SelectedAttachments>,
+    spawned: Query<
+        (),
+        (
+            With<PlayerId>,
+            With<Predicted>,
+            With<Controlled>,
+            Added<WeaponAttachments>,
+        ),
+    >,
+    mut sender_q: Query<
+        &mut MessageSender<EquipAttachmentsRequest>,
+        (With<Client>, With<Connected>),
+    >,
+) {
+    if !selected.is_changed() && spawned.is_empty() {
+        return;
+    }
+
+    if let Some(mut sender) = sender_q.iter_mut().next() {
+        sender.send::<LoadoutChannel>(EquipAttachmentsRequest {
+            attachments: selected.0.clone(),
+        });
+    }
+}
+
+fn send_selected_loadout(
+    selected: Res<SelectedLoadout>,
+    newly_connected: Query<(), (With<Client>, Added<Connected>)>,
+    mut sender_q: Query<&mut MessageSender<SubmitLoadoutRequest>, (With<Client>, With<Connected>)>,
+) {
+    if !selected.is_changed() && newly_connected.is_empty() {
+        return;
+    }
+    if !selected.0.is_valid() {
+        return;
+    }
+
+    if let Some(mut sender) = sender_q.iter_mut().next() {
+        sender.send::<LoadoutChannel>(SubmitLoadoutRequest {
+            loadout: selected.0,
+        });
+    }
+}
+
+/// Narrow the player camera field of view while aiming, using the optic zoom in first
+/// person and a light fixed zoom in third person.
+fn apply_aim_zoom(
+    time: Res<Time>,
+    mut camera_query: Query<(&mut Projection, &ChildOf), (With<PlayerCamera>, With<Camera>)>,
+    player_query: Query<(&ActionState<PlayerAction>, Option<&WeaponAttachments>)>,
+    settings: Option<Res<GameSettings>>,
+    view: Option<Res<CameraView>>,
+) {
+    let third_person = view.is_some_and(|view| view.third_person);
+    let base_fov = settings.map_or(PerspectiveProjection::default().fov, |settings| {
+        settings.fov_radians()
+    });
+
+    for (mut projection, child_of) in camera_query.iter_mut() {
+        let Projection::Perspective(perspective) = projection.as_mut() else {
+            continue;
+        };
+        let Ok((action_state, attachments)) = player_query.get(child_of.parent()) else {
+            continue;
+        };
+
+        let is_aiming = !action_state.disabled() && action_state.pressed(&PlayerAction::Aim);
+        let zoom = if is_aiming && third_person {
+            THIRD_PERSON_AIM_ZOOM
+        } else if is_aiming {
+            attachments
+                .map(|attachments| attachments.modifiers().aim_zoom)
+                .unwrap_or(DEFAULT_AIM_ZOOM)
+        } else {
+            1.0
+        };
+
+        let target_fov = base_fov / zoom.max(1.0);
+        let blend = (AIM_ZOOM_SPEED * time.delta_secs()).min(1.0);
+        perspective.fov += (target_fov - perspective.fov) * blend;
+    }
+}
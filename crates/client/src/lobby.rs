@@ -1,20 +1,29 @@
 use crate::ClientGameState;
 use crate::LocalPlayerId;
-use bevy::color::palettes::tailwind::{GREEN_500, SLATE_700, SLATE_800};
+use crate::loadout::{SelectedAttachments, SelectedLoadout};
+use bevy::color::palettes::tailwind::{GREEN_500, SLATE_600, SLATE_700, SLATE_800};
 use bevy::ecs::system::SystemParam;
 
 use bevy::ecs::query::Changed;
 use bevy::prelude::{
-    AlignItems, App, BackgroundColor, Camera2d, Click, Commands, Component, Entity, FlexDirection,
-    IntoScheduleConfigs, JustifyContent, Name, Node, On, OnEnter, OnExit, Plugin, Pointer, Query,
-    Res, Resource, Text, TextFont, UiRect, Update, Val, With, in_state,
+    AlignItems, App, BackgroundColor, Camera2d, Click, Commands, Component, DetectChanges, Entity,
+    FlexDirection, IntoScheduleConfigs, JustifyContent, Name, Node, On, OnEnter, OnExit, Plugin,
+    Pointer, Query, Res, ResMut, Resource, Text, TextFont, UiRect, Update, Val, With, Without,
+    in_state,
 };
 use bevy::window::{CursorGrabMode, CursorOptions, PrimaryWindow};
 
 use crate::Headless;
 use lightyear::prelude::{Client, Confirmed, MessageSender};
+use shared::aim_assist::AimAssistSettings;
+use shared::bots::MatchBotSettings;
+use shared::components::attachments::{Attachment, AttachmentSlot, WeaponAttachments};
+use shared::components::loadout::{
+    Equipment, LOADOUT_BUDGET, Loadout, PrimaryWeapon, SecondaryWeapon,
+};
+use shared::components::team::{Team, TeamRules};
 use shared::debug::debug_println;
-use shared::protocol::{HostStartGameEvent, LobbyControlChannel, LobbyState};
+use shared::protocol::{HostStartGameEvent, LobbyControlChannel, LobbyState, TeamSelectRequest};
 
 #[derive(Resource)]
 pub struct AutoStart(pub bool);
@@ -41,7 +50,18 @@ impl Plugin for ClientLobbyPlugin {
         );
         app.add_systems(
             Update,
-            (handle_auto_start, update_lobby_text.run_if(is_not_headless))
+            (
+                handle_auto_start,
+                (
+                    update_lobby_text,
+                    update_bot_settings_text,
+                    update_aim_assist_text,
+                    update_team_rules_text,
+                    update_loadout_text,
+                    update_attachment_text,
+                )
+                    .run_if(is_not_headless),
+            )
                 .run_if(in_state(ClientGameState::Lobby)),
         );
     }
@@ -92,7 +112,9 @@ fn handle_auto_start(
             }
         } else {
             // No lobby yet; will try again on next tick
-            debug_println(format_args!("DEBUG: handle_auto_start - No LobbyState found"));
+            debug_println(format_args!(
+                "DEBUG: handle_auto_start - No LobbyState found"
+            ));
         }
     }
 }
@@ -125,7 +147,106 @@ pub struct PlayerListContainer;
 #[derive(Component)]
 pub struct PlayerText;
 
-fn spawn_lobby_ui(mut commands: Commands) {
+#[derive(Component)]
+pub struct BotSettingsText;
+
+#[derive(Component)]
+pub struct AimAssistText;
+
+#[derive(Component)]
+pub struct TeamRulesText;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoadoutSlot {
+    Primary,
+    Secondary,
+    Equipment,
+}
+
+impl LoadoutSlot {
+    const ALL: [LoadoutSlot; 3] = [
+        LoadoutSlot::Primary,
+        LoadoutSlot::Secondary,
+        LoadoutSlot::Equipment,
+    ];
+
+    /// Switch this slot to the next available choice, wrapping around.
+    fn cycle(&self, loadout: &mut Loadout) {
+        match self {
+            LoadoutSlot::Primary => {
+                loadout.primary = next_option(&PrimaryWeapon::ALL, loadout.primary)
+            }
+            LoadoutSlot::Secondary => {
+                loadout.secondary = next_option(&SecondaryWeapon::ALL, loadout.secondary)
+            }
+            LoadoutSlot::Equipment => {
+                loadout.equipment = next_option(&Equipment::ALL, loadout.equipment)
+            }
+        }
+    }
+
+    fn describe(&self, loadout: &Loadout) -> String {
+        match self {
+            LoadoutSlot::Primary => format!("Primary: {}", loadout.primary.label()),
+            LoadoutSlot::Secondary => format!("Secondary: {}", loadout.secondary.label()),
+            LoadoutSlot::Equipment => format!("Equipment: {}", loadout.equipment.label()),
+        }
+    }
+}
+
+fn next_option<T: Copy + PartialEq>(options: &[T], current: T) -> T {
+    let index = options
+        .iter()
+        .position(|option| *option == current)
+        .unwrap_or(0);
+    options[(index + 1) % options.len()]
+}
+
+/// Switch `slot` to its next attachment, then to nothing, wrapping around.
+fn cycle_attachment(selected: &mut Vec<Attachment>, slot: AttachmentSlot) {
+    let mut attachments = WeaponAttachments::from_requested(selected);
+    let options: Vec<Option<Attachment>> = std::iter::once(None)
+        .chain(
+            Attachment::ALL
+                .into_iter()
+                .filter(|attachment| attachment.slot() == slot)
+                .map(Some),
+        )
+        .collect();
+    match next_option(&options, attachments.in_slot(slot)) {
+        Some(attachment) => attachments.equip(attachment),
+        None => attachments.unequip(slot),
+    }
+    *selected = attachments.equipped;
+}
+
+fn describe_attachment(selected: &[Attachment], slot: AttachmentSlot) -> String {
+    let attachment = WeaponAttachments::from_requested(selected).in_slot(slot);
+    format!(
+        "{}: {}",
+        slot.label(),
+        attachment.map_or("None", |attachment| attachment.label())
+    )
+}
+
+/// Label of a loadout slot button; clicking the button cycles the slot.
+#[derive(Component)]
+pub struct LoadoutSlotText(pub LoadoutSlot);
+
+/// Label of an attachment slot button; clicking the button cycles the slot.
+#[derive(Component)]
+pub struct AttachmentSlotText(pub AttachmentSlot);
+
+#[derive(Component)]
+pub struct LoadoutCostText;
+
+fn spawn_lobby_ui(
+    mut commands: Commands,
+    selected_loadout: Res<SelectedLoadout>,
+    selected_attachments: Res<SelectedAttachments>,
+) {
+    let loadout = selected_loadout.0;
+    let attachments = selected_attachments.0.clone();
     commands
         .spawn((
             Node {
@@ -165,6 +286,162 @@ fn spawn_lobby_ui(mut commands: Commands) {
                 },
                 LobbyStatusText,
             ));
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 18.0,
+                    ..Default::default()
+                },
+                Node {
+                    padding: UiRect::bottom(Val::Px(20.0)),
+                    ..Default::default()
+                },
+                BotSettingsText,
+            ));
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 18.0,
+                    ..Default::default()
+                },
+                Node {
+                    padding: UiRect::bottom(Val::Px(20.0)),
+                    ..Default::default()
+                },
+                AimAssistText,
+            ));
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 18.0,
+                    ..Default::default()
+                },
+                Node {
+                    padding: UiRect::bottom(Val::Px(20.0)),
+                    ..Default::default()
+                },
+                TeamRulesText,
+            ));
+
+            // Team picker
+            parent
+                .spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    margin: UiRect::bottom(Val::Px(20.0)),
+                    ..Default::default()
+                })
+                .with_children(|team_parent| {
+                    for team in Team::ALL {
+                        team_parent
+                            .spawn((
+                                Node {
+                                    padding: UiRect::all(Val::Px(10.0)),
+                                    margin: UiRect::horizontal(Val::Px(8.0)),
+                                    ..Default::default()
+                                },
+                                BackgroundColor(team.color()),
+                            ))
+                            .with_children(|button_parent| {
+                                button_parent.spawn((
+                                    Text::new(format!("Join {}", team.label())),
+                                    TextFont {
+                                        font_size: 18.0,
+                                        ..Default::default()
+                                    },
+                                ));
+                            })
+                            .observe(
+                                move |_click: On<Pointer<Click>>,
+                                      mut sender_q: Query<
+                                    &mut MessageSender<TeamSelectRequest>,
+                                    With<Client>,
+                                >| {
+                                    if let Some(mut sender) = sender_q.iter_mut().next() {
+                                        sender.send::<LobbyControlChannel>(TeamSelectRequest {
+                                            team,
+                                        });
+                                    }
+                                },
+                            );
+                    }
+                });
+
+            // Loadout picker
+            parent
+                .spawn((
+                    Node {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        padding: UiRect::all(Val::Px(15.0)),
+                        margin: UiRect::bottom(Val::Px(20.0)),
+                        ..Default::default()
+                    },
+                    BackgroundColor(SLATE_700.into()),
+                ))
+                .with_children(|loadout_parent| {
+                    for slot in LoadoutSlot::ALL {
+                        loadout_parent
+                            .spawn((
+                                Node {
+                                    padding: UiRect::all(Val::Px(8.0)),
+                                    margin: UiRect::bottom(Val::Px(6.0)),
+                                    ..Default::default()
+                                },
+                                BackgroundColor(SLATE_600.into()),
+                            ))
+                            .with_children(|button_parent| {
+                                button_parent.spawn((
+                                    Text::new(slot.describe(&loadout)),
+                                    TextFont {
+                                        font_size: 18.0,
+                                        ..Default::default()
+                                    },
+                                    LoadoutSlotText(slot),
+                                ));
+                            })
+                            .observe(
+                                move |_click: On<Pointer<Click>>,
+                                      mut selected: ResMut<SelectedLoadout>| {
+                                    slot.cycle(&mut selected.0);
+                                },
+                            );
+                    }
+                    for slot in AttachmentSlot::ALL {
+                        loadout_parent
+                            .spawn((
+                                Node {
+                                    padding: UiRect::all(Val::Px(8.0)),
+                                    margin: UiRect::bottom(Val::Px(6.0)),
+                                    ..Default::default()
+                                },
+                                BackgroundColor(SLATE_600.into()),
+                            ))
+                            .with_children(|button_parent| {
+                                button_parent.spawn((
+                                    Text::new(describe_attachment(&attachments, slot)),
+                                    TextFont {
+                                        font_size: 18.0,
+                                        ..Default::default()
+                                    },
+                                    AttachmentSlotText(slot),
+                                ));
+                            })
+                            .observe(
+                                move |_click: On<Pointer<Click>>,
+                                      mut selected: ResMut<SelectedAttachments>| {
+                                    cycle_attachment(&mut selected.0, slot);
+                                },
+                            );
+                    }
+                    loadout_parent.spawn((
+                        Text::new(format_loadout_cost(&loadout)),
+                        TextFont {
+                            font_size: 16.0,
+                            ..Default::default()
+                        },
+                        LoadoutCostText,
+                    ));
+                });
 
             // Player list container
             parent
@@ -199,6 +476,106 @@ fn despawn_lobby_ui(mut commands: Commands, lobby_ui_query: Query<Entity, With<L
     }
 }
 
+fn update_bot_settings_text(
+    bot_settings: Query<&MatchBotSettings, Changed<MatchBotSettings>>,
+    mut text_query: Query<&mut Text, With<BotSettingsText>>,
+) {
+    let Some(settings) = bot_settings.iter().next() else {
+        return;
+    };
+
+    for mut text in text_query.iter_mut() {
+        **text = format_bot_settings(settings);
+    }
+}
+
+fn update_aim_assist_text(
+    aim_assist: Query<&AimAssistSettings, Changed<AimAssistSettings>>,
+    mut text_query: Query<&mut Text, With<AimAssistText>>,
+) {
+    let Some(settings) = aim_assist.iter().next() else {
+        return;
+    };
+
+    for mut text in text_query.iter_mut() {
+        **text = settings.label().to_string();
+    }
+}
+
+fn update_team_rules_text(
+    team_rules: Query<&TeamRules, Changed<TeamRules>>,
+    mut text_query: Query<&mut Text, With<TeamRulesText>>,
+) {
+    let Some(rules) = team_rules.iter().next() else {
+        return;
+    };
+
+    for mut text in text_query.iter_mut() {
+        **text = format_team_rules(rules);
+    }
+}
+
+fn format_team_rules(rules: &TeamRules) -> String {
+    if rules.friendly_fire {
+        "Friendly fire: on".to_string()
+    } else {
+        "Friendly fire: off".to_string()
+    }
+}
+
+fn format_bot_settings(settings: &MatchBotSettings) -> String {
+    format!(
+        "Bots: {} ({})",
+        settings.bot_count,
+        settings.difficulty.label()
+    )
+}
+
+fn update_loadout_text(
+    selected_loadout: Res<SelectedLoadout>,
+    mut slot_texts: Query<(&mut Text, &LoadoutSlotText)>,
+    mut cost_texts: Query<&mut Text, (With<LoadoutCostText>, Without<LoadoutSlotText>)>,
+) {
+    if !selected_loadout.is_changed() {
+        return;
+    }
+
+    let loadout = selected_loadout.0;
+    for (mut text, slot_text) in slot_texts.iter_mut() {
+        **text = slot_text.0.describe(&loadout);
+    }
+    for mut text in cost_texts.iter_mut() {
+        **text = format_loadout_cost(&loadout);
+    }
+}
+
+fn update_attachment_text(
+    selected_attachments: Res<SelectedAttachments>,
+    mut slot_texts: Query<(&mut Text, &AttachmentSlotText)>,
+) {
+    if !selected_attachments.is_changed() {
+        return;
+    }
+
+    for (mut text, slot_text) in slot_texts.iter_mut() {
+        **text = describe_attachment(&selected_attachments.0, slot_text.0);
+    }
+}
+
+/// Over-budget loadouts are kept locally but never sent, so the player keeps their last
+/// valid choice until they fix it.
+fn format_loadout_cost(loadout: &Loadout) -> String {
+    if loadout.is_valid() {
+        format!("Cost: {} / {}", loadout.cost(), LOADOUT_BUDGET)
+    } else {
+        format!(
+            "Cost: {} / {} (over budget)",
+            loadout.cost(),
+            LOADOUT_BUDGET
+        )
+    }
+}
+
 #[derive(SystemParam)]
 pub struct LobbyUiQueries<'w, 's> {
     pub status_text: Query<'w, 's, &'static mut Text, With<LobbyStatusText>>,
@@ -286,9 +663,19 @@ fn update_lobby_text(
                     } else {
                         ""
                     };
+                    let team = lobby_data
+                        .teams
+                        .get(player_id)
+                        .map(|team| format!(" [{}]", team.label()))
+                        .unwrap_or_default();
+
+                    let name = lobby_data.profiles.get(player_id).map_or_else(
+                        || format!("Player {}", i + 1),
+                        |profile| profile.name.clone(),
+                    );
 
                     parent.spawn((
-                        Text::new(format!("Player {}{}{}", i + 1, is_host_marker, is_you)),
+                        Text::new(format!("{}{}{}{}", name, is_host_marker, is_you, team)),
                         TextFont {
                             font_size: 18.0,
                             ..Default::default()
@@ -307,9 +694,15 @@ fn update_lobby_text(
 
 #[cfg(test)]
 mod tests {
-    use super::ensure_cursor_visible_in_lobby;
+    use super::{
+        LoadoutSlot, cycle_attachment, describe_attachment, ensure_cursor_visible_in_lobby,
+        format_bot_settings, format_loadout_cost,
+    };
     use bevy::prelude::{App, MinimalPlugins, Update};
     use bevy::window::{CursorGrabMode, CursorOptions, PrimaryWindow};
+    use shared::bots::{BotDifficulty, MatchBotSettings};
+    use shared::components::attachments::{Attachment, AttachmentSlot};
+    use shared::components::loadout::{Equipment, Loadout, PrimaryWeapon};
 
     #[test]
     fn lobby_cursor_is_visible_and_unlocked() {
@@ -339,4 +732,57 @@ mod tests {
         assert_eq!(cursor_options.grab_mode, CursorGrabMode::None);
         assert!(cursor_options.visible);
     }
+
+    #[test]
+    fn bot_settings_text_shows_count_and_difficulty() {
+        let settings = MatchBotSettings {
+            bot_count: 3,
+            difficulty: BotDifficulty::Hard,
+        };
+        assert_eq!(format_bot_settings(&settings), "Bots: 3 (Hard)");
+    }
+
+    #[test]
+    fn loadout_slots_cycle_and_wrap_around() {
+        let mut loadout = Loadout::default();
+
+        for _ in 0..PrimaryWeapon::ALL.len() {
+            LoadoutSlot::Primary.cycle(&mut loadout);
+        }
+        assert_eq!(loadout, Loadout::default());
+
+        LoadoutSlot::Equipment.cycle(&mut loadout);
+        assert_eq!(loadout.equipment, Equipment::ArmorPlate);
+        assert_eq!(
+            LoadoutSlot::Equipment.describe(&loadout),
+            "Equipment: Armor plate"
+        );
+
+        LoadoutSlot::Primary.cycle(&mut loadout);
+        LoadoutSlot::Primary.cycle(&mut loadout);
+        LoadoutSlot::Secondary.cycle(&mut loadout);
+        assert_eq!(format_loadout_cost(&loadout), "Cost: 7 / 5 (over budget)");
+    }
+
+    #[test]
+    fn attachment_slots_cycle_through_nothing() {
+        let mut selected = Vec::new();
+        assert_eq!(
+            describe_attachment(&selected, AttachmentSlot::Optic),
+            "Optic: None"
+        );
+
+        cycle_attachment(&mut selected, AttachmentSlot::Optic);
+        cycle_attachment(&mut selected, AttachmentSlot::Muzzle);
+        assert_eq!(selected, vec![Attachment::RedDot, Attachment::Suppressor]);
+        assert_eq!(
+            describe_attachment(&selected, AttachmentSlot::Optic),
+            "Optic: Red dot"
+        );
+
+        cycle_attachment(&mut selected, AttachmentSlot::Optic);
+        assert_eq!(selected, vec![Attachment::Suppressor, Attachment::Scope]);
+        cycle_attachment(&mut selected, AttachmentSlot::Optic);
+        assert_eq!(selected, vec![Attachment::Suppressor]);
+    }
 }
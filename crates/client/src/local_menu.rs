@@ -1,14 +1,20 @@
 use crate::AutoJoin;
 use crate::ClientGameState;
+use crate::audio::{AudioBus, AudioMixer, MIXER_FADERS, fader_label};
+use crate::resolution::DynamicResolution;
+use crate::settings::{ChangeSetting, Setting, SettingsWindowState};
 use bevy::{
     color::palettes::tailwind::SLATE_800,
     prelude::{
-        AlignItems, App, BackgroundColor, Camera2d, Click, Commands, CommandsStatesExt, Component,
-        Entity, FlexDirection, JustifyContent, Name, Node, On, OnEnter, OnExit, Plugin, Pointer,
-        Query, Res, Text, TextFont, UiRect, Val, With, debug, default,
+        AlignItems, App, BackgroundColor, Camera2d, ChildSpawnerCommands, Click, Commands,
+        CommandsStatesExt, Component, Entity, FlexDirection, IntoScheduleConfigs, JustifyContent,
+        MessageWriter, Name, Node, On, OnEnter, OnExit, Plugin, Pointer, Query, Res, ResMut, Text,
+        TextFont, UiRect, Update, Val, With, debug, default, in_state,
     },
 };
 
+const VOLUME_STEP: f32 = 0.1;
+
 pub struct LocalMenuPlugin;
 
 impl Plugin for LocalMenuPlugin {
@@ -22,6 +28,11 @@ impl Plugin for LocalMenuPlugin {
             OnExit(ClientGameState::LocalMenu),
             (despawn_menu_camera, despawn_main_menu_ui),
         );
+        app.add_systems(
+            Update,
+            (update_volume_texts, update_dynamic_resolution_text)
+                .run_if(in_state(ClientGameState::LocalMenu)),
+        );
     }
 }
 
@@ -63,6 +74,13 @@ pub struct HostButton;
 #[derive(Component)]
 pub struct JoinButton;
 
+/// Volume readout of a mixer fader (`None` is the master fader).
+#[derive(Component)]
+pub struct VolumeText(pub Option<AudioBus>);
+
+#[derive(Component)]
+pub struct DynamicResolutionText;
+
 fn spawn_main_menu_ui(mut commands: Commands, q_main_menu: Query<Entity, With<MainMenu>>) {
     for entity in &q_main_menu {
         commands.entity(entity).despawn();
@@ -109,9 +127,127 @@ fn spawn_main_menu_ui(mut commands: Commands, q_main_menu: Query<Entity, With<Ma
                 .observe(|_click: On<Pointer<Click>>, commands: Commands| {
                     on_join_game(commands);
                 });
+
+            child_builder.spawn((
+                Text::new("Audio"),
+                Node {
+                    padding: UiRect::vertical(Val::Px(10.)),
+                    ..default()
+                },
+            ));
+            for fader in MIXER_FADERS {
+                spawn_volume_row(child_builder, fader);
+            }
+
+            child_builder.spawn((
+                Text::new("Graphics"),
+                Node {
+                    padding: UiRect::vertical(Val::Px(10.)),
+                    ..default()
+                },
+            ));
+            child_builder
+                .spawn((Text::new("Dynamic resolution"), DynamicResolutionText))
+                .observe(
+                    |_click: On<Pointer<Click>>, resolution: Option<ResMut<DynamicResolution>>| {
+                        if let Some(mut resolution) = resolution {
+                            resolution.toggle();
+                        }
+                    },
+                );
+
+            child_builder
+                .spawn((
+                    Text::new("Settings"),
+                    Node {
+                        padding: UiRect::top(Val::Px(20.)),
+                        ..default()
+                    },
+                ))
+                .observe(
+                    |_click: On<Pointer<Click>>, window: Option<ResMut<SettingsWindowState>>| {
+                        if let Some(mut window) = window {
+                            window.visible = true;
+                        }
+                    },
+                );
+        });
+}
+
+fn spawn_volume_row(parent: &mut ChildSpawnerCommands, fader: Option<AudioBus>) {
+    parent
+        .spawn((
+            Node {
+                column_gap: Val::Px(16.),
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            Name::new(format!("{}VolumeRow", fader_label(fader))),
+        ))
+        .with_children(|row| {
+            row.spawn(Text::new("-")).observe(
+                move |_click: On<Pointer<Click>>,
+                      mixer: Option<Res<AudioMixer>>,
+                      changes: MessageWriter<ChangeSetting>| {
+                    step_volume(mixer, changes, fader, -VOLUME_STEP);
+                },
+            );
+            row.spawn((Text::new(fader_label(fader)), VolumeText(fader)));
+            row.spawn(Text::new("+")).observe(
+                move |_click: On<Pointer<Click>>,
+                      mixer: Option<Res<AudioMixer>>,
+                      changes: MessageWriter<ChangeSetting>| {
+                    step_volume(mixer, changes, fader, VOLUME_STEP);
+                },
+            );
         });
 }
 
+fn step_volume(
+    mixer: Option<Res<AudioMixer>>,
+    mut changes: MessageWriter<ChangeSetting>,
+    fader: Option<AudioBus>,
+    delta: f32,
+) {
+    if let Some(mixer) = mixer {
+        let volume = (mixer.fader(fader) + delta).clamp(0.0, 1.0);
+        changes.write(ChangeSetting(Setting::Volume(fader, volume)));
+    }
+}
+
+fn update_volume_texts(mixer: Option<Res<AudioMixer>>, mut texts: Query<(&mut Text, &VolumeText)>) {
+    let Some(mixer) = mixer else {
+        return;
+    };
+
+    for (mut text, volume_text) in texts.iter_mut() {
+        let content = format!(
+            "{}: {:.0}%",
+            fader_label(volume_text.0),
+            mixer.fader(volume_text.0) * 100.0
+        );
+        if **text != content {
+            **text = content;
+        }
+    }
+}
+
+fn update_dynamic_resolution_text(
+    resolution: Option<Res<DynamicResolution>>,
+    mut texts: Query<&mut Text, With<DynamicResolutionText>>,
+) {
+    let Some(resolution) = resolution else {
+        return;
+    };
+
+    for mut text in texts.iter_mut() {
+        let content = resolution.label();
+        if **text != content {
+            **text = content;
+        }
+    }
+}
+
 fn despawn_main_menu_ui(mut commands: Commands, q_main_menu: Query<Entity, With<MainMenu>>) {
     for entity in &q_main_menu {
         commands.entity(entity).despawn();
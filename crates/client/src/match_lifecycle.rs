@@ -0,0 +1,263 @@
+use bevy::prelude::{
+    AlignItems, App, BackgroundColor, Color, Commands, CommandsStatesExt, Component, Entity,
+    FlexDirection, GlobalZIndex, IntoScheduleConfigs, JustifyContent, Name, Node, OnEnter, OnExit,
+    Or, Plugin, Query, Res, ResMut, Resource, State, Text, TextColor, TextFont, UiRect, Update,
+    Val, With, resource_changed,
+};
+use lightyear::prelude::{Client, MessageReceiver};
+use shared::NetworkMode;
+use shared::components::score::MatchScore;
+use shared::level::generation::LevelGeometry;
+use shared::level::preload::LevelPreloader;
+use shared::match_recap::MatchRecapEvent;
+use shared::protocol::{LevelSeed, MatchEndedEvent, PreloadLevelEvent, ReturnToLobbyEvent};
+
+use crate::scoreboard::format_scoreboard;
+use crate::{ClientGameState, Headless, LocalPlayerId};
+
+/// Follows the server through the end of a match: final scores in `PostGame`, then back to
+/// the lobby with the match cleared. Meanwhile the next level is generated in the background
+/// as soon as the server names it.
+pub struct ClientMatchLifecyclePlugin;
+
+impl Plugin for ClientMatchLifecyclePlugin {
+    fn build(&self, app: &mut App) {
+        fn is_not_headless(headless: Option<Res<Headless>>) -> bool {
+            !headless.map(|h| h.0).unwrap_or(false)
+        }
+
+        app.add_systems(
+            Update,
+            (
+                receive_match_ended,
+                receive_return_to_lobby,
+                receive_match_recap,
+                receive_level_preload,
+                show_match_recap.run_if(resource_changed::<MatchRecap>),
+            ),
+        );
+        app.add_systems(
+            OnEnter(ClientGameState::PostGame),
+            spawn_post_game_overlay.run_if(is_not_headless),
+        );
+        app.add_systems(
+            OnExit(ClientGameState::PostGame),
+            (despawn_post_game_overlay, despawn_local_match_entities),
+        );
+    }
+}
+
+/// Scores the server announced when the match ended.
+#[derive(Resource, Clone, Debug, Default)]
+struct FinalScore(MatchScore);
+
+/// Narrative recap of the finished match; may arrive after the results screen is up.
+#[derive(Resource, Clone, Debug, Default)]
+struct MatchRecap(String);
+
+#[derive(Component)]
+struct PostGameOverlay;
+
+#[derive(Component)]
+struct PostGameRecapText;
+
+/// Post-game summary: the winner, then the full scoreboard.
+pub fn format_post_game(score: &MatchScore, local_player_id: u64) -> String {
+    let headline = match score.ranked().first() {
+        Some(best) if best.player_id == local_player_id => "Match over - you win!".to_string(),
+        Some(best) => format!("Match over - Player_{} wins", best.player_id),
+        None => "Match over".to_string(),
+    };
+    format!(
+        "{}\n\n{}",
+        headline,
+        format_scoreboard(score, local_player_id, &[])
+    )
+}
+
+fn receive_match_ended(
+    mut receiver_q: Query<&mut MessageReceiver<MatchEndedEvent>, With<Client>>,
+    state: Res<State<ClientGameState>>,
+    mut commands: Commands,
+) {
+    for mut receiver in receiver_q.iter_mut() {
+        let Some(event) = receiver.receive().last() else {
+            continue;
+        };
+        if !matches!(
+            state.get(),
+            ClientGameState::Playing | ClientGameState::Spectating
+        ) {
+            continue;
+        }
+
+        bevy::log::info!("🏁 Match ended, showing final scores");
+        commands.insert_resource(FinalScore(event.final_score));
+        commands.set_state(ClientGameState::PostGame);
+    }
+}
+
+fn receive_return_to_lobby(
+    mut receiver_q: Query<&mut MessageReceiver<ReturnToLobbyEvent>, With<Client>>,
+    state: Res<State<ClientGameState>>,
+    mut commands: Commands,
+) {
+    for mut receiver in receiver_q.iter_mut() {
+        if receiver.receive().last().is_none() {
+            continue;
+        }
+        if matches!(
+            state.get(),
+            ClientGameState::LocalMenu | ClientGameState::Connecting | ClientGameState::Lobby
+        ) {
+            continue;
+        }
+
+        bevy::log::info!("🏠 Server returned to the lobby");
+        commands.set_state(ClientGameState::Lobby);
+    }
+}
+
+fn receive_match_recap(
+    mut receiver_q: Query<&mut MessageReceiver<MatchRecapEvent>, With<Client>>,
+    mut commands: Commands,
+) {
+    for mut receiver in receiver_q.iter_mut() {
+        if let Some(event) = receiver.receive().last() {
+            commands.insert_resource(MatchRecap(event.text));
+        }
+    }
+}
+
+/// In local host mode the server shares this world and its preloader, and already started.
+fn receive_level_preload(
+    mut receiver_q: Query<&mut MessageReceiver<PreloadLevelEvent>, With<Client>>,
+    network_mode: Res<NetworkMode>,
+    preloader: Option<ResMut<LevelPreloader>>,
+) {
+    let Some(mut preloader) = preloader else {
+        return;
+    };
+    for mut receiver in receiver_q.iter_mut() {
+        let Some(event) = receiver.receive().last() else {
+            continue;
+        };
+        if *network_mode != NetworkMode::Local {
+            preloader.preload(event.seed);
+        }
+    }
+}
+
+fn show_match_recap(
+    recap: Res<MatchRecap>,
+    mut text_query: Query<&mut Text, With<PostGameRecapText>>,
+) {
+    for mut text in text_query.iter_mut() {
+        text.0 = recap.0.clone();
+    }
+}
+
+fn spawn_post_game_overlay(
+    mut commands: Commands,
+    final_score: Option<Res<FinalScore>>,
+    recap: Option<Res<MatchRecap>>,
+    local_player_id: Res<LocalPlayerId>,
+) {
+    let summary = final_score.map_or_else(
+        || "Match over".to_string(),
+        |score| format_post_game(&score.0, local_player_id.0),
+    );
+
+    commands
+        .spawn((
+            Name::new("PostGameOverlay"),
+            PostGameOverlay,
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..Default::default()
+            },
+            BackgroundColor(Color::srgba(0.02, 0.02, 0.04, 0.9)),
+            GlobalZIndex(i32::MAX - 1),
+        ))
+        .with_children(|overlay| {
+            overlay.spawn((
+                Text::new(summary),
+                TextFont {
+                    font_size: 24.0,
+                    ..Default::default()
+                },
+                Node {
+                    padding: UiRect::all(Val::Px(24.0)),
+                    ..Default::default()
+                },
+            ));
+            overlay.spawn((
+                PostGameRecapText,
+                Text::new(recap.map(|recap| recap.0.clone()).unwrap_or_default()),
+                TextFont {
+                    font_size: 18.0,
+                    ..Default::default()
+                },
+                TextColor(Color::srgb(0.8, 0.8, 0.85)),
+                Node {
+                    max_width: Val::Px(720.0),
+                    padding: UiRect::horizontal(Val::Px(24.0)),
+                    ..Default::default()
+                },
+            ));
+        });
+}
+
+fn despawn_post_game_overlay(
+    mut commands: Commands,
+    overlay_query: Query<Entity, With<PostGameOverlay>>,
+) {
+    commands.remove_resource::<FinalScore>();
+    commands.remove_resource::<MatchRecap>();
+    for overlay in &overlay_query {
+        commands.entity(overlay).despawn();
+    }
+}
+
+/// The server despawns replicated match entities itself; the client only clears the level
+/// it built locally, and the level seed so the lobby does not start loading it again before
+/// the server's despawn arrives. In local host mode the world is shared and the server has
+/// already done all of it.
+fn despawn_local_match_entities(
+    mut commands: Commands,
+    network_mode: Res<NetworkMode>,
+    match_entities: Query<Entity, Or<(With<LevelGeometry>, With<LevelSeed>)>>,
+) {
+    if *network_mode == NetworkMode::Local {
+        return;
+    }
+
+    for entity in match_entities.iter() {
+        commands.entity(entity).try_despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_post_game;
+    use shared::components::score::MatchScore;
+
+    #[test]
+    fn post_game_names_the_winner() {
+        let mut score = MatchScore::default();
+        score.record_kill(Some(2), Some(1), &[]);
+
+        let summary = format_post_game(&score, 1);
+        assert!(summary.starts_with("Match over - Player_2 wins"));
+        assert!(summary.contains("Player_1"));
+        assert!(format_post_game(&score, 2).starts_with("Match over - you win!"));
+        assert_eq!(
+            format_post_game(&MatchScore::default(), 1).lines().next(),
+            Some("Match over")
+        );
+    }
+}
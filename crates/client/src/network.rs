@@ -209,7 +209,7 @@ fn start_connection(
         server_addr,
         client_id: client_id.0,
         private_key: SHARED_SETTINGS.private_key,
-        protocol_id: SHARED_SETTINGS.protocol_id,
+        protocol_id: shared::manifest::protocol_hash(),
     };
 
     let netcode_config = NetcodeConfig {
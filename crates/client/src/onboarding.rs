@@ -0,0 +1,368 @@
+use bevy::prelude::{
+    App, Color, Commands, Component, Entity, IntoScheduleConfigs, Name, Node, OnEnter, OnExit,
+    Plugin, PositionType, Query, Res, ResMut, Resource, Text, TextColor, TextFont, Time, Timer,
+    TimerMode, Update, Val, Vec2, With, default, in_state, info, warn,
+};
+use leafwing_input_manager::prelude::{ActionState, InputMap};
+use shared::inputs::input::PlayerAction;
+use shared::protocol::PlayerId;
+use std::path::PathBuf;
+
+use crate::inputs::gamepad::{ActiveInputDevice, gamepad_label};
+use crate::{ClientGameState, Headless, LocalPlayerId, config_file};
+
+/// Seconds the objective hint stays up; it has no action to complete it.
+const OBJECTIVE_HINT_SECS: f32 = 8.0;
+
+/// Tutorial steps, shown one at a time in this order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OnboardingStep {
+    Move,
+    Sprint,
+    Shoot,
+    Objective,
+}
+
+const STEPS: [OnboardingStep; 4] = [
+    OnboardingStep::Move,
+    OnboardingStep::Sprint,
+    OnboardingStep::Shoot,
+    OnboardingStep::Objective,
+];
+
+impl OnboardingStep {
+    /// Hint text for the step, `binding` being the player's key(s) for it.
+    pub fn hint(&self, binding: &str) -> String {
+        match self {
+            OnboardingStep::Move => format!("Use {} to move", binding),
+            OnboardingStep::Sprint => format!("Hold {} while moving to sprint", binding),
+            OnboardingStep::Shoot => format!("Press {} to shoot", binding),
+            OnboardingStep::Objective => {
+                "Find the exit to reach the next level. Stay alive.".to_string()
+            }
+        }
+    }
+}
+
+/// First-run tutorial progress. Once every step is done a marker file is written so the
+/// tutorial never shows again.
+#[derive(Resource, Debug)]
+pub struct Onboarding {
+    /// Marker written when the tutorial is finished; `None` keeps progress in memory only.
+    pub marker_path: Option<PathBuf>,
+    current: Option<usize>,
+    finished: bool,
+    objective_timer: Timer,
+}
+
+impl Default for Onboarding {
+    fn default() -> Self {
+        Self::with_marker(default_marker_path())
+    }
+}
+
+impl Onboarding {
+    pub fn with_marker(marker_path: Option<PathBuf>) -> Self {
+        let finished = marker_path.as_ref().is_some_and(|path| path.exists());
+        Self {
+            marker_path,
+            current: None,
+            finished,
+            objective_timer: Timer::from_seconds(OBJECTIVE_HINT_SECS, TimerMode::Once),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    pub fn current_step(&self) -> Option<OnboardingStep> {
+        self.current.map(|index| STEPS[index])
+    }
+
+    /// Show the first step, unless the tutorial was already completed.
+    pub fn start(&mut self) {
+        if !self.finished && self.current.is_none() {
+            self.current = Some(0);
+        }
+    }
+
+    /// Mark `step` as performed. Only the current step advances the tutorial. Returns
+    /// true when this finished the tutorial.
+    pub fn complete(&mut self, step: OnboardingStep) -> bool {
+        if self.current_step() != Some(step) {
+            return false;
+        }
+
+        let next = self.current.map_or(0, |index| index + 1);
+        if next < STEPS.len() {
+            self.current = Some(next);
+            return false;
+        }
+
+        self.current = None;
+        self.finished = true;
+        if let Some(path) = &self.marker_path {
+            let written = path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|_| std::fs::write(path, "done\n"));
+            if let Err(e) = written {
+                warn!(
+                    "Failed to save tutorial progress to {}: {}",
+                    path.display(),
+                    e
+                );
+            }
+        }
+        true
+    }
+}
+
+/// `$XDG_CONFIG_HOME/yolo-game/onboarding_done`, falling back to `~/.config`.
+pub fn default_marker_path() -> Option<PathBuf> {
+    config_file("onboarding_done")
+}
+
+/// Short label for a binding, from the input's debug name (`KeyR` -> `R`,
+/// `ShiftLeft` -> `ShiftLeft`, mouse `Left` -> `LMB`).
+fn key_labels(debug: &str) -> Vec<String> {
+    debug
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter_map(|token| match token {
+            "Left" => Some("LMB".to_string()),
+            "Right" => Some("RMB".to_string()),
+            "Middle" => Some("MMB".to_string()),
+            _ if token.len() == 4 && token.starts_with("Key") => Some(token[3..].to_string()),
+            _ if token.starts_with("Digit") && token.len() == 6 => Some(token[5..].to_string()),
+            _ if token.starts_with("Arrow")
+                || token.starts_with("Shift")
+                || token.starts_with("Control")
+                || token.starts_with("Alt")
+                || matches!(token, "Space" | "Tab" | "Enter") =>
+            {
+                Some(token.to_string())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// The player's first binding for `action`, as shown in hints.
+pub fn binding_label(input_map: &InputMap<PlayerAction>, action: PlayerAction) -> String {
+    if let Some(binding) = input_map
+        .get_buttonlike(&action)
+        .and_then(|bindings| bindings.first())
+    {
+        return key_labels(&format!("{:?}", binding)).join("+");
+    }
+
+    let Some(binding) = input_map
+        .get_dual_axislike(&action)
+        .and_then(|bindings| bindings.first())
+    else {
+        return "?".to_string();
+    };
+    match key_labels(&format!("{:?}", binding)).as_slice() {
+        // Virtual d-pads list up, down, left, right; show them as up, left, down, right.
+        [up, down, left, right] if [up, down, left, right].iter().all(|k| k.len() == 1) => {
+            format!("{}{}{}{}", up, left, down, right)
+        }
+        [] => "?".to_string(),
+        labels => labels.join("/"),
+    }
+}
+
+pub struct ClientOnboardingPlugin;
+
+impl Plugin for ClientOnboardingPlugin {
+    fn build(&self, app: &mut App) {
+        fn is_not_headless(headless: Option<Res<Headless>>) -> bool {
+            !headless.map(|h| h.0).unwrap_or(false)
+        }
+
+        app.init_resource::<Onboarding>();
+        app.add_systems(
+            OnEnter(ClientGameState::Playing),
+            (start_onboarding, spawn_onboarding_overlay)
+                .chain()
+                .run_if(is_not_headless),
+        );
+        app.add_systems(
+            OnExit(ClientGameState::Playing),
+            despawn_onboarding_overlay.run_if(is_not_headless),
+        );
+        app.add_systems(
+            Update,
+            (track_onboarding_actions, update_onboarding_overlay)
+                .chain()
+                .run_if(in_state(ClientGameState::Playing))
+                .run_if(is_not_headless),
+        );
+    }
+}
+
+#[derive(Component)]
+struct OnboardingText;
+
+fn start_onboarding(mut onboarding: ResMut<Onboarding>) {
+    onboarding.start();
+}
+
+fn spawn_onboarding_overlay(mut commands: Commands, onboarding: Res<Onboarding>) {
+    if onboarding.is_finished() {
+        return;
+    }
+
+    commands.spawn((
+        Name::new("OnboardingOverlay"),
+        OnboardingText,
+        Text::new(""),
+        TextFont {
+            font_size: 22.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 0.95, 0.7)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(20.0),
+            left: Val::Percent(35.0),
+            ..default()
+        },
+    ));
+}
+
+fn despawn_onboarding_overlay(
+    mut commands: Commands,
+    overlay: Query<Entity, With<OnboardingText>>,
+) {
+    for entity in &overlay {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn track_onboarding_actions(
+    time: Res<Time>,
+    local_player_id: Res<LocalPlayerId>,
+    mut onboarding: ResMut<Onboarding>,
+    players: Query<(&PlayerId, &ActionState<PlayerAction>)>,
+) {
+    let Some(step) = onboarding.current_step() else {
+        return;
+    };
+    let Some((_, action_state)) = players
+        .iter()
+        .find(|(player_id, _)| player_id.0.to_bits() == local_player_id.0)
+    else {
+        return;
+    };
+
+    let moving = action_state.axis_pair(&PlayerAction::Move) != Vec2::ZERO;
+    let performed = match step {
+        OnboardingStep::Move => moving,
+        OnboardingStep::Sprint => moving && action_state.pressed(&PlayerAction::Sprint),
+        OnboardingStep::Shoot => action_state.just_pressed(&PlayerAction::Shoot),
+        OnboardingStep::Objective => onboarding.objective_timer.tick(time.delta()).is_finished(),
+    };
+
+    if performed && onboarding.complete(step) {
+        info!("🎓 Tutorial completed");
+    }
+}
+
+fn update_onboarding_overlay(
+    local_player_id: Res<LocalPlayerId>,
+    onboarding: Res<Onboarding>,
+    device: Option<Res<ActiveInputDevice>>,
+    players: Query<(&PlayerId, &InputMap<PlayerAction>)>,
+    mut overlay: Query<&mut Text, With<OnboardingText>>,
+) {
+    let Ok(mut text) = overlay.single_mut() else {
+        return;
+    };
+
+    let content = match onboarding.current_step() {
+        Some(step) => {
+            let input_map = players
+                .iter()
+                .find(|(player_id, _)| player_id.0.to_bits() == local_player_id.0)
+                .map(|(_, input_map)| input_map);
+            let action = match step {
+                OnboardingStep::Move => Some(PlayerAction::Move),
+                OnboardingStep::Sprint => Some(PlayerAction::Sprint),
+                OnboardingStep::Shoot => Some(PlayerAction::Shoot),
+                OnboardingStep::Objective => None,
+            };
+            let on_gamepad = device.is_some_and(|device| *device == ActiveInputDevice::Gamepad);
+            let binding = match (input_map, action) {
+                (_, Some(action)) if on_gamepad => gamepad_label(action).to_string(),
+                (Some(input_map), Some(action)) => binding_label(input_map, action),
+                _ => "?".to_string(),
+            };
+            step.hint(&binding)
+        }
+        None => String::new(),
+    };
+
+    if **text != content {
+        **text = content;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Onboarding, OnboardingStep, binding_label};
+    use crate::inputs::input_map::get_player_input_map;
+    use shared::inputs::input::PlayerAction;
+
+    #[test]
+    fn steps_advance_in_order_and_finish_once() {
+        let mut onboarding = Onboarding::with_marker(None);
+        assert_eq!(onboarding.current_step(), None);
+
+        onboarding.start();
+        assert_eq!(onboarding.current_step(), Some(OnboardingStep::Move));
+        assert!(!onboarding.complete(OnboardingStep::Shoot));
+        assert_eq!(onboarding.current_step(), Some(OnboardingStep::Move));
+
+        assert!(!onboarding.complete(OnboardingStep::Move));
+        assert!(!onboarding.complete(OnboardingStep::Sprint));
+        assert!(!onboarding.complete(OnboardingStep::Shoot));
+        assert!(onboarding.complete(OnboardingStep::Objective));
+        assert!(onboarding.is_finished());
+
+        onboarding.start();
+        assert_eq!(onboarding.current_step(), None);
+    }
+
+    #[test]
+    fn finished_tutorial_is_remembered() {
+        let marker = std::env::temp_dir()
+            .join(format!("yolo-game-onboarding-{}", std::process::id()))
+            .join("onboarding_done");
+        let _ = std::fs::remove_file(&marker);
+
+        let mut onboarding = Onboarding::with_marker(Some(marker.clone()));
+        onboarding.start();
+        for step in super::STEPS {
+            onboarding.complete(step);
+        }
+        assert!(marker.exists());
+
+        let mut next_run = Onboarding::with_marker(Some(marker.clone()));
+        next_run.start();
+        assert!(next_run.is_finished());
+        assert_eq!(next_run.current_step(), None);
+
+        let _ = std::fs::remove_dir_all(marker.parent().unwrap());
+    }
+
+    #[test]
+    fn hints_use_the_player_bindings() {
+        let input_map = get_player_input_map();
+        assert_eq!(binding_label(&input_map, PlayerAction::Reload), "R");
+        assert_eq!(binding_label(&input_map, PlayerAction::Shoot), "LMB");
+        assert_eq!(binding_label(&input_map, PlayerAction::Sprint), "ShiftLeft");
+        assert_eq!(binding_label(&input_map, PlayerAction::Move), "WASD");
+    }
+}
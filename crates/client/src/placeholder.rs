@@ -0,0 +1,279 @@
+//! Placeholders for assets that fail to load. A missing file is logged once and listed in
+//! an on-screen notice in debug builds. Entities drawing a missing mesh, a material or
+//! texture that is missing, or a missing scene show a magenta checker cube instead of
+//! nothing, so a broken asset is obvious in game rather than an invisible entity.
+//!
+//! Substitution covers every `Mesh3d`, `MeshMaterial3d<StandardMaterial>` and `SceneRoot`
+//! entity, whichever system gave it its visuals. Assets built in code have no path and
+//! are never replaced.
+
+use std::collections::BTreeSet;
+
+use bevy::asset::{AssetPath, RenderAssetUsages, UntypedAssetId, UntypedAssetLoadFailedEvent};
+use bevy::image::{Image, ImageSampler};
+use bevy::prelude::{
+    App, AssetServer, Assets, BackgroundColor, Color, Commands, Component, Cuboid, Entity, Handle,
+    IntoScheduleConfigs, Mesh, Mesh3d, MeshMaterial3d, MessageReader, Name, Node, Plugin,
+    PositionType, Query, Ref, Res, ResMut, Resource, SceneRoot, StandardMaterial, Startup, Text,
+    TextFont, UiRect, Update, Val, Visibility, With, Without, default, warn,
+};
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+/// Checker squares per side of the placeholder texture.
+const CHECKER_CELLS: u32 = 8;
+const CHECKER_COLORS: [[u8; 4]; 2] = [[255, 0, 255, 255], [24, 24, 24, 255]];
+/// Paths listed in the notice before the rest are summed up.
+const NOTICE_MAX_PATHS: usize = 8;
+
+pub struct ClientPlaceholderPlugin;
+
+impl Plugin for ClientPlaceholderPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MissingAssets>();
+        app.add_systems(
+            Startup,
+            (create_placeholder_assets, spawn_missing_assets_notice),
+        );
+        app.add_systems(
+            Update,
+            (
+                record_failed_loads,
+                substitute_missing_assets,
+                update_missing_assets_notice,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// The magenta checker cube drawn in place of missing assets.
+#[derive(Resource, Clone, Debug)]
+pub struct PlaceholderAssets {
+    /// A unit cube.
+    pub mesh: Handle<Mesh>,
+    pub material: Handle<StandardMaterial>,
+}
+
+/// Files that failed to load, by path without label: a missing glTF file takes all of
+/// its meshes, materials and scenes with it.
+#[derive(Resource, Debug, Default)]
+pub struct MissingAssets {
+    paths: BTreeSet<String>,
+}
+
+impl MissingAssets {
+    /// Records `path`, returning whether it was new.
+    pub fn report(&mut self, path: &AssetPath) -> bool {
+        self.paths.insert(path.without_label().to_string())
+    }
+
+    pub fn contains(&self, path: &AssetPath) -> bool {
+        self.paths.contains(&path.without_label().to_string())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.paths.iter().map(String::as_str)
+    }
+}
+
+#[derive(Component)]
+struct MissingAssetsNotice;
+
+fn checker_image() -> Image {
+    let data = (0..CHECKER_CELLS * CHECKER_CELLS)
+        .flat_map(|index| {
+            let (x, y) = (index % CHECKER_CELLS, index / CHECKER_CELLS);
+            CHECKER_COLORS[((x + y) % 2) as usize]
+        })
+        .collect();
+    let mut image = Image::new(
+        Extent3d {
+            width: CHECKER_CELLS,
+            height: CHECKER_CELLS,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    image.sampler = ImageSampler::nearest();
+    image
+}
+
+fn create_placeholder_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    commands.insert_resource(PlaceholderAssets {
+        mesh: meshes.add(Cuboid::default()),
+        material: materials.add(StandardMaterial {
+            base_color_texture: Some(images.add(checker_image())),
+            unlit: true,
+            ..default()
+        }),
+    });
+}
+
+fn record_failed_loads(
+    mut failed_loads: MessageReader<UntypedAssetLoadFailedEvent>,
+    mut missing: ResMut<MissingAssets>,
+) {
+    for failed in failed_loads.read() {
+        if missing.report(&failed.path) {
+            warn!(
+                "Missing asset {}, drawing a placeholder where it is used: {}",
+                failed.path.without_label(),
+                failed.error
+            );
+        }
+    }
+}
+
+/// Swaps in the placeholder on entities whose visuals changed, and on every entity when
+/// another file went missing.
+fn substitute_missing_assets(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    missing: Res<MissingAssets>,
+    placeholders: Option<Res<PlaceholderAssets>>,
+    materials: Res<Assets<StandardMaterial>>,
+    meshes: Query<(Entity, Ref<Mesh3d>)>,
+    mesh_materials: Query<(Entity, Ref<MeshMaterial3d<StandardMaterial>>)>,
+    scenes: Query<(Entity, Ref<SceneRoot>), Without<Mesh3d>>,
+) {
+    let Some(placeholders) = placeholders else {
+        return;
+    };
+    if missing.is_empty() {
+        return;
+    }
+    let recheck = missing.is_changed();
+    let is_missing = |id: UntypedAssetId| {
+        asset_server
+            .get_path(id)
+            .is_some_and(|path| missing.contains(&path))
+    };
+    let placeholder = || {
+        (
+            Mesh3d(placeholders.mesh.clone()),
+            MeshMaterial3d(placeholders.material.clone()),
+        )
+    };
+
+    for (entity, mesh) in &meshes {
+        if (recheck || mesh.is_changed()) && is_missing(mesh.0.id().untyped()) {
+            commands.entity(entity).insert(placeholder());
+        }
+    }
+    for (entity, material) in &mesh_materials {
+        if !(recheck || material.is_changed()) || material.0 == placeholders.material {
+            continue;
+        }
+        let textures_missing = materials.get(&material.0).is_some_and(|material| {
+            [
+                &material.base_color_texture,
+                &material.emissive_texture,
+                &material.metallic_roughness_texture,
+                &material.normal_map_texture,
+                &material.occlusion_texture,
+            ]
+            .into_iter()
+            .flatten()
+            .any(|texture| is_missing(texture.id().untyped()))
+        });
+        if textures_missing || is_missing(material.0.id().untyped()) {
+            commands
+                .entity(entity)
+                .insert(MeshMaterial3d(placeholders.material.clone()));
+        }
+    }
+    for (entity, scene) in &scenes {
+        if (recheck || scene.is_changed()) && is_missing(scene.0.id().untyped()) {
+            commands.entity(entity).insert(placeholder());
+        }
+    }
+}
+
+fn spawn_missing_assets_notice(mut commands: Commands) {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+    commands.spawn((
+        Name::new("MissingAssetsNotice"),
+        MissingAssetsNotice,
+        Text::new(""),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        Visibility::Hidden,
+        BackgroundColor(Color::srgba(0.3, 0.0, 0.3, 0.8)),
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(16.0),
+            bottom: Val::Px(16.0),
+            padding: UiRect::all(Val::Px(8.0)),
+            ..default()
+        },
+    ));
+}
+
+fn update_missing_assets_notice(
+    missing: Res<MissingAssets>,
+    mut notice: Query<(&mut Text, &mut Visibility), With<MissingAssetsNotice>>,
+) {
+    if !missing.is_changed() {
+        return;
+    }
+    let Ok((mut text, mut visibility)) = notice.single_mut() else {
+        return;
+    };
+    text.0 = notice_text(&missing);
+    *visibility = if missing.is_empty() {
+        Visibility::Hidden
+    } else {
+        Visibility::Visible
+    };
+}
+
+fn notice_text(missing: &MissingAssets) -> String {
+    let mut text = "Missing assets, drawn as placeholders:".to_string();
+    for path in missing.paths().take(NOTICE_MAX_PATHS) {
+        text.push_str("\n  ");
+        text.push_str(path);
+    }
+    let hidden = missing.paths.len().saturating_sub(NOTICE_MAX_PATHS);
+    if hidden > 0 {
+        text.push_str(&format!("\n  and {} more", hidden));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MissingAssets, notice_text};
+    use bevy::asset::AssetPath;
+
+    #[test]
+    fn missing_files_are_reported_once_whatever_the_label() {
+        let mut missing = MissingAssets::default();
+        assert!(missing.report(&AssetPath::from("scenes/character.glb#Scene0")));
+        assert!(!missing.report(&AssetPath::from("scenes/character.glb")));
+        assert!(missing.contains(&AssetPath::from("scenes/character.glb#Mesh0/Primitive0")));
+        assert!(!missing.contains(&AssetPath::from("scenes/level.glb")));
+
+        for index in 0..10 {
+            missing.report(&AssetPath::from(format!("textures/{}.png", index)));
+        }
+        let notice = notice_text(&missing);
+        assert!(notice.contains("scenes/character.glb"));
+        assert!(notice.ends_with("and 3 more"));
+    }
+}
@@ -0,0 +1,200 @@
+use std::path::PathBuf;
+
+use bevy::color::ColorToPacked;
+use bevy::prelude::{
+    Added, App, Color, DetectChanges, IntoScheduleConfigs, Plugin, Query, Res, ResMut, Resource,
+    Result, Update, With, in_state, info, warn,
+};
+use bevy_inspector_egui::bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use lightyear::prelude::{Client, Connected, MessageSender};
+use shared::components::profile::{MAX_NAME_CHARS, PLAYER_COLORS, PlayerProfile};
+use shared::protocol::{LobbyControlChannel, PlayerProfileRequest};
+
+use crate::{ClientGameState, Headless, config_file};
+
+const PROFILE_FILE: &str = "profile";
+
+/// The name and color this player goes by. They are sent to the server on connect and
+/// again whenever they are edited in the lobby's profile window, and saved between runs.
+pub struct ClientProfilePlugin;
+
+impl Plugin for ClientProfilePlugin {
+    fn build(&self, app: &mut App) {
+        let is_headless = app
+            .world()
+            .get_resource::<Headless>()
+            .is_some_and(|headless| headless.0);
+
+        let profile_path = config_file(PROFILE_FILE);
+        let profile = profile_path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .map_or_else(PlayerProfile::default, |text| profile_from_text(&text));
+        app.insert_resource(ProfileWindowState {
+            name: profile.name.clone(),
+        });
+        app.insert_resource(LocalProfile(profile));
+        app.insert_resource(ProfilePath(profile_path));
+        app.add_systems(Update, (send_profile, save_profile));
+
+        if !is_headless {
+            app.add_systems(
+                EguiPrimaryContextPass,
+                profile_window.run_if(in_state(ClientGameState::Lobby)),
+            );
+        }
+    }
+}
+
+/// Profile the player asked for. The server may hand back a different name, which shows
+/// in the lobby.
+#[derive(Resource, Clone, Debug, Default, PartialEq)]
+pub struct LocalProfile(pub PlayerProfile);
+
+/// Where the profile is saved; `None` keeps it in memory only.
+#[derive(Resource)]
+struct ProfilePath(Option<PathBuf>);
+
+/// Name being typed, applied once the field loses focus.
+#[derive(Resource)]
+struct ProfileWindowState {
+    name: String,
+}
+
+/// Profile read back from its `name=value` lines. Unknown lines are skipped.
+pub fn profile_from_text(text: &str) -> PlayerProfile {
+    let mut profile = PlayerProfile::default();
+    for line in text.lines() {
+        let Some((name, value)) = line.split_once('=') else {
+            continue;
+        };
+        match name.trim() {
+            "name" => profile.name = value.trim().to_string(),
+            "color" => {
+                profile.color = value
+                    .trim()
+                    .parse()
+                    .ok()
+                    .filter(|index: &u8| usize::from(*index) < PLAYER_COLORS.len());
+            }
+            _ => {}
+        }
+    }
+    profile
+}
+
+pub fn profile_to_text(profile: &PlayerProfile) -> String {
+    let mut text = format!("name={}\n", profile.name);
+    if let Some(color) = profile.color {
+        text.push_str(&format!("color={}\n", color));
+    }
+    text
+}
+
+/// Sends the profile when the connection comes up, and again after every edit.
+fn send_profile(
+    profile: Res<LocalProfile>,
+    mut senders: Query<&mut MessageSender<PlayerProfileRequest>, With<Client>>,
+    just_connected: Query<(), (With<Client>, Added<Connected>)>,
+    connected: Query<(), (With<Client>, With<Connected>)>,
+) {
+    let edited = profile.is_changed() && !profile.is_added() && !connected.is_empty();
+    if !edited && just_connected.is_empty() {
+        return;
+    }
+    for mut sender in senders.iter_mut() {
+        info!("🪪 Sending profile: {:?}", profile.0.name);
+        sender.send::<LobbyControlChannel>(PlayerProfileRequest {
+            profile: profile.0.clone(),
+        });
+    }
+}
+
+fn save_profile(profile: Res<LocalProfile>, profile_path: Res<ProfilePath>) {
+    if !profile.is_changed() || profile.is_added() {
+        return;
+    }
+    let Some(path) = &profile_path.0 else {
+        return;
+    };
+    let written = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(path, profile_to_text(&profile.0)));
+    if let Err(e) = written {
+        warn!("Failed to save profile to {}: {}", path.display(), e);
+    }
+}
+
+fn color32(color: Color) -> egui::Color32 {
+    let [r, g, b, _] = color.to_srgba().to_u8_array();
+    egui::Color32::from_rgb(r, g, b)
+}
+
+fn profile_window(
+    mut contexts: EguiContexts,
+    mut profile: ResMut<LocalProfile>,
+    mut window_state: ResMut<ProfileWindowState>,
+) -> Result {
+    let mut color = profile.0.color;
+    let mut name_done = false;
+
+    egui::Window::new("Profile")
+        .resizable(false)
+        .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-12.0, 12.0))
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Name");
+                let field = ui.add(
+                    egui::TextEdit::singleline(&mut window_state.name)
+                        .char_limit(MAX_NAME_CHARS)
+                        .hint_text("Player"),
+                );
+                name_done = field.lost_focus();
+            });
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut color, None, "Auto");
+                for (index, swatch) in PLAYER_COLORS.iter().enumerate() {
+                    let index = index as u8;
+                    let selected = color == Some(index);
+                    let button =
+                        egui::Button::new("  ")
+                            .fill(color32(*swatch))
+                            .stroke(egui::Stroke::new(
+                                if selected { 2.0 } else { 0.0 },
+                                egui::Color32::WHITE,
+                            ));
+                    if ui.add(button).clicked() {
+                        color = Some(index);
+                    }
+                }
+            });
+        });
+
+    if name_done && window_state.name != profile.0.name {
+        profile.0.name = window_state.name.clone();
+    }
+    if color != profile.0.color {
+        profile.0.color = color;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{profile_from_text, profile_to_text};
+    use shared::components::profile::PlayerProfile;
+
+    #[test]
+    fn profile_round_trips_and_drops_unknown_colors() {
+        let profile = PlayerProfile {
+            name: "Ada = Lovelace".to_string(),
+            color: Some(3),
+        };
+        assert_eq!(profile_from_text(&profile_to_text(&profile)), profile);
+
+        let unknown = profile_from_text("name=Ada\ncolor=99\nskin=3\n");
+        assert_eq!(unknown.name, "Ada");
+        assert_eq!(unknown.color, None);
+    }
+}
@@ -0,0 +1,262 @@
+use bevy::camera::MainPassResolutionOverride;
+use bevy::prelude::{
+    App, Commands, Component, DetectChanges, DetectChangesMut, Entity, IntoScheduleConfigs, Name,
+    Node, Plugin, PositionType, Query, Ref, Res, ResMut, Resource, Startup, Text, TextFont, Time,
+    UVec2, Update, Val, Visibility, With, Without,
+};
+use bevy::window::{PrimaryWindow, Window};
+
+use crate::camera::PlayerCamera;
+
+/// Frame time is averaged over roughly this many seconds before it is compared to the
+/// budget.
+const FRAME_TIME_SMOOTHING_SECS: f32 = 0.5;
+/// How often the render scale may change. Changing it every frame makes the image shimmer.
+const ADJUST_INTERVAL_SECS: f32 = 0.5;
+/// Render scale change per adjustment.
+const SCALE_STEP: f32 = 0.05;
+/// Frames slower than the budget by this factor lower the scale...
+const OVER_BUDGET: f32 = 1.05;
+/// ...and frames faster than this fraction of the budget raise it again.
+const UNDER_BUDGET: f32 = 0.85;
+
+/// Render the 3D view below the window resolution when frames run over budget, so the
+/// game holds its target frame rate on weaker GPUs. The UI always renders at full
+/// resolution.
+pub struct ClientResolutionPlugin;
+
+impl Plugin for ClientResolutionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DynamicResolution>();
+        app.add_systems(Startup, spawn_resolution_indicator);
+        app.add_systems(
+            Update,
+            (
+                adjust_render_scale,
+                apply_render_scale,
+                update_resolution_indicator,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// Dynamic resolution settings and the current render scale. Toggled from the main menu;
+/// the bounds and target can be set from the launcher.
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct DynamicResolution {
+    pub enabled: bool,
+    pub target_fps: f32,
+    /// Lowest fraction of the window resolution the 3D view is rendered at.
+    pub min_scale: f32,
+    pub max_scale: f32,
+    /// Current fraction of the window resolution.
+    pub scale: f32,
+    average_frame_secs: f32,
+    since_adjust_secs: f32,
+}
+
+impl Default for DynamicResolution {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            target_fps: 60.0,
+            min_scale: 0.5,
+            max_scale: 1.0,
+            scale: 1.0,
+            average_frame_secs: 0.0,
+            since_adjust_secs: 0.0,
+        }
+    }
+}
+
+impl DynamicResolution {
+    pub fn with_bounds(target_fps: f32, min_scale: f32, max_scale: f32) -> Self {
+        let min_scale = min_scale.clamp(0.1, 1.0);
+        Self {
+            target_fps: target_fps.max(1.0),
+            min_scale,
+            max_scale: max_scale.clamp(min_scale, 1.0),
+            scale: max_scale.clamp(min_scale, 1.0),
+            ..Self::default()
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+    }
+
+    pub fn label(&self) -> String {
+        if self.enabled {
+            format!("Dynamic resolution: on ({:.0} FPS)", self.target_fps)
+        } else {
+            "Dynamic resolution: off".to_string()
+        }
+    }
+
+    /// Feed one frame's duration. Returns true when the render scale changed.
+    pub fn record_frame(&mut self, frame_secs: f32) -> bool {
+        if frame_secs <= 0.0 {
+            return false;
+        }
+
+        let blend = (frame_secs / FRAME_TIME_SMOOTHING_SECS).min(1.0);
+        self.average_frame_secs = if self.average_frame_secs == 0.0 {
+            frame_secs
+        } else {
+            self.average_frame_secs + (frame_secs - self.average_frame_secs) * blend
+        };
+
+        self.since_adjust_secs += frame_secs;
+        if self.since_adjust_secs < ADJUST_INTERVAL_SECS {
+            return false;
+        }
+        self.since_adjust_secs = 0.0;
+
+        let scale = next_render_scale(
+            self.scale,
+            self.average_frame_secs,
+            1.0 / self.target_fps,
+            self.min_scale,
+            self.max_scale,
+        );
+        let changed = scale != self.scale;
+        self.scale = scale;
+        changed
+    }
+}
+
+/// Step the render scale down when frames run over `budget_secs`, and back up when there
+/// is comfortable headroom. Always stays within `min..=max`.
+pub fn next_render_scale(scale: f32, frame_secs: f32, budget_secs: f32, min: f32, max: f32) -> f32 {
+    let scale = if frame_secs > budget_secs * OVER_BUDGET {
+        scale - SCALE_STEP
+    } else if frame_secs < budget_secs * UNDER_BUDGET {
+        scale + SCALE_STEP
+    } else {
+        scale
+    };
+    scale.clamp(min, max)
+}
+
+#[derive(Component)]
+struct ResolutionIndicator;
+
+fn spawn_resolution_indicator(mut commands: Commands) {
+    commands.spawn((
+        Name::new("ResolutionIndicator"),
+        ResolutionIndicator,
+        Text::new(""),
+        TextFont {
+            font_size: 14.0,
+            ..Default::default()
+        },
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(8.0),
+            top: Val::Px(8.0),
+            ..Default::default()
+        },
+        Visibility::Hidden,
+    ));
+}
+
+/// Frames are paced by the GPU whenever it is the bottleneck, so the frame delta is the
+/// frame time the render scale can actually win back.
+fn adjust_render_scale(time: Res<Time>, mut resolution: ResMut<DynamicResolution>) {
+    if !resolution.enabled {
+        return;
+    }
+
+    // Only flag a change when the scale moves, so the camera and indicator are not touched
+    // every frame.
+    if resolution
+        .bypass_change_detection()
+        .record_frame(time.delta_secs())
+    {
+        resolution.set_changed();
+    }
+}
+
+fn apply_render_scale(
+    mut commands: Commands,
+    resolution: Res<DynamicResolution>,
+    window: Query<Ref<Window>, With<PrimaryWindow>>,
+    scaled_cameras: Query<Entity, (With<PlayerCamera>, With<MainPassResolutionOverride>)>,
+    new_cameras: Query<Entity, (With<PlayerCamera>, Without<MainPassResolutionOverride>)>,
+) {
+    let Ok(window) = window.single() else {
+        return;
+    };
+
+    let full_resolution = !resolution.enabled || resolution.scale >= 1.0;
+    if full_resolution {
+        for camera in &scaled_cameras {
+            commands
+                .entity(camera)
+                .remove::<MainPassResolutionOverride>();
+        }
+        return;
+    }
+
+    if !resolution.is_changed() && !window.is_changed() && new_cameras.is_empty() {
+        return;
+    }
+
+    let size = (UVec2::new(window.physical_width(), window.physical_height()).as_vec2()
+        * resolution.scale)
+        .as_uvec2()
+        .max(UVec2::ONE);
+    for camera in scaled_cameras.iter().chain(new_cameras.iter()) {
+        commands
+            .entity(camera)
+            .insert(MainPassResolutionOverride(size));
+    }
+}
+
+fn update_resolution_indicator(
+    resolution: Res<DynamicResolution>,
+    mut indicator: Query<(&mut Text, &mut Visibility), With<ResolutionIndicator>>,
+) {
+    if !resolution.is_changed() {
+        return;
+    }
+
+    for (mut text, mut visibility) in indicator.iter_mut() {
+        *visibility = if resolution.enabled && resolution.scale < 1.0 {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        **text = format!("Render {:.0}%", resolution.scale * 100.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DynamicResolution, next_render_scale};
+
+    #[test]
+    fn scale_steps_toward_budget_within_bounds() {
+        let budget = 1.0 / 60.0;
+        assert!(next_render_scale(1.0, 1.0 / 30.0, budget, 0.5, 1.0) < 1.0);
+        assert_eq!(next_render_scale(0.5, 1.0 / 20.0, budget, 0.5, 1.0), 0.5);
+        assert!(next_render_scale(0.7, 1.0 / 120.0, budget, 0.5, 1.0) > 0.7);
+        assert_eq!(next_render_scale(1.0, 1.0 / 120.0, budget, 0.5, 1.0), 1.0);
+        assert_eq!(next_render_scale(0.8, budget, budget, 0.5, 1.0), 0.8);
+    }
+
+    #[test]
+    fn sustained_slow_frames_lower_the_scale_to_the_minimum() {
+        let mut resolution = DynamicResolution::with_bounds(60.0, 0.6, 1.0);
+        for _ in 0..600 {
+            resolution.record_frame(1.0 / 25.0);
+        }
+        assert_eq!(resolution.scale, 0.6);
+
+        for _ in 0..2000 {
+            resolution.record_frame(1.0 / 200.0);
+        }
+        assert_eq!(resolution.scale, 1.0);
+    }
+}
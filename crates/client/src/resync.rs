@@ -0,0 +1,181 @@
+use avian3d::prelude::{Position, Rotation};
+use bevy::prelude::{App, Entity, Plugin, Query, ResMut, Resource, Update, With, info};
+use std::collections::HashMap;
+
+use lightyear::prelude::{Client, Confirmed, MessageReceiver, MessageSender};
+use shared::components::health::Health;
+use shared::protocol::LobbyControlChannel;
+use shared::resync::{
+    CriticalState, RESYNC_MISMATCH_ROUNDS, ResyncChecksum, ResyncKeyframe, ResyncRequest,
+};
+
+/// Checks the server's periodic checksums against the local copy of each character, and
+/// overwrites it with the server's keyframe when it keeps disagreeing.
+pub struct ClientResyncPlugin;
+
+impl Plugin for ClientResyncPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ResyncMismatches>();
+        app.add_systems(Update, (validate_resync_checksums, apply_resync_keyframes));
+    }
+}
+
+/// Consecutive mismatching rounds per entity.
+#[derive(Resource, Default, Debug)]
+pub struct ResyncMismatches(pub HashMap<Entity, u32>);
+
+impl ResyncMismatches {
+    /// Record one round's result for `entity`. Returns true when the entity has mismatched
+    /// long enough to ask for a keyframe; the count then starts over.
+    pub fn record(&mut self, entity: Entity, matches: bool) -> bool {
+        if matches {
+            self.0.remove(&entity);
+            return false;
+        }
+
+        let rounds = self.0.entry(entity).or_default();
+        *rounds += 1;
+        if *rounds >= RESYNC_MISMATCH_ROUNDS {
+            self.0.remove(&entity);
+            return true;
+        }
+        false
+    }
+}
+
+/// The last server state the client received: the `Confirmed` copy on predicted entities,
+/// the component itself elsewhere.
+type ReplicatedStateData<'a> = (
+    Option<&'a Position>,
+    Option<&'a Confirmed<Position>>,
+    Option<&'a Rotation>,
+    Option<&'a Confirmed<Rotation>>,
+    Option<&'a Health>,
+    Option<&'a Confirmed<Health>>,
+);
+
+fn replicated_state(row: ReplicatedStateData) -> Option<CriticalState> {
+    let (position, confirmed_position, rotation, confirmed_rotation, health, confirmed_health) =
+        row;
+    let position = confirmed_position.map(|c| &c.0).or(position)?;
+    let rotation = confirmed_rotation.map(|c| &c.0).or(rotation)?;
+    let health = confirmed_health.map(|c| &c.0).or(health);
+    Some(CriticalState {
+        position: position.0,
+        rotation: rotation.0,
+        health: health.cloned(),
+    })
+}
+
+fn validate_resync_checksums(
+    mut receiver_q: Query<&mut MessageReceiver<ResyncChecksum>, With<Client>>,
+    mut sender_q: Query<&mut MessageSender<ResyncRequest>, With<Client>>,
+    states: Query<ReplicatedStateData>,
+    mut mismatches: ResMut<ResyncMismatches>,
+) {
+    for mut receiver in receiver_q.iter_mut() {
+        for checksum in receiver.receive() {
+            let mut mismatched = Vec::new();
+            for (index, entry) in checksum.entries.iter().enumerate() {
+                // Entities not replicated to this client yet have nothing to compare.
+                let Some(state) = states.get(entry.entity).ok().and_then(replicated_state) else {
+                    continue;
+                };
+                if mismatches.record(
+                    entry.entity,
+                    state.checksum(entry.at_rest) == entry.checksum,
+                ) {
+                    mismatched.push(index as u32);
+                }
+            }
+            mismatches
+                .0
+                .retain(|entity, _| checksum.entries.iter().any(|e| e.entity == *entity));
+
+            if mismatched.is_empty() {
+                continue;
+            }
+            info!(
+                "Resync: {} entities drifted from the server, requesting a keyframe",
+                mismatched.len()
+            );
+            if let Some(mut sender) = sender_q.iter_mut().next() {
+                sender.send::<LobbyControlChannel>(ResyncRequest {
+                    round: checksum.round,
+                    mismatched,
+                });
+            }
+        }
+    }
+}
+
+fn apply_resync_keyframes(
+    mut receiver_q: Query<&mut MessageReceiver<ResyncKeyframe>, With<Client>>,
+    mut states: Query<(
+        Option<&mut Position>,
+        Option<&mut Confirmed<Position>>,
+        Option<&mut Rotation>,
+        Option<&mut Confirmed<Rotation>>,
+        Option<&mut Health>,
+        Option<&mut Confirmed<Health>>,
+    )>,
+) {
+    for mut receiver in receiver_q.iter_mut() {
+        for keyframe in receiver.receive() {
+            for entry in keyframe.entries {
+                let Ok((
+                    position,
+                    confirmed_position,
+                    rotation,
+                    confirmed_rotation,
+                    health,
+                    confirmed_health,
+                )) = states.get_mut(entry.entity)
+                else {
+                    continue;
+                };
+
+                // Predicted entities keep the server state in `Confirmed`; correcting it
+                // too stops the next rollback from bringing the drift back.
+                if let Some(mut position) = position {
+                    position.0 = entry.state.position;
+                }
+                if let Some(mut confirmed) = confirmed_position {
+                    confirmed.0.0 = entry.state.position;
+                }
+                if let Some(mut rotation) = rotation {
+                    rotation.0 = entry.state.rotation;
+                }
+                if let Some(mut confirmed) = confirmed_rotation {
+                    confirmed.0.0 = entry.state.rotation;
+                }
+                if let Some(server_health) = entry.state.health {
+                    if let Some(mut health) = health {
+                        *health = server_health.clone();
+                    }
+                    if let Some(mut confirmed) = confirmed_health {
+                        confirmed.0 = server_health;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ResyncMismatches;
+    use bevy::prelude::Entity;
+
+    #[test]
+    fn keyframes_are_requested_only_for_persistent_mismatches() {
+        let mut mismatches = ResyncMismatches::default();
+        let entity = Entity::PLACEHOLDER;
+
+        assert!(!mismatches.record(entity, false));
+        assert!(!mismatches.record(entity, true));
+        assert!(!mismatches.record(entity, false));
+        assert!(mismatches.record(entity, false));
+        assert!(mismatches.0.is_empty());
+    }
+}
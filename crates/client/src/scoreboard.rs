@@ -0,0 +1,338 @@
+use bevy::prelude::{
+    App, BackgroundColor, ButtonInput, Color, Commands, Component, Entity, IntoScheduleConfigs,
+    KeyCode, Name, Node, OnEnter, Plugin, PositionType, Query, Res, ResMut, Resource, State, Text,
+    TextFont, Time, UiRect, Update, Val, Visibility, With, info,
+};
+use lightyear::prelude::{Client, MessageReceiver};
+use shared::afk::Afk;
+use shared::components::score::MatchScore;
+use shared::protocol::{BotChatMessage, KillFeedEvent, PlayerId, ServerAnnouncement};
+use std::collections::VecDeque;
+
+use crate::{ClientGameState, Headless, LocalPlayerId};
+
+const KILL_FEED_MAX_ENTRIES: usize = 5;
+const KILL_FEED_ENTRY_SECS: f32 = 6.0;
+
+pub struct ClientScoreboardPlugin;
+
+impl Plugin for ClientScoreboardPlugin {
+    fn build(&self, app: &mut App) {
+        fn is_not_headless(headless: Option<Res<Headless>>) -> bool {
+            !headless.map(|h| h.0).unwrap_or(false)
+        }
+
+        fn in_match(state: Res<State<ClientGameState>>) -> bool {
+            matches!(
+                state.get(),
+                ClientGameState::Playing | ClientGameState::Spectating
+            )
+        }
+
+        app.init_resource::<KillFeed>();
+        app.add_systems(
+            OnEnter(ClientGameState::Playing),
+            spawn_score_overlay.run_if(is_not_headless),
+        );
+        app.add_systems(
+            OnEnter(ClientGameState::Spectating),
+            spawn_score_overlay.run_if(is_not_headless),
+        );
+        app.add_systems(OnEnter(ClientGameState::Lobby), despawn_score_overlay);
+        app.add_systems(
+            Update,
+            (receive_bot_chat, receive_kill_feed_events)
+                .chain()
+                .run_if(in_match),
+        );
+        app.add_systems(Update, receive_server_announcements);
+        app.add_systems(
+            Update,
+            (update_kill_feed_text, update_scoreboard)
+                .run_if(in_match)
+                .run_if(is_not_headless),
+        );
+    }
+}
+
+/// Most recent kills, server announcements and bot chat, newest last, with the time they were
+/// received.
+#[derive(Resource, Default)]
+pub struct KillFeed(pub VecDeque<(String, f32)>);
+
+#[derive(Component)]
+struct ScoreOverlayRoot;
+
+#[derive(Component)]
+struct KillFeedText;
+
+#[derive(Component)]
+struct ScoreboardPanel;
+
+#[derive(Component)]
+struct ScoreboardText;
+
+pub fn format_kill_feed_entry(event: &KillFeedEvent) -> String {
+    let headshot = if event.headshot { " (headshot)" } else { "" };
+    match &event.killer {
+        Some(killer) => format!("{} killed {}{}", killer, event.victim, headshot),
+        None => format!("{} died", event.victim),
+    }
+}
+
+/// Scoreboard rows, best player first. The local player is marked with `>`, idle players
+/// listed in `afk` get an `(AFK)` tag.
+pub fn format_scoreboard(score: &MatchScore, local_player_id: u64, afk: &[u64]) -> String {
+    let mut lines = vec![format!(
+        "  {:<16} {:>5} {:>6} {:>7}",
+        "Player", "Kills", "Deaths", "Assists"
+    )];
+    for entry in score.ranked() {
+        let marker = if entry.player_id == local_player_id {
+            '>'
+        } else {
+            ' '
+        };
+        let name = format!("Player_{}", entry.player_id);
+        let tag = if afk.contains(&entry.player_id) {
+            " (AFK)"
+        } else {
+            ""
+        };
+        lines.push(format!(
+            "{} {:<16} {:>5} {:>6} {:>7}{}",
+            marker, name, entry.kills, entry.deaths, entry.assists, tag
+        ));
+    }
+    lines.join("\n")
+}
+
+fn spawn_score_overlay(mut commands: Commands, existing: Query<Entity, With<ScoreOverlayRoot>>) {
+    if !existing.is_empty() {
+        return;
+    }
+
+    commands
+        .spawn((
+            Name::new("ScoreOverlay"),
+            ScoreOverlayRoot,
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Name::new("KillFeedText"),
+                KillFeedText,
+                Text::new(""),
+                TextFont {
+                    font_size: 18.0,
+                    ..Default::default()
+                },
+                Node {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(24.0),
+                    top: Val::Px(24.0),
+                    ..Default::default()
+                },
+            ));
+
+            parent
+                .spawn((
+                    Name::new("ScoreboardPanel"),
+                    ScoreboardPanel,
+                    Visibility::Hidden,
+                    BackgroundColor(Color::srgba(0.05, 0.05, 0.08, 0.85)),
+                    Node {
+                        position_type: PositionType::Absolute,
+                        left: Val::Percent(30.0),
+                        top: Val::Percent(20.0),
+                        width: Val::Percent(40.0),
+                        padding: UiRect::all(Val::Px(16.0)),
+                        ..Default::default()
+                    },
+                ))
+                .with_child((
+                    ScoreboardText,
+                    Text::new(""),
+                    TextFont {
+                        font_size: 20.0,
+                        ..Default::default()
+                    },
+                ));
+        });
+}
+
+fn despawn_score_overlay(
+    mut commands: Commands,
+    overlay_query: Query<Entity, With<ScoreOverlayRoot>>,
+    mut kill_feed: ResMut<KillFeed>,
+) {
+    for overlay in &overlay_query {
+        commands.entity(overlay).despawn();
+    }
+    kill_feed.0.clear();
+}
+
+fn receive_kill_feed_events(
+    time: Res<Time>,
+    mut receiver_q: Query<&mut MessageReceiver<KillFeedEvent>, With<Client>>,
+    mut kill_feed: ResMut<KillFeed>,
+) {
+    let now = time.elapsed_secs();
+    for mut receiver in receiver_q.iter_mut() {
+        for event in receiver.receive() {
+            kill_feed.0.push_back((format_kill_feed_entry(&event), now));
+        }
+    }
+
+    while kill_feed.0.len() > KILL_FEED_MAX_ENTRIES {
+        kill_feed.0.pop_front();
+    }
+    while kill_feed
+        .0
+        .front()
+        .is_some_and(|(_, received)| now - received > KILL_FEED_ENTRY_SECS)
+    {
+        kill_feed.0.pop_front();
+    }
+}
+
+/// Operator messages from the server console. They join the kill feed during a match;
+/// outside one they are only logged.
+fn receive_server_announcements(
+    time: Res<Time>,
+    state: Res<State<ClientGameState>>,
+    mut receiver_q: Query<&mut MessageReceiver<ServerAnnouncement>, With<Client>>,
+    mut kill_feed: ResMut<KillFeed>,
+) {
+    let in_match = matches!(
+        state.get(),
+        ClientGameState::Playing | ClientGameState::Spectating
+    );
+    for mut receiver in receiver_q.iter_mut() {
+        for announcement in receiver.receive() {
+            info!("📢 [Server] {}", announcement.text);
+            if in_match {
+                kill_feed.0.push_back((
+                    format!("[Server] {}", announcement.text),
+                    time.elapsed_secs(),
+                ));
+            }
+        }
+    }
+}
+
+/// Bot taunts and callouts join the kill feed under the bot's name.
+fn receive_bot_chat(
+    time: Res<Time>,
+    mut receiver_q: Query<&mut MessageReceiver<BotChatMessage>, With<Client>>,
+    mut kill_feed: ResMut<KillFeed>,
+) {
+    for mut receiver in receiver_q.iter_mut() {
+        for message in receiver.receive() {
+            kill_feed.0.push_back((
+                format!("{}: {}", message.speaker, message.text),
+                time.elapsed_secs(),
+            ));
+        }
+    }
+}
+
+fn update_kill_feed_text(
+    kill_feed: Res<KillFeed>,
+    mut text_query: Query<&mut Text, With<KillFeedText>>,
+) {
+    if !kill_feed.is_changed() {
+        return;
+    }
+
+    for mut text in text_query.iter_mut() {
+        **text = kill_feed
+            .0
+            .iter()
+            .map(|(entry, _)| entry.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+}
+
+/// Show the scoreboard while Tab is held.
+fn update_scoreboard(
+    keys: Res<ButtonInput<KeyCode>>,
+    local_player_id: Res<LocalPlayerId>,
+    score_query: Query<&MatchScore>,
+    afk_query: Query<&PlayerId, With<Afk>>,
+    mut panel_query: Query<&mut Visibility, With<ScoreboardPanel>>,
+    mut text_query: Query<&mut Text, With<ScoreboardText>>,
+) {
+    let visible = keys.pressed(KeyCode::Tab);
+    for mut visibility in panel_query.iter_mut() {
+        visibility.set_if_neq(if visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+
+    if !visible {
+        return;
+    }
+
+    let Some(score) = score_query.iter().next() else {
+        return;
+    };
+    let afk: Vec<u64> = afk_query.iter().map(|id| id.0.to_bits()).collect();
+    let content = format_scoreboard(score, local_player_id.0, &afk);
+    for mut text in text_query.iter_mut() {
+        if **text != content {
+            **text = content.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_kill_feed_entry, format_scoreboard};
+    use shared::components::score::MatchScore;
+    use shared::protocol::KillFeedEvent;
+
+    #[test]
+    fn kill_feed_entry_mentions_killer_victim_and_headshot() {
+        let event = KillFeedEvent {
+            killer: Some("Player_1".to_string()),
+            victim: "Bot_3".to_string(),
+            headshot: true,
+        };
+        assert_eq!(
+            format_kill_feed_entry(&event),
+            "Player_1 killed Bot_3 (headshot)"
+        );
+
+        let environment = KillFeedEvent {
+            killer: None,
+            ..event
+        };
+        assert_eq!(format_kill_feed_entry(&environment), "Bot_3 died");
+    }
+
+    #[test]
+    fn scoreboard_lists_best_player_first_and_marks_local_player() {
+        let mut score = MatchScore::default();
+        score.record_kill(Some(2), Some(1), &[]);
+
+        let lines: Vec<String> = format_scoreboard(&score, 1, &[2])
+            .lines()
+            .map(str::to_string)
+            .collect();
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].starts_with("  Player_2"));
+        assert!(lines[1].ends_with(" (AFK)"));
+        assert!(lines[2].starts_with("> Player_1"));
+        assert!(!lines[2].contains("AFK"));
+    }
+}
@@ -0,0 +1,43 @@
+use bevy::prelude::{Added, App, Plugin, Query, Res, ResMut, Resource, Update, With, info};
+use lightyear::prelude::{Client, Connected, MessageReceiver, MessageSender};
+use shared::protocol::{LobbyControlChannel, ResumeSessionRequest, SessionGranted};
+
+/// Keeps the session token the server granted, and presents it after a reconnect so the
+/// server hands back the character this client left behind.
+pub struct ClientSessionPlugin;
+
+impl Plugin for ClientSessionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SessionToken>();
+        app.add_systems(Update, (receive_session_grant, resume_session_on_reconnect));
+    }
+}
+
+/// Latest token from the server. Survives disconnects, unlike the client entity's state.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub struct SessionToken(pub Option<u64>);
+
+fn receive_session_grant(
+    mut receiver_q: Query<&mut MessageReceiver<SessionGranted>, With<Client>>,
+    mut session_token: ResMut<SessionToken>,
+) {
+    for mut receiver in receiver_q.iter_mut() {
+        if let Some(grant) = receiver.receive().last() {
+            session_token.0 = Some(grant.token);
+        }
+    }
+}
+
+fn resume_session_on_reconnect(
+    session_token: Res<SessionToken>,
+    mut sender_q: Query<&mut MessageSender<ResumeSessionRequest>, (With<Client>, Added<Connected>)>,
+) {
+    let Some(token) = session_token.0 else {
+        return;
+    };
+
+    for mut sender in sender_q.iter_mut() {
+        info!("🔌 Reconnected, resuming the previous session");
+        sender.send::<LobbyControlChannel>(ResumeSessionRequest { token });
+    }
+}
@@ -0,0 +1,882 @@
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::{
+    App, ButtonInput, DetectChanges, IntoScheduleConfigs, KeyCode, Message, MessageReader,
+    MessageWriter, MouseButton, Plugin, Projection, Query, Res, ResMut, Resource, Result, Startup,
+    Time, Update, Window, With, in_state, warn,
+};
+use bevy::window::{
+    CursorOptions, MonitorSelection, PrimaryWindow, VideoModeSelection, WindowMode,
+};
+use bevy_inspector_egui::bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use leafwing_input_manager::prelude::InputMap;
+use serde::{Deserialize, Serialize};
+use shared::inputs::input::PlayerAction;
+
+use crate::ClientGameState;
+use crate::audio::{AudioBus, AudioMixer, MIXER_FADERS, fader_label};
+use crate::camera::PlayerCamera;
+use crate::config_file;
+use crate::inputs::gamepad::{DEADZONE_RANGE, EXPONENT_RANGE, OUTER_DEADZONE_RANGE, StickCurve};
+use crate::inputs::input_map::{BINDABLE_MOUSE_BUTTONS, Binding, Control, KeyBindings};
+use crate::inputs::window::is_cursor_locked;
+
+const SETTINGS_FILE: &str = "settings.ron";
+
+/// Vertical field of view, in degrees.
+pub const FOV_RANGE_DEGREES: RangeInclusive<f32> = 30.0..=90.0;
+/// Multiplier on the mouse look speed.
+pub const SENSITIVITY_RANGE: RangeInclusive<f32> = 0.1..=5.0;
+const VOLUME_RANGE: RangeInclusive<f32> = 0.0..=1.0;
+/// A new display mode reverts unless confirmed within this many seconds, so a mode the
+/// monitor cannot show does not leave the player stuck on a black screen.
+pub const DISPLAY_CONFIRM_SECS: f32 = 10.0;
+
+/// Settings applied while the game runs, from the menu or mid-match, without a restart.
+/// Every change goes through [`ChangeSetting`]: it is validated, applied to what it
+/// controls, put back if that fails, and saved once it sticks. The settings window is
+/// toggled with F10, or opened from the main menu and the in-game menu.
+pub struct ClientSettingsPlugin;
+
+impl Plugin for ClientSettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let is_headless = app
+            .world()
+            .get_resource::<crate::Headless>()
+            .is_some_and(|headless| headless.0);
+
+        let settings_path = config_file(SETTINGS_FILE);
+        let settings = settings_path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .map_or_else(GameSettings::default, |text| {
+                GameSettings::from_ron(&text).unwrap_or_else(|e| {
+                    warn!("Invalid settings, using defaults: {}", e);
+                    GameSettings::default()
+                })
+            });
+        app.insert_resource(settings);
+        app.insert_resource(SettingsPath(settings_path));
+        app.init_resource::<SettingsStatus>();
+        app.add_message::<ChangeSetting>();
+        app.add_systems(Startup, apply_saved_display_mode);
+        app.add_systems(
+            Update,
+            (
+                apply_setting_changes,
+                expire_display_confirmation,
+                save_settings,
+            )
+                .chain(),
+        );
+
+        if !is_headless {
+            app.init_resource::<SettingsWindowState>();
+            app.add_systems(
+                Update,
+                (
+                    toggle_settings_window,
+                    capture_binding.run_if(settings_window_visible),
+                ),
+            );
+            app.add_systems(
+                EguiPrimaryContextPass,
+                (
+                    settings_window.run_if(settings_window_visible),
+                    in_game_menu_window.run_if(in_state(ClientGameState::Playing)),
+                ),
+            );
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DisplayMode {
+    #[default]
+    Windowed,
+    Borderless,
+    Fullscreen,
+}
+
+impl DisplayMode {
+    pub const ALL: [DisplayMode; 3] = [
+        DisplayMode::Windowed,
+        DisplayMode::Borderless,
+        DisplayMode::Fullscreen,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DisplayMode::Windowed => "Windowed",
+            DisplayMode::Borderless => "Borderless",
+            DisplayMode::Fullscreen => "Fullscreen",
+        }
+    }
+
+    fn window_mode(self) -> WindowMode {
+        match self {
+            DisplayMode::Windowed => WindowMode::Windowed,
+            DisplayMode::Borderless => WindowMode::BorderlessFullscreen(MonitorSelection::Current),
+            DisplayMode::Fullscreen => {
+                WindowMode::Fullscreen(MonitorSelection::Current, VideoModeSelection::Current)
+            }
+        }
+    }
+}
+
+/// One setting with its new value.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Setting {
+    FieldOfView(f32),
+    MouseSensitivity(f32),
+    Display(DisplayMode),
+    /// A mixer fader, `None` being the master fader. Volumes live in [`AudioMixer`].
+    Volume(Option<AudioBus>, f32),
+    /// Rebinds a control; the control already bound there gets the old binding.
+    Binding(Control, Binding),
+    /// Response of the gamepad look stick.
+    LookStick(StickCurve),
+}
+
+impl Setting {
+    pub fn label(self) -> &'static str {
+        match self {
+            Setting::FieldOfView(_) => "Field of view",
+            Setting::MouseSensitivity(_) => "Mouse sensitivity",
+            Setting::Display(_) => "Display mode",
+            Setting::Volume(fader, _) => fader_label(fader),
+            Setting::Binding(control, _) => control.label(),
+            Setting::LookStick(_) => "Look stick",
+        }
+    }
+
+    /// The setting if its value is usable, otherwise why not.
+    pub fn validate(self) -> Result<Self, String> {
+        let check = |value: f32, range: &RangeInclusive<f32>, unit: &str| {
+            if value.is_finite() && range.contains(&value) {
+                Ok(self)
+            } else {
+                Err(format!(
+                    "{} must be between {}{} and {}{}",
+                    self.label(),
+                    range.start(),
+                    unit,
+                    range.end(),
+                    unit
+                ))
+            }
+        };
+        match self {
+            Setting::FieldOfView(degrees) => check(degrees, &FOV_RANGE_DEGREES, " degrees"),
+            Setting::MouseSensitivity(sensitivity) => check(sensitivity, &SENSITIVITY_RANGE, ""),
+            Setting::Volume(_, volume) => check(volume, &VOLUME_RANGE, ""),
+            Setting::Display(_) => Ok(self),
+            Setting::Binding(control, binding) => {
+                if !binding.is_bindable() {
+                    Err(format!("{} cannot be bound", binding.label()))
+                } else if !control.accepts(binding) {
+                    Err(format!("{} needs a key", control.label()))
+                } else {
+                    Ok(self)
+                }
+            }
+            Setting::LookStick(curve) => curve.validate().map(|()| self),
+        }
+    }
+}
+
+/// Asks for a setting to change. The outcome shows up in [`SettingsStatus`].
+#[derive(Message, Clone, Copy, Debug, PartialEq)]
+pub struct ChangeSetting(pub Setting);
+
+/// Settings as currently applied. Only the settings pipeline changes it.
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct GameSettings {
+    pub fov_degrees: f32,
+    pub mouse_sensitivity: f32,
+    pub display_mode: DisplayMode,
+    pub bindings: KeyBindings,
+    pub look_stick: StickCurve,
+}
+
+impl Default for GameSettings {
+    fn default() -> Self {
+        Self {
+            fov_degrees: 45.0,
+            mouse_sensitivity: 1.0,
+            display_mode: DisplayMode::Windowed,
+            bindings: KeyBindings::default(),
+            look_stick: StickCurve::default(),
+        }
+    }
+}
+
+impl GameSettings {
+    pub fn fov_radians(&self) -> f32 {
+        self.fov_degrees.to_radians()
+    }
+
+    /// The player's input map: their bindings, with their mouse sensitivity.
+    pub fn input_map(&self) -> InputMap<PlayerAction> {
+        self.bindings.input_map(self.mouse_sensitivity)
+    }
+
+    /// `setting` if it fits with the other settings: a binding can only take over a key
+    /// whose control can use the binding it leaves.
+    fn check(&self, setting: Setting) -> Result<Setting, String> {
+        match setting {
+            Setting::Binding(control, binding) => {
+                self.bindings.check(control, binding).map(|()| setting)
+            }
+            _ => Ok(setting),
+        }
+    }
+
+    /// Records an applied setting; volumes are kept by the mixer instead.
+    fn store(&mut self, setting: Setting) {
+        match setting {
+            Setting::FieldOfView(degrees) => self.fov_degrees = degrees,
+            Setting::MouseSensitivity(sensitivity) => self.mouse_sensitivity = sensitivity,
+            Setting::Display(mode) => self.display_mode = mode,
+            Setting::Volume(..) => {}
+            Setting::Binding(control, binding) => self.bindings.set(control, binding),
+            Setting::LookStick(curve) => self.look_stick = curve,
+        }
+    }
+
+    pub fn to_ron(&self) -> Result<String, String> {
+        ron::ser::to_string_pretty(&SettingsFile::from(self), ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())
+    }
+
+    /// Settings from a settings file. Missing entries, and values that do not validate,
+    /// keep their default.
+    pub fn from_ron(text: &str) -> Result<Self, String> {
+        let file: SettingsFile = ron::from_str(text).map_err(|e| e.to_string())?;
+        let mut entries = vec![
+            Setting::FieldOfView(file.fov),
+            Setting::MouseSensitivity(file.sensitivity),
+            Setting::Display(file.display),
+            Setting::LookStick(file.look_stick),
+        ];
+        for (control, binding) in &file.bindings {
+            let control = Control::from_key(control)
+                .ok_or_else(|| format!("unknown control `{}`", control))?;
+            let binding = Binding::from_key(binding)
+                .ok_or_else(|| format!("`{}` is not a key or button that can be bound", binding))?;
+            entries.push(Setting::Binding(control, binding));
+        }
+
+        let mut settings = Self::default();
+        for setting in entries {
+            if let Ok(setting) = setting
+                .validate()
+                .and_then(|setting| settings.check(setting))
+            {
+                settings.store(setting);
+            }
+        }
+        Ok(settings)
+    }
+}
+
+/// What the settings file holds: [`GameSettings`] with bindings by name, such as
+/// `"jump": "Space"`, so the file stays readable and editable by hand.
+#[derive(Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct SettingsFile {
+    fov: f32,
+    sensitivity: f32,
+    display: DisplayMode,
+    bindings: BTreeMap<String, String>,
+    look_stick: StickCurve,
+}
+
+impl Default for SettingsFile {
+    fn default() -> Self {
+        Self::from(&GameSettings::default())
+    }
+}
+
+impl From<&GameSettings> for SettingsFile {
+    fn from(settings: &GameSettings) -> Self {
+        Self {
+            fov: settings.fov_degrees,
+            sensitivity: settings.mouse_sensitivity,
+            display: settings.display_mode,
+            bindings: Control::ALL
+                .into_iter()
+                .map(|control| {
+                    (
+                        control.key().to_string(),
+                        settings.bindings.get(control).key(),
+                    )
+                })
+                .collect(),
+            look_stick: settings.look_stick,
+        }
+    }
+}
+
+/// Where the settings are saved; `None` keeps them in memory only.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct SettingsPath(pub Option<PathBuf>);
+
+#[derive(Resource, Debug, Default)]
+pub struct SettingsStatus {
+    /// Why the last rejected or failed change did not go through.
+    pub message: Option<String>,
+    /// Display mode to go back to, and seconds left to keep the new one.
+    pub pending_display: Option<(DisplayMode, f32)>,
+}
+
+/// What settings are applied to.
+#[derive(SystemParam)]
+pub struct SettingTargets<'w, 's> {
+    cameras: Query<'w, 's, &'static mut Projection, With<PlayerCamera>>,
+    input_maps: Query<'w, 's, &'static mut InputMap<PlayerAction>>,
+    windows: Query<'w, 's, &'static mut Window, With<PrimaryWindow>>,
+    mixer: Option<ResMut<'w, AudioMixer>>,
+}
+
+impl SettingTargets<'_, '_> {
+    /// Value `setting` currently has, `None` when there is nothing it applies to.
+    fn current(&self, settings: &GameSettings, setting: Setting) -> Option<Setting> {
+        match setting {
+            Setting::FieldOfView(_) => Some(Setting::FieldOfView(settings.fov_degrees)),
+            Setting::MouseSensitivity(_) => {
+                Some(Setting::MouseSensitivity(settings.mouse_sensitivity))
+            }
+            Setting::Display(_) => Some(Setting::Display(settings.display_mode)),
+            Setting::Volume(fader, _) => self
+                .mixer
+                .as_ref()
+                .map(|mixer| Setting::Volume(fader, mixer.fader(fader))),
+            Setting::Binding(control, _) => {
+                Some(Setting::Binding(control, settings.bindings.get(control)))
+            }
+            Setting::LookStick(_) => Some(Setting::LookStick(settings.look_stick)),
+        }
+    }
+
+    /// Applies `setting`, on top of `settings`, to the running game. Fails when what it
+    /// controls is missing.
+    fn apply(&mut self, settings: &GameSettings, setting: Setting) -> Result<(), String> {
+        match setting {
+            Setting::FieldOfView(degrees) => {
+                // Aim zoom eases from here toward the new base field of view.
+                for mut projection in self.cameras.iter_mut() {
+                    if let Projection::Perspective(perspective) = projection.as_mut() {
+                        perspective.fov = degrees.to_radians();
+                    }
+                }
+            }
+            Setting::MouseSensitivity(_) | Setting::Binding(..) => {
+                let mut next = settings.clone();
+                next.store(setting);
+                let next_input_map = next.input_map();
+                for mut input_map in self.input_maps.iter_mut() {
+                    *input_map = next_input_map.clone();
+                }
+            }
+            Setting::Display(mode) => {
+                let mut window = self
+                    .windows
+                    .single_mut()
+                    .map_err(|_| "there is no window".to_string())?;
+                window.mode = mode.window_mode();
+            }
+            Setting::Volume(fader, volume) => {
+                let mixer = self.mixer.as_mut().ok_or("audio is disabled")?;
+                mixer.set(fader, volume);
+            }
+            // Read from the settings by the look system every frame.
+            Setting::LookStick(_) => {}
+        }
+        Ok(())
+    }
+}
+
+/// Validates and applies every requested change. A change that fails to apply puts the
+/// previous value back, in case it got half applied.
+fn apply_setting_changes(
+    mut changes: MessageReader<ChangeSetting>,
+    mut settings: ResMut<GameSettings>,
+    mut status: ResMut<SettingsStatus>,
+    mut targets: SettingTargets,
+) {
+    for ChangeSetting(setting) in changes.read().copied() {
+        let setting = match setting
+            .validate()
+            .and_then(|setting| settings.check(setting))
+        {
+            Ok(setting) => setting,
+            Err(e) => {
+                status.message = Some(e);
+                continue;
+            }
+        };
+        let previous = targets.current(&settings, setting);
+        if previous == Some(setting) {
+            continue;
+        }
+
+        if let Err(e) = targets.apply(&settings, setting) {
+            if let Some(previous) = previous
+                && let Err(revert_error) = targets.apply(&settings, previous)
+            {
+                warn!("Failed to restore {}: {}", previous.label(), revert_error);
+            }
+            status.message = Some(format!("{} not applied: {}", setting.label(), e));
+            continue;
+        }
+        if let Some(Setting::Display(previous_mode)) = previous {
+            // Going through several modes before confirming still falls back to the last
+            // confirmed one.
+            let fallback = status
+                .pending_display
+                .map_or(previous_mode, |(fallback, _)| fallback);
+            status.pending_display = Some((fallback, DISPLAY_CONFIRM_SECS));
+        }
+        settings.store(setting);
+        status.message = None;
+    }
+}
+
+/// Goes back to the previous display mode when the new one is not kept in time.
+fn expire_display_confirmation(
+    time: Res<Time>,
+    mut settings: ResMut<GameSettings>,
+    mut status: ResMut<SettingsStatus>,
+    mut targets: SettingTargets,
+) {
+    let Some((fallback, remaining)) = status.pending_display.as_mut() else {
+        return;
+    };
+    *remaining -= time.delta_secs();
+    if *remaining > 0.0 {
+        return;
+    }
+
+    let fallback = *fallback;
+    status.pending_display = None;
+    match targets.apply(&settings, Setting::Display(fallback)) {
+        Ok(()) => {
+            settings.display_mode = fallback;
+            status.message = Some(format!(
+                "Display mode not kept, back to {}",
+                fallback.label()
+            ));
+        }
+        Err(e) => warn!("Failed to restore display mode: {}", e),
+    }
+}
+
+/// Writes the settings back once they stick; a display mode waiting for confirmation is
+/// not saved until kept.
+fn save_settings(
+    settings: Res<GameSettings>,
+    status: Res<SettingsStatus>,
+    settings_path: Res<SettingsPath>,
+) {
+    if !settings.is_changed() && !status.is_changed() {
+        return;
+    }
+    if settings.is_added() || status.pending_display.is_some() {
+        return;
+    }
+    let Some(path) = &settings_path.0 else {
+        return;
+    };
+    let written = settings.to_ron().and_then(|text| {
+        path.parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(path, text))
+            .map_err(|e| e.to_string())
+    });
+    if let Err(e) = written {
+        warn!("Failed to save settings to {}: {}", path.display(), e);
+    }
+}
+
+fn apply_saved_display_mode(
+    settings: Res<GameSettings>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if settings.display_mode == DisplayMode::Windowed {
+        return;
+    }
+    if let Ok(mut window) = windows.single_mut() {
+        window.mode = settings.display_mode.window_mode();
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct SettingsWindowState {
+    pub visible: bool,
+    /// Control waiting for the next key or mouse button pressed.
+    capturing: Option<Control>,
+}
+
+fn settings_window_visible(state: Res<SettingsWindowState>) -> bool {
+    state.visible
+}
+
+fn toggle_settings_window(keys: Res<ButtonInput<KeyCode>>, mut state: ResMut<SettingsWindowState>) {
+    if keys.just_pressed(KeyCode::F10) {
+        state.visible = !state.visible;
+        state.capturing = None;
+    }
+}
+
+/// Binds the control being captured to the next key or mouse button pressed; Escape
+/// cancels. Keys that cannot be bound are rejected by the settings pipeline.
+fn capture_binding(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    mut state: ResMut<SettingsWindowState>,
+    mut changes: MessageWriter<ChangeSetting>,
+) {
+    let Some(control) = state.capturing else {
+        return;
+    };
+    if keys.just_pressed(KeyCode::Escape) {
+        state.capturing = None;
+        return;
+    }
+    let pressed = keys
+        .get_just_pressed()
+        .next()
+        .map(|key| Binding::Key(*key))
+        .or_else(|| {
+            BINDABLE_MOUSE_BUTTONS
+                .into_iter()
+                .find(|button| mouse_buttons.just_pressed(*button))
+                .map(Binding::Mouse)
+        });
+    if let Some(binding) = pressed {
+        changes.write(ChangeSetting(Setting::Binding(control, binding)));
+        state.capturing = None;
+    }
+}
+
+/// Shown in a match while the cursor is released (Escape). The match keeps running.
+fn in_game_menu_window(
+    mut contexts: EguiContexts,
+    mut settings_window: ResMut<SettingsWindowState>,
+    cursor_options: Query<&CursorOptions, With<PrimaryWindow>>,
+) -> Result {
+    if is_cursor_locked(&cursor_options) {
+        return Ok(());
+    }
+    egui::Window::new("Menu")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 24.0))
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.label("Click the game to resume");
+            if ui.button("Settings").clicked() {
+                settings_window.visible = true;
+            }
+        });
+    Ok(())
+}
+
+fn settings_window(
+    mut contexts: EguiContexts,
+    settings: Res<GameSettings>,
+    mixer: Option<Res<AudioMixer>>,
+    mut status: ResMut<SettingsStatus>,
+    mut window_state: ResMut<SettingsWindowState>,
+    mut changes: MessageWriter<ChangeSetting>,
+) -> Result {
+    let mut fov = settings.fov_degrees;
+    let mut sensitivity = settings.mouse_sensitivity;
+    let mut display_mode = settings.display_mode;
+    let mut look_stick = settings.look_stick;
+    let mut volumes = MIXER_FADERS.map(|fader| mixer.as_ref().map(|mixer| mixer.fader(fader)));
+    let mut keep_display = None;
+
+    egui::Window::new("Settings").show(contexts.ctx_mut()?, |ui| {
+        ui.add(egui::Slider::new(&mut fov, FOV_RANGE_DEGREES).text("Field of view"));
+        ui.add(
+            egui::Slider::new(&mut sensitivity, SENSITIVITY_RANGE)
+                .logarithmic(true)
+                .text("Mouse sensitivity"),
+        );
+        ui.horizontal(|ui| {
+            for mode in DisplayMode::ALL {
+                ui.radio_value(&mut display_mode, mode, mode.label());
+            }
+        });
+        if let Some((fallback, remaining)) = status.pending_display {
+            ui.label(format!(
+                "Keep this display mode? Back to {} in {:.0} s",
+                fallback.label(),
+                remaining.ceil()
+            ));
+            ui.horizontal(|ui| {
+                if ui.button("Keep").clicked() {
+                    keep_display = Some(true);
+                }
+                if ui.button("Revert").clicked() {
+                    keep_display = Some(false);
+                }
+            });
+        }
+        ui.separator();
+        for (fader, volume) in MIXER_FADERS.iter().zip(volumes.iter_mut()) {
+            if let Some(volume) = volume {
+                ui.add(egui::Slider::new(volume, VOLUME_RANGE).text(fader_label(*fader)));
+            }
+        }
+        ui.separator();
+        ui.collapsing("Gamepad", |ui| {
+            ui.add(
+                egui::Slider::new(&mut look_stick.sensitivity, SENSITIVITY_RANGE)
+                    .logarithmic(true)
+                    .text("Look sensitivity"),
+            );
+            ui.add(
+                egui::Slider::new(&mut look_stick.exponent, EXPONENT_RANGE).text("Response curve"),
+            );
+            ui.add(
+                egui::Slider::new(&mut look_stick.inner_deadzone, DEADZONE_RANGE).text("Deadzone"),
+            );
+            ui.add(
+                egui::Slider::new(&mut look_stick.outer_deadzone, OUTER_DEADZONE_RANGE)
+                    .text("Outer deadzone"),
+            );
+        });
+        ui.collapsing("Controls", |ui| {
+            egui::Grid::new("key_bindings").show(ui, |ui| {
+                for control in Control::ALL {
+                    ui.label(control.label());
+                    let text = if window_state.capturing == Some(control) {
+                        "Press a key, Esc cancels".to_string()
+                    } else {
+                        settings.bindings.get(control).label()
+                    };
+                    if ui.button(text).clicked() {
+                        window_state.capturing = Some(control);
+                    }
+                    ui.end_row();
+                }
+            });
+            if ui.button("Reset controls").clicked() {
+                let defaults = KeyBindings::default();
+                for control in Control::ALL {
+                    changes.write(ChangeSetting(Setting::Binding(
+                        control,
+                        defaults.get(control),
+                    )));
+                }
+            }
+        });
+        if let Some(message) = &status.message {
+            ui.separator();
+            ui.colored_label(egui::Color32::from_rgb(255, 120, 100), message);
+        }
+    });
+
+    if fov != settings.fov_degrees {
+        changes.write(ChangeSetting(Setting::FieldOfView(fov)));
+    }
+    if sensitivity != settings.mouse_sensitivity {
+        changes.write(ChangeSetting(Setting::MouseSensitivity(sensitivity)));
+    }
+    if display_mode != settings.display_mode {
+        changes.write(ChangeSetting(Setting::Display(display_mode)));
+    }
+    if look_stick != settings.look_stick {
+        changes.write(ChangeSetting(Setting::LookStick(look_stick)));
+    }
+    if let Some(mixer) = &mixer {
+        for (fader, volume) in MIXER_FADERS.into_iter().zip(volumes) {
+            if let Some(volume) = volume
+                && volume != mixer.fader(fader)
+            {
+                changes.write(ChangeSetting(Setting::Volume(fader, volume)));
+            }
+        }
+    }
+    match keep_display {
+        Some(true) => status.pending_display = None,
+        Some(false) => {
+            if let Some((_, remaining)) = status.pending_display.as_mut() {
+                *remaining = 0.0;
+            }
+        }
+        None => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ChangeSetting, DisplayMode, GameSettings, Setting, SettingsStatus, apply_setting_changes,
+    };
+    use crate::audio::{AudioBus, AudioMixer};
+    use crate::camera::PlayerCamera;
+    use crate::inputs::gamepad::StickCurve;
+    use crate::inputs::input_map::{Binding, Control, get_player_input_map};
+    use bevy::prelude::{App, KeyCode, MouseButton, PerspectiveProjection, Projection, Update};
+    use leafwing_input_manager::prelude::InputMap;
+    use shared::inputs::input::PlayerAction;
+
+    fn settings_app() -> App {
+        let mut app = App::new();
+        app.add_message::<ChangeSetting>();
+        app.init_resource::<GameSettings>();
+        app.init_resource::<SettingsStatus>();
+        app.add_systems(Update, apply_setting_changes);
+        app
+    }
+
+    fn change(app: &mut App, setting: Setting) {
+        app.world_mut().write_message(ChangeSetting(setting));
+        app.update();
+    }
+
+    #[test]
+    fn settings_round_trip_and_ignore_invalid_values() {
+        let mut settings = GameSettings {
+            fov_degrees: 70.0,
+            mouse_sensitivity: 0.5,
+            display_mode: DisplayMode::Borderless,
+            look_stick: StickCurve {
+                inner_deadzone: 0.2,
+                exponent: 1.5,
+                ..StickCurve::default()
+            },
+            ..GameSettings::default()
+        };
+        settings.bindings.set(
+            Control::Action(PlayerAction::Reload),
+            Binding::Mouse(MouseButton::Middle),
+        );
+        assert_eq!(
+            GameSettings::from_ron(&settings.to_ron().unwrap()),
+            Ok(settings)
+        );
+
+        let parsed = GameSettings::from_ron(
+            r#"(
+                fov: 500.0,
+                sensitivity: 0.5,
+                bindings: {"move_forward": "MouseLeft"},
+                look_stick: (inner_deadzone: 0.4, outer_deadzone: 0.3),
+            )"#,
+        )
+        .unwrap();
+        assert_eq!(
+            parsed,
+            GameSettings {
+                mouse_sensitivity: 0.5,
+                ..GameSettings::default()
+            }
+        );
+
+        assert!(GameSettings::from_ron("(display: tiny)").is_err());
+        assert!(GameSettings::from_ron(r#"(bindings: {"dance": "KeyX"})"#).is_err());
+        assert!(GameSettings::from_ron(r#"(bindings: {"jump": "Escape"})"#).is_err());
+        assert!(GameSettings::from_ron("(volume: 1.0)").is_err());
+    }
+
+    #[test]
+    fn rebinding_swaps_conflicts_and_updates_input_maps() {
+        let mut app = settings_app();
+        let player = app.world_mut().spawn(get_player_input_map()).id();
+
+        // R was reload: reload takes jump's space bar.
+        change(
+            &mut app,
+            Setting::Binding(
+                Control::Action(PlayerAction::Jump),
+                Binding::Key(KeyCode::KeyR),
+            ),
+        );
+        let bindings = &app.world().resource::<GameSettings>().bindings;
+        assert_eq!(
+            bindings.get(Control::Action(PlayerAction::Reload)),
+            Binding::Key(KeyCode::Space)
+        );
+        let input_map = app.world().get::<InputMap<PlayerAction>>(player).unwrap();
+        assert_eq!(
+            input_map,
+            &app.world().resource::<GameSettings>().input_map()
+        );
+
+        // Taking W would leave moving forward on the left mouse button.
+        change(
+            &mut app,
+            Setting::Binding(
+                Control::Action(PlayerAction::Shoot),
+                Binding::Key(KeyCode::KeyW),
+            ),
+        );
+        let bindings = &app.world().resource::<GameSettings>().bindings;
+        assert_eq!(
+            bindings.get(Control::Action(PlayerAction::Shoot)),
+            Binding::Mouse(MouseButton::Left)
+        );
+        assert!(app.world().resource::<SettingsStatus>().message.is_some());
+    }
+
+    #[test]
+    fn changes_apply_live_and_invalid_ones_are_rejected() {
+        let mut app = settings_app();
+        app.insert_resource(AudioMixer::default());
+        let camera = app
+            .world_mut()
+            .spawn((
+                PlayerCamera,
+                Projection::Perspective(PerspectiveProjection::default()),
+            ))
+            .id();
+
+        change(&mut app, Setting::FieldOfView(70.0));
+        let Some(Projection::Perspective(perspective)) = app.world().get::<Projection>(camera)
+        else {
+            panic!("camera should keep its perspective projection");
+        };
+        assert_eq!(perspective.fov, 70f32.to_radians());
+        assert_eq!(app.world().resource::<GameSettings>().fov_degrees, 70.0);
+
+        change(&mut app, Setting::FieldOfView(179.0));
+        assert_eq!(app.world().resource::<GameSettings>().fov_degrees, 70.0);
+        assert!(app.world().resource::<SettingsStatus>().message.is_some());
+
+        change(&mut app, Setting::Volume(Some(AudioBus::Music), 0.25));
+        assert_eq!(
+            app.world()
+                .resource::<AudioMixer>()
+                .fader(Some(AudioBus::Music)),
+            0.25
+        );
+        assert!(app.world().resource::<SettingsStatus>().message.is_none());
+    }
+
+    #[test]
+    fn failed_changes_keep_the_previous_value() {
+        // No window and no mixer to apply to.
+        let mut app = settings_app();
+
+        change(&mut app, Setting::Display(DisplayMode::Fullscreen));
+        assert_eq!(
+            app.world().resource::<GameSettings>().display_mode,
+            DisplayMode::Windowed
+        );
+        let status = app.world().resource::<SettingsStatus>();
+        assert!(status.message.is_some());
+        assert!(status.pending_display.is_none());
+
+        change(&mut app, Setting::Volume(None, 0.5));
+        assert!(app.world().resource::<SettingsStatus>().message.is_some());
+    }
+}
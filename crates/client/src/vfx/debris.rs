@@ -0,0 +1,45 @@
+use bevy::prelude::*;
+use lightyear::prelude::{Client, MessageReceiver};
+use shared::components::destructible::{
+    AUTHORITATIVE_DEBRIS_PIECES, COSMETIC_DEBRIS_LIFETIME_SECS, DESTRUCTIBLE_COLOR, DebrisLifetime,
+    debris_body, debris_layout,
+};
+use shared::protocol::DebrisBurst;
+
+pub struct DebrisEffectsPlugin;
+
+impl Plugin for DebrisEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, spawn_cosmetic_debris);
+    }
+}
+
+/// Client-only debris, never replicated and despawned after a short lifetime.
+#[derive(Component)]
+struct CosmeticDebris;
+
+fn spawn_cosmetic_debris(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut receivers: Query<&mut MessageReceiver<DebrisBurst>, With<Client>>,
+) {
+    for mut receiver in receivers.iter_mut() {
+        for burst in receiver.receive() {
+            let material = materials.add(DESTRUCTIBLE_COLOR);
+            // The first pieces of the layout are simulated and replicated by the server.
+            let layout = debris_layout(burst.seed, burst.size, burst.pieces);
+            for spec in layout.iter().skip(AUTHORITATIVE_DEBRIS_PIECES) {
+                commands.spawn((
+                    Name::new("CosmeticDebris"),
+                    CosmeticDebris,
+                    DebrisLifetime::from_seconds(COSMETIC_DEBRIS_LIFETIME_SECS),
+                    debris_body(burst.origin, spec),
+                    Transform::from_translation(burst.origin + spec.offset),
+                    Mesh3d(meshes.add(Cuboid::from_length(spec.size))),
+                    MeshMaterial3d(material.clone()),
+                ));
+            }
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use bevy::prelude::*;
+use lightyear::prelude::{Client, MessageReceiver};
+use shared::components::grenade::{GRENADE_COLOR, GRENADE_RADIUS, Grenade};
+use shared::protocol::GrenadeExplosion;
+
+const EXPLOSION_FLASH_SECS: f32 = 0.35;
+const EXPLOSION_LIGHT_INTENSITY: f32 = 2_000_000.0;
+
+pub struct GrenadeEffectsPlugin;
+
+impl Plugin for GrenadeEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                handle_grenade_setup,
+                spawn_explosion_flashes,
+                update_explosion_flashes,
+            ),
+        );
+    }
+}
+
+/// Expanding fireball left by an explosion, scaled up to the blast radius.
+#[derive(Component)]
+struct ExplosionFlash {
+    timer: Timer,
+    radius: f32,
+}
+
+/// Grenades are simulated by the server, clients only give them a mesh.
+fn handle_grenade_setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    grenades: Query<Entity, (With<Grenade>, Without<Mesh3d>)>,
+) {
+    for entity in grenades.iter() {
+        commands.entity(entity).insert((
+            Mesh3d(meshes.add(Sphere::new(GRENADE_RADIUS))),
+            MeshMaterial3d(materials.add(GRENADE_COLOR)),
+        ));
+    }
+}
+
+fn spawn_explosion_flashes(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut receivers: Query<&mut MessageReceiver<GrenadeExplosion>, With<Client>>,
+) {
+    for mut receiver in receivers.iter_mut() {
+        for explosion in receiver.receive() {
+            commands.spawn((
+                Name::new("ExplosionFlash"),
+                ExplosionFlash {
+                    timer: Timer::from_seconds(EXPLOSION_FLASH_SECS, TimerMode::Once),
+                    radius: explosion.radius,
+                },
+                Mesh3d(meshes.add(Sphere::new(1.0))),
+                MeshMaterial3d(materials.add(StandardMaterial {
+                    base_color: Color::srgba(1.0, 0.6, 0.2, 0.8),
+                    emissive: LinearRgba::rgb(12.0, 5.0, 1.0),
+                    alpha_mode: AlphaMode::Blend,
+                    ..default()
+                })),
+                Transform::from_translation(explosion.origin).with_scale(Vec3::splat(0.1)),
+                PointLight {
+                    color: Color::srgb(1.0, 0.7, 0.3),
+                    intensity: EXPLOSION_LIGHT_INTENSITY,
+                    range: explosion.radius * 3.0,
+                    ..default()
+                },
+            ));
+        }
+    }
+}
+
+fn update_explosion_flashes(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut flashes: Query<(
+        Entity,
+        &mut ExplosionFlash,
+        &mut Transform,
+        &mut PointLight,
+        &MeshMaterial3d<StandardMaterial>,
+    )>,
+) {
+    for (entity, mut flash, mut transform, mut light, material) in flashes.iter_mut() {
+        flash.timer.tick(time.delta());
+        if flash.timer.is_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let progress = flash.timer.fraction();
+        // Grow quickly to the blast radius, then fade out.
+        transform.scale = Vec3::splat((progress.sqrt() * flash.radius).max(0.1));
+        light.intensity = EXPLOSION_LIGHT_INTENSITY * (1.0 - progress);
+        if let Some(material) = materials.get_mut(&material.0) {
+            material.base_color.set_alpha(0.8 * (1.0 - progress));
+        }
+    }
+}
@@ -1,68 +1,200 @@
-use avian3d::prelude::Position;
 use bevy::prelude::*;
-use shared::components::weapons::HitEvent;
+use shared::components::destructible::Destructible;
+use shared::components::weapon_vfx::{ImpactSurface, WeaponVfx};
+use shared::components::weapons::{Gun, ShotFired};
+use shared::protocol::CharacterMarker;
 
+use crate::vfx::weapon_heat::BARREL_OFFSET;
+
+/// Pull on impact particles, gentler than real gravity so they stay visible.
+const PARTICLE_GRAVITY: f32 = 5.0;
+/// How far particles fan out from the shot's way back, as the tangent of the cone.
+const PARTICLE_SPREAD: f32 = 0.9;
+/// Particles start this far off the surface so they are not buried in it.
+const IMPACT_OFFSET: f32 = 0.08;
+const MUZZLE_LIGHT_RANGE: f32 = 6.0;
+
+/// Plays the tracer, muzzle flash and impact of every shot the client simulates, as the
+/// shooter's [`Gun`] describes them in its [`WeaponVfx`].
 pub struct GunEffectsPlugin;
 
 impl Plugin for GunEffectsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (show_hit_markers, cleanup_old_hit_markers));
+        app.add_systems(Update, (spawn_shot_effects, update_shot_effects).chain());
     }
 }
 
+/// Short-lived piece of a shot effect. It drifts, falls and shrinks along `shrink` until
+/// its timer runs out.
 #[derive(Component)]
-struct HitMarker {
+struct ShotEffect {
     timer: Timer,
+    velocity: Vec3,
+    gravity: f32,
+    start_scale: Vec3,
+    shrink: Vec3,
+}
+
+impl ShotEffect {
+    fn new(lifetime_secs: f32, start_scale: Vec3, shrink: Vec3) -> Self {
+        Self {
+            timer: Timer::from_seconds(lifetime_secs, TimerMode::Once),
+            velocity: Vec3::ZERO,
+            gravity: 0.0,
+            start_scale,
+            shrink,
+        }
+    }
 }
 
-fn show_hit_markers(
+/// Directions of `count` impact particles, fanned out around `back` in a golden-angle
+/// spiral so bursts look scattered without randomness.
+fn burst_directions(back: Vec3, count: u32) -> impl Iterator<Item = Vec3> {
+    let back = back.normalize_or(Vec3::Y);
+    let (side, up) = back.any_orthonormal_pair();
+    let golden_angle = std::f32::consts::PI * (3.0 - 5.0_f32.sqrt());
+    (0..count).map(move |index| {
+        let angle = index as f32 * golden_angle;
+        let spread = PARTICLE_SPREAD * ((index as f32 + 0.5) / count as f32).sqrt();
+        (back + (side * angle.cos() + up * angle.sin()) * spread).normalize()
+    })
+}
+
+fn glowing(
+    materials: &mut Assets<StandardMaterial>,
+    color: Color,
+    glow: f32,
+) -> Handle<StandardMaterial> {
+    materials.add(StandardMaterial {
+        base_color: color,
+        emissive: color.to_linear() * glow,
+        unlit: true,
+        ..default()
+    })
+}
+
+fn spawn_shot_effects(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    hit_events: Query<&HitEvent, Added<HitEvent>>,
-    shooter_positions: Query<&Position>,
+    mut shots: MessageReader<ShotFired>,
+    shooters: Query<(&GlobalTransform, Option<&Gun>)>,
+    characters: Query<(), With<CharacterMarker>>,
+    props: Query<(), With<Destructible>>,
 ) {
-    for hit_event in hit_events.iter() {
-        // Offset markers slightly toward the shooter so they stay visible on impact surfaces.
-        let marker_position = shooter_positions
-            .get(hit_event.shooter)
-            .map(|pos| {
-                let shot_direction = (hit_event.hit_point - pos.0).normalize_or_zero();
-                if shot_direction.length_squared() > 0.0 {
-                    hit_event.hit_point - (shot_direction * 0.08)
-                } else {
-                    hit_event.hit_point
-                }
-            })
-            .unwrap_or(hit_event.hit_point);
-
-        commands.spawn((
-            Mesh3d(meshes.add(Sphere::new(0.14))),
-            MeshMaterial3d(materials.add(StandardMaterial {
-                base_color: Color::srgb(0.2, 1.0, 0.9),
-                emissive: LinearRgba::rgb(2.0, 10.0, 9.0),
-                ..default()
-            })),
-            Transform::from_translation(marker_position),
-            HitMarker {
-                timer: Timer::from_seconds(0.2, TimerMode::Once),
+    for shot in shots.read() {
+        let (barrel, vfx) = shooters.get(shot.shooter).map_or_else(
+            |_| (shot.origin, WeaponVfx::default()),
+            |(transform, gun)| {
+                (
+                    transform.transform_point(BARREL_OFFSET),
+                    gun.map(|gun| gun.vfx).unwrap_or_default(),
+                )
             },
-            Name::new("HitMarker"),
+        );
+
+        let flash = vfx.muzzle_flash;
+        let mut flash_entity = commands.spawn((
+            Mesh3d(meshes.add(Sphere::new(flash.size))),
+            MeshMaterial3d(glowing(&mut materials, flash.color, 12.0)),
+            Transform::from_translation(barrel),
+            ShotEffect::new(flash.lifetime_secs, Vec3::ONE, Vec3::ONE),
+            Name::new("MuzzleFlash"),
         ));
+        if flash.light_intensity > 0.0 {
+            flash_entity.with_child(PointLight {
+                color: flash.color,
+                intensity: flash.light_intensity,
+                range: MUZZLE_LIGHT_RANGE,
+                shadows_enabled: false,
+                ..default()
+            });
+        }
+
+        if let Some(tracer) = vfx.tracer {
+            let length = barrel.distance(shot.end);
+            if length > f32::EPSILON {
+                let scale = Vec3::new(1.0, 1.0, length);
+                commands.spawn((
+                    Mesh3d(meshes.add(Cuboid::new(tracer.width, tracer.width, 1.0))),
+                    MeshMaterial3d(glowing(&mut materials, tracer.color, 6.0)),
+                    Transform::from_translation((barrel + shot.end) * 0.5)
+                        .looking_at(shot.end, Vec3::Y)
+                        .with_scale(scale),
+                    // Thins out, keeping its length.
+                    ShotEffect::new(tracer.lifetime_secs, scale, Vec3::new(1.0, 1.0, 0.0)),
+                    Name::new("Tracer"),
+                ));
+            }
+        }
 
-        info!("💥 Hit marker spawned at {:?}", hit_event.hit_point);
+        let Some(hit) = shot.hit else {
+            continue;
+        };
+        let surface = if characters.contains(hit) {
+            ImpactSurface::Character
+        } else if props.contains(hit) {
+            ImpactSurface::Prop
+        } else {
+            ImpactSurface::Wall
+        };
+        let particles = vfx.impacts.for_surface(surface);
+        let back = shot.origin - shot.end;
+        let impact_point = shot.end + back.normalize_or_zero() * IMPACT_OFFSET;
+        let mesh = meshes.add(Sphere::new(particles.size));
+        let material = glowing(&mut materials, particles.color, 1.5);
+        for direction in burst_directions(back, particles.count) {
+            commands.spawn((
+                Mesh3d(mesh.clone()),
+                MeshMaterial3d(material.clone()),
+                Transform::from_translation(impact_point),
+                ShotEffect {
+                    velocity: direction * particles.speed,
+                    gravity: PARTICLE_GRAVITY,
+                    ..ShotEffect::new(particles.lifetime_secs, Vec3::ONE, Vec3::ONE)
+                },
+                Name::new("ImpactParticle"),
+            ));
+        }
     }
 }
 
-fn cleanup_old_hit_markers(
+fn update_shot_effects(
     mut commands: Commands,
-    mut markers: Query<(Entity, &mut HitMarker)>,
+    mut effects: Query<(Entity, &mut ShotEffect, &mut Transform)>,
     time: Res<Time>,
 ) {
-    for (entity, mut marker) in markers.iter_mut() {
-        marker.timer.tick(time.delta());
-        if marker.timer.is_finished() {
+    let delta_secs = time.delta_secs();
+    for (entity, mut effect, mut transform) in effects.iter_mut() {
+        effect.timer.tick(time.delta());
+        if effect.timer.is_finished() {
             commands.entity(entity).despawn();
+            continue;
+        }
+
+        effect.velocity.y -= effect.gravity * delta_secs;
+        transform.translation += effect.velocity * delta_secs;
+        let progress = effect.timer.fraction();
+        transform.scale = effect.start_scale * (Vec3::ONE - effect.shrink * progress);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PARTICLE_SPREAD, burst_directions};
+    use bevy::prelude::Vec3;
+
+    #[test]
+    fn impact_bursts_fan_out_back_toward_the_shooter() {
+        let back = Vec3::new(0.0, 0.0, 1.0);
+        let directions: Vec<Vec3> = burst_directions(back, 8).collect();
+        assert_eq!(directions.len(), 8);
+
+        let min_alignment = 1.0 / (1.0 + PARTICLE_SPREAD * PARTICLE_SPREAD).sqrt();
+        for direction in &directions {
+            assert!(direction.is_normalized());
+            assert!(direction.dot(back) >= min_alignment - 1e-4);
         }
+        assert_ne!(directions[0], directions[1]);
     }
 }
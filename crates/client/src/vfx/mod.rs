@@ -1,8 +1,20 @@
+mod debris;
 mod flashlight;
+mod grenade;
 mod gun;
+mod pickup;
+mod weapon_charge;
+mod weapon_heat;
+mod world_items;
 
+use crate::vfx::debris::DebrisEffectsPlugin;
 use crate::vfx::flashlight::ClientFlashlightPlugin;
+use crate::vfx::grenade::GrenadeEffectsPlugin;
 use crate::vfx::gun::GunEffectsPlugin;
+use crate::vfx::pickup::PickupEffectsPlugin;
+use crate::vfx::weapon_charge::WeaponChargeEffectsPlugin;
+use crate::vfx::weapon_heat::WeaponHeatEffectsPlugin;
+use crate::vfx::world_items::WorldItemEffectsPlugin;
 use bevy::prelude::*;
 
 pub struct ClientVFXPlugin;
@@ -11,5 +23,11 @@ impl Plugin for ClientVFXPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(GunEffectsPlugin);
         app.add_plugins(ClientFlashlightPlugin);
+        app.add_plugins(DebrisEffectsPlugin);
+        app.add_plugins(GrenadeEffectsPlugin);
+        app.add_plugins(PickupEffectsPlugin);
+        app.add_plugins(WeaponChargeEffectsPlugin);
+        app.add_plugins(WeaponHeatEffectsPlugin);
+        app.add_plugins(WorldItemEffectsPlugin);
     }
 }
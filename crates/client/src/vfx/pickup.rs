@@ -0,0 +1,51 @@
+use bevy::prelude::*;
+use shared::components::pickup::{Pickup, PickupKind};
+
+pub struct PickupEffectsPlugin;
+
+impl Plugin for PickupEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (handle_pickup_setup, update_pickup_visibility));
+    }
+}
+
+/// Pickups are collected on the server, clients only draw them.
+fn handle_pickup_setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    pickups: Query<(Entity, &Pickup), Without<Mesh3d>>,
+) {
+    for (entity, pickup) in pickups.iter() {
+        let mesh = match pickup.kind {
+            PickupKind::HealthPack => meshes.add(Cuboid::new(0.5, 0.3, 0.5)),
+            PickupKind::Ammo => meshes.add(Cuboid::new(0.4, 0.25, 0.3)),
+            PickupKind::Attachment(_) => meshes.add(Sphere::new(0.2)),
+        };
+        let color = pickup.kind.color();
+        commands.entity(entity).insert((
+            Mesh3d(mesh),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: color,
+                emissive: color.to_linear() * 2.0,
+                ..default()
+            })),
+            pickup_visibility(pickup),
+        ));
+    }
+}
+
+/// Hide taken pickups until the server makes them available again.
+fn update_pickup_visibility(mut pickups: Query<(&Pickup, &mut Visibility), Changed<Pickup>>) {
+    for (pickup, mut visibility) in pickups.iter_mut() {
+        *visibility = pickup_visibility(pickup);
+    }
+}
+
+fn pickup_visibility(pickup: &Pickup) -> Visibility {
+    if pickup.available {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    }
+}
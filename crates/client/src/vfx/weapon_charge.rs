@@ -0,0 +1,104 @@
+use bevy::prelude::*;
+use lightyear::prelude::{Interpolated, Predicted};
+use shared::components::weapons::Gun;
+use shared::protocol::PlayerId;
+
+use crate::vfx::weapon_heat::BARREL_OFFSET;
+
+/// How far past the barrel's end the charge orb sits.
+const ORB_OFFSET: Vec3 = Vec3::new(0.0, 0.0, -0.3);
+const ORB_MAX_RADIUS: f32 = 0.12;
+/// Flickers per second of a fully charged orb.
+const FULL_CHARGE_FLICKER_HZ: f32 = 12.0;
+
+pub struct WeaponChargeEffectsPlugin;
+
+/// Orb of energy gathering at the muzzle while a charge weapon is held.
+#[derive(Component)]
+struct ChargeOrb {
+    material: Handle<StandardMaterial>,
+}
+
+#[derive(Component)]
+struct HasChargeOrb;
+
+impl Plugin for WeaponChargeEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (spawn_charge_orbs, update_charge_orbs).chain());
+    }
+}
+
+fn spawn_charge_orbs(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    player_query: Query<
+        Entity,
+        (
+            Or<(With<Predicted>, With<Interpolated>)>,
+            With<PlayerId>,
+            With<Gun>,
+            Without<HasChargeOrb>,
+        ),
+    >,
+) {
+    for player_entity in player_query.iter() {
+        let material = materials.add(StandardMaterial {
+            base_color: Color::srgba(0.5, 0.75, 1.0, 0.6),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        });
+        let orb_entity = commands
+            .spawn((
+                ChargeOrb {
+                    material: material.clone(),
+                },
+                Mesh3d(meshes.add(Sphere::new(1.0))),
+                MeshMaterial3d(material),
+                Transform::from_translation(BARREL_OFFSET + ORB_OFFSET).with_scale(Vec3::ZERO),
+                Visibility::Hidden,
+                Name::new("ChargeOrb"),
+            ))
+            .id();
+
+        commands
+            .entity(player_entity)
+            .add_child(orb_entity)
+            .insert(HasChargeOrb);
+    }
+}
+
+/// Grow and brighten the orb with the charge, predicted for the local player and
+/// replicated for everyone else; flicker once fully charged. Hidden at rest.
+fn update_charge_orbs(
+    time: Res<Time>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut orb_query: Query<(&ChargeOrb, &ChildOf, &mut Transform, &mut Visibility)>,
+    gun_query: Query<&Gun>,
+) {
+    for (orb, child_of, mut transform, mut visibility) in orb_query.iter_mut() {
+        let Some(charge) = gun_query
+            .get(child_of.parent())
+            .ok()
+            .and_then(|gun| gun.charge.as_ref())
+            .filter(|charge| charge.is_charging())
+        else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *visibility = Visibility::Inherited;
+
+        let fraction = charge.fraction();
+        let flicker = if fraction >= 1.0 {
+            0.8 + 0.2 * (time.elapsed_secs() * FULL_CHARGE_FLICKER_HZ * std::f32::consts::TAU).sin()
+        } else {
+            1.0
+        };
+        transform.scale = Vec3::splat(ORB_MAX_RADIUS * (0.2 + 0.8 * fraction) * flicker);
+
+        if let Some(material) = materials.get_mut(&orb.material) {
+            material.emissive = LinearRgba::rgb(2.0, 4.0, 10.0) * (fraction * flicker);
+        }
+    }
+}
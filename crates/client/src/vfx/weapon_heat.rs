@@ -0,0 +1,112 @@
+use bevy::prelude::*;
+use lightyear::prelude::{Interpolated, Predicted};
+use shared::components::weapons::Gun;
+use shared::inputs::input::PLAYER_CAPSULE_HEIGHT;
+use shared::protocol::PlayerId;
+
+/// Where the barrel sits on the character, right hand side at chest height.
+pub(super) const BARREL_OFFSET: Vec3 = Vec3::new(0.35, PLAYER_CAPSULE_HEIGHT * 0.6, -0.45);
+/// How far the barrel slides back while venting.
+const VENT_RECOIL: f32 = 0.08;
+/// Pulses per second of the glow while venting.
+const VENT_PULSE_HZ: f32 = 6.0;
+
+pub struct WeaponHeatEffectsPlugin;
+
+/// Glowing barrel shown on characters holding a heat weapon.
+#[derive(Component)]
+struct HeatBarrel {
+    material: Handle<StandardMaterial>,
+}
+
+#[derive(Component)]
+struct HasHeatBarrel;
+
+impl Plugin for WeaponHeatEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (spawn_heat_barrels, update_heat_barrels).chain());
+    }
+}
+
+fn spawn_heat_barrels(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    player_query: Query<
+        Entity,
+        (
+            Or<(With<Predicted>, With<Interpolated>)>,
+            With<PlayerId>,
+            With<Gun>,
+            Without<HasHeatBarrel>,
+        ),
+    >,
+) {
+    for player_entity in player_query.iter() {
+        let material = materials.add(StandardMaterial {
+            base_color: Color::srgb(0.25, 0.25, 0.28),
+            ..default()
+        });
+        let barrel_entity = commands
+            .spawn((
+                HeatBarrel {
+                    material: material.clone(),
+                },
+                Mesh3d(meshes.add(Cuboid::new(0.08, 0.08, 0.5))),
+                MeshMaterial3d(material),
+                Transform::from_translation(BARREL_OFFSET),
+                Visibility::Hidden,
+                Name::new("HeatBarrel"),
+            ))
+            .id();
+
+        commands
+            .entity(player_entity)
+            .add_child(barrel_entity)
+            .insert(HasHeatBarrel);
+    }
+}
+
+/// Glow from dull red to white hot with the replicated heat; pulse and slide back while
+/// venting. Hidden while the character holds a magazine weapon.
+fn update_heat_barrels(
+    time: Res<Time>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut barrel_query: Query<(&HeatBarrel, &ChildOf, &mut Transform, &mut Visibility)>,
+    gun_query: Query<&Gun>,
+) {
+    for (barrel, child_of, mut transform, mut visibility) in barrel_query.iter_mut() {
+        let Some(heat) = gun_query
+            .get(child_of.parent())
+            .ok()
+            .and_then(|gun| gun.heat.as_ref())
+        else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *visibility = Visibility::Inherited;
+
+        let (glow, recoil) = if heat.venting {
+            let pulse =
+                0.5 + 0.5 * (time.elapsed_secs() * VENT_PULSE_HZ * std::f32::consts::TAU).sin();
+            (heat.level * (0.6 + 0.4 * pulse), VENT_RECOIL)
+        } else {
+            (heat.level, 0.0)
+        };
+        transform.translation = BARREL_OFFSET + Vec3::Z * recoil;
+
+        if let Some(material) = materials.get_mut(&barrel.material) {
+            material.emissive = heat_glow(glow);
+        }
+    }
+}
+
+/// Emissive color for a heat level: nothing when cold, red, then orange, then white hot.
+fn heat_glow(level: f32) -> LinearRgba {
+    let level = level.clamp(0.0, 1.0);
+    LinearRgba::rgb(
+        12.0 * level,
+        8.0 * level * level,
+        4.0 * level * level * level,
+    )
+}
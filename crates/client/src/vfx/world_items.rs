@@ -0,0 +1,34 @@
+use bevy::prelude::*;
+use shared::components::world_items::{WorldItem, WorldItemSettings, WorldItemSpawnedAt};
+
+pub struct WorldItemEffectsPlugin;
+
+impl Plugin for WorldItemEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, fade_out_world_items);
+    }
+}
+
+/// Fade corpses and dropped weapons out before the server removes them. Items evicted early
+/// by the world item cap simply disappear.
+fn fade_out_world_items(
+    time: Res<Time>,
+    settings: Res<WorldItemSettings>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    items: Query<(
+        &WorldItem,
+        &WorldItemSpawnedAt,
+        &MeshMaterial3d<StandardMaterial>,
+    )>,
+) {
+    let now = time.elapsed_secs();
+    for (item, spawned_at, material) in items.iter() {
+        let alpha = settings.fade_alpha(item.kind, now - spawned_at.0);
+        if alpha >= 1.0 {
+            continue;
+        }
+        if let Some(material) = materials.get_mut(&material.0) {
+            material.base_color.set_alpha(alpha);
+        }
+    }
+}
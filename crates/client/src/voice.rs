@@ -0,0 +1,269 @@
+use avian3d::prelude::Position;
+use bevy::prelude::{
+    App, ButtonInput, Color, Commands, Component, IntoScheduleConfigs, KeyCode, Name, Node, Plugin,
+    PositionType, Query, Res, ResMut, Resource, Startup, Text, TextColor, TextFont, Time, Update,
+    Val, With, default,
+};
+use lightyear::prelude::{Client, Connected, MessageReceiver, MessageSender};
+use shared::protocol::{
+    MAX_VOICE_FRAME_BYTES, PlayerId, VOICE_PROXIMITY_RANGE, VoiceChannel, VoiceFrame,
+};
+use std::collections::HashMap;
+
+use crate::LocalPlayerId;
+use crate::audio::{AudioBus, AudioMixer};
+
+const PUSH_TO_TALK: KeyCode = KeyCode::KeyV;
+/// A speaker stays marked as talking this long after their last frame.
+const VOICE_ACTIVITY_HOLD_SECS: f32 = 0.3;
+
+/// Source of encoded voice frames, typically a microphone feeding a codec such as Opus.
+pub trait VoiceCapture: Send + Sync + 'static {
+    /// Frames encoded since the last call, oldest first.
+    fn poll_frames(&mut self) -> Vec<Vec<u8>>;
+}
+
+/// Sink for voice frames relayed by the server.
+pub trait VoicePlayback: Send + Sync + 'static {
+    /// Decode and play one frame from `speaker` at `volume` (0.0..=1.0, already mixed).
+    fn play_frame(&mut self, speaker: u64, frame: &[u8], volume: f32);
+}
+
+/// Backend without a microphone or decoder. Relayed frames still drive the voice activity
+/// indicator.
+pub struct SilentVoice;
+
+impl VoiceCapture for SilentVoice {
+    fn poll_frames(&mut self) -> Vec<Vec<u8>> {
+        Vec::new()
+    }
+}
+
+impl VoicePlayback for SilentVoice {
+    fn play_frame(&mut self, _speaker: u64, _frame: &[u8], _volume: f32) {}
+}
+
+/// Audio side of voice chat. Insert one before adding [`ClientVoicePlugin`] to plug in a
+/// real codec; the networking code only ever sees opaque frames.
+#[derive(Resource)]
+pub struct VoiceBackend {
+    pub capture: Box<dyn VoiceCapture>,
+    pub playback: Box<dyn VoicePlayback>,
+}
+
+impl VoiceBackend {
+    pub fn new(capture: impl VoiceCapture, playback: impl VoicePlayback) -> Self {
+        Self {
+            capture: Box::new(capture),
+            playback: Box::new(playback),
+        }
+    }
+}
+
+impl Default for VoiceBackend {
+    fn default() -> Self {
+        Self::new(SilentVoice, SilentVoice)
+    }
+}
+
+/// Who has been talking recently, the local player included.
+#[derive(Resource, Debug, Default)]
+pub struct VoiceActivity {
+    last_heard: HashMap<u64, f32>,
+    sequence: u32,
+}
+
+impl VoiceActivity {
+    pub fn record(&mut self, speaker: u64, now: f32) {
+        self.last_heard.insert(speaker, now);
+    }
+
+    pub fn is_talking(&self, speaker: u64, now: f32) -> bool {
+        self.last_heard
+            .get(&speaker)
+            .is_some_and(|heard| now - heard <= VOICE_ACTIVITY_HOLD_SECS)
+    }
+
+    /// Speakers heard within the hold time, in a stable order.
+    pub fn speakers(&self, now: f32) -> Vec<u64> {
+        let mut speakers: Vec<u64> = self
+            .last_heard
+            .keys()
+            .copied()
+            .filter(|speaker| self.is_talking(*speaker, now))
+            .collect();
+        speakers.sort_unstable();
+        speakers
+    }
+}
+
+/// Linear falloff from full volume next to the speaker to silence at the proximity range.
+pub fn proximity_volume(distance: f32) -> f32 {
+    (1.0 - distance / VOICE_PROXIMITY_RANGE).clamp(0.0, 1.0)
+}
+
+/// Push-to-talk (V) proximity voice chat with a talking indicator.
+pub struct ClientVoicePlugin;
+
+impl Plugin for ClientVoicePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<VoiceBackend>();
+        app.init_resource::<VoiceActivity>();
+        app.add_systems(Startup, spawn_voice_indicator);
+        app.add_systems(
+            Update,
+            (send_voice_frames, play_voice_frames, update_voice_indicator).chain(),
+        );
+    }
+}
+
+#[derive(Component)]
+struct VoiceIndicatorText;
+
+fn spawn_voice_indicator(mut commands: Commands) {
+    commands.spawn((
+        Name::new("VoiceIndicator"),
+        VoiceIndicatorText,
+        Text::new(""),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.6, 1.0, 0.6)),
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(16.0),
+            bottom: Val::Px(120.0),
+            ..default()
+        },
+    ));
+}
+
+fn send_voice_frames(
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    local_player_id: Res<LocalPlayerId>,
+    mut backend: ResMut<VoiceBackend>,
+    mut activity: ResMut<VoiceActivity>,
+    mut sender_q: Query<&mut MessageSender<VoiceFrame>, (With<Client>, With<Connected>)>,
+) {
+    // Always drain the capture so releasing push-to-talk does not leave stale audio behind.
+    let frames = backend.capture.poll_frames();
+    if !keys.pressed(PUSH_TO_TALK) {
+        return;
+    }
+    let Some(mut sender) = sender_q.iter_mut().next() else {
+        return;
+    };
+
+    for data in frames {
+        if data.len() > MAX_VOICE_FRAME_BYTES {
+            continue;
+        }
+        activity.sequence = activity.sequence.wrapping_add(1);
+        sender.send::<VoiceChannel>(VoiceFrame {
+            speaker: local_player_id.0,
+            sequence: activity.sequence,
+            data,
+        });
+    }
+    activity.record(local_player_id.0, time.elapsed_secs());
+}
+
+fn play_voice_frames(
+    time: Res<Time>,
+    local_player_id: Res<LocalPlayerId>,
+    mixer: Option<Res<AudioMixer>>,
+    mut backend: ResMut<VoiceBackend>,
+    mut activity: ResMut<VoiceActivity>,
+    mut receivers: Query<&mut MessageReceiver<VoiceFrame>, With<Client>>,
+    players: Query<(&PlayerId, &Position)>,
+) {
+    let position_of = |peer: u64| {
+        players
+            .iter()
+            .find(|(player_id, _)| player_id.0.to_bits() == peer)
+            .map(|(_, position)| position.0)
+    };
+    let listener = position_of(local_player_id.0);
+
+    for mut receiver in receivers.iter_mut() {
+        for frame in receiver.receive() {
+            activity.record(frame.speaker, time.elapsed_secs());
+
+            let distance = match (listener, position_of(frame.speaker)) {
+                (Some(listener), Some(speaker)) => listener.distance(speaker),
+                _ => 0.0,
+            };
+            let volume = proximity_volume(distance);
+            let volume = mixer
+                .as_ref()
+                .map_or(volume, |mixer| mixer.mix(AudioBus::Voice, volume, 1.0));
+            backend
+                .playback
+                .play_frame(frame.speaker, &frame.data, volume);
+        }
+    }
+}
+
+fn update_voice_indicator(
+    time: Res<Time>,
+    local_player_id: Res<LocalPlayerId>,
+    activity: Res<VoiceActivity>,
+    players: Query<(&PlayerId, &Name)>,
+    mut indicator: Query<&mut Text, With<VoiceIndicatorText>>,
+) {
+    let Ok(mut text) = indicator.single_mut() else {
+        return;
+    };
+
+    let lines: Vec<String> = activity
+        .speakers(time.elapsed_secs())
+        .into_iter()
+        .map(|speaker| {
+            if speaker == local_player_id.0 {
+                return "🎙 You".to_string();
+            }
+            let name = players
+                .iter()
+                .find(|(player_id, _)| player_id.0.to_bits() == speaker)
+                .map_or_else(
+                    || format!("Player {}", speaker),
+                    |(_, name)| name.to_string(),
+                );
+            format!("🔊 {}", name)
+        })
+        .collect();
+
+    let content = lines.join("\n");
+    if **text != content {
+        **text = content;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{VOICE_ACTIVITY_HOLD_SECS, VoiceActivity, proximity_volume};
+    use shared::protocol::VOICE_PROXIMITY_RANGE;
+
+    #[test]
+    fn speakers_fade_out_after_the_hold_time() {
+        let mut activity = VoiceActivity::default();
+        activity.record(7, 1.0);
+        activity.record(3, 1.2);
+
+        assert_eq!(activity.speakers(1.25), vec![3, 7]);
+        assert_eq!(
+            activity.speakers(1.0 + VOICE_ACTIVITY_HOLD_SECS + 0.1),
+            vec![3]
+        );
+        assert!(activity.speakers(5.0).is_empty());
+    }
+
+    #[test]
+    fn voice_fades_with_distance() {
+        assert_eq!(proximity_volume(0.0), 1.0);
+        assert!((proximity_volume(VOICE_PROXIMITY_RANGE * 0.5) - 0.5).abs() < 1e-6);
+        assert_eq!(proximity_volume(VOICE_PROXIMITY_RANGE * 2.0), 0.0);
+    }
+}
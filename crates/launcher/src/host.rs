@@ -3,12 +3,9 @@ use bevy::prelude::{
     PluginGroup, Shader, StandardMaterial, Window, WindowPlugin, default,
 };
 use bevy::window::PresentMode;
-use client::{
-    ClientGameState, Headless, LocalPlayerId, camera::ClientCameraPlugin, debug::ClientDebugPlugin,
-    entities::ClientEntitiesPlugin, game::ClientGameCyclePlugin, hud::ClientHudPlugin,
-    inputs::ClientInputPlugin, lobby::ClientLobbyPlugin, network::ClientNetworkPlugin,
-    vfx::ClientVFXPlugin,
-};
+use client::debug::ClientDebugPlugin;
+use client::debug::level_editor::ClientLevelEditorPlugin;
+use client::{ClientGameState, Headless, LocalPlayerId, compose_client_plugins};
 use lightyear::prelude::server::ServerPlugins;
 use std::time::Duration;
 
@@ -18,10 +15,8 @@ use bevy::render::{
     settings::{Backends, WgpuSettings},
 };
 
-use server::{
-    ServerGameState, debug::ServerDebugPlugin, entities::ServerEntitiesPlugin,
-    lobby::ServerLobbyPlugin, network::ServerNetworkPlugin,
-};
+use server::{ServerGameState, compose_server_plugins};
+use shared::composition::{ComposeApp, check_composition};
 use shared::{NetworkMode, SharedPlugin};
 
 use lightyear::prelude::client::ClientPlugins;
@@ -30,17 +25,17 @@ pub fn create_host_app(headless: bool, asset_path: String) -> App {
     let mut host_app = App::new();
     let client_id = 1;
 
-    host_app.insert_resource(Headless(headless));
-    host_app.insert_resource(NetworkMode::Local);
+    host_app.compose_resource(Headless(headless));
+    host_app.compose_resource(NetworkMode::Local);
 
     if headless {
         // Add base plugins first for headless mode
         host_app.add_plugins(MinimalPlugins);
-        host_app.add_plugins(bevy::state::app::StatesPlugin);
-        host_app.add_plugins(bevy::diagnostic::DiagnosticsPlugin);
-        host_app.add_plugins(bevy::scene::ScenePlugin);
-        host_app.add_plugins(bevy::mesh::MeshPlugin);
-        host_app.add_plugins(bevy::animation::AnimationPlugin);
+        host_app.compose_plugin(bevy::state::app::StatesPlugin);
+        host_app.compose_plugin(bevy::diagnostic::DiagnosticsPlugin);
+        host_app.compose_plugin(bevy::scene::ScenePlugin);
+        host_app.compose_plugin(bevy::mesh::MeshPlugin);
+        host_app.compose_plugin(bevy::animation::AnimationPlugin);
         host_app.add_plugins(AssetPlugin {
             file_path: asset_path.clone(),
             ..Default::default()
@@ -102,40 +97,29 @@ pub fn create_host_app(headless: bool, asset_path: String) -> App {
     host_app.add_plugins(ServerPlugins {
         tick_duration: Duration::from_secs_f64(1.0 / shared::FIXED_TIMESTEP_HZ),
     });
-    host_app.add_plugins(ServerNetworkPlugin);
-    host_app.add_plugins(ServerLobbyPlugin);
-    host_app.add_plugins(ServerEntitiesPlugin);
-    host_app.add_plugins(ServerDebugPlugin);
-    host_app.init_state::<ServerGameState>();
-    host_app.insert_state(ServerGameState::Lobby);
+    compose_server_plugins(&mut host_app);
+    host_app.compose_state(ServerGameState::Lobby);
 
     // Add client plugins to the same app (HostServer setup)
     host_app.add_plugins(ClientPlugins {
         tick_duration: Duration::from_secs_f64(1.0 / shared::FIXED_TIMESTEP_HZ),
     });
-    host_app.add_plugins(SharedPlugin);
+    host_app.compose_plugin(SharedPlugin);
 
-    host_app.insert_resource(LocalPlayerId(client_id));
+    host_app.compose_resource(LocalPlayerId(client_id));
 
     // Create the host client entity in OnEnter(ClientGameState::Lobby) system
     // The client network plugin will handle creating the HostClient entity
 
-    host_app.add_plugins(ClientNetworkPlugin);
-    host_app.add_plugins(ClientInputPlugin);
-    host_app.add_plugins(ClientCameraPlugin);
-
-    host_app.add_plugins(ClientEntitiesPlugin);
-    host_app.add_plugins(ClientLobbyPlugin);
-    host_app.add_plugins(ClientGameCyclePlugin);
-    host_app.add_plugins(ClientHudPlugin);
+    compose_client_plugins(&mut host_app, headless);
 
-    host_app.init_state::<ClientGameState>();
-    host_app.insert_state(ClientGameState::Lobby);
+    host_app.compose_state(ClientGameState::Lobby);
 
     if !headless {
-        host_app.add_plugins(ClientDebugPlugin);
-        host_app.add_plugins(ClientVFXPlugin);
+        host_app.compose_plugin(ClientDebugPlugin);
+        host_app.compose_plugin(ClientLevelEditorPlugin);
     }
+    check_composition(&host_app);
 
     host_app
 }
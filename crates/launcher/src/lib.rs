@@ -1,5 +1,7 @@
 pub mod host;
 pub mod native;
+#[cfg(not(target_family = "wasm"))]
+pub mod trace_chrome;
 
 #[cfg(test)]
 mod tests;
@@ -1,11 +1,39 @@
 use crate::host::create_host_app;
+use crate::trace_chrome;
 use clap::{Parser, ValueEnum};
 use client::AutoJoin;
+use client::ClientGameState;
+use client::analytics::create_analytics_app;
 use client::create_client_app;
+use client::demo::DemoCaptureSettings;
+use client::inspector::{ClientInspectorPlugin, DEFAULT_INSPECTOR_CLIENT_ID, InspectorFilter};
 use client::lobby::AutoStart;
 use client::local_menu::LocalMenuPlugin;
+use client::resolution::DynamicResolution;
+use server::afk::{AfkAction, AfkSettings};
+use server::bot_behavior::BotBehaviorSettings;
+use server::bot_dialogue::BotDialogueSettings;
+use server::bot_policy::BotPolicySettings;
+use server::console::AdminConsoleSettings;
 use server::create_server_app;
 use server::lobby::AutoStartOnLobbyReady;
+use server::match_events::MatchEventsSettings;
+use server::match_recap::MatchRecapSettings;
+use server::metrics::MetricsSettings;
+use server::network::rcon::RconSettings;
+#[cfg(feature = "remote-agents")]
+use server::remote_agents::RemoteAgentSettings;
+use server::replication_profile::ReplicationProfile;
+use server::snapshot::SnapshotSettings;
+use shared::aim_assist::AimAssistSettings;
+use shared::balance::{BalanceConfig, BalanceConfigPath};
+use shared::balance_sim::{
+    BalanceVariant, Matchup, SimulationSettings, format_balance_report, run_balance_simulation,
+};
+use shared::components::match_timer::{DEFAULT_MATCH_DURATION_SECS, MatchTimerSettings};
+use shared::components::team::TeamRules;
+use shared::manifest::protocol_manifest_json;
+use shared::protocol::EntitySnapshotSubscribe;
 use shared::{GymMode, NetworkMode};
 
 #[derive(Parser)]
@@ -22,7 +50,29 @@ EXAMPLES:
     cargo run --bin launcher -- client --auto-join --client-id 2 # Auto-join a game
     cargo run --bin launcher -- server                           # Start dedicated server
     cargo run --bin launcher -- server --stop-after 30          # Start server, stop after 30 seconds
+    cargo run --bin launcher -- server --bot-policy bots.policy # Bots driven by a trained checkpoint
+    cargo run --bin launcher -- server --bot-tree assets/ai/classic_bot.ron # Bots driven by a behavior tree file
+    cargo run --bin launcher --features remote-agents -- server --headless --remote-agents-port 9400 # Bots driven by trainers over TCP
+    cargo run --bin launcher -- server --events-port 9001        # Stream match events over WebSocket
+    cargo run --bin launcher -- server --admin-port 9002         # Admin console on localhost:9002 (and stdin)
+    RCON_PASSWORD=secret cargo run --bin launcher -- server --rcon-port 27015 # Source RCON for hosting panels
+    RCON_PASSWORD=secret cargo run --bin launcher -- server --rcon-port 27015 --rcon-public # RCON from other machines
+    cargo run --bin launcher -- server --recap-llm target/release/llm-recap # Narrate match recaps with the llm crate
+    cargo run --bin launcher -- server --match-records records   # Save every match with its recap
+    cargo run --bin launcher -- server --metrics-port 9100       # Prometheus metrics on :9100/metrics
+    cargo run --bin launcher -- server --trace-chrome trace.json # Record tick spans for chrome://tracing
+    cargo run --bin launcher -- server --aim-assist              # Casual lobby with projectile aim assist
+    cargo run --bin launcher -- server --match-duration 300       # Five minute matches
+    cargo run --bin launcher -- server --afk-secs 90 --afk-kick    # Kick players idle for 90 seconds
+    cargo run --bin launcher -- server --balance balance.ron     # Load tuned balance values
     cargo run --bin launcher -- client --auto-host --stop-after 60 # Auto-host, stop after 1 minute
+    cargo run --bin launcher -- client --target-fps 144           # Scale render resolution to hold 144 FPS
+    cargo run --bin launcher -- client --record-demo demos/1.demo  # Record play for imitation learning
+    INSPECTOR_PASSWORD=secret cargo run --bin launcher -- server  # Let inspectors with the password in
+    INSPECTOR_PASSWORD=secret cargo run --bin launcher -- inspect --component health # Live-diff replicated Health
+    cargo run --bin launcher -- manifest                         # Print the protocol manifest as JSON
+    cargo run --bin launcher -- analytics --telemetry records/match_1.ron # Heatmaps of a recorded match
+    cargo run --bin launcher -- balance-sim --matches 200        # Compare balance variants with bot matches
 ")]
 struct Cli {
     #[arg(value_enum)]
@@ -53,6 +103,136 @@ struct Cli {
     #[arg(long, default_value_t = false)]
     #[arg(help = "Use gym mode (test environment with simple square room and one NPC)")]
     gym: bool,
+
+    #[arg(long, default_value_t = false)]
+    #[arg(help = "Server mode: replicate cosmetics in gym mode too, for watching with a client")]
+    full_replication: bool,
+
+    #[arg(long)]
+    #[arg(help = "Inspect mode: only show components whose name contains this string")]
+    component: Option<String>,
+
+    #[arg(long)]
+    #[arg(help = "Inspect mode: only show the entity with these bits")]
+    entity: Option<u64>,
+
+    #[arg(long)]
+    #[arg(help = "Let a trained policy checkpoint drive the bots (server and host modes)")]
+    bot_policy: Option<std::path::PathBuf>,
+
+    #[arg(long)]
+    #[arg(help = "Drive the bots by this RON behavior tree (server and host modes)")]
+    bot_tree: Option<std::path::PathBuf>,
+
+    #[cfg(feature = "remote-agents")]
+    #[arg(long, conflicts_with = "bot_policy")]
+    #[arg(help = "Let localhost processes drive the bots over TCP on this port (server mode)")]
+    remote_agents_port: Option<u16>,
+
+    #[cfg(feature = "remote-agents")]
+    #[arg(long, default_value_t = false, requires = "remote_agents_port")]
+    #[arg(help = "Accept remote agents from any address, not just localhost")]
+    remote_agents_public: bool,
+
+    #[arg(long)]
+    #[arg(help = "Stream match events over WebSocket on this port (server and host modes)")]
+    events_port: Option<u16>,
+
+    #[arg(long)]
+    #[arg(help = "Record tracing spans of every tick to this chrome://tracing file")]
+    trace_chrome: Option<std::path::PathBuf>,
+
+    #[arg(long)]
+    #[arg(help = "Serve Prometheus metrics on this port at /metrics (server and host modes)")]
+    metrics_port: Option<u16>,
+
+    #[arg(long)]
+    #[arg(help = "Accept admin console commands on this localhost port (server and host modes)")]
+    admin_port: Option<u16>,
+
+    #[arg(long)]
+    #[arg(help = "Accept Source RCON connections on this port (server and host modes)")]
+    rcon_port: Option<u16>,
+
+    #[arg(long)]
+    #[arg(help = "RCON password, or set RCON_PASSWORD (required with --rcon-port)")]
+    rcon_password: Option<String>,
+
+    #[arg(long, default_value_t = false)]
+    #[arg(help = "Accept RCON from any address, not just localhost (plaintext: use a tunnel)")]
+    rcon_public: bool,
+
+    #[arg(long)]
+    #[arg(help = "Password inspectors need for entity snapshots, or set INSPECTOR_PASSWORD")]
+    inspector_password: Option<String>,
+
+    #[arg(long)]
+    #[arg(help = "Write post-match recaps with this llm-recap executable (server and host modes)")]
+    recap_llm: Option<std::path::PathBuf>,
+
+    #[arg(long)]
+    #[arg(help = "Have bots taunt with this llm-dialogue executable (server and host modes)")]
+    bot_dialogue_llm: Option<std::path::PathBuf>,
+
+    #[arg(long)]
+    #[arg(help = "Save each match record and its recap to this directory (server and host modes)")]
+    match_records: Option<std::path::PathBuf>,
+
+    #[arg(long, default_value_t = false)]
+    #[arg(help = "Pull shots slightly toward nearby targets (server and host modes)")]
+    aim_assist: bool,
+
+    #[arg(long, default_value_t = false)]
+    #[arg(help = "Ranked lobby: aim assist is always disabled (server and host modes)")]
+    ranked: bool,
+
+    #[arg(long, default_value_t = false)]
+    #[arg(help = "Let teammates damage each other (server and host modes)")]
+    friendly_fire: bool,
+
+    #[arg(long, default_value_t = DEFAULT_MATCH_DURATION_SECS)]
+    #[arg(help = "Match length in seconds before final scores (server and host modes)")]
+    match_duration: f32,
+
+    #[arg(long, default_value_t = 60.0)]
+    #[arg(help = "Warn players idle for this many seconds (server and host modes)")]
+    afk_warn_secs: f32,
+
+    #[arg(long, default_value_t = 120.0)]
+    #[arg(help = "Seconds idle before the AFK action, 0 disables (server and host modes)")]
+    afk_secs: f32,
+
+    #[arg(long, default_value_t = false)]
+    #[arg(help = "Kick idle players instead of moving them to spectators (server and host modes)")]
+    afk_kick: bool,
+
+    #[arg(long)]
+    #[arg(help = "Balance file to load, and to save hearing tuning to (server and host modes)")]
+    balance: Option<std::path::PathBuf>,
+
+    #[arg(long, default_value_t = 60.0)]
+    #[arg(help = "Frame rate dynamic resolution tries to hold (client and host modes)")]
+    target_fps: f32,
+
+    #[arg(long, default_value_t = 0.5)]
+    #[arg(help = "Lowest render scale dynamic resolution may drop to (client and host modes)")]
+    min_render_scale: f32,
+
+    #[arg(long)]
+    #[arg(help = "Record demonstrations of the local player to this file (client and host modes)")]
+    record_demo: Option<std::path::PathBuf>,
+
+    #[arg(long)]
+    #[arg(help = "Analytics mode: a match telemetry file from --match-records (repeatable)")]
+    telemetry: Vec<std::path::PathBuf>,
+
+    #[arg(long, default_value_t = 100)]
+    #[arg(help = "Balance sim mode: matches played per variant and matchup")]
+    matches: usize,
+
+    #[arg(long, default_value_t = 0)]
+    #[arg(help = "Balance sim mode: seed of the first match")]
+    seed: u64,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
@@ -60,10 +240,23 @@ enum Mode {
     Client,
     Server,
     Host,
+    Inspect,
+    Manifest,
+    BalanceSim,
+    Analytics,
 }
 
 pub fn run() {
     let cli = Cli::parse();
+    let inspector_password = cli
+        .inspector_password
+        .clone()
+        .or_else(|| std::env::var("INSPECTOR_PASSWORD").ok());
+    if let Some(path) = &cli.trace_chrome
+        && let Err(e) = trace_chrome::start(path)
+    {
+        eprintln!("Chrome tracing to {} not started: {}", path.display(), e);
+    }
 
     match cli.mode {
         Mode::Client => {
@@ -81,6 +274,14 @@ pub fn run() {
             };
 
             client_app.insert_resource(GymMode(cli.gym));
+            client_app.insert_resource(DynamicResolution::with_bounds(
+                cli.target_fps,
+                cli.min_render_scale,
+                1.0,
+            ));
+            client_app.insert_resource(DemoCaptureSettings {
+                path: cli.record_demo,
+            });
 
             if cli.auto_start {
                 client_app.insert_resource(AutoStart(true));
@@ -99,6 +300,7 @@ pub fn run() {
                 std::thread::spawn(move || {
                     std::thread::sleep(std::time::Duration::from_secs(stop_after_seconds));
                     println!("Auto-stopping after {} seconds", stop_after_seconds);
+                    trace_chrome::finish();
                     std::process::exit(0);
                 });
             }
@@ -110,6 +312,75 @@ pub fn run() {
             if cli.gym {
                 server_app.insert_resource(GymMode(cli.gym));
             }
+            server_app.insert_resource(ReplicationProfile::for_gym(
+                cli.gym && !cli.full_replication,
+            ));
+
+            if let Some(checkpoint) = cli.bot_policy {
+                server_app.insert_resource(BotPolicySettings::from_checkpoint(checkpoint));
+            }
+
+            if let Some(tree) = cli.bot_tree {
+                server_app.insert_resource(BotBehaviorSettings::from_file(tree));
+            }
+
+            #[cfg(feature = "remote-agents")]
+            if let Some(port) = cli.remote_agents_port {
+                let settings = RemoteAgentSettings::on_port(port);
+                server_app.insert_resource(if cli.remote_agents_public {
+                    settings.public()
+                } else {
+                    settings
+                });
+            }
+
+            if let Some(port) = cli.events_port {
+                server_app.insert_resource(MatchEventsSettings::on_port(port));
+            }
+
+            if let Some(port) = cli.metrics_port {
+                server_app.insert_resource(MetricsSettings::on_port(port));
+            }
+
+            // Dedicated servers have no UI: the console reads commands from stdin.
+            let console = AdminConsoleSettings {
+                stdin: true,
+                addr: None,
+            };
+            server_app.insert_resource(match cli.admin_port {
+                Some(port) => console.on_port(port),
+                None => console,
+            });
+            if let Some(rcon) = rcon_settings(cli.rcon_port, cli.rcon_password, cli.rcon_public) {
+                server_app.insert_resource(rcon);
+            }
+            server_app.insert_resource(SnapshotSettings {
+                password: inspector_password,
+            });
+            server_app.insert_resource(MatchRecapSettings {
+                llm_command: cli.recap_llm,
+                records_dir: cli.match_records,
+            });
+            server_app.insert_resource(BotDialogueSettings {
+                llm_command: cli.bot_dialogue_llm,
+                ..Default::default()
+            });
+
+            server_app.insert_resource(AimAssistSettings {
+                enabled: cli.aim_assist,
+                ranked: cli.ranked,
+            });
+            server_app.insert_resource(TeamRules {
+                friendly_fire: cli.friendly_fire,
+            });
+            server_app.insert_resource(MatchTimerSettings {
+                duration_secs: cli.match_duration,
+            });
+            server_app.insert_resource(afk_settings(cli.afk_warn_secs, cli.afk_secs, cli.afk_kick));
+            if let Some(path) = cli.balance {
+                server_app.insert_resource(balance_config(&path));
+                server_app.insert_resource(BalanceConfigPath(path));
+            }
 
             if let Some(stop_after_seconds) = cli.stop_after
                 && stop_after_seconds > 0
@@ -117,6 +388,7 @@ pub fn run() {
                 std::thread::spawn(move || {
                     std::thread::sleep(std::time::Duration::from_secs(stop_after_seconds));
                     println!("Auto-stopping server after {} seconds", stop_after_seconds);
+                    trace_chrome::finish();
                     std::process::exit(0);
                 });
             }
@@ -130,6 +402,64 @@ pub fn run() {
                 host_app.insert_resource(GymMode(cli.gym));
             }
 
+            if let Some(checkpoint) = cli.bot_policy {
+                host_app.insert_resource(BotPolicySettings::from_checkpoint(checkpoint));
+            }
+
+            if let Some(tree) = cli.bot_tree {
+                host_app.insert_resource(BotBehaviorSettings::from_file(tree));
+            }
+
+            if let Some(port) = cli.events_port {
+                host_app.insert_resource(MatchEventsSettings::on_port(port));
+            }
+
+            if let Some(port) = cli.metrics_port {
+                host_app.insert_resource(MetricsSettings::on_port(port));
+            }
+
+            if let Some(port) = cli.admin_port {
+                host_app.insert_resource(AdminConsoleSettings::default().on_port(port));
+            }
+            if let Some(rcon) = rcon_settings(cli.rcon_port, cli.rcon_password, cli.rcon_public) {
+                host_app.insert_resource(rcon);
+            }
+            host_app.insert_resource(SnapshotSettings {
+                password: inspector_password,
+            });
+            host_app.insert_resource(MatchRecapSettings {
+                llm_command: cli.recap_llm,
+                records_dir: cli.match_records,
+            });
+            host_app.insert_resource(BotDialogueSettings {
+                llm_command: cli.bot_dialogue_llm,
+                ..Default::default()
+            });
+
+            host_app.insert_resource(AimAssistSettings {
+                enabled: cli.aim_assist,
+                ranked: cli.ranked,
+            });
+            host_app.insert_resource(TeamRules {
+                friendly_fire: cli.friendly_fire,
+            });
+            host_app.insert_resource(MatchTimerSettings {
+                duration_secs: cli.match_duration,
+            });
+            host_app.insert_resource(afk_settings(cli.afk_warn_secs, cli.afk_secs, cli.afk_kick));
+            if let Some(path) = cli.balance {
+                host_app.insert_resource(balance_config(&path));
+                host_app.insert_resource(BalanceConfigPath(path));
+            }
+            host_app.insert_resource(DynamicResolution::with_bounds(
+                cli.target_fps,
+                cli.min_render_scale,
+                1.0,
+            ));
+            host_app.insert_resource(DemoCaptureSettings {
+                path: cli.record_demo,
+            });
+
             if cli.auto_start {
                 host_app.insert_resource(AutoStart(true));
                 host_app.insert_resource(AutoStartOnLobbyReady(true));
@@ -141,11 +471,116 @@ pub fn run() {
                 std::thread::spawn(move || {
                     std::thread::sleep(std::time::Duration::from_secs(stop_after_seconds));
                     println!("Auto-stopping after {} seconds", stop_after_seconds);
+                    trace_chrome::finish();
                     std::process::exit(0);
                 });
             }
 
             host_app.run();
         }
+        Mode::Inspect => {
+            let client_id = if cli.client_id == 0 {
+                DEFAULT_INSPECTOR_CLIENT_ID
+            } else {
+                cli.client_id
+            };
+            let mut inspect_app = create_client_app(
+                client_id,
+                "../../assets".to_string(),
+                true,
+                NetworkMode::Udp,
+            );
+            inspect_app.insert_resource(InspectorFilter(EntitySnapshotSubscribe {
+                password: inspector_password.unwrap_or_default(),
+                component_filter: cli.component,
+                entity_filter: cli.entity,
+            }));
+            inspect_app.add_plugins(ClientInspectorPlugin);
+            inspect_app.insert_state(ClientGameState::Lobby);
+
+            if let Some(stop_after_seconds) = cli.stop_after
+                && stop_after_seconds > 0
+            {
+                std::thread::spawn(move || {
+                    std::thread::sleep(std::time::Duration::from_secs(stop_after_seconds));
+                    trace_chrome::finish();
+                    std::process::exit(0);
+                });
+            }
+
+            inspect_app.run();
+        }
+        Mode::Manifest => {
+            print!("{}", protocol_manifest_json());
+        }
+        Mode::BalanceSim => {
+            let settings = SimulationSettings {
+                matches: cli.matches,
+                seed: cli.seed,
+                ..Default::default()
+            };
+            let reports = run_balance_simulation(
+                &BalanceVariant::defaults(),
+                &Matchup::defaults(),
+                &settings,
+            );
+            println!("{}", format_balance_report(&reports));
+        }
+        Mode::Analytics => {
+            create_analytics_app("../../assets".to_string(), cli.telemetry).run();
+        }
+    }
+    trace_chrome::finish();
+}
+
+/// AFK settings from the command line; `afk_secs` of 0 turns detection off.
+fn afk_settings(warn_after_secs: f32, act_after_secs: f32, kick: bool) -> AfkSettings {
+    AfkSettings {
+        enabled: act_after_secs > 0.0,
+        warn_after_secs,
+        act_after_secs,
+        action: if kick {
+            AfkAction::Kick
+        } else {
+            AfkAction::MoveToSpectators
+        },
+    }
+}
+
+/// Balance from `path`. A missing file starts from the defaults, so tuning can create it;
+/// a broken one is reported and the defaults are used.
+fn balance_config(path: &std::path::Path) -> BalanceConfig {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return BalanceConfig::default(),
+        Err(e) => {
+            eprintln!("Failed to read {}: {}, using defaults", path.display(), e);
+            return BalanceConfig::default();
+        }
+    };
+    BalanceConfig::from_ron(&text).unwrap_or_else(|e| {
+        eprintln!("Invalid balance {}: {}, using defaults", path.display(), e);
+        BalanceConfig::default()
+    })
+}
+
+/// RCON settings from the command line, with the password falling back to `RCON_PASSWORD`
+/// so it does not have to show up in the process list. Localhost only unless `public`.
+fn rcon_settings(
+    port: Option<u16>,
+    password: Option<String>,
+    public: bool,
+) -> Option<RconSettings> {
+    let port = port?;
+    let password = password.or_else(|| std::env::var("RCON_PASSWORD").ok());
+    match password {
+        Some(password) if !password.is_empty() => {
+            let settings = RconSettings::on_port(port, password);
+            Some(if public { settings.public() } else { settings })
+        }
+        _ => {
+            eprintln!("--rcon-port needs --rcon-password or RCON_PASSWORD, RCON stays off");
+            None
+        }
     }
 }
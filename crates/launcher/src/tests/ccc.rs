@@ -44,6 +44,7 @@ fn spawn_local_player_for_ccc(client_app: &mut App, player_id: u64) -> Entity {
                 ground_normal: Vec3::Y,
                 ground_distance: 0.0,
                 ground_tick: 0,
+                ground_velocity: Vec3::ZERO,
             },
             action_state,
         ))
@@ -26,12 +26,13 @@ fn test_shooting_applies_damage_and_sets_death_state() {
     use avian3d::prelude::{Position, Rotation};
     use bevy::prelude::Update;
     use leafwing_input_manager::prelude::ActionState;
+    use shared::clock::GameClockPlugin;
     use shared::components::health::Health;
     use shared::components::weapons::{Projectile, ProjectileGun, fire_projectile_gun_system};
     use shared::inputs::input::PlayerAction;
 
     let mut app = App::new();
-    app.add_plugins(MinimalPlugins);
+    app.add_plugins((MinimalPlugins, GameClockPlugin));
     app.add_systems(Update, fire_projectile_gun_system);
 
     let mut action_state = ActionState::<PlayerAction>::default();
@@ -183,6 +184,7 @@ fn test_e2e_procedural_level_load_many_characters_move_and_shoot() {
     use lightyear::prelude::{ControlledBy, PeerId};
     use shared::components::health::Health;
     use shared::components::weapons::Gun;
+    use shared::bots::MatchBotSettings;
     use shared::inputs::input::PlayerAction;
     use shared::inputs::movement::GroundState;
     use shared::level::building::{
@@ -208,7 +210,11 @@ fn test_e2e_procedural_level_load_many_characters_move_and_shoot() {
         }
 
         build_level_physics(commands.reborrow(), &level_graph.0);
-        build_procedural_runtime_content(&mut commands, &level_graph.0);
+        build_procedural_runtime_content(
+            &mut commands,
+            &level_graph.0,
+            &MatchBotSettings::default(),
+        );
         loaded.0 = true;
     }
 
@@ -259,6 +265,7 @@ fn test_e2e_procedural_level_load_many_characters_move_and_shoot() {
                     ground_normal: Vec3::Y,
                     ground_distance: 0.0,
                     ground_tick: 1,
+                    ground_velocity: Vec3::ZERO,
                 },
                 Gun {
                     cooldown: bevy::prelude::Timer::from_seconds(
@@ -361,6 +368,7 @@ fn test_e2e_procedural_level_characters_do_not_fall_below_threshold() {
     use bevy::prelude::{Commands, FixedUpdate, Quat, Resource, Update, Vec2};
     use leafwing_input_manager::prelude::ActionState;
     use lightyear::prelude::{ControlledBy, PeerId};
+    use shared::bots::MatchBotSettings;
     use shared::inputs::input::PlayerAction;
     use shared::inputs::movement::GroundState;
     use shared::level::building::{
@@ -385,7 +393,11 @@ fn test_e2e_procedural_level_characters_do_not_fall_below_threshold() {
         }
 
         build_level_physics(commands.reborrow(), &level_graph.0);
-        build_procedural_runtime_content(&mut commands, &level_graph.0);
+        build_procedural_runtime_content(
+            &mut commands,
+            &level_graph.0,
+            &MatchBotSettings::default(),
+        );
         loaded.0 = true;
     }
 
@@ -432,6 +444,7 @@ fn test_e2e_procedural_level_characters_do_not_fall_below_threshold() {
                     ground_normal: Vec3::Y,
                     ground_distance: 0.0,
                     ground_tick: 1,
+                    ground_velocity: Vec3::ZERO,
                 },
                 action_state,
                 ControlledBy {
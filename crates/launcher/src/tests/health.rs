@@ -18,11 +18,15 @@ fn npc_health_accumulates_two_player_hits_to_fifty() {
         target,
         amount: 25.0,
         source: None,
+        penetration: 0.0,
+        headshot: false,
     });
     app.world_mut().write_message(DamageEvent {
         target,
         amount: 25.0,
         source: None,
+        penetration: 0.0,
+        headshot: false,
     });
 
     app.update();
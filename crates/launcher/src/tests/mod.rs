@@ -21,6 +21,7 @@ use server::ServerGameState;
 use server::entities::ServerEntitiesPlugin;
 use server::lobby::ServerLobbyPlugin;
 use server::network::ServerNetworkPlugin;
+use server::replication_profile::ServerReplicationProfilePlugin;
 use shared::{NetworkMode, SharedPlugin};
 use std::time::Duration;
 
@@ -395,15 +396,6 @@ fn translate_server_player_entity(
     }
 }
 
-fn snap_vec3(value: Vec3) -> Vec3 {
-    let scale = 1_000_000.0;
-    Vec3::new(
-        (value.x * scale).round() / scale,
-        (value.y * scale).round() / scale,
-        (value.z * scale).round() / scale,
-    )
-}
-
 fn first_level_seed(app: &mut App) -> Option<u64> {
     use lightyear::prelude::Confirmed;
     use shared::protocol::LevelSeed;
@@ -692,6 +684,7 @@ fn create_test_server_app_with_mode(gym_mode: bool, network_mode: NetworkMode) -
     app.add_plugins(ServerNetworkPlugin);
     app.add_plugins(ServerLobbyPlugin);
     app.add_plugins(ServerEntitiesPlugin);
+    app.add_plugins(ServerReplicationProfilePlugin);
     app.insert_resource(server::lobby::AutoStartOnLobbyReady(false));
     app.init_state::<ServerGameState>();
     app.insert_state(ServerGameState::Lobby);
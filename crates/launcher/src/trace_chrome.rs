@@ -0,0 +1,42 @@
+//! `--trace-chrome`: record tracing spans to a file chrome://tracing and Perfetto can open.
+
+use std::path::Path;
+use std::sync::Mutex;
+
+use tracing_chrome::{ChromeLayerBuilder, FlushGuard, TraceStyle};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::prelude::*;
+
+/// Kept until the trace is finished; dropping it writes out the rest of the file.
+static FLUSH_GUARD: Mutex<Option<FlushGuard>> = Mutex::new(None);
+
+/// Install a global subscriber that logs to stdout and records spans to `path`. Must run
+/// before the app is built: Bevy's log plugin then keeps this subscriber.
+pub fn start(path: &Path) -> Result<(), String> {
+    // Tick phase spans are opened and closed by different systems, possibly on different
+    // threads, so they are recorded from creation to close rather than per enter.
+    let (chrome_layer, guard) = ChromeLayerBuilder::new()
+        .file(path)
+        .trace_style(TraceStyle::Async)
+        .include_args(true)
+        .build();
+    tracing_subscriber::registry()
+        .with(LevelFilter::INFO)
+        .with(tracing_subscriber::fmt::layer())
+        .with(chrome_layer)
+        .try_init()
+        .map_err(|e| e.to_string())?;
+
+    if let Ok(mut flush_guard) = FLUSH_GUARD.lock() {
+        *flush_guard = Some(guard);
+    }
+    println!("Recording a chrome trace to {}", path.display());
+    Ok(())
+}
+
+/// Finish the trace file, if one is being recorded. Call before exiting the process.
+pub fn finish() {
+    if let Ok(mut flush_guard) = FLUSH_GUARD.lock() {
+        flush_guard.take();
+    }
+}
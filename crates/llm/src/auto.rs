@@ -1,4 +1,5 @@
 use anyhow::{Error as E, Result, anyhow};
+use candle_core::quantized::gguf_file;
 use candle_core::{DType, Device, Tensor};
 use candle_examples::token_output_stream::TokenOutputStream;
 use candle_nn::VarBuilder;
@@ -22,8 +23,14 @@ use hf_hub::{Repo, RepoType, api::sync::Api};
 use ndarray::{Array, CowArray, IxDyn};
 use ort::{Environment, SessionBuilder};
 use serde_json::Value;
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use tokenizers::Tokenizer;
+
+use crate::device::{self, DeviceSelection};
+use crate::gguf;
+use crate::tokenize::TokenizerWorker;
 use tracing::debug;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -161,6 +168,17 @@ impl UnifiedModel {
             Self::Onnx(_) => {}
         }
     }
+
+    /// Logits of the last position of each sequence of the batch `xs`, as `(batch, vocab)`,
+    /// whatever shape the architecture returns them in.
+    pub fn forward_last(&mut self, xs: &Tensor, pos: usize) -> candle_core::Result<Tensor> {
+        let logits = self.forward(xs, pos)?;
+        match logits.rank() {
+            1 => logits.unsqueeze(0),
+            3 => logits.narrow(1, logits.dim(1)? - 1, 1)?.squeeze(1),
+            _ => Ok(logits),
+        }
+    }
 }
 
 pub struct OnnxModel {
@@ -250,6 +268,84 @@ impl OnnxModel {
     }
 }
 
+/// Where a model's files come from: a hub repository, downloaded on demand, or a local
+/// directory laid out the same way.
+enum ModelSource {
+    Hub(hf_hub::api::sync::ApiRepo),
+    Local(PathBuf),
+}
+
+impl ModelSource {
+    fn get(&self, name: &str) -> Result<PathBuf> {
+        match self {
+            Self::Hub(repo) => Ok(repo.get(name)?),
+            Self::Local(dir) => {
+                let path = dir.join(name);
+                if path.is_file() {
+                    Ok(path)
+                } else {
+                    Err(anyhow!("{} not found", path.display()))
+                }
+            }
+        }
+    }
+
+    /// Names of the model's files; sorted for a local directory, so picking the first
+    /// match does not depend on the file system.
+    fn list(&self) -> Result<Vec<String>> {
+        match self {
+            Self::Hub(repo) => Ok(repo
+                .info()?
+                .siblings
+                .into_iter()
+                .map(|sibling| sibling.rfilename)
+                .collect()),
+            Self::Local(dir) => {
+                let mut names = Vec::new();
+                for entry in std::fs::read_dir(dir)? {
+                    let entry = entry?;
+                    if entry.file_type()?.is_file() {
+                        names.push(entry.file_name().to_string_lossy().into_owned());
+                    }
+                }
+                names.sort();
+                Ok(names)
+            }
+        }
+    }
+
+    /// Safetensors weights: the files named in the index of a split model, else the single
+    /// `model.safetensors`.
+    fn safetensors(&self) -> Result<Vec<PathBuf>> {
+        const INDEX: &str = "model.safetensors.index.json";
+        match self {
+            Self::Hub(repo) => {
+                if let Ok(filenames) = candle_examples::hub_load_safetensors(repo, INDEX) {
+                    return Ok(filenames);
+                }
+            }
+            Self::Local(_) => {
+                if let Ok(index) = self.get(INDEX) {
+                    let index: Value = serde_json::from_str(&std::fs::read_to_string(index)?)?;
+                    let weight_map = index
+                        .get("weight_map")
+                        .and_then(Value::as_object)
+                        .ok_or_else(|| anyhow!("{} has no weight_map", INDEX))?;
+                    let mut names: Vec<&str> =
+                        weight_map.values().filter_map(Value::as_str).collect();
+                    names.sort();
+                    names.dedup();
+                    return names.into_iter().map(|name| self.get(name)).collect();
+                }
+            }
+        }
+        Ok(vec![self.get("model.safetensors")?])
+    }
+}
+
+/// Most prompts [`AutoModel::generate_batch`] runs in one forward.
+pub const MAX_BATCH: usize = 8;
+
 #[derive(Debug, Clone)]
 pub struct AutoModelConfig {
     pub max_new_tokens: usize,
@@ -258,6 +354,9 @@ pub struct AutoModelConfig {
     pub repeat_penalty: f32,
     pub repeat_last_n: usize,
     pub seed: u64,
+    /// Generation stops before the first of these appears; the sequence itself is left
+    /// out of the text.
+    pub stop_sequences: Vec<String>,
 }
 
 impl Default for AutoModelConfig {
@@ -269,14 +368,51 @@ impl Default for AutoModelConfig {
             repeat_penalty: 1.1,
             repeat_last_n: 64,
             seed: 42,
+            stop_sequences: Vec::new(),
         }
     }
 }
 
+/// Why a generation ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// The model produced its end-of-text token.
+    EndOfText,
+    /// One of [`AutoModelConfig::stop_sequences`] was generated.
+    StopSequence,
+    /// [`AutoModelConfig::max_new_tokens`] tokens were generated.
+    MaxTokens,
+    /// The callback of [`AutoModel::generate_with_callback`] asked to stop.
+    Cancelled,
+}
+
+/// A finished generation, from [`AutoModel::generate`].
+#[derive(Debug, Clone)]
+pub struct GenerationOutput {
+    /// The new text, without the prompt or the stop sequence that ended it.
+    pub text: String,
+    pub prompt_tokens: usize,
+    /// Tokens generated, those of a stop sequence included.
+    pub tokens: usize,
+    pub elapsed: Duration,
+    pub stop_reason: StopReason,
+}
+
+impl GenerationOutput {
+    pub fn tokens_per_sec(&self) -> f64 {
+        self.tokens as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
 pub struct AutoModel {
     model: UnifiedModel,
+    /// Session whose tokens fill the KV cache, and how many of them were fed.
+    cached_session: Option<u64>,
+    cached_len: usize,
+    next_session: u64,
     device: Device,
     tokenizer: TokenOutputStream,
+    tokenizer_worker: TokenizerWorker,
     logits_processor: LogitsProcessor,
     config: AutoModelConfig,
     model_id: String,
@@ -285,9 +421,13 @@ pub struct AutoModel {
 }
 
 impl AutoModel {
+    /// Load on the device named in `LLM_DEVICE`, or the best one available.
     pub fn from_pretrained(model_id: &str) -> Result<Self> {
-        let device = Self::auto_device()?;
-        Self::from_pretrained_with_device(model_id, &device)
+        Self::from_pretrained_on(model_id, DeviceSelection::from_env()?)
+    }
+
+    pub fn from_pretrained_on(model_id: &str, selection: DeviceSelection) -> Result<Self> {
+        Self::from_pretrained_with_device(model_id, &Self::open_device(selection)?)
     }
 
     pub fn from_pretrained_with_device(model_id: &str, device: &Device) -> Result<Self> {
@@ -297,8 +437,72 @@ impl AutoModel {
             RepoType::Model,
             "main".to_string(),
         ));
+        Self::load(&ModelSource::Hub(repo), model_id, device)
+    }
+
+    /// Load from disk, without the hub: a directory laid out like a hub repository (config,
+    /// tokenizer and weights), or a bare `.gguf` file. A bare file uses the
+    /// `tokenizer.json` next to it if there is one, else the tokenizer it embeds. Runs on
+    /// the device named in `LLM_DEVICE`.
+    pub fn from_local_path(path: impl AsRef<Path>) -> Result<Self> {
+        let device = Self::open_device(DeviceSelection::from_env()?)?;
+        Self::from_local_path_with_device(path, &device)
+    }
 
-        let config_path = repo.get("config.json").ok();
+    pub fn from_local_path_with_device(path: impl AsRef<Path>, device: &Device) -> Result<Self> {
+        let path = path.as_ref();
+        if path.is_dir() {
+            return Self::load(
+                &ModelSource::Local(path.to_path_buf()),
+                &path.display().to_string(),
+                device,
+            );
+        }
+        if !path.is_file() {
+            return Err(anyhow!("No model at {}", path.display()));
+        }
+
+        device::ensure_fits(device, device::estimate_file_bytes(path)?)?;
+        let mut file = std::fs::File::open(path)?;
+        let content = gguf_file::Content::read(&mut file)?;
+        let model_id = path.display().to_string();
+        let architecture = gguf::architecture(&content)
+            .unwrap_or_else(|| Self::detect_architecture_from_name(&model_id));
+        let beside = path.with_file_name("tokenizer.json");
+        let tokenizer = if beside.is_file() {
+            Tokenizer::from_file(beside).map_err(E::msg)?
+        } else {
+            gguf::tokenizer(&content)?
+        };
+        let model = Self::load_gguf_content(path, content, &mut file, &architecture, device)?;
+        Self::assemble(
+            model,
+            tokenizer,
+            device,
+            &model_id,
+            architecture,
+            ModelFormat::QuantizedGguf,
+        )
+    }
+
+    /// A local directory or `.gguf` file when `name` is one, else a hub model id.
+    pub fn open(name: &str) -> Result<Self> {
+        if Path::new(name).exists() {
+            Self::from_local_path(name)
+        } else {
+            Self::from_pretrained(name)
+        }
+    }
+
+    fn open_device(selection: DeviceSelection) -> Result<Device> {
+        match selection.open()? {
+            Some(device) => Ok(device),
+            None => Self::auto_device(),
+        }
+    }
+
+    fn load(source: &ModelSource, model_id: &str, device: &Device) -> Result<Self> {
+        let config_path = source.get("config.json").ok();
         let architecture = if let Some(config_path) = &config_path {
             let config_data = std::fs::read_to_string(config_path)?;
             let config: Value = serde_json::from_str(&config_data)?;
@@ -307,13 +511,13 @@ impl AutoModel {
             Self::detect_architecture_from_name(model_id)
         };
 
-        let files = Self::list_model_files(&repo)?;
+        let files = source.list()?;
         let format = ModelFormat::detect_from_files(&files);
 
         // Try to get tokenizer, with multiple fallback options
-        let tokenizer = if let Ok(tokenizer_path) = repo.get("tokenizer.json") {
+        let tokenizer = if let Ok(tokenizer_path) = source.get("tokenizer.json") {
             Tokenizer::from_file(tokenizer_path).map_err(E::msg)?
-        } else if let Ok(tokenizer_path) = repo.get("tokenizer.model") {
+        } else if let Ok(tokenizer_path) = source.get("tokenizer.model") {
             Tokenizer::from_file(tokenizer_path).map_err(E::msg)?
         } else {
             return Err(anyhow!(
@@ -323,19 +527,33 @@ impl AutoModel {
 
         // Load model based on format and architecture
         let model = Self::load_model(
-            &repo,
+            source,
             &architecture,
             &format,
             device,
             config_path.as_deref(),
         )?;
+        Self::assemble(model, tokenizer, device, model_id, architecture, format)
+    }
 
+    fn assemble(
+        model: UnifiedModel,
+        tokenizer: Tokenizer,
+        device: &Device,
+        model_id: &str,
+        architecture: ModelArchitecture,
+        format: ModelFormat,
+    ) -> Result<Self> {
         let config = AutoModelConfig::default();
         let logits_processor = LogitsProcessor::new(config.seed, config.temperature, config.top_p);
 
         Ok(Self {
             model,
+            cached_session: None,
+            cached_len: 0,
+            next_session: 0,
             device: device.clone(),
+            tokenizer_worker: TokenizerWorker::spawn(tokenizer.clone())?,
             tokenizer: TokenOutputStream::new(tokenizer),
             logits_processor,
             config,
@@ -345,7 +563,8 @@ impl AutoModel {
         })
     }
 
-    /// Auto-detect optimal device with GPU optimizations
+    /// Auto-detect optimal device with GPU optimizations: the CUDA device with the most free
+    /// memory, then Metal, then the CPU.
     fn auto_device() -> Result<Device> {
         // Try CUDA first with optimizations
         if let Ok(device) = Self::try_cuda_optimized() {
@@ -364,7 +583,7 @@ impl AutoModel {
     }
 
     fn try_cuda_optimized() -> Result<Device> {
-        let device = Device::new_cuda(0)?;
+        let device = Device::new_cuda(device::roomiest_cuda_device().unwrap_or(0))?;
 
         // Test GPU with F32 operations for consistency
         let test_size = (512, 512);
@@ -402,19 +621,8 @@ impl AutoModel {
         }
     }
 
-    fn list_model_files(repo: &hf_hub::api::sync::ApiRepo) -> Result<Vec<String>> {
-        // Try to get repo info to list files
-        let repo_info = repo.info()?;
-        let files: Vec<String> = repo_info
-            .siblings
-            .iter()
-            .map(|sibling| sibling.rfilename.clone())
-            .collect();
-        Ok(files)
-    }
-
     fn load_model(
-        repo: &hf_hub::api::sync::ApiRepo,
+        source: &ModelSource,
         architecture: &ModelArchitecture,
         format: &ModelFormat,
         device: &Device,
@@ -422,17 +630,17 @@ impl AutoModel {
     ) -> Result<UnifiedModel> {
         match format {
             ModelFormat::SafeTensors => {
-                Self::load_safetensors_model(repo, architecture, device, config_path)
+                Self::load_safetensors_model(source, architecture, device, config_path)
             }
-            ModelFormat::QuantizedGguf => Self::load_gguf_model(repo, architecture, device),
-            ModelFormat::QuantizedGgml => Self::load_ggml_model(repo, architecture, device),
+            ModelFormat::QuantizedGguf => Self::load_gguf_model(source, architecture, device),
+            ModelFormat::QuantizedGgml => Self::load_ggml_model(source, architecture, device),
 
-            ModelFormat::Onnx => Self::load_onnx_model(repo, device),
+            ModelFormat::Onnx => Self::load_onnx_model(source, device),
         }
     }
 
     fn load_safetensors_model(
-        repo: &hf_hub::api::sync::ApiRepo,
+        source: &ModelSource,
         architecture: &ModelArchitecture,
         device: &Device,
         config_path: Option<&Path>,
@@ -447,16 +655,11 @@ impl AutoModel {
             DType::F32
         };
 
-        // Try to load either split model or single file
-        let filenames = if let Ok(filenames) =
-            candle_examples::hub_load_safetensors(repo, "model.safetensors.index.json")
-        {
-            // Split model with index
-            filenames
-        } else {
-            // Single file model
-            vec![repo.get("model.safetensors")?]
-        };
+        let filenames = source.safetensors()?;
+        device::ensure_fits(
+            device,
+            device::estimate_safetensors_bytes(&filenames, dtype)?,
+        )?;
 
         let vb = unsafe { VarBuilder::from_mmaped_safetensors(&filenames, dtype, device)? };
 
@@ -502,47 +705,53 @@ impl AutoModel {
     }
 
     fn load_gguf_model(
-        repo: &hf_hub::api::sync::ApiRepo,
+        source: &ModelSource,
         architecture: &ModelArchitecture,
         device: &Device,
     ) -> Result<UnifiedModel> {
-        // Find GGUF file
-        let repo_info = repo.info()?;
-        let gguf_files: Vec<_> = repo_info
-            .siblings
-            .iter()
-            .filter(|s| s.rfilename.ends_with(".gguf"))
-            .collect();
-
-        if gguf_files.is_empty() {
-            return Err(anyhow!("No GGUF files found"));
-        }
-
-        let gguf_path = repo.get(&gguf_files[0].rfilename)?;
+        let gguf_name = source
+            .list()?
+            .into_iter()
+            .find(|name| name.ends_with(".gguf"))
+            .ok_or_else(|| anyhow!("No GGUF files found"))?;
+
+        let gguf_path = source.get(&gguf_name)?;
+        device::ensure_fits(device, device::estimate_file_bytes(&gguf_path)?)?;
         let mut file = std::fs::File::open(&gguf_path)?;
-        let model = candle_core::quantized::gguf_file::Content::read(&mut file)?;
+        let model = gguf_file::Content::read(&mut file)?;
+        Self::load_gguf_content(&gguf_path, model, &mut file, architecture, device)
+    }
 
+    /// Build the model from the GGUF file at `gguf_path`, whose header was read into
+    /// `model`.
+    fn load_gguf_content(
+        gguf_path: &Path,
+        model: gguf_file::Content,
+        file: &mut std::fs::File,
+        architecture: &ModelArchitecture,
+        device: &Device,
+    ) -> Result<UnifiedModel> {
         match architecture {
             ModelArchitecture::Llama => {
-                let weights = QuantizedLlama::from_gguf(model, &mut file, device)?;
+                let weights = QuantizedLlama::from_gguf(model, file, device)?;
                 Ok(UnifiedModel::QuantizedLlama(weights))
             }
             ModelArchitecture::Mistral => {
-                let vb = QVarBuilder::from_gguf(&gguf_path, device)?;
+                let vb = QVarBuilder::from_gguf(gguf_path, device)?;
                 let config = MistralConfig::config_7b_v0_1(true); // Default config
                 let model = QuantizedMistral::new(&config, vb)?;
                 Ok(UnifiedModel::QuantizedMistral(model))
             }
             ModelArchitecture::Phi => {
-                let weights = QuantizedPhi::from_gguf(model, &mut file, device)?;
+                let weights = QuantizedPhi::from_gguf(model, file, device)?;
                 Ok(UnifiedModel::QuantizedPhi(weights))
             }
             ModelArchitecture::Phi3 => {
-                let weights = QuantizedPhi3::from_gguf(false, model, &mut file, device)?;
+                let weights = QuantizedPhi3::from_gguf(false, model, file, device)?;
                 Ok(UnifiedModel::QuantizedPhi3(weights))
             }
             ModelArchitecture::Qwen2 => {
-                let weights = QuantizedQwen2::from_gguf(model, &mut file, device)?;
+                let weights = QuantizedQwen2::from_gguf(model, file, device)?;
                 Ok(UnifiedModel::QuantizedQwen2(weights))
             }
             ModelArchitecture::Unknown(name) => {
@@ -552,23 +761,18 @@ impl AutoModel {
     }
 
     fn load_ggml_model(
-        repo: &hf_hub::api::sync::ApiRepo,
+        source: &ModelSource,
         architecture: &ModelArchitecture,
         device: &Device,
     ) -> Result<UnifiedModel> {
-        // Find GGML file
-        let repo_info = repo.info()?;
-        let ggml_files: Vec<_> = repo_info
-            .siblings
-            .iter()
-            .filter(|s| s.rfilename.ends_with(".bin") || s.rfilename.ends_with(".ggml"))
-            .collect();
-
-        if ggml_files.is_empty() {
-            return Err(anyhow!("No GGML files found"));
-        }
-
-        let ggml_path = repo.get(&ggml_files[0].rfilename)?;
+        let ggml_name = source
+            .list()?
+            .into_iter()
+            .find(|name| name.ends_with(".bin") || name.ends_with(".ggml"))
+            .ok_or_else(|| anyhow!("No GGML files found"))?;
+
+        let ggml_path = source.get(&ggml_name)?;
+        device::ensure_fits(device, device::estimate_file_bytes(&ggml_path)?)?;
         let mut file = std::fs::File::open(&ggml_path)?;
         let model = candle_core::quantized::ggml_file::Content::read(&mut file, device)?;
 
@@ -581,146 +785,273 @@ impl AutoModel {
         }
     }
 
-    fn load_onnx_model(repo: &hf_hub::api::sync::ApiRepo, device: &Device) -> Result<UnifiedModel> {
-        let repo_info = repo.info()?;
-        let onnx_files: Vec<_> = repo_info
-            .siblings
-            .iter()
-            .filter(|s| s.rfilename.ends_with(".onnx"))
-            .collect();
-
-        if onnx_files.is_empty() {
-            return Err(anyhow!("No ONNX files found"));
-        }
+    fn load_onnx_model(source: &ModelSource, device: &Device) -> Result<UnifiedModel> {
+        let onnx_name = source
+            .list()?
+            .into_iter()
+            .find(|name| name.ends_with(".onnx"))
+            .ok_or_else(|| anyhow!("No ONNX files found"))?;
 
-        let onnx_path = repo.get(&onnx_files[0].rfilename)?;
+        let onnx_path = source.get(&onnx_name)?;
+        device::ensure_fits(device, device::estimate_file_bytes(&onnx_path)?)?;
         let model = OnnxModel::load(onnx_path.as_path(), device)?;
         Ok(UnifiedModel::Onnx(model))
     }
 
-    /// Generate with custom configuration
-    pub fn generate_with_config(&mut self, prompt: &str, config: &AutoModelConfig) -> Result<()> {
-        self.config = config.clone();
+    /// Generate, printing the prompt and the text as it comes, then the speed.
+    pub fn generate_with_config(
+        &mut self,
+        prompt: &str,
+        config: &AutoModelConfig,
+    ) -> Result<GenerationOutput> {
+        print!("{prompt}");
+        let output = self.generate_with_callback(prompt, config, |text| {
+            print!("{text}");
+            std::io::stdout().flush().is_ok()
+        })?;
+        println!(
+            "\n{} tokens generated ({:.2} token/s) on {:?}",
+            output.tokens,
+            output.tokens_per_sec(),
+            self.device
+        );
+        Ok(output)
+    }
 
-        // Update logits processor with new config
-        self.logits_processor = LogitsProcessor::new(config.seed, config.temperature, config.top_p);
+    /// Generate quietly and return the new text with its token counts and timing.
+    pub fn generate(&mut self, prompt: &str, config: &AutoModelConfig) -> Result<GenerationOutput> {
+        self.generate_with_callback(prompt, config, |_| true)
+    }
 
-        self.run(prompt, config.max_new_tokens)
+    /// Generate quietly and return only the new text, for callers that use the answer.
+    pub fn generate_text(&mut self, prompt: &str, config: &AutoModelConfig) -> Result<String> {
+        Ok(self.generate(prompt, config)?.text)
     }
 
-    /// Get model information
-    pub fn info(&self) -> String {
-        format!(
-            "AutoModel: {} | Architecture: {:?} | Format: {:?} | Device: {:?}",
-            self.model_id, self.architecture, self.format, self.device
-        )
+    /// Start generating from `prompt`, one token per step of the returned stream. Nothing is
+    /// computed between steps, so callers can spread a generation over frames, show the
+    /// text as it comes, or stop early by dropping the stream.
+    pub fn generate_stream(
+        &mut self,
+        prompt: &str,
+        config: &AutoModelConfig,
+    ) -> Result<TokenStream<'_>> {
+        let tokens = self.tokenizer_worker.encode(prompt, true).wait()?;
+        self.cached_session = None;
+        Ok(self.start_stream(tokens, 0, config))
     }
 
-    /// Main generation loop with GPU optimizations
-    fn run(&mut self, prompt: &str, sample_len: usize) -> Result<()> {
-        self.model.clear_kv_cache();
-        self.tokenizer.clear();
+    /// A new conversation, empty. See [`AutoModel::generate_in`].
+    pub fn session(&mut self) -> Session {
+        self.next_session += 1;
+        Session {
+            id: self.next_session,
+            tokens: Vec::new(),
+        }
+    }
 
-        let mut tokens = self
-            .tokenizer
-            .tokenizer()
-            .encode(prompt, true)
-            .map_err(E::msg)?
-            .get_ids()
-            .to_vec();
-
-        // Print prompt tokens with safety check
-        for &t in tokens.iter() {
-            if let Some(t) = self.tokenizer.next_token(t)? {
-                print!("{t}");
-                std::io::stdout().flush()?;
+    /// Append `text` to `session` and generate its continuation, which is appended too.
+    /// While no other generation ran on this model since the session's last one, its
+    /// history is still in the KV cache and only `text` is fed; otherwise the whole history
+    /// is fed again.
+    pub fn generate_in(
+        &mut self,
+        session: &mut Session,
+        text: &str,
+        config: &AutoModelConfig,
+    ) -> Result<GenerationOutput> {
+        let new_tokens = self
+            .tokenizer_worker
+            .encode(text, session.tokens.is_empty())
+            .wait()?;
+        session.tokens.extend(new_tokens);
+        let cached = match self.cached_session {
+            Some(id) if id == session.id && self.cached_len <= session.tokens.len() => {
+                self.cached_len
             }
+            _ => 0,
+        };
+
+        let mut stream = self.start_stream(session.tokens.clone(), cached, config);
+        let output = stream.run_to_end(&config.stop_sequences, |_| true);
+        session.tokens = std::mem::take(&mut stream.tokens);
+        let fed = stream.fed;
+        drop(stream);
+        self.cached_session = Some(session.id);
+        self.cached_len = fed;
+        output
+    }
+
+    /// A stream continuing `tokens`, the first `cached` of which are in the KV cache.
+    fn start_stream(
+        &mut self,
+        tokens: Vec<u32>,
+        cached: usize,
+        config: &AutoModelConfig,
+    ) -> TokenStream<'_> {
+        self.config = config.clone();
+        self.logits_processor = LogitsProcessor::new(config.seed, config.temperature, config.top_p);
+        if cached == 0 {
+            self.model.clear_kv_cache();
         }
+        self.tokenizer.clear();
 
-        // Generation loop with optimizations and safety checks
-        let mut generated_tokens = 0usize;
-        let eos_token = self.get_eos_token();
-        let start_gen = std::time::Instant::now();
+        TokenStream {
+            prompt_len: tokens.len(),
+            tokens,
+            fed: cached,
+            max_new_tokens: config.max_new_tokens,
+            eos_token: self.get_eos_token(),
+            finished: false,
+            stop_reason: None,
+            model: self,
+        }
+    }
 
-        for index in 0..sample_len {
-            // Safety check for timeout
-            if start_gen.elapsed().as_secs() > 60 {
-                println!("\n⚠️ Generation timeout after 60 seconds");
-                break;
-            }
+    /// Generate, handing the text to `on_text` as it comes. Stops early when `on_text`
+    /// returns false. Text that may begin a stop sequence is held back until it is known
+    /// not to, so `on_text` never sees part of one.
+    pub fn generate_with_callback(
+        &mut self,
+        prompt: &str,
+        config: &AutoModelConfig,
+        on_text: impl FnMut(&str) -> bool,
+    ) -> Result<GenerationOutput> {
+        self.generate_stream(prompt, config)?
+            .run_to_end(&config.stop_sequences, on_text)
+    }
 
-            // Optimize context window for GPU memory
-            let context_size = if index > 0 {
-                1 // Only use last token for subsequent generations
-            } else {
-                tokens.len().min(2048) // Limit initial context for memory efficiency
-            };
-
-            let start_pos = tokens.len().saturating_sub(context_size);
-            let ctxt = &tokens[start_pos..];
-
-            // Create input tensor with optimal memory layout
-            let input = Tensor::new(ctxt, &self.device)?.unsqueeze(0)?;
-
-            // Forward pass with GPU optimizations and error handling
-            let logits = match self.model.forward(&input, start_pos) {
-                Ok(logits) => logits,
-                Err(e) => {
-                    println!("\n❌ Forward pass failed: {}", e);
-                    break;
+    /// Generate from several prompts at once and return their outputs in order. Prompts
+    /// that encode to the same number of tokens share one forward per step, up to
+    /// [`MAX_BATCH`] of them, so serving several bots costs little more than serving one.
+    /// Each prompt gets its own sampler, seeded from `config.seed` and its index.
+    pub fn generate_batch(
+        &mut self,
+        prompts: &[&str],
+        config: &AutoModelConfig,
+    ) -> Result<Vec<GenerationOutput>> {
+        self.config = config.clone();
+        self.cached_session = None;
+        let mut by_len: BTreeMap<usize, Vec<(usize, Vec<u32>)>> = BTreeMap::new();
+        for (index, prompt) in prompts.iter().enumerate() {
+            let tokens = self.tokenizer_worker.encode(prompt, true).wait()?;
+            by_len
+                .entry(tokens.len())
+                .or_default()
+                .push((index, tokens));
+        }
+
+        let mut outputs = vec![None; prompts.len()];
+        for group in by_len.into_values() {
+            for chunk in group.chunks(MAX_BATCH) {
+                let rows = chunk
+                    .iter()
+                    .map(|(index, tokens)| {
+                        let seed = config.seed.wrapping_add(*index as u64);
+                        BatchRow::new(
+                            tokens.clone(),
+                            LogitsProcessor::new(seed, config.temperature, config.top_p),
+                            &config.stop_sequences,
+                        )
+                    })
+                    .collect();
+                let done = self.run_batch(rows, config)?;
+                for ((index, _), output) in chunk.iter().zip(done) {
+                    outputs[*index] = Some(output);
                 }
-            };
-
-            let logits = logits.squeeze(0)?.squeeze(0)?.to_dtype(DType::F32)?;
-
-            // Apply repetition penalty efficiently
-            let logits = if self.config.repeat_penalty == 1.0 {
-                logits
-            } else {
-                let start_at = tokens.len().saturating_sub(self.config.repeat_last_n);
-                candle_transformers::utils::apply_repeat_penalty(
-                    &logits,
-                    self.config.repeat_penalty,
-                    &tokens[start_at..],
-                )?
-            };
-
-            // Sample next token
-            let next_token = self.logits_processor.sample(&logits)?;
-            tokens.push(next_token);
-            generated_tokens += 1;
-
-            // Check for end-of-sequence
-            if next_token == eos_token {
-                break;
             }
+        }
+        Ok(outputs.into_iter().flatten().collect())
+    }
 
-            // Output token with debug info
-            if let Some(t) = self.tokenizer.next_token(next_token)? {
-                print!("{t}");
-                std::io::stdout().flush()?;
+    /// Generate for prompts of equal length together. Finished rows keep being fed their
+    /// last token, since the batch's KV cache cannot drop them, and their samples are
+    /// ignored.
+    fn run_batch(
+        &mut self,
+        mut rows: Vec<BatchRow<'_>>,
+        config: &AutoModelConfig,
+    ) -> Result<Vec<GenerationOutput>> {
+        let start = Instant::now();
+        self.model.clear_kv_cache();
+        let eos_token = self.get_eos_token();
+        let prompt_len = rows[0].prompt_len;
+        let flat: Vec<u32> = rows.iter().flat_map(|row| row.tokens.clone()).collect();
+        let mut input = Tensor::from_vec(flat, (rows.len(), prompt_len), &self.device)?;
+        let mut pos = 0;
+
+        for _ in 0..config.max_new_tokens {
+            let logits = self.model.forward_last(&input, pos)?.to_dtype(DType::F32)?;
+            pos += input.dim(1)?;
+            for (index, row) in rows.iter_mut().enumerate() {
+                if row.stop_reason.is_some() {
+                    continue;
+                }
+                let logits = self.penalize(logits.get(index)?, &row.tokens)?;
+                let token = row.logits_processor.sample(&logits)?;
+                if token == eos_token {
+                    row.stop_reason = Some(StopReason::EndOfText);
+                } else {
+                    row.push(token, self.tokenizer.tokenizer())?;
+                }
             }
-
-            // Debug output every few tokens
-            if generated_tokens % 3 == 0 {
-                println!(" [{}]", generated_tokens);
-                std::io::stdout().flush()?;
+            if rows.iter().all(|row| row.stop_reason.is_some()) {
+                break;
             }
+            let last: Vec<u32> = rows
+                .iter()
+                .map(|row| row.tokens[row.tokens.len() - 1])
+                .collect();
+            input = Tensor::from_vec(last, (rows.len(), 1), &self.device)?;
         }
 
-        let dt = start_gen.elapsed();
-        if let Some(rest) = self.tokenizer.decode_rest().map_err(E::msg)? {
-            print!("{rest}");
+        let elapsed = start.elapsed();
+        Ok(rows.into_iter().map(|row| row.finish(elapsed)).collect())
+    }
+
+    /// Sample the token following `tokens`, the first `cached` of which are already in the
+    /// KV cache: only the others are fed. A fresh prompt feeds at most its last 2048.
+    fn sample_next(&mut self, tokens: &[u32], cached: usize) -> Result<u32> {
+        let start_pos = if cached == 0 {
+            tokens.len().saturating_sub(2048)
+        } else {
+            cached.min(tokens.len() - 1)
+        };
+        let input = Tensor::new(&tokens[start_pos..], &self.device)?.unsqueeze(0)?;
+        let logits = self
+            .model
+            .forward_last(&input, start_pos)?
+            .squeeze(0)?
+            .to_dtype(DType::F32)?;
+        let logits = self.penalize(logits, tokens)?;
+        Ok(self.logits_processor.sample(&logits)?)
+    }
+
+    /// Apply the repeat penalty for the last tokens of `tokens` to `logits`.
+    fn penalize(&self, logits: Tensor, tokens: &[u32]) -> Result<Tensor> {
+        if self.config.repeat_penalty == 1.0 {
+            return Ok(logits);
         }
-        std::io::stdout().flush()?;
+        let start_at = tokens.len().saturating_sub(self.config.repeat_last_n);
+        Ok(candle_transformers::utils::apply_repeat_penalty(
+            &logits,
+            self.config.repeat_penalty,
+            &tokens[start_at..],
+        )?)
+    }
 
-        let tokens_per_sec = generated_tokens as f64 / dt.as_secs_f64();
-        println!(
-            "\n{} tokens generated ({:.2} token/s) on {:?}",
-            generated_tokens, tokens_per_sec, self.device
-        );
+    /// This model's tokenizer on its worker thread, for callers that must not block while a
+    /// long prompt is encoded.
+    pub fn tokenizer_worker(&self) -> TokenizerWorker {
+        self.tokenizer_worker.clone()
+    }
 
-        Ok(())
+    /// Get model information
+    pub fn info(&self) -> String {
+        format!(
+            "AutoModel: {} | Architecture: {:?} | Format: {:?} | Device: {:?}",
+            self.model_id, self.architecture, self.format, self.device
+        )
     }
 
     fn get_eos_token(&self) -> u32 {
@@ -731,3 +1062,251 @@ impl AutoModel {
             .unwrap_or(2) // Common EOS token ID
     }
 }
+
+/// Text generated one token per `next`, from [`AutoModel::generate_stream`]. Items are the
+/// text each token completes, empty while a token is only part of a character; the rest
+/// comes with the last item.
+pub struct TokenStream<'a> {
+    model: &'a mut AutoModel,
+    tokens: Vec<u32>,
+    prompt_len: usize,
+    /// Tokens already in the KV cache.
+    fed: usize,
+    max_new_tokens: usize,
+    eos_token: u32,
+    finished: bool,
+    stop_reason: Option<StopReason>,
+}
+
+impl TokenStream<'_> {
+    /// Tokens generated so far.
+    pub fn generated(&self) -> &[u32] {
+        &self.tokens[self.prompt_len..]
+    }
+
+    /// Why the stream ended, once it has.
+    pub fn stop_reason(&self) -> Option<StopReason> {
+        self.stop_reason
+    }
+
+    /// Run the stream to its end for [`AutoModel::generate_with_callback`].
+    fn run_to_end(
+        &mut self,
+        stop_sequences: &[String],
+        mut on_text: impl FnMut(&str) -> bool,
+    ) -> Result<GenerationOutput> {
+        let start = Instant::now();
+        let mut stops = StopSequences::new(stop_sequences);
+        let mut text = String::new();
+        let mut stop_reason = None;
+        while let Some(piece) = self.next() {
+            let (piece, stopped) = stops.push(&piece?);
+            text.push_str(&piece);
+            let go_on = piece.is_empty() || on_text(&piece);
+            if stopped {
+                stop_reason = Some(StopReason::StopSequence);
+                break;
+            }
+            if !go_on {
+                stop_reason = Some(StopReason::Cancelled);
+                break;
+            }
+        }
+        if stop_reason.is_none() {
+            let rest = stops.rest();
+            if !rest.is_empty() {
+                text.push_str(&rest);
+                on_text(&rest);
+            }
+        }
+        Ok(GenerationOutput {
+            text,
+            prompt_tokens: self.prompt_len,
+            tokens: self.generated().len(),
+            elapsed: start.elapsed(),
+            stop_reason: stop_reason
+                .or(self.stop_reason)
+                .unwrap_or(StopReason::MaxTokens),
+        })
+    }
+
+    fn finish(&mut self) -> Option<Result<String>> {
+        self.finished = true;
+        self.model
+            .tokenizer
+            .decode_rest()
+            .map_err(E::msg)
+            .transpose()
+    }
+}
+
+impl Iterator for TokenStream<'_> {
+    type Item = Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        if self.generated().len() >= self.max_new_tokens {
+            self.stop_reason = Some(StopReason::MaxTokens);
+            return self.finish();
+        }
+
+        let text = self
+            .model
+            .sample_next(&self.tokens, self.fed)
+            .and_then(|token| {
+                self.fed = self.tokens.len();
+                if token == self.eos_token {
+                    return Ok(None);
+                }
+                self.tokens.push(token);
+                Ok(Some(
+                    self.model.tokenizer.next_token(token)?.unwrap_or_default(),
+                ))
+            });
+        match text {
+            Ok(Some(text)) => Some(Ok(text)),
+            Ok(None) => {
+                self.stop_reason = Some(StopReason::EndOfText);
+                self.finish()
+            }
+            Err(e) => {
+                self.finished = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// A conversation kept in a model's KV cache between generations, from
+/// [`AutoModel::session`]: a bot that keeps talking only pays for its new lines.
+pub struct Session {
+    id: u64,
+    tokens: Vec<u32>,
+}
+
+impl Session {
+    /// The whole conversation so far, prompts and answers.
+    pub fn tokens(&self) -> &[u32] {
+        &self.tokens
+    }
+
+    /// Forget the conversation; the next generation starts from scratch.
+    pub fn clear(&mut self) {
+        self.tokens.clear();
+    }
+}
+
+/// One prompt of a batch in [`AutoModel::generate_batch`].
+struct BatchRow<'a> {
+    tokens: Vec<u32>,
+    prompt_len: usize,
+    logits_processor: LogitsProcessor,
+    stops: StopSequences<'a>,
+    text: String,
+    /// Bytes of the decoded generation already passed to `stops`.
+    decoded: usize,
+    stop_reason: Option<StopReason>,
+}
+
+impl<'a> BatchRow<'a> {
+    fn new(tokens: Vec<u32>, logits_processor: LogitsProcessor, stops: &'a [String]) -> Self {
+        Self {
+            prompt_len: tokens.len(),
+            tokens,
+            logits_processor,
+            stops: StopSequences::new(stops),
+            text: String::new(),
+            decoded: 0,
+            stop_reason: None,
+        }
+    }
+
+    /// Add a sampled token, and the text it completes. Text ending in the middle of a
+    /// character waits for the next token.
+    fn push(&mut self, token: u32, tokenizer: &Tokenizer) -> Result<()> {
+        self.tokens.push(token);
+        let decoded = tokenizer
+            .decode(&self.tokens[self.prompt_len..], true)
+            .map_err(E::msg)?;
+        if decoded.len() <= self.decoded
+            || decoded.ends_with('\u{fffd}')
+            || !decoded.is_char_boundary(self.decoded)
+        {
+            return Ok(());
+        }
+        let (piece, stopped) = self.stops.push(&decoded[self.decoded..]);
+        self.decoded = decoded.len();
+        self.text.push_str(&piece);
+        if stopped {
+            self.stop_reason = Some(StopReason::StopSequence);
+        }
+        Ok(())
+    }
+
+    fn finish(mut self, elapsed: Duration) -> GenerationOutput {
+        if self.stop_reason.is_none() {
+            self.text.push_str(&self.stops.rest());
+        }
+        GenerationOutput {
+            text: self.text,
+            prompt_tokens: self.prompt_len,
+            tokens: self.tokens.len() - self.prompt_len,
+            elapsed,
+            stop_reason: self.stop_reason.unwrap_or(StopReason::MaxTokens),
+        }
+    }
+}
+
+/// Generated text on its way out of [`AutoModel::generate_with_callback`]: the tail that
+/// could still grow into a stop sequence is kept until the next piece settles it.
+struct StopSequences<'a> {
+    sequences: &'a [String],
+    pending: String,
+}
+
+impl<'a> StopSequences<'a> {
+    fn new(sequences: &'a [String]) -> Self {
+        Self {
+            sequences,
+            pending: String::new(),
+        }
+    }
+
+    /// Add `piece`, returning the text now safe to hand out and whether a stop sequence
+    /// was completed. After a stop, the text is what came before the sequence.
+    fn push(&mut self, piece: &str) -> (String, bool) {
+        self.pending.push_str(piece);
+        let first_stop = self
+            .sequences
+            .iter()
+            .filter(|stop| !stop.is_empty())
+            .filter_map(|stop| self.pending.find(stop.as_str()))
+            .min();
+        if let Some(at) = first_stop {
+            self.pending.truncate(at);
+            return (std::mem::take(&mut self.pending), true);
+        }
+
+        let held = self
+            .sequences
+            .iter()
+            .map(|stop| {
+                (1..stop.len())
+                    .rev()
+                    .filter(|&len| stop.is_char_boundary(len))
+                    .find(|&len| self.pending.ends_with(&stop[..len]))
+                    .unwrap_or(0)
+            })
+            .max()
+            .unwrap_or(0);
+        let ready = self.pending.len() - held;
+        (self.pending.drain(..ready).collect(), false)
+    }
+
+    /// Text held back when generation ended without a stop sequence.
+    fn rest(&mut self) -> String {
+        std::mem::take(&mut self.pending)
+    }
+}
@@ -0,0 +1,43 @@
+//! Bot dialogue for the game server: reads one prompt per line on stdin and answers each
+//! with exactly one line on stdout, keeping the model loaded between prompts. Usage:
+//! `llm-dialogue [model_id | path]`, a path being a local model directory or `.gguf` file;
+//! `LLM_DEVICE` picks the device (`cpu`, `cuda:1`, ...).
+
+use anyhow::Result;
+use llm::{AutoModel, AutoModelConfig};
+use std::io::{BufRead, Write};
+
+const DEFAULT_MODEL: &str = "Qwen/Qwen3-0.6B";
+
+fn main() -> Result<()> {
+    let model_id = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_MODEL.to_string());
+
+    let mut model = AutoModel::open(&model_id)?;
+    let mut config = AutoModelConfig {
+        max_new_tokens: 32,
+        temperature: Some(0.9),
+        ..Default::default()
+    };
+
+    let mut stdout = std::io::stdout().lock();
+    for prompt in std::io::stdin().lock().lines() {
+        let prompt = prompt?;
+        // A new seed each time, or the same prompt would always get the same line.
+        config.seed = config.seed.wrapping_add(1);
+        // The server reads one line per prompt: a failed generation is an empty line, and
+        // line breaks in the answer are folded.
+        let reply = model
+            .generate_text(&prompt, &config)
+            .inspect_err(|e| eprintln!("Generation failed: {}", e))
+            .unwrap_or_default();
+        writeln!(
+            stdout,
+            "{}",
+            reply.split_whitespace().collect::<Vec<_>>().join(" ")
+        )?;
+        stdout.flush()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,28 @@
+//! Match recap narrator for the game server: reads a prompt on stdin and prints only the
+//! generated recap on stdout. Usage: `llm-recap [model_id | path]`, a path being a local
+//! model directory or `.gguf` file; `LLM_DEVICE` picks the device (`cpu`, `cuda:1`, ...).
+
+use anyhow::Result;
+use llm::{AutoModel, AutoModelConfig};
+use std::io::Read;
+
+const DEFAULT_MODEL: &str = "Qwen/Qwen3-0.6B";
+
+fn main() -> Result<()> {
+    let model_id = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_MODEL.to_string());
+
+    let mut prompt = String::new();
+    std::io::stdin().read_to_string(&mut prompt)?;
+
+    let mut model = AutoModel::open(&model_id)?;
+    let config = AutoModelConfig {
+        max_new_tokens: 80,
+        temperature: Some(0.7),
+        ..Default::default()
+    };
+    let recap = model.generate_text(&prompt, &config)?;
+    println!("{}", recap.trim());
+    Ok(())
+}
@@ -0,0 +1,161 @@
+//! Where a model runs, and whether it fits there before any weight is loaded.
+//!
+//! Layer-wise placement across GPUs is not offered: candle's model implementations build
+//! every layer from one `VarBuilder`, so a model lives on a single device.
+
+use anyhow::{Result, anyhow};
+use candle_core::safetensors::MmapedSafetensors;
+use candle_core::{DType, Device, DeviceLocation};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::str::FromStr;
+
+/// Environment variable read by `DeviceSelection::from_env`, e.g. `LLM_DEVICE=cuda:1`.
+pub const DEVICE_ENV: &str = "LLM_DEVICE";
+/// Headroom over the weights for the KV cache, activations and allocator slack.
+const LOAD_OVERHEAD: f64 = 1.2;
+
+const BYTES_PER_GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeviceSelection {
+    /// The CUDA device with the most free memory, then Metal on macOS, then the CPU.
+    #[default]
+    Auto,
+    Cpu,
+    Cuda(usize),
+    Metal(usize),
+}
+
+impl FromStr for DeviceSelection {
+    type Err = anyhow::Error;
+
+    /// `auto`, `cpu`, `cuda`, `cuda:<index>`, `metal` or `metal:<index>`.
+    fn from_str(value: &str) -> Result<Self> {
+        let value = value.trim().to_lowercase();
+        let (kind, index) = match value.split_once(':') {
+            Some((kind, index)) => (
+                kind,
+                index
+                    .parse::<usize>()
+                    .map_err(|_| anyhow!("Bad device index in '{}'", value))?,
+            ),
+            None => (value.as_str(), 0),
+        };
+        match kind {
+            "auto" => Ok(Self::Auto),
+            "cpu" => Ok(Self::Cpu),
+            "cuda" => Ok(Self::Cuda(index)),
+            "metal" => Ok(Self::Metal(index)),
+            _ => Err(anyhow!(
+                "Unknown device '{}': expected auto, cpu, cuda[:index] or metal[:index]",
+                value
+            )),
+        }
+    }
+}
+
+impl DeviceSelection {
+    /// The selection in `LLM_DEVICE`, `Auto` when unset.
+    pub fn from_env() -> Result<Self> {
+        match std::env::var(DEVICE_ENV) {
+            Ok(value) => value.parse(),
+            Err(_) => Ok(Self::Auto),
+        }
+    }
+
+    /// Open an explicit selection. `Auto` is resolved by the caller, which knows how to
+    /// probe devices.
+    pub fn open(self) -> Result<Option<Device>> {
+        Ok(match self {
+            Self::Auto => None,
+            Self::Cpu => Some(Device::Cpu),
+            Self::Cuda(index) => Some(Device::new_cuda(index)?),
+            Self::Metal(index) => Some(Device::new_metal(index)?),
+        })
+    }
+}
+
+/// Free memory of every CUDA device in bytes, by index, as reported by `nvidia-smi`.
+/// Empty when it is not available.
+pub fn cuda_free_memory() -> Vec<u64> {
+    Command::new("nvidia-smi")
+        .args(["--query-gpu=memory.free", "--format=csv,noheader,nounits"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| parse_free_mib(&String::from_utf8_lossy(&output.stdout)))
+        .unwrap_or_default()
+}
+
+fn parse_free_mib(output: &str) -> Vec<u64> {
+    output
+        .lines()
+        .filter_map(|line| line.trim().parse::<u64>().ok())
+        .map(|mib| mib * 1024 * 1024)
+        .collect()
+}
+
+/// The CUDA device with the most free memory, if more than one can be compared.
+pub fn roomiest_cuda_device() -> Option<usize> {
+    cuda_free_memory()
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, free)| **free)
+        .map(|(index, _)| index)
+}
+
+/// Bytes the safetensors weights take once loaded as `dtype`, with headroom.
+pub fn estimate_safetensors_bytes(files: &[PathBuf], dtype: DType) -> Result<u64> {
+    // Only the headers are read; the mapping is dropped before the real load.
+    let tensors = unsafe { MmapedSafetensors::multi(files)? };
+    let elements: usize = tensors
+        .tensors()
+        .iter()
+        .map(|(_, view)| view.shape().iter().product::<usize>())
+        .sum();
+    Ok(with_overhead((elements * dtype.size_in_bytes()) as u64))
+}
+
+/// Bytes a quantized or ONNX file takes once loaded: its size, with headroom.
+pub fn estimate_file_bytes(path: &Path) -> Result<u64> {
+    Ok(with_overhead(std::fs::metadata(path)?.len()))
+}
+
+fn with_overhead(bytes: u64) -> u64 {
+    (bytes as f64 * LOAD_OVERHEAD) as u64
+}
+
+/// Fail with a readable error if a GPU is known to lack the memory for `required` bytes.
+/// Devices whose free memory cannot be read pass.
+pub fn ensure_fits(device: &Device, required: u64) -> Result<()> {
+    let DeviceLocation::Cuda { gpu_id } = device.location() else {
+        return Ok(());
+    };
+    let free_memory = cuda_free_memory();
+    let Some(&free) = free_memory.get(gpu_id) else {
+        return Ok(());
+    };
+    if required <= free {
+        return Ok(());
+    }
+
+    let roomier = free_memory
+        .iter()
+        .enumerate()
+        .filter(|&(index, &other)| index != gpu_id && other >= required)
+        .map(|(index, _)| format!("cuda:{}", index))
+        .collect::<Vec<_>>();
+    let hint = if roomier.is_empty() {
+        "use a quantized model or the cpu device".to_string()
+    } else {
+        format!("{}={} has room", DEVICE_ENV, roomier.join(" or "))
+    };
+    Err(anyhow!(
+        "Model needs about {:.1} GiB but cuda:{} has {:.1} GiB free; {}",
+        required as f64 / BYTES_PER_GIB,
+        gpu_id,
+        free as f64 / BYTES_PER_GIB,
+        hint
+    ))
+}
@@ -0,0 +1,214 @@
+//! What a bare `.gguf` file carries besides its weights: the architecture, and the
+//! tokenizer in its `tokenizer.ggml.*` metadata, rebuilt as a `tokenizers` one so a single
+//! file is enough to run a model offline.
+//!
+//! The rebuilt tokenizer covers the two kinds llama.cpp writes: byte-level BPE (`gpt2`,
+//! used by Qwen2 and Llama 3) and SentencePiece (`llama`). Byte-level BPE is split with
+//! GPT-2's pattern, which some models refine; a `tokenizer.json` next to the file is
+//! preferred when exact tokenization matters.
+
+use anyhow::{Error as E, Result, anyhow};
+use candle_core::quantized::gguf_file::{Content, Value};
+use serde_json::{Map, Value as Json, json};
+use std::str::FromStr;
+use tokenizers::Tokenizer;
+
+use crate::auto::ModelArchitecture;
+
+/// `tokenizer.ggml.token_type` of tokens that are never split.
+const TOKEN_CONTROL: i64 = 3;
+const TOKEN_USER_DEFINED: i64 = 4;
+
+/// The architecture in `general.architecture`. Mistral models are written as `llama`.
+pub fn architecture(content: &Content) -> Option<ModelArchitecture> {
+    let name = content
+        .metadata
+        .get("general.architecture")?
+        .to_string()
+        .ok()?;
+    Some(match name.as_str() {
+        "llama" => ModelArchitecture::Llama,
+        "qwen2" => ModelArchitecture::Qwen2,
+        "phi2" => ModelArchitecture::Phi,
+        "phi3" => ModelArchitecture::Phi3,
+        other => ModelArchitecture::Unknown(other.to_string()),
+    })
+}
+
+/// The tokenizer embedded in the file.
+pub fn tokenizer(content: &Content) -> Result<Tokenizer> {
+    let metadata = &content.metadata;
+    let get = |key: &str| {
+        metadata
+            .get(key)
+            .ok_or_else(|| anyhow!("GGUF file has no {} for its tokenizer", key))
+    };
+    let tokens = strings(get("tokenizer.ggml.tokens")?)?;
+    let types: Vec<i64> = metadata
+        .get("tokenizer.ggml.token_type")
+        .and_then(|types| types.to_vec().ok())
+        .map(|types| types.iter().map(|t| integer(t).unwrap_or(1)).collect())
+        .unwrap_or_default();
+    let added_tokens: Vec<Json> = tokens
+        .iter()
+        .enumerate()
+        .filter_map(|(id, token)| {
+            let kind = *types.get(id)?;
+            (kind == TOKEN_CONTROL || kind == TOKEN_USER_DEFINED).then(|| {
+                json!({
+                    "id": id,
+                    "content": token,
+                    "single_word": false,
+                    "lstrip": false,
+                    "rstrip": false,
+                    "normalized": false,
+                    "special": kind == TOKEN_CONTROL,
+                })
+            })
+        })
+        .collect();
+
+    let (normalizer, pre_tokenizer, decoder, model) =
+        match get("tokenizer.ggml.model")?.to_string()?.as_str() {
+            "gpt2" => {
+                let merges = strings(get("tokenizer.ggml.merges")?)?;
+                let vocab: Map<String, Json> = tokens
+                    .iter()
+                    .enumerate()
+                    .map(|(id, token)| (token.to_string(), json!(id)))
+                    .collect();
+                let byte_level = json!({
+                    "type": "ByteLevel",
+                    "add_prefix_space": false,
+                    "trim_offsets": true,
+                    "use_regex": true,
+                });
+                let model = json!({
+                    "type": "BPE",
+                    "dropout": null,
+                    "unk_token": null,
+                    "continuing_subword_prefix": null,
+                    "end_of_word_suffix": null,
+                    "fuse_unk": false,
+                    "byte_fallback": false,
+                    "vocab": vocab,
+                    "merges": merges,
+                });
+                (Json::Null, byte_level.clone(), byte_level, model)
+            }
+            "llama" => {
+                let scores: Vec<f64> = metadata
+                    .get("tokenizer.ggml.scores")
+                    .and_then(|scores| scores.to_vec().ok())
+                    .map(|scores| {
+                        scores
+                            .iter()
+                            .map(|score| score.to_f32().map_or(0.0, f64::from))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let vocab: Vec<Json> = tokens
+                    .iter()
+                    .enumerate()
+                    .map(|(id, token)| json!([token, scores.get(id).copied().unwrap_or(0.0)]))
+                    .collect();
+                let unk_id = metadata
+                    .get("tokenizer.ggml.unknown_token_id")
+                    .and_then(integer)
+                    .unwrap_or(0);
+                let normalizer = json!({
+                    "type": "Sequence",
+                    "normalizers": [
+                        {"type": "Prepend", "prepend": "\u{2581}"},
+                        {"type": "Replace", "pattern": {"String": " "}, "content": "\u{2581}"},
+                    ],
+                });
+                let decoder = json!({
+                    "type": "Sequence",
+                    "decoders": [
+                        {"type": "Replace", "pattern": {"String": "\u{2581}"}, "content": " "},
+                        {"type": "ByteFallback"},
+                        {"type": "Fuse"},
+                        {"type": "Strip", "content": " ", "start": 1, "stop": 0},
+                    ],
+                });
+                let model = json!({
+                    "type": "Unigram",
+                    "unk_id": unk_id,
+                    "vocab": vocab,
+                    "byte_fallback": true,
+                });
+                (normalizer, Json::Null, decoder, model)
+            }
+            other => return Err(anyhow!("Unsupported GGUF tokenizer '{}'", other)),
+        };
+
+    let tokenizer = json!({
+        "version": "1.0",
+        "truncation": null,
+        "padding": null,
+        "added_tokens": added_tokens,
+        "normalizer": normalizer,
+        "pre_tokenizer": pre_tokenizer,
+        "post_processor": bos_template(content, &tokens),
+        "decoder": decoder,
+        "model": model,
+    });
+    Tokenizer::from_str(&tokenizer.to_string()).map_err(E::msg)
+}
+
+/// A post-processor putting the BOS token first, when the file asks for one.
+fn bos_template(content: &Content, tokens: &[&str]) -> Json {
+    let metadata = &content.metadata;
+    let add_bos = metadata
+        .get("tokenizer.ggml.add_bos_token")
+        .and_then(|add| add.to_bool().ok())
+        .unwrap_or(false);
+    let bos = metadata
+        .get("tokenizer.ggml.bos_token_id")
+        .and_then(integer)
+        .and_then(|id| Some((id, *tokens.get(usize::try_from(id).ok()?)?)));
+    let Some((id, token)) = bos.filter(|_| add_bos) else {
+        return Json::Null;
+    };
+
+    let special = json!({"SpecialToken": {"id": token, "type_id": 0}});
+    let mut special_tokens = Map::new();
+    special_tokens.insert(
+        token.to_string(),
+        json!({"id": token, "ids": [id], "tokens": [token]}),
+    );
+    json!({
+        "type": "TemplateProcessing",
+        "single": [special, {"Sequence": {"id": "A", "type_id": 0}}],
+        "pair": [
+            special,
+            {"Sequence": {"id": "A", "type_id": 0}},
+            {"Sequence": {"id": "B", "type_id": 1}},
+        ],
+        "special_tokens": special_tokens,
+    })
+}
+
+fn strings(value: &Value) -> Result<Vec<&str>> {
+    value
+        .to_vec()?
+        .iter()
+        .map(|item| Ok(item.to_string()?.as_str()))
+        .collect()
+}
+
+/// Any GGUF integer, whatever its width.
+fn integer(value: &Value) -> Option<i64> {
+    match *value {
+        Value::U8(v) => Some(v.into()),
+        Value::I8(v) => Some(v.into()),
+        Value::U16(v) => Some(v.into()),
+        Value::I16(v) => Some(v.into()),
+        Value::U32(v) => Some(v.into()),
+        Value::I32(v) => Some(v.into()),
+        Value::U64(v) => i64::try_from(v).ok(),
+        Value::I64(v) => Some(v),
+        _ => None,
+    }
+}
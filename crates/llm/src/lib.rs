@@ -0,0 +1,26 @@
+//! Local text generation with candle and ONNX models from the Hugging Face hub, for crates
+//! that embed it (the game server's recap and bot lines, tools) instead of running the
+//! `llm-*` executables.
+//!
+//! [`AutoModel::generate`] returns the whole answer with its token counts, timing and why it
+//! stopped ([`AutoModelConfig::stop_sequences`] end it early); [`AutoModel::generate_stream`]
+//! yields it token by token so callers can spread the work out or stop early, and
+//! [`AutoModel::generate_with_callback`] hands each piece to a closure.
+//!
+//! [`AutoModel::from_local_path`] loads a model from disk, a hub-style directory or a bare
+//! `.gguf` file, for games and servers without internet access.
+//!
+//! A [`Session`] keeps a conversation in the model's KV cache, so a bot that talks again
+//! only feeds its new lines ([`AutoModel::generate_in`]), and [`AutoModel::generate_batch`]
+//! answers several prompts with one forward per step.
+
+pub mod auto;
+pub mod device;
+pub mod gguf;
+pub mod tokenize;
+
+pub use auto::{
+    AutoModel, AutoModelConfig, GenerationOutput, MAX_BATCH, Session, StopReason, TokenStream,
+};
+pub use device::DeviceSelection;
+pub use tokenize::{Pending, TokenizerWorker};
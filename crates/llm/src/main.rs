@@ -1,8 +1,6 @@
-mod auto;
-
 use anyhow::Result;
-use auto::{AutoModel, AutoModelConfig};
 use candle_core::{DType, Device, Tensor};
+use llm::{AutoModel, AutoModelConfig};
 use tracing_subscriber;
 
 fn try_cuda_device() -> Result<Device> {
@@ -110,6 +108,7 @@ fn main() -> Result<()> {
             repeat_penalty: 1.1,
             repeat_last_n: 64,
             seed: 42,
+            stop_sequences: Vec::new(),
         };
 
         let prompts = [
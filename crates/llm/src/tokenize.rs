@@ -0,0 +1,186 @@
+//! Tokenization off the calling thread. Long prompts take a while to encode; the game-side
+//! bridge submits them here and polls the result from its systems instead of stalling a
+//! frame.
+
+use anyhow::{Error as E, Result, anyhow};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::mpsc::{Receiver, SyncSender, TrySendError, sync_channel};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+use tokenizers::Tokenizer;
+
+/// Requests waiting for the worker. Submitting to a full queue fails instead of blocking.
+const QUEUE_LEN: usize = 8;
+
+enum Request {
+    Encode {
+        text: String,
+        add_special_tokens: bool,
+        reply: Pending<Vec<u32>>,
+    },
+    Decode {
+        ids: Vec<u32>,
+        skip_special_tokens: bool,
+        reply: Pending<String>,
+    },
+}
+
+struct Slot<T> {
+    result: Option<Result<T>>,
+    waker: Option<Waker>,
+}
+
+struct Shared<T> {
+    slot: Mutex<Slot<T>>,
+    ready: Condvar,
+}
+
+/// Result of a queued request. Poll it with `try_take` from a system, `.await` it, or
+/// `wait` for it where blocking is fine.
+pub struct Pending<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Pending<T> {
+    fn new() -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                slot: Mutex::new(Slot {
+                    result: None,
+                    waker: None,
+                }),
+                ready: Condvar::new(),
+            }),
+        }
+    }
+
+    fn share(&self) -> Self {
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Slot<T>> {
+        self.shared.slot.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn resolve(&self, result: Result<T>) {
+        let mut slot = self.lock();
+        slot.result = Some(result);
+        if let Some(waker) = slot.waker.take() {
+            waker.wake();
+        }
+        self.shared.ready.notify_all();
+    }
+
+    /// The result if the worker is done, without waiting.
+    pub fn try_take(&self) -> Option<Result<T>> {
+        self.lock().result.take()
+    }
+
+    /// Block until the worker is done.
+    pub fn wait(self) -> Result<T> {
+        let mut slot = self.lock();
+        loop {
+            if let Some(result) = slot.result.take() {
+                return result;
+            }
+            slot = self
+                .shared
+                .ready
+                .wait(slot)
+                .unwrap_or_else(|e| e.into_inner());
+        }
+    }
+}
+
+impl<T> Future for Pending<T> {
+    type Output = Result<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut slot = self.lock();
+        match slot.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                slot.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Handle to a tokenizer running on its own thread. Cheap to clone; the thread stops when
+/// the last handle is dropped.
+#[derive(Clone)]
+pub struct TokenizerWorker {
+    requests: SyncSender<Request>,
+}
+
+impl TokenizerWorker {
+    pub fn spawn(tokenizer: Tokenizer) -> Result<Self> {
+        let (requests, queue) = sync_channel(QUEUE_LEN);
+        std::thread::Builder::new()
+            .name("llm-tokenizer".to_string())
+            .spawn(move || serve(tokenizer, queue))?;
+        Ok(Self { requests })
+    }
+
+    pub fn encode(&self, text: impl Into<String>, add_special_tokens: bool) -> Pending<Vec<u32>> {
+        let reply = Pending::new();
+        self.submit(
+            Request::Encode {
+                text: text.into(),
+                add_special_tokens,
+                reply: reply.share(),
+            },
+            &reply,
+        );
+        reply
+    }
+
+    pub fn decode(&self, ids: Vec<u32>, skip_special_tokens: bool) -> Pending<String> {
+        let reply = Pending::new();
+        self.submit(
+            Request::Decode {
+                ids,
+                skip_special_tokens,
+                reply: reply.share(),
+            },
+            &reply,
+        );
+        reply
+    }
+
+    fn submit<T>(&self, request: Request, reply: &Pending<T>) {
+        match self.requests.try_send(request) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => reply.resolve(Err(anyhow!("tokenizer queue is full"))),
+            Err(TrySendError::Disconnected(_)) => {
+                reply.resolve(Err(anyhow!("tokenizer worker has stopped")))
+            }
+        }
+    }
+}
+
+fn serve(tokenizer: Tokenizer, queue: Receiver<Request>) {
+    for request in queue {
+        match request {
+            Request::Encode {
+                text,
+                add_special_tokens,
+                reply,
+            } => reply.resolve(
+                tokenizer
+                    .encode(text, add_special_tokens)
+                    .map(|encoding| encoding.get_ids().to_vec())
+                    .map_err(E::msg),
+            ),
+            Request::Decode {
+                ids,
+                skip_special_tokens,
+                reply,
+            } => reply.resolve(tokenizer.decode(&ids, skip_special_tokens).map_err(E::msg)),
+        }
+    }
+}
@@ -0,0 +1,170 @@
+//! Headless PPO training in the exit room. Usage:
+//! `train [--ppo FILE] [--network FILE] [--rewards FILE] [--resume FILE] [--out FILE] [--iterations N]
+//! [--run-dir DIR] [--metrics tensorboard|csv|jsonl] [--bot-policy FILE]`.
+//! `--ppo` and `--network` are training configs for the hyperparameters and the network,
+//! `--rewards` changes reward weights, `--resume` continues from a checkpoint. The policy is
+//! saved to `--out` after every iteration. With `--run-dir`, each iteration's metrics are
+//! also written there. With `--bot-policy`, the policy is also exported there for
+//! `launcher server --bot-policy`.
+
+use reinforcement_learning::environment::{ExitEnvironment, TrainingEnvironment};
+use reinforcement_learning::metrics::{MetricsFormat, MetricsWriter};
+use reinforcement_learning::policy_network::{PolicyNetworkConfig, PolicyValueNetwork};
+use reinforcement_learning::ppo::{PpoConfig, PpoTrainer};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+const DEFAULT_OUT: &str = "checkpoints/ppo.safetensors";
+
+#[derive(Default)]
+struct Args {
+    ppo: Option<PathBuf>,
+    network: Option<PathBuf>,
+    rewards: Option<PathBuf>,
+    resume: Option<PathBuf>,
+    out: Option<PathBuf>,
+    iterations: Option<usize>,
+    run_dir: Option<PathBuf>,
+    metrics: MetricsFormat,
+    bot_policy: Option<PathBuf>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args::default();
+    let mut words = std::env::args().skip(1);
+    while let Some(flag) = words.next() {
+        let value = words
+            .next()
+            .ok_or_else(|| format!("{} needs a value", flag))?;
+        match flag.as_str() {
+            "--ppo" => args.ppo = Some(value.into()),
+            "--network" => args.network = Some(value.into()),
+            "--rewards" => args.rewards = Some(value.into()),
+            "--resume" => args.resume = Some(value.into()),
+            "--out" => args.out = Some(value.into()),
+            "--run-dir" => args.run_dir = Some(value.into()),
+            "--bot-policy" => args.bot_policy = Some(value.into()),
+            "--metrics" => args.metrics = MetricsFormat::parse(&value)?,
+            "--iterations" => {
+                args.iterations = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("bad iteration count {:?}", value))?,
+                );
+            }
+            _ => return Err(format!("unknown argument {}", flag)),
+        }
+    }
+    Ok(args)
+}
+
+fn read_config(path: &Path) -> Result<String, String> {
+    std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = parse_args()?;
+
+    let mut ppo_config = match &args.ppo {
+        Some(path) => PpoConfig::from_ron(&read_config(path)?)
+            .map_err(|e| format!("{}: {}", path.display(), e))?,
+        None => PpoConfig::default(),
+    };
+    if let Some(iterations) = args.iterations {
+        ppo_config.iterations = iterations;
+    }
+
+    let mut environment = ExitEnvironment::default();
+    if let Some(path) = &args.rewards {
+        environment
+            .rewards
+            .apply_weights(&read_config(path)?)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+    }
+
+    let mut network_config = match &args.network {
+        Some(path) => PolicyNetworkConfig::from_ron(&read_config(path)?)
+            .map_err(|e| format!("{}: {}", path.display(), e))?,
+        None => PolicyNetworkConfig::default(),
+    };
+    network_config.input_size = environment.state_size();
+    let network = match &args.resume {
+        Some(path) => PolicyValueNetwork::load(path, &network_config)?,
+        None => PolicyValueNetwork::new(network_config)?,
+    };
+    if network.config().input_size != environment.state_size() {
+        return Err(format!(
+            "the network reads {} values, the environment gives {}",
+            network.config().input_size,
+            environment.state_size()
+        )
+        .into());
+    }
+
+    let mut metrics = match &args.run_dir {
+        Some(run_dir) => Some(
+            MetricsWriter::create(run_dir, args.metrics)
+                .map_err(|e| format!("{}: {}", run_dir.display(), e))?,
+        ),
+        None => None,
+    };
+
+    let out = args.out.unwrap_or_else(|| PathBuf::from(DEFAULT_OUT));
+    let mut trainer = PpoTrainer::new(network, ppo_config);
+    let mut rng = rand::rng();
+    for iteration in 1..=trainer.config.iterations {
+        let started = Instant::now();
+        let rollout = trainer.collect(&mut environment, &mut rng)?;
+        let stats = trainer.update(&rollout, &mut rng)?;
+        let fps = rollout.len() as f32 / started.elapsed().as_secs_f32().max(f32::EPSILON);
+
+        let episodes = rollout.episode_rewards.len();
+        let mean = |total: f32| {
+            if episodes == 0 {
+                f32::NAN
+            } else {
+                total / episodes as f32
+            }
+        };
+        let mean_reward = mean(rollout.episode_rewards.iter().sum());
+        let mean_length = mean(rollout.episode_lengths.iter().sum::<usize>() as f32);
+        println!(
+            "iteration {}: {} episodes, mean reward {:.2} | policy {:.4}, value {:.4}, entropy {:.3}, grad norm {:.3}, clipped {:.0}%",
+            iteration,
+            episodes,
+            mean_reward,
+            stats.policy_loss,
+            stats.value_loss,
+            stats.entropy,
+            stats.grad_norm,
+            stats.clip_fraction * 100.0
+        );
+        if let Some(metrics) = &mut metrics {
+            metrics.write_scalars(
+                iteration as u64,
+                &[
+                    ("episode/reward", mean_reward),
+                    ("episode/length", mean_length),
+                    ("loss/policy", stats.policy_loss),
+                    ("loss/value", stats.value_loss),
+                    ("loss/entropy", stats.entropy),
+                    ("train/grad_norm", stats.grad_norm),
+                    ("train/clip_fraction", stats.clip_fraction),
+                    ("perf/fps", fps),
+                ],
+            )?;
+        }
+        trainer.network.save(&out)?;
+        if let Some(path) = &args.bot_policy {
+            let policy = trainer.network.to_bot_policy()?;
+            std::fs::write(path, policy.to_checkpoint())
+                .map_err(|e| format!("{}: {}", path.display(), e))?;
+        }
+    }
+    println!("Saved the policy to {}", out.display());
+    if let Some(path) = &args.bot_policy {
+        println!("Exported it for the server to {}", path.display());
+    }
+    Ok(())
+}
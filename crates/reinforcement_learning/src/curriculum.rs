@@ -0,0 +1,393 @@
+//! Curriculum learning: the gym starts easy and gets harder as the agent learns.
+//!
+//! A curriculum is an ordered list of [`CurriculumStage`]s. Every finished episode counts
+//! as a success when its reward reaches the stage's `success_reward`; once the success rate
+//! over the last `window` episodes reaches the stage's `advance_at`, training moves on to
+//! the next stage. Between episodes the gym room is rebuilt for the current stage: crates
+//! come and go, the opponents are respawned, and the agent is healed and sent back to where
+//! it started.
+//!
+//! Curricula are written in a RON training config file, stages in order:
+//!
+//! ```text
+//! (
+//!     window: 20,
+//!     stages: [
+//!         (name: "empty_room", obstacles: false, opponents: 0, success_reward: 5.0),
+//!         (name: "one_weak_bot", opponents: 1, opponent_health: 30.0),
+//!     ],
+//! )
+//! ```
+//!
+//! Stage settings left out keep the defaults of [`CurriculumStage::new`]. Only the server
+//! rebuilds the room, so stages with and without crates are meant for headless training.
+
+use avian3d::prelude::{LinearVelocity, Position};
+use bevy::prelude::*;
+use serde::Deserialize;
+use shared::GymMode;
+use shared::components::health::Health;
+use shared::gym::{
+    GymObstacle, GymRandomWanderer, LevelDoneMarker, spawn_gym_obstacles, spawn_gym_wanderer,
+};
+use shared::navigation::NavigationObstacle;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+
+use crate::reinforcement_learning::{EpisodeFinished, RLTrainingState};
+
+/// Where opponents spawn, the first ones first.
+const OPPONENT_SPAWNS: [Vec3; 4] = [
+    Vec3::new(-18.0, 1.0, -8.0),
+    Vec3::new(18.0, 1.0, 8.0),
+    Vec3::new(8.0, 1.0, -18.0),
+    Vec3::new(-8.0, 1.0, 18.0),
+];
+const OPPONENT_SPEED: f32 = 3.0;
+const DEFAULT_WINDOW: usize = 20;
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CurriculumStage {
+    pub name: String,
+    /// Whether the crates of the gym room are there.
+    pub obstacles: bool,
+    pub opponents: u32,
+    pub opponent_health: f32,
+    /// Episodes ending with at least this reward are successes.
+    pub success_reward: f32,
+    /// Success rate over the window that moves training to the next stage.
+    pub advance_at: f32,
+    /// Episodes the agent survives end after this long.
+    pub episode_secs: f32,
+}
+
+impl CurriculumStage {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            obstacles: true,
+            opponents: 0,
+            opponent_health: 100.0,
+            success_reward: 10.0,
+            advance_at: 0.8,
+            episode_secs: 60.0,
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.name.is_empty() {
+            return Err("a stage has no name".to_string());
+        }
+        let numbers = [
+            ("opponent_health", self.opponent_health),
+            ("success_reward", self.success_reward),
+            ("advance_at", self.advance_at),
+            ("episode_secs", self.episode_secs),
+        ];
+        match numbers.into_iter().find(|(_, value)| !value.is_finite()) {
+            Some((name, value)) => Err(format!(
+                "stage {}: bad value {} for {}",
+                self.name, value, name
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Default for CurriculumStage {
+    /// A stage without a name, which a curriculum file must give.
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+/// What a curriculum file holds, see the module docs.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CurriculumFile {
+    #[serde(default = "default_window")]
+    window: usize,
+    stages: Vec<CurriculumStage>,
+}
+
+fn default_window() -> usize {
+    DEFAULT_WINDOW
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct Curriculum {
+    pub stages: Vec<CurriculumStage>,
+    /// Episodes the success rate is measured over.
+    pub window: usize,
+    stage: usize,
+    results: VecDeque<bool>,
+    /// The room is rebuilt for the current stage before the next tick.
+    needs_reset: bool,
+    agent_spawn: Option<Vec3>,
+}
+
+impl Default for Curriculum {
+    /// Empty room, then crates, then one weak bot, then several bots.
+    fn default() -> Self {
+        let empty_room = CurriculumStage {
+            obstacles: false,
+            success_reward: 5.0,
+            ..CurriculumStage::new("empty_room")
+        };
+        let obstacles = CurriculumStage {
+            success_reward: 5.0,
+            ..CurriculumStage::new("obstacles")
+        };
+        let one_weak_bot = CurriculumStage {
+            opponents: 1,
+            opponent_health: 30.0,
+            ..CurriculumStage::new("one_weak_bot")
+        };
+        let multiple_bots = CurriculumStage {
+            opponents: 3,
+            ..CurriculumStage::new("multiple_bots")
+        };
+        Self::new(
+            vec![empty_room, obstacles, one_weak_bot, multiple_bots],
+            DEFAULT_WINDOW,
+        )
+    }
+}
+
+impl Curriculum {
+    pub fn new(stages: Vec<CurriculumStage>, window: usize) -> Self {
+        Self {
+            stages,
+            window: window.max(1),
+            stage: 0,
+            results: VecDeque::new(),
+            needs_reset: true,
+            agent_spawn: None,
+        }
+    }
+
+    /// Reads a training config, see the module docs. Unknown settings and bad values are
+    /// errors, and so is a curriculum without stages.
+    pub fn from_ron(text: &str) -> Result<Self, String> {
+        let file: CurriculumFile = ron::from_str(text).map_err(|e| e.to_string())?;
+        if file.stages.is_empty() {
+            return Err("the curriculum has no stages".to_string());
+        }
+        for stage in &file.stages {
+            stage.validate()?;
+        }
+        Ok(Self::new(file.stages, file.window))
+    }
+
+    pub fn stage(&self) -> &CurriculumStage {
+        &self.stages[self.stage]
+    }
+
+    pub fn stage_index(&self) -> usize {
+        self.stage
+    }
+
+    /// Success rate over the episodes played in this stage, up to `window` of them.
+    pub fn success_rate(&self) -> f32 {
+        if self.results.is_empty() {
+            return 0.0;
+        }
+        self.results.iter().filter(|success| **success).count() as f32 / self.results.len() as f32
+    }
+
+    /// Records a finished episode. Returns true when it moved training to the next stage.
+    pub fn record_episode(&mut self, reward: f32) -> bool {
+        self.results
+            .push_back(reward >= self.stage().success_reward);
+        if self.results.len() > self.window {
+            self.results.pop_front();
+        }
+        self.needs_reset = true;
+
+        let last_stage = self.stage + 1 >= self.stages.len();
+        if last_stage
+            || self.results.len() < self.window
+            || self.success_rate() < self.stage().advance_at
+        {
+            return false;
+        }
+        self.stage += 1;
+        self.results.clear();
+        true
+    }
+}
+
+/// Training config file holding the curriculum. The default curriculum applies when unset.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct CurriculumSettings {
+    pub config: Option<PathBuf>,
+}
+
+/// Runs a [`Curriculum`] on top of [`RLPlugin`](crate::reinforcement_learning::RLPlugin).
+pub struct CurriculumPlugin;
+
+impl Plugin for CurriculumPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CurriculumSettings>()
+            .init_resource::<Curriculum>()
+            .add_systems(Startup, load_curriculum)
+            .add_systems(
+                FixedUpdate,
+                (advance_curriculum, reset_gym_for_stage).chain(),
+            );
+    }
+}
+
+fn load_curriculum(settings: Res<CurriculumSettings>, mut curriculum: ResMut<Curriculum>) {
+    let Some(path) = &settings.config else {
+        return;
+    };
+
+    match std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| Curriculum::from_ron(&text))
+    {
+        Ok(loaded) => {
+            info!(
+                "Loaded a curriculum of {} stages from {}",
+                loaded.stages.len(),
+                path.display()
+            );
+            *curriculum = loaded;
+        }
+        Err(e) => error!("Failed to load curriculum from {}: {}", path.display(), e),
+    }
+}
+
+fn advance_curriculum(
+    mut episodes: MessageReader<EpisodeFinished>,
+    mut curriculum: ResMut<Curriculum>,
+) {
+    for episode in episodes.read() {
+        let stage = curriculum.stage().name.clone();
+        if curriculum.record_episode(episode.reward) {
+            info!(
+                "Curriculum: stage {} passed after episode {}, moving on to {}",
+                stage,
+                episode.episode,
+                curriculum.stage().name
+            );
+        }
+    }
+}
+
+/// Rebuilds the gym room for the current stage once an episode is over.
+#[allow(clippy::too_many_arguments)]
+fn reset_gym_for_stage(
+    mut commands: Commands,
+    gym_mode: Option<Res<GymMode>>,
+    level: Query<(), With<LevelDoneMarker>>,
+    mut meshes: Option<ResMut<Assets<Mesh>>>,
+    mut materials: Option<ResMut<Assets<StandardMaterial>>>,
+    crates: Query<Entity, With<GymObstacle>>,
+    opponents: Query<Entity, With<GymRandomWanderer>>,
+    obstacles: Query<&Position, With<NavigationObstacle>>,
+    mut agents: Query<
+        (&mut Position, &mut LinearVelocity, &mut Health),
+        Without<NavigationObstacle>,
+    >,
+    mut curriculum: ResMut<Curriculum>,
+    mut rl_state: ResMut<RLTrainingState>,
+) {
+    let is_gym_mode = gym_mode.is_some_and(|gm| gm.0);
+    if !is_gym_mode || !curriculum.needs_reset || level.is_empty() {
+        return;
+    }
+    let Some((mut position, mut velocity, mut health)) =
+        rl_state.agent.and_then(|agent| agents.get_mut(agent).ok())
+    else {
+        return;
+    };
+    curriculum.needs_reset = false;
+    let stage = curriculum.stage().clone();
+
+    let has_crates = !crates.is_empty();
+    if stage.obstacles != has_crates {
+        for entity in &crates {
+            commands.entity(entity).despawn();
+        }
+        if stage.obstacles
+            && let Some(meshes) = meshes.as_deref_mut()
+        {
+            spawn_gym_obstacles(&mut commands, meshes, materials.as_deref_mut());
+        }
+    }
+
+    for entity in &opponents {
+        commands.entity(entity).despawn();
+    }
+    for index in 0..stage.opponents as usize {
+        let health = Health {
+            current: stage.opponent_health,
+            max: stage.opponent_health,
+            ..Health::basic()
+        };
+        spawn_gym_wanderer(
+            &mut commands,
+            &obstacles,
+            &format!("Curriculum_Opponent_{}", index + 1),
+            OPPONENT_SPAWNS[index % OPPONENT_SPAWNS.len()],
+            OPPONENT_SPEED,
+            health,
+        );
+    }
+
+    let spawn = *curriculum.agent_spawn.get_or_insert(position.0);
+    position.0 = spawn;
+    velocity.0 = Vec3::ZERO;
+    health.reset();
+
+    // The teleport is not something the agent did.
+    rl_state.last_observation = None;
+    rl_state.last_action = None;
+    rl_state.max_episode_secs = stage.episode_secs;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Curriculum;
+
+    #[test]
+    fn stages_advance_on_a_rolling_success_rate() {
+        let mut curriculum = Curriculum::from_ron(
+            r#"(
+                window: 4,
+                stages: [
+                    (name: "empty_room", obstacles: false, success_reward: 5.0, advance_at: 0.75),
+                    (name: "bots", opponents: 2),
+                ],
+            )"#,
+        )
+        .unwrap();
+        assert_eq!(curriculum.stages.len(), 2);
+        assert!(!curriculum.stage().obstacles);
+        assert_eq!(curriculum.stages[1].opponents, 2);
+        assert!(Curriculum::from_ron("(window: 4, stages: [])").is_err());
+        assert!(Curriculum::from_ron("(stages: [(opponents: 2)])").is_err());
+        assert!(Curriculum::from_ron(r#"(stages: [(name: "a", opponents: "many")])"#).is_err());
+        assert!(Curriculum::from_ron(r#"(stages: [(name: "a", bots: 2)])"#).is_err());
+
+        // Not before the window is full, and not below the threshold.
+        for reward in [10.0, 0.0, 0.0, 10.0] {
+            assert!(!curriculum.record_episode(reward));
+        }
+        assert_eq!(curriculum.success_rate(), 0.5);
+
+        assert!(!curriculum.record_episode(10.0));
+        assert!(curriculum.record_episode(10.0));
+        assert_eq!(curriculum.stage().name, "bots");
+        assert_eq!(curriculum.success_rate(), 0.0);
+
+        // The last stage is kept forever.
+        for _ in 0..8 {
+            assert!(!curriculum.record_episode(100.0));
+        }
+        assert_eq!(curriculum.stage_index(), 1);
+    }
+}
@@ -0,0 +1,132 @@
+//! Demonstrations recorded by players (`launcher client --record-demo FILE`), turned into
+//! experiences for behavior cloning: `PolicyValueNetwork::fit_experiences` on them
+//! pretrains a policy to act like the players before reinforcement learning takes over.
+//!
+//! States are the observation buffers as recorded, so networks trained on them read
+//! [`ObservationSchema::current`](crate::observation::ObservationSchema::current) inputs.
+//! Recordings carry no rewards: each step is scored by a [`RewardRegistry`] instead, so
+//! the value head learns returns too.
+
+use std::path::Path;
+
+use shared::demo::{DemoFrame, read_demo};
+
+use crate::observation::ExternalObservation;
+use crate::reinforcement_learning::{Experience, PlayerActionSet, RLObservation};
+use crate::rewards::{RewardRegistry, RewardStep};
+
+/// Seconds between two recorded frames: one fixed tick.
+const FRAME_SECS: f32 = (1.0 / shared::FIXED_TIMESTEP_HZ) as f32;
+
+/// Reads a demonstration file into experiences, see [`demonstration_experiences`].
+pub fn load_demonstrations(
+    path: &Path,
+    rewards: &RewardRegistry,
+) -> Result<Vec<Experience>, String> {
+    let frames = read_demo(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    demonstration_experiences(&frames, rewards).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// One experience per frame, in order. An episode ends wherever the recording does or
+/// skips ticks, such as between a death and the respawn.
+pub fn demonstration_experiences(
+    frames: &[DemoFrame],
+    rewards: &RewardRegistry,
+) -> Result<Vec<Experience>, String> {
+    let observations = frames
+        .iter()
+        .enumerate()
+        .map(|(index, frame)| {
+            ExternalObservation::from_buffer(&frame.observation)
+                .map_err(|e| format!("frame {}: {}", index, e))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(frames
+        .iter()
+        .enumerate()
+        .map(|(index, frame)| {
+            let next = frames
+                .get(index + 1)
+                .filter(|next| next.tick == frame.tick.wrapping_add(1));
+            let current = rl_observation(&observations[index]);
+            let following = match next {
+                Some(_) => rl_observation(&observations[index + 1]),
+                None => current.clone(),
+            };
+            let action = PlayerActionSet::from_vector(&frame.action);
+            let reward = rewards
+                .evaluate(&RewardStep {
+                    previous: &current,
+                    current: &following,
+                    action: &action,
+                    damage_dealt: 0.0,
+                    kills: 0,
+                    dt: FRAME_SECS,
+                })
+                .total();
+            Experience {
+                state: frame.observation.clone(),
+                action,
+                reward,
+                next_state: next.map_or_else(
+                    || frame.observation.clone(),
+                    |next| next.observation.clone(),
+                ),
+                done: next.is_none(),
+            }
+        })
+        .collect())
+}
+
+fn rl_observation(observation: &ExternalObservation) -> RLObservation {
+    RLObservation {
+        position: observation.position,
+        velocity: observation.velocity,
+        health: observation.health,
+        max_health: observation.max_health,
+        distance_to_objective: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::demonstration_experiences;
+    use crate::rewards::{RewardRegistry, SurvivalTime};
+    use bevy::prelude::Vec3;
+    use shared::demo::DemoFrame;
+    use shared::observation::{RAY_COUNT, observation_buffer};
+
+    #[test]
+    fn recordings_split_into_episodes_where_ticks_are_skipped() {
+        let frame = |tick: u32| DemoFrame {
+            tick,
+            observation: observation_buffer(
+                Vec3::new(tick as f32, 0.0, 0.0),
+                Vec3::ZERO,
+                100.0,
+                100.0,
+                &[5.0; RAY_COUNT],
+                &[0.0; 6],
+            ),
+            action: [1.0, 0.0, 0.0, 0.0, 0.0, 1.0],
+        };
+        let frames = [frame(7), frame(8), frame(20)];
+        let rewards = RewardRegistry::empty().with(SurvivalTime, 1.0);
+
+        let experiences = demonstration_experiences(&frames, &rewards).unwrap();
+        assert_eq!(experiences.len(), 3);
+        assert_eq!(
+            experiences.iter().map(|e| e.done).collect::<Vec<_>>(),
+            [false, true, true]
+        );
+        assert_eq!(experiences[0].next_state, frames[1].observation);
+        assert_eq!(experiences[1].next_state, frames[1].observation);
+        assert!(experiences[0].action.shoot);
+        assert!(experiences[0].reward > 0.0);
+
+        let mut stale = frame(21);
+        stale.observation[0] = 0.0;
+        assert!(demonstration_experiences(&[stale], &rewards).is_err());
+    }
+}
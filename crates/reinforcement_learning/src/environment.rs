@@ -0,0 +1,155 @@
+//! Environments that step without a Bevy app, for headless training.
+//!
+//! [`ExitEnvironment`] is the gym room reduced to its floor: the agent walks at the speed
+//! `RLPlugin` gives it towards an exit placed at random, and is scored by a
+//! [`RewardRegistry`] like in the game.
+
+use bevy::prelude::{Vec2, Vec3};
+use rand::Rng;
+use shared::bot_policy::{BotObservation, OBSERVATION_SIZE};
+use shared::game_math::yaw_facing;
+
+use crate::reinforcement_learning::{PlayerActionSet, RLObservation};
+use crate::rewards::{ObjectiveProgress, RewardRegistry, RewardStep, SurvivalTime};
+
+/// Speed at full stick, as applied by `RLPlugin`.
+const MOVE_SPEED: f32 = 5.0;
+
+/// Outcome of one environment step.
+#[derive(Clone, Debug)]
+pub struct EnvironmentStep {
+    pub state: Vec<f32>,
+    pub reward: f32,
+    /// The episode is over; the next step needs a reset first.
+    pub done: bool,
+}
+
+/// Something a policy can be trained in.
+pub trait TrainingEnvironment {
+    /// Length of the state vectors.
+    fn state_size(&self) -> usize;
+    /// Starts a new episode and returns its first state.
+    fn reset(&mut self, rng: &mut impl Rng) -> Vec<f32>;
+    fn step(&mut self, action: &PlayerActionSet) -> EnvironmentStep;
+}
+
+/// A flat, square room with an exit to reach.
+pub struct ExitEnvironment {
+    /// Half the width of the room, in meters.
+    pub half_extent: f32,
+    /// How close to the exit counts as reaching it.
+    pub exit_radius: f32,
+    /// Seconds between two decisions of the agent.
+    pub step_secs: f32,
+    pub max_episode_secs: f32,
+    pub rewards: RewardRegistry,
+    position: Vec3,
+    velocity: Vec3,
+    yaw: f32,
+    exit: Vec3,
+    elapsed: f32,
+}
+
+impl Default for ExitEnvironment {
+    fn default() -> Self {
+        Self {
+            half_extent: 15.0,
+            exit_radius: 1.5,
+            step_secs: 0.1,
+            max_episode_secs: 20.0,
+            // Every second spent looking for the exit costs a little.
+            rewards: RewardRegistry::empty()
+                .with(ObjectiveProgress, 1.0)
+                .with(SurvivalTime, -0.1),
+            position: Vec3::ZERO,
+            velocity: Vec3::ZERO,
+            yaw: 0.0,
+            exit: Vec3::ZERO,
+            elapsed: 0.0,
+        }
+    }
+}
+
+impl ExitEnvironment {
+    fn observation(&self) -> RLObservation {
+        RLObservation {
+            position: self.position,
+            velocity: self.velocity,
+            health: 100.0,
+            max_health: 100.0,
+            distance_to_objective: Some(self.position.distance(self.exit)),
+        }
+    }
+
+    /// What a server bot sees of its target, with the exit as the target, so trained
+    /// policies export to `BotPolicy`.
+    fn state(&self) -> Vec<f32> {
+        BotObservation {
+            target_offset: Some(self.exit - self.position),
+            health_fraction: 1.0,
+            yaw: self.yaw,
+        }
+        .to_vector()
+        .to_vec()
+    }
+
+    fn random_point(&self, rng: &mut impl Rng) -> Vec3 {
+        let extent = self.half_extent - self.exit_radius;
+        Vec3::new(
+            rng.random_range(-extent..extent),
+            0.0,
+            rng.random_range(-extent..extent),
+        )
+    }
+}
+
+impl TrainingEnvironment for ExitEnvironment {
+    fn state_size(&self) -> usize {
+        OBSERVATION_SIZE
+    }
+
+    fn reset(&mut self, rng: &mut impl Rng) -> Vec<f32> {
+        self.position = self.random_point(rng);
+        self.exit = self.random_point(rng);
+        while self.position.distance(self.exit) < self.exit_radius * 2.0 {
+            self.exit = self.random_point(rng);
+        }
+        self.velocity = Vec3::ZERO;
+        self.yaw = 0.0;
+        self.elapsed = 0.0;
+        self.state()
+    }
+
+    fn step(&mut self, action: &PlayerActionSet) -> EnvironmentStep {
+        let previous = self.observation();
+        let direction = Vec2::new(action.movement.x, -action.movement.y).clamp_length_max(1.0);
+        self.velocity = Vec3::new(direction.x, 0.0, direction.y) * MOVE_SPEED;
+        if direction != Vec2::ZERO {
+            self.yaw = yaw_facing(self.velocity);
+        }
+        self.position = (self.position + self.velocity * self.step_secs).clamp(
+            Vec3::splat(-self.half_extent),
+            Vec3::splat(self.half_extent),
+        );
+        self.elapsed += self.step_secs;
+
+        let current = self.observation();
+        let reward = self
+            .rewards
+            .evaluate(&RewardStep {
+                previous: &previous,
+                current: &current,
+                action,
+                damage_dealt: 0.0,
+                kills: 0,
+                dt: self.step_secs,
+            })
+            .total();
+        let reached = self.position.distance(self.exit) <= self.exit_radius;
+        EnvironmentStep {
+            state: self.state(),
+            reward,
+            done: reached || self.elapsed >= self.max_episode_secs,
+        }
+    }
+}
@@ -1 +1,11 @@
+pub mod curriculum;
+pub mod demonstrations;
+pub mod environment;
+pub mod metrics;
+pub mod observation;
+pub mod policy_network;
+pub mod ppo;
 pub mod reinforcement_learning;
+pub mod remote_agents;
+pub mod rewards;
+pub mod self_play;
@@ -0,0 +1,251 @@
+//! Training metrics written to a run directory, for TensorBoard and friends.
+//!
+//! The default format is a TensorBoard event file, which TensorBoard reads directly and
+//! Weights & Biases imports with `wandb sync --sync-tensorboard`. Where neither is around,
+//! the same scalars can go to a CSV file (`wall_time,step,tag,value` rows) or a JSONL file
+//! (one object per step).
+//!
+//! Event files are written by hand: each event is a small protobuf in a TFRecord frame, so
+//! no TensorFlow or protobuf dependency is needed for the handful of fields scalars use.
+
+use bevy::prelude::Resource;
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MetricsFormat {
+    #[default]
+    TensorBoard,
+    Csv,
+    Jsonl,
+}
+
+impl MetricsFormat {
+    /// `tensorboard`, `csv` or `jsonl`.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_lowercase().as_str() {
+            "tensorboard" => Ok(Self::TensorBoard),
+            "csv" => Ok(Self::Csv),
+            "jsonl" => Ok(Self::Jsonl),
+            _ => Err(format!("unknown metrics format {:?}", value)),
+        }
+    }
+}
+
+/// Where a training run writes its metrics. Nothing is written without a run directory.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct MetricsSettings {
+    pub run_dir: Option<PathBuf>,
+    pub format: MetricsFormat,
+}
+
+/// Appends scalars to a metrics file in a run directory.
+pub struct MetricsWriter {
+    format: MetricsFormat,
+    path: PathBuf,
+    file: BufWriter<File>,
+}
+
+impl MetricsWriter {
+    /// Creates `run_dir` if needed and starts a new metrics file in it.
+    pub fn create(run_dir: &Path, format: MetricsFormat) -> io::Result<Self> {
+        std::fs::create_dir_all(run_dir)?;
+        let file_name = match format {
+            MetricsFormat::TensorBoard => format!(
+                "events.out.tfevents.{}.{}",
+                wall_time() as u64,
+                std::env::var("HOSTNAME").unwrap_or_else(|_| "localhost".to_string())
+            ),
+            MetricsFormat::Csv => "metrics.csv".to_string(),
+            MetricsFormat::Jsonl => "metrics.jsonl".to_string(),
+        };
+        let path = run_dir.join(file_name);
+        let mut writer = Self {
+            format,
+            file: BufWriter::new(File::create(&path)?),
+            path,
+        };
+
+        match format {
+            MetricsFormat::TensorBoard => {
+                let mut event = event_header(0);
+                proto_bytes(&mut event, 3, b"brain.Event:2");
+                writer.write_record(&event)?;
+            }
+            MetricsFormat::Csv => writeln!(writer.file, "wall_time,step,tag,value")?,
+            MetricsFormat::Jsonl => {}
+        }
+        writer.file.flush()?;
+        Ok(writer)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Writes `scalars` as `(tag, value)` pairs at `step`, and flushes them so a running
+    /// TensorBoard picks them up.
+    pub fn write_scalars(&mut self, step: u64, scalars: &[(&str, f32)]) -> io::Result<()> {
+        let wall_time = wall_time();
+        match self.format {
+            MetricsFormat::TensorBoard => {
+                let mut summary = Vec::new();
+                for (tag, value) in scalars {
+                    let mut summary_value = Vec::new();
+                    proto_bytes(&mut summary_value, 1, tag.as_bytes());
+                    proto_key(&mut summary_value, 2, 5);
+                    summary_value.extend_from_slice(&value.to_le_bytes());
+                    proto_bytes(&mut summary, 1, &summary_value);
+                }
+                let mut event = event_header(step);
+                proto_bytes(&mut event, 5, &summary);
+                self.write_record(&event)?;
+            }
+            MetricsFormat::Csv => {
+                for (tag, value) in scalars {
+                    writeln!(self.file, "{:.3},{},{},{}", wall_time, step, tag, value)?;
+                }
+            }
+            MetricsFormat::Jsonl => {
+                let row = JsonlRow {
+                    wall_time,
+                    step,
+                    scalars,
+                };
+                serde_json::to_writer(&mut self.file, &row)?;
+                writeln!(self.file)?;
+            }
+        }
+        self.file.flush()
+    }
+
+    /// TFRecord frame: length, its checksum, the data and its checksum.
+    fn write_record(&mut self, data: &[u8]) -> io::Result<()> {
+        let length = (data.len() as u64).to_le_bytes();
+        self.file.write_all(&length)?;
+        self.file.write_all(&masked_crc32c(&length).to_le_bytes())?;
+        self.file.write_all(data)?;
+        self.file.write_all(&masked_crc32c(data).to_le_bytes())
+    }
+}
+
+/// One JSONL line: the wall time, the step, then each scalar under its tag.
+struct JsonlRow<'a> {
+    wall_time: f64,
+    step: u64,
+    scalars: &'a [(&'a str, f32)],
+}
+
+impl Serialize for JsonlRow<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut row = serializer.serialize_map(Some(2 + self.scalars.len()))?;
+        row.serialize_entry("wall_time", &self.wall_time)?;
+        row.serialize_entry("step", &self.step)?;
+        // JSON has no NaN or infinities; serde_json writes them as null.
+        for (tag, value) in self.scalars {
+            row.serialize_entry(tag, value)?;
+        }
+        row.end()
+    }
+}
+
+fn wall_time() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0.0, |since| since.as_secs_f64())
+}
+
+/// Start of an `Event` message: its wall time and step.
+fn event_header(step: u64) -> Vec<u8> {
+    let mut event = Vec::new();
+    proto_key(&mut event, 1, 1);
+    event.extend_from_slice(&wall_time().to_le_bytes());
+    proto_key(&mut event, 2, 0);
+    proto_varint(&mut event, step);
+    event
+}
+
+fn proto_key(buffer: &mut Vec<u8>, field: u64, wire_type: u64) {
+    proto_varint(buffer, (field << 3) | wire_type);
+}
+
+fn proto_varint(buffer: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buffer.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buffer.push(value as u8);
+}
+
+/// A length-delimited field: strings and nested messages.
+fn proto_bytes(buffer: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+    proto_key(buffer, field, 2);
+    proto_varint(buffer, bytes.len() as u64);
+    buffer.extend_from_slice(bytes);
+}
+
+/// CRC-32C (Castagnoli), as TFRecord frames use.
+fn crc32c(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0x82f6_3b78
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn masked_crc32c(data: &[u8]) -> u32 {
+    crc32c(data).rotate_right(15).wrapping_add(0xa282_ead8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MetricsFormat, MetricsWriter, crc32c};
+
+    #[test]
+    fn scalars_land_in_the_run_directory() {
+        // Check value of CRC-32C.
+        assert_eq!(crc32c(b"123456789"), 0xe306_9283);
+        assert_eq!(MetricsFormat::parse("CSV"), Ok(MetricsFormat::Csv));
+        assert!(MetricsFormat::parse("xml").is_err());
+
+        let run_dir = std::env::temp_dir().join(format!("metrics_test_{}", std::process::id()));
+        let mut csv = MetricsWriter::create(&run_dir, MetricsFormat::Csv).unwrap();
+        csv.write_scalars(3, &[("episode/reward", 1.5), ("episode/length", 40.0)])
+            .unwrap();
+        let mut jsonl = MetricsWriter::create(&run_dir, MetricsFormat::Jsonl).unwrap();
+        jsonl.write_scalars(3, &[("loss/value", f32::NAN)]).unwrap();
+        let mut events = MetricsWriter::create(&run_dir, MetricsFormat::TensorBoard).unwrap();
+        events.write_scalars(3, &[("episode/reward", 1.5)]).unwrap();
+
+        let rows = std::fs::read_to_string(csv.path()).unwrap();
+        let rows: Vec<_> = rows
+            .lines()
+            .map(|row| row.split(',').collect::<Vec<_>>())
+            .collect();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[1][1..], ["3", "episode/reward", "1.5"]);
+        let line = std::fs::read_to_string(jsonl.path()).unwrap();
+        assert!(
+            line.trim_end()
+                .ends_with(",\"step\":3,\"loss/value\":null}")
+        );
+        let bytes = std::fs::read(events.path()).unwrap();
+        // Two frames: the file version, then the scalar.
+        let first_length = u64::from_le_bytes(bytes[..8].try_into().unwrap()) as usize;
+        let second = 8 + 4 + first_length + 4;
+        let second_length =
+            u64::from_le_bytes(bytes[second..second + 8].try_into().unwrap()) as usize;
+        assert_eq!(bytes.len(), second + 8 + 4 + second_length + 4);
+        std::fs::remove_dir_all(&run_dir).ok();
+    }
+}
@@ -0,0 +1,216 @@
+//! Observation layout handed to external RL agents.
+//!
+//! An observation is a flat buffer of little-endian `f32`s whose layout is fixed for a
+//! given [`OBSERVATION_VERSION`]: the version itself comes first so trainers can reject
+//! buffers they were not written for, then the fields listed by [`ObservationSchema`] in
+//! order. Any change to the layout bumps the version.
+
+use avian3d::prelude::SpatialQueryPipeline;
+use bevy::prelude::{Entity, Quat, Vec3};
+use serde::Serialize;
+
+use crate::reinforcement_learning::PlayerActionSet;
+use shared::observation::observation_buffer;
+
+// The layout lives in `shared`, where clients recording demonstrations build it too.
+pub use shared::observation::{
+    LAST_ACTION_SIZE, OBSERVATION_VERSION, RAY_COUNT, RAY_HEIGHT, RAY_RANGE, ray_distances,
+};
+
+/// One named run of values in the observation buffer.
+#[derive(Clone, Copy, Debug, Serialize, PartialEq)]
+pub struct ObservationField {
+    pub name: &'static str,
+    /// Index of the field's first value in the buffer.
+    pub offset: usize,
+    pub len: usize,
+    pub unit: &'static str,
+}
+
+/// Layout of an observation buffer, serializable so trainers can load it instead of
+/// hard-coding offsets.
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct ObservationSchema {
+    pub version: u32,
+    pub len: usize,
+    pub fields: Vec<ObservationField>,
+}
+
+impl ObservationSchema {
+    pub fn current() -> Self {
+        let runs = [
+            ("version", 1, "schema version"),
+            ("position", 3, "m, world x y z"),
+            ("velocity", 3, "m/s, world x y z"),
+            ("health", 1, "hit points"),
+            ("max_health", 1, "hit points"),
+            ("ray_distances", RAY_COUNT, "m, capped at the ray range"),
+            (
+                "last_action",
+                LAST_ACTION_SIZE,
+                "move x y, look x y, jump, shoot",
+            ),
+        ];
+        let mut offset = 0;
+        let fields = runs
+            .into_iter()
+            .map(|(name, len, unit)| {
+                let field = ObservationField {
+                    name,
+                    offset,
+                    len,
+                    unit,
+                };
+                offset += len;
+                field
+            })
+            .collect();
+        Self {
+            version: OBSERVATION_VERSION,
+            len: offset,
+            fields,
+        }
+    }
+
+    pub fn field(&self, name: &str) -> Option<&ObservationField> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+}
+
+/// Everything an external agent sees in one tick.
+#[derive(Clone, Debug)]
+pub struct ExternalObservation {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub health: f32,
+    pub max_health: f32,
+    pub ray_distances: [f32; RAY_COUNT],
+    pub last_action: PlayerActionSet,
+}
+
+impl ExternalObservation {
+    /// The observation laid out as described by [`ObservationSchema::current`].
+    pub fn to_buffer(&self) -> Vec<f32> {
+        let mut last_action = [0.0; LAST_ACTION_SIZE];
+        last_action.copy_from_slice(&self.last_action.to_vector());
+        observation_buffer(
+            self.position,
+            self.velocity,
+            self.health,
+            self.max_health,
+            &self.ray_distances,
+            &last_action,
+        )
+    }
+
+    /// The buffer as little-endian bytes, for sending over a socket or pipe.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_buffer()
+            .into_iter()
+            .flat_map(f32::to_le_bytes)
+            .collect()
+    }
+
+    pub fn from_buffer(buffer: &[f32]) -> Result<Self, String> {
+        let schema = ObservationSchema::current();
+        if buffer.len() != schema.len {
+            return Err(format!(
+                "observation has {} values, expected {}",
+                buffer.len(),
+                schema.len
+            ));
+        }
+        if buffer[0] != OBSERVATION_VERSION as f32 {
+            return Err(format!(
+                "observation version {}, expected {}",
+                buffer[0], OBSERVATION_VERSION
+            ));
+        }
+
+        let values = |name: &str| {
+            let field = schema.field(name).expect("field is part of the schema");
+            &buffer[field.offset..field.offset + field.len]
+        };
+        let mut ray_distances = [0.0; RAY_COUNT];
+        ray_distances.copy_from_slice(values("ray_distances"));
+        Ok(Self {
+            position: Vec3::from_slice(values("position")),
+            velocity: Vec3::from_slice(values("velocity")),
+            health: values("health")[0],
+            max_health: values("max_health")[0],
+            ray_distances,
+            last_action: PlayerActionSet::from_vector(values("last_action")),
+        })
+    }
+}
+
+/// Observation of `agent` for an external trainer.
+#[allow(clippy::too_many_arguments)]
+pub fn get_external_agent_observation(
+    spatial_query: &SpatialQueryPipeline,
+    agent: Entity,
+    position: Vec3,
+    rotation: Quat,
+    velocity: Vec3,
+    health: f32,
+    max_health: f32,
+    last_action: Option<&PlayerActionSet>,
+) -> ExternalObservation {
+    ExternalObservation {
+        position,
+        velocity,
+        health,
+        max_health,
+        ray_distances: ray_distances(spatial_query, agent, position, rotation),
+        last_action: last_action.cloned().unwrap_or(PlayerActionSet {
+            movement: Default::default(),
+            look: Default::default(),
+            jump: false,
+            shoot: false,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ExternalObservation, OBSERVATION_VERSION, ObservationSchema, RAY_COUNT};
+    use crate::reinforcement_learning::PlayerActionSet;
+    use bevy::prelude::{Vec2, Vec3};
+
+    #[test]
+    fn buffers_follow_the_schema_and_decode_back() {
+        let observation = ExternalObservation {
+            position: Vec3::new(1.0, 2.0, 3.0),
+            velocity: Vec3::new(-1.0, 0.0, 4.0),
+            health: 60.0,
+            max_health: 100.0,
+            ray_distances: std::array::from_fn(|index| index as f32),
+            last_action: PlayerActionSet {
+                movement: Vec2::new(0.5, -1.0),
+                look: Vec2::ZERO,
+                jump: true,
+                shoot: false,
+            },
+        };
+        let schema = ObservationSchema::current();
+        let buffer = observation.to_buffer();
+        assert_eq!(buffer.len(), schema.len);
+        assert_eq!(schema.len, shared::observation::OBSERVATION_LEN);
+        assert_eq!(observation.to_bytes().len(), schema.len * 4);
+        assert_eq!(buffer[0], OBSERVATION_VERSION as f32);
+
+        let rays = schema.field("ray_distances").unwrap();
+        assert_eq!(rays.len, RAY_COUNT);
+        assert_eq!(buffer[rays.offset + 3], 3.0);
+        let health = schema.field("health").unwrap();
+        assert_eq!(buffer[health.offset], 60.0);
+
+        let decoded = ExternalObservation::from_buffer(&buffer).unwrap();
+        assert_eq!(decoded.to_buffer(), buffer);
+
+        let mut stale = buffer.clone();
+        stale[0] = 0.0;
+        assert!(ExternalObservation::from_buffer(&stale).is_err());
+        assert!(ExternalObservation::from_buffer(&buffer[1..]).is_err());
+    }
+}
@@ -0,0 +1,607 @@
+//! Policy/value network on candle, for training beyond what [`SimpleNetwork`] can learn.
+//!
+//! A trunk of fully connected ReLU layers feeds two heads: the policy head outputs the six
+//! values of `PlayerActionSet::to_vector` (sticks squashed to -1..1, buttons to 0..1) and
+//! the value head estimates the return from a state. The trunk is set in a
+//! RON training config:
+//!
+//! ```text
+//! (
+//!     hidden_layers: [128, 128],
+//!     device: Cuda(0),
+//!     learning_rate: 0.0003,
+//! )
+//! ```
+//!
+//! GPUs need the crate's `cuda` feature; without it, or without a GPU, the network runs on
+//! the CPU. Checkpoints are safetensors files holding the weights and the layer sizes, so
+//! they load without their config.
+//!
+//! For policy-gradient training (see [`ppo`](crate::ppo)) the policy is stochastic: sticks
+//! are Gaussian around the policy head's output with a learned spread, buttons are pressed
+//! with the probability the head outputs.
+//!
+//! Networks reading `BotObservation`s, like those trained in
+//! [`ExitEnvironment`](crate::environment::ExitEnvironment), export to the policy format
+//! `launcher --bot-policy` loads with [`PolicyValueNetwork::to_bot_policy`].
+//!
+//! Experience buffers collected for [`SimpleNetwork`] carry over:
+//! [`PolicyValueNetwork::fit_experiences`] warm-starts a network by imitating their actions
+//! and regressing their discounted returns.
+//!
+//! [`SimpleNetwork`]: crate::reinforcement_learning::SimpleNetwork
+
+use bevy::prelude::Resource;
+use candle_core::{DType, Device, Result, Tensor, bail};
+use candle_nn::{AdamW, Init, Linear, Module, Optimizer, ParamsAdamW, VarBuilder, VarMap, linear};
+use rand::Rng;
+use serde::Deserialize;
+use shared::bot_policy::{self, BotPolicy};
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use std::path::{Path, PathBuf};
+
+use crate::reinforcement_learning::{Experience, PlayerActionSet};
+
+/// Values of `PlayerActionSet::to_vector`: move and look sticks, then jump and shoot.
+pub const ACTION_VALUES: usize = 6;
+const STICK_VALUES: usize = 4;
+/// Log of the stick spread before training.
+const INITIAL_LOG_STD: f64 = -0.5;
+/// Keeps button log-probabilities finite.
+const PROBABILITY_EPSILON: f32 = 1e-6;
+/// Name of the layer sizes in checkpoints.
+const LAYERS_KEY: &str = "layer_sizes";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum PolicyDevice {
+    #[default]
+    Cpu,
+    /// Falls back to the CPU when no CUDA device is usable.
+    Cuda(usize),
+}
+
+impl PolicyDevice {
+    pub fn device(self) -> Result<Device> {
+        match self {
+            Self::Cpu => Ok(Device::Cpu),
+            Self::Cuda(index) => Device::cuda_if_available(index),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PolicyNetworkConfig {
+    /// Length of the state vectors fed to the network. Set by the environment, not the
+    /// config.
+    #[serde(skip)]
+    pub input_size: usize,
+    pub hidden_layers: Vec<usize>,
+    pub device: PolicyDevice,
+    pub learning_rate: f64,
+}
+
+impl Default for PolicyNetworkConfig {
+    fn default() -> Self {
+        Self {
+            input_size: 8, // RLTrainingState::observation_to_state
+            hidden_layers: vec![64, 64],
+            device: PolicyDevice::Cpu,
+            learning_rate: 3e-4,
+        }
+    }
+}
+
+impl PolicyNetworkConfig {
+    /// Reads a training config over the defaults. Unknown settings and bad values are
+    /// errors.
+    pub fn from_ron(text: &str) -> std::result::Result<Self, String> {
+        let config: Self = ron::from_str(text).map_err(|e| e.to_string())?;
+        if config.hidden_layers.contains(&0) {
+            return Err(format!("bad hidden_layers {:?}", config.hidden_layers));
+        }
+        if !(config.learning_rate > 0.0 && config.learning_rate.is_finite()) {
+            return Err(format!("bad learning_rate {}", config.learning_rate));
+        }
+        Ok(config)
+    }
+}
+
+/// Where to find the policy network of a training run. A checkpoint wins over a config;
+/// with neither, training keeps using the `SimpleNetwork`.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct PolicyNetworkSettings {
+    pub config: Option<PathBuf>,
+    pub checkpoint: Option<PathBuf>,
+}
+
+pub struct PolicyValueNetwork {
+    config: PolicyNetworkConfig,
+    device: Device,
+    varmap: VarMap,
+    trunk: Vec<Linear>,
+    policy_head: Linear,
+    value_head: Linear,
+    /// Log standard deviation of each stick value, `[STICK_VALUES]`.
+    log_std: Tensor,
+    optimizer: AdamW,
+}
+
+/// An action drawn from the policy.
+#[derive(Clone, Debug)]
+pub struct SampledAction {
+    /// The drawn values, laid out like `PlayerActionSet::to_vector`. Sticks are not
+    /// clamped, so the log-probability can be evaluated again after an update.
+    pub values: Vec<f32>,
+    pub log_prob: f32,
+    /// Estimated value of the state.
+    pub value: f32,
+}
+
+impl SampledAction {
+    pub fn action(&self) -> PlayerActionSet {
+        PlayerActionSet::from_vector(&self.values)
+    }
+}
+
+impl PolicyValueNetwork {
+    pub fn new(config: PolicyNetworkConfig) -> Result<Self> {
+        let device = config.device.device()?;
+        let varmap = VarMap::new();
+        let vb = VarBuilder::from_varmap(&varmap, DType::F32, &device);
+
+        let mut trunk = Vec::with_capacity(config.hidden_layers.len());
+        let mut width = config.input_size;
+        for (index, &size) in config.hidden_layers.iter().enumerate() {
+            trunk.push(linear(width, size, vb.pp(format!("trunk.{}", index)))?);
+            width = size;
+        }
+        let policy_head = linear(width, ACTION_VALUES, vb.pp("policy"))?;
+        let value_head = linear(width, 1, vb.pp("value"))?;
+        let log_std = vb.get_with_hints(STICK_VALUES, "log_std", Init::Const(INITIAL_LOG_STD))?;
+        let optimizer = AdamW::new(
+            varmap.all_vars(),
+            ParamsAdamW {
+                lr: config.learning_rate,
+                ..Default::default()
+            },
+        )?;
+
+        Ok(Self {
+            config,
+            device,
+            varmap,
+            trunk,
+            policy_head,
+            value_head,
+            log_std,
+            optimizer,
+        })
+    }
+
+    pub fn config(&self) -> &PolicyNetworkConfig {
+        &self.config
+    }
+
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// Actions and values of a batch of states, shaped `[batch, ACTION_VALUES]` and
+    /// `[batch]`.
+    pub fn forward(&self, states: &Tensor) -> Result<(Tensor, Tensor)> {
+        let mut hidden = states.clone();
+        for layer in &self.trunk {
+            hidden = layer.forward(&hidden)?.relu()?;
+        }
+
+        let raw = self.policy_head.forward(&hidden)?;
+        let sticks = raw.narrow(1, 0, STICK_VALUES)?.tanh()?;
+        let buttons =
+            candle_nn::ops::sigmoid(&raw.narrow(1, STICK_VALUES, ACTION_VALUES - STICK_VALUES)?)?;
+        let actions = Tensor::cat(&[&sticks, &buttons], 1)?;
+        let values = self.value_head.forward(&hidden)?.squeeze(1)?;
+        Ok((actions, values))
+    }
+
+    /// Greedy action for one state, and the state's estimated value.
+    pub fn act(&self, state: &[f32]) -> Result<(PlayerActionSet, f32)> {
+        let input = Tensor::from_slice(state, (1, state.len()), &self.device)?;
+        let (actions, values) = self.forward(&input)?;
+        let action = PlayerActionSet::from_vector(&actions.squeeze(0)?.to_vec1::<f32>()?);
+        Ok((action, values.squeeze(0)?.to_scalar::<f32>()?))
+    }
+
+    /// Draws an action for one state from the stochastic policy.
+    pub fn sample(&self, state: &[f32], rng: &mut impl Rng) -> Result<SampledAction> {
+        let input = Tensor::from_slice(state, (1, state.len()), &self.device)?;
+        let (means, _) = self.forward(&input)?;
+        let mut values = means.squeeze(0)?.to_vec1::<f32>()?;
+        let stds = self.log_std.exp()?.to_vec1::<f32>()?;
+        for (value, std) in values.iter_mut().zip(stds) {
+            *value += std * standard_normal(rng);
+        }
+        for button in &mut values[STICK_VALUES..] {
+            *button = if rng.random::<f32>() < *button {
+                1.0
+            } else {
+                0.0
+            };
+        }
+
+        let actions = Tensor::from_slice(&values, (1, ACTION_VALUES), &self.device)?;
+        let (log_probs, _, state_values) = self.evaluate(&input, &actions)?;
+        Ok(SampledAction {
+            values,
+            log_prob: log_probs.squeeze(0)?.to_scalar::<f32>()?,
+            value: state_values.squeeze(0)?.to_scalar::<f32>()?,
+        })
+    }
+
+    /// Log-probabilities of `actions` under the current policy, the policy's entropy and
+    /// the estimated values, each `[batch]`, for a batch of states.
+    pub fn evaluate(&self, states: &Tensor, actions: &Tensor) -> Result<(Tensor, Tensor, Tensor)> {
+        let (means, values) = self.forward(states)?;
+        let button_count = ACTION_VALUES - STICK_VALUES;
+        let half_log_two_pi = 0.5 * (2.0 * PI).ln() as f64;
+
+        // Sticks: independent Gaussians around the policy head's output.
+        let stick_means = means.narrow(1, 0, STICK_VALUES)?;
+        let sticks = actions.narrow(1, 0, STICK_VALUES)?;
+        let stick_log_probs = sticks
+            .sub(&stick_means)?
+            .broadcast_div(&self.log_std.exp()?)?
+            .sqr()?
+            .affine(-0.5, -half_log_two_pi)?
+            .broadcast_sub(&self.log_std)?
+            .sum(1)?;
+        let stick_entropy = self.log_std.affine(1.0, 0.5 + half_log_two_pi)?.sum_all()?;
+
+        // Buttons: independent Bernoullis.
+        let pressed = means
+            .narrow(1, STICK_VALUES, button_count)?
+            .clamp(PROBABILITY_EPSILON, 1.0 - PROBABILITY_EPSILON)?;
+        let released = pressed.affine(-1.0, 1.0)?;
+        let buttons = actions.narrow(1, STICK_VALUES, button_count)?;
+        let button_log_probs = buttons
+            .mul(&pressed.log()?)?
+            .add(&buttons.affine(-1.0, 1.0)?.mul(&released.log()?)?)?
+            .sum(1)?;
+        let button_entropy = pressed
+            .mul(&pressed.log()?)?
+            .add(&released.mul(&released.log()?)?)?
+            .sum(1)?
+            .neg()?;
+
+        Ok((
+            stick_log_probs.add(&button_log_probs)?,
+            button_entropy.broadcast_add(&stick_entropy)?,
+            values,
+        ))
+    }
+
+    /// One optimizer step on `loss`, with the gradients scaled down to a global norm of at
+    /// most `max_grad_norm` (0 leaves them alone). Returns the norm before clipping.
+    pub fn step(&mut self, loss: &Tensor, max_grad_norm: f32) -> Result<f32> {
+        let mut grads = loss.backward()?;
+        let vars = self.varmap.all_vars();
+        let mut squared_norm = 0.0;
+        for var in &vars {
+            if let Some(grad) = grads.get(var) {
+                squared_norm += grad.sqr()?.sum_all()?.to_scalar::<f32>()?;
+            }
+        }
+
+        let norm = squared_norm.sqrt();
+        if max_grad_norm > 0.0 && norm > max_grad_norm {
+            let scale = (max_grad_norm / norm) as f64;
+            for var in &vars {
+                if let Some(grad) = grads.remove(var) {
+                    grads.insert(var, grad.affine(scale, 0.0)?);
+                }
+            }
+        }
+        self.optimizer.step(&grads)?;
+        Ok(norm)
+    }
+
+    /// Trains on an experience buffer for `epochs` full-batch steps: the policy head
+    /// imitates the buffer's actions and the value head learns its discounted returns.
+    /// Returns the last loss.
+    pub fn fit_experiences(
+        &mut self,
+        experiences: &[Experience],
+        gamma: f32,
+        epochs: usize,
+    ) -> Result<f32> {
+        if experiences.is_empty() {
+            return Ok(0.0);
+        }
+
+        let batch = ExperienceBatch::new(experiences, gamma, &self.device)?;
+        let mut last_loss = 0.0;
+        for _ in 0..epochs {
+            let (actions, values) = self.forward(&batch.states)?;
+            let loss = candle_nn::loss::mse(&actions, &batch.actions)?
+                .add(&candle_nn::loss::mse(&values, &batch.returns)?)?;
+            self.optimizer.backward_step(&loss)?;
+            last_loss = loss.to_scalar::<f32>()?;
+        }
+        Ok(last_loss)
+    }
+
+    /// Export as a policy the server can run for live bots: the trunk and the move stick
+    /// of the policy head, turned into a direction on the floor the way `ExitEnvironment`
+    /// moves the agent. Only networks reading `bot_policy::OBSERVATION_SIZE` values
+    /// qualify.
+    pub fn to_bot_policy(&self) -> std::result::Result<BotPolicy, String> {
+        let values = |tensor: &Tensor| {
+            tensor
+                .flatten_all()
+                .and_then(|values| values.to_vec1::<f32>())
+                .map_err(|e| e.to_string())
+        };
+        let layer_values = |layer: &Linear| {
+            let bias = layer
+                .bias()
+                .ok_or_else(|| "policy network layer without a bias".to_string())?;
+            Ok::<_, String>((values(layer.weight())?, values(bias)?))
+        };
+
+        let mut layers = self
+            .trunk
+            .iter()
+            .map(&layer_values)
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        // The move stick is the head's first two outputs. Pushing it up walks toward -z.
+        let width = self
+            .config
+            .hidden_layers
+            .last()
+            .copied()
+            .unwrap_or(self.config.input_size);
+        let (mut weights, mut bias) = layer_values(&self.policy_head)?;
+        weights.truncate(bot_policy::ACTION_SIZE * width);
+        bias.truncate(bot_policy::ACTION_SIZE);
+        weights[width..]
+            .iter_mut()
+            .for_each(|weight| *weight = -*weight);
+        bias[1] = -bias[1];
+        layers.push((weights, bias));
+
+        let sizes: Vec<usize> = std::iter::once(self.config.input_size)
+            .chain(self.config.hidden_layers.iter().copied())
+            .chain(std::iter::once(bot_policy::ACTION_SIZE))
+            .collect();
+        Ok(BotPolicy::from_layers(&sizes, layers)?.with_tanh_output())
+    }
+
+    /// Writes the weights and layer sizes to a safetensors file.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut tensors: HashMap<String, Tensor> = match self.varmap.data().lock() {
+            Ok(vars) => vars
+                .iter()
+                .map(|(name, var)| (name.clone(), var.as_tensor().clone()))
+                .collect(),
+            Err(_) => bail!("the weights of the policy network are poisoned"),
+        };
+        let layers: Vec<u32> = std::iter::once(self.config.input_size)
+            .chain(self.config.hidden_layers.iter().copied())
+            .chain(std::iter::once(ACTION_VALUES))
+            .map(|size| size as u32)
+            .collect();
+        tensors.insert(
+            LAYERS_KEY.to_string(),
+            Tensor::new(layers.as_slice(), &Device::Cpu)?,
+        );
+
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+        candle_core::safetensors::save(&tensors, path)
+    }
+
+    /// Loads a checkpoint written by [`save`](Self::save). Its layer sizes replace those of
+    /// `config`; the device and learning rate are kept.
+    pub fn load(path: &Path, config: &PolicyNetworkConfig) -> Result<Self> {
+        let tensors = candle_core::safetensors::load(path, &Device::Cpu)?;
+        let Some(layers) = tensors.get(LAYERS_KEY) else {
+            bail!("{} has no {}", path.display(), LAYERS_KEY);
+        };
+        let layers = layers.to_vec1::<u32>()?;
+        let [input_size, hidden_layers @ .., output_size] = layers.as_slice() else {
+            bail!("{} has {} layer sizes", path.display(), layers.len());
+        };
+        if *output_size as usize != ACTION_VALUES {
+            bail!(
+                "{} outputs {} action values, expected {}",
+                path.display(),
+                output_size,
+                ACTION_VALUES
+            );
+        }
+
+        let mut network = Self::new(PolicyNetworkConfig {
+            input_size: *input_size as usize,
+            hidden_layers: hidden_layers.iter().map(|size| *size as usize).collect(),
+            ..config.clone()
+        })?;
+        network.varmap.load(path)?;
+        Ok(network)
+    }
+}
+
+/// An experience buffer as tensors.
+pub struct ExperienceBatch {
+    /// `[n, state]`
+    pub states: Tensor,
+    /// `[n, ACTION_VALUES]`
+    pub actions: Tensor,
+    /// `[n]`
+    pub rewards: Tensor,
+    /// `[n, state]`
+    pub next_states: Tensor,
+    /// `[n]`, 1 where the episode ended.
+    pub dones: Tensor,
+    /// `[n]`, see [`discounted_returns`].
+    pub returns: Tensor,
+}
+
+impl ExperienceBatch {
+    /// Experiences must be in the order they were collected, as in
+    /// `RLTrainingState::experience_buffer`.
+    pub fn new(experiences: &[Experience], gamma: f32, device: &Device) -> Result<Self> {
+        let count = experiences.len();
+        let state_size = experiences.first().map_or(0, |first| first.state.len());
+        let rows =
+            |values: Vec<f32>, width: usize| Tensor::from_vec(values, (count, width), device);
+
+        Ok(Self {
+            states: rows(
+                experiences.iter().flat_map(|e| e.state.clone()).collect(),
+                state_size,
+            )?,
+            actions: rows(
+                experiences
+                    .iter()
+                    .flat_map(|e| e.action.to_vector())
+                    .collect(),
+                ACTION_VALUES,
+            )?,
+            rewards: Tensor::from_vec(
+                experiences.iter().map(|e| e.reward).collect::<Vec<_>>(),
+                count,
+                device,
+            )?,
+            next_states: rows(
+                experiences
+                    .iter()
+                    .flat_map(|e| e.next_state.clone())
+                    .collect(),
+                state_size,
+            )?,
+            dones: Tensor::from_vec(
+                experiences
+                    .iter()
+                    .map(|e| if e.done { 1.0f32 } else { 0.0 })
+                    .collect::<Vec<_>>(),
+                count,
+                device,
+            )?,
+            returns: Tensor::from_vec(discounted_returns(experiences, gamma), count, device)?,
+        })
+    }
+}
+
+/// Draw from the standard normal distribution, by the Box-Muller transform.
+fn standard_normal(rng: &mut impl Rng) -> f32 {
+    // 1 - u keeps the logarithm away from 0.
+    let radius = (-2.0 * (1.0 - rng.random::<f32>()).ln()).sqrt();
+    radius * (2.0 * PI * rng.random::<f32>()).cos()
+}
+
+/// Return from each step to the end of its episode, discounted by `gamma` per step. The
+/// last, unfinished episode of a buffer is cut short where the buffer ends.
+pub fn discounted_returns(experiences: &[Experience], gamma: f32) -> Vec<f32> {
+    let mut returns = vec![0.0; experiences.len()];
+    let mut running = 0.0;
+    for (index, experience) in experiences.iter().enumerate().rev() {
+        if experience.done {
+            running = 0.0;
+        }
+        running = experience.reward + gamma * running;
+        returns[index] = running;
+    }
+    returns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PolicyNetworkConfig, PolicyValueNetwork, discounted_returns};
+    use crate::reinforcement_learning::{Experience, PlayerActionSet};
+    use bevy::prelude::Vec2;
+    use shared::bot_policy::{BotPolicy, OBSERVATION_SIZE};
+
+    fn experience(reward: f32, done: bool) -> Experience {
+        Experience {
+            state: vec![0.1, -0.2, 0.3, 0.0, 0.0, 0.0, 1.0, 1.0],
+            action: PlayerActionSet {
+                movement: Vec2::new(0.5, -0.5),
+                look: Vec2::ZERO,
+                jump: false,
+                shoot: true,
+            },
+            reward,
+            next_state: vec![0.2, -0.2, 0.3, 0.0, 0.0, 0.0, 1.0, 1.0],
+            done,
+        }
+    }
+
+    #[test]
+    fn returns_are_discounted_within_episodes() {
+        let buffer = [
+            experience(1.0, false),
+            experience(1.0, true),
+            experience(2.0, false),
+        ];
+        assert_eq!(discounted_returns(&buffer, 0.5), [1.5, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn networks_learn_from_old_buffers_and_survive_checkpoints() {
+        let config = PolicyNetworkConfig::from_ron(
+            "// small\n(hidden_layers: [16, 8], device: Cpu, learning_rate: 0.01)",
+        )
+        .unwrap();
+        assert_eq!(config.hidden_layers, [16, 8]);
+        assert!(PolicyNetworkConfig::from_ron("(hidden_layers: [16, 0])").is_err());
+        assert!(PolicyNetworkConfig::from_ron("(device: Tpu)").is_err());
+        assert!(PolicyNetworkConfig::from_ron("(input_size: 4)").is_err());
+
+        let mut network = PolicyValueNetwork::new(config.clone()).unwrap();
+        let buffer: Vec<_> = (0..32).map(|step| experience(1.0, step % 8 == 7)).collect();
+        let first_loss = network.fit_experiences(&buffer, 0.9, 1).unwrap();
+        let last_loss = network.fit_experiences(&buffer, 0.9, 200).unwrap();
+        assert!(last_loss < first_loss, "{} !< {}", last_loss, first_loss);
+        let (action, _) = network.act(&buffer[0].state).unwrap();
+        assert!(action.shoot);
+
+        let path = std::env::temp_dir().join(format!(
+            "policy_network_test_{}.safetensors",
+            std::process::id()
+        ));
+        network.save(&path).unwrap();
+        let restored = PolicyValueNetwork::load(&path, &PolicyNetworkConfig::default()).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(restored.config().hidden_layers, [16, 8]);
+        assert_eq!(
+            restored.act(&buffer[0].state).unwrap().1,
+            network.act(&buffer[0].state).unwrap().1
+        );
+    }
+
+    #[test]
+    fn exported_bot_policies_walk_where_the_move_stick_points() {
+        let network = PolicyValueNetwork::new(PolicyNetworkConfig {
+            input_size: OBSERVATION_SIZE,
+            hidden_layers: vec![8, 4],
+            ..Default::default()
+        })
+        .unwrap();
+        let policy = network.to_bot_policy().unwrap();
+        let restored = BotPolicy::from_checkpoint(&policy.to_checkpoint()).unwrap();
+
+        let state = [0.3, -0.5, 1.0, 0.8, 0.0, 1.0];
+        let (action, _) = network.act(&state).unwrap();
+        let [x, z] = restored.forward(&state);
+        assert!((x - action.movement.x).abs() < 1e-5);
+        assert!((z + action.movement.y).abs() < 1e-5);
+
+        // The default network reads the 8 values of `RLTrainingState`, not a bot's.
+        let network = PolicyValueNetwork::new(PolicyNetworkConfig::default()).unwrap();
+        assert!(network.to_bot_policy().is_err());
+    }
+}
@@ -0,0 +1,382 @@
+//! Proximal policy optimization for [`PolicyValueNetwork`].
+//!
+//! Each iteration the trainer plays `rollout_steps` steps with the stochastic policy,
+//! estimates advantages with generalized advantage estimation, then runs `epochs` passes of
+//! shuffled minibatches over the rollout. Each minibatch minimizes the clipped surrogate
+//! objective, plus the value loss and minus an entropy bonus, with gradients clipped to a
+//! global norm.
+//!
+//! Hyperparameters come from a RON training config, like the network's:
+//!
+//! ```text
+//! (
+//!     gamma: 0.99,
+//!     gae_lambda: 0.95,
+//!     clip_epsilon: 0.2,
+//!     entropy_coef: 0.01,
+//!     minibatch_size: 64,
+//! )
+//! ```
+
+use candle_core::{Result, Tensor};
+use rand::Rng;
+use rand::seq::SliceRandom;
+use serde::Deserialize;
+
+use crate::environment::TrainingEnvironment;
+use crate::policy_network::{ACTION_VALUES, PolicyValueNetwork};
+
+/// Keeps advantage normalization finite when all advantages are equal.
+const ADVANTAGE_EPSILON: f32 = 1e-8;
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PpoConfig {
+    /// Discount per step.
+    pub gamma: f32,
+    /// Trades bias for variance in advantage estimates; 1 is plain Monte Carlo.
+    pub gae_lambda: f32,
+    /// How far the probability ratio of an action may move in one update.
+    pub clip_epsilon: f32,
+    pub entropy_coef: f32,
+    pub value_coef: f32,
+    /// Global gradient norm to clip to; 0 turns clipping off.
+    pub max_grad_norm: f32,
+    /// Passes over each rollout.
+    pub epochs: usize,
+    pub minibatch_size: usize,
+    /// Steps collected between two updates.
+    pub rollout_steps: usize,
+    /// Rollout and update rounds of a training run.
+    pub iterations: usize,
+}
+
+impl Default for PpoConfig {
+    fn default() -> Self {
+        Self {
+            gamma: 0.99,
+            gae_lambda: 0.95,
+            clip_epsilon: 0.2,
+            entropy_coef: 0.01,
+            value_coef: 0.5,
+            max_grad_norm: 0.5,
+            epochs: 4,
+            minibatch_size: 64,
+            rollout_steps: 2048,
+            iterations: 200,
+        }
+    }
+}
+
+impl PpoConfig {
+    /// Reads a training config over the defaults. Unknown settings and bad values are
+    /// errors.
+    pub fn from_ron(text: &str) -> std::result::Result<Self, String> {
+        let config: Self = ron::from_str(text).map_err(|e| e.to_string())?;
+        let fractions = [
+            ("gamma", config.gamma),
+            ("gae_lambda", config.gae_lambda),
+            ("clip_epsilon", config.clip_epsilon),
+        ];
+        let non_negative = [
+            ("entropy_coef", config.entropy_coef),
+            ("value_coef", config.value_coef),
+            ("max_grad_norm", config.max_grad_norm),
+        ];
+        let counts = [
+            ("epochs", config.epochs),
+            ("minibatch_size", config.minibatch_size),
+            ("rollout_steps", config.rollout_steps),
+            ("iterations", config.iterations),
+        ];
+        let bad_fraction = fractions
+            .into_iter()
+            .find(|(_, value)| !(0.0..=1.0).contains(value));
+        let bad_number = non_negative
+            .into_iter()
+            .find(|(_, value)| !(*value >= 0.0 && value.is_finite()));
+        if let Some((name, value)) = bad_fraction.or(bad_number) {
+            return Err(format!("bad value {} for {}", value, name));
+        }
+        if let Some((name, _)) = counts.into_iter().find(|(_, count)| *count == 0) {
+            return Err(format!("{} must be at least 1", name));
+        }
+        Ok(config)
+    }
+}
+
+/// Steps played with the policy, in order.
+#[derive(Clone, Debug, Default)]
+pub struct Rollout {
+    pub states: Vec<Vec<f32>>,
+    /// Drawn action values, see [`SampledAction`](crate::policy_network::SampledAction).
+    pub actions: Vec<Vec<f32>>,
+    pub log_probs: Vec<f32>,
+    pub values: Vec<f32>,
+    pub rewards: Vec<f32>,
+    pub dones: Vec<bool>,
+    /// Estimated value of the state after the last step, for an unfinished episode.
+    pub last_value: f32,
+    /// Total reward of each episode that ended during the rollout.
+    pub episode_rewards: Vec<f32>,
+    /// Steps of each episode that ended during the rollout.
+    pub episode_lengths: Vec<usize>,
+}
+
+impl Rollout {
+    pub fn len(&self) -> usize {
+        self.rewards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rewards.is_empty()
+    }
+}
+
+/// Generalized advantage estimates of each step of a rollout, and the returns the value
+/// head is trained on (advantage plus value).
+pub fn generalized_advantages(
+    rewards: &[f32],
+    values: &[f32],
+    dones: &[bool],
+    last_value: f32,
+    gamma: f32,
+    lambda: f32,
+) -> (Vec<f32>, Vec<f32>) {
+    let mut advantages = vec![0.0; rewards.len()];
+    let mut running = 0.0;
+    for step in (0..rewards.len()).rev() {
+        let next_value = values.get(step + 1).copied().unwrap_or(last_value);
+        let continues = if dones[step] { 0.0 } else { 1.0 };
+        let delta = rewards[step] + gamma * next_value * continues - values[step];
+        running = delta + gamma * lambda * continues * running;
+        advantages[step] = running;
+    }
+    let returns = advantages
+        .iter()
+        .zip(values)
+        .map(|(advantage, value)| advantage + value)
+        .collect();
+    (advantages, returns)
+}
+
+/// Losses of one update, averaged over its minibatches.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PpoStats {
+    pub policy_loss: f32,
+    pub value_loss: f32,
+    pub entropy: f32,
+    /// Gradient norm before clipping.
+    pub grad_norm: f32,
+    /// Share of samples whose probability ratio was clipped.
+    pub clip_fraction: f32,
+}
+
+pub struct PpoTrainer {
+    pub network: PolicyValueNetwork,
+    pub config: PpoConfig,
+    /// State the next rollout starts from, mid-episode when the last rollout cut one short.
+    state: Option<Vec<f32>>,
+    episode_reward: f32,
+    episode_steps: usize,
+}
+
+impl PpoTrainer {
+    pub fn new(network: PolicyValueNetwork, config: PpoConfig) -> Self {
+        Self {
+            network,
+            config,
+            state: None,
+            episode_reward: 0.0,
+            episode_steps: 0,
+        }
+    }
+
+    /// Plays `rollout_steps` steps in `environment`, resetting it whenever an episode ends.
+    /// Episodes carry over from one rollout to the next.
+    pub fn collect<E: TrainingEnvironment>(
+        &mut self,
+        environment: &mut E,
+        rng: &mut impl Rng,
+    ) -> Result<Rollout> {
+        let mut rollout = Rollout::default();
+        let mut state = match self.state.take() {
+            Some(state) => state,
+            None => environment.reset(rng),
+        };
+
+        for _ in 0..self.config.rollout_steps {
+            let sampled = self.network.sample(&state, rng)?;
+            let step = environment.step(&sampled.action());
+            self.episode_reward += step.reward;
+            self.episode_steps += 1;
+
+            rollout.states.push(state);
+            rollout.actions.push(sampled.values);
+            rollout.log_probs.push(sampled.log_prob);
+            rollout.values.push(sampled.value);
+            rollout.rewards.push(step.reward);
+            rollout.dones.push(step.done);
+
+            state = if step.done {
+                rollout.episode_rewards.push(self.episode_reward);
+                rollout.episode_lengths.push(self.episode_steps);
+                self.episode_reward = 0.0;
+                self.episode_steps = 0;
+                environment.reset(rng)
+            } else {
+                step.state
+            };
+        }
+
+        rollout.last_value = self.network.sample(&state, rng)?.value;
+        self.state = Some(state);
+        Ok(rollout)
+    }
+
+    /// Runs `epochs` passes of shuffled minibatches over `rollout`.
+    pub fn update(&mut self, rollout: &Rollout, rng: &mut impl Rng) -> Result<PpoStats> {
+        if rollout.is_empty() {
+            return Ok(PpoStats::default());
+        }
+
+        let (mut advantages, returns) = generalized_advantages(
+            &rollout.rewards,
+            &rollout.values,
+            &rollout.dones,
+            rollout.last_value,
+            self.config.gamma,
+            self.config.gae_lambda,
+        );
+        normalize(&mut advantages);
+
+        let device = self.network.device().clone();
+        let count = rollout.len();
+        let state_size = rollout.states[0].len();
+        let states = Tensor::from_vec(rollout.states.concat(), (count, state_size), &device)?;
+        let actions = Tensor::from_vec(rollout.actions.concat(), (count, ACTION_VALUES), &device)?;
+        let old_log_probs = Tensor::from_slice(&rollout.log_probs, count, &device)?;
+        let advantages = Tensor::from_vec(advantages, count, &device)?;
+        let returns = Tensor::from_vec(returns, count, &device)?;
+
+        let clip = self.config.clip_epsilon;
+        let mut indices: Vec<u32> = (0..count as u32).collect();
+        let mut totals = PpoStats::default();
+        let mut minibatches = 0;
+        for _ in 0..self.config.epochs {
+            indices.shuffle(rng);
+            for minibatch in indices.chunks(self.config.minibatch_size.max(1)) {
+                let rows = Tensor::from_slice(minibatch, minibatch.len(), &device)?;
+                let (log_probs, entropy, values) = self.network.evaluate(
+                    &states.index_select(&rows, 0)?,
+                    &actions.index_select(&rows, 0)?,
+                )?;
+                let advantages = advantages.index_select(&rows, 0)?;
+
+                let ratios = log_probs
+                    .sub(&old_log_probs.index_select(&rows, 0)?)?
+                    .exp()?;
+                let clipped = ratios.clamp(1.0 - clip, 1.0 + clip)?;
+                let policy_loss = ratios
+                    .mul(&advantages)?
+                    .minimum(&clipped.mul(&advantages)?)?
+                    .mean_all()?
+                    .neg()?;
+                let value_loss = candle_nn::loss::mse(&values, &returns.index_select(&rows, 0)?)?;
+                let entropy = entropy.mean_all()?;
+                let loss = policy_loss
+                    .add(&value_loss.affine(self.config.value_coef as f64, 0.0)?)?
+                    .sub(&entropy.affine(self.config.entropy_coef as f64, 0.0)?)?;
+
+                let clip_fraction = ratios
+                    .sub(&clipped)?
+                    .abs()?
+                    .gt(0.0f32)?
+                    .to_dtype(candle_core::DType::F32)?
+                    .mean_all()?;
+                totals.grad_norm += self.network.step(&loss, self.config.max_grad_norm)?;
+                totals.policy_loss += policy_loss.to_scalar::<f32>()?;
+                totals.value_loss += value_loss.to_scalar::<f32>()?;
+                totals.entropy += entropy.to_scalar::<f32>()?;
+                totals.clip_fraction += clip_fraction.to_scalar::<f32>()?;
+                minibatches += 1;
+            }
+        }
+
+        let minibatches = minibatches as f32;
+        Ok(PpoStats {
+            policy_loss: totals.policy_loss / minibatches,
+            value_loss: totals.value_loss / minibatches,
+            entropy: totals.entropy / minibatches,
+            grad_norm: totals.grad_norm / minibatches,
+            clip_fraction: totals.clip_fraction / minibatches,
+        })
+    }
+}
+
+/// Shifts and scales `values` to a mean of 0 and a standard deviation of 1.
+fn normalize(values: &mut [f32]) {
+    let count = values.len() as f32;
+    let mean = values.iter().sum::<f32>() / count;
+    let variance = values
+        .iter()
+        .map(|value| (value - mean).powi(2))
+        .sum::<f32>()
+        / count;
+    let std = variance.sqrt() + ADVANTAGE_EPSILON;
+    for value in values {
+        *value = (*value - mean) / std;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PpoConfig, PpoTrainer, generalized_advantages};
+    use crate::environment::{ExitEnvironment, TrainingEnvironment};
+    use crate::policy_network::{PolicyNetworkConfig, PolicyValueNetwork};
+
+    #[test]
+    fn advantages_bootstrap_until_the_episode_ends() {
+        let rewards = [1.0, 1.0, 1.0];
+        let values = [0.5, 0.5, 0.5];
+        let dones = [false, true, false];
+
+        // With lambda 1 advantages are discounted returns minus values; the last step is
+        // bootstrapped from the value after the rollout.
+        let (advantages, returns) =
+            generalized_advantages(&rewards, &values, &dones, 2.0, 0.5, 1.0);
+        assert_eq!(returns, [1.5, 1.0, 2.0]);
+        assert_eq!(advantages, [1.0, 0.5, 1.5]);
+
+        // With lambda 0 each advantage is the one-step temporal difference.
+        let (advantages, _) = generalized_advantages(&rewards, &values, &dones, 2.0, 0.5, 0.0);
+        assert_eq!(advantages, [0.75, 0.5, 1.5]);
+    }
+
+    #[test]
+    fn updates_run_on_collected_rollouts() {
+        let config =
+            PpoConfig::from_ron("(rollout_steps: 64, minibatch_size: 16, epochs: 2)").unwrap();
+        assert!(PpoConfig::from_ron("(gamma: 1.5)").is_err());
+        assert!(PpoConfig::from_ron("(epochs: 0)").is_err());
+        assert!(PpoConfig::from_ron("(minibatch: 16)").is_err());
+
+        let mut environment = ExitEnvironment::default();
+        let network = PolicyValueNetwork::new(PolicyNetworkConfig {
+            input_size: environment.state_size(),
+            hidden_layers: vec![16],
+            ..PolicyNetworkConfig::default()
+        })
+        .unwrap();
+        let mut trainer = PpoTrainer::new(network, config);
+        let mut rng = rand::rng();
+
+        let rollout = trainer.collect(&mut environment, &mut rng).unwrap();
+        assert_eq!(rollout.len(), 64);
+        let stats = trainer.update(&rollout, &mut rng).unwrap();
+        assert!(stats.policy_loss.is_finite() && stats.value_loss.is_finite());
+        assert!(stats.entropy.is_finite());
+        // The first epoch starts from the policy that collected the rollout.
+        assert!(stats.clip_fraction < 1.0);
+    }
+}
@@ -3,17 +3,40 @@ use bevy::prelude::*;
 use leafwing_input_manager::prelude::ActionState;
 use nalgebra::{DMatrix, DVector};
 use rand::{Rng, rng};
-use shared::input::PlayerAction;
+use shared::bot_policy::BotPolicy;
+use shared::clock::GameClock;
+use shared::components::health::{DamageEvent, DeathEvent, Health};
+use shared::inputs::input::PlayerAction;
+use shared::level::transition::LevelExit;
 use std::collections::VecDeque;
+use std::time::Instant;
+
+use crate::metrics::{MetricsSettings, MetricsWriter};
+use crate::policy_network::{PolicyNetworkConfig, PolicyNetworkSettings, PolicyValueNetwork};
+use crate::rewards::{RewardBreakdown, RewardRegistry, RewardSettings, RewardStep};
+use crate::self_play::{CheckpointPool, SelfPlayOpponent};
 
 pub struct RLPlugin;
 
 impl Plugin for RLPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(RLTrainingState::default()).add_systems(
-            FixedUpdate,
-            (collect_rl_observations, train_rl_agent, apply_rl_actions).chain(),
-        );
+        app.insert_resource(RLTrainingState::default())
+            .init_resource::<RewardSettings>()
+            .init_resource::<PolicyNetworkSettings>()
+            .init_resource::<MetricsSettings>()
+            .add_message::<EpisodeFinished>()
+            .add_systems(
+                Startup,
+                (
+                    load_reward_weights,
+                    load_policy_network,
+                    open_metrics_writer,
+                ),
+            )
+            .add_systems(
+                FixedUpdate,
+                (collect_rl_observations, train_rl_agent, apply_rl_actions).chain(),
+            );
     }
 }
 
@@ -67,6 +90,19 @@ impl SimpleNetwork {
     pub fn q_values(&self, state: &DVector<f32>) -> DVector<f32> {
         self.forward(state)
     }
+
+    /// Export as a policy the server can run for live bots (see `launcher --bot-policy`).
+    /// Only networks mapping `OBSERVATION_SIZE` inputs to `ACTION_SIZE` outputs qualify.
+    pub fn to_bot_policy(&self) -> Result<BotPolicy, String> {
+        // nalgebra stores matrices column-major; the policy expects row-major weights.
+        BotPolicy::new(
+            self.bias1.len(),
+            self.weights1.transpose().as_slice().to_vec(),
+            self.bias1.as_slice().to_vec(),
+            self.weights2.transpose().as_slice().to_vec(),
+            self.bias2.as_slice().to_vec(),
+        )
+    }
 }
 
 /// Experience replay buffer entry
@@ -83,6 +119,8 @@ pub struct Experience {
 #[derive(Resource)]
 pub struct RLTrainingState {
     pub q_network: Option<SimpleNetwork>,
+    /// Candle network acting in place of `q_network` when set.
+    pub policy: Option<PolicyValueNetwork>,
     pub target_network: Option<SimpleNetwork>,
     pub experience_buffer: VecDeque<Experience>,
     pub epsilon: f32,
@@ -92,6 +130,33 @@ pub struct RLTrainingState {
     pub buffer_size: usize,
     pub batch_size: usize,
     pub episode_count: usize,
+    pub rewards: RewardRegistry,
+    /// Reward of the current episode so far, term by term.
+    pub episode_rewards: RewardBreakdown,
+    /// The bot being trained, once seen.
+    pub agent: Option<Entity>,
+    /// Game time since the current episode started.
+    pub episode_secs: f32,
+    /// Episodes the agent survives end after this long.
+    pub max_episode_secs: f32,
+    /// Frozen policies for self-play, see [`crate::self_play`].
+    pub self_play: CheckpointPool,
+    /// Steps taken in the current episode.
+    pub episode_steps: usize,
+    /// Wall-clock start of the current episode, for the training speed.
+    pub episode_started: Instant,
+    /// Latest loss components of whatever trains the policy, logged with each episode
+    /// under `loss/`.
+    pub losses: Vec<(&'static str, f32)>,
+    /// Set when [`MetricsSettings`] has a run directory.
+    pub metrics: Option<MetricsWriter>,
+}
+
+/// Sent when an episode ends, by death or by running out of time.
+#[derive(Message, Clone, Debug)]
+pub struct EpisodeFinished {
+    pub episode: usize,
+    pub reward: f32,
 }
 
 /// Minimal RL observation for a single bot
@@ -101,6 +166,8 @@ pub struct RLObservation {
     pub velocity: Vec3,
     pub health: f32,
     pub max_health: f32,
+    /// Distance to the level exit, when the level has one.
+    pub distance_to_objective: Option<f32>,
 }
 
 /// Player action representation for RL
@@ -171,6 +238,7 @@ impl Default for RLTrainingState {
     fn default() -> Self {
         Self {
             q_network: None,
+            policy: None,
             target_network: None,
             experience_buffer: VecDeque::new(),
             epsilon: 1.0,
@@ -180,6 +248,16 @@ impl Default for RLTrainingState {
             buffer_size: 10000,
             batch_size: 32,
             episode_count: 0,
+            rewards: RewardRegistry::default(),
+            episode_rewards: RewardBreakdown::default(),
+            agent: None,
+            episode_secs: 0.0,
+            max_episode_secs: 60.0,
+            self_play: CheckpointPool::default(),
+            episode_steps: 0,
+            episode_started: Instant::now(),
+            losses: Vec::new(),
+            metrics: None,
         }
     }
 }
@@ -224,54 +302,6 @@ impl RLTrainingState {
         PlayerActionSet::from_vector(data)
     }
 
-    /// Calculate reward based on game state and actions
-    pub fn calculate_reward(
-        &self,
-        current_obs: &RLObservation,
-        previous_obs: Option<&RLObservation>,
-        actions: &PlayerActionSet,
-    ) -> f32 {
-        let mut reward = 0.0;
-
-        if let Some(prev_obs) = previous_obs {
-            // Health-based rewards
-            let health_diff = current_obs.health - prev_obs.health;
-            reward += health_diff * 0.5;
-
-            // Death penalty
-            if current_obs.health <= 0.0 {
-                reward -= 50.0;
-                return reward;
-            }
-
-            // Survival bonus
-            reward += 0.1;
-
-            // Movement rewards
-            let movement_magnitude = current_obs.velocity.length();
-            if movement_magnitude > 0.1 {
-                reward += 0.05;
-            }
-
-            // Boundary penalty
-            let distance_from_center = current_obs.position.length();
-            if distance_from_center > 15.0 {
-                reward -= 0.1 * (distance_from_center - 15.0);
-            }
-
-            // Action efficiency
-            let action_intensity = actions.movement.length()
-                + actions.look.length()
-                + if actions.jump { 1.0 } else { 0.0 }
-                + if actions.shoot { 1.0 } else { 0.0 };
-            if action_intensity > 4.0 {
-                reward -= 0.01 * (action_intensity - 4.0);
-            }
-        }
-
-        reward
-    }
-
     /// Add experience to replay buffer
     pub fn add_experience(&mut self, experience: Experience) {
         self.experience_buffer.push_back(experience);
@@ -294,6 +324,15 @@ impl RLTrainingState {
                 shoot: rng.random::<f32>() < 0.2,
             }
         } else {
+            if let Some(action) = self
+                .policy
+                .as_ref()
+                .and_then(|policy| policy.act(state).ok())
+                .map(|(action, _value)| action)
+            {
+                return action;
+            }
+
             // Use Q-network to predict action
             if let Some(network) = &self.q_network {
                 let input = DVector::from_vec(state.to_vec());
@@ -311,6 +350,49 @@ impl RLTrainingState {
         }
     }
 
+    /// Writes the metrics of the episode that just ended to the run directory, if any:
+    /// reward in total and by term, length, losses, epsilon and steps per wall-clock second.
+    pub fn write_episode_metrics(&mut self) {
+        if self.metrics.is_none() {
+            return;
+        }
+
+        let wall_secs = self.episode_started.elapsed().as_secs_f32();
+        let tags: Vec<(String, f32)> = self
+            .episode_rewards
+            .terms
+            .iter()
+            .map(|(name, reward)| (format!("reward/{}", name), *reward))
+            .chain(
+                self.losses
+                    .iter()
+                    .map(|(name, loss)| (format!("loss/{}", name), *loss)),
+            )
+            .collect();
+        let mut scalars = vec![
+            ("episode/reward", self.episode_rewards.total()),
+            ("episode/length", self.episode_steps as f32),
+            ("episode/game_secs", self.episode_secs),
+            ("train/epsilon", self.epsilon),
+            (
+                "perf/fps",
+                self.episode_steps as f32 / wall_secs.max(f32::EPSILON),
+            ),
+        ];
+        scalars.extend(tags.iter().map(|(tag, value)| (tag.as_str(), *value)));
+
+        let step = self.episode_count as u64;
+        if let Some(metrics) = &mut self.metrics
+            && let Err(e) = metrics.write_scalars(step, &scalars)
+        {
+            error!(
+                "Failed to write metrics to {}: {}",
+                metrics.path().display(),
+                e
+            );
+        }
+    }
+
     /// Lightweight online training step with epsilon decay and periodic target refresh
     pub fn train_step(&mut self) {
         if self.experience_buffer.len() < self.batch_size {
@@ -368,10 +450,95 @@ impl RLTrainingState {
     }
 }
 
+fn load_reward_weights(settings: Res<RewardSettings>, mut rl_state: ResMut<RLTrainingState>) {
+    let Some(path) = &settings.config else {
+        return;
+    };
+
+    let applied = std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| rl_state.rewards.apply_weights(&text));
+    match applied {
+        Ok(()) => info!("Loaded reward weights from {}", path.display()),
+        Err(e) => error!(
+            "Failed to load reward weights from {}: {}",
+            path.display(),
+            e
+        ),
+    }
+}
+
+fn load_policy_network(
+    settings: Res<PolicyNetworkSettings>,
+    mut rl_state: ResMut<RLTrainingState>,
+) {
+    let config = match &settings.config {
+        Some(path) => match std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| PolicyNetworkConfig::from_ron(&text))
+        {
+            Ok(config) => config,
+            Err(e) => {
+                error!(
+                    "Failed to load the policy network config from {}: {}",
+                    path.display(),
+                    e
+                );
+                return;
+            }
+        },
+        None if settings.checkpoint.is_some() => PolicyNetworkConfig::default(),
+        None => return,
+    };
+
+    let network = match &settings.checkpoint {
+        Some(path) => PolicyValueNetwork::load(path, &config),
+        None => PolicyValueNetwork::new(config),
+    };
+    match network {
+        Ok(network) => {
+            info!(
+                "Policy network with hidden layers {:?} on {:?}",
+                network.config().hidden_layers,
+                network.device()
+            );
+            rl_state.policy = Some(network);
+        }
+        Err(e) => error!("Failed to set up the policy network: {}", e),
+    }
+}
+
+fn open_metrics_writer(settings: Res<MetricsSettings>, mut rl_state: ResMut<RLTrainingState>) {
+    let Some(run_dir) = &settings.run_dir else {
+        return;
+    };
+
+    match MetricsWriter::create(run_dir, settings.format) {
+        Ok(writer) => {
+            info!("Writing training metrics to {}", writer.path().display());
+            rl_state.metrics = Some(writer);
+        }
+        Err(e) => error!(
+            "Failed to start the metrics of {}: {}",
+            run_dir.display(),
+            e
+        ),
+    }
+}
+
 /// System to collect observations and train RL agents (minimal, single bot)
+#[allow(clippy::too_many_arguments)]
 fn collect_rl_observations(
-    bot_query: Query<(&Position, &LinearVelocity)>,
+    bot_query: Query<
+        (Entity, &Position, &LinearVelocity, Option<&Health>),
+        Without<SelfPlayOpponent>,
+    >,
+    exits: Query<&LevelExit>,
+    mut damage_events: MessageReader<DamageEvent>,
+    mut death_events: MessageReader<DeathEvent>,
+    clock: Res<GameClock>,
     mut rl_state: ResMut<RLTrainingState>,
+    mut episodes: MessageWriter<EpisodeFinished>,
 ) {
     // Initialize RL agent if not done
     if rl_state.q_network.is_none() {
@@ -379,38 +546,73 @@ fn collect_rl_observations(
     }
 
     // Assume only one bot/player for RL
-    if let Some((position, velocity)) = bot_query.iter().next() {
-        // For now, hardcode health
-        let observation = RLObservation {
-            position: position.0,
-            velocity: velocity.0,
-            health: 100.0,
-            max_health: 100.0,
+    let Some((agent, position, velocity, health)) = bot_query.iter().next() else {
+        return;
+    };
+    rl_state.agent = Some(agent);
+    let observation = RLObservation {
+        position: position.0,
+        velocity: velocity.0,
+        health: health.map_or(100.0, |health| health.current),
+        max_health: health.map_or(100.0, |health| health.max),
+        distance_to_objective: exits
+            .iter()
+            .next()
+            .map(|exit| exit.position.distance(position.0)),
+    };
+    let damage_dealt = damage_events
+        .read()
+        .filter(|event| event.source == Some(agent) && event.target != agent)
+        .map(|event| event.amount)
+        .sum();
+    let kills = death_events
+        .read()
+        .filter(|event| event.source == Some(agent) && event.target != agent)
+        .count() as u32;
+
+    // Score the step and store experience if we have previous data
+    if let (Some(prev_obs), Some(prev_action)) = (&rl_state.last_observation, &rl_state.last_action)
+    {
+        let breakdown = rl_state.rewards.evaluate(&RewardStep {
+            previous: prev_obs,
+            current: &observation,
+            action: prev_action,
+            damage_dealt,
+            kills,
+            dt: clock.delta_secs(),
+        });
+        let done = observation.health <= 0.0
+            || rl_state.episode_secs + clock.delta_secs() >= rl_state.max_episode_secs;
+        let experience = Experience {
+            state: rl_state.observation_to_state(prev_obs),
+            action: prev_action.clone(),
+            reward: breakdown.total(),
+            next_state: rl_state.observation_to_state(&observation),
+            done,
         };
-
-        // Calculate reward and store experience if we have previous data
-        if let (Some(prev_obs), Some(prev_action)) =
-            (&rl_state.last_observation, &rl_state.last_action)
-        {
-            let reward = rl_state.calculate_reward(&observation, Some(prev_obs), prev_action);
-            let experience = Experience {
-                state: rl_state.observation_to_state(prev_obs),
-                action: prev_action.clone(),
-                reward,
-                next_state: rl_state.observation_to_state(&observation),
-                done: observation.health <= 0.0,
-            };
-            rl_state.add_experience(experience);
-            if observation.health <= 0.0 {
-                rl_state.episode_count += 1;
-                debug!(
-                    "Episode {} ended with reward {:.2}",
-                    rl_state.episode_count, reward
-                );
-            }
+        rl_state.add_experience(experience);
+        rl_state.episode_rewards.accumulate(&breakdown);
+        rl_state.episode_secs += clock.delta_secs();
+        rl_state.episode_steps += 1;
+        if done {
+            rl_state.episode_count += 1;
+            info!(
+                "Episode {} ended with reward {}",
+                rl_state.episode_count,
+                rl_state.episode_rewards.summary()
+            );
+            rl_state.write_episode_metrics();
+            episodes.write(EpisodeFinished {
+                episode: rl_state.episode_count,
+                reward: rl_state.episode_rewards.total(),
+            });
+            rl_state.episode_rewards = RewardBreakdown::default();
+            rl_state.episode_secs = 0.0;
+            rl_state.episode_steps = 0;
+            rl_state.episode_started = Instant::now();
         }
-        rl_state.last_observation = Some(observation);
     }
+    rl_state.last_observation = Some(observation);
 }
 
 /// System to train the RL agent
@@ -420,7 +622,7 @@ fn train_rl_agent(mut rl_state: ResMut<RLTrainingState>) {
 
 /// System to apply RL agent actions to the single bot (minimal)
 fn apply_rl_actions(
-    mut bot_query: Query<&mut LinearVelocity>,
+    mut bot_query: Query<&mut LinearVelocity, Without<SelfPlayOpponent>>,
     mut rl_state: ResMut<RLTrainingState>,
 ) {
     if let Some(mut velocity) = bot_query.iter_mut().next() {
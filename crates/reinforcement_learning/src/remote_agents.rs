@@ -0,0 +1,385 @@
+//! TCP bridge letting processes outside the app (Python trainers, remote bots) drive
+//! agent bots in real time.
+//!
+//! Every fixed tick each [`RemoteAgent`] bot's observation is streamed to every connected
+//! peer, and peers send back actions for the agents they control. Both directions use the
+//! same frame, all little-endian:
+//!
+//! ```text
+//! kind: u32 | agent: u32 | len: u32 | len × f32
+//! ```
+//!
+//! Observation frames (`kind` 1) carry the buffer laid out by
+//! [`ObservationSchema`](crate::observation::ObservationSchema); action frames (`kind` 2)
+//! carry the six values of `PlayerActionSet::to_vector`.
+//!
+//! Each peer has a small queue of frames in each direction. A peer that falls behind misses
+//! observations instead of growing the server's memory; actions past the queue are dropped
+//! until the next tick drains it.
+
+use avian3d::prelude::{LinearVelocity, Position, Rotation, SpatialQueryPipeline};
+use bevy::prelude::{
+    App, Commands, Component, Entity, FixedPostUpdate, FixedPreUpdate, Plugin, Query, Res,
+    Resource, Startup, error, info,
+};
+use leafwing_input_manager::prelude::ActionState;
+use shared::components::health::Health;
+use shared::inputs::input::PlayerAction;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+
+use crate::observation::{LAST_ACTION_SIZE, get_external_agent_observation};
+use crate::reinforcement_learning::PlayerActionSet;
+
+pub const OBSERVATION_FRAME: u32 = 1;
+pub const ACTION_FRAME: u32 = 2;
+/// Frames longer than this are a protocol error and drop the connection.
+const MAX_FRAME_VALUES: u32 = 4096;
+/// Observation frames waiting to be written to one peer.
+const PEER_QUEUE_FRAMES: usize = 256;
+/// Actions waiting for the next tick, from all peers together.
+const ACTION_QUEUE_FRAMES: usize = 1024;
+
+/// Opt-in remote agent bridge. Disabled unless `addr` is set.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct RemoteAgentSettings {
+    pub addr: Option<SocketAddr>,
+}
+
+impl RemoteAgentSettings {
+    /// Accept agents from this machine on `port`.
+    pub fn on_port(port: u16) -> Self {
+        Self {
+            addr: Some(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port)),
+        }
+    }
+
+    /// Accept agents from any address instead, for trainers on other machines.
+    pub fn public(mut self) -> Self {
+        if let Some(addr) = &mut self.addr {
+            addr.set_ip(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+        }
+        self
+    }
+}
+
+/// A bot whose actions come from a remote peer, addressed by `id` in frames.
+#[derive(Component, Clone, Debug)]
+pub struct RemoteAgent {
+    pub id: u32,
+    pub last_action: Option<PlayerActionSet>,
+}
+
+impl RemoteAgent {
+    pub fn new(id: u32) -> Self {
+        Self {
+            id,
+            last_action: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    pub kind: u32,
+    pub agent: u32,
+    pub values: Vec<f32>,
+}
+
+impl Frame {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(12 + self.values.len() * 4);
+        bytes.extend_from_slice(&self.kind.to_le_bytes());
+        bytes.extend_from_slice(&self.agent.to_le_bytes());
+        bytes.extend_from_slice(&(self.values.len() as u32).to_le_bytes());
+        for value in &self.values {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes
+    }
+
+    pub fn read(reader: &mut impl Read) -> io::Result<Self> {
+        let kind = u32::from_le_bytes(read_word(reader)?);
+        let agent = u32::from_le_bytes(read_word(reader)?);
+        let len = u32::from_le_bytes(read_word(reader)?);
+        if len > MAX_FRAME_VALUES {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("frame of {} values, at most {}", len, MAX_FRAME_VALUES),
+            ));
+        }
+        let values = (0..len)
+            .map(|_| read_word(reader).map(f32::from_le_bytes))
+            .collect::<io::Result<_>>()?;
+        Ok(Self {
+            kind,
+            agent,
+            values,
+        })
+    }
+}
+
+fn read_word(reader: &mut impl Read) -> io::Result<[u8; 4]> {
+    let mut word = [0u8; 4];
+    reader.read_exact(&mut word)?;
+    Ok(word)
+}
+
+/// Running bridge. Each peer gets a writer thread fed by a bounded channel, so a slow peer
+/// never blocks the game loop, and a reader thread queueing its actions for the next tick.
+#[derive(Resource, Clone)]
+pub struct RemoteAgentBridge {
+    local_addr: SocketAddr,
+    peers: Arc<Mutex<Vec<SyncSender<Vec<u8>>>>>,
+    actions: Arc<Mutex<Receiver<(u32, PlayerActionSet)>>>,
+}
+
+impl RemoteAgentBridge {
+    pub fn bind(addr: SocketAddr) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let (action_sender, actions) = mpsc::sync_channel(ACTION_QUEUE_FRAMES);
+        let bridge = Self {
+            local_addr: listener.local_addr()?,
+            peers: Arc::default(),
+            actions: Arc::new(Mutex::new(actions)),
+        };
+
+        let peers = bridge.peers.clone();
+        std::thread::spawn(move || {
+            for connection in listener.incoming().flatten() {
+                let _ = connection.set_nodelay(true);
+                let Ok(reader) = connection.try_clone() else {
+                    continue;
+                };
+                let (sender, receiver) = mpsc::sync_channel::<Vec<u8>>(PEER_QUEUE_FRAMES);
+                if let Ok(mut peers) = peers.lock() {
+                    peers.push(sender);
+                }
+                std::thread::spawn(move || write_frames(connection, receiver));
+                let action_sender = action_sender.clone();
+                std::thread::spawn(move || read_actions(reader, action_sender));
+            }
+        });
+        Ok(bridge)
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    pub fn peer_count(&self) -> usize {
+        self.peers.lock().map_or(0, |peers| peers.len())
+    }
+
+    /// Queue `frame` for every peer. Peers whose queue is full miss it; disconnected ones
+    /// are forgotten.
+    pub fn publish(&self, frame: &Frame) {
+        let bytes = frame.to_bytes();
+        if let Ok(mut peers) = self.peers.lock() {
+            peers.retain(|peer| {
+                !matches!(
+                    peer.try_send(bytes.clone()),
+                    Err(TrySendError::Disconnected(_))
+                )
+            });
+        }
+    }
+
+    /// Actions received since the last call, oldest first.
+    pub fn drain_actions(&self) -> Vec<(u32, PlayerActionSet)> {
+        self.actions
+            .lock()
+            .map(|actions| actions.try_iter().collect())
+            .unwrap_or_default()
+    }
+}
+
+fn write_frames(connection: TcpStream, frames: Receiver<Vec<u8>>) {
+    let mut writer = BufWriter::new(connection);
+    while let Ok(bytes) = frames.recv() {
+        // Whatever else is already queued goes out in the same write.
+        let queued = std::iter::once(bytes).chain(frames.try_iter());
+        for bytes in queued {
+            if writer.write_all(&bytes).is_err() {
+                return;
+            }
+        }
+        if writer.flush().is_err() {
+            return;
+        }
+    }
+}
+
+fn read_actions(connection: TcpStream, actions: SyncSender<(u32, PlayerActionSet)>) {
+    let mut reader = BufReader::new(connection);
+    while let Ok(frame) = Frame::read(&mut reader) {
+        if frame.kind != ACTION_FRAME || frame.values.len() != LAST_ACTION_SIZE {
+            continue;
+        }
+        let action = PlayerActionSet::from_vector(&frame.values);
+        if let Err(TrySendError::Disconnected(_)) = actions.try_send((frame.agent, action)) {
+            return;
+        }
+    }
+}
+
+pub struct RemoteAgentBridgePlugin;
+
+impl Plugin for RemoteAgentBridgePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RemoteAgentSettings>();
+        app.add_systems(Startup, start_remote_agent_bridge);
+        app.add_systems(FixedPreUpdate, apply_remote_actions);
+        app.add_systems(FixedPostUpdate, stream_remote_observations);
+    }
+}
+
+fn start_remote_agent_bridge(mut commands: Commands, settings: Res<RemoteAgentSettings>) {
+    let Some(addr) = settings.addr else {
+        return;
+    };
+
+    match RemoteAgentBridge::bind(addr) {
+        Ok(bridge) => {
+            info!(
+                "🤖 Remote agents can connect on tcp://{}",
+                bridge.local_addr()
+            );
+            commands.insert_resource(bridge);
+        }
+        Err(e) => error!("Failed to start the remote agent bridge on {}: {}", addr, e),
+    }
+}
+
+/// The latest action each peer sent for an agent holds until it sends another.
+fn apply_remote_actions(
+    bridge: Option<Res<RemoteAgentBridge>>,
+    mut agents: Query<(&mut RemoteAgent, &mut ActionState<PlayerAction>)>,
+) {
+    let Some(bridge) = bridge else {
+        return;
+    };
+
+    for (agent_id, action) in bridge.drain_actions() {
+        let Some((mut agent, mut action_state)) =
+            agents.iter_mut().find(|(agent, _)| agent.id == agent_id)
+        else {
+            continue;
+        };
+        action.apply_to_action_state(&mut action_state);
+        agent.last_action = Some(action);
+    }
+}
+
+fn stream_remote_observations(
+    bridge: Option<Res<RemoteAgentBridge>>,
+    spatial_query: Res<SpatialQueryPipeline>,
+    agents: Query<(
+        Entity,
+        &RemoteAgent,
+        &Position,
+        &Rotation,
+        &LinearVelocity,
+        Option<&Health>,
+    )>,
+) {
+    let Some(bridge) = bridge else {
+        return;
+    };
+    if bridge.peer_count() == 0 {
+        return;
+    }
+
+    for (entity, agent, position, rotation, velocity, health) in agents.iter() {
+        let observation = get_external_agent_observation(
+            &spatial_query,
+            entity,
+            position.0,
+            rotation.0,
+            velocity.0,
+            health.map_or(0.0, |health| health.current),
+            health.map_or(0.0, |health| health.max),
+            agent.last_action.as_ref(),
+        );
+        bridge.publish(&Frame {
+            kind: OBSERVATION_FRAME,
+            agent: agent.id,
+            values: observation.to_buffer(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ACTION_FRAME, Frame, OBSERVATION_FRAME, RemoteAgentBridge, RemoteAgentSettings};
+    use std::io::Write;
+    use std::net::TcpStream;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn agents_connect_from_localhost_unless_public() {
+        let local = RemoteAgentSettings::on_port(9400).addr.unwrap();
+        assert!(local.ip().is_loopback());
+        let public = RemoteAgentSettings::on_port(9400).public().addr.unwrap();
+        assert!(public.ip().is_unspecified());
+        assert_eq!(public.port(), 9400);
+    }
+
+    #[test]
+    fn peers_that_stop_reading_miss_frames_without_blocking() {
+        let bridge = RemoteAgentBridge::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+        let _peer = TcpStream::connect(bridge.local_addr()).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while bridge.peer_count() == 0 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let observation = Frame {
+            kind: OBSERVATION_FRAME,
+            agent: 1,
+            values: vec![0.0; 1024],
+        };
+        let started = Instant::now();
+        for _ in 0..10_000 {
+            bridge.publish(&observation);
+        }
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(bridge.peer_count(), 1, "a slow peer stays connected");
+    }
+
+    #[test]
+    fn peers_receive_observations_and_send_actions() {
+        let bridge = RemoteAgentBridge::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+        let mut peer = TcpStream::connect(bridge.local_addr()).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while bridge.peer_count() == 0 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let observation = Frame {
+            kind: OBSERVATION_FRAME,
+            agent: 7,
+            values: vec![1.0, 2.5, -3.0],
+        };
+        bridge.publish(&observation);
+        assert_eq!(Frame::read(&mut peer).unwrap(), observation);
+
+        let action = Frame {
+            kind: ACTION_FRAME,
+            agent: 7,
+            values: vec![0.0, 1.0, 0.0, 0.0, 1.0, 0.0],
+        };
+        peer.write_all(&action.to_bytes()).unwrap();
+        let mut actions = Vec::new();
+        while actions.is_empty() && Instant::now() < deadline {
+            actions = bridge.drain_actions();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let (agent, action) = actions.pop().expect("the action should arrive");
+        assert_eq!(agent, 7);
+        assert_eq!(action.movement.y, 1.0);
+        assert!(action.jump);
+    }
+}
@@ -0,0 +1,288 @@
+//! Reward terms for RL training. Each term is a [`RewardSource`] scoring one step; a
+//! [`RewardRegistry`] weighs and sums them, and keeps the per-term breakdown so episodes
+//! can be logged term by term.
+//!
+//! Weights come from a training config file with one `term = weight` line per term, like
+//! the balance file. A weight of 0 turns a term off.
+
+use bevy::prelude::Resource;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use crate::reinforcement_learning::{PlayerActionSet, RLObservation};
+
+/// What happened to the agent between two observations.
+#[derive(Clone, Debug)]
+pub struct RewardStep<'a> {
+    pub previous: &'a RLObservation,
+    pub current: &'a RLObservation,
+    pub action: &'a PlayerActionSet,
+    /// Damage the agent dealt during the step.
+    pub damage_dealt: f32,
+    pub kills: u32,
+    pub dt: f32,
+}
+
+impl RewardStep<'_> {
+    pub fn died(&self) -> bool {
+        self.current.health <= 0.0 && self.previous.health > 0.0
+    }
+}
+
+/// One reward term. Scores are unweighted; the registry applies the weights.
+pub trait RewardSource: Send + Sync {
+    /// Name of the term in config files and logs.
+    fn name(&self) -> &'static str;
+    fn score(&self, step: &RewardStep) -> f32;
+}
+
+pub struct DamageDealt;
+
+impl RewardSource for DamageDealt {
+    fn name(&self) -> &'static str {
+        "damage_dealt"
+    }
+
+    fn score(&self, step: &RewardStep) -> f32 {
+        step.damage_dealt
+    }
+}
+
+pub struct Kills;
+
+impl RewardSource for Kills {
+    fn name(&self) -> &'static str {
+        "kills"
+    }
+
+    fn score(&self, step: &RewardStep) -> f32 {
+        step.kills as f32
+    }
+}
+
+/// Meters closer to the objective than at the previous step; moving away scores negative.
+pub struct ObjectiveProgress;
+
+impl RewardSource for ObjectiveProgress {
+    fn name(&self) -> &'static str {
+        "objective_progress"
+    }
+
+    fn score(&self, step: &RewardStep) -> f32 {
+        match (
+            step.previous.distance_to_objective,
+            step.current.distance_to_objective,
+        ) {
+            (Some(previous), Some(current)) => previous - current,
+            _ => 0.0,
+        }
+    }
+}
+
+/// Seconds survived.
+pub struct SurvivalTime;
+
+impl RewardSource for SurvivalTime {
+    fn name(&self) -> &'static str {
+        "survival_time"
+    }
+
+    fn score(&self, step: &RewardStep) -> f32 {
+        if step.current.health > 0.0 {
+            step.dt
+        } else {
+            0.0
+        }
+    }
+}
+
+/// 1 on the step the agent dies.
+pub struct Death;
+
+impl RewardSource for Death {
+    fn name(&self) -> &'static str {
+        "death"
+    }
+
+    fn score(&self, step: &RewardStep) -> f32 {
+        if step.died() { 1.0 } else { 0.0 }
+    }
+}
+
+/// Weighted reward of one step or one episode, term by term.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RewardBreakdown {
+    pub terms: Vec<(&'static str, f32)>,
+}
+
+impl RewardBreakdown {
+    pub fn total(&self) -> f32 {
+        self.terms.iter().map(|(_, reward)| reward).sum()
+    }
+
+    /// Adds `other` term by term.
+    pub fn accumulate(&mut self, other: &RewardBreakdown) {
+        for (name, reward) in &other.terms {
+            match self.terms.iter_mut().find(|(term, _)| term == name) {
+                Some((_, total)) => *total += reward,
+                None => self.terms.push((*name, *reward)),
+            }
+        }
+    }
+
+    /// `total (term=reward, ...)`, for logs.
+    pub fn summary(&self) -> String {
+        let mut summary = format!("{:.2} (", self.total());
+        for (index, (name, reward)) in self.terms.iter().enumerate() {
+            if index > 0 {
+                summary.push_str(", ");
+            }
+            let _ = write!(summary, "{}={:.2}", name, reward);
+        }
+        summary.push(')');
+        summary
+    }
+}
+
+/// Weighted reward terms of a training run.
+pub struct RewardRegistry {
+    terms: Vec<(Box<dyn RewardSource>, f32)>,
+}
+
+impl Default for RewardRegistry {
+    fn default() -> Self {
+        Self::empty()
+            .with(DamageDealt, 0.02)
+            .with(Kills, 10.0)
+            .with(ObjectiveProgress, 0.5)
+            .with(SurvivalTime, 0.1)
+            .with(Death, -50.0)
+    }
+}
+
+impl RewardRegistry {
+    pub fn empty() -> Self {
+        Self { terms: Vec::new() }
+    }
+
+    /// Adds a term, or changes its weight if one with the same name is registered.
+    pub fn with(mut self, source: impl RewardSource + 'static, weight: f32) -> Self {
+        self.terms.retain(|(term, _)| term.name() != source.name());
+        self.terms.push((Box::new(source), weight));
+        self
+    }
+
+    pub fn weight(&self, name: &str) -> Option<f32> {
+        self.terms
+            .iter()
+            .find(|(term, _)| term.name() == name)
+            .map(|(_, weight)| *weight)
+    }
+
+    /// Applies a training config: one `term = weight` line per term to change. Unknown
+    /// terms and bad weights are errors so a typo does not silently train on a default.
+    /// Blank lines and `#` comments are skipped.
+    pub fn apply_weights(&mut self, text: &str) -> Result<(), String> {
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((name, value)) = line.split_once('=') else {
+                return Err(format!("line {}: expected `term = weight`", index + 1));
+            };
+            let (name, value) = (name.trim(), value.trim());
+            let weight = value
+                .parse::<f32>()
+                .ok()
+                .filter(|weight| weight.is_finite())
+                .ok_or_else(|| {
+                    format!("line {}: bad weight {:?} for {}", index + 1, value, name)
+                })?;
+            let Some((_, term_weight)) =
+                self.terms.iter_mut().find(|(term, _)| term.name() == name)
+            else {
+                return Err(format!("line {}: unknown reward term {}", index + 1, name));
+            };
+            *term_weight = weight;
+        }
+        Ok(())
+    }
+
+    pub fn evaluate(&self, step: &RewardStep) -> RewardBreakdown {
+        RewardBreakdown {
+            terms: self
+                .terms
+                .iter()
+                .filter(|(_, weight)| *weight != 0.0)
+                .map(|(term, weight)| (term.name(), term.score(step) * weight))
+                .collect(),
+        }
+    }
+}
+
+/// Training config file holding reward weights. Defaults apply when unset.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct RewardSettings {
+    pub config: Option<PathBuf>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RewardRegistry, RewardStep};
+    use crate::reinforcement_learning::{PlayerActionSet, RLObservation};
+    use bevy::prelude::{Vec2, Vec3};
+
+    fn observation(health: f32, distance_to_objective: f32) -> RLObservation {
+        RLObservation {
+            position: Vec3::ZERO,
+            velocity: Vec3::ZERO,
+            health,
+            max_health: 100.0,
+            distance_to_objective: Some(distance_to_objective),
+        }
+    }
+
+    #[test]
+    fn weighted_terms_come_from_the_config_and_add_up() {
+        let mut registry = RewardRegistry::default();
+        registry
+            .apply_weights("# aggressive\nkills = 20\nsurvival_time = 0\n")
+            .unwrap();
+        assert!(registry.apply_weights("kils = 1").is_err());
+        assert!(registry.apply_weights("kills = lots").is_err());
+
+        let action = PlayerActionSet {
+            movement: Vec2::ZERO,
+            look: Vec2::ZERO,
+            jump: false,
+            shoot: true,
+        };
+        let (previous, current) = (observation(100.0, 10.0), observation(0.0, 8.0));
+        let step = RewardStep {
+            previous: &previous,
+            current: &current,
+            action: &action,
+            damage_dealt: 50.0,
+            kills: 1,
+            dt: 0.1,
+        };
+        let breakdown = registry.evaluate(&step);
+        let term = |name: &str| {
+            breakdown
+                .terms
+                .iter()
+                .find(|(term, _)| *term == name)
+                .map(|(_, reward)| *reward)
+        };
+        assert_eq!(term("kills"), Some(20.0));
+        assert_eq!(term("objective_progress"), Some(1.0));
+        assert_eq!(term("death"), Some(-50.0));
+        assert_eq!(term("survival_time"), None);
+        assert!((breakdown.total() - (1.0 + 20.0 + 1.0 - 50.0)).abs() < 1e-4);
+
+        let mut episode = breakdown.clone();
+        episode.accumulate(&breakdown);
+        assert_eq!(episode.total(), breakdown.total() * 2.0);
+    }
+}
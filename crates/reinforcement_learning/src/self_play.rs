@@ -0,0 +1,326 @@
+//! Self-play: the agent trains against frozen copies of itself.
+//!
+//! [`RLTrainingState`] keeps a [`CheckpointPool`] of past policies. Every
+//! `snapshot_every` episodes the current network is frozen into the pool, dropping the
+//! oldest checkpoint once the pool is full. Each episode an opponent checkpoint is drawn
+//! from the pool and drives every bot tagged [`SelfPlayOpponent`].
+//!
+//! Episodes are scored like games: a win when the agent kills an opponent without dying,
+//! a loss when it dies without killing one, a draw otherwise. Results update Elo ratings
+//! of the learner and of the checkpoint it played, so a learner climbing above its own
+//! past checkpoints is a sign that training is going somewhere.
+
+use avian3d::prelude::{LinearVelocity, Position};
+use bevy::prelude::*;
+use nalgebra::DVector;
+use rand::{Rng, rng};
+use shared::components::health::{DeathEvent, Health};
+
+use crate::reinforcement_learning::{
+    EpisodeFinished, RLObservation, RLTrainingState, SimpleNetwork,
+};
+
+/// Rating of the first policy, and of checkpoints snapshotted before any game.
+pub const INITIAL_RATING: f32 = 1000.0;
+/// How far one game moves a rating.
+const ELO_K: f32 = 32.0;
+/// Speed of opponent bots at full stick, same as the agent.
+const OPPONENT_SPEED: f32 = 5.0;
+
+/// A bot driven by the self-play opponent drawn for the current episode.
+#[derive(Component, Clone, Debug, Default)]
+pub struct SelfPlayOpponent;
+
+/// A frozen copy of the policy.
+#[derive(Clone, Debug)]
+pub struct PolicyCheckpoint {
+    pub id: usize,
+    /// Episode the policy was snapshotted after.
+    pub episode: usize,
+    pub network: SimpleNetwork,
+    pub rating: f32,
+    pub games: u32,
+}
+
+/// Past policies to play against, with their ratings and the learner's.
+#[derive(Clone, Debug)]
+pub struct CheckpointPool {
+    pub checkpoints: Vec<PolicyCheckpoint>,
+    /// Checkpoints kept; the oldest goes first.
+    pub capacity: usize,
+    /// Episodes between two snapshots.
+    pub snapshot_every: usize,
+    /// Rating of the policy being trained.
+    pub learner_rating: f32,
+    /// Checkpoint playing the current episode.
+    pub opponent: Option<usize>,
+    next_id: usize,
+}
+
+impl Default for CheckpointPool {
+    fn default() -> Self {
+        Self {
+            checkpoints: Vec::new(),
+            capacity: 10,
+            snapshot_every: 25,
+            learner_rating: INITIAL_RATING,
+            opponent: None,
+            next_id: 0,
+        }
+    }
+}
+
+/// Outcome of an episode for the learner.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameResult {
+    Win,
+    Draw,
+    Loss,
+}
+
+impl GameResult {
+    pub fn score(self) -> f32 {
+        match self {
+            GameResult::Win => 1.0,
+            GameResult::Draw => 0.5,
+            GameResult::Loss => 0.0,
+        }
+    }
+}
+
+/// Chance that a player rated `rating` beats one rated `opponent_rating`.
+pub fn expected_score(rating: f32, opponent_rating: f32) -> f32 {
+    1.0 / (1.0 + 10f32.powf((opponent_rating - rating) / 400.0))
+}
+
+impl CheckpointPool {
+    /// Freezes `network` into the pool at the learner's current rating and returns its id.
+    pub fn snapshot(&mut self, network: &SimpleNetwork, episode: usize) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.checkpoints.push(PolicyCheckpoint {
+            id,
+            episode,
+            network: network.clone(),
+            rating: self.learner_rating,
+            games: 0,
+        });
+        while self.checkpoints.len() > self.capacity.max(1) {
+            let evicted = self.checkpoints.remove(0);
+            if self.opponent == Some(evicted.id) {
+                self.opponent = None;
+            }
+        }
+        id
+    }
+
+    pub fn get(&self, id: usize) -> Option<&PolicyCheckpoint> {
+        self.checkpoints
+            .iter()
+            .find(|checkpoint| checkpoint.id == id)
+    }
+
+    /// Draws the opponent of the next episode, uniformly over the pool.
+    pub fn sample_opponent(&mut self, rng: &mut impl Rng) -> Option<usize> {
+        self.opponent = (!self.checkpoints.is_empty())
+            .then(|| self.checkpoints[rng.random_range(0..self.checkpoints.len())].id);
+        self.opponent
+    }
+
+    /// Updates the ratings of the learner and of the checkpoint `opponent` after a game.
+    pub fn record_result(&mut self, opponent: usize, result: GameResult) {
+        let learner_rating = self.learner_rating;
+        let Some(checkpoint) = self
+            .checkpoints
+            .iter_mut()
+            .find(|checkpoint| checkpoint.id == opponent)
+        else {
+            return;
+        };
+
+        let expected = expected_score(learner_rating, checkpoint.rating);
+        let change = ELO_K * (result.score() - expected);
+        self.learner_rating += change;
+        checkpoint.rating -= change;
+        checkpoint.games += 1;
+    }
+
+    /// `learner 1032 | #0 968 (12 games), ...`, for logs.
+    pub fn summary(&self) -> String {
+        let checkpoints = self
+            .checkpoints
+            .iter()
+            .map(|checkpoint| {
+                format!(
+                    "#{} {:.0} ({} games)",
+                    checkpoint.id, checkpoint.rating, checkpoint.games
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("learner {:.0} | {}", self.learner_rating, checkpoints)
+    }
+}
+
+/// Deaths seen during the current episode.
+#[derive(Resource, Clone, Debug, Default)]
+struct SelfPlayMatch {
+    agent_died: bool,
+    opponent_died: bool,
+}
+
+impl SelfPlayMatch {
+    fn result(&self) -> GameResult {
+        match (self.opponent_died, self.agent_died) {
+            (true, false) => GameResult::Win,
+            (false, true) => GameResult::Loss,
+            _ => GameResult::Draw,
+        }
+    }
+}
+
+/// Runs self-play on top of [`RLPlugin`](crate::reinforcement_learning::RLPlugin).
+pub struct SelfPlayPlugin;
+
+impl Plugin for SelfPlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SelfPlayMatch>().add_systems(
+            FixedUpdate,
+            (
+                track_self_play_deaths,
+                finish_self_play_games,
+                drive_self_play_opponents,
+            )
+                .chain(),
+        );
+    }
+}
+
+fn track_self_play_deaths(
+    mut deaths: MessageReader<DeathEvent>,
+    opponents: Query<(), With<SelfPlayOpponent>>,
+    rl_state: Res<RLTrainingState>,
+    mut game: ResMut<SelfPlayMatch>,
+) {
+    for death in deaths.read() {
+        if Some(death.target) == rl_state.agent {
+            game.agent_died = true;
+        } else if opponents.contains(death.target) && death.source == rl_state.agent {
+            game.opponent_died = true;
+        }
+    }
+}
+
+fn finish_self_play_games(
+    mut episodes: MessageReader<EpisodeFinished>,
+    mut rl_state: ResMut<RLTrainingState>,
+    mut game: ResMut<SelfPlayMatch>,
+) {
+    for episode in episodes.read() {
+        let result = game.result();
+        *game = SelfPlayMatch::default();
+        let pool = &mut rl_state.self_play;
+        if let Some(opponent) = pool.opponent {
+            pool.record_result(opponent, result);
+        }
+
+        if episode.episode % pool.snapshot_every.max(1) == 0
+            && let Some(network) = rl_state.q_network.clone()
+        {
+            let id = rl_state.self_play.snapshot(&network, episode.episode);
+            info!(
+                "Self-play: froze the policy of episode {} as #{}",
+                episode.episode, id
+            );
+        }
+
+        let pool = &mut rl_state.self_play;
+        pool.sample_opponent(&mut rng());
+        info!(
+            "Self-play: episode {} was a {:?}, ratings {}",
+            episode.episode,
+            result,
+            pool.summary()
+        );
+    }
+}
+
+/// Drives opponent bots with the drawn checkpoint, greedily. Training starts against the
+/// initial policy, so there is always someone to play.
+fn drive_self_play_opponents(
+    mut opponents: Query<(&Position, &mut LinearVelocity, Option<&Health>), With<SelfPlayOpponent>>,
+    mut rl_state: ResMut<RLTrainingState>,
+) {
+    if opponents.is_empty() {
+        return;
+    }
+    if rl_state.self_play.checkpoints.is_empty()
+        && let Some(network) = rl_state.q_network.clone()
+    {
+        rl_state
+            .self_play
+            .snapshot(&network, rl_state.episode_count);
+    }
+    if rl_state.self_play.opponent.is_none() {
+        rl_state.self_play.sample_opponent(&mut rng());
+    }
+    let Some(checkpoint) = rl_state
+        .self_play
+        .opponent
+        .and_then(|id| rl_state.self_play.get(id))
+    else {
+        return;
+    };
+
+    for (position, mut velocity, health) in opponents.iter_mut() {
+        let observation = RLObservation {
+            position: position.0,
+            velocity: velocity.0,
+            health: health.map_or(100.0, |health| health.current),
+            max_health: health.map_or(100.0, |health| health.max),
+            distance_to_objective: None,
+        };
+        let state = rl_state.observation_to_state(&observation);
+        let output = checkpoint.network.forward(&DVector::from_vec(state));
+        let action = rl_state.vector_to_action(&output);
+        let input_direction = Vec3::new(action.movement.x, 0.0, -action.movement.y);
+        let desired_velocity = input_direction * OPPONENT_SPEED;
+        velocity.0 = Vec3::new(desired_velocity.x, velocity.0.y, desired_velocity.z);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CheckpointPool, GameResult, INITIAL_RATING, expected_score};
+    use crate::reinforcement_learning::SimpleNetwork;
+
+    #[test]
+    fn pool_keeps_recent_checkpoints_and_rates_games() {
+        let network = SimpleNetwork::new(8, 4, 6);
+        let mut pool = CheckpointPool {
+            capacity: 2,
+            ..CheckpointPool::default()
+        };
+        for episode in [25, 50, 75] {
+            pool.snapshot(&network, episode);
+        }
+        let ids: Vec<_> = pool.checkpoints.iter().map(|c| c.id).collect();
+        assert_eq!(ids, [1, 2]);
+
+        let opponent = pool.sample_opponent(&mut rand::rng()).unwrap();
+        assert!(pool.get(opponent).is_some());
+
+        // Evenly rated: a win moves both ratings by half the K factor.
+        assert_eq!(expected_score(INITIAL_RATING, INITIAL_RATING), 0.5);
+        pool.record_result(opponent, GameResult::Win);
+        assert_eq!(pool.learner_rating, INITIAL_RATING + 16.0);
+        let checkpoint = pool.get(opponent).unwrap();
+        assert_eq!(checkpoint.rating, INITIAL_RATING - 16.0);
+        assert_eq!(checkpoint.games, 1);
+
+        // Losing as the favourite costs more than the even win earned.
+        assert!(expected_score(pool.learner_rating, checkpoint.rating) > 0.5);
+        pool.record_result(opponent, GameResult::Loss);
+        assert!(pool.learner_rating < INITIAL_RATING);
+    }
+}
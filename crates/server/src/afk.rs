@@ -0,0 +1,184 @@
+use bevy::prelude::{
+    App, Commands, Component, Entity, Has, IntoScheduleConfigs, Plugin, Query, Res, Resource, Time,
+    Update, in_state, info,
+};
+use leafwing_input_manager::prelude::ActionState;
+use lightyear::prelude::{ControlledBy, Disconnect, MessageSender};
+use shared::afk::{Afk, has_meaningful_input, is_bot_player};
+use shared::inputs::input::PlayerAction;
+use shared::protocol::{LobbyControlChannel, PlayerId, ServerAnnouncement};
+
+use crate::ServerGameState;
+use crate::entities::spectator::Spectator;
+
+/// What happens to a player idle for `act_after_secs`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AfkAction {
+    /// Free their slot in the match but keep them connected, watching.
+    #[default]
+    MoveToSpectators,
+    Kick,
+}
+
+/// Idle players are warned after `warn_after_secs` without meaningful input and moved to
+/// spectators (or kicked) after `act_after_secs`. Bot clients are exempt.
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct AfkSettings {
+    pub enabled: bool,
+    pub warn_after_secs: f32,
+    pub act_after_secs: f32,
+    pub action: AfkAction,
+}
+
+impl Default for AfkSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            warn_after_secs: 60.0,
+            act_after_secs: 120.0,
+            action: AfkAction::MoveToSpectators,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AfkStep {
+    Active,
+    Idle,
+    Warn,
+    Act,
+}
+
+/// Seconds since a player's last meaningful input. Server only; the replicated part is
+/// the `Afk` marker.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+pub struct IdleTime {
+    pub secs: f32,
+    warned: bool,
+}
+
+impl IdleTime {
+    /// Advance by `delta_secs`. `Warn` and `Act` are each returned once per idle stretch.
+    pub fn advance(&mut self, delta_secs: f32, active: bool, settings: &AfkSettings) -> AfkStep {
+        if active {
+            *self = Self::default();
+            return AfkStep::Active;
+        }
+
+        self.secs += delta_secs;
+        if self.secs >= settings.act_after_secs {
+            *self = Self::default();
+            AfkStep::Act
+        } else if self.secs >= settings.warn_after_secs && !self.warned {
+            self.warned = true;
+            AfkStep::Warn
+        } else {
+            AfkStep::Idle
+        }
+    }
+}
+
+pub struct ServerAfkPlugin;
+
+impl Plugin for ServerAfkPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AfkSettings>();
+        app.add_systems(
+            Update,
+            track_idle_players.run_if(in_state(ServerGameState::Playing)),
+        );
+    }
+}
+
+fn track_idle_players(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<AfkSettings>,
+    mut players: Query<(
+        Entity,
+        &PlayerId,
+        &ControlledBy,
+        &ActionState<PlayerAction>,
+        Option<&mut IdleTime>,
+        Has<Afk>,
+    )>,
+    mut announcers: Query<&mut MessageSender<ServerAnnouncement>>,
+) {
+    if !settings.enabled {
+        return;
+    }
+
+    for (entity, player_id, controlled_by, action_state, idle, is_afk) in players.iter_mut() {
+        if is_bot_player(player_id) {
+            continue;
+        }
+        let Some(mut idle) = idle else {
+            commands.entity(entity).insert(IdleTime::default());
+            continue;
+        };
+
+        let active = has_meaningful_input(action_state);
+        match idle.advance(time.delta_secs(), active, &settings) {
+            AfkStep::Active if is_afk => {
+                commands.entity(entity).remove::<Afk>();
+            }
+            AfkStep::Active | AfkStep::Idle => {}
+            AfkStep::Warn => {
+                info!("💤 Player {} is idle", player_id.0);
+                commands.entity(entity).insert(Afk);
+                if let Ok(mut sender) = announcers.get_mut(controlled_by.owner) {
+                    let action = match settings.action {
+                        AfkAction::MoveToSpectators => "moved to spectators",
+                        AfkAction::Kick => "kicked",
+                    };
+                    sender.send::<LobbyControlChannel>(ServerAnnouncement {
+                        text: format!(
+                            "You seem to be away; you will be {} in {:.0} seconds",
+                            action,
+                            settings.act_after_secs - settings.warn_after_secs
+                        ),
+                    });
+                }
+            }
+            AfkStep::Act => match settings.action {
+                AfkAction::MoveToSpectators => {
+                    info!("💤 Moving idle player {} to spectators", player_id.0);
+                    commands.entity(controlled_by.owner).insert(Spectator);
+                    commands.entity(entity).despawn();
+                }
+                AfkAction::Kick => {
+                    info!("💤 Kicking idle player {}", player_id.0);
+                    commands.trigger(Disconnect {
+                        entity: controlled_by.owner,
+                    });
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AfkSettings, AfkStep, IdleTime};
+
+    #[test]
+    fn idle_players_are_warned_once_then_acted_on() {
+        let settings = AfkSettings {
+            warn_after_secs: 2.0,
+            act_after_secs: 4.0,
+            ..Default::default()
+        };
+        let mut idle = IdleTime::default();
+
+        assert_eq!(idle.advance(1.5, false, &settings), AfkStep::Idle);
+        assert_eq!(idle.advance(1.0, false, &settings), AfkStep::Warn);
+        assert_eq!(idle.advance(1.0, false, &settings), AfkStep::Idle);
+        assert_eq!(idle.advance(1.0, true, &settings), AfkStep::Active);
+        assert_eq!(idle.secs, 0.0);
+
+        for _ in 0..3 {
+            idle.advance(1.0, false, &settings);
+        }
+        assert_eq!(idle.advance(1.0, false, &settings), AfkStep::Act);
+    }
+}
@@ -0,0 +1,156 @@
+use avian3d::prelude::Position;
+use bevy::prelude::{
+    App, Commands, Entity, IntoScheduleConfigs, Plugin, Query, Res, ResMut, Resource, Startup,
+    Update, With, Without, in_state, info, warn,
+};
+use std::path::PathBuf;
+
+use shared::ai::bot::{BehaviorTarget, BotBehaviorTree, BotBlackboard, BotIntent};
+use shared::bot_policy::PolicyControlled;
+use shared::bots::BotProfile;
+use shared::components::health::Health;
+use shared::navigation::SimpleNavigationAgent;
+use shared::perception::Awareness;
+use shared::squads::SquadMember;
+
+use crate::ServerGameState;
+use crate::cover::{BotCover, assign_bot_cover};
+use crate::squads::{SquadBlackboards, pick_cover_positions, squad_centers};
+
+/// A bot is only sent to a new target once the old one is this far off, so trees do not
+/// replan its path every frame.
+const RETARGET_DISTANCE: f32 = 2.0;
+
+/// Behavior tree file for the bots. Bots follow the classic tree unless `tree` is set.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct BotBehaviorSettings {
+    pub tree: Option<PathBuf>,
+}
+
+impl BotBehaviorSettings {
+    pub fn from_file(path: impl Into<PathBuf>) -> Self {
+        Self {
+            tree: Some(path.into()),
+        }
+    }
+}
+
+/// The tree every bot is driven by.
+#[derive(Resource, Debug)]
+pub struct BotBehavior(pub BotBehaviorTree);
+
+impl Default for BotBehavior {
+    fn default() -> Self {
+        Self(BotBehaviorTree::classic())
+    }
+}
+
+/// Drives bots' movement by a behavior tree over what they perceive and what their squad
+/// wants from them. Bots under an active trained policy are left to it.
+pub struct ServerBotBehaviorPlugin;
+
+impl Plugin for ServerBotBehaviorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BotBehaviorSettings>();
+        app.init_resource::<BotBehavior>();
+        app.add_systems(Startup, load_bot_behavior);
+        app.add_systems(
+            Update,
+            (give_bots_behavior, run_bot_behavior)
+                .chain()
+                .after(pick_cover_positions)
+                .after(assign_bot_cover)
+                .run_if(in_state(ServerGameState::Playing)),
+        );
+    }
+}
+
+fn load_bot_behavior(settings: Res<BotBehaviorSettings>, mut behavior: ResMut<BotBehavior>) {
+    let Some(path) = &settings.tree else {
+        return;
+    };
+
+    match BotBehaviorTree::load(path) {
+        Ok(tree) => {
+            info!("🌳 Loaded bot behavior tree from {}", path.display());
+            behavior.0 = tree;
+        }
+        Err(e) => {
+            warn!(
+                "Failed to load bot behavior tree from {}, bots keep the classic one: {}",
+                path.display(),
+                e
+            );
+        }
+    }
+}
+
+fn give_bots_behavior(
+    mut commands: Commands,
+    bots: Query<Entity, (With<BotProfile>, Without<BehaviorTarget>)>,
+) {
+    for bot in bots.iter() {
+        commands.entity(bot).insert(BehaviorTarget::default());
+    }
+}
+
+/// Tick every living bot's tree and send it where the tree says. Bots handed back to
+/// their patrol drop the tree's target once, and the patrol picks the next point.
+fn run_bot_behavior(
+    behavior: Res<BotBehavior>,
+    blackboards: Res<SquadBlackboards>,
+    members: Query<(&SquadMember, &Position, &Health)>,
+    mut bots: Query<
+        (
+            Entity,
+            &mut SimpleNavigationAgent,
+            &mut BehaviorTarget,
+            &Position,
+            &Health,
+            Option<&Awareness>,
+            Option<&BotCover>,
+            Option<&PolicyControlled>,
+        ),
+        With<BotProfile>,
+    >,
+) {
+    let centers = squad_centers(members.iter());
+    for (bot, mut nav_agent, mut target, position, health, awareness, cover, policy) in
+        bots.iter_mut()
+    {
+        if health.is_dead || policy.is_some_and(|policy| policy.active) {
+            continue;
+        }
+
+        let squad_position = members.get(bot).ok().and_then(|(member, ..)| {
+            let center = *centers.get(&member.squad)?;
+            blackboards.position_for(bot, member, center)
+        });
+        let mut blackboard = BotBlackboard::new(
+            position.0,
+            health.current / health.max.max(f32::EPSILON),
+            awareness.copied().unwrap_or_default(),
+            squad_position,
+            cover.and_then(|cover| cover.point),
+        );
+        behavior.0.tick(&mut blackboard);
+
+        match blackboard.intent {
+            BotIntent::MoveTo(destination) => {
+                // Patrols may have moved the agent on since; send it back too.
+                let off_target = nav_agent
+                    .current_target
+                    .is_none_or(|current| current.distance(destination) > RETARGET_DISTANCE);
+                if off_target {
+                    nav_agent.current_target = Some(destination);
+                    target.0 = Some(destination);
+                }
+            }
+            BotIntent::Patrol => {
+                if target.0.take().is_some() {
+                    nav_agent.current_target = None;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,349 @@
+use bevy::prelude::{
+    App, Commands, Entity, IntoScheduleConfigs, MessageReader, Name, OnEnter, Plugin, Query, Res,
+    ResMut, Resource, Single, Startup, Time, Update, With, error, in_state, info, warn,
+};
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+
+use lightyear::prelude::{NetworkTarget, Server, ServerMultiMessageSender};
+use shared::bot_dialogue::{DialogueCache, DialogueTrigger, clean_line};
+use shared::bot_policy::PolicyControlled;
+use shared::bots::BotProfile;
+use shared::components::health::{DeathEvent, Health, process_damage_events};
+use shared::protocol::{BotChatMessage, LobbyControlChannel, PlayerId};
+use shared::squads::SquadMember;
+
+use crate::ServerGameState;
+use crate::squads::SquadBlackboards;
+
+/// Seconds a bot stays quiet after speaking.
+const BOT_LINE_COOLDOWN_SECS: f32 = 12.0;
+/// Seconds between two lines from any bots, so chat is not flooded in a busy fight.
+const CHAT_LINE_GAP_SECS: f32 = 3.0;
+
+/// Opt-in language model for bot lines. Without one bots say template lines.
+#[derive(Resource, Clone, Debug)]
+pub struct BotDialogueSettings {
+    /// The llm crate's `llm-dialogue` executable: reads one prompt per line on stdin and
+    /// prints one line per prompt.
+    pub llm_command: Option<PathBuf>,
+    /// How long a bot waits for the model before saying a kept or template line instead.
+    pub latency_budget_secs: f32,
+}
+
+impl Default for BotDialogueSettings {
+    fn default() -> Self {
+        Self {
+            llm_command: None,
+            latency_budget_secs: 1.5,
+        }
+    }
+}
+
+/// A running `llm-dialogue` process. A worker thread feeds it one prompt at a time, so the
+/// game loop never waits on the model.
+#[derive(Resource)]
+pub struct BotDialogueBridge {
+    prompts: Sender<(DialogueTrigger, String)>,
+    replies: Mutex<Receiver<(DialogueTrigger, Option<String>)>>,
+}
+
+impl BotDialogueBridge {
+    pub fn spawn(command: &Path) -> std::io::Result<Self> {
+        let mut child = Command::new(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let (Some(mut stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            return Err(std::io::Error::other("no pipes to the process"));
+        };
+
+        let (prompts, prompt_queue) = mpsc::channel::<(DialogueTrigger, String)>();
+        let (reply, replies) = mpsc::channel();
+        let command = command.to_path_buf();
+        std::thread::spawn(move || {
+            let mut stdout = BufReader::new(stdout);
+            for (trigger, prompt) in prompt_queue {
+                let mut line = String::new();
+                let answered = writeln!(stdin, "{}", prompt)
+                    .and_then(|_| stdin.flush())
+                    .and_then(|_| stdout.read_line(&mut line));
+                if !matches!(answered, Ok(read) if read > 0) {
+                    warn!(
+                        "{} stopped answering, bots keep their lines",
+                        command.display()
+                    );
+                    let _ = reply.send((trigger, None));
+                    break;
+                }
+                if reply.send((trigger, clean_line(&line))).is_err() {
+                    break;
+                }
+            }
+            let _ = child.kill();
+        });
+
+        Ok(Self {
+            prompts,
+            replies: Mutex::new(replies),
+        })
+    }
+
+    /// Queues a prompt. False once the process is gone.
+    fn request(&self, trigger: DialogueTrigger) -> bool {
+        self.prompts.send((trigger, trigger.prompt())).is_ok()
+    }
+
+    /// The next reply, if one came. Disconnected once the process is gone.
+    fn try_reply(&self) -> Result<(DialogueTrigger, Option<String>), TryRecvError> {
+        self.replies
+            .lock()
+            .map_err(|_| TryRecvError::Disconnected)?
+            .try_recv()
+    }
+}
+
+/// A line a bot is about to say, waiting for the model until `deadline_secs`.
+struct PendingLine {
+    speaker: String,
+    trigger: DialogueTrigger,
+    deadline_secs: f32,
+}
+
+#[derive(Resource, Default)]
+struct BotDialogue {
+    cache: DialogueCache,
+    pending: Vec<PendingLine>,
+    /// Triggers with a prompt sent to the model and not answered yet. One at a time each,
+    /// so a slow model does not pile up prompts.
+    in_flight: HashSet<DialogueTrigger>,
+    next_line_secs: HashMap<Entity, f32>,
+    quiet_until_secs: f32,
+    /// Enemies each squad knew about at the last check.
+    known_enemies: HashMap<u32, HashSet<Entity>>,
+}
+
+impl BotDialogue {
+    /// Starts a line for `bot`, unless it or the chat spoke too recently. Lines come from
+    /// the model while there are fewer than a full cache of them.
+    fn start_line(
+        &mut self,
+        bot: Entity,
+        speaker: &str,
+        trigger: DialogueTrigger,
+        now: f32,
+        bridge: Option<&BotDialogueBridge>,
+        budget_secs: f32,
+    ) {
+        if now < self.quiet_until_secs || self.next_line_secs.get(&bot).is_some_and(|&t| now < t) {
+            return;
+        }
+        self.quiet_until_secs = now + CHAT_LINE_GAP_SECS;
+        self.next_line_secs
+            .insert(bot, now + BOT_LINE_COOLDOWN_SECS);
+
+        let mut deadline_secs = now;
+        if let Some(bridge) = bridge
+            && !self.cache.is_full(trigger)
+            && (self.in_flight.contains(&trigger) || bridge.request(trigger))
+        {
+            self.in_flight.insert(trigger);
+            deadline_secs += budget_secs;
+        }
+        self.pending.push(PendingLine {
+            speaker: speaker.to_string(),
+            trigger,
+            deadline_secs,
+        });
+    }
+}
+
+/// Classic AI bots taunt the players they kill and call out the ones they spot, in chat,
+/// with lines from a language model when one is configured.
+pub struct ServerBotDialoguePlugin;
+
+impl Plugin for ServerBotDialoguePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BotDialogueSettings>();
+        app.init_resource::<BotDialogue>();
+        app.add_systems(Startup, start_dialogue_model);
+        app.add_systems(OnEnter(ServerGameState::Playing), reset_bot_dialogue);
+        app.add_systems(
+            Update,
+            (start_kill_lines, start_spotted_lines, say_bot_lines)
+                .chain()
+                .after(process_damage_events)
+                .run_if(in_state(ServerGameState::Playing)),
+        );
+    }
+}
+
+fn start_dialogue_model(mut commands: Commands, settings: Res<BotDialogueSettings>) {
+    let Some(command) = &settings.llm_command else {
+        return;
+    };
+    match BotDialogueBridge::spawn(command) {
+        Ok(bridge) => {
+            info!("Bot lines from {}", command.display());
+            commands.insert_resource(bridge);
+        }
+        Err(e) => error!("Failed to run {}: {}", command.display(), e),
+    }
+}
+
+/// Lines and cooldowns of the last match do not carry over; kept model lines do.
+fn reset_bot_dialogue(mut dialogue: ResMut<BotDialogue>) {
+    dialogue.pending.clear();
+    dialogue.next_line_secs.clear();
+    dialogue.quiet_until_secs = 0.0;
+    dialogue.known_enemies.clear();
+}
+
+type ClassicBotData<'a> = (&'a Name, Option<&'a PolicyControlled>);
+
+fn is_classic(policy: Option<&PolicyControlled>) -> bool {
+    policy.is_none_or(|policy| !policy.active)
+}
+
+fn start_kill_lines(
+    time: Res<Time>,
+    settings: Res<BotDialogueSettings>,
+    bridge: Option<Res<BotDialogueBridge>>,
+    mut dialogue: ResMut<BotDialogue>,
+    mut death_events: MessageReader<DeathEvent>,
+    bots: Query<ClassicBotData, With<BotProfile>>,
+    players: Query<(), With<PlayerId>>,
+) {
+    let now = time.elapsed_secs();
+    for event in death_events.read() {
+        let Some(bot) = event.source.filter(|_| players.contains(event.target)) else {
+            continue;
+        };
+        let Ok((name, policy)) = bots.get(bot) else {
+            continue;
+        };
+        if is_classic(policy) {
+            dialogue.start_line(
+                bot,
+                name.as_str(),
+                DialogueTrigger::Kill,
+                now,
+                bridge.as_deref(),
+                settings.latency_budget_secs,
+            );
+        }
+    }
+}
+
+/// The first living member of a squad calls out enemies the squad did not know about.
+fn start_spotted_lines(
+    time: Res<Time>,
+    settings: Res<BotDialogueSettings>,
+    bridge: Option<Res<BotDialogueBridge>>,
+    blackboards: Res<SquadBlackboards>,
+    mut dialogue: ResMut<BotDialogue>,
+    members: Query<(Entity, ClassicBotData, &SquadMember, &Health), With<BotProfile>>,
+) {
+    let now = time.elapsed_secs();
+    let dialogue = &mut *dialogue;
+    dialogue
+        .known_enemies
+        .retain(|squad, _| blackboards.squads.contains_key(squad));
+    for (&squad, blackboard) in &blackboards.squads {
+        let known: HashSet<Entity> = blackboard
+            .known_enemies
+            .iter()
+            .map(|known| known.enemy)
+            .collect();
+        let previous = dialogue.known_enemies.insert(squad, known.clone());
+        if known.is_empty() || previous.is_some_and(|previous| known.is_subset(&previous)) {
+            continue;
+        }
+
+        let speaker = members
+            .iter()
+            .filter(|(_, (_, policy), member, health)| {
+                member.squad == squad && !health.is_dead && is_classic(*policy)
+            })
+            .min_by_key(|(_, _, member, _)| member.index);
+        if let Some((bot, (name, _), ..)) = speaker {
+            dialogue.start_line(
+                bot,
+                name.as_str(),
+                DialogueTrigger::Spotted,
+                now,
+                bridge.as_deref(),
+                settings.latency_budget_secs,
+            );
+        }
+    }
+}
+
+/// Keeps model replies and says each pending line: the reply it waited for, or a kept or
+/// template line once out of time.
+fn say_bot_lines(
+    mut commands: Commands,
+    time: Res<Time>,
+    bridge: Option<Res<BotDialogueBridge>>,
+    mut dialogue: ResMut<BotDialogue>,
+    mut sender: ServerMultiMessageSender,
+    server: Single<&Server>,
+) {
+    let now = time.elapsed_secs();
+    let server = server.into_inner();
+    let dialogue = &mut *dialogue;
+
+    let mut fresh = Vec::new();
+    if let Some(bridge) = &bridge {
+        loop {
+            match bridge.try_reply() {
+                Ok((trigger, reply)) => {
+                    dialogue.in_flight.remove(&trigger);
+                    if let Some(line) = reply {
+                        dialogue.cache.insert(trigger, line.clone());
+                        fresh.push((trigger, line));
+                    }
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    // Lines still waiting get kept or template ones when their time is up.
+                    commands.remove_resource::<BotDialogueBridge>();
+                    dialogue.in_flight.clear();
+                    break;
+                }
+            }
+        }
+    }
+
+    let mut said = Vec::new();
+    dialogue.pending.retain(|pending| {
+        let reply = fresh
+            .iter()
+            .position(|(trigger, _)| *trigger == pending.trigger)
+            .map(|index| fresh.remove(index).1);
+        let text = match reply {
+            Some(text) => text,
+            None if now >= pending.deadline_secs => dialogue.cache.next_line(pending.trigger),
+            None => return true,
+        };
+        said.push(BotChatMessage {
+            speaker: pending.speaker.clone(),
+            text,
+        });
+        false
+    });
+
+    for message in said {
+        info!("💬 {}: {}", message.speaker, message.text);
+        sender
+            .send::<BotChatMessage, LobbyControlChannel>(&message, server, &NetworkTarget::All)
+            .unwrap_or_else(|e| {
+                error!("Failed to send message: {:?}", e);
+            });
+    }
+}
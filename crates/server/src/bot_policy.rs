@@ -0,0 +1,252 @@
+use avian3d::prelude::{LinearVelocity, Position, Rotation};
+use bevy::prelude::{
+    App, Commands, Entity, FixedUpdate, IntoScheduleConfigs, Local, Plugin, Quat, Query, Res,
+    ResMut, Resource, Startup, With, Without, in_state, info, warn,
+};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use shared::bot_policy::{BotObservation, BotPolicy, PolicyControlled};
+use shared::bots::BotProfile;
+use shared::components::health::Health;
+use shared::game_math::{yaw_facing, yaw_of};
+use shared::navigation::SimpleNavigationAgent;
+use shared::protocol::PlayerId;
+
+use crate::ServerGameState;
+
+const DEFAULT_TICK_BUDGET: Duration = Duration::from_millis(2);
+const DEFAULT_POLICY_BOT_SPEED: f32 = 3.0;
+
+/// Opt-in control of Classic bots by a trained policy checkpoint. Disabled unless
+/// `checkpoint` is set.
+#[derive(Resource, Clone, Debug)]
+pub struct BotPolicySettings {
+    pub checkpoint: Option<PathBuf>,
+    /// Inference time allowed per fixed tick for all bots together. Bots that did not get
+    /// their turn before the budget ran out follow their scripted patrol for that tick.
+    pub tick_budget: Duration,
+}
+
+impl Default for BotPolicySettings {
+    fn default() -> Self {
+        Self {
+            checkpoint: None,
+            tick_budget: DEFAULT_TICK_BUDGET,
+        }
+    }
+}
+
+impl BotPolicySettings {
+    pub fn from_checkpoint(path: impl Into<PathBuf>) -> Self {
+        Self {
+            checkpoint: Some(path.into()),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct LoadedBotPolicy(Option<BotPolicy>);
+
+pub struct ServerBotPolicyPlugin;
+
+impl Plugin for ServerBotPolicyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BotPolicySettings>();
+        app.init_resource::<LoadedBotPolicy>();
+        app.add_systems(Startup, load_bot_policy);
+        app.add_systems(
+            FixedUpdate,
+            (attach_policy_to_bots, drive_policy_bots)
+                .chain()
+                .run_if(in_state(ServerGameState::Playing)),
+        );
+    }
+}
+
+fn load_bot_policy(settings: Res<BotPolicySettings>, mut loaded: ResMut<LoadedBotPolicy>) {
+    let Some(path) = &settings.checkpoint else {
+        return;
+    };
+
+    let policy = std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| BotPolicy::from_checkpoint(&text));
+    match policy {
+        Ok(policy) => {
+            info!("🧠 Loaded bot policy from {}", path.display());
+            loaded.0 = Some(policy);
+        }
+        Err(e) => {
+            warn!(
+                "Failed to load bot policy from {}, bots stay scripted: {}",
+                path.display(),
+                e
+            );
+        }
+    }
+}
+
+fn attach_policy_to_bots(
+    mut commands: Commands,
+    loaded: Res<LoadedBotPolicy>,
+    bots: Query<Entity, (With<BotProfile>, Without<PolicyControlled>)>,
+) {
+    if loaded.0.is_none() {
+        return;
+    }
+
+    for bot in &bots {
+        commands.entity(bot).insert(PolicyControlled::default());
+    }
+}
+
+/// Steer every policy bot along the policy's chosen direction through its velocity, so
+/// physics keeps it out of walls, handing bots back to their scripted patrol once this
+/// tick's inference budget is spent.
+fn drive_policy_bots(
+    settings: Res<BotPolicySettings>,
+    loaded: Res<LoadedBotPolicy>,
+    mut warned: Local<bool>,
+    mut bots: Query<
+        (
+            &mut PolicyControlled,
+            &Position,
+            &mut Rotation,
+            &mut LinearVelocity,
+            &Health,
+            Option<&SimpleNavigationAgent>,
+        ),
+        Without<PlayerId>,
+    >,
+    players: Query<(&Position, &Health), With<PlayerId>>,
+) {
+    let Some(policy) = &loaded.0 else {
+        return;
+    };
+
+    let started = Instant::now();
+    let mut over_budget = false;
+    for (mut control, position, mut rotation, mut velocity, health, nav_agent) in bots.iter_mut() {
+        over_budget = over_budget || started.elapsed() >= settings.tick_budget;
+        if over_budget || health.is_dead {
+            if control.active {
+                // The scripted patrol moves bots itself; do not leave them drifting.
+                velocity.0.x = 0.0;
+                velocity.0.z = 0.0;
+            }
+            control.active = false;
+            continue;
+        }
+
+        let target_offset = players
+            .iter()
+            .filter(|(_, player_health)| !player_health.is_dead)
+            .map(|(player_position, _)| player_position.0 - position.0)
+            .min_by(|a, b| a.length_squared().total_cmp(&b.length_squared()));
+        let observation = BotObservation {
+            target_offset,
+            health_fraction: health.current / health.max.max(1.0),
+            yaw: yaw_of(rotation.0),
+        };
+
+        control.active = true;
+        let direction = policy.act(&observation);
+        let speed = nav_agent.map_or(DEFAULT_POLICY_BOT_SPEED, |agent| agent.speed);
+        let planar = direction.unwrap_or_default() * speed;
+        velocity.0.x = planar.x;
+        velocity.0.z = planar.z;
+        if let Some(direction) = direction {
+            rotation.0 = Quat::from_rotation_y(yaw_facing(direction));
+        }
+    }
+
+    if over_budget && !*warned {
+        *warned = true;
+        warn!(
+            "Bot policy exceeded its {:?} tick budget, falling back to scripted bots",
+            settings.tick_budget
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BotPolicySettings, LoadedBotPolicy, drive_policy_bots};
+    use avian3d::prelude::{LinearVelocity, Position, Rotation};
+    use bevy::prelude::{App, MinimalPlugins, Update, Vec3};
+    use lightyear::prelude::PeerId;
+    use shared::bot_policy::{ACTION_SIZE, BotPolicy, OBSERVATION_SIZE, PolicyControlled};
+    use shared::components::health::Health;
+    use shared::protocol::PlayerId;
+    use std::time::Duration;
+
+    /// Walks along +x whenever a target is visible.
+    fn walk_x_policy() -> BotPolicy {
+        let mut weights1 = vec![0.0; OBSERVATION_SIZE];
+        weights1[2] = 1.0;
+        let weights2 = vec![1.0, 0.0];
+        BotPolicy::new(1, weights1, vec![0.0], weights2, vec![0.0; ACTION_SIZE])
+            .expect("policy sizes should match")
+    }
+
+    fn app_with_bot(tick_budget: Duration) -> (App, bevy::prelude::Entity) {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.insert_resource(BotPolicySettings {
+            checkpoint: None,
+            tick_budget,
+        });
+        app.insert_resource(LoadedBotPolicy(Some(walk_x_policy())));
+        app.add_systems(Update, drive_policy_bots);
+
+        app.world_mut().spawn((
+            PlayerId(PeerId::Netcode(1)),
+            Position::new(Vec3::new(10.0, 1.0, 0.0)),
+            Health::basic(),
+        ));
+        let bot = app
+            .world_mut()
+            .spawn((
+                PolicyControlled::default(),
+                Position::new(Vec3::new(0.0, 1.0, 0.0)),
+                Rotation::default(),
+                LinearVelocity::default(),
+                Health::basic(),
+            ))
+            .id();
+
+        for _ in 0..3 {
+            app.update();
+        }
+        (app, bot)
+    }
+
+    #[test]
+    fn policy_moves_bots_within_budget() {
+        let (app, bot) = app_with_bot(Duration::from_secs(1));
+
+        let control = app.world().get::<PolicyControlled>(bot).unwrap();
+        let velocity = app.world().get::<LinearVelocity>(bot).unwrap();
+        assert!(control.active);
+        assert!(
+            velocity.0.x > 0.1,
+            "bot should walk along +x, got {:?}",
+            velocity.0
+        );
+        // Physics moves the bot, so it never goes through walls.
+        let position = app.world().get::<Position>(bot).unwrap();
+        assert_eq!(position.0, Vec3::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn exhausted_budget_falls_back_to_scripted_movement() {
+        let (app, bot) = app_with_bot(Duration::ZERO);
+
+        let control = app.world().get::<PolicyControlled>(bot).unwrap();
+        let velocity = app.world().get::<LinearVelocity>(bot).unwrap();
+        assert!(!control.active);
+        assert_eq!(velocity.0, Vec3::ZERO);
+    }
+}
@@ -0,0 +1,680 @@
+use avian3d::prelude::Position;
+use bevy::diagnostic::DiagnosticsStore;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::{
+    Added, App, Commands, Entity, Name, Plugin, Query, Res, ResMut, Resource, Single, Startup,
+    State, Update, Vec3, With, error, info, warn,
+};
+use std::collections::HashSet;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::Mutex;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::Duration;
+
+use lightyear::connection::client_of::ClientOf;
+use lightyear::prelude::{
+    Connected, Disconnect, NetworkTarget, RemoteId, Server, ServerMultiMessageSender,
+};
+use shared::bots::{BotProfile, MAX_BOT_COUNT, MatchBotSettings, spawn_classic_ai_bot};
+use shared::components::health::{Health, Respawnable};
+use shared::cpu_profile::{cpu_breakdown, cpu_profile_csv, format_cpu_breakdown};
+use shared::level::generation::LevelGeometry;
+use shared::protocol::{LevelSeed, LobbyControlChannel, PlayerId, ServerAnnouncement};
+use shared::squads::{SquadManeuver, SquadMember};
+
+use crate::ServerGameState;
+use crate::director::AiDirector;
+use crate::lobby::{NextMatchSeed, transition_to_loading};
+use crate::network::rate_limit::RateLimitMetrics;
+use crate::scheduled_messages::{ScheduledMessages, ensure_scheduled_messages};
+use crate::squads::SquadBlackboards;
+use crate::visibility::LineOfSightMetrics;
+
+/// Directory `profile csv` writes into, relative to the server's working directory.
+pub const PROFILE_DIR: &str = "profiles";
+
+/// How long a console thread waits for the game loop to run its command.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub const CONSOLE_HELP: &str = "commands: status | kick <player id or name> | ban <player id> \
+    | map <seed> | bots add <count> | bots squad <count> [crossfire|pincer] | say <message> | start | profile [csv <name>] | help";
+
+/// Where the admin console reads commands from. Both sources are off unless enabled; the
+/// dedicated server turns on stdin.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct AdminConsoleSettings {
+    pub stdin: bool,
+    pub addr: Option<SocketAddr>,
+}
+
+impl AdminConsoleSettings {
+    /// Accept admin connections on `port`. Loopback only: the socket has no authentication.
+    pub fn on_port(mut self, port: u16) -> Self {
+        self.addr = Some(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port));
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConsoleCommand {
+    Status,
+    /// Player id or display name.
+    Kick(String),
+    Ban(u64),
+    Map(u64),
+    AddBots(usize),
+    /// Bots spawned together as one squad, for this match only.
+    AddSquad(usize, SquadManeuver),
+    Say(String),
+    Start,
+    /// Time per module over the recent frames.
+    Profile,
+    /// Writes the recent frames' timings, per module, to a CSV file of this name in
+    /// [`PROFILE_DIR`] on the server.
+    ProfileCsv(String),
+    Help,
+}
+
+impl ConsoleCommand {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let line = line.trim();
+        let (name, rest) = line
+            .split_once(char::is_whitespace)
+            .map_or((line, ""), |(name, rest)| (name, rest.trim()));
+        match name {
+            "status" => Ok(ConsoleCommand::Status),
+            "start" => Ok(ConsoleCommand::Start),
+            "help" => Ok(ConsoleCommand::Help),
+            "kick" if !rest.is_empty() => Ok(ConsoleCommand::Kick(rest.to_string())),
+            "ban" => rest
+                .parse()
+                .map(ConsoleCommand::Ban)
+                .map_err(|_| format!("ban: `{}` is not a player id", rest)),
+            "map" => rest
+                .parse()
+                .map(ConsoleCommand::Map)
+                .map_err(|_| format!("map: `{}` is not a seed", rest)),
+            "bots" => match rest.split_once(char::is_whitespace) {
+                Some(("add", count)) => parse_bot_count(count.trim())
+                    .map(ConsoleCommand::AddBots)
+                    .map_err(|e| format!("bots add: {}", e)),
+                Some(("squad", args)) => {
+                    let (count, maneuver) = args
+                        .trim()
+                        .split_once(char::is_whitespace)
+                        .map_or((args.trim(), None), |(count, maneuver)| {
+                            (count, Some(maneuver.trim()))
+                        });
+                    let count = parse_bot_count(count)
+                        .and_then(|count| match count {
+                            0 => Err("a squad needs at least one bot".to_string()),
+                            _ => Ok(count),
+                        })
+                        .map_err(|e| format!("bots squad: {}", e))?;
+                    let maneuver = match maneuver {
+                        Some(name) => SquadManeuver::parse(name)
+                            .ok_or_else(|| format!("bots squad: unknown maneuver `{}`", name))?,
+                        None => SquadManeuver::default(),
+                    };
+                    Ok(ConsoleCommand::AddSquad(count, maneuver))
+                }
+                _ => Err(
+                    "usage: bots add <count> | bots squad <count> [crossfire|pincer]".to_string(),
+                ),
+            },
+            "say" if !rest.is_empty() => Ok(ConsoleCommand::Say(rest.to_string())),
+            "profile" if rest.is_empty() => Ok(ConsoleCommand::Profile),
+            "profile" => match rest.split_once(char::is_whitespace) {
+                Some(("csv", name)) => {
+                    profile_file_name(name.trim()).map(ConsoleCommand::ProfileCsv)
+                }
+                _ => Err("usage: profile [csv <name>]".to_string()),
+            },
+            _ => Err(format!("unknown command `{}`; {}", line, CONSOLE_HELP)),
+        }
+    }
+}
+
+/// A bot count of at most [`MAX_BOT_COUNT`], since every bot asked for is spawned at once.
+fn parse_bot_count(count: &str) -> Result<usize, String> {
+    let count = count
+        .parse::<usize>()
+        .map_err(|_| format!("`{}` is not a count", count))?;
+    if count > MAX_BOT_COUNT {
+        return Err(format!("at most {} bots", MAX_BOT_COUNT));
+    }
+    Ok(count)
+}
+
+/// `name` as a CSV file name in [`PROFILE_DIR`]. Console commands also come over RCON, so
+/// only plain names are taken: no directories, no hidden files.
+fn profile_file_name(name: &str) -> Result<String, String> {
+    let plain = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !plain {
+        return Err(format!(
+            "profile csv: `{}` is not a plain file name (letters, digits, `-`, `_`, `.`)",
+            name
+        ));
+    }
+    Ok(if name.ends_with(".csv") {
+        name.to_string()
+    } else {
+        format!("{}.csv", name)
+    })
+}
+
+struct QueuedCommand {
+    line: String,
+    reply: Sender<String>,
+}
+
+/// Commands waiting for the game loop. Console threads only queue lines and wait for the
+/// reply, so every command runs on the game thread with full ECS access.
+#[derive(Resource)]
+pub struct ConsoleQueue {
+    sender: Sender<QueuedCommand>,
+    receiver: Mutex<Receiver<QueuedCommand>>,
+}
+
+impl Default for ConsoleQueue {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            sender,
+            receiver: Mutex::new(receiver),
+        }
+    }
+}
+
+impl ConsoleQueue {
+    pub fn submitter(&self) -> ConsoleSubmitter {
+        ConsoleSubmitter(self.sender.clone())
+    }
+}
+
+/// Handle console threads use to run a command.
+#[derive(Clone)]
+pub struct ConsoleSubmitter(Sender<QueuedCommand>);
+
+impl ConsoleSubmitter {
+    /// Queue `line` and block until the game loop has run it. Returns the command's output.
+    pub fn run(&self, line: &str) -> String {
+        let (reply, replies) = mpsc::channel();
+        let queued = QueuedCommand {
+            line: line.to_string(),
+            reply,
+        };
+        if self.0.send(queued).is_err() {
+            return "the server is shutting down".to_string();
+        }
+        replies
+            .recv_timeout(REPLY_TIMEOUT)
+            .unwrap_or_else(|_| "no reply from the game loop".to_string())
+    }
+}
+
+/// Player ids that may not connect.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct BanList(pub HashSet<u64>);
+
+/// Operator console for headless dedicated servers: `status`, `kick`, `ban`, `map`,
+/// `bots add`, `say`, `start` and `profile`, read from stdin and/or a loopback TCP socket.
+pub struct ServerConsolePlugin;
+
+impl Plugin for ServerConsolePlugin {
+    fn build(&self, app: &mut App) {
+        ensure_scheduled_messages(app);
+        app.init_resource::<AdminConsoleSettings>();
+        app.init_resource::<ConsoleQueue>();
+        app.init_resource::<BanList>();
+        app.add_systems(Startup, start_admin_console);
+        app.add_systems(Update, (run_console_commands, disconnect_banned_clients));
+    }
+}
+
+fn start_admin_console(settings: Res<AdminConsoleSettings>, queue: Res<ConsoleQueue>) {
+    if settings.stdin {
+        let submitter = queue.submitter();
+        std::thread::spawn(move || {
+            for line in io::stdin().lock().lines().map_while(Result::ok) {
+                if !line.trim().is_empty() {
+                    println!("{}", submitter.run(&line));
+                }
+            }
+        });
+        info!(
+            "🖥️  Admin console reading commands from stdin ({})",
+            CONSOLE_HELP
+        );
+    }
+
+    let Some(addr) = settings.addr else {
+        return;
+    };
+    match TcpListener::bind(addr) {
+        Ok(listener) => {
+            info!("🖥️  Admin console listening on {}", addr);
+            let submitter = queue.submitter();
+            std::thread::spawn(move || {
+                for connection in listener.incoming().flatten() {
+                    let submitter = submitter.clone();
+                    std::thread::spawn(move || serve_admin_connection(connection, submitter));
+                }
+            });
+        }
+        Err(e) => error!("Failed to start the admin console on {}: {}", addr, e),
+    }
+}
+
+fn serve_admin_connection(connection: TcpStream, submitter: ConsoleSubmitter) {
+    let Ok(mut writer) = connection.try_clone() else {
+        return;
+    };
+    for line in BufReader::new(connection).lines().map_while(Result::ok) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if writeln!(writer, "{}", submitter.run(&line)).is_err() {
+            return;
+        }
+    }
+}
+
+#[derive(SystemParam)]
+pub struct ConsoleTargets<'w, 's> {
+    pub clients: Query<'w, 's, (Entity, &'static RemoteId), (With<ClientOf>, With<Connected>)>,
+    pub players: Query<'w, 's, (&'static PlayerId, &'static Name, &'static Health)>,
+    pub bots: Query<
+        'w,
+        's,
+        (
+            &'static Health,
+            &'static Position,
+            Option<&'static Respawnable>,
+        ),
+        With<BotProfile>,
+    >,
+    pub level_seed: Query<'w, 's, &'static LevelSeed>,
+    pub line_of_sight: Option<Res<'w, LineOfSightMetrics>>,
+    pub director: Option<Res<'w, AiDirector>>,
+}
+
+impl ConsoleTargets<'_, '_> {
+    /// The connection of the player with this id or display name.
+    fn find_client(&self, target: &str) -> Option<(Entity, u64)> {
+        let player_id = target.parse::<u64>().ok().or_else(|| {
+            self.players
+                .iter()
+                .find(|(_, name, _)| name.as_str().eq_ignore_ascii_case(target))
+                .map(|(player_id, ..)| player_id.0.to_bits())
+        })?;
+        self.clients
+            .iter()
+            .find(|(_, remote_id)| remote_id.0.to_bits() == player_id)
+            .map(|(entity, _)| (entity, player_id))
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_console_commands(
+    mut commands: Commands,
+    queue: Res<ConsoleQueue>,
+    server_state: Res<State<ServerGameState>>,
+    mut next_seed: ResMut<NextMatchSeed>,
+    mut bot_settings: ResMut<MatchBotSettings>,
+    mut bans: ResMut<BanList>,
+    metrics: Option<Res<RateLimitMetrics>>,
+    diagnostics: Option<Res<DiagnosticsStore>>,
+    targets: ConsoleTargets,
+    mut sender: ServerMultiMessageSender,
+    server: Single<&Server>,
+    mut scheduled: ResMut<ScheduledMessages>,
+    mut squads: ResMut<SquadBlackboards>,
+) {
+    let server = server.into_inner();
+    let Ok(receiver) = queue.receiver.lock() else {
+        return;
+    };
+
+    for queued in receiver.try_iter() {
+        let command = match ConsoleCommand::parse(&queued.line) {
+            Ok(command) => command,
+            Err(usage) => {
+                let _ = queued.reply.send(usage);
+                continue;
+            }
+        };
+        info!("Admin console: {}", queued.line.trim());
+
+        let reply = match command {
+            ConsoleCommand::Help => CONSOLE_HELP.to_string(),
+            ConsoleCommand::Status => format_status(
+                server_state.get(),
+                &next_seed,
+                &bot_settings,
+                &bans,
+                metrics.as_deref(),
+                &targets,
+            ),
+            ConsoleCommand::Kick(target) => match targets.find_client(&target) {
+                Some((connection, player_id)) => {
+                    commands.trigger(Disconnect { entity: connection });
+                    format!("kicked player {}", player_id)
+                }
+                None => format!("no connected player `{}`", target),
+            },
+            ConsoleCommand::Ban(player_id) => {
+                bans.0.insert(player_id);
+                match targets.find_client(&player_id.to_string()) {
+                    Some((connection, _)) => {
+                        commands.trigger(Disconnect { entity: connection });
+                        format!("banned and kicked player {}", player_id)
+                    }
+                    None => format!("banned player {}", player_id),
+                }
+            }
+            ConsoleCommand::Map(seed) => {
+                next_seed.0 = seed;
+                match server_state.get() {
+                    ServerGameState::Lobby => format!("the next match uses seed {}", seed),
+                    _ => format!(
+                        "seed {} applies from the next match; this one keeps its level",
+                        seed
+                    ),
+                }
+            }
+            ConsoleCommand::AddBots(count) => {
+                let previous = bot_settings.bot_count;
+                bot_settings.bot_count = previous.saturating_add(count).min(MAX_BOT_COUNT);
+                let count = bot_settings.bot_count.saturating_sub(previous);
+                if *server_state.get() == ServerGameState::Playing {
+                    let spawned =
+                        spawn_extra_bots(&mut commands, &targets, count, &bot_settings, None);
+                    format!(
+                        "{} bots per match; spawned {} now",
+                        bot_settings.bot_count, spawned
+                    )
+                } else {
+                    format!("{} bots per match", bot_settings.bot_count)
+                }
+            }
+            ConsoleCommand::AddSquad(count, maneuver) => {
+                if *server_state.get() == ServerGameState::Playing {
+                    let squad = squads.new_squad();
+                    let spawned = spawn_extra_bots(
+                        &mut commands,
+                        &targets,
+                        count,
+                        &bot_settings,
+                        Some((squad, maneuver)),
+                    );
+                    format!(
+                        "spawned squad {} of {} bots, maneuver {}",
+                        squad,
+                        spawned,
+                        maneuver.label()
+                    )
+                } else {
+                    "squads spawn during a match".to_string()
+                }
+            }
+            ConsoleCommand::Say(text) => {
+                info!("📢 [Server] {}", text);
+                sender
+                    .send::<ServerAnnouncement, LobbyControlChannel>(
+                        &ServerAnnouncement { text },
+                        server,
+                        &NetworkTarget::All,
+                    )
+                    .unwrap_or_else(|e| {
+                        error!("Failed to send message: {:?}", e);
+                    });
+                "announced".to_string()
+            }
+            ConsoleCommand::Profile => format_cpu_breakdown(
+                &diagnostics
+                    .as_deref()
+                    .map(cpu_breakdown)
+                    .unwrap_or_default(),
+            ),
+            ConsoleCommand::ProfileCsv(name) => {
+                let stats = diagnostics
+                    .as_deref()
+                    .map(cpu_breakdown)
+                    .unwrap_or_default();
+                let frames = stats
+                    .iter()
+                    .map(|section| section.history_ms.len())
+                    .max()
+                    .unwrap_or(0);
+                let path = std::path::Path::new(PROFILE_DIR).join(&name);
+                match std::fs::create_dir_all(PROFILE_DIR)
+                    .and_then(|()| std::fs::write(&path, cpu_profile_csv(&stats)))
+                {
+                    Ok(()) => format!("wrote {} frames to {}", frames, path.display()),
+                    Err(e) => format!("could not write {}: {}", path.display(), e),
+                }
+            }
+            ConsoleCommand::Start => {
+                if *server_state.get() == ServerGameState::Lobby {
+                    transition_to_loading(&mut commands, &mut scheduled, next_seed.0);
+                    format!("starting a match with seed {}", next_seed.0)
+                } else {
+                    "a match is already running".to_string()
+                }
+            }
+        };
+        let _ = queued.reply.send(reply);
+    }
+}
+
+/// Spawn `count` bots next to the existing bots' spawn points, or all next to one of them
+/// as members of `squad`. Returns how many spawned.
+fn spawn_extra_bots(
+    commands: &mut Commands,
+    targets: &ConsoleTargets,
+    count: usize,
+    bot_settings: &MatchBotSettings,
+    squad: Option<(u32, SquadManeuver)>,
+) -> usize {
+    let spawn_points: Vec<Vec3> = targets
+        .bots
+        .iter()
+        .map(|(_, position, respawnable)| {
+            respawnable
+                .and_then(|respawnable| respawnable.respawn_position)
+                .unwrap_or(position.0)
+        })
+        .collect();
+    if spawn_points.is_empty() {
+        warn!("No bot spawn points on this level; extra bots join next match");
+        return 0;
+    }
+
+    for index in 0..count {
+        let (anchor, spread) = match squad {
+            Some((squad, _)) => (spawn_points[squad as usize % spawn_points.len()], index),
+            None => (spawn_points[index % spawn_points.len()], 0),
+        };
+        let position = anchor + Vec3::new(1.5 * (1 + spread) as f32, 0.0, 1.5);
+        let name = format!("ConsoleBot_{}", spawn_points.len() + index);
+        // No route: the bot patrols around its spawn on the navmesh.
+        let bot = spawn_classic_ai_bot(name, position)
+            .difficulty(bot_settings.difficulty)
+            .spawn(commands);
+        commands.entity(bot).insert(LevelGeometry);
+        if let Some((squad, maneuver)) = squad {
+            commands
+                .entity(bot)
+                .insert(SquadMember::new(squad, index).with_maneuver(maneuver));
+        }
+    }
+    count
+}
+
+fn format_status(
+    state: &ServerGameState,
+    next_seed: &NextMatchSeed,
+    bot_settings: &MatchBotSettings,
+    bans: &BanList,
+    metrics: Option<&RateLimitMetrics>,
+    targets: &ConsoleTargets,
+) -> String {
+    let mut lines = vec![format!(
+        "state: {:?} | level seed: {} | next match seed: {}",
+        state,
+        targets
+            .level_seed
+            .iter()
+            .next()
+            .map_or_else(|| "-".to_string(), |level| level.seed.to_string()),
+        next_seed.0
+    )];
+
+    lines.push(format!("players: {}", targets.clients.iter().count()));
+    for (_, remote_id) in targets.clients.iter() {
+        let player_id = remote_id.0.to_bits();
+        let character = targets
+            .players
+            .iter()
+            .find(|(id, ..)| id.0.to_bits() == player_id);
+        lines.push(match character {
+            Some((_, name, health)) => format!(
+                "  {} {} ({:.0} hp{})",
+                player_id,
+                name,
+                health.current,
+                if health.is_dead { ", dead" } else { "" }
+            ),
+            None => format!("  {} (no character)", player_id),
+        });
+    }
+
+    let bots_alive = targets
+        .bots
+        .iter()
+        .filter(|(health, ..)| !health.is_dead)
+        .count();
+    lines.push(format!(
+        "bots: {} alive, {} per match ({})",
+        bots_alive,
+        bot_settings.bot_count,
+        bot_settings.difficulty.label()
+    ));
+    lines.push(format!(
+        "bans: {} | flood disconnects: {}",
+        bans.0.len(),
+        metrics.map_or(0, |metrics| metrics.abuse_disconnects)
+    ));
+    if let Some(line_of_sight) = &targets.line_of_sight {
+        lines.push(format!(
+            "line of sight cache: {:.0}% hits, {} casts last tick",
+            line_of_sight.hit_rate() * 100.0,
+            line_of_sight.last_tick_misses
+        ));
+    }
+    if let Some(director) = &targets.director {
+        lines.extend(director.status_lines());
+    }
+    lines.join("\n")
+}
+
+fn disconnect_banned_clients(
+    mut commands: Commands,
+    bans: Res<BanList>,
+    clients: Query<(Entity, &RemoteId), (With<ClientOf>, Added<Connected>)>,
+) {
+    for (connection, remote_id) in clients.iter() {
+        if bans.0.contains(&remote_id.0.to_bits()) {
+            warn!("Refusing banned client {:?}", remote_id.0);
+            commands.trigger(Disconnect { entity: connection });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConsoleCommand, ConsoleQueue};
+    use shared::squads::SquadManeuver;
+
+    #[test]
+    fn console_lines_parse_into_commands() {
+        assert_eq!(
+            ConsoleCommand::parse(" status "),
+            Ok(ConsoleCommand::Status)
+        );
+        assert_eq!(
+            ConsoleCommand::parse("kick Player_2"),
+            Ok(ConsoleCommand::Kick("Player_2".to_string()))
+        );
+        assert_eq!(ConsoleCommand::parse("ban 7"), Ok(ConsoleCommand::Ban(7)));
+        assert_eq!(
+            ConsoleCommand::parse("map 1234"),
+            Ok(ConsoleCommand::Map(1234))
+        );
+        assert_eq!(
+            ConsoleCommand::parse("bots add 3"),
+            Ok(ConsoleCommand::AddBots(3))
+        );
+        assert_eq!(
+            ConsoleCommand::parse("bots squad 4 pincer"),
+            Ok(ConsoleCommand::AddSquad(4, SquadManeuver::Pincer))
+        );
+        assert_eq!(
+            ConsoleCommand::parse("bots squad 3"),
+            Ok(ConsoleCommand::AddSquad(3, SquadManeuver::Crossfire))
+        );
+        assert_eq!(
+            ConsoleCommand::parse("say  back in 5 minutes"),
+            Ok(ConsoleCommand::Say("back in 5 minutes".to_string()))
+        );
+
+        assert_eq!(
+            ConsoleCommand::parse("profile"),
+            Ok(ConsoleCommand::Profile)
+        );
+        assert_eq!(
+            ConsoleCommand::parse("profile csv tick_times"),
+            Ok(ConsoleCommand::ProfileCsv("tick_times.csv".to_string()))
+        );
+        assert_eq!(
+            ConsoleCommand::parse("profile csv match-3.csv"),
+            Ok(ConsoleCommand::ProfileCsv("match-3.csv".to_string()))
+        );
+
+        assert!(ConsoleCommand::parse("ban everyone").is_err());
+        assert!(ConsoleCommand::parse("bots squad 0").is_err());
+        assert!(ConsoleCommand::parse("bots add 4000000000").is_err());
+        assert!(ConsoleCommand::parse("bots add 99999999999999999999999").is_err());
+        assert!(ConsoleCommand::parse("bots squad 33 pincer").is_err());
+        assert_eq!(
+            ConsoleCommand::parse("bots add 32"),
+            Ok(ConsoleCommand::AddBots(32))
+        );
+        assert!(ConsoleCommand::parse("bots squad 3 wedge").is_err());
+        assert!(ConsoleCommand::parse("profile csv").is_err());
+        assert!(ConsoleCommand::parse("profile csv /tmp/tick.csv").is_err());
+        assert!(ConsoleCommand::parse("profile csv ../Cargo.toml").is_err());
+        assert!(ConsoleCommand::parse("profile csv .bashrc").is_err());
+        assert!(ConsoleCommand::parse("bots remove 2").is_err());
+        assert!(ConsoleCommand::parse("kick").is_err());
+        assert!(ConsoleCommand::parse("rm -rf").is_err());
+    }
+
+    #[test]
+    fn submitted_lines_wait_for_the_game_loop_reply() {
+        let queue = ConsoleQueue::default();
+        let submitter = queue.submitter();
+        let console = std::thread::spawn(move || submitter.run("status"));
+
+        let queued = queue.receiver.lock().unwrap().recv().unwrap();
+        assert_eq!(queued.line, "status");
+        queued.reply.send("ok".to_string()).unwrap();
+        assert_eq!(console.join().unwrap(), "ok");
+    }
+}
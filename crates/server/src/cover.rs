@@ -0,0 +1,191 @@
+use avian3d::prelude::{
+    Collider, ColliderAabb, Position, RigidBody, SpatialQuery, SpatialQueryFilter,
+};
+use bevy::prelude::{
+    Added, App, Commands, Component, Dir3, Entity, IntoScheduleConfigs, Local, Plugin, Query, Res,
+    ResMut, Update, Vec3, With, in_state, info,
+};
+
+use shared::components::health::Health;
+use shared::cover::{
+    COVER_MIN_HEIGHT, COVER_OFFSET, COVER_SPACING, CoverPoint, CoverPoints, face_candidates,
+};
+use shared::inputs::input::PLAYER_CAPSULE_HEIGHT;
+use shared::level::generation::LevelGeometry;
+use shared::perception::Awareness;
+
+use crate::ServerGameState;
+use crate::perception::update_bot_awareness;
+use crate::visibility::LineOfSight;
+
+/// The ground under a cover point must be this close to the foot of its obstacle.
+const COVER_GROUND_TOLERANCE: f32 = 0.5;
+/// Bots look for cover up to this far away.
+const COVER_SEARCH_DISTANCE: f32 = 15.0;
+/// A bot's cover is only picked again once its threat moved this far.
+const THREAT_MOVED_DISTANCE: f32 = 2.0;
+
+/// Bakes cover points from the level's static colliders whenever level geometry is
+/// added, and finds every bot cover from what it perceives. Bots retreat there when their
+/// behavior tree says so (`TakeCover`), and squad support prefers it too.
+pub struct ServerCoverPlugin;
+
+impl Plugin for ServerCoverPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CoverPoints>();
+        app.add_systems(
+            Update,
+            (bake_cover_points, assign_bot_cover)
+                .chain()
+                .after(update_bot_awareness)
+                .run_if(in_state(ServerGameState::Playing)),
+        );
+    }
+}
+
+/// Cover a bot would retreat to from its current threat.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+pub struct BotCover {
+    /// Where the threat was when the cover was picked.
+    pub threat: Option<Vec3>,
+    pub point: Option<Vec3>,
+}
+
+/// Bake the frame after static level geometry was added, once avian has its bounds and
+/// the spatial query sees it. Props destroyed later leave their points behind; bots only
+/// take cover that still blocks the threat's view.
+fn bake_cover_points(
+    mut pending: Local<bool>,
+    mut cover: ResMut<CoverPoints>,
+    spatial_query: SpatialQuery,
+    added: Query<&RigidBody, (With<LevelGeometry>, Added<Collider>)>,
+    obstacles: Query<(Entity, &RigidBody, &ColliderAabb), With<LevelGeometry>>,
+) {
+    if added.iter().any(|body| *body == RigidBody::Static) {
+        *pending = true;
+        return;
+    }
+    if !std::mem::take(&mut *pending) {
+        return;
+    }
+
+    let filter = SpatialQueryFilter::default();
+    let mut points = Vec::new();
+    for (obstacle, body, aabb) in obstacles.iter() {
+        if *body != RigidBody::Static || aabb.max.y - aabb.min.y < COVER_MIN_HEIGHT {
+            continue;
+        }
+        for (candidate, facing) in face_candidates(aabb.min, aabb.max) {
+            // Ground right under the point, level with the foot of the obstacle.
+            let above = candidate.with_y(aabb.max.y + 0.1);
+            let Some(ground) = spatial_query.cast_ray(
+                above,
+                Dir3::NEG_Y,
+                aabb.max.y - aabb.min.y + 2.0 * COVER_GROUND_TOLERANCE,
+                true,
+                &filter,
+            ) else {
+                continue;
+            };
+            let ground_y = above.y - ground.distance;
+            if (ground_y - aabb.min.y).abs() > COVER_GROUND_TOLERANCE {
+                continue;
+            }
+
+            // The obstacle itself stands behind the point, not a gap or a slanted side.
+            let position = candidate.with_y(ground_y);
+            let probe = position + Vec3::Y * (COVER_MIN_HEIGHT * 0.5);
+            let Ok(direction) = Dir3::new(facing) else {
+                continue;
+            };
+            let behind = spatial_query
+                .cast_ray(probe, direction, COVER_OFFSET + 0.5, true, &filter)
+                .is_some_and(|hit| hit.entity == obstacle);
+            if behind {
+                points.push(CoverPoint {
+                    position,
+                    facing,
+                    height: aabb.max.y - ground_y,
+                });
+            }
+        }
+    }
+    info!("🛡️ Baked {} cover points", points.len());
+    cover.points = points;
+}
+
+/// Find every living bot cover from the enemy it engages or searches for: the nearest
+/// point facing the threat that nothing blocks the threat's view of and no other bot
+/// took. Bots with no threat have no cover.
+pub fn assign_bot_cover(
+    mut commands: Commands,
+    cover: Res<CoverPoints>,
+    line_of_sight: LineOfSight,
+    mut bots: Query<(
+        Entity,
+        &Position,
+        &Health,
+        &Awareness,
+        Option<&mut BotCover>,
+    )>,
+) {
+    let eye = Vec3::Y * (PLAYER_CAPSULE_HEIGHT * 0.5);
+    let mut taken: Vec<(Entity, Vec3)> = bots
+        .iter()
+        .filter_map(|(bot, .., bot_cover)| Some((bot, bot_cover?.point?)))
+        .collect();
+
+    for (bot, position, health, awareness, bot_cover) in bots.iter_mut() {
+        let threat = match *awareness {
+            _ if health.is_dead => None,
+            Awareness::Idle => None,
+            Awareness::Searching { last_known } => Some((bot, last_known)),
+            Awareness::Engaging { enemy, position } => Some((enemy, position)),
+        };
+        let current = bot_cover.as_deref().copied().unwrap_or_default();
+        let keep = match (threat, current.threat) {
+            (Some((_, threat)), Some(picked_for)) => {
+                threat.distance(picked_for) <= THREAT_MOVED_DISTANCE
+            }
+            (None, None) => true,
+            _ => false,
+        };
+        if keep {
+            continue;
+        }
+
+        taken.retain(|(other, _)| *other != bot);
+        let next = match threat {
+            Some((source, threat)) => {
+                let point = cover
+                    .nearest_against(position.0, threat, COVER_SEARCH_DISTANCE, |point| {
+                        !taken
+                            .iter()
+                            .any(|(_, other)| other.distance(point.position) < COVER_SPACING)
+                            && line_of_sight.occluders(
+                                bot,
+                                point.position + eye,
+                                source,
+                                threat + eye,
+                                1,
+                            ) > 0
+                    })
+                    .map(|point| point.position);
+                if let Some(point) = point {
+                    taken.push((bot, point));
+                }
+                BotCover {
+                    threat: Some(threat),
+                    point,
+                }
+            }
+            None => BotCover::default(),
+        };
+        match bot_cover {
+            Some(mut bot_cover) => *bot_cover = next,
+            None => {
+                commands.entity(bot).insert(next);
+            }
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use bevy::ecs::schedule::SystemSet;
+use bevy::prelude::{App, FixedUpdate, Plugin, Update};
+use shared::cpu_profile::CpuProfileAppExt;
+
+/// Server modules timed by the CPU profiler, on top of the network and physics phases of
+/// [`shared::cpu_profile::CpuProfilePlugin`]. The admin console's `profile` command shows
+/// the breakdown.
+#[derive(SystemSet, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ServerModuleSet {
+    Lobby,
+    Entities,
+}
+
+pub struct ServerCpuProfilePlugin;
+
+impl Plugin for ServerCpuProfilePlugin {
+    fn build(&self, app: &mut App) {
+        app.profile_set("lobby", Update, ServerModuleSet::Lobby);
+        app.profile_set("entities", FixedUpdate, ServerModuleSet::Entities);
+        app.profile_set("entities", Update, ServerModuleSet::Entities);
+    }
+}
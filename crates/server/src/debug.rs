@@ -1,11 +1,28 @@
-use bevy::prelude::{App, IntoScheduleConfigs, Plugin, Update};
+use bevy::prelude::{
+    App, IntoScheduleConfigs, Local, Plugin, Query, Res, ResMut, Resource, Time, Timer, TimerMode,
+    Update, error,
+};
 use bevy::state::condition::in_state;
+use lightyear::prelude::{NetworkTarget, Server, ServerMultiMessageSender};
 
 use shared::debug::log_gym_wandering_diagnostics;
 use shared::gym::update_gym_wandering_npc_targets;
+use shared::protocol::{NET_PROBE_INTERVAL_SECS, NetProbe, NetProbeChannel};
 
 use crate::ServerGameState;
 
+#[derive(Resource)]
+struct NetProbeTimer(Timer);
+
+impl Default for NetProbeTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(
+            NET_PROBE_INTERVAL_SECS,
+            TimerMode::Repeating,
+        ))
+    }
+}
+
 pub struct ServerDebugPlugin;
 
 impl Plugin for ServerDebugPlugin {
@@ -16,5 +33,38 @@ impl Plugin for ServerDebugPlugin {
                 .after(update_gym_wandering_npc_targets)
                 .run_if(in_state(ServerGameState::Playing)),
         );
+        app.init_resource::<NetProbeTimer>();
+        app.add_systems(Update, send_net_probes);
     }
 }
+
+/// Stream numbered probes to every client so their netgraph can measure packet loss.
+fn send_net_probes(
+    time: Res<Time>,
+    mut timer: ResMut<NetProbeTimer>,
+    mut sequence: Local<u32>,
+    mut sender: ServerMultiMessageSender,
+    server: Query<&Server>,
+) {
+    timer.0.tick(time.delta());
+    if !timer.0.just_finished() {
+        return;
+    }
+
+    let Ok(server) = server.single() else {
+        return;
+    };
+
+    sender
+        .send::<NetProbe, NetProbeChannel>(
+            &NetProbe {
+                sequence: *sequence,
+            },
+            server,
+            &NetworkTarget::All,
+        )
+        .unwrap_or_else(|e| {
+            error!("Failed to send message: {:?}", e);
+        });
+    *sequence = sequence.wrapping_add(1);
+}
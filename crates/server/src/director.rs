@@ -0,0 +1,215 @@
+use bevy::prelude::{
+    App, IntoScheduleConfigs, MessageReader, OnEnter, Plugin, Query, Res, ResMut, Resource, Update,
+    With, debug, in_state, info,
+};
+use std::fmt::Write as _;
+
+use shared::bots::{BotProfile, MatchBotSettings};
+use shared::clock::{GameClock, ensure_game_clock};
+use shared::components::health::{DamageEvent, DeathEvent, process_damage_events};
+use shared::components::weapons::ShotFired;
+use shared::director::{Director, DirectorDecision, PerformanceEvent};
+use shared::protocol::{CharacterMarker, PlayerId};
+
+use crate::ServerGameState;
+
+/// Seconds between two decisions of the director.
+const DECISION_INTERVAL_SECS: f32 = 10.0;
+
+/// Tracks how players are doing and adapts the bots to keep matches competitive: their
+/// aim, aggression and reactions follow the director's intensity, and while players
+/// struggle fewer of them respawn (see `respawn_dead_npcs`). Bots keep the profile of the
+/// match's difficulty scaled, so profiles set one by one are overridden.
+pub struct ServerDirectorPlugin;
+
+impl Plugin for ServerDirectorPlugin {
+    fn build(&self, app: &mut App) {
+        ensure_game_clock(app);
+        app.init_resource::<AiDirector>();
+        app.add_systems(OnEnter(ServerGameState::Playing), reset_director);
+        app.add_systems(
+            Update,
+            // Read deaths in the frame they happen, before dead players are despawned.
+            (record_player_performance, direct_bots)
+                .chain()
+                .after(process_damage_events)
+                .run_if(in_state(ServerGameState::Playing)),
+        );
+    }
+}
+
+/// The current match's director.
+#[derive(Resource, Debug)]
+pub struct AiDirector {
+    pub director: Director,
+    next_decision: f32,
+}
+
+impl Default for AiDirector {
+    fn default() -> Self {
+        Self {
+            director: Director::new(0.0),
+            next_decision: 0.0,
+        }
+    }
+}
+
+impl AiDirector {
+    /// Bots allowed alive at once, once the director has made a decision.
+    pub fn active_bot_cap(&self) -> Option<usize> {
+        self.director
+            .last_decision
+            .as_ref()
+            .map(|decision| decision.active_bots)
+    }
+
+    /// The director's last decision and every tracked player's window, for the console.
+    pub fn status_lines(&self) -> Vec<String> {
+        let Some(decision) = &self.director.last_decision else {
+            return vec!["director: no decision yet".to_string()];
+        };
+        let mut lines = vec![format!(
+            "director: intensity {:.2}, player skill {:+.2} ({} judged), {} bots active, \
+             bot accuracy {:.0}%",
+            decision.intensity,
+            decision.player_skill,
+            decision.players_judged,
+            decision.active_bots,
+            decision.bot_profile.accuracy * 100.0
+        )];
+        for (player_id, summary) in self.director.tracker.summaries() {
+            lines.push(format!(
+                "  {}: K/D {:.1}, accuracy {}, {:.0} damage taken",
+                player_id,
+                summary.kd(),
+                summary.accuracy().map_or_else(
+                    || "-".to_string(),
+                    |accuracy| format!("{:.0}%", accuracy * 100.0)
+                ),
+                summary.damage_taken
+            ));
+        }
+        lines
+    }
+}
+
+/// Prometheus gauges for the director's last decision, appended to `/metrics`.
+pub fn render_director_metrics(out: &mut String, decision: &DirectorDecision) {
+    for (name, help, value) in [
+        (
+            "yolo_server_director_intensity",
+            "Bot intensity chosen by the director, 1 being the match difficulty.",
+            decision.intensity,
+        ),
+        (
+            "yolo_server_director_player_skill",
+            "Mean skill of the judged players, from -1 (outmatched) to 1 (dominating).",
+            decision.player_skill,
+        ),
+        (
+            "yolo_server_director_active_bots",
+            "Bots the director allows alive at once.",
+            decision.active_bots as f32,
+        ),
+        (
+            "yolo_server_director_bot_accuracy",
+            "Accuracy the director gives the bots.",
+            decision.bot_profile.accuracy,
+        ),
+    ] {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} gauge", name);
+        let _ = writeln!(out, "{} {}", name, value);
+    }
+}
+
+fn reset_director(clock: Res<GameClock>, mut director: ResMut<AiDirector>) {
+    let now = clock.elapsed_secs();
+    *director = AiDirector {
+        director: Director::new(now),
+        next_decision: now + DECISION_INTERVAL_SECS,
+    };
+}
+
+/// Feed the director the shots, damage, kills and deaths of players. Bots are not judged.
+fn record_player_performance(
+    clock: Res<GameClock>,
+    mut director: ResMut<AiDirector>,
+    mut shots: MessageReader<ShotFired>,
+    mut damage_events: MessageReader<DamageEvent>,
+    mut death_events: MessageReader<DeathEvent>,
+    players: Query<&PlayerId>,
+    characters: Query<(), With<CharacterMarker>>,
+) {
+    let now = clock.elapsed_secs();
+    let tracker = &mut director.director.tracker;
+    let player = |entity| players.get(entity).ok().map(|id| id.0.to_bits());
+
+    for shot in shots.read() {
+        if let Some(shooter) = player(shot.shooter) {
+            let hit = shot.hit.is_some_and(|target| characters.contains(target));
+            tracker.record(shooter, now, PerformanceEvent::Shot { hit });
+        }
+    }
+    for damage in damage_events.read() {
+        if let Some(target) = player(damage.target)
+            && damage.amount > 0.0
+        {
+            tracker.record(target, now, PerformanceEvent::DamageTaken(damage.amount));
+        }
+    }
+    for death in death_events.read() {
+        // Destroyed props are not kills.
+        if !characters.contains(death.target) {
+            continue;
+        }
+        let victim = player(death.target);
+        if let Some(victim) = victim {
+            tracker.record(victim, now, PerformanceEvent::Death);
+        }
+        if let Some(killer) = death.source.and_then(player)
+            && Some(killer) != victim
+        {
+            tracker.record(killer, now, PerformanceEvent::Kill);
+        }
+    }
+}
+
+/// Every few seconds, let the director decide and give every bot the resulting profile.
+fn direct_bots(
+    clock: Res<GameClock>,
+    bot_settings: Option<Res<MatchBotSettings>>,
+    mut director: ResMut<AiDirector>,
+    mut bots: Query<&mut BotProfile>,
+) {
+    let now = clock.elapsed_secs();
+    if now < director.next_decision {
+        return;
+    }
+    director.next_decision = now + DECISION_INTERVAL_SECS;
+
+    let base = bot_settings
+        .map(|settings| settings.difficulty.profile())
+        .unwrap_or_else(|| MatchBotSettings::default().difficulty.profile());
+    let previous = director
+        .director
+        .last_decision
+        .as_ref()
+        .map(|decision| decision.intensity);
+    let decision = director.director.decide(now, base, bots.iter().count());
+    for mut profile in bots.iter_mut() {
+        if *profile != decision.bot_profile {
+            *profile = decision.bot_profile;
+        }
+    }
+
+    let summary = format!(
+        "player skill {:+.2} over {} players, intensity {:.2}, {} bots active",
+        decision.player_skill, decision.players_judged, decision.intensity, decision.active_bots
+    );
+    if previous.is_none_or(|intensity| intensity != decision.intensity) {
+        info!("🎬 Director: {}", summary);
+    } else {
+        debug!("🎬 Director: {}", summary);
+    }
+}
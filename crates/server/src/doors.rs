@@ -0,0 +1,388 @@
+use avian3d::prelude::Position;
+use bevy::prelude::{
+    App, Commands, Component, Entity, IntoScheduleConfigs, MessageWriter, Plugin, Query, Res,
+    Update, Vec2, Vec3, With, Without, debug, in_state,
+};
+
+use shared::bot_policy::PolicyControlled;
+use shared::bots::BotProfile;
+use shared::clock::{GameClock, ensure_game_clock};
+use shared::components::health::Health;
+use shared::level::interactive::{Door, UseRequest};
+use shared::navigation::{
+    DoorLink, NavigationDetour, NavigationPathState, SimpleNavigationAgent, planar_distance,
+};
+
+use crate::ServerGameState;
+
+/// Closed doors further along a bot's path than this are left for later.
+const DOOR_LOOKAHEAD: f32 = 6.0;
+/// Time a bot gets to reach the spot it works a door from.
+const DOOR_APPROACH_SECS: f32 = 8.0;
+/// Time a door gets to open after a bot works it, before the bot tries again.
+const DOOR_OPEN_WAIT_SECS: f32 = 2.5;
+/// Times a bot works a door before giving up on it.
+const MAX_DOOR_USES: u32 = 2;
+/// A door a bot gave up on is left alone this long, so it goes somewhere else meanwhile.
+const DOOR_RETRY_SECS: f32 = 10.0;
+
+/// Bots open the closed doors on their path. Doors are no navmesh obstacles, so paths run
+/// through them; a bot whose path crosses a closed [`DoorLink`] door walks to where it
+/// works it, uses it, and waits for it to open before going on. A door that will not
+/// open is given up on, and the bot picks another target.
+pub struct ServerDoorsPlugin;
+
+impl Plugin for ServerDoorsPlugin {
+    fn build(&self, app: &mut App) {
+        ensure_game_clock(app);
+        app.add_systems(
+            Update,
+            (plan_door_transits, drive_door_transits)
+                .chain()
+                .run_if(in_state(ServerGameState::Playing)),
+        );
+    }
+}
+
+/// A bot on its way through a closed door. While it lasts the bot is on a
+/// [`NavigationDetour`] to `use_at`.
+#[derive(Component, Clone, Debug, PartialEq)]
+struct DoorTransit {
+    door: Entity,
+    use_at: Vec3,
+    stage: DoorStage,
+    stage_secs: f32,
+    uses: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DoorStage {
+    /// Walking to `use_at`.
+    Approach,
+    /// Worked the door, waiting for it to open.
+    Wait,
+}
+
+/// What a bot in a [`DoorTransit`] does next.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DoorStep {
+    Wait,
+    Use,
+    /// The door is open: back to the path.
+    Through,
+    GiveUp,
+}
+
+impl DoorTransit {
+    fn new(door: Entity, use_at: Vec3) -> Self {
+        Self {
+            door,
+            use_at,
+            stage: DoorStage::Approach,
+            stage_secs: 0.0,
+            uses: 0,
+        }
+    }
+
+    /// Step `dt` seconds on with the bot at `use_at` or not, and the door as it is.
+    fn advance(&mut self, dt: f32, arrived: bool, door: &Door) -> DoorStep {
+        if door.progress >= 1.0 {
+            return DoorStep::Through;
+        }
+        self.stage_secs += dt;
+        match self.stage {
+            // Someone else is opening it: wait at the door.
+            DoorStage::Approach if door.open && arrived => DoorStep::Wait,
+            DoorStage::Approach if arrived => {
+                self.stage = DoorStage::Wait;
+                self.stage_secs = 0.0;
+                self.uses += 1;
+                DoorStep::Use
+            }
+            DoorStage::Approach if self.stage_secs >= DOOR_APPROACH_SECS => DoorStep::GiveUp,
+            DoorStage::Wait if self.stage_secs >= DOOR_OPEN_WAIT_SECS => {
+                if door.open {
+                    // Still sliding; give it another wait.
+                    self.stage_secs = 0.0;
+                    DoorStep::Wait
+                } else if self.uses >= MAX_DOOR_USES {
+                    DoorStep::GiveUp
+                } else {
+                    self.stage_secs = 0.0;
+                    self.uses += 1;
+                    DoorStep::Use
+                }
+            }
+            _ => DoorStep::Wait,
+        }
+    }
+}
+
+/// Doors a bot gave up on, with the seconds left before it tries them again.
+#[derive(Component, Clone, Debug, Default)]
+struct AvoidedDoors(Vec<(Entity, f32)>);
+
+impl AvoidedDoors {
+    fn contains(&self, door: Entity) -> bool {
+        self.0.iter().any(|(avoided, _)| *avoided == door)
+    }
+}
+
+/// Fraction of the way along `a`→`b` at which it enters the ground-plane box from `min` to
+/// `max`, if it does.
+fn segment_entry(a: Vec2, b: Vec2, min: Vec2, max: Vec2) -> Option<f32> {
+    let delta = b - a;
+    let (mut enter, mut exit) = (0.0_f32, 1.0_f32);
+    for axis in 0..2 {
+        if delta[axis].abs() <= f32::EPSILON {
+            if a[axis] < min[axis] || a[axis] > max[axis] {
+                return None;
+            }
+            continue;
+        }
+        let t0 = (min[axis] - a[axis]) / delta[axis];
+        let t1 = (max[axis] - a[axis]) / delta[axis];
+        enter = enter.max(t0.min(t1));
+        exit = exit.min(t0.max(t1));
+    }
+    (enter <= exit).then_some(enter)
+}
+
+/// Distance along `path` at which it runs into `door`, within [`DOOR_LOOKAHEAD`].
+fn door_on_path(path: &[Vec3], door: &Door) -> Option<f32> {
+    let planar = |point: Vec3| Vec2::new(point.x, point.z);
+    let half_size = planar(door.size * 0.5);
+    let center = planar(door.closed_at);
+    let mut travelled = 0.0;
+    for segment in path.windows(2) {
+        if travelled > DOOR_LOOKAHEAD {
+            return None;
+        }
+        let length = planar_distance(segment[0], segment[1]);
+        if let Some(entry) = segment_entry(
+            planar(segment[0]),
+            planar(segment[1]),
+            center - half_size,
+            center + half_size,
+        ) {
+            let distance = travelled + entry * length;
+            return (distance <= DOOR_LOOKAHEAD).then_some(distance);
+        }
+        travelled += length;
+    }
+    None
+}
+
+/// Where the bot goes next: from where it stands, through its waypoints to its target.
+fn upcoming_path(
+    position: Vec3,
+    nav_agent: &SimpleNavigationAgent,
+    path_state: &NavigationPathState,
+) -> Vec<Vec3> {
+    let mut path = vec![position];
+    path.extend(path_state.current_waypoint);
+    path.extend(path_state.remaining_waypoints.iter().rev());
+    if path.len() == 1 {
+        path.extend(nav_agent.current_target);
+    }
+    path
+}
+
+/// Start a transit for bots whose path runs into a closed door on their floor.
+fn plan_door_transits(
+    mut commands: Commands,
+    clock: Res<GameClock>,
+    mut bots: Query<
+        (
+            Entity,
+            &Position,
+            &SimpleNavigationAgent,
+            &NavigationPathState,
+            &Health,
+            Option<&mut AvoidedDoors>,
+            Option<&PolicyControlled>,
+        ),
+        (With<BotProfile>, Without<DoorTransit>),
+    >,
+    doors: Query<(Entity, &Door, &DoorLink)>,
+) {
+    for (bot, position, nav_agent, path_state, health, mut avoided, policy) in bots.iter_mut() {
+        if let Some(avoided) = avoided.as_deref_mut() {
+            avoided
+                .0
+                .iter_mut()
+                .for_each(|(_, secs)| *secs -= clock.delta_secs());
+            avoided.0.retain(|(_, secs)| *secs > 0.0);
+        }
+        if health.is_dead || policy.is_some_and(|policy| policy.active) {
+            continue;
+        }
+        if nav_agent.current_target.is_none() {
+            continue;
+        }
+
+        let path = upcoming_path(position.0, nav_agent, path_state);
+        let blocking = doors
+            .iter()
+            .filter(|(entity, door, _)| {
+                !door.open
+                    && (door.closed_at.y - position.0.y).abs() <= door.size.y
+                    && !avoided
+                        .as_ref()
+                        .is_some_and(|avoided| avoided.contains(*entity))
+            })
+            .filter_map(|(entity, door, link)| {
+                door_on_path(&path, door).map(|distance| (entity, link, distance))
+            })
+            .min_by(|(.., a), (.., b)| a.total_cmp(b));
+        let Some((door, link, _)) = blocking else {
+            continue;
+        };
+
+        let use_at = link.use_point(position.0);
+        debug!("Bot {:?}: opening door {:?} from {:?}", bot, door, use_at);
+        commands
+            .entity(bot)
+            .insert((DoorTransit::new(door, use_at), NavigationDetour(use_at)));
+    }
+}
+
+/// Walk transiting bots to their door, work it, and send them on once it is open. Bots
+/// that give up drop their target, so their patrol or behavior picks another, and leave
+/// the door alone for a while.
+fn drive_door_transits(
+    mut commands: Commands,
+    clock: Res<GameClock>,
+    mut uses: MessageWriter<UseRequest>,
+    mut bots: Query<(
+        Entity,
+        &Position,
+        &mut SimpleNavigationAgent,
+        &mut NavigationPathState,
+        &mut DoorTransit,
+        &Health,
+        Option<&mut AvoidedDoors>,
+    )>,
+    doors: Query<&Door>,
+) {
+    for (bot, position, mut nav_agent, mut path_state, mut transit, health, avoided) in
+        bots.iter_mut()
+    {
+        let Ok(door) = doors.get(transit.door) else {
+            commands
+                .entity(bot)
+                .remove::<(DoorTransit, NavigationDetour)>();
+            continue;
+        };
+        if health.is_dead {
+            commands
+                .entity(bot)
+                .remove::<(DoorTransit, NavigationDetour)>();
+            continue;
+        }
+
+        let arrived = planar_distance(position.0, transit.use_at) <= nav_agent.arrival_threshold;
+        match transit.advance(clock.delta_secs(), arrived, door) {
+            DoorStep::Wait => {}
+            DoorStep::Use => {
+                uses.write(UseRequest { from: position.0 });
+            }
+            DoorStep::Through => {
+                commands
+                    .entity(bot)
+                    .remove::<(DoorTransit, NavigationDetour)>();
+                // Path again from where the bot stands, through the open door.
+                path_state.current_waypoint = None;
+                path_state.remaining_waypoints.clear();
+            }
+            DoorStep::GiveUp => {
+                debug!("Bot {:?}: giving up on door {:?}", bot, transit.door);
+                commands
+                    .entity(bot)
+                    .remove::<(DoorTransit, NavigationDetour)>();
+                nav_agent.current_target = None;
+                path_state.clear();
+                let entry = (transit.door, DOOR_RETRY_SECS);
+                match avoided {
+                    Some(mut avoided) => avoided.0.push(entry),
+                    None => {
+                        commands.entity(bot).insert(AvoidedDoors(vec![entry]));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        DOOR_APPROACH_SECS, DOOR_OPEN_WAIT_SECS, DoorStep, DoorTransit, door_on_path, segment_entry,
+    };
+    use bevy::prelude::{Entity, Vec2, Vec3};
+    use shared::level::interactive::Door;
+
+    fn door() -> Door {
+        Door::new(Vec3::new(5.0, 1.5, 0.0), Vec3::new(0.2, 3.0, 2.0), false)
+    }
+
+    #[test]
+    fn closed_doors_are_found_along_the_path_ahead() {
+        let (min, max) = (Vec2::new(-1.0, -1.0), Vec2::new(1.0, 1.0));
+        let entry = segment_entry(Vec2::new(-3.0, 0.0), Vec2::new(3.0, 0.0), min, max);
+        assert!((entry.unwrap() - 2.0 / 6.0).abs() < 1e-5);
+        assert_eq!(
+            segment_entry(Vec2::new(-3.0, 2.0), Vec2::new(3.0, 2.0), min, max),
+            None
+        );
+
+        let door = door();
+        let through = [Vec3::new(0.0, 1.0, 0.0), Vec3::new(10.0, 1.0, 0.0)];
+        assert!((door_on_path(&through, &door).unwrap() - 4.9).abs() < 1e-4);
+        // Along the wall the door sits in, never through it.
+        let beside = [Vec3::new(0.0, 1.0, 3.0), Vec3::new(10.0, 1.0, 3.0)];
+        assert_eq!(door_on_path(&beside, &door), None);
+        // Through it, but too far along the path to matter yet.
+        let far = [
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 1.0, 8.0),
+            Vec3::new(10.0, 1.0, 0.0),
+        ];
+        assert_eq!(door_on_path(&far, &door), None);
+    }
+
+    #[test]
+    fn bots_work_a_door_and_wait_for_it_to_open() {
+        let mut door = door();
+        let mut transit = DoorTransit::new(Entity::PLACEHOLDER, Vec3::new(4.15, 1.5, 0.0));
+        assert_eq!(transit.advance(0.5, false, &door), DoorStep::Wait);
+        assert_eq!(transit.advance(0.5, true, &door), DoorStep::Use);
+        door.open = true;
+        door.progress = 0.5;
+        assert_eq!(transit.advance(0.5, true, &door), DoorStep::Wait);
+        door.progress = 1.0;
+        assert_eq!(transit.advance(0.5, true, &door), DoorStep::Through);
+    }
+
+    #[test]
+    fn bots_retry_a_stuck_door_then_give_up() {
+        let door = door();
+        let mut transit = DoorTransit::new(Entity::PLACEHOLDER, Vec3::new(4.15, 1.5, 0.0));
+        assert_eq!(transit.advance(0.1, true, &door), DoorStep::Use);
+        assert_eq!(
+            transit.advance(DOOR_OPEN_WAIT_SECS, true, &door),
+            DoorStep::Use
+        );
+        assert_eq!(
+            transit.advance(DOOR_OPEN_WAIT_SECS, true, &door),
+            DoorStep::GiveUp
+        );
+
+        // A door it never reaches is given up on too.
+        let mut transit = DoorTransit::new(Entity::PLACEHOLDER, Vec3::new(4.15, 1.5, 0.0));
+        assert_eq!(
+            transit.advance(DOOR_APPROACH_SECS, false, &door),
+            DoorStep::GiveUp
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use avian3d::prelude::Position;
+use bevy::prelude::{Commands, Entity, Name, Query, Res, Single, error, info};
+use lightyear::prelude::{
+    InterpolationTarget, NetworkTarget, Replicate, Server, ServerMultiMessageSender,
+};
+use shared::components::destructible::{
+    AUTHORITATIVE_DEBRIS_LIFETIME_SECS, AUTHORITATIVE_DEBRIS_PIECES, DebrisLifetime, DebrisPiece,
+    Destructible, debris_body, debris_layout,
+};
+use shared::components::health::Health;
+use shared::protocol::{DebrisBurst, LobbyControlChannel};
+
+use crate::replication_profile::ReplicationProfile;
+
+/// Replace destroyed props with debris. Only a handful of pieces are simulated here and
+/// replicated; the burst message lets every client spawn the rest locally.
+pub fn shatter_destroyed_props(
+    mut commands: Commands,
+    props: Query<(Entity, &Destructible, &Health, &Position, Option<&Name>)>,
+    mut sender: ServerMultiMessageSender,
+    server: Single<&Server>,
+    profile: Res<ReplicationProfile>,
+) {
+    let server = server.into_inner();
+
+    for (entity, destructible, health, position, name) in props.iter() {
+        if !health.is_dead {
+            continue;
+        }
+
+        let burst = DebrisBurst {
+            origin: position.0,
+            size: destructible.size,
+            seed: entity.to_bits(),
+            pieces: destructible.debris_pieces,
+        };
+        let layout = debris_layout(burst.seed, burst.size, burst.pieces);
+        for spec in layout.iter().take(AUTHORITATIVE_DEBRIS_PIECES) {
+            commands.spawn((
+                Name::new("Debris"),
+                DebrisPiece { size: spec.size },
+                DebrisLifetime::from_seconds(AUTHORITATIVE_DEBRIS_LIFETIME_SECS),
+                debris_body(burst.origin, spec),
+                Replicate::to_clients(NetworkTarget::All),
+                InterpolationTarget::to_clients(NetworkTarget::All),
+            ));
+        }
+
+        if profile.sends_cosmetics() {
+            sender
+                .send::<DebrisBurst, LobbyControlChannel>(&burst, server, &NetworkTarget::All)
+                .unwrap_or_else(|e| {
+                    error!("Failed to send message: {:?}", e);
+                });
+        }
+
+        info!(
+            "🧱 {} shattered into {} pieces",
+            name.map_or("Prop", |name| name.as_str()),
+            burst.pieces
+        );
+        commands.entity(entity).despawn();
+    }
+}
@@ -4,30 +4,61 @@ use bevy::prelude::{
 };
 
 use lightyear::prelude::{RemoteId, server::ClientOf};
+use shared::components::loadout::Loadout;
 use shared::level::visuals::build_level_visuals;
 use shared::{
     GymMode,
+    balance::BalanceConfig,
+    bots::MatchBotSettings,
     gym::setup_gym_level,
     level::{
         building::build_procedural_runtime_content,
-        generation::{LevelConfig, build_level_physics, generate_level},
+        generation::{LevelConfig, LevelGraph, build_level_physics, generate_level},
+        preload::LevelPreloader,
     },
     protocol::{LevelSeed, LobbyState},
 };
 
 use crate::{ServerGameState, entities::player::spawn_player_entities};
 
+/// Spawn the physics, visuals and runtime content (navmesh, lights, enemies, exit) of a
+/// generated procedural level.
+pub(super) fn build_procedural_level(
+    mut commands: Commands,
+    meshes: Option<ResMut<Assets<Mesh>>>,
+    materials: Option<ResMut<Assets<StandardMaterial>>>,
+    level_graph: &LevelGraph,
+    bot_settings: &MatchBotSettings,
+) {
+    build_level_physics(commands.reborrow(), level_graph);
+
+    if let (Some(mesh_assets), Some(mat_assets)) = (meshes, materials) {
+        build_level_visuals(
+            commands.reborrow(),
+            mesh_assets,
+            Some(mat_assets),
+            level_graph,
+        );
+    }
+
+    build_procedural_runtime_content(&mut commands, level_graph, bot_settings);
+}
+
 #[allow(clippy::too_many_arguments)]
 pub(super) fn generate_and_build_level(
     mut commands: Commands,
     meshes: Option<ResMut<Assets<Mesh>>>,
     mut materials: Option<ResMut<Assets<StandardMaterial>>>,
     gym_mode: Option<Res<GymMode>>,
+    bot_settings: Option<Res<MatchBotSettings>>,
+    balance: Option<Res<BalanceConfig>>,
+    preloader: Option<ResMut<LevelPreloader>>,
     level_seed_query: Query<&LevelSeed>,
     lobby_state: Query<&LobbyState>,
-    client_query: Query<(Entity, &RemoteId), With<ClientOf>>,
+    client_query: Query<(Entity, &RemoteId, Option<&Loadout>), With<ClientOf>>,
 ) {
     let is_gym_mode = gym_mode.map(|gm| gm.0).unwrap_or(false);
+    let balance = balance.map(|balance| balance.clone()).unwrap_or_default();
 
     if is_gym_mode {
         info!("🏋️  GYM MODE: Setting up simple test environment with one NPC and obstacles");
@@ -36,7 +67,7 @@ pub(super) fn generate_and_build_level(
             setup_gym_level(commands.reborrow(), mesh_assets, material_assets);
         }
         // Spawn players in gym mode.
-        spawn_player_entities(commands.reborrow(), &lobby_state, &client_query);
+        spawn_player_entities(commands.reborrow(), &lobby_state, &client_query, &balance);
     } else if let Some(level_seed) = level_seed_query.iter().next() {
         bevy::log::info!(
             "🌱 Server generating level on state enter with seed: {}",
@@ -44,28 +75,22 @@ pub(super) fn generate_and_build_level(
         );
 
         info!("🎮 NORMAL MODE: Setting up procedural level generation");
-        let config = LevelConfig {
-            seed: level_seed.seed,
-            target_zone_count: 12,
-            min_zone_spacing: 35.0,
-            max_depth: 8,
-        };
-        let level_graph = generate_level(config);
-        build_level_physics(commands.reborrow(), &level_graph);
-
-        if let (Some(mesh_assets), Some(mat_assets)) = (meshes, materials) {
-            build_level_visuals(
-                commands.reborrow(),
-                mesh_assets,
-                Some(mat_assets),
-                &level_graph,
-            );
-        }
-
-        build_procedural_runtime_content(&mut commands, &level_graph);
+        // Usually generated in the background during the previous match's post-game.
+        let level_graph = preloader.map_or_else(
+            || generate_level(LevelConfig::for_seed(level_seed.seed)),
+            |mut preloader| preloader.take(level_seed.seed),
+        );
+        let bot_settings = bot_settings.map(|settings| *settings).unwrap_or_default();
+        build_procedural_level(
+            commands.reborrow(),
+            meshes,
+            materials,
+            &level_graph,
+            &bot_settings,
+        );
 
         // Spawn players in normal mode.
-        spawn_player_entities(commands.reborrow(), &lobby_state, &client_query);
+        spawn_player_entities(commands.reborrow(), &lobby_state, &client_query, &balance);
     }
 
     // After loading is complete, transition to Playing.
@@ -0,0 +1,126 @@
+use avian3d::prelude::{
+    Collider, LinearVelocity, Position, Rotation, SpatialQuery, SpatialQueryFilter,
+};
+use bevy::prelude::{
+    Commands, Entity, MessageWriter, Name, Quat, Query, Res, Single, Vec3, With, error, info,
+};
+use leafwing_input_manager::prelude::ActionState;
+use lightyear::prelude::{
+    InterpolationTarget, NetworkTarget, Replicate, Server, ServerMultiMessageSender,
+};
+use shared::clock::GameClock;
+use shared::components::grenade::{
+    GRENADE_BLAST_RADIUS, GRENADE_PENETRATION, Grenade, GrenadeFuse, GrenadeThrower,
+    explosion_damage, grenade_body, throw_velocity,
+};
+use shared::components::health::{DamageEvent, Health};
+use shared::inputs::input::{PLAYER_CAPSULE_HEIGHT, PlayerAction};
+use shared::protocol::{GrenadeExplosion, LobbyControlChannel, PlayerId};
+
+use crate::replication_profile::ReplicationProfile;
+
+/// Throw a grenade from eye level when a living player presses Throw.
+pub fn throw_grenades(
+    mut commands: Commands,
+    clock: Res<GameClock>,
+    mut throwers: Query<
+        (
+            Entity,
+            &mut GrenadeThrower,
+            &Health,
+            &Position,
+            &Rotation,
+            &LinearVelocity,
+            &ActionState<PlayerAction>,
+        ),
+        With<PlayerId>,
+    >,
+) {
+    for (entity, mut thrower, health, position, rotation, velocity, action_state) in
+        throwers.iter_mut()
+    {
+        thrower.cooldown.tick(clock.delta());
+        if health.is_dead
+            || action_state.disabled()
+            || !action_state.just_pressed(&PlayerAction::Throw)
+            || !thrower.cooldown.is_finished()
+        {
+            continue;
+        }
+
+        let aim = rotation.0 * Vec3::NEG_Z;
+        let origin = position.0 + Vec3::Y * (PLAYER_CAPSULE_HEIGHT * 0.5) + aim * 0.8;
+        commands.spawn((
+            Name::new("Grenade"),
+            Grenade,
+            GrenadeFuse::new(entity),
+            grenade_body(origin, throw_velocity(rotation.0, velocity.0)),
+            Replicate::to_clients(NetworkTarget::All),
+            InterpolationTarget::to_clients(NetworkTarget::All),
+        ));
+        thrower.cooldown.reset();
+    }
+}
+
+/// Explode grenades whose fuse ran out: everything with health inside the blast radius
+/// takes damage falling off with distance.
+#[allow(clippy::too_many_arguments)]
+pub fn detonate_grenades(
+    mut commands: Commands,
+    clock: Res<GameClock>,
+    mut grenades: Query<(Entity, &mut GrenadeFuse, &Position), With<Grenade>>,
+    targets: Query<&Position, With<Health>>,
+    spatial_query: SpatialQuery,
+    mut damage_writer: MessageWriter<DamageEvent>,
+    mut sender: ServerMultiMessageSender,
+    server: Single<&Server>,
+    profile: Res<ReplicationProfile>,
+) {
+    let server = server.into_inner();
+    let blast = Collider::sphere(GRENADE_BLAST_RADIUS);
+
+    for (entity, mut fuse, position) in grenades.iter_mut() {
+        fuse.timer.tick(clock.delta());
+        if !fuse.timer.is_finished() {
+            continue;
+        }
+
+        let origin = position.0;
+        let filter = SpatialQueryFilter::default().with_excluded_entities([entity]);
+        let hits = spatial_query.shape_intersections(&blast, origin, Quat::IDENTITY, &filter);
+        for target in hits {
+            let Ok(target_position) = targets.get(target) else {
+                continue;
+            };
+            let amount = explosion_damage(target_position.0.distance(origin));
+            if amount <= 0.0 {
+                continue;
+            }
+            damage_writer.write(DamageEvent {
+                target,
+                amount,
+                source: Some(fuse.thrower),
+                penetration: GRENADE_PENETRATION,
+                headshot: false,
+            });
+        }
+
+        if profile.sends_cosmetics() {
+            sender
+                .send::<GrenadeExplosion, LobbyControlChannel>(
+                    &GrenadeExplosion {
+                        origin,
+                        radius: GRENADE_BLAST_RADIUS,
+                    },
+                    server,
+                    &NetworkTarget::All,
+                )
+                .unwrap_or_else(|e| {
+                    error!("Failed to send message: {:?}", e);
+                });
+        }
+
+        info!("💣 Grenade exploded at {:?}", origin);
+        commands.entity(entity).despawn();
+    }
+}
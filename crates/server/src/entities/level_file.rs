@@ -0,0 +1,76 @@
+use avian3d::prelude::{LinearVelocity, Position};
+use bevy::prelude::{
+    Assets, Commands, Entity, Mesh, MessageReader, Query, ResMut, StandardMaterial, Vec3, With,
+    Without, info,
+};
+use lightyear::prelude::PeerId;
+use shared::{
+    components::health::Respawnable,
+    level::{
+        file::{LoadLevelFile, spawn_level_file},
+        generation::LevelGeometry,
+    },
+    protocol::{CharacterMarker, PlayerId},
+};
+
+/// Swaps the current level for a hand-made one sent by the host's level editor. Players
+/// keep their entities and are moved to the level's spawn points, as on a level transition.
+pub(super) fn load_level_files(
+    mut commands: Commands,
+    mut requests: MessageReader<LoadLevelFile>,
+    geometry_query: Query<Entity, (With<LevelGeometry>, Without<PlayerId>)>,
+    mut player_query: Query<
+        (
+            &PlayerId,
+            &mut Position,
+            &mut LinearVelocity,
+            Option<&mut Respawnable>,
+        ),
+        With<CharacterMarker>,
+    >,
+    mut meshes: Option<ResMut<Assets<Mesh>>>,
+    mut materials: Option<ResMut<Assets<StandardMaterial>>>,
+) {
+    // Only the latest edit matters.
+    let Some(LoadLevelFile(level)) = requests.read().last() else {
+        return;
+    };
+    info!(
+        "🛠️ Loading edited level: {} blocks, {} spawn points, {} patrol routes, {} lights, {} doors",
+        level.blocks.len(),
+        level.spawn_points.len(),
+        level.patrol_routes.len(),
+        level.lights.len(),
+        level.doors.len()
+    );
+
+    for entity in geometry_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    spawn_level_file(
+        &mut commands,
+        level,
+        meshes.as_deref_mut(),
+        materials.as_deref_mut(),
+    );
+
+    let mut players: Vec<_> = player_query.iter_mut().collect();
+    players.sort_by_key(|(player_id, ..)| match player_id.0 {
+        PeerId::Netcode(id) => id,
+        _ => u64::MAX,
+    });
+    for (index, (_, mut position, mut linear_velocity, respawnable)) in
+        players.into_iter().enumerate()
+    {
+        let Some(spawn_position) = level.spawn_point(index) else {
+            break;
+        };
+        position.0 = spawn_position;
+        linear_velocity.0 = Vec3::ZERO;
+        if let Some(mut respawnable) = respawnable
+            && respawnable.respawn_position.is_some()
+        {
+            respawnable.respawn_position = Some(spawn_position);
+        }
+    }
+}
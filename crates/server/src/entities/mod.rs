@@ -1,32 +1,66 @@
+mod destructible;
 mod game;
+mod grenade;
+mod level_file;
 mod npc;
+mod pickup;
 mod player;
+pub mod spectator;
+mod transition;
+mod world_items;
 
 use bevy::{
 	ecs::schedule::IntoScheduleConfigs,
 	prelude::{App, FixedUpdate, Plugin, Update},
 	state::{condition::in_state, state::OnEnter},
 };
+use shared::clock::ensure_game_clock;
 use shared::gym::{spawn_gym_patrolling_npc_entities, update_gym_wandering_npc_targets};
+use shared::level::file::LoadLevelFile;
 
+use self::destructible::shatter_destroyed_props;
 use self::game::generate_and_build_level;
+use self::grenade::{detonate_grenades, throw_grenades};
+use self::level_file::load_level_files;
 use self::npc::{mark_dead_npcs_for_respawn, respawn_dead_npcs};
-use self::player::{handle_player_death, spawn_late_joining_players};
+use self::pickup::{collect_pickups, respawn_pickups};
+use self::player::{
+	handle_equip_attachment_requests, handle_player_death, handle_submit_loadout_requests,
+	spawn_late_joining_players, update_player_look_velocity,
+};
+use self::spectator::handle_spectate_requests;
+use self::transition::advance_level_on_exit;
+use self::world_items::{cleanup_world_items, spawn_corpses};
 
 use crate::ServerGameState;
+use crate::cpu_profile::ServerModuleSet;
 
 pub struct ServerEntitiesPlugin;
 
 impl Plugin for ServerEntitiesPlugin {
 	fn build(&self, app: &mut App) {
+		ensure_game_clock(app);
+		app.add_message::<LoadLevelFile>();
 		app.add_systems(
 			FixedUpdate,
 			(
 				spawn_late_joining_players,
+				spawn_corpses
+					.before(handle_player_death)
+					.before(mark_dead_npcs_for_respawn),
 				handle_player_death,
+				update_player_look_velocity,
 				mark_dead_npcs_for_respawn,
 				respawn_dead_npcs,
+				throw_grenades,
+				detonate_grenades,
+				collect_pickups,
+				respawn_pickups,
+				shatter_destroyed_props,
+				cleanup_world_items,
+				advance_level_on_exit,
 			)
+				.in_set(ServerModuleSet::Entities)
 				.run_if(in_state(ServerGameState::Playing)),
 		);
 		app.add_systems(OnEnter(ServerGameState::Loading), generate_and_build_level);
@@ -36,7 +70,19 @@ impl Plugin for ServerEntitiesPlugin {
 		);
 		app.add_systems(
 			Update,
-			update_gym_wandering_npc_targets.run_if(in_state(ServerGameState::Playing)),
+			(
+				update_gym_wandering_npc_targets,
+				handle_equip_attachment_requests,
+				handle_spectate_requests,
+				load_level_files,
+			)
+				.in_set(ServerModuleSet::Entities)
+				.run_if(in_state(ServerGameState::Playing)),
+		);
+		// Loadouts are picked in the lobby but can be changed mid-match for the next spawn.
+		app.add_systems(
+			Update,
+			handle_submit_loadout_requests.in_set(ServerModuleSet::Entities),
 		);
 	}
 }
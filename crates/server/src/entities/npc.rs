@@ -1,19 +1,23 @@
 use avian3d::prelude::{LinearVelocity, Position};
 use bevy::{
     ecs::query::Without,
-    prelude::{Commands, Entity, Query, Res, Vec3, With, info},
+    prelude::{Commands, Entity, Has, Query, Res, Vec3, With, info},
 };
 use shared::{
+    bots::BotProfile,
+    clock::GameClock,
     components::health::{Health, Respawnable},
     protocol::{CharacterMarker, PlayerId},
 };
 
+use crate::director::AiDirector;
+
 #[derive(bevy::prelude::Component)]
 pub struct PendingNpcRespawn;
 
 pub fn mark_dead_npcs_for_respawn(
     mut commands: Commands,
-    time: Res<bevy::prelude::Time>,
+    clock: Res<GameClock>,
     mut npc_query: Query<
         (
             Entity,
@@ -29,7 +33,7 @@ pub fn mark_dead_npcs_for_respawn(
         ),
     >,
 ) {
-    let now = time.elapsed().as_secs_f32();
+    let now = clock.elapsed_secs();
 
     for (entity, health, mut respawnable, mut position, mut linear_velocity) in &mut npc_query {
         if !health.is_dead {
@@ -45,9 +49,12 @@ pub fn mark_dead_npcs_for_respawn(
     }
 }
 
+/// Respawn dead NPCs once their delay is over. While the AI director caps the bots alive at
+/// once, dead bots above the cap wait for a slot.
 pub fn respawn_dead_npcs(
     mut commands: Commands,
-    time: Res<bevy::prelude::Time>,
+    clock: Res<GameClock>,
+    director: Option<Res<AiDirector>>,
     mut npc_query: Query<
         (
             Entity,
@@ -55,6 +62,7 @@ pub fn respawn_dead_npcs(
             &Respawnable,
             &mut Position,
             &mut LinearVelocity,
+            Has<BotProfile>,
         ),
         (
             With<CharacterMarker>,
@@ -62,13 +70,24 @@ pub fn respawn_dead_npcs(
             With<PendingNpcRespawn>,
         ),
     >,
+    living_bots: Query<&Health, (With<BotProfile>, Without<PendingNpcRespawn>)>,
 ) {
-    let now = time.elapsed().as_secs_f32();
+    let now = clock.elapsed_secs();
+    let bot_cap = director.and_then(|director| director.active_bot_cap());
+    let mut bots_alive = living_bots.iter().filter(|health| !health.is_dead).count();
 
-    for (entity, mut health, respawnable, mut position, mut linear_velocity) in &mut npc_query {
+    for (entity, mut health, respawnable, mut position, mut linear_velocity, is_bot) in
+        &mut npc_query
+    {
         if !respawnable.can_respawn(now) {
             continue;
         }
+        if is_bot {
+            if bot_cap.is_some_and(|cap| bots_alive >= cap) {
+                continue;
+            }
+            bots_alive += 1;
+        }
 
         health.reset();
         if let Some(respawn_position) = respawnable.respawn_position {
@@ -86,19 +105,17 @@ mod tests {
     use super::{mark_dead_npcs_for_respawn, respawn_dead_npcs};
     use avian3d::prelude::{LinearVelocity, Position};
     use bevy::prelude::{App, MinimalPlugins, Update, Vec3};
+    use shared::clock::GameClock;
     use shared::components::health::{DamageEvent, Health, HealthPlugin, Respawnable};
     use shared::protocol::CharacterMarker;
-
-    fn advance(app: &mut App, dt: std::time::Duration) {
-        app.insert_resource(bevy::time::TimeUpdateStrategy::ManualDuration(dt));
-        app.update();
-    }
+    use std::time::Duration;
 
     #[test]
     fn npc_damage_kill_and_respawn_cycle() {
         let mut app = App::new();
         app.add_plugins(MinimalPlugins);
         app.add_plugins(HealthPlugin);
+        app.insert_resource(GameClock::stepped(Duration::from_millis(16)));
         app.add_systems(Update, (mark_dead_npcs_for_respawn, respawn_dead_npcs));
 
         let spawn_position = Vec3::new(-18.0, 1.0, -8.0);
@@ -117,10 +134,12 @@ mod tests {
             target: npc,
             amount: 500.0,
             source: None,
+            penetration: 0.0,
+            headshot: false,
         });
 
         for _ in 0..4 {
-            advance(&mut app, std::time::Duration::from_millis(16));
+            app.update();
         }
 
         let health_after_damage = app
@@ -141,7 +160,7 @@ mod tests {
         );
 
         for _ in 0..8 {
-            advance(&mut app, std::time::Duration::from_millis(16));
+            app.update();
         }
 
         let still_dead = app
@@ -151,7 +170,7 @@ mod tests {
         assert!(still_dead.is_dead, "NPC should still be dead before delay");
 
         for _ in 0..12 {
-            advance(&mut app, std::time::Duration::from_millis(16));
+            app.update();
         }
 
         let health_after_respawn = app
@@ -0,0 +1,79 @@
+use avian3d::prelude::CollisionStart;
+use bevy::prelude::{MessageReader, Name, Query, Res, With, Without, info};
+use shared::clock::GameClock;
+use shared::components::attachments::WeaponAttachments;
+use shared::components::health::Health;
+use shared::components::pickup::{Pickup, PickupRespawn, apply_pickup};
+use shared::components::weapons::Gun;
+use shared::protocol::CharacterMarker;
+
+/// Hand an available pickup to the first living character that enters its trigger volume.
+/// A character standing in the trigger when the pickup respawns has to step out and back
+/// in, since only the start of a contact is reported.
+pub fn collect_pickups(
+    mut collisions: MessageReader<CollisionStart>,
+    mut pickups: Query<(&mut Pickup, &mut PickupRespawn, &Name)>,
+    mut characters: Query<
+        (
+            &mut Health,
+            Option<&mut Gun>,
+            Option<&mut WeaponAttachments>,
+            &Name,
+        ),
+        (With<CharacterMarker>, Without<Pickup>),
+    >,
+) {
+    for collision in collisions.read() {
+        let (pickup_entity, collector) = if pickups.contains(collision.collider1) {
+            (
+                collision.collider1,
+                collision.body2.unwrap_or(collision.collider2),
+            )
+        } else if pickups.contains(collision.collider2) {
+            (
+                collision.collider2,
+                collision.body1.unwrap_or(collision.collider1),
+            )
+        } else {
+            continue;
+        };
+        let Ok((mut pickup, mut respawn, pickup_name)) = pickups.get_mut(pickup_entity) else {
+            continue;
+        };
+        let Ok((mut health, gun, attachments, collector_name)) = characters.get_mut(collector)
+        else {
+            continue;
+        };
+        if !pickup.available || health.is_dead {
+            continue;
+        }
+
+        if apply_pickup(
+            pickup.kind,
+            &mut health,
+            gun.map(|gun| gun.into_inner()),
+            attachments.map(|attachments| attachments.into_inner()),
+        ) {
+            info!("{} collected {}", collector_name, pickup_name);
+            pickup.available = false;
+            respawn.0.reset();
+        }
+    }
+}
+
+/// Make taken pickups available again once their respawn timer runs out.
+pub fn respawn_pickups(
+    clock: Res<GameClock>,
+    mut pickups: Query<(&mut Pickup, &mut PickupRespawn)>,
+) {
+    for (mut pickup, mut respawn) in pickups.iter_mut() {
+        if pickup.available {
+            continue;
+        }
+
+        respawn.0.tick(clock.delta());
+        if respawn.0.is_finished() {
+            pickup.available = true;
+        }
+    }
+}
@@ -1,81 +1,109 @@
 use avian3d::prelude::{LinearVelocity, Position, Rotation};
-use bevy::prelude::{Commands, Entity, Name, Query, Vec3, With, info};
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::{Commands, Entity, Local, Name, Quat, Query, Res, Time, With, Without, info};
 use leafwing_input_manager::prelude::ActionState;
+use std::collections::HashMap;
 
 use lightyear::prelude::{
-    Connected, ControlledBy, InterpolationTarget, NetworkTarget, PeerId, PredictionTarget,
-    RemoteId, Replicate, server::ClientOf,
+    Connected, ControlledBy, InterpolationTarget, Lifetime, MessageReceiver, NetworkTarget, PeerId,
+    PredictionTarget, RemoteId, Replicate, server::ClientOf,
 };
 use shared::debug::debug_println;
+use shared::inputs::climbing::ClimbState;
+use shared::inputs::crouch::CrouchState;
 use shared::inputs::input::PlayerAction;
+use shared::inputs::look::LookVelocity;
 use shared::inputs::movement::GroundState;
 use shared::{
+    balance::BalanceConfig,
     components::{
+        attachments::WeaponAttachments,
         flashlight::PlayerFlashlight,
+        grenade::GrenadeThrower,
         health::{Health, Respawnable},
-        weapons::Gun,
+        loadout::{ARMOR_PLATE_SHIELD_BONUS, Equipment, HolsteredGun, Loadout},
+        profile::{player_color, player_name},
+        shield::Shield,
+    },
+    entities::PlayerPhysicsBundle,
+    level::transition::lobby_spawn_point,
+    protocol::{
+        CharacterMarker, EquipAttachmentsRequest, LobbyState, PlayerColor, PlayerId,
+        SubmitLoadoutRequest,
     },
-    entities::{PlayerPhysicsBundle, color_from_id},
-    protocol::{CharacterMarker, LobbyState, PlayerColor, PlayerId},
 };
 
+use super::spectator::Spectator;
+
 pub fn spawn_player_entities(
     mut commands: Commands,
     lobby_state: &Query<&LobbyState>,
-    client_query: &Query<(Entity, &RemoteId), With<ClientOf>>,
+    client_query: &Query<(Entity, &RemoteId, Option<&Loadout>), With<ClientOf>>,
+    balance: &BalanceConfig,
 ) {
     let Ok(lobby_data) = lobby_state.single() else {
         return;
     };
 
-    let player_count = lobby_data.players.len() as f32;
-    let spawn_radius = 3.0;
-
-    for (index, player_id) in lobby_data.players.iter().enumerate() {
-        if let Some((client_entity, remote_id)) =
+    for player_id in lobby_data.players.iter() {
+        if let Some((client_entity, remote_id, loadout)) =
             client_query
                 .iter()
-                .find(|(_, remote_id)| match remote_id.0 {
+                .find(|(_, remote_id, _)| match remote_id.0 {
                     PeerId::Netcode(id) => id == *player_id,
                     _ => false,
                 })
         {
-            let angle = (index as f32) * 2.0 * std::f32::consts::PI / player_count;
-            let spawn_position =
-                Vec3::new(spawn_radius * angle.cos(), 3.5, spawn_radius * angle.sin());
+            let (spawn_position, team) = lobby_spawn_point(lobby_data, *player_id);
 
             debug_println(format_args!(
                 "DEBUG: Spawning player entity for ID: {} at {:?}",
                 player_id, spawn_position
             ));
 
-            commands
+            let player = commands
                 .spawn((
-                    Name::new(format!("Player_{}", player_id)),
+                    Name::new(player_name(&lobby_data.profiles, *player_id)),
                     PlayerId(PeerId::Netcode(*player_id)),
-                    PlayerColor(color_from_id(*player_id)),
+                    PlayerColor(player_color(&lobby_data.profiles, *player_id)),
                     Rotation::default(),
                     Position::new(spawn_position),
                     LinearVelocity::default(),
                     Health::basic(),
                     Respawnable::new(3.0),
-                    Gun::default(),
+                    WeaponAttachments::default(),
                     PlayerFlashlight::new(),
+                    // Outlives its client's session so a dropped player can reclaim it.
                     ControlledBy {
                         owner: client_entity,
-                        lifetime: Default::default(),
+                        lifetime: Lifetime::Persistent,
                     },
                     Replicate::to_clients(NetworkTarget::All),
                     PredictionTarget::to_clients(NetworkTarget::Single(remote_id.0)),
                     InterpolationTarget::to_clients(NetworkTarget::AllExceptSingle(remote_id.0)),
                 ))
-                .insert(GroundState::default())
+                .insert((
+                    GroundState::default(),
+                    ClimbState::default(),
+                    CrouchState::default(),
+                    LookVelocity::default(),
+                    balance.player_stamina(),
+                ))
                 .insert((
                     CharacterMarker,
                     PlayerPhysicsBundle::default(),
                     ActionState::<PlayerAction>::default(),
                     leafwing_input_manager::prelude::InputMap::<PlayerAction>::default(),
-                ));
+                ))
+                .id();
+            equip_loadout(
+                commands.entity(player),
+                loadout.copied().unwrap_or_default(),
+                balance,
+            );
+            if let Some(team) = team {
+                commands.entity(player).insert(team);
+            }
         } else {
             debug_println(format_args!(
                 "DEBUG: Could not find client entity for player ID: {}",
@@ -91,18 +119,23 @@ pub fn spawn_player_entities(
     }
 }
 
-/// Spawn player entities for clients that join after the game has already started.
+/// Spawn player entities for clients that join after the game has already started, or that
+/// stopped spectating.
 pub fn spawn_late_joining_players(
     mut commands: Commands,
     lobby_state: Query<&LobbyState>,
-    client_query: Query<(Entity, &RemoteId), (With<ClientOf>, With<Connected>)>,
+    client_query: Query<
+        (Entity, &RemoteId, Option<&Loadout>),
+        (With<ClientOf>, With<Connected>, Without<Spectator>),
+    >,
     existing_players: Query<&PlayerId>,
+    balance: Res<BalanceConfig>,
 ) {
     let Ok(lobby_data) = lobby_state.single() else {
         return;
     };
 
-    for (client_entity, remote_id) in client_query.iter() {
+    for (client_entity, remote_id, loadout) in client_query.iter() {
         let player_id_bits = match remote_id.0 {
             PeerId::Netcode(id) => id,
             _ => continue,
@@ -118,53 +151,82 @@ pub fn spawn_late_joining_players(
         });
 
         if !player_exists {
-            let index = lobby_data
-                .players
-                .iter()
-                .position(|&id| id == player_id_bits)
-                .unwrap_or(0);
-            let player_count = lobby_data.players.len() as f32;
-            let spawn_radius = 3.0;
-            let angle = (index as f32) * 2.0 * std::f32::consts::PI / player_count;
-            let spawn_position =
-                Vec3::new(spawn_radius * angle.cos(), 3.5, spawn_radius * angle.sin());
+            let (spawn_position, team) = lobby_spawn_point(lobby_data, player_id_bits);
 
             debug_println(format_args!(
                 "DEBUG: Spawning late-joining player entity for ID: {} at {:?}",
                 player_id_bits, spawn_position
             ));
 
-            commands
+            let player = commands
                 .spawn((
-                    Name::new(format!("Player_{}", player_id_bits)),
+                    Name::new(player_name(&lobby_data.profiles, player_id_bits)),
                     PlayerId(PeerId::Netcode(player_id_bits)),
-                    PlayerColor(color_from_id(player_id_bits)),
+                    PlayerColor(player_color(&lobby_data.profiles, player_id_bits)),
                     Rotation::default(),
                     Position::new(spawn_position),
                     LinearVelocity::default(),
                     Health::basic(),
                     Respawnable::new(3.0),
-                    Gun::default(),
+                    WeaponAttachments::default(),
                     PlayerFlashlight::new(),
+                    // Outlives its client's session so a dropped player can reclaim it.
                     ControlledBy {
                         owner: client_entity,
-                        lifetime: Default::default(),
+                        lifetime: Lifetime::Persistent,
                     },
                     Replicate::to_clients(NetworkTarget::All),
                     PredictionTarget::to_clients(NetworkTarget::Single(remote_id.0)),
                     InterpolationTarget::to_clients(NetworkTarget::AllExceptSingle(remote_id.0)),
                 ))
-                .insert(GroundState::default())
+                .insert((
+                    GroundState::default(),
+                    ClimbState::default(),
+                    CrouchState::default(),
+                    LookVelocity::default(),
+                    balance.player_stamina(),
+                ))
                 .insert((
                     CharacterMarker,
                     PlayerPhysicsBundle::default(),
                     ActionState::<PlayerAction>::default(),
                     leafwing_input_manager::prelude::InputMap::<PlayerAction>::default(),
-                ));
+                ))
+                .id();
+            equip_loadout(
+                commands.entity(player),
+                loadout.copied().unwrap_or_default(),
+                &balance,
+            );
+            if let Some(team) = team {
+                commands.entity(player).insert(team);
+            }
         }
     }
 }
 
+/// Give a freshly spawned player the weapons and equipment of its loadout. The secondary
+/// starts holstered.
+fn equip_loadout(mut player: EntityCommands, loadout: Loadout, balance: &BalanceConfig) {
+    let mut shield = balance.player_shield();
+    match loadout.equipment {
+        Equipment::Grenades => {
+            player.insert(GrenadeThrower::default());
+        }
+        Equipment::ArmorPlate => {
+            shield.max += ARMOR_PLATE_SHIELD_BONUS;
+            shield.current = shield.max;
+        }
+    }
+
+    player.insert((
+        loadout,
+        loadout.primary.gun(),
+        HolsteredGun(loadout.secondary.gun()),
+        shield,
+    ));
+}
+
 /// Handle player death by despawning entities with empty health.
 pub fn handle_player_death(
     mut commands: Commands,
@@ -180,3 +242,91 @@ pub fn handle_player_death(
         }
     }
 }
+
+/// Measure how fast each player turns so clients can extrapolate remote aim.
+pub fn update_player_look_velocity(
+    time: Res<Time>,
+    mut previous_rotations: Local<HashMap<Entity, Quat>>,
+    mut player_query: Query<(Entity, &Rotation, &mut LookVelocity), With<PlayerId>>,
+) {
+    let delta_secs = time.delta_secs();
+    previous_rotations.retain(|entity, _| player_query.contains(*entity));
+
+    for (entity, rotation, mut look_velocity) in player_query.iter_mut() {
+        if let Some(previous) = previous_rotations.insert(entity, rotation.0) {
+            let measured = LookVelocity::between(previous, rotation.0, delta_secs);
+            if *look_velocity != measured {
+                *look_velocity = measured;
+            }
+        }
+    }
+}
+
+/// Apply attachment changes requested by clients to the weapon of the player they control.
+/// Requests are sanitized so a client can never mount more than one attachment per slot.
+pub fn handle_equip_attachment_requests(
+    mut receivers: Query<
+        (
+            Entity,
+            &RemoteId,
+            &mut MessageReceiver<EquipAttachmentsRequest>,
+        ),
+        (With<ClientOf>, With<Connected>),
+    >,
+    mut player_query: Query<(&ControlledBy, &mut WeaponAttachments), With<PlayerId>>,
+) {
+    for (client_entity, remote_id, mut receiver) in receivers.iter_mut() {
+        let Some(request) = receiver.receive().last() else {
+            continue;
+        };
+
+        let validated = WeaponAttachments::from_requested(&request.attachments);
+        for (controlled_by, mut attachments) in player_query.iter_mut() {
+            if controlled_by.owner != client_entity {
+                continue;
+            }
+
+            if *attachments != validated {
+                info!(
+                    "Client {:?} equipped attachments {:?}",
+                    remote_id.0, validated.equipped
+                );
+                *attachments = validated.clone();
+            }
+        }
+    }
+}
+
+/// Remember the loadout each client picked, for its next spawn. Loadouts over budget are
+/// ignored and the previous choice is kept.
+pub fn handle_submit_loadout_requests(
+    mut commands: Commands,
+    mut receivers: Query<
+        (
+            Entity,
+            &RemoteId,
+            &mut MessageReceiver<SubmitLoadoutRequest>,
+        ),
+        (With<ClientOf>, With<Connected>),
+    >,
+) {
+    for (client_entity, remote_id, mut receiver) in receivers.iter_mut() {
+        let Some(request) = receiver.receive().last() else {
+            continue;
+        };
+
+        if !request.loadout.is_valid() {
+            info!(
+                "Client {:?} submitted a loadout over budget: {:?}",
+                remote_id.0, request.loadout
+            );
+            continue;
+        }
+
+        info!(
+            "Client {:?} selected loadout {:?}",
+            remote_id.0, request.loadout
+        );
+        commands.entity(client_entity).insert(request.loadout);
+    }
+}
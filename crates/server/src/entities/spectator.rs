@@ -0,0 +1,62 @@
+use bevy::prelude::{Commands, Component, Entity, Has, Query, With, info};
+use lightyear::prelude::{Connected, ControlledBy, MessageReceiver, RemoteId, server::ClientOf};
+use shared::components::health::Health;
+use shared::protocol::{PlayerId, SpectateRequest};
+
+/// Marks a `ClientOf` that watches the match instead of playing. Spectators own no player
+/// entity; players replicate to every client and interpolate for everyone but their owner,
+/// so spectators still receive every player.
+#[derive(Component, Debug)]
+pub struct Spectator;
+
+/// Toggle clients between playing and spectating. Clients can only start spectating while
+/// they have no living player, so it is never a way out of a fight into a fresh spawn;
+/// their dead player entity is removed then. Leaving spectator mode lets
+/// `spawn_late_joining_players` spawn a fresh one.
+pub fn handle_spectate_requests(
+    mut commands: Commands,
+    mut receivers: Query<
+        (
+            Entity,
+            &RemoteId,
+            &mut MessageReceiver<SpectateRequest>,
+            Has<Spectator>,
+        ),
+        (With<ClientOf>, With<Connected>),
+    >,
+    player_query: Query<(Entity, &ControlledBy, &Health), With<PlayerId>>,
+) {
+    for (client_entity, remote_id, mut receiver, is_spectator) in receivers.iter_mut() {
+        let Some(request) = receiver.receive().last() else {
+            continue;
+        };
+
+        if request.spectate == is_spectator {
+            continue;
+        }
+
+        if request.spectate {
+            let owned_players: Vec<(Entity, &Health)> = player_query
+                .iter()
+                .filter(|(_, controlled_by, _)| controlled_by.owner == client_entity)
+                .map(|(player_entity, _, health)| (player_entity, health))
+                .collect();
+            if owned_players.iter().any(|(_, health)| !health.is_dead) {
+                info!(
+                    "Client {} asked to spectate while alive, ignoring",
+                    remote_id.0
+                );
+                continue;
+            }
+
+            info!("👁️ Client {} is now spectating", remote_id.0);
+            commands.entity(client_entity).insert(Spectator);
+            for (player_entity, _) in owned_players {
+                commands.entity(player_entity).despawn();
+            }
+        } else {
+            info!("🎮 Client {} stopped spectating", remote_id.0);
+            commands.entity(client_entity).remove::<Spectator>();
+        }
+    }
+}
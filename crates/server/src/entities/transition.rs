@@ -0,0 +1,130 @@
+use avian3d::prelude::{LinearVelocity, Position};
+use bevy::prelude::{
+    Assets, Commands, Entity, Mesh, Query, Res, ResMut, Single, StandardMaterial, Vec3, With,
+    Without, error, info,
+};
+use lightyear::prelude::{NetworkTarget, PeerId, Server, ServerMultiMessageSender};
+use shared::{
+    bots::MatchBotSettings,
+    components::{
+        health::{Health, Respawnable},
+        team::Team,
+    },
+    level::{
+        generation::{LevelConfig, LevelGeometry, generate_level},
+        transition::{
+            LevelExit, next_level_seed, player_spawn_position, team_reached_exit,
+            team_spawn_position,
+        },
+    },
+    protocol::{CharacterMarker, LevelSeed, LevelTransitionEvent, LobbyControlChannel, PlayerId},
+};
+
+use std::collections::HashMap;
+
+use super::game::build_procedural_level;
+
+/// Load the next campaign level in place once every living player stands in the exit.
+/// Players keep their entities (health, weapons, attachments) and are teleported to the new
+/// spawn; clients are told to rebuild their local geometry behind a loading overlay.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn advance_level_on_exit(
+    mut commands: Commands,
+    exit_query: Query<&LevelExit>,
+    mut player_query: Query<
+        (
+            &PlayerId,
+            &Health,
+            &mut Position,
+            &mut LinearVelocity,
+            Option<&mut Respawnable>,
+            Option<&Team>,
+        ),
+        With<CharacterMarker>,
+    >,
+    geometry_query: Query<Entity, (With<LevelGeometry>, Without<PlayerId>)>,
+    mut level_seed_query: Query<&mut LevelSeed>,
+    meshes: Option<ResMut<Assets<Mesh>>>,
+    materials: Option<ResMut<Assets<StandardMaterial>>>,
+    bot_settings: Option<Res<MatchBotSettings>>,
+    mut sender: ServerMultiMessageSender,
+    server: Single<&Server>,
+) {
+    let Some(exit) = exit_query.iter().next() else {
+        return;
+    };
+
+    let living_positions = player_query
+        .iter()
+        .filter(|(_, health, ..)| !health.is_dead)
+        .map(|(_, _, position, ..)| position.0);
+    if !team_reached_exit(exit, living_positions) {
+        return;
+    }
+
+    let Some(mut level_seed) = level_seed_query.iter_mut().next() else {
+        return;
+    };
+    level_seed.seed = next_level_seed(level_seed.seed);
+    let seed = level_seed.seed;
+    info!(
+        "🚪 Team reached the exit, loading next level with seed: {}",
+        seed
+    );
+
+    for entity in geometry_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let bot_settings = bot_settings.map(|settings| *settings).unwrap_or_default();
+    let level_graph = generate_level(LevelConfig::for_seed(seed));
+    build_procedural_level(
+        commands.reborrow(),
+        meshes,
+        materials,
+        &level_graph,
+        &bot_settings,
+    );
+
+    let mut players: Vec<_> = player_query.iter_mut().collect();
+    players.sort_by_key(|(player_id, ..)| match player_id.0 {
+        PeerId::Netcode(id) => id,
+        _ => u64::MAX,
+    });
+    let player_count = players.len();
+    let mut team_sizes: HashMap<Team, usize> = HashMap::new();
+    for team in players.iter().filter_map(|(.., team)| team.copied()) {
+        *team_sizes.entry(team).or_default() += 1;
+    }
+    let mut team_indices: HashMap<Team, usize> = HashMap::new();
+    for (index, (_, _, mut position, mut linear_velocity, respawnable, team)) in
+        players.into_iter().enumerate()
+    {
+        let spawn_position = match team {
+            Some(team) => {
+                let team_index = team_indices.entry(*team).or_default();
+                let spawn_position = team_spawn_position(*team, *team_index, team_sizes[team]);
+                *team_index += 1;
+                spawn_position
+            }
+            None => player_spawn_position(index, player_count),
+        };
+        position.0 = spawn_position;
+        linear_velocity.0 = Vec3::ZERO;
+        if let Some(mut respawnable) = respawnable
+            && respawnable.respawn_position.is_some()
+        {
+            respawnable.respawn_position = Some(spawn_position);
+        }
+    }
+
+    sender
+        .send::<LevelTransitionEvent, LobbyControlChannel>(
+            &LevelTransitionEvent { seed },
+            server.into_inner(),
+            &NetworkTarget::All,
+        )
+        .unwrap_or_else(|e| {
+            error!("Failed to send message: {:?}", e);
+        });
+}
@@ -0,0 +1,91 @@
+use avian3d::prelude::{Position, Rotation};
+use bevy::prelude::{
+    Color, Commands, Entity, Name, Quat, Query, Res, Time, Vec3, With, Without, info,
+};
+use lightyear::prelude::{NetworkTarget, Replicate};
+use shared::components::health::Health;
+use shared::components::weapons::Gun;
+use shared::components::world_items::{
+    LOOT_COLOR, LOOT_SIZE, WorldItem, WorldItemKind, WorldItemSettings, WorldItemSpawnedAt,
+    world_items_to_evict,
+};
+use shared::inputs::input::{PLAYER_CAPSULE_HEIGHT, PLAYER_CAPSULE_RADIUS};
+use shared::level::generation::LevelGeometry;
+use shared::protocol::{CharacterMarker, PlayerColor};
+
+use super::npc::PendingNpcRespawn;
+
+const NPC_CORPSE_COLOR: Color = Color::srgb(0.5, 0.5, 0.5);
+
+/// Leave a corpse where a character died, and its weapon next to it. Runs before the
+/// death handlers despawn players and move dead NPCs away. Tagged as level geometry so a
+/// level transition clears them too.
+pub fn spawn_corpses(
+    mut commands: Commands,
+    time: Res<Time>,
+    characters: Query<
+        (&Health, &Position, Option<&PlayerColor>, Option<&Gun>),
+        (With<CharacterMarker>, Without<PendingNpcRespawn>),
+    >,
+) {
+    let now = time.elapsed_secs();
+
+    for (health, position, player_color, gun) in characters.iter() {
+        if !health.is_dead {
+            continue;
+        }
+
+        let floor = position.0 - Vec3::Y * (PLAYER_CAPSULE_HEIGHT * 0.5 + PLAYER_CAPSULE_RADIUS);
+        commands.spawn((
+            Name::new("Corpse"),
+            WorldItem {
+                kind: WorldItemKind::Corpse,
+                color: player_color.map_or(NPC_CORPSE_COLOR, |color| color.0),
+            },
+            WorldItemSpawnedAt(now),
+            LevelGeometry,
+            Position::new(floor + Vec3::Y * PLAYER_CAPSULE_RADIUS),
+            Rotation::from(Quat::from_rotation_z(std::f32::consts::FRAC_PI_2)),
+            Replicate::to_clients(NetworkTarget::All),
+        ));
+
+        if gun.is_some() {
+            commands.spawn((
+                Name::new("DroppedWeapon"),
+                WorldItem {
+                    kind: WorldItemKind::Loot,
+                    color: LOOT_COLOR,
+                },
+                WorldItemSpawnedAt(now),
+                LevelGeometry,
+                Position::new(floor + Vec3::new(0.6, LOOT_SIZE.y * 0.5, 0.3)),
+                Rotation::default(),
+                Replicate::to_clients(NetworkTarget::All),
+            ));
+        }
+    }
+}
+
+/// Despawn expired corpses and dropped items, then the oldest ones above the cap.
+pub fn cleanup_world_items(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<WorldItemSettings>,
+    items: Query<(Entity, &WorldItem, &WorldItemSpawnedAt)>,
+) {
+    let evicted = world_items_to_evict(
+        items
+            .iter()
+            .map(|(entity, item, spawned_at)| (entity, item.kind, spawned_at.0)),
+        time.elapsed_secs(),
+        &settings,
+    );
+    if evicted.is_empty() {
+        return;
+    }
+
+    info!("🧹 Removing {} corpses and dropped items", evicted.len());
+    for entity in evicted {
+        commands.entity(entity).despawn();
+    }
+}
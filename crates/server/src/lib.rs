@@ -1,8 +1,33 @@
+pub mod afk;
+pub mod bot_behavior;
+pub mod bot_dialogue;
+pub mod bot_policy;
+pub mod console;
+pub mod cover;
+pub mod cpu_profile;
 pub mod debug;
+pub mod director;
+pub mod doors;
 pub mod entities;
 pub mod lobby;
+pub mod match_events;
+pub mod match_lifecycle;
+pub mod match_recap;
+pub mod metrics;
 pub mod network;
+pub mod perception;
+#[cfg(feature = "remote-agents")]
+pub mod remote_agents;
 pub mod render;
+pub mod replication_profile;
+pub mod resync;
+pub mod scheduled_messages;
+pub mod score;
+pub mod session;
+pub mod snapshot;
+pub mod squads;
+pub mod visibility;
+pub mod voice;
 
 use bevy::MinimalPlugins;
 use bevy::log::LogPlugin;
@@ -12,11 +37,36 @@ use bevy::window::{Window, WindowPlugin};
 use lightyear::prelude::server::ServerPlugins;
 use std::time::Duration;
 
+use crate::afk::ServerAfkPlugin;
+use crate::bot_behavior::ServerBotBehaviorPlugin;
+use crate::bot_dialogue::ServerBotDialoguePlugin;
+use crate::bot_policy::ServerBotPolicyPlugin;
+use crate::console::ServerConsolePlugin;
+use crate::cover::ServerCoverPlugin;
+use crate::cpu_profile::ServerCpuProfilePlugin;
 use crate::debug::ServerDebugPlugin;
+use crate::director::ServerDirectorPlugin;
+use crate::doors::ServerDoorsPlugin;
 use crate::entities::ServerEntitiesPlugin;
 use crate::lobby::ServerLobbyPlugin;
+use crate::match_events::ServerMatchEventsPlugin;
+use crate::match_lifecycle::ServerMatchLifecyclePlugin;
+use crate::match_recap::ServerMatchRecapPlugin;
+use crate::metrics::ServerMetricsPlugin;
 use crate::network::ServerNetworkPlugin;
+use crate::perception::ServerPerceptionPlugin;
 use crate::render::RenderPlugin;
+use crate::replication_profile::ServerReplicationProfilePlugin;
+use crate::resync::ServerResyncPlugin;
+use crate::score::ServerScorePlugin;
+use crate::session::ServerSessionPlugin;
+use crate::snapshot::ServerSnapshotPlugin;
+use crate::squads::ServerSquadPlugin;
+use crate::visibility::ServerVisibilityPlugin;
+use crate::voice::ServerVoicePlugin;
+use shared::composition::{ComposeApp, check_composition};
+use shared::cpu_profile::CpuProfilePlugin;
+use shared::tick_trace::TickTracePlugin;
 use shared::{NetworkMode, SharedPlugin};
 #[derive(States, Debug, Default, Clone, PartialEq, Eq, Hash)]
 pub enum ServerGameState {
@@ -24,6 +74,8 @@ pub enum ServerGameState {
     Lobby,
     Loading,
     Playing,
+    /// Final scores are shown; the match is cleared when leaving this state.
+    PostGame,
 }
 
 pub fn create_server_app(headless: bool, network_mode: NetworkMode) -> App {
@@ -63,21 +115,53 @@ pub fn create_server_app(headless: bool, network_mode: NetworkMode) -> App {
         .add_plugins(RenderPlugin);
     }
 
-    app.insert_resource(network_mode);
+    app.compose_resource(network_mode);
     app.add_plugins(ServerPlugins {
         tick_duration: Duration::from_secs_f64(1.0 / shared::FIXED_TIMESTEP_HZ),
     });
-    app.add_plugins(SharedPlugin);
-    app.add_plugins(ServerNetworkPlugin);
-    app.add_plugins(ServerLobbyPlugin);
-    app.add_plugins(ServerEntitiesPlugin);
-    app.add_plugins(ServerDebugPlugin);
-    app.init_state::<ServerGameState>();
-    app.insert_state(ServerGameState::Lobby);
+    app.compose_plugin(SharedPlugin);
+    compose_server_plugins(&mut app);
+    #[cfg(feature = "remote-agents")]
+    app.compose_plugin(remote_agents::ServerRemoteAgentsPlugin);
+    app.compose_state(ServerGameState::Lobby);
+    check_composition(&app);
 
     app
 }
 
+/// Gameplay plugins of the server, shared by the dedicated server and the host app so
+/// the listen server runs the same match as a dedicated one.
+pub fn compose_server_plugins(app: &mut App) {
+    app.compose_plugin(ServerNetworkPlugin);
+    app.compose_plugin(ServerLobbyPlugin);
+    app.compose_plugin(ServerEntitiesPlugin);
+    app.compose_plugin(ServerDebugPlugin);
+    app.compose_plugin(ServerSnapshotPlugin);
+    app.compose_plugin(ServerScorePlugin);
+    app.compose_plugin(ServerBotPolicyPlugin);
+    app.compose_plugin(ServerVoicePlugin);
+    app.compose_plugin(ServerMatchEventsPlugin);
+    app.compose_plugin(ServerMatchLifecyclePlugin);
+    app.compose_plugin(ServerMatchRecapPlugin);
+    app.compose_plugin(ServerMetricsPlugin);
+    app.compose_plugin(TickTracePlugin);
+    app.compose_plugin(CpuProfilePlugin);
+    app.compose_plugin(ServerCpuProfilePlugin);
+    app.compose_plugin(ServerSessionPlugin);
+    app.compose_plugin(ServerResyncPlugin);
+    app.compose_plugin(ServerVisibilityPlugin);
+    app.compose_plugin(ServerPerceptionPlugin);
+    app.compose_plugin(ServerSquadPlugin);
+    app.compose_plugin(ServerCoverPlugin);
+    app.compose_plugin(ServerBotBehaviorPlugin);
+    app.compose_plugin(ServerDoorsPlugin);
+    app.compose_plugin(ServerDirectorPlugin);
+    app.compose_plugin(ServerBotDialoguePlugin);
+    app.compose_plugin(ServerAfkPlugin);
+    app.compose_plugin(ServerConsolePlugin);
+    app.compose_plugin(ServerReplicationProfilePlugin);
+}
+
 #[cfg(test)]
 mod tests {
     use super::{ServerGameState, create_server_app};
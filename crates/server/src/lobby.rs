@@ -1,18 +1,23 @@
 use bevy::prelude::{
-    App, Assets, Commands, CommandsStatesExt, IntoScheduleConfigs, Mesh, Plugin, Query, Res,
-    ResMut, Single, StandardMaterial, Update, error,
+    App, Assets, Changed, Commands, CommandsStatesExt, Component, DetectChanges,
+    IntoScheduleConfigs, Mesh, Name, Plugin, Query, Res, ResMut, Resource, StandardMaterial, State,
+    Update, With, info,
 };
 
-use lightyear::prelude::{
-    Connected, MessageReceiver, NetworkTarget, RemoteId, Replicate, Server,
-    ServerMultiMessageSender,
-};
+use lightyear::prelude::{Connected, MessageReceiver, NetworkTarget, RemoteId, Replicate};
 
 use crate::ServerGameState;
+use crate::cpu_profile::ServerModuleSet;
+use crate::scheduled_messages::{ScheduledMessages, SendWhen, ensure_scheduled_messages};
 
+use shared::aim_assist::AimAssistSettings;
+use shared::bots::MatchBotSettings;
+use shared::components::profile::{player_name, validate_profile};
+use shared::components::team::{TeamRules, assign_teams, can_join_team, rebalance_teams};
 use shared::debug::debug_println;
 use shared::protocol::{
-    GameSeed, HostStartGameEvent, LevelSeed, LobbyControlChannel, LobbyState, StartLoadingGameEvent,
+    CharacterMarker, GameSeed, HostStartGameEvent, LevelSeed, LobbyControlChannel, LobbyState,
+    PlayerId, PlayerProfileRequest, StartLoadingGameEvent, TeamSelectRequest,
 };
 
 pub struct ServerLobbyPlugin;
@@ -20,52 +25,217 @@ pub struct ServerLobbyPlugin;
 #[derive(bevy::prelude::Resource, Clone, Copy, Debug, Default)]
 pub struct AutoStartOnLobbyReady(pub bool);
 
+/// Level seed of the next match; the admin console's `map` command changes it.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NextMatchSeed(pub u64);
+
+impl Default for NextMatchSeed {
+    fn default() -> Self {
+        Self(42)
+    }
+}
+
 impl Plugin for ServerLobbyPlugin {
     fn build(&self, app: &mut App) {
+        ensure_scheduled_messages(app);
+        app.init_resource::<MatchBotSettings>();
+        app.init_resource::<NextMatchSeed>();
+        app.init_resource::<AimAssistSettings>();
+        app.init_resource::<TeamRules>();
+        app.add_systems(
+            Update,
+            (
+                sync_replicated_settings::<MatchBotSettings>,
+                sync_replicated_settings::<AimAssistSettings>,
+                sync_replicated_settings::<TeamRules>,
+                balance_lobby_teams,
+                (handle_player_profile_requests, rename_characters).chain(),
+            )
+                .in_set(ServerModuleSet::Lobby),
+        );
         app.add_systems(
             Update,
-            host_start_game_event.run_if(bevy::state::condition::in_state(ServerGameState::Lobby)),
+            handle_team_select_requests
+                .before(balance_lobby_teams)
+                .in_set(ServerModuleSet::Lobby)
+                .run_if(bevy::state::condition::in_state(ServerGameState::Lobby)),
+        );
+        app.add_systems(
+            Update,
+            host_start_game_event
+                .in_set(ServerModuleSet::Lobby)
+                .run_if(bevy::state::condition::in_state(ServerGameState::Lobby)),
         );
         app.add_systems(
             Update,
             auto_start_game_when_lobby_ready
+                .in_set(ServerModuleSet::Lobby)
                 .run_if(bevy::state::condition::in_state(ServerGameState::Lobby)),
         );
     }
 }
 
-fn transition_to_loading(
+/// Mirror server-side match settings (bots, aim assist, team rules) onto a replicated
+/// entity for the client lobby UI.
+fn sync_replicated_settings<T: Resource + Component + Copy>(
+    settings: Res<T>,
+    mut replicated: Query<&mut T>,
+    mut commands: Commands,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    if let Some(mut replicated_settings) = replicated.iter_mut().next() {
+        *replicated_settings = *settings;
+    } else {
+        let type_name = std::any::type_name::<T>();
+        commands.spawn((
+            *settings,
+            Replicate::to_clients(NetworkTarget::All),
+            Name::from(type_name.rsplit("::").next().unwrap_or(type_name)),
+        ));
+    }
+}
+
+/// Keep every lobby player on a team. While in the lobby, teams left lopsided by departures
+/// are rebalanced; once a match runs only newcomers are placed, so spawned players keep
+/// their team.
+fn balance_lobby_teams(
+    mut lobby_query: Query<&mut LobbyState, Changed<LobbyState>>,
+    server_state: Res<State<ServerGameState>>,
+) {
+    for mut lobby_state in lobby_query.iter_mut() {
+        let teams = if *server_state.get() == ServerGameState::Lobby {
+            rebalance_teams(&lobby_state.players, &lobby_state.teams)
+        } else {
+            assign_teams(&lobby_state.players, &lobby_state.teams)
+        };
+        if teams != lobby_state.teams {
+            lobby_state.teams = teams;
+        }
+        if lobby_state
+            .profiles
+            .keys()
+            .any(|player| !lobby_state.players.contains(player))
+        {
+            let LobbyState {
+                players, profiles, ..
+            } = &mut *lobby_state;
+            profiles.retain(|player, _| players.contains(player));
+        }
+    }
+}
+
+/// Store the name and color each player asked for, cleaned up and made unique. Accepted in
+/// any state, so a renamed player shows up under the new name mid-match too.
+fn handle_player_profile_requests(
+    mut receivers: Query<(&RemoteId, &mut MessageReceiver<PlayerProfileRequest>), With<Connected>>,
+    mut lobby_query: Query<&mut LobbyState>,
+) {
+    let Some(mut lobby_state) = lobby_query.iter_mut().next() else {
+        return;
+    };
+
+    for (remote_id, mut receiver) in receivers.iter_mut() {
+        let Some(request) = receiver.receive().last() else {
+            continue;
+        };
+
+        let player_id = remote_id.0.to_bits();
+        if !lobby_state.players.contains(&player_id) {
+            continue;
+        }
+        let profile = validate_profile(&request.profile, player_id, &lobby_state.profiles);
+        if lobby_state.profiles.get(&player_id) != Some(&profile) {
+            info!("Client {} is now {}", player_id, profile.name);
+            lobby_state.profiles.insert(player_id, profile);
+        }
+    }
+}
+
+/// Keep the replicated `Name` of characters in step with their players' profiles. Their
+/// color is picked up at the next spawn.
+fn rename_characters(
+    lobby_query: Query<&LobbyState, Changed<LobbyState>>,
+    mut characters: Query<(&PlayerId, &mut Name), With<CharacterMarker>>,
+) {
+    let Some(lobby_state) = lobby_query.iter().next() else {
+        return;
+    };
+    for (player_id, mut name) in characters.iter_mut() {
+        let wanted = player_name(&lobby_state.profiles, player_id.0.to_bits());
+        if name.as_str() != wanted {
+            name.set(wanted);
+        }
+    }
+}
+
+/// Move players to the team they picked, unless that would unbalance the teams.
+fn handle_team_select_requests(
+    mut receivers: Query<(&RemoteId, &mut MessageReceiver<TeamSelectRequest>), With<Connected>>,
+    mut lobby_query: Query<&mut LobbyState>,
+) {
+    let Some(mut lobby_state) = lobby_query.iter_mut().next() else {
+        return;
+    };
+
+    for (remote_id, mut receiver) in receivers.iter_mut() {
+        let Some(request) = receiver.receive().last() else {
+            continue;
+        };
+
+        let player_id = remote_id.0.to_bits();
+        if !lobby_state.players.contains(&player_id)
+            || lobby_state.teams.get(&player_id) == Some(&request.team)
+        {
+            continue;
+        }
+
+        if can_join_team(&lobby_state.teams, player_id, request.team) {
+            info!("Client {} joined team {}", player_id, request.team.label());
+            lobby_state.teams.insert(player_id, request.team);
+        } else {
+            info!(
+                "Client {} cannot join team {}: teams would be unbalanced",
+                player_id,
+                request.team.label()
+            );
+        }
+    }
+}
+
+/// Starts loading a match on `seed`. Clients are told once the server is loading, so the
+/// level seed is there when they start.
+pub(crate) fn transition_to_loading(
     commands: &mut Commands,
-    sender: &mut ServerMultiMessageSender,
-    server: &Server,
+    scheduled: &mut ScheduledMessages,
+    seed: u64,
 ) {
     debug_println(format_args!("DEBUG: Server transitioning to Loading state"));
-    commands.spawn(GameSeed { seed: 42 });
+    commands.spawn(GameSeed { seed });
     commands.spawn((
-        LevelSeed { seed: 42 },
+        LevelSeed { seed },
         Replicate::to_clients(NetworkTarget::All),
     ));
     commands.set_state(ServerGameState::Loading);
-    sender
-        .send::<StartLoadingGameEvent, LobbyControlChannel>(
-            &StartLoadingGameEvent { start: true },
-            server,
-            &NetworkTarget::All,
-        )
-        .unwrap_or_else(|e| {
-            error!("Failed to send message: {:?}", e);
-        });
+    scheduled.schedule::<_, LobbyControlChannel>(
+        StartLoadingGameEvent { start: true },
+        SendWhen::StateEntered(ServerGameState::Loading),
+        NetworkTarget::All,
+    );
 }
 
+#[allow(clippy::too_many_arguments)]
 fn host_start_game_event(
     mut message_receiver_query: Query<
         (&RemoteId, &mut MessageReceiver<HostStartGameEvent>),
         bevy::prelude::With<Connected>,
     >,
-    mut sender: ServerMultiMessageSender,
-    server: Single<&Server>,
+    mut scheduled: ResMut<ScheduledMessages>,
     mut commands: Commands,
     server_state: Res<bevy::prelude::State<ServerGameState>>,
+    next_seed: Res<NextMatchSeed>,
     _meshes: ResMut<Assets<Mesh>>,
     _materials: Option<ResMut<Assets<StandardMaterial>>>,
 ) {
@@ -87,15 +257,15 @@ fn host_start_game_event(
     }
 
     if trigger {
-        transition_to_loading(&mut commands, &mut sender, server.into_inner());
+        transition_to_loading(&mut commands, &mut scheduled, next_seed.0);
     }
 }
 
 fn auto_start_game_when_lobby_ready(
     auto_start: Option<Res<AutoStartOnLobbyReady>>,
+    next_seed: Res<NextMatchSeed>,
     lobby_state: Query<&LobbyState>,
-    mut sender: ServerMultiMessageSender,
-    server: Single<&Server>,
+    mut scheduled: ResMut<ScheduledMessages>,
     mut commands: Commands,
 ) {
     let enabled = auto_start.map(|resource| resource.0).unwrap_or(false);
@@ -108,6 +278,6 @@ fn auto_start_game_when_lobby_ready(
     };
 
     if !lobby.players.is_empty() {
-        transition_to_loading(&mut commands, &mut sender, server.into_inner());
+        transition_to_loading(&mut commands, &mut scheduled, next_seed.0);
     }
 }
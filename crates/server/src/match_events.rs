@@ -0,0 +1,279 @@
+use bevy::prelude::{
+    App, Changed, Commands, IntoScheduleConfigs, Name, OnEnter, Plugin, Query, Res, Resource,
+    Startup, Update, error, in_state, info, warn,
+};
+use serde::Serialize;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tungstenite::Message;
+
+use shared::components::score::MatchScore;
+use shared::protocol::PlayerId;
+
+use crate::ServerGameState;
+
+/// Subscribers have this long to finish the WebSocket handshake.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+/// A send blocked this long means the subscriber stopped reading; it is dropped.
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+/// Subscribers served at once; more are closed right away.
+const MAX_SUBSCRIBERS: usize = 32;
+/// Events queued for one subscriber. One that falls this far behind is dropped.
+const SUBSCRIBER_QUEUE_EVENTS: usize = 256;
+
+/// Opt-in WebSocket stream of match events for companion apps (stream overlays, tournament
+/// dashboards). Disabled unless `addr` is set.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct MatchEventsSettings {
+    pub addr: Option<SocketAddr>,
+}
+
+impl MatchEventsSettings {
+    /// Listen on `port` on every interface.
+    pub fn on_port(port: u16) -> Self {
+        Self {
+            addr: Some(SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), port)),
+        }
+    }
+}
+
+/// What companion apps see, as JSON tagged by `type`. Players are identified by display
+/// name only: no peer ids, addresses or entity ids leave the server.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MatchEvent {
+    Phase {
+        phase: &'static str,
+    },
+    Kill {
+        killer: Option<String>,
+        victim: String,
+        headshot: bool,
+    },
+    /// Every player, best first.
+    Scores {
+        players: Vec<PlayerScore>,
+    },
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct PlayerScore {
+    pub name: String,
+    pub kills: u32,
+    pub deaths: u32,
+    pub assists: u32,
+}
+
+impl MatchEvent {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("match events serialize to JSON")
+    }
+}
+
+/// Running WebSocket broadcaster. Each subscriber is served by its own thread, up to
+/// [`MAX_SUBSCRIBERS`], so a slow consumer never blocks the game loop; disconnected or
+/// lagging ones are dropped on the next publish.
+#[derive(Resource, Clone)]
+pub struct MatchEventStream {
+    local_addr: SocketAddr,
+    subscribers: Arc<Mutex<Vec<SyncSender<String>>>>,
+}
+
+impl MatchEventStream {
+    pub fn bind(addr: SocketAddr) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let stream = Self {
+            local_addr: listener.local_addr()?,
+            subscribers: Arc::default(),
+        };
+
+        let subscribers = stream.subscribers.clone();
+        let active = Arc::new(AtomicUsize::new(0));
+        std::thread::spawn(move || {
+            for connection in listener.incoming().flatten() {
+                if active.fetch_add(1, Ordering::SeqCst) >= MAX_SUBSCRIBERS {
+                    active.fetch_sub(1, Ordering::SeqCst);
+                    warn!("Too many match event subscribers, closing a connection");
+                    continue;
+                }
+
+                let subscribers = subscribers.clone();
+                let active = active.clone();
+                std::thread::spawn(move || {
+                    serve_subscriber(connection, &subscribers);
+                    active.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+        });
+
+        Ok(stream)
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers
+            .lock()
+            .map_or(0, |subscribers| subscribers.len())
+    }
+
+    pub fn publish(&self, event: &MatchEvent) {
+        let json = event.to_json();
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.retain(|subscriber| match subscriber.try_send(json.clone()) {
+                Ok(()) => true,
+                Err(TrySendError::Full(_)) => {
+                    warn!("Dropping a match event subscriber that stopped reading");
+                    false
+                }
+                Err(TrySendError::Disconnected(_)) => false,
+            });
+        }
+    }
+}
+
+fn serve_subscriber(connection: TcpStream, subscribers: &Mutex<Vec<SyncSender<String>>>) {
+    let _ = connection.set_read_timeout(Some(HANDSHAKE_TIMEOUT));
+    let _ = connection.set_write_timeout(Some(HANDSHAKE_TIMEOUT));
+    let Ok(mut socket) = tungstenite::accept(connection) else {
+        return;
+    };
+    let _ = socket.get_ref().set_write_timeout(Some(SEND_TIMEOUT));
+
+    let (sender, receiver) = mpsc::sync_channel::<String>(SUBSCRIBER_QUEUE_EVENTS);
+    if let Ok(mut subscribers) = subscribers.lock() {
+        subscribers.push(sender);
+    }
+    for json in receiver {
+        if socket.send(Message::text(json)).is_err() {
+            break;
+        }
+    }
+}
+
+pub struct ServerMatchEventsPlugin;
+
+impl Plugin for ServerMatchEventsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MatchEventsSettings>();
+        app.add_systems(Startup, start_match_event_stream);
+        app.add_systems(OnEnter(ServerGameState::Lobby), publish_phase("lobby"));
+        app.add_systems(OnEnter(ServerGameState::Loading), publish_phase("loading"));
+        app.add_systems(OnEnter(ServerGameState::Playing), publish_phase("playing"));
+        app.add_systems(
+            OnEnter(ServerGameState::PostGame),
+            publish_phase("post_game"),
+        );
+        app.add_systems(
+            Update,
+            publish_scores.run_if(in_state(ServerGameState::Playing)),
+        );
+    }
+}
+
+fn start_match_event_stream(mut commands: Commands, settings: Res<MatchEventsSettings>) {
+    let Some(addr) = settings.addr else {
+        return;
+    };
+
+    match MatchEventStream::bind(addr) {
+        Ok(stream) => {
+            info!("📡 Streaming match events on ws://{}", stream.local_addr());
+            commands.insert_resource(stream);
+        }
+        Err(e) => error!("Failed to start the match event stream on {}: {}", addr, e),
+    }
+}
+
+fn publish_phase(phase: &'static str) -> impl Fn(Option<Res<MatchEventStream>>) {
+    move |stream: Option<Res<MatchEventStream>>| {
+        if let Some(stream) = stream {
+            stream.publish(&MatchEvent::Phase { phase });
+        }
+    }
+}
+
+fn publish_scores(
+    stream: Option<Res<MatchEventStream>>,
+    scores: Query<&MatchScore, Changed<MatchScore>>,
+    players: Query<(&PlayerId, &Name)>,
+) {
+    let (Some(stream), Some(score)) = (stream, scores.iter().next()) else {
+        return;
+    };
+
+    let players = score
+        .ranked()
+        .into_iter()
+        .map(|entry| {
+            let name = players
+                .iter()
+                .find(|(player_id, _)| player_id.0.to_bits() == entry.player_id)
+                .map_or_else(|| "Player".to_string(), |(_, name)| name.to_string());
+            PlayerScore {
+                name,
+                kills: entry.kills,
+                deaths: entry.deaths,
+                assists: entry.assists,
+            }
+        })
+        .collect();
+    stream.publish(&MatchEvent::Scores { players });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MatchEvent, MatchEventStream, PlayerScore};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn events_are_serialized_without_internal_ids() {
+        let kill = MatchEvent::Kill {
+            killer: None,
+            victim: "Bob \"the\" Bot".to_string(),
+            headshot: true,
+        };
+        assert_eq!(
+            kill.to_json(),
+            r#"{"type":"kill","killer":null,"victim":"Bob \"the\" Bot","headshot":true}"#
+        );
+
+        let scores = MatchEvent::Scores {
+            players: vec![PlayerScore {
+                name: "Player_1".to_string(),
+                kills: 3,
+                deaths: 1,
+                assists: 2,
+            }],
+        };
+        assert_eq!(
+            scores.to_json(),
+            r#"{"type":"scores","players":[{"name":"Player_1","kills":3,"deaths":1,"assists":2}]}"#
+        );
+    }
+
+    #[test]
+    fn subscribers_receive_published_events() {
+        let stream = MatchEventStream::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+        let (mut socket, _) =
+            tungstenite::connect(format!("ws://{}", stream.local_addr())).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while stream.subscriber_count() == 0 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        stream.publish(&MatchEvent::Phase { phase: "playing" });
+
+        let message = socket.read().unwrap();
+        assert_eq!(
+            message.to_text().unwrap(),
+            r#"{"type":"phase","phase":"playing"}"#
+        );
+    }
+}
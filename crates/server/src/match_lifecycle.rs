@@ -0,0 +1,213 @@
+use bevy::prelude::{
+    App, Commands, CommandsStatesExt, Entity, IntoScheduleConfigs, Name, OnEnter, OnExit, Or,
+    Plugin, Query, Res, ResMut, Resource, Single, Time, Timer, TimerMode, Update, With, error,
+    in_state, info, resource_changed,
+};
+
+use lightyear::prelude::{NetworkTarget, Replicate, Server, ServerMultiMessageSender};
+use shared::components::destructible::DebrisPiece;
+use shared::components::grenade::Grenade;
+use shared::components::match_timer::{MatchTimer, MatchTimerSettings, POST_GAME_SECS};
+use shared::components::score::MatchScore;
+use shared::components::weapons::Projectile;
+use shared::level::generation::LevelGeometry;
+use shared::level::preload::LevelPreloader;
+use shared::protocol::{
+    CharacterMarker, GameSeed, LevelSeed, LobbyControlChannel, MatchEndedEvent, PreloadLevelEvent,
+    ReturnToLobbyEvent,
+};
+
+use crate::ServerGameState;
+use crate::lobby::NextMatchSeed;
+use crate::scheduled_messages::{ScheduledMessages, SendWhen, ensure_scheduled_messages};
+
+/// Everything a match leaves behind: the level, characters, live projectiles and the
+/// per-match bookkeeping entities.
+type MatchEntityFilter = Or<(
+    With<LevelGeometry>,
+    With<CharacterMarker>,
+    With<Projectile>,
+    With<Grenade>,
+    With<DebrisPiece>,
+    With<LevelSeed>,
+    With<GameSeed>,
+    With<MatchScore>,
+    With<MatchTimer>,
+)>;
+
+/// Match timer, post-game screen and return to the lobby. The next match's level is
+/// generated in the background from the start of the post-game screen, here and on the
+/// clients, so the next `Loading` only has to spawn it.
+pub struct ServerMatchLifecyclePlugin;
+
+impl Plugin for ServerMatchLifecyclePlugin {
+    fn build(&self, app: &mut App) {
+        ensure_scheduled_messages(app);
+        app.init_resource::<MatchTimerSettings>();
+        app.add_systems(OnEnter(ServerGameState::Playing), start_match_timer);
+        app.add_systems(
+            Update,
+            end_match_when_time_runs_out.run_if(in_state(ServerGameState::Playing)),
+        );
+        app.add_systems(
+            OnEnter(ServerGameState::PostGame),
+            (start_post_game, preload_next_level),
+        );
+        // A map change before the next match starts replaces the preload.
+        app.add_systems(
+            Update,
+            preload_next_level
+                .run_if(resource_changed::<NextMatchSeed>)
+                .run_if(is_preloading),
+        );
+        app.add_systems(
+            Update,
+            return_to_lobby_after_post_game.run_if(in_state(ServerGameState::PostGame)),
+        );
+        app.add_systems(OnExit(ServerGameState::PostGame), despawn_match_entities);
+    }
+}
+
+#[derive(Resource)]
+struct PostGameTimer(Timer);
+
+fn start_match_timer(
+    mut commands: Commands,
+    settings: Res<MatchTimerSettings>,
+    existing: Query<&MatchTimer>,
+) {
+    if existing.is_empty() {
+        commands.spawn((
+            MatchTimer::new(settings.duration_secs),
+            Replicate::to_clients(NetworkTarget::All),
+            Name::from("MatchTimer"),
+        ));
+    }
+}
+
+fn end_match_when_time_runs_out(
+    time: Res<Time>,
+    mut timer_query: Query<&mut MatchTimer>,
+    score_query: Query<&MatchScore>,
+    mut scheduled: ResMut<ScheduledMessages>,
+    mut commands: Commands,
+) {
+    let Some(mut timer) = timer_query.iter_mut().next() else {
+        return;
+    };
+    if !timer.tick(time.delta_secs()) {
+        return;
+    }
+
+    info!("⏱️ Match time is up, showing final scores");
+    let final_score = score_query.iter().next().cloned().unwrap_or_default();
+    commands.set_state(ServerGameState::PostGame);
+    scheduled.schedule::<_, LobbyControlChannel>(
+        MatchEndedEvent { final_score },
+        SendWhen::StateEntered(ServerGameState::PostGame),
+        NetworkTarget::All,
+    );
+}
+
+fn start_post_game(mut commands: Commands) {
+    commands.insert_resource(PostGameTimer(Timer::from_seconds(
+        POST_GAME_SECS,
+        TimerMode::Once,
+    )));
+}
+
+fn is_preloading(preloader: Option<Res<LevelPreloader>>) -> bool {
+    preloader.is_some_and(|preloader| preloader.seed().is_some())
+}
+
+fn preload_next_level(
+    next_seed: Res<NextMatchSeed>,
+    preloader: Option<ResMut<LevelPreloader>>,
+    mut sender: ServerMultiMessageSender,
+    server: Single<&Server>,
+) {
+    if let Some(mut preloader) = preloader {
+        preloader.preload(next_seed.0);
+    }
+    sender
+        .send::<PreloadLevelEvent, LobbyControlChannel>(
+            &PreloadLevelEvent { seed: next_seed.0 },
+            server.into_inner(),
+            &NetworkTarget::All,
+        )
+        .unwrap_or_else(|e| {
+            error!("Failed to send message: {:?}", e);
+        });
+}
+
+fn return_to_lobby_after_post_game(
+    time: Res<Time>,
+    mut post_game: ResMut<PostGameTimer>,
+    mut scheduled: ResMut<ScheduledMessages>,
+    mut commands: Commands,
+) {
+    if !post_game.0.tick(time.delta()).is_finished() {
+        return;
+    }
+
+    info!("🏁 Post-game over, returning to the lobby");
+    commands.set_state(ServerGameState::Lobby);
+    // Sent once the match is cleared, on leaving the post-game screen.
+    scheduled.schedule::<_, LobbyControlChannel>(
+        ReturnToLobbyEvent,
+        SendWhen::StateEntered(ServerGameState::Lobby),
+        NetworkTarget::All,
+    );
+}
+
+/// Clear the finished match so the next one starts from an empty world. Despawning the
+/// replicated entities also removes them from every client.
+fn despawn_match_entities(
+    mut commands: Commands,
+    match_entities: Query<Entity, MatchEntityFilter>,
+) {
+    commands.remove_resource::<PostGameTimer>();
+    for entity in match_entities.iter() {
+        // Children are despawned with their parent; skip them if they come up again.
+        commands.entity(entity).try_despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MatchEntityFilter, despawn_match_entities};
+    use bevy::prelude::{App, MinimalPlugins, Name, Update};
+    use shared::components::match_timer::MatchTimer;
+    use shared::level::generation::LevelGeometry;
+    use shared::protocol::{CharacterMarker, LobbyState};
+
+    #[test]
+    fn match_cleanup_keeps_the_lobby() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_systems(Update, despawn_match_entities);
+
+        let lobby = app
+            .world_mut()
+            .spawn(LobbyState {
+                players: vec![1],
+                host_id: 1,
+                teams: Default::default(),
+                profiles: Default::default(),
+            })
+            .id();
+        app.world_mut().spawn((LevelGeometry, Name::new("Wall")));
+        app.world_mut().spawn(CharacterMarker);
+        app.world_mut().spawn(MatchTimer::new(0.0));
+
+        app.update();
+
+        let remaining = app
+            .world_mut()
+            .query_filtered::<bevy::prelude::Entity, MatchEntityFilter>()
+            .iter(app.world())
+            .count();
+        assert_eq!(remaining, 0);
+        assert!(app.world().get_entity(lobby).is_ok());
+    }
+}
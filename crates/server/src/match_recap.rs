@@ -0,0 +1,389 @@
+use avian3d::prelude::Position;
+use bevy::prelude::{
+    App, Commands, IntoScheduleConfigs, Name, OnEnter, Plugin, Query, Res, ResMut, Resource,
+    Single, Time, Timer, TimerMode, Update, Vec3, With, error, in_state, info, warn,
+};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use lightyear::prelude::{NetworkTarget, Server, ServerMultiMessageSender};
+use shared::components::health::Health;
+use shared::components::score::MatchScore;
+use shared::match_recap::{
+    DamageRecord, KillRecord, MatchRecapEvent, MatchTelemetry, PlayerTrack, Standing,
+    TRACK_SAMPLE_SECS,
+};
+use shared::protocol::{KillFeedEvent, LevelSeed, LobbyControlChannel, PlayerId};
+
+use crate::ServerGameState;
+
+/// How long the results screen waits for the language model before using the template.
+const LLM_RECAP_TIMEOUT_SECS: f32 = 6.0;
+
+/// Opt-in extras for the post-match recap. The template recap is shown either way.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct MatchRecapSettings {
+    /// The llm crate's `llm-recap` executable: reads a prompt on stdin and prints the recap.
+    pub llm_command: Option<PathBuf>,
+    /// Directory match records are saved to, one JSON file per match.
+    pub records_dir: Option<PathBuf>,
+}
+
+/// Telemetry of the running match.
+#[derive(Resource, Default, Debug)]
+pub struct MatchTelemetryRecorder {
+    started_at_secs: f32,
+    pub telemetry: MatchTelemetry,
+    next_sample_secs: f32,
+    /// Players alive at the last sample, whose tracks continue.
+    sampled: HashSet<String>,
+}
+
+impl MatchTelemetryRecorder {
+    pub fn record_kill(&mut self, now_secs: f32, kill: &KillFeedEvent, position: Option<Vec3>) {
+        self.telemetry.kills.push(KillRecord {
+            at_secs: now_secs - self.started_at_secs,
+            killer: kill.killer.clone(),
+            victim: kill.victim.clone(),
+            headshot: kill.headshot,
+            position,
+        });
+    }
+
+    pub fn record_damage(&mut self, now_secs: f32, position: Vec3, amount: f32) {
+        self.telemetry.damage.push(DamageRecord {
+            at_secs: now_secs - self.started_at_secs,
+            position,
+            amount,
+        });
+    }
+
+    /// Adds a point to the track of `name`, starting a new segment unless the player was
+    /// also sampled last time.
+    fn sample_track(&mut self, name: &str, position: Vec3, continues: bool) {
+        let index = match self
+            .telemetry
+            .tracks
+            .iter()
+            .position(|track| track.name == name)
+        {
+            Some(index) => index,
+            None => {
+                self.telemetry.tracks.push(PlayerTrack {
+                    name: name.to_string(),
+                    segments: Vec::new(),
+                });
+                self.telemetry.tracks.len() - 1
+            }
+        };
+        let track = &mut self.telemetry.tracks[index];
+        if !continues || track.segments.is_empty() {
+            track.segments.push(Vec::new());
+        }
+        if let Some(segment) = track.segments.last_mut() {
+            segment.push(position);
+        }
+    }
+}
+
+/// Recap requested from the language model, and how long we still wait for it.
+#[derive(Resource)]
+struct PendingLlmRecap {
+    replies: Mutex<Receiver<Option<String>>>,
+    timeout: Timer,
+}
+
+/// Writes a short narrative recap of every finished match for the results screen, and saves
+/// it with the match record when records are enabled.
+pub struct ServerMatchRecapPlugin;
+
+impl Plugin for ServerMatchRecapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MatchRecapSettings>();
+        app.init_resource::<MatchTelemetryRecorder>();
+        app.add_systems(OnEnter(ServerGameState::Playing), start_match_telemetry);
+        app.add_systems(OnEnter(ServerGameState::PostGame), write_match_recap);
+        app.add_systems(
+            Update,
+            sample_player_tracks.run_if(in_state(ServerGameState::Playing)),
+        );
+        app.add_systems(
+            Update,
+            poll_llm_recap.run_if(in_state(ServerGameState::PostGame)),
+        );
+    }
+}
+
+fn start_match_telemetry(
+    time: Res<Time>,
+    mut recorder: ResMut<MatchTelemetryRecorder>,
+    level_seed: Query<&LevelSeed>,
+) {
+    *recorder = MatchTelemetryRecorder {
+        started_at_secs: time.elapsed_secs(),
+        telemetry: MatchTelemetry {
+            seed: level_seed.iter().next().map_or(0, |level| level.seed),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+}
+
+fn sample_player_tracks(
+    time: Res<Time>,
+    mut recorder: ResMut<MatchTelemetryRecorder>,
+    players: Query<(&Name, &Position, &Health), With<PlayerId>>,
+) {
+    let now = time.elapsed_secs() - recorder.started_at_secs;
+    if now < recorder.next_sample_secs {
+        return;
+    }
+    recorder.next_sample_secs = now + TRACK_SAMPLE_SECS;
+
+    // Dead players are not sampled, so their track breaks until they respawn.
+    let previous = std::mem::take(&mut recorder.sampled);
+    for (name, position, _) in players.iter().filter(|(.., health)| !health.is_dead) {
+        recorder.sample_track(name.as_str(), position.0, previous.contains(name.as_str()));
+        recorder.sampled.insert(name.to_string());
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn write_match_recap(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<MatchRecapSettings>,
+    mut recorder: ResMut<MatchTelemetryRecorder>,
+    score: Query<&MatchScore>,
+    players: Query<(&PlayerId, &Name)>,
+    mut sender: ServerMultiMessageSender,
+    server: Single<&Server>,
+) {
+    let started_at_secs = recorder.started_at_secs;
+    let telemetry = &mut recorder.telemetry;
+    telemetry.duration_secs = time.elapsed_secs() - started_at_secs;
+    telemetry.standings = score
+        .iter()
+        .next()
+        .map(MatchScore::ranked)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|entry| Standing {
+            name: players
+                .iter()
+                .find(|(player_id, _)| player_id.0.to_bits() == entry.player_id)
+                .map_or_else(
+                    || format!("Player_{}", entry.player_id),
+                    |(_, name)| name.to_string(),
+                ),
+            kills: entry.kills,
+            deaths: entry.deaths,
+            assists: entry.assists,
+        })
+        .collect();
+
+    let Some(llm_command) = settings.llm_command.clone() else {
+        let recap = telemetry.template_recap();
+        publish_recap(
+            recap,
+            telemetry,
+            &settings,
+            &mut sender,
+            server.into_inner(),
+        );
+        return;
+    };
+
+    // Generation takes seconds: run it off the game loop and poll for the answer.
+    let prompt = telemetry.recap_prompt();
+    let (reply, replies) = mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = reply.send(run_llm_recap(&llm_command, &prompt));
+    });
+    commands.insert_resource(PendingLlmRecap {
+        replies: Mutex::new(replies),
+        timeout: Timer::from_seconds(LLM_RECAP_TIMEOUT_SECS, TimerMode::Once),
+    });
+}
+
+fn poll_llm_recap(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<MatchRecapSettings>,
+    recorder: Res<MatchTelemetryRecorder>,
+    pending: Option<ResMut<PendingLlmRecap>>,
+    mut sender: ServerMultiMessageSender,
+    server: Single<&Server>,
+) {
+    let Some(mut pending) = pending else {
+        return;
+    };
+
+    let timed_out = pending.timeout.tick(time.delta()).is_finished();
+    let reply = match pending.replies.lock().map(|replies| replies.try_recv()) {
+        Ok(Ok(reply)) => reply,
+        Ok(Err(TryRecvError::Empty)) if !timed_out => return,
+        _ => None,
+    };
+    if reply.is_none() {
+        warn!("No recap from the language model, using the template");
+    }
+
+    commands.remove_resource::<PendingLlmRecap>();
+    let recap = reply.unwrap_or_else(|| recorder.telemetry.template_recap());
+    publish_recap(
+        recap,
+        &recorder.telemetry,
+        &settings,
+        &mut sender,
+        server.into_inner(),
+    );
+}
+
+/// Feed `prompt` to the recap executable. Returns its first paragraph, if it printed one.
+fn run_llm_recap(command: &Path, prompt: &str) -> Option<String> {
+    let mut child = Command::new(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .inspect_err(|e| error!("Failed to run {}: {}", command.display(), e))
+        .ok()?;
+    child.stdin.take()?.write_all(prompt.as_bytes()).ok()?;
+
+    let output = child.wait_with_output().ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let recap = text.trim().split("\n\n").next()?.trim();
+    (output.status.success() && !recap.is_empty()).then(|| recap.to_string())
+}
+
+fn publish_recap(
+    recap: String,
+    telemetry: &MatchTelemetry,
+    settings: &MatchRecapSettings,
+    sender: &mut ServerMultiMessageSender,
+    server: &Server,
+) {
+    info!("📝 Match recap: {}", recap);
+    if let Some(dir) = &settings.records_dir {
+        save_match_record(dir, telemetry, &recap);
+    }
+
+    sender
+        .send::<MatchRecapEvent, LobbyControlChannel>(
+            &MatchRecapEvent { text: recap },
+            server,
+            &NetworkTarget::All,
+        )
+        .unwrap_or_else(|e| {
+            error!("Failed to send message: {:?}", e);
+        });
+}
+
+/// What a match record holds: the telemetry without positions, and the recap.
+#[derive(Serialize)]
+struct MatchRecord<'a> {
+    seed: u64,
+    duration_secs: f32,
+    recap: &'a str,
+    standings: &'a [Standing],
+    kills: Vec<RecordedKill<'a>>,
+}
+
+#[derive(Serialize)]
+struct RecordedKill<'a> {
+    at_secs: f32,
+    killer: Option<&'a str>,
+    victim: &'a str,
+    headshot: bool,
+}
+
+/// The match record: telemetry and recap, as JSON.
+pub fn match_record_json(telemetry: &MatchTelemetry, recap: &str) -> String {
+    let record = MatchRecord {
+        seed: telemetry.seed,
+        duration_secs: telemetry.duration_secs,
+        recap,
+        standings: &telemetry.standings,
+        kills: telemetry
+            .kills
+            .iter()
+            .map(|kill| RecordedKill {
+                at_secs: kill.at_secs,
+                killer: kill.killer.as_deref(),
+                victim: &kill.victim,
+                headshot: kill.headshot,
+            })
+            .collect(),
+    };
+    serde_json::to_string(&record).expect("match records serialize to JSON")
+}
+
+fn save_match_record(dir: &Path, telemetry: &MatchTelemetry, recap: &str) {
+    let finished_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let path = dir.join(format!("match_{}.json", finished_at));
+    let saved = std::fs::create_dir_all(dir)
+        .and_then(|_| std::fs::write(&path, match_record_json(telemetry, recap)));
+    match saved {
+        Ok(()) => info!("💾 Saved match record to {}", path.display()),
+        Err(e) => error!(
+            "Failed to save the match record to {}: {}",
+            path.display(),
+            e
+        ),
+    }
+
+    // The full telemetry, positions included, for `launcher analytics`.
+    let path = dir.join(format!("match_{}.ron", finished_at));
+    let saved = telemetry
+        .to_ron()
+        .and_then(|text| std::fs::write(&path, text).map_err(|e| e.to_string()));
+    if let Err(e) = saved {
+        error!(
+            "Failed to save the match telemetry to {}: {}",
+            path.display(),
+            e
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::match_record_json;
+    use shared::match_recap::{KillRecord, MatchTelemetry, Standing};
+
+    #[test]
+    fn match_records_hold_the_telemetry_and_recap() {
+        let telemetry = MatchTelemetry {
+            seed: 7,
+            duration_secs: 120.0,
+            kills: vec![KillRecord {
+                at_secs: 12.5,
+                killer: None,
+                victim: "Player_1".to_string(),
+                headshot: false,
+                position: None,
+            }],
+            standings: vec![Standing {
+                name: "Player_1".to_string(),
+                kills: 0,
+                deaths: 1,
+                assists: 0,
+            }],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            match_record_json(&telemetry, "A \"quiet\" one."),
+            r#"{"seed":7,"duration_secs":120.0,"recap":"A \"quiet\" one.","standings":[{"name":"Player_1","kills":0,"deaths":1,"assists":0}],"kills":[{"at_secs":12.5,"killer":null,"victim":"Player_1","headshot":false}]}"#
+        );
+    }
+}
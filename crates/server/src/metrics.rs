@@ -0,0 +1,591 @@
+use avian3d::prelude::PhysicsSystems;
+use bevy::prelude::{
+    App, Commands, Entity, First, FixedPostUpdate, IntoScheduleConfigs, Last, Local, Plugin,
+    PostUpdate, PreUpdate, Query, Res, ResMut, Resource, Startup, Time, Timer, TimerMode, Update,
+    With, error, info, warn,
+};
+use metrics::{Counter, Gauge, Histogram, Key, KeyName, Metadata, Recorder, SharedString, Unit};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use lightyear::connection::client_of::ClientOf;
+use lightyear::prelude::{Connected, Link, LinkSystems, Replicate, TransportSystems};
+use shared::bots::BotProfile;
+use shared::protocol::PlayerId;
+
+use crate::director::{AiDirector, render_director_metrics};
+
+/// Scrapes read a snapshot refreshed this often, not live state.
+const METRICS_REFRESH_SECS: f32 = 1.0;
+/// Upper bounds of the duration histogram buckets, in seconds.
+const DURATION_BUCKETS: [f64; 10] = [
+    0.0005, 0.001, 0.0025, 0.005, 0.01, 0.0167, 0.025, 0.05, 0.1, 0.25,
+];
+/// Scrapes have this long to send their request; slower ones are closed.
+const SCRAPE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Scrapes served at once; more are closed right away.
+const MAX_SCRAPES: usize = 4;
+/// Longest request read from a scrape, headers included.
+const MAX_SCRAPE_REQUEST_BYTES: u64 = 8 * 1024;
+
+/// Opt-in Prometheus endpoint. Disabled unless `addr` is set.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct MetricsSettings {
+    pub addr: Option<SocketAddr>,
+}
+
+impl MetricsSettings {
+    /// Serve `/metrics` on `port` on every interface.
+    pub fn on_port(port: u16) -> Self {
+        Self {
+            addr: Some(SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), port)),
+        }
+    }
+}
+
+/// Cumulative histogram in the Prometheus layout.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DurationHistogram {
+    buckets: [u64; DURATION_BUCKETS.len()],
+    count: u64,
+    sum_secs: f64,
+}
+
+impl Default for DurationHistogram {
+    fn default() -> Self {
+        Self {
+            buckets: [0; DURATION_BUCKETS.len()],
+            count: 0,
+            sum_secs: 0.0,
+        }
+    }
+}
+
+impl DurationHistogram {
+    pub fn observe(&mut self, secs: f64) {
+        for (bucket, bound) in self.buckets.iter_mut().zip(DURATION_BUCKETS) {
+            if secs <= bound {
+                *bucket += 1;
+            }
+        }
+        self.count += 1;
+        self.sum_secs += secs;
+    }
+
+    fn render(&self, out: &mut String, name: &str, help: &str) {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} histogram", name);
+        for (bucket, bound) in self.buckets.iter().zip(DURATION_BUCKETS) {
+            let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, bound, bucket);
+        }
+        let _ = writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, self.count);
+        let _ = writeln!(out, "{}_sum {}", name, self.sum_secs);
+        let _ = writeln!(out, "{}_count {}", name, self.count);
+    }
+}
+
+/// What the server measures for operators. Counts of players and entities are taken when
+/// the snapshot is rendered.
+#[derive(Resource, Clone, Debug, Default, PartialEq)]
+pub struct ServerMetrics {
+    /// Wall time of a whole frame, from `First` to `Last`.
+    pub tick_duration: DurationHistogram,
+    pub physics_step: DurationHistogram,
+    /// Traffic of every channel together, at the link. Lightyear's own counters break it
+    /// down per channel, see [`LightyearMetrics`].
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+}
+
+/// Gauges sampled when the snapshot is rendered.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct EntityCounts {
+    pub connected_players: usize,
+    pub characters: usize,
+    pub bots: usize,
+    pub replicated: usize,
+    pub total: usize,
+}
+
+/// The Prometheus text exposition of `metrics`.
+pub fn render_metrics(metrics: &ServerMetrics, counts: &EntityCounts) -> String {
+    let mut out = String::new();
+    metrics.tick_duration.render(
+        &mut out,
+        "yolo_server_tick_duration_seconds",
+        "Wall time of one server frame.",
+    );
+    metrics.physics_step.render(
+        &mut out,
+        "yolo_server_physics_step_seconds",
+        "Wall time of one physics step.",
+    );
+
+    let _ = writeln!(
+        out,
+        "# HELP yolo_server_network_bytes_total Bytes moved through client links."
+    );
+    let _ = writeln!(out, "# TYPE yolo_server_network_bytes_total counter");
+    let _ = writeln!(
+        out,
+        "yolo_server_network_bytes_total{{direction=\"sent\"}} {}",
+        metrics.bytes_sent
+    );
+    let _ = writeln!(
+        out,
+        "yolo_server_network_bytes_total{{direction=\"received\"}} {}",
+        metrics.bytes_received
+    );
+
+    let _ = writeln!(
+        out,
+        "# HELP yolo_server_connected_players Clients currently connected."
+    );
+    let _ = writeln!(out, "# TYPE yolo_server_connected_players gauge");
+    let _ = writeln!(
+        out,
+        "yolo_server_connected_players {}",
+        counts.connected_players
+    );
+
+    let _ = writeln!(
+        out,
+        "# HELP yolo_server_entities Entities in the server world."
+    );
+    let _ = writeln!(out, "# TYPE yolo_server_entities gauge");
+    for (kind, count) in [
+        ("character", counts.characters),
+        ("bot", counts.bots),
+        ("replicated", counts.replicated),
+        ("all", counts.total),
+    ] {
+        let _ = writeln!(out, "yolo_server_entities{{kind=\"{}\"}} {}", kind, count);
+    }
+    out
+}
+
+/// What lightyear records through the `metrics` facade: bytes and messages per channel,
+/// replication and input counters, with their labels. Installed as the process' recorder
+/// when the endpoint starts, and rendered under `lightyear_`.
+#[derive(Resource, Clone, Default)]
+pub struct LightyearMetrics {
+    counters: Arc<Mutex<HashMap<Key, Arc<AtomicU64>>>>,
+    /// Gauges hold the bits of an `f64`.
+    gauges: Arc<Mutex<HashMap<Key, Arc<AtomicU64>>>>,
+}
+
+impl LightyearMetrics {
+    pub fn render(&self, out: &mut String) {
+        render_samples(out, &self.counters, "counter", |value| value.to_string());
+        render_samples(out, &self.gauges, "gauge", |value| {
+            f64::from_bits(value).to_string()
+        });
+    }
+}
+
+impl Recorder for LightyearMetrics {
+    fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+        Counter::from_arc(register_sample(&self.counters, key))
+    }
+
+    fn register_gauge(&self, key: &Key, _: &Metadata<'_>) -> Gauge {
+        Gauge::from_arc(register_sample(&self.gauges, key))
+    }
+
+    /// Lightyear's histograms are timings its UI plots; the server has its own.
+    fn register_histogram(&self, _: &Key, _: &Metadata<'_>) -> Histogram {
+        Histogram::noop()
+    }
+}
+
+fn register_sample(samples: &Mutex<HashMap<Key, Arc<AtomicU64>>>, key: &Key) -> Arc<AtomicU64> {
+    let Ok(mut samples) = samples.lock() else {
+        return Arc::default();
+    };
+    samples.entry(key.clone()).or_default().clone()
+}
+
+fn render_samples(
+    out: &mut String,
+    samples: &Mutex<HashMap<Key, Arc<AtomicU64>>>,
+    kind: &str,
+    format_value: impl Fn(u64) -> String,
+) {
+    let Ok(samples) = samples.lock() else {
+        return;
+    };
+    let mut lines: Vec<(String, String, String)> = samples
+        .iter()
+        .map(|(key, value)| {
+            (
+                format!("lightyear_{}", prometheus_name(key.name())),
+                prometheus_labels(key),
+                format_value(value.load(Ordering::Relaxed)),
+            )
+        })
+        .collect();
+    lines.sort();
+
+    let mut typed: Option<&str> = None;
+    for (name, labels, value) in &lines {
+        if typed != Some(name.as_str()) {
+            let _ = writeln!(out, "# TYPE {} {}", name, kind);
+            typed = Some(name);
+        }
+        let _ = writeln!(out, "{}{} {}", name, labels, value);
+    }
+}
+
+/// `name` with everything Prometheus does not allow in names replaced by `_`.
+fn prometheus_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+fn prometheus_labels(key: &Key) -> String {
+    let labels: Vec<String> = key
+        .labels()
+        .map(|label| {
+            let value = label
+                .value()
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{}=\"{}\"", prometheus_name(label.key()), value)
+        })
+        .collect();
+    if labels.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", labels.join(","))
+    }
+}
+
+/// Running `/metrics` endpoint. Scrapes are answered from the last published snapshot by a
+/// thread per connection, at most [`MAX_SCRAPES`] at once, so they never wait on the game
+/// loop.
+#[derive(Resource, Clone)]
+pub struct MetricsEndpoint {
+    local_addr: SocketAddr,
+    snapshot: Arc<Mutex<String>>,
+}
+
+impl MetricsEndpoint {
+    pub fn bind(addr: SocketAddr) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let endpoint = Self {
+            local_addr: listener.local_addr()?,
+            snapshot: Arc::default(),
+        };
+
+        let snapshot = endpoint.snapshot.clone();
+        let active = Arc::new(AtomicUsize::new(0));
+        std::thread::spawn(move || {
+            for connection in listener.incoming().flatten() {
+                if active.fetch_add(1, Ordering::SeqCst) >= MAX_SCRAPES {
+                    active.fetch_sub(1, Ordering::SeqCst);
+                    warn!("Too many metrics scrapes, closing a connection");
+                    continue;
+                }
+
+                let snapshot = snapshot.clone();
+                let active = active.clone();
+                std::thread::spawn(move || {
+                    serve_scrape(connection, &snapshot);
+                    active.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+        });
+
+        Ok(endpoint)
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    pub fn publish(&self, text: String) {
+        if let Ok(mut snapshot) = self.snapshot.lock() {
+            *snapshot = text;
+        }
+    }
+}
+
+fn serve_scrape(connection: TcpStream, snapshot: &Mutex<String>) {
+    let started = Instant::now();
+    let _ = connection.set_read_timeout(Some(SCRAPE_TIMEOUT));
+    let _ = connection.set_write_timeout(Some(SCRAPE_TIMEOUT));
+    let Ok(mut writer) = connection.try_clone() else {
+        return;
+    };
+    let mut reader = BufReader::new(connection.take(MAX_SCRAPE_REQUEST_BYTES));
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    // Skip the headers; scrapes have no body. A client trickling them in is cut off once
+    // the whole request took too long.
+    let mut header = String::new();
+    while reader.read_line(&mut header).is_ok_and(|read| read > 2) {
+        if started.elapsed() > SCRAPE_TIMEOUT {
+            return;
+        }
+        header.clear();
+    }
+
+    let path = request_line.split_whitespace().nth(1).unwrap_or("");
+    let (status, body) = if request_line.starts_with("GET ") && path == "/metrics" {
+        let body = snapshot
+            .lock()
+            .map_or_else(|_| String::new(), |text| text.clone());
+        ("200 OK", body)
+    } else {
+        ("404 Not Found", "try /metrics\n".to_string())
+    };
+    let _ = write!(
+        writer,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+}
+
+/// Prometheus metrics for operators: frame and physics step durations, connected players,
+/// entity counts and network traffic, per channel too, served on `/metrics` when enabled.
+pub struct ServerMetricsPlugin;
+
+impl Plugin for ServerMetricsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MetricsSettings>();
+        app.init_resource::<ServerMetrics>();
+        app.init_resource::<TickClock>();
+        app.init_resource::<PhysicsClock>();
+        app.add_systems(Startup, start_metrics_endpoint);
+        app.add_systems(First, start_tick_clock);
+        app.add_systems(Last, stop_tick_clock);
+        app.add_systems(
+            FixedPostUpdate,
+            (
+                start_physics_clock.before(PhysicsSystems::StepSimulation),
+                stop_physics_clock.after(PhysicsSystems::StepSimulation),
+            ),
+        );
+        // Count bytes while they sit in the link buffers, between the IO and transport
+        // layers.
+        app.add_systems(
+            PreUpdate,
+            count_received_bytes
+                .after(LinkSystems::Receive)
+                .before(TransportSystems::Receive),
+        );
+        app.add_systems(
+            PostUpdate,
+            count_sent_bytes
+                .after(TransportSystems::Send)
+                .before(LinkSystems::Send),
+        );
+        app.add_systems(Update, publish_metrics);
+    }
+}
+
+/// When the running frame started.
+#[derive(Resource, Default)]
+struct TickClock(Option<Instant>);
+
+/// When the running physics step started.
+#[derive(Resource, Default)]
+struct PhysicsClock(Option<Instant>);
+
+fn start_metrics_endpoint(mut commands: Commands, settings: Res<MetricsSettings>) {
+    let Some(addr) = settings.addr else {
+        return;
+    };
+
+    match MetricsEndpoint::bind(addr) {
+        Ok(endpoint) => {
+            info!(
+                "📈 Serving metrics on http://{}/metrics",
+                endpoint.local_addr()
+            );
+            commands.insert_resource(endpoint);
+        }
+        Err(e) => {
+            error!("Failed to start the metrics endpoint on {}: {}", addr, e);
+            return;
+        }
+    }
+
+    // The recorder is global, so a second server in the same process keeps the first's.
+    let recorder = LightyearMetrics::default();
+    match metrics::set_global_recorder(recorder.clone()) {
+        Ok(()) => commands.insert_resource(recorder),
+        Err(_) => warn!("A metrics recorder is already installed, lightyear's are left out"),
+    }
+}
+
+fn start_tick_clock(mut clock: ResMut<TickClock>) {
+    clock.0 = Some(Instant::now());
+}
+
+fn stop_tick_clock(mut clock: ResMut<TickClock>, mut metrics: ResMut<ServerMetrics>) {
+    if let Some(started) = clock.0.take() {
+        metrics
+            .tick_duration
+            .observe(started.elapsed().as_secs_f64());
+    }
+}
+
+fn start_physics_clock(mut clock: ResMut<PhysicsClock>) {
+    clock.0 = Some(Instant::now());
+}
+
+fn stop_physics_clock(mut clock: ResMut<PhysicsClock>, mut metrics: ResMut<ServerMetrics>) {
+    if let Some(started) = clock.0.take() {
+        metrics
+            .physics_step
+            .observe(started.elapsed().as_secs_f64());
+    }
+}
+
+fn count_received_bytes(links: Query<&Link, With<ClientOf>>, mut metrics: ResMut<ServerMetrics>) {
+    for link in &links {
+        metrics.bytes_received += link
+            .recv
+            .iter()
+            .map(|payload| payload.len() as u64)
+            .sum::<u64>();
+    }
+}
+
+fn count_sent_bytes(links: Query<&Link, With<ClientOf>>, mut metrics: ResMut<ServerMetrics>) {
+    for link in &links {
+        metrics.bytes_sent += link
+            .send
+            .iter()
+            .map(|payload| payload.len() as u64)
+            .sum::<u64>();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn publish_metrics(
+    time: Res<Time>,
+    mut refresh: Local<Option<Timer>>,
+    endpoint: Option<Res<MetricsEndpoint>>,
+    metrics: Res<ServerMetrics>,
+    clients: Query<(), (With<ClientOf>, With<Connected>)>,
+    characters: Query<(), With<PlayerId>>,
+    bots: Query<(), With<BotProfile>>,
+    replicated: Query<(), With<Replicate>>,
+    entities: Query<Entity>,
+    lightyear: Option<Res<LightyearMetrics>>,
+    director: Option<Res<AiDirector>>,
+) {
+    let Some(endpoint) = endpoint else {
+        return;
+    };
+    let refresh = refresh
+        .get_or_insert_with(|| Timer::from_seconds(METRICS_REFRESH_SECS, TimerMode::Repeating));
+    if !refresh.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let counts = EntityCounts {
+        connected_players: clients.iter().count(),
+        characters: characters.iter().count(),
+        bots: bots.iter().count(),
+        replicated: replicated.iter().count(),
+        total: entities.iter().count(),
+    };
+    let mut text = render_metrics(&metrics, &counts);
+    if let Some(lightyear) = lightyear {
+        lightyear.render(&mut text);
+    }
+    if let Some(decision) = director
+        .as_ref()
+        .and_then(|director| director.director.last_decision.as_ref())
+    {
+        render_director_metrics(&mut text, decision);
+    }
+    endpoint.publish(text);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        DurationHistogram, EntityCounts, LightyearMetrics, MetricsEndpoint, ServerMetrics,
+        render_metrics,
+    };
+    use std::io::{Read, Write};
+    use std::net::{Ipv4Addr, SocketAddr, TcpStream};
+
+    #[test]
+    fn histograms_are_cumulative() {
+        let mut histogram = DurationHistogram::default();
+        histogram.observe(0.0008);
+        histogram.observe(0.02);
+        histogram.observe(1.0);
+
+        let mut out = String::new();
+        histogram.render(&mut out, "frame_seconds", "Frame time.");
+        assert!(out.contains("frame_seconds_bucket{le=\"0.001\"} 1\n"));
+        assert!(out.contains("frame_seconds_bucket{le=\"0.025\"} 2\n"));
+        assert!(out.contains("frame_seconds_bucket{le=\"+Inf\"} 3\n"));
+        assert!(out.contains("frame_seconds_count 3\n"));
+    }
+
+    #[test]
+    fn lightyear_counters_keep_their_channel_labels() {
+        let recorder = LightyearMetrics::default();
+        metrics::with_local_recorder(&recorder, || {
+            metrics::counter!("transport/send/bytes", "channel" => "VoiceChannel").increment(3);
+            metrics::counter!("transport/send/bytes", "channel" => "VoiceChannel").increment(4);
+            metrics::gauge!("sync/rtt_ms").set(42.5);
+        });
+
+        let mut out = String::new();
+        recorder.render(&mut out);
+        assert!(out.contains("# TYPE lightyear_transport_send_bytes counter\n"));
+        assert!(out.contains("lightyear_transport_send_bytes{channel=\"VoiceChannel\"} 7\n"));
+        assert!(out.contains("lightyear_sync_rtt_ms 42.5\n"));
+    }
+
+    #[test]
+    fn scrapes_get_the_published_snapshot() {
+        let endpoint = MetricsEndpoint::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, 0))).unwrap();
+        let metrics = ServerMetrics {
+            bytes_sent: 1200,
+            ..Default::default()
+        };
+        let counts = EntityCounts {
+            connected_players: 2,
+            ..Default::default()
+        };
+        endpoint.publish(render_metrics(&metrics, &counts));
+
+        let mut scrape = TcpStream::connect(endpoint.local_addr()).unwrap();
+        scrape
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        scrape.read_to_string(&mut response).unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("yolo_server_connected_players 2\n"));
+        assert!(response.contains("yolo_server_network_bytes_total{direction=\"sent\"} 1200\n"));
+    }
+}
@@ -1,5 +1,5 @@
 use bevy::prelude::{
-    Add, App, Commands, Entity, Name, On, Plugin, PreStartup, Query, Res, Single, State, Update,
+    Add, App, Commands, Entity, Name, On, Plugin, PreStartup, Query, Res, ResMut, State, Update,
     With, Without, info,
 };
 use std::collections::HashSet;
@@ -9,7 +9,7 @@ use lightyear::connection::client_of::ClientOf;
 use lightyear::prelude::{
     Client, Connected, ControlledBy, DeltaManager, Disconnected, Link, LinkOf, Linked, LocalAddr,
     LocalId, NetworkTarget, PeerId, RemoteId, Replicate, ReplicationReceiver, ReplicationSender,
-    SendUpdatesMode, Server, ServerMultiMessageSender,
+    SendUpdatesMode, Server,
     server::{NetcodeConfig, NetcodeServer, ServerUdpIo, Start, Started},
 };
 use shared::debug::debug_println;
@@ -17,6 +17,27 @@ use shared::protocol::{LobbyControlChannel, LobbyState, PlayerId, StartLoadingGa
 use shared::{SERVER_BIND_ADDR, SHARED_SETTINGS};
 
 use crate::ServerGameState;
+use crate::scheduled_messages::{ScheduledMessages, SendWhen, ensure_scheduled_messages};
+use crate::session::AwaitingReconnect;
+
+pub mod rate_limit;
+pub mod rcon;
+
+use self::rate_limit::ServerRateLimitPlugin;
+use self::rcon::ServerRconPlugin;
+
+/// Whether a password someone sent matches `expected`, comparing in constant time so the
+/// time taken does not tell how much of a guess was right.
+pub fn secrets_match(given: &str, expected: &str) -> bool {
+    let (given, expected) = (given.as_bytes(), expected.as_bytes());
+    let mut difference = given.len() ^ expected.len();
+    for (index, byte) in expected.iter().enumerate() {
+        let guess = given.get(index).copied().unwrap_or(0);
+        difference |= usize::from(guess ^ byte);
+    }
+    difference == 0
+}
+
 pub struct ServerNetworkPlugin;
 
 impl Plugin for ServerNetworkPlugin {
@@ -45,6 +66,9 @@ impl Plugin for ServerNetworkPlugin {
             }
         }
 
+        ensure_scheduled_messages(app);
+        app.add_plugins(ServerRateLimitPlugin);
+        app.add_plugins(ServerRconPlugin);
         app.add_observer(handle_disconnected);
         app.add_observer(handle_connected);
         app.add_systems(Update, ensure_local_host_clientof_links);
@@ -134,7 +158,7 @@ fn startup_server(mut commands: Commands) {
         num_disconnect_packets: 10,
         keep_alive_send_rate: 1.0 / 10.0,
         client_timeout_secs: 10,
-        protocol_id: SHARED_SETTINGS.protocol_id,
+        protocol_id: shared::manifest::protocol_hash(),
         private_key: SHARED_SETTINGS.private_key,
     };
     let server_entity = commands
@@ -151,15 +175,13 @@ fn startup_server(mut commands: Commands) {
     });
 }
 
-#[allow(clippy::too_many_arguments)]
 fn handle_connected(
     trigger: On<Add, Connected>,
     query: Query<&RemoteId, With<ClientOf>>,
     mut lobby_query: Query<(Entity, &mut LobbyState)>,
     mut commands: Commands,
     server_state: Res<State<ServerGameState>>,
-    mut sender: ServerMultiMessageSender,
-    server: Single<&Server>,
+    mut scheduled: ResMut<ScheduledMessages>,
 ) {
     let Ok(client_id) = query.get(trigger.entity) else {
         return;
@@ -190,33 +212,28 @@ fn handle_connected(
                 debug_println(format_args!("DEBUG: Client_{} became host", client_id_bits));
                 lobby_state.host_id = client_id_bits;
             }
-
-            // If the game is already in progress, send the StartLoadingGameEvent to the newly connected client
-            if *server_state.get() == ServerGameState::Playing {
-                debug_println(format_args!(
-                    "DEBUG: Game already started, sending StartLoadingGameEvent to late-joining Client_{}",
-                    client_id_bits
-                ));
-
-                sender
-                    .send::<StartLoadingGameEvent, LobbyControlChannel>(
-                        &StartLoadingGameEvent { start: true },
-                        server.into_inner(),
-                        &NetworkTarget::Single(client_id.0),
-                    )
-                    .unwrap_or_else(|e| {
-                        bevy::log::error!(
-                            "Failed to send StartLoadingGameEvent to late-joining client: {:?}",
-                            e
-                        );
-                    });
-            }
         } else {
+            // A player whose character is waiting for it to reconnect stays in the lobby.
             debug_println(format_args!(
                 "DEBUG: Client_{} already in lobby",
                 client_id_bits
             ));
         }
+
+        // If the game is already in progress, send the StartLoadingGameEvent to the newly
+        // connected client, whether it joins late or comes back after a drop
+        if *server_state.get() == ServerGameState::Playing {
+            debug_println(format_args!(
+                "DEBUG: Game already started, sending StartLoadingGameEvent to late-joining Client_{}",
+                client_id_bits
+            ));
+
+            scheduled.schedule::<_, LobbyControlChannel>(
+                StartLoadingGameEvent { start: true },
+                SendWhen::StateEntered(ServerGameState::Playing),
+                NetworkTarget::Single(client_id.0),
+            );
+        }
     } else {
         // No lobby exists, create it with this first client as host
         debug_println(format_args!(
@@ -227,6 +244,8 @@ fn handle_connected(
             LobbyState {
                 players: vec![client_id_bits],
                 host_id: client_id_bits,
+                teams: Default::default(),
+                profiles: Default::default(),
             },
             Replicate::to_clients(NetworkTarget::All),
             Name::from("LobbyState"),
@@ -239,6 +258,7 @@ fn handle_disconnected(
     query: Query<&RemoteId, With<ClientOf>>,
     mut lobby_query: Query<&mut LobbyState>,
     player_query: Query<(Entity, &ControlledBy), With<PlayerId>>,
+    server_state: Res<State<ServerGameState>>,
     mut commands: Commands,
 ) {
     let Ok(client_id) = query.get(trigger.entity) else {
@@ -248,11 +268,24 @@ fn handle_disconnected(
     let client_id_bits = client_id.0.to_bits();
     info!("Client {} disconnected", client_id_bits);
 
+    // Mid-match the character waits for its client to reconnect; it keeps its lobby slot
+    // until the session plugin gives up on it.
+    let await_reconnect = *server_state.get() == ServerGameState::Playing;
     for (player_entity, controlled_by) in player_query.iter() {
-        if controlled_by.owner == trigger.entity {
+        if controlled_by.owner != trigger.entity {
+            continue;
+        }
+        if await_reconnect {
+            commands
+                .entity(player_entity)
+                .insert(AwaitingReconnect::default());
+        } else {
             commands.entity(player_entity).despawn();
         }
     }
+    if await_reconnect {
+        return;
+    }
 
     if let Some(mut lobby_state) = lobby_query.iter_mut().next()
         && let Some(pos) = lobby_state
@@ -275,7 +308,8 @@ fn handle_disconnected(
 fn reconcile_disconnected_clients(
     connected_clients: Query<(Entity, &RemoteId), (With<ClientOf>, With<Connected>)>,
     mut lobby_query: Query<&mut LobbyState>,
-    player_query: Query<(Entity, &ControlledBy, &PlayerId), With<PlayerId>>,
+    player_query: Query<(Entity, &ControlledBy, &PlayerId), Without<AwaitingReconnect>>,
+    awaiting_query: Query<&PlayerId, With<AwaitingReconnect>>,
     mut commands: Commands,
 ) {
     let Some(mut lobby_state) = lobby_query.iter_mut().next() else {
@@ -287,13 +321,20 @@ fn reconcile_disconnected_clients(
         .map(|(_, remote_id)| remote_id.0.to_bits())
         .collect();
 
+    let awaiting_ids: HashSet<u64> = awaiting_query.iter().map(|id| id.0.to_bits()).collect();
+
     let previous_len = lobby_state.players.len();
     lobby_state
         .players
-        .retain(|player_id| connected_ids.contains(player_id));
+        .retain(|player_id| connected_ids.contains(player_id) || awaiting_ids.contains(player_id));
 
     if lobby_state.host_id != 0 && !connected_ids.contains(&lobby_state.host_id) {
-        lobby_state.host_id = lobby_state.players.first().copied().unwrap_or(0);
+        lobby_state.host_id = lobby_state
+            .players
+            .iter()
+            .copied()
+            .find(|player_id| connected_ids.contains(player_id))
+            .unwrap_or(0);
     }
 
     if lobby_state.players.len() != previous_len {
@@ -318,6 +359,7 @@ fn reconcile_disconnected_clients(
 #[cfg(test)]
 mod tests {
     use super::reconcile_disconnected_clients;
+    use crate::session::AwaitingReconnect;
     use bevy::prelude::{App, MinimalPlugins, Update};
     use lightyear::connection::client_of::ClientOf;
     use lightyear::prelude::{Connected, ControlledBy, PeerId, RemoteId};
@@ -342,6 +384,8 @@ mod tests {
         app.world_mut().spawn(LobbyState {
             players: vec![1, 2],
             host_id: 2,
+            teams: Default::default(),
+            profiles: Default::default(),
         });
 
         let player_1 = app
@@ -382,4 +426,52 @@ mod tests {
         assert!(app.world().entities().contains(player_1));
         assert!(!app.world().entities().contains(player_2));
     }
+
+    #[test]
+    fn reconcile_keeps_players_waiting_to_reconnect() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_systems(Update, reconcile_disconnected_clients);
+
+        app.world_mut()
+            .spawn((ClientOf, Connected, RemoteId(PeerId::Netcode(1))));
+        let dropped_client = app
+            .world_mut()
+            .spawn((ClientOf, RemoteId(PeerId::Netcode(2))))
+            .id();
+
+        app.world_mut().spawn(LobbyState {
+            players: vec![2, 1],
+            host_id: 2,
+            teams: Default::default(),
+            profiles: Default::default(),
+        });
+
+        let frozen_player = app
+            .world_mut()
+            .spawn((
+                PlayerId(PeerId::Netcode(2)),
+                ControlledBy {
+                    owner: dropped_client,
+                    lifetime: Default::default(),
+                },
+                AwaitingReconnect::default(),
+            ))
+            .id();
+
+        app.update();
+
+        let lobby = {
+            let world = app.world_mut();
+            world
+                .query::<&LobbyState>()
+                .single(world)
+                .expect("lobby should exist")
+                .clone()
+        };
+
+        assert_eq!(lobby.players, vec![2, 1]);
+        assert_eq!(lobby.host_id, 1);
+        assert!(app.world().entities().contains(frozen_player));
+    }
 }
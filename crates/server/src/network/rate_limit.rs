@@ -0,0 +1,337 @@
+use bevy::prelude::{
+    App, Commands, Entity, IntoScheduleConfigs, Plugin, PreUpdate, Query, Res, ResMut, Resource,
+    Time, With, info, warn,
+};
+use std::collections::HashMap;
+
+use lightyear::connection::client_of::ClientOf;
+use lightyear::prelude::{
+    Connected, Disconnect, MessageReceiver, MessageSystems, PeerId, RemoteId,
+};
+use shared::protocol::{
+    ClientWorldCreatedEvent, EntitySnapshotSubscribe, EquipAttachmentsRequest, HostStartGameEvent,
+    PlayerProfileRequest, ResumeSessionRequest, SpectateRequest, SubmitLoadoutRequest,
+    TeamSelectRequest, VoiceFrame,
+};
+use shared::resync::ResyncRequest;
+
+/// Token bucket for one message type: `per_second` messages on average, with bursts of up
+/// to `burst`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimit {
+    pub per_second: f32,
+    pub burst: f32,
+}
+
+impl RateLimit {
+    pub const fn new(per_second: f32, burst: f32) -> Self {
+        Self { per_second, burst }
+    }
+}
+
+/// Limits for every message clients can send, keyed by message type name.
+#[derive(Resource, Clone, Debug)]
+pub struct RateLimitSettings {
+    pub limits: HashMap<&'static str, RateLimit>,
+    /// Throttled batches a connection may cause within `violation_window_secs` before it is
+    /// disconnected.
+    pub violations_before_disconnect: u32,
+    pub violation_window_secs: f32,
+    /// Closed connections whose counters are kept for the console; the ones that sent
+    /// their last message longest ago are forgotten first.
+    pub closed_connections_kept: usize,
+}
+
+impl Default for RateLimitSettings {
+    fn default() -> Self {
+        let limits = [
+            limit::<HostStartGameEvent>(1.0, 2.0),
+            limit::<ClientWorldCreatedEvent>(1.0, 3.0),
+            limit::<TeamSelectRequest>(2.0, 4.0),
+            limit::<PlayerProfileRequest>(1.0, 3.0),
+            limit::<SpectateRequest>(2.0, 4.0),
+            limit::<ResumeSessionRequest>(1.0, 2.0),
+            limit::<SubmitLoadoutRequest>(2.0, 5.0),
+            limit::<EquipAttachmentsRequest>(4.0, 8.0),
+            limit::<EntitySnapshotSubscribe>(2.0, 4.0),
+            limit::<ResyncRequest>(1.0, 3.0),
+            // Voice arrives in 20 ms frames.
+            limit::<VoiceFrame>(60.0, 30.0),
+        ];
+        Self {
+            limits: limits.into_iter().collect(),
+            violations_before_disconnect: 5,
+            violation_window_secs: 10.0,
+            closed_connections_kept: 64,
+        }
+    }
+}
+
+/// Counters for one connection, kept for a while after it disconnects (see
+/// `RateLimitSettings::closed_connections_kept`).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConnectionRateMetrics {
+    pub accepted: u64,
+    pub dropped: u64,
+    pub violations: u32,
+    pub disconnected_for_abuse: bool,
+    /// When the connection last sent a message.
+    pub last_seen_secs: f32,
+}
+
+/// Rate limiting counters, for the admin console.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct RateLimitMetrics {
+    pub connections: HashMap<PeerId, ConnectionRateMetrics>,
+    /// Dropped messages per message type name.
+    pub dropped_by_message: HashMap<&'static str, u64>,
+    pub abuse_disconnects: u32,
+}
+
+impl RateLimitMetrics {
+    /// Forget the counters of closed connections past the `keep` seen most recently.
+    fn forget_closed(&mut self, is_open: impl Fn(&PeerId) -> bool, keep: usize) {
+        let mut closed: Vec<(PeerId, f32)> = self
+            .connections
+            .iter()
+            .filter(|(peer, _)| !is_open(peer))
+            .map(|(peer, metrics)| (*peer, metrics.last_seen_secs))
+            .collect();
+        if closed.len() <= keep {
+            return;
+        }
+        closed.sort_by(|a, b| b.1.total_cmp(&a.1));
+        for (peer, _) in &closed[keep..] {
+            self.connections.remove(peer);
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Bucket {
+    tokens: f32,
+    refilled_at: f32,
+}
+
+#[derive(Resource, Default, Debug)]
+struct RateLimiter {
+    buckets: HashMap<(Entity, &'static str), Bucket>,
+    /// Times of recent violations per connection.
+    violations: HashMap<Entity, Vec<f32>>,
+}
+
+impl RateLimiter {
+    /// Take `count` tokens from the bucket of `connection` for `message`. Returns false,
+    /// taking nothing, when the bucket does not hold enough.
+    fn try_take(
+        &mut self,
+        connection: Entity,
+        message: &'static str,
+        limit: RateLimit,
+        count: f32,
+        now: f32,
+    ) -> bool {
+        let bucket = self.buckets.entry((connection, message)).or_insert(Bucket {
+            tokens: limit.burst,
+            refilled_at: now,
+        });
+        bucket.tokens =
+            (bucket.tokens + (now - bucket.refilled_at) * limit.per_second).min(limit.burst);
+        bucket.refilled_at = now;
+        if bucket.tokens < count {
+            return false;
+        }
+        bucket.tokens -= count;
+        true
+    }
+
+    /// Record a violation and return how many `connection` caused within `window` seconds.
+    fn record_violation(&mut self, connection: Entity, now: f32, window: f32) -> u32 {
+        let recent = self.violations.entry(connection).or_default();
+        recent.retain(|&at| now - at <= window);
+        recent.push(now);
+        recent.len() as u32
+    }
+
+    fn forget(&mut self, connection: Entity) {
+        self.buckets.retain(|(entity, _), _| *entity != connection);
+        self.violations.remove(&connection);
+    }
+}
+
+fn limit<M>(per_second: f32, burst: f32) -> (&'static str, RateLimit) {
+    (message_name::<M>(), RateLimit::new(per_second, burst))
+}
+
+fn message_name<M>() -> &'static str {
+    let name = std::any::type_name::<M>();
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+/// Per-connection flood protection for client messages. Runs right after messages are
+/// received, before any gameplay system reads them: a batch over the connection's budget is
+/// dropped whole, and connections that keep flooding are disconnected.
+pub struct ServerRateLimitPlugin;
+
+impl Plugin for ServerRateLimitPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RateLimitSettings>();
+        app.init_resource::<RateLimitMetrics>();
+        app.init_resource::<RateLimiter>();
+        app.add_systems(
+            PreUpdate,
+            (
+                limit_message_rate::<HostStartGameEvent>,
+                limit_message_rate::<ClientWorldCreatedEvent>,
+                limit_message_rate::<TeamSelectRequest>,
+                limit_message_rate::<PlayerProfileRequest>,
+                limit_message_rate::<SpectateRequest>,
+                limit_message_rate::<ResumeSessionRequest>,
+                limit_message_rate::<SubmitLoadoutRequest>,
+                limit_message_rate::<EquipAttachmentsRequest>,
+                limit_message_rate::<EntitySnapshotSubscribe>,
+                limit_message_rate::<ResyncRequest>,
+                limit_message_rate::<VoiceFrame>,
+                forget_closed_connections,
+            )
+                .chain()
+                .after(MessageSystems::Receive),
+        );
+    }
+}
+
+fn limit_message_rate<M: Send + Sync + 'static>(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<RateLimitSettings>,
+    mut limiter: ResMut<RateLimiter>,
+    mut metrics: ResMut<RateLimitMetrics>,
+    mut receivers: Query<
+        (Entity, &RemoteId, &mut MessageReceiver<M>),
+        (With<ClientOf>, With<Connected>),
+    >,
+) {
+    let message = message_name::<M>();
+    let Some(&limit) = settings.limits.get(message) else {
+        return;
+    };
+    let now = time.elapsed_secs();
+    let limiter = &mut *limiter;
+    let metrics = &mut *metrics;
+
+    for (connection, remote_id, mut receiver) in receivers.iter_mut() {
+        let count = receiver.num_messages();
+        if count == 0 {
+            continue;
+        }
+
+        let connection_metrics = metrics.connections.entry(remote_id.0).or_default();
+        connection_metrics.last_seen_secs = now;
+        if limiter.try_take(connection, message, limit, count as f32, now) {
+            connection_metrics.accepted += count as u64;
+            continue;
+        }
+
+        receiver.receive().for_each(drop);
+        connection_metrics.dropped += count as u64;
+        connection_metrics.violations += 1;
+        *metrics.dropped_by_message.entry(message).or_default() += count as u64;
+
+        let recent = limiter.record_violation(connection, now, settings.violation_window_secs);
+        if recent < settings.violations_before_disconnect {
+            info!(
+                "Throttled {} {} messages from client {:?}",
+                count, message, remote_id.0
+            );
+            continue;
+        }
+
+        warn!(
+            "Disconnecting client {:?}: flooded {} messages {} times in {}s",
+            remote_id.0, message, recent, settings.violation_window_secs
+        );
+        connection_metrics.disconnected_for_abuse = true;
+        metrics.abuse_disconnects += 1;
+        limiter.forget(connection);
+        commands.trigger(Disconnect { entity: connection });
+    }
+}
+
+fn forget_closed_connections(
+    settings: Res<RateLimitSettings>,
+    mut limiter: ResMut<RateLimiter>,
+    mut metrics: ResMut<RateLimitMetrics>,
+    connections: Query<(Entity, &RemoteId), (With<ClientOf>, With<Connected>)>,
+) {
+    limiter
+        .buckets
+        .retain(|(connection, _), _| connections.contains(*connection));
+    limiter
+        .violations
+        .retain(|connection, _| connections.contains(*connection));
+    metrics.forget_closed(
+        |peer| {
+            connections
+                .iter()
+                .any(|(_, remote_id)| remote_id.0 == *peer)
+        },
+        settings.closed_connections_kept,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConnectionRateMetrics, RateLimit, RateLimitMetrics, RateLimiter};
+    use bevy::prelude::Entity;
+    use lightyear::prelude::PeerId;
+
+    #[test]
+    fn bursts_are_allowed_then_refilled_at_the_configured_rate() {
+        let mut limiter = RateLimiter::default();
+        let limit = RateLimit::new(2.0, 4.0);
+        let client = Entity::PLACEHOLDER;
+
+        assert!(limiter.try_take(client, "Spam", limit, 4.0, 0.0));
+        assert!(!limiter.try_take(client, "Spam", limit, 1.0, 0.1));
+        // Half a second later one message worth of tokens is back.
+        assert!(limiter.try_take(client, "Spam", limit, 1.0, 0.6));
+        assert!(!limiter.try_take(client, "Spam", limit, 1.0, 0.6));
+        // Idle time never stores more than a burst.
+        assert!(!limiter.try_take(client, "Spam", limit, 5.0, 100.0));
+    }
+
+    #[test]
+    fn only_recent_violations_count_toward_a_disconnect() {
+        let mut limiter = RateLimiter::default();
+        let client = Entity::PLACEHOLDER;
+
+        assert_eq!(limiter.record_violation(client, 0.0, 10.0), 1);
+        assert_eq!(limiter.record_violation(client, 5.0, 10.0), 2);
+        assert_eq!(limiter.record_violation(client, 14.0, 10.0), 2);
+        limiter.forget(client);
+        assert_eq!(limiter.record_violation(client, 15.0, 10.0), 1);
+    }
+
+    #[test]
+    fn only_the_most_recent_closed_connections_are_remembered() {
+        let mut metrics = RateLimitMetrics::default();
+        for id in 0..5 {
+            metrics.connections.insert(
+                PeerId::Netcode(id),
+                ConnectionRateMetrics {
+                    last_seen_secs: id as f32,
+                    ..Default::default()
+                },
+            );
+        }
+
+        // Connection 0 is still open, so it is kept whatever its age.
+        metrics.forget_closed(|peer| *peer == PeerId::Netcode(0), 2);
+        let mut kept: Vec<PeerId> = metrics.connections.keys().copied().collect();
+        kept.sort_by_key(|peer| peer.to_bits());
+        assert_eq!(
+            kept,
+            [PeerId::Netcode(0), PeerId::Netcode(3), PeerId::Netcode(4)]
+        );
+    }
+}
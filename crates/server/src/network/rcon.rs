@@ -0,0 +1,421 @@
+use bevy::prelude::{App, Plugin, Res, Resource, Startup, error, info, warn};
+use std::collections::HashMap;
+use std::io::{self, BufReader, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::console::{ConsoleQueue, ConsoleSubmitter};
+use crate::network::secrets_match;
+
+/// Source RCON packet types. `SERVERDATA_EXECCOMMAND` and `SERVERDATA_AUTH_RESPONSE` share
+/// their value; which one is meant depends on the direction.
+pub const SERVERDATA_AUTH: i32 = 3;
+pub const SERVERDATA_AUTH_RESPONSE: i32 = 2;
+pub const SERVERDATA_EXECCOMMAND: i32 = 2;
+pub const SERVERDATA_RESPONSE_VALUE: i32 = 0;
+
+/// Largest packet the protocol allows, size field excluded.
+const MAX_PACKET_SIZE: i32 = 4096;
+/// id, type and the two terminating nulls.
+const PACKET_OVERHEAD: i32 = 10;
+/// Longest response body sent in one packet; longer output is split.
+const MAX_RESPONSE_BODY: usize = (MAX_PACKET_SIZE - PACKET_OVERHEAD) as usize;
+/// Idle RCON connections are dropped after this long.
+const RCON_IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+/// Connections have this long to authenticate.
+const RCON_AUTH_TIMEOUT: Duration = Duration::from_secs(10);
+/// Connections served at once; more are closed right away.
+const MAX_RCON_CONNECTIONS: usize = 8;
+/// Lockout after the first failed login from an address, doubled on each further one.
+const LOGIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_LOGIN_BACKOFF: Duration = Duration::from_secs(300);
+/// Addresses whose lockout ended this long ago start over.
+const LOGIN_FAILURE_MEMORY: Duration = Duration::from_secs(3600);
+
+/// Source-style RCON for hosting panels and remote tooling. Off unless `addr` is set, and
+/// never started without a password. The protocol is plaintext, so it listens on loopback
+/// unless the operator opts in to every address.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct RconSettings {
+    pub addr: Option<SocketAddr>,
+    pub password: String,
+}
+
+impl RconSettings {
+    /// Accept RCON connections from this machine on `port`.
+    pub fn on_port(port: u16, password: impl Into<String>) -> Self {
+        Self {
+            addr: Some(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port)),
+            password: password.into(),
+        }
+    }
+
+    /// Accept connections from any address instead, for panels on other machines.
+    pub fn public(mut self) -> Self {
+        if let Some(addr) = &mut self.addr {
+            addr.set_ip(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+        }
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct LoginFailures {
+    count: u32,
+    locked_until: Instant,
+}
+
+/// Failed logins per address. Each failure locks the address out for twice as long as the
+/// last, so guessing the password over fresh connections gets slow fast.
+#[derive(Debug, Default)]
+pub struct LoginThrottle {
+    failures: HashMap<IpAddr, LoginFailures>,
+}
+
+impl LoginThrottle {
+    /// Whether `ip` may try to log in at `now`.
+    pub fn allows(&self, ip: IpAddr, now: Instant) -> bool {
+        self.failures
+            .get(&ip)
+            .is_none_or(|failures| now >= failures.locked_until)
+    }
+
+    pub fn failed(&mut self, ip: IpAddr, now: Instant) {
+        self.failures.retain(|_, failures| {
+            now.saturating_duration_since(failures.locked_until) < LOGIN_FAILURE_MEMORY
+        });
+        let count = self.failures.get(&ip).map_or(0, |failures| failures.count) + 1;
+        let backoff = LOGIN_BACKOFF
+            .saturating_mul(1 << (count - 1).min(16))
+            .min(MAX_LOGIN_BACKOFF);
+        self.failures.insert(
+            ip,
+            LoginFailures {
+                count,
+                locked_until: now + backoff,
+            },
+        );
+    }
+
+    pub fn succeeded(&mut self, ip: IpAddr) {
+        self.failures.remove(&ip);
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RconPacket {
+    pub id: i32,
+    pub kind: i32,
+    pub body: String,
+}
+
+impl RconPacket {
+    pub fn new(id: i32, kind: i32, body: impl Into<String>) -> Self {
+        Self {
+            id,
+            kind,
+            body: body.into(),
+        }
+    }
+
+    /// Little-endian size, id and type, then the body and two nulls.
+    pub fn encode(&self) -> Vec<u8> {
+        let size = self.body.len() as i32 + PACKET_OVERHEAD;
+        let mut bytes = Vec::with_capacity(size as usize + 4);
+        bytes.extend_from_slice(&size.to_le_bytes());
+        bytes.extend_from_slice(&self.id.to_le_bytes());
+        bytes.extend_from_slice(&self.kind.to_le_bytes());
+        bytes.extend_from_slice(self.body.as_bytes());
+        bytes.extend_from_slice(&[0, 0]);
+        bytes
+    }
+
+    pub fn read_from(reader: &mut impl Read) -> io::Result<Self> {
+        let mut field = [0; 4];
+        reader.read_exact(&mut field)?;
+        let size = i32::from_le_bytes(field);
+        if !(PACKET_OVERHEAD..=MAX_PACKET_SIZE).contains(&size) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("bad RCON packet size {}", size),
+            ));
+        }
+
+        let mut packet = vec![0; size as usize];
+        reader.read_exact(&mut packet)?;
+        let id = i32::from_le_bytes([packet[0], packet[1], packet[2], packet[3]]);
+        let kind = i32::from_le_bytes([packet[4], packet[5], packet[6], packet[7]]);
+        let body = &packet[8..];
+        let body = &body[..body.iter().position(|&b| b == 0).unwrap_or(body.len())];
+        Ok(Self::new(id, kind, String::from_utf8_lossy(body)))
+    }
+}
+
+/// One RCON connection: commands are refused until the client authenticates.
+#[derive(Debug)]
+pub struct RconSession {
+    password: String,
+    authenticated: bool,
+}
+
+impl RconSession {
+    pub fn new(password: impl Into<String>) -> Self {
+        Self {
+            password: password.into(),
+            authenticated: false,
+        }
+    }
+
+    /// Packets to send back for `packet`, running commands with `run`. `None` means the
+    /// connection should be closed.
+    pub fn handle(
+        &mut self,
+        packet: RconPacket,
+        run: impl FnOnce(&str) -> String,
+    ) -> Option<Vec<RconPacket>> {
+        match packet.kind {
+            SERVERDATA_AUTH => {
+                self.authenticated = secrets_match(&packet.body, &self.password);
+                // Source servers send an empty value before the auth answer; clients expect it.
+                let empty = RconPacket::new(packet.id, SERVERDATA_RESPONSE_VALUE, "");
+                let id = if self.authenticated { packet.id } else { -1 };
+                let answer = RconPacket::new(id, SERVERDATA_AUTH_RESPONSE, "");
+                Some(vec![empty, answer])
+            }
+            SERVERDATA_EXECCOMMAND if self.authenticated => {
+                let output = run(&packet.body);
+                Some(
+                    split_response(&output)
+                        .into_iter()
+                        .map(|chunk| RconPacket::new(packet.id, SERVERDATA_RESPONSE_VALUE, chunk))
+                        .collect(),
+                )
+            }
+            // Clients send an empty value after a command to find the end of a split
+            // response: mirror it.
+            SERVERDATA_RESPONSE_VALUE if self.authenticated => Some(vec![RconPacket::new(
+                packet.id,
+                SERVERDATA_RESPONSE_VALUE,
+                "",
+            )]),
+            _ => None,
+        }
+    }
+}
+
+/// Split `output` into bodies that fit a packet, on character boundaries.
+fn split_response(output: &str) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut rest = output;
+    while rest.len() > MAX_RESPONSE_BODY {
+        let mut end = MAX_RESPONSE_BODY;
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let (chunk, tail) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = tail;
+    }
+    chunks.push(rest);
+    chunks
+}
+
+/// Remote administration over the Source RCON protocol. Commands are the admin console's
+/// and run through its queue on the game thread.
+pub struct ServerRconPlugin;
+
+impl Plugin for ServerRconPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RconSettings>();
+        app.init_resource::<ConsoleQueue>();
+        app.add_systems(Startup, start_rcon_listener);
+    }
+}
+
+fn start_rcon_listener(settings: Res<RconSettings>, queue: Res<ConsoleQueue>) {
+    let Some(addr) = settings.addr else {
+        return;
+    };
+    if settings.password.is_empty() {
+        error!("RCON on {} needs a password, not starting it", addr);
+        return;
+    }
+
+    match TcpListener::bind(addr) {
+        Ok(listener) => {
+            info!("🔑 RCON listening on {}", addr);
+            let submitter = queue.submitter();
+            let password = settings.password.clone();
+            let throttle = Arc::new(Mutex::new(LoginThrottle::default()));
+            let active = Arc::new(AtomicUsize::new(0));
+            std::thread::spawn(move || {
+                for connection in listener.incoming().flatten() {
+                    let Ok(peer) = connection.peer_addr() else {
+                        continue;
+                    };
+                    let allowed = throttle
+                        .lock()
+                        .is_ok_and(|throttle| throttle.allows(peer.ip(), Instant::now()));
+                    if !allowed {
+                        continue;
+                    }
+                    if active.fetch_add(1, Ordering::SeqCst) >= MAX_RCON_CONNECTIONS {
+                        active.fetch_sub(1, Ordering::SeqCst);
+                        warn!("Too many RCON connections, closing the one from {}", peer);
+                        continue;
+                    }
+
+                    let submitter = submitter.clone();
+                    let session = RconSession::new(password.clone());
+                    let throttle = throttle.clone();
+                    let active = active.clone();
+                    std::thread::spawn(move || {
+                        serve_rcon_connection(connection, peer, session, submitter, &throttle);
+                        active.fetch_sub(1, Ordering::SeqCst);
+                    });
+                }
+            });
+        }
+        Err(e) => error!("Failed to start RCON on {}: {}", addr, e),
+    }
+}
+
+fn serve_rcon_connection(
+    connection: TcpStream,
+    peer: SocketAddr,
+    mut session: RconSession,
+    submitter: ConsoleSubmitter,
+    throttle: &Mutex<LoginThrottle>,
+) {
+    let _ = connection.set_read_timeout(Some(RCON_AUTH_TIMEOUT));
+    let Ok(mut writer) = connection.try_clone() else {
+        return;
+    };
+    let mut reader = BufReader::new(&connection);
+
+    while let Ok(packet) = RconPacket::read_from(&mut reader) {
+        let is_auth = packet.kind == SERVERDATA_AUTH;
+        let Some(replies) = session.handle(packet, |command| submitter.run(command)) else {
+            warn!("Closing unauthenticated RCON connection from {}", peer);
+            return;
+        };
+        for reply in &replies {
+            if writer.write_all(&reply.encode()).is_err() {
+                return;
+            }
+        }
+        if is_auth {
+            let Ok(mut throttle) = throttle.lock() else {
+                return;
+            };
+            if !session.authenticated {
+                throttle.failed(peer.ip(), Instant::now());
+                warn!("Rejected RCON password from {}", peer);
+                return;
+            }
+            throttle.succeeded(peer.ip());
+            let _ = connection.set_read_timeout(Some(RCON_IDLE_TIMEOUT));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        LOGIN_BACKOFF, LoginThrottle, MAX_LOGIN_BACKOFF, MAX_RESPONSE_BODY, RconPacket,
+        RconSession, RconSettings, SERVERDATA_AUTH, SERVERDATA_AUTH_RESPONSE,
+        SERVERDATA_EXECCOMMAND, SERVERDATA_RESPONSE_VALUE,
+    };
+    use std::net::{IpAddr, Ipv4Addr};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn rcon_listens_on_loopback_unless_made_public() {
+        let settings = RconSettings::on_port(27015, "hunter2");
+        assert!(settings.addr.unwrap().ip().is_loopback());
+        assert!(settings.public().addr.unwrap().ip().is_unspecified());
+    }
+
+    #[test]
+    fn failed_logins_lock_the_address_out_for_longer_each_time() {
+        let mut throttle = LoginThrottle::default();
+        let guesser = IpAddr::V4(Ipv4Addr::new(203, 0, 113, 7));
+        let other = IpAddr::V4(Ipv4Addr::new(203, 0, 113, 8));
+        let start = Instant::now();
+
+        throttle.failed(guesser, start);
+        assert!(!throttle.allows(guesser, start));
+        assert!(throttle.allows(other, start));
+        assert!(throttle.allows(guesser, start + LOGIN_BACKOFF));
+
+        throttle.failed(guesser, start + LOGIN_BACKOFF);
+        assert!(!throttle.allows(guesser, start + LOGIN_BACKOFF * 2));
+        assert!(throttle.allows(guesser, start + LOGIN_BACKOFF * 3));
+
+        let mut later = start;
+        for _ in 0..20 {
+            throttle.failed(guesser, later);
+            later += Duration::from_secs(1);
+        }
+        assert!(!throttle.allows(guesser, later + MAX_LOGIN_BACKOFF - Duration::from_secs(2)));
+        assert!(throttle.allows(guesser, later + MAX_LOGIN_BACKOFF));
+
+        throttle.succeeded(guesser);
+        assert!(throttle.allows(guesser, later));
+    }
+
+    #[test]
+    fn packets_round_trip_through_the_wire_format() {
+        let packet = RconPacket::new(7, SERVERDATA_EXECCOMMAND, "status");
+        let bytes = packet.encode();
+        assert_eq!(&bytes[..4], &16i32.to_le_bytes());
+        assert_eq!(
+            RconPacket::read_from(&mut bytes.as_slice()).unwrap(),
+            packet
+        );
+
+        let mut oversized = 5000i32.to_le_bytes().to_vec();
+        oversized.resize(5004, 0);
+        assert!(RconPacket::read_from(&mut oversized.as_slice()).is_err());
+    }
+
+    #[test]
+    fn commands_need_the_password_first() {
+        let mut session = RconSession::new("hunter2");
+        let ran = |command: &str| format!("ran {}", command);
+
+        let command = RconPacket::new(1, SERVERDATA_EXECCOMMAND, "status");
+        assert_eq!(session.handle(command.clone(), ran), None);
+
+        let wrong = session
+            .handle(RconPacket::new(2, SERVERDATA_AUTH, "guess"), ran)
+            .unwrap();
+        assert_eq!(wrong[1], RconPacket::new(-1, SERVERDATA_AUTH_RESPONSE, ""));
+
+        let right = session
+            .handle(RconPacket::new(3, SERVERDATA_AUTH, "hunter2"), ran)
+            .unwrap();
+        assert_eq!(
+            right,
+            vec![
+                RconPacket::new(3, SERVERDATA_RESPONSE_VALUE, ""),
+                RconPacket::new(3, SERVERDATA_AUTH_RESPONSE, ""),
+            ]
+        );
+        assert_eq!(
+            session.handle(command, ran).unwrap(),
+            vec![RconPacket::new(1, SERVERDATA_RESPONSE_VALUE, "ran status")]
+        );
+
+        let long = session
+            .handle(
+                RconPacket::new(4, SERVERDATA_EXECCOMMAND, "x".repeat(5000)),
+                ran,
+            )
+            .unwrap();
+        assert_eq!(long.len(), 2);
+        assert_eq!(long[0].body.len(), MAX_RESPONSE_BODY);
+    }
+}
@@ -0,0 +1,184 @@
+use avian3d::prelude::{LinearVelocity, Position, Rotation};
+use bevy::prelude::{
+    App, Commands, Entity, IntoScheduleConfigs, Local, Plugin, Query, Res, ResMut, Update, Vec3,
+    With, Without, in_state,
+};
+use std::collections::HashMap;
+
+use shared::balance::BalanceConfig;
+use shared::bots::BotProfile;
+use shared::clock::{GameClock, ensure_game_clock};
+use shared::components::attachments::WeaponAttachments;
+use shared::components::health::Health;
+use shared::components::weapons::Gun;
+use shared::hearing::SoundKind;
+use shared::inputs::input::PLAYER_CAPSULE_HEIGHT;
+use shared::inputs::movement::GroundState;
+use shared::perception::{Awareness, BotPerception, in_view};
+use shared::protocol::PlayerId;
+
+use crate::ServerGameState;
+use crate::visibility::LineOfSight;
+
+/// Obstacles counted between a bot and a sound; past this nothing is heard anyway.
+const MAX_HEARD_OCCLUDERS: u32 = 4;
+
+/// Gives every bot its own perception: players it sees in its vision cone and hears, a
+/// fading memory of where they were, and the `Awareness` the rest of its behavior follows.
+pub struct ServerPerceptionPlugin;
+
+impl Plugin for ServerPerceptionPlugin {
+    fn build(&self, app: &mut App) {
+        ensure_game_clock(app);
+        app.add_systems(
+            Update,
+            (
+                give_bots_perception,
+                see_players,
+                hear_players,
+                update_bot_awareness,
+            )
+                .chain()
+                .run_if(in_state(ServerGameState::Playing)),
+        );
+    }
+}
+
+type PerceivingBot<'a> = (Entity, &'a Position, &'a Health, &'a mut BotPerception);
+
+fn give_bots_perception(
+    mut commands: Commands,
+    bots: Query<Entity, (With<BotProfile>, Without<BotPerception>)>,
+) {
+    for bot in bots.iter() {
+        commands
+            .entity(bot)
+            .insert((BotPerception::default(), Awareness::default()));
+    }
+}
+
+/// Bots see living players in range and in their vision cone that nothing blocks their
+/// view of. Bots face where they walk (`movement_system`).
+fn see_players(
+    clock: Res<GameClock>,
+    mut line_of_sight: LineOfSight,
+    mut bots: Query<(PerceivingBot, &Rotation)>,
+    players: Query<(Entity, &Position, &Health), With<PlayerId>>,
+) {
+    let now = clock.elapsed_secs();
+    let eye = Vec3::Y * (PLAYER_CAPSULE_HEIGHT * 0.5);
+    for ((bot, position, health, mut perception), rotation) in bots.iter_mut() {
+        if health.is_dead {
+            continue;
+        }
+        let facing = rotation.0 * Vec3::Z;
+        for (player, player_position, player_health) in players.iter() {
+            if !player_health.is_dead
+                && in_view(position.0 + eye, facing, player_position.0 + eye)
+                && line_of_sight.can_see(bot, position.0 + eye, player, player_position.0 + eye)
+            {
+                perception.see(player, player_position.0, now);
+            }
+        }
+    }
+}
+
+/// Bots hear players' footsteps and gunshots, through walls when loud enough, and learn
+/// where they came from. Muzzle attachments change how far shots carry.
+fn hear_players(
+    clock: Res<GameClock>,
+    balance: Res<BalanceConfig>,
+    line_of_sight: LineOfSight,
+    mut last_gun_states: Local<HashMap<Entity, (u32, f32)>>,
+    mut bots: Query<PerceivingBot>,
+    players: Query<
+        (
+            Entity,
+            &Position,
+            &LinearVelocity,
+            &Health,
+            Option<&GroundState>,
+            Option<&Gun>,
+            Option<&WeaponAttachments>,
+        ),
+        With<PlayerId>,
+    >,
+) {
+    let now = clock.elapsed_secs();
+    let eye = Vec3::Y * (PLAYER_CAPSULE_HEIGHT * 0.5);
+    let hearing = &balance.hearing;
+
+    last_gun_states.retain(|player, _| players.contains(*player));
+    let mut sounds = Vec::new();
+    for (player, position, velocity, health, ground, gun, attachments) in players.iter() {
+        // A shot shows as less ammo, or more heat on heat weapons.
+        let fired = gun.is_some_and(|gun| {
+            let state = (
+                gun.ammo_in_magazine,
+                gun.heat.as_ref().map_or(0.0, |heat| heat.level),
+            );
+            last_gun_states
+                .insert(player, state)
+                .is_some_and(|(ammo, heat)| state.0 < ammo || state.1 > heat)
+        });
+        let grounded = ground.is_none_or(|ground| ground.is_grounded);
+        if !health.is_dead
+            && let Some(kind) = SoundKind::made_by(velocity.0, grounded, fired)
+        {
+            let range_multiplier = match (kind, attachments) {
+                (SoundKind::Gunshot, Some(attachments)) => {
+                    attachments.modifiers().sound_range_multiplier
+                }
+                _ => 1.0,
+            };
+            sounds.push((player, position.0, kind, range_multiplier));
+        }
+    }
+    if sounds.is_empty() {
+        return;
+    }
+
+    for (bot, position, health, mut perception) in bots.iter_mut() {
+        if health.is_dead {
+            continue;
+        }
+        for &(player, source, kind, range_multiplier) in &sounds {
+            // Carrying twice as far is as loud as being twice as close.
+            let distance = position.0.distance(source) / range_multiplier.max(f32::EPSILON);
+            // Only cast for sounds that would be heard with nothing in the way.
+            if !hearing.is_heard(kind, distance, 0) {
+                continue;
+            }
+            let occluders = line_of_sight.occluders(
+                bot,
+                position.0 + eye,
+                player,
+                source + eye,
+                MAX_HEARD_OCCLUDERS,
+            );
+            if hearing.is_heard(kind, distance, occluders) {
+                perception.hear(player, source, now);
+            }
+        }
+    }
+}
+
+/// Let memories fade and update what each bot does about what is left. Dead bots forget
+/// everything.
+pub fn update_bot_awareness(
+    clock: Res<GameClock>,
+    mut bots: Query<(&Health, &mut BotPerception, &mut Awareness)>,
+) {
+    let now = clock.elapsed_secs();
+    for (health, mut perception, mut awareness) in bots.iter_mut() {
+        if health.is_dead {
+            perception.enemies.clear();
+        } else {
+            perception.forget_faded(now);
+        }
+        let next = perception.awareness(now);
+        if *awareness != next {
+            *awareness = next;
+        }
+    }
+}
@@ -0,0 +1,55 @@
+//! Bots driven by outside processes over the remote agent bridge of the
+//! `reinforcement_learning` crate, for training against a live server. Built with the
+//! `remote-agents` feature and started by `RemoteAgentSettings`.
+
+use bevy::prelude::{
+    App, Commands, Entity, FixedUpdate, IntoScheduleConfigs, Local, Plugin, Query, Res, With,
+    Without, in_state,
+};
+use leafwing_input_manager::prelude::ActionState;
+use reinforcement_learning::remote_agents::{
+    RemoteAgent, RemoteAgentBridge, RemoteAgentBridgePlugin,
+};
+use shared::bot_policy::PolicyControlled;
+use shared::bots::BotProfile;
+use shared::inputs::input::PlayerAction;
+use shared::inputs::movement::GroundState;
+
+use crate::ServerGameState;
+
+pub use reinforcement_learning::remote_agents::RemoteAgentSettings;
+
+pub struct ServerRemoteAgentsPlugin;
+
+impl Plugin for ServerRemoteAgentsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RemoteAgentBridgePlugin);
+        app.add_systems(
+            FixedUpdate,
+            attach_remote_agents.run_if(in_state(ServerGameState::Playing)),
+        );
+    }
+}
+
+/// Once the bridge is up, hand every bot to the remote agents, numbered as they show up.
+/// Their scripted navigation stands down and they move by the same inputs as players.
+fn attach_remote_agents(
+    mut commands: Commands,
+    bridge: Option<Res<RemoteAgentBridge>>,
+    mut next_id: Local<u32>,
+    bots: Query<Entity, (With<BotProfile>, Without<RemoteAgent>)>,
+) {
+    if bridge.is_none() {
+        return;
+    }
+
+    for bot in &bots {
+        commands.entity(bot).insert((
+            RemoteAgent::new(*next_id),
+            ActionState::<PlayerAction>::default(),
+            GroundState::default(),
+            PolicyControlled { active: true },
+        ));
+        *next_id += 1;
+    }
+}
@@ -0,0 +1,60 @@
+use bevy::prelude::{Add, App, Commands, Component, Name, On, Plugin, Res, Resource};
+use lightyear::prelude::{ComponentReplicationOverrides, Replicate};
+use shared::components::flashlight::PlayerFlashlight;
+use shared::inputs::look::LookVelocity;
+use shared::protocol::PlayerColor;
+
+/// What the server replicates besides the simulation itself. Pure RL/gym servers have no
+/// one looking at the result, so `Minimal` saves the bandwidth and CPU of cosmetics.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReplicationProfile {
+    #[default]
+    Full,
+    /// No names, player colors, look velocities or flashlights, and no messages that only
+    /// trigger effects (explosions, debris bursts).
+    Minimal,
+}
+
+impl ReplicationProfile {
+    /// Gym servers only replicate what agents need.
+    pub fn for_gym(gym: bool) -> Self {
+        if gym { Self::Minimal } else { Self::Full }
+    }
+
+    pub fn sends_cosmetics(self) -> bool {
+        self == Self::Full
+    }
+}
+
+pub struct ServerReplicationProfilePlugin;
+
+impl Plugin for ServerReplicationProfilePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReplicationProfile>();
+        app.add_observer(strip_cosmetic_replication);
+    }
+}
+
+fn disabled<C: Component>() -> ComponentReplicationOverrides<C> {
+    let mut overrides = ComponentReplicationOverrides::<C>::default();
+    overrides.disable_all();
+    overrides
+}
+
+/// Keeps cosmetic components of every replicated entity on the server under the
+/// `Minimal` profile.
+fn strip_cosmetic_replication(
+    trigger: On<Add, Replicate>,
+    profile: Res<ReplicationProfile>,
+    mut commands: Commands,
+) {
+    if profile.sends_cosmetics() {
+        return;
+    }
+    commands.entity(trigger.entity).insert((
+        disabled::<Name>(),
+        disabled::<PlayerColor>(),
+        disabled::<LookVelocity>(),
+        disabled::<PlayerFlashlight>(),
+    ));
+}
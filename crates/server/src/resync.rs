@@ -0,0 +1,162 @@
+use avian3d::prelude::{LinearVelocity, Position, Rotation};
+use bevy::prelude::{
+    App, Entity, IntoScheduleConfigs, Plugin, Query, Res, ResMut, Resource, Single, Time, Timer,
+    TimerMode, Update, With, debug, error, in_state,
+};
+
+use lightyear::connection::client_of::ClientOf;
+use lightyear::prelude::{
+    Connected, MessageReceiver, NetworkTarget, RemoteId, Replicate, Server,
+    ServerMultiMessageSender,
+};
+use shared::components::health::Health;
+use shared::protocol::LobbyControlChannel;
+use shared::resync::{
+    CriticalState, RESYNC_AT_REST_SPEED, RESYNC_INTERVAL_SECS, ResyncChecksum, ResyncChecksumEntry,
+    ResyncKeyframe, ResyncKeyframeEntry, ResyncRequest,
+};
+
+use crate::ServerGameState;
+
+/// Periodic checksums of every replicated character's critical state, and keyframes for
+/// the clients whose copy has drifted.
+pub struct ServerResyncPlugin;
+
+impl Plugin for ServerResyncPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ResyncRounds>();
+        app.add_systems(
+            Update,
+            (send_resync_checksums, send_resync_keyframes)
+                .chain()
+                .run_if(in_state(ServerGameState::Playing)),
+        );
+    }
+}
+
+/// The latest checksum round. Requests refer to its entries by index, so only requests
+/// for this round can be answered; an older mismatch shows up again in the next round.
+#[derive(Resource)]
+struct ResyncRounds {
+    timer: Timer,
+    round: u32,
+    entities: Vec<Entity>,
+}
+
+impl Default for ResyncRounds {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(RESYNC_INTERVAL_SECS, TimerMode::Repeating),
+            round: 0,
+            entities: Vec::new(),
+        }
+    }
+}
+
+type CriticalStateData<'a> = (
+    Entity,
+    &'a Position,
+    &'a Rotation,
+    Option<&'a LinearVelocity>,
+    &'a Health,
+);
+
+/// Replicated characters, players and NPCs alike.
+type CriticalStateFilter = (With<Replicate>, With<Health>);
+
+fn critical_state(position: &Position, rotation: &Rotation, health: &Health) -> CriticalState {
+    CriticalState {
+        position: position.0,
+        rotation: rotation.0,
+        health: Some(health.clone()),
+    }
+}
+
+fn send_resync_checksums(
+    time: Res<Time>,
+    mut rounds: ResMut<ResyncRounds>,
+    states: Query<CriticalStateData, CriticalStateFilter>,
+    mut sender: ServerMultiMessageSender,
+    server: Single<&Server>,
+) {
+    if !rounds.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    rounds.round = rounds.round.wrapping_add(1);
+    rounds.entities.clear();
+    let mut entries = Vec::new();
+    for (entity, position, rotation, velocity, health) in states.iter() {
+        let at_rest = velocity.is_none_or(|velocity| velocity.0.length() < RESYNC_AT_REST_SPEED);
+        entries.push(ResyncChecksumEntry {
+            entity,
+            checksum: critical_state(position, rotation, health).checksum(at_rest),
+            at_rest,
+        });
+        rounds.entities.push(entity);
+    }
+
+    sender
+        .send::<ResyncChecksum, LobbyControlChannel>(
+            &ResyncChecksum {
+                round: rounds.round,
+                entries,
+            },
+            server.into_inner(),
+            &NetworkTarget::All,
+        )
+        .unwrap_or_else(|e| {
+            error!("Failed to send message: {:?}", e);
+        });
+}
+
+fn send_resync_keyframes(
+    rounds: Res<ResyncRounds>,
+    mut receivers: Query<
+        (&RemoteId, &mut MessageReceiver<ResyncRequest>),
+        (With<ClientOf>, With<Connected>),
+    >,
+    states: Query<CriticalStateData, CriticalStateFilter>,
+    mut sender: ServerMultiMessageSender,
+    server: Single<&Server>,
+) {
+    let server = server.into_inner();
+    for (remote_id, mut receiver) in receivers.iter_mut() {
+        for request in receiver.receive() {
+            if request.round != rounds.round {
+                continue;
+            }
+
+            let entries: Vec<ResyncKeyframeEntry> = request
+                .mismatched
+                .iter()
+                .filter_map(|&index| rounds.entities.get(index as usize))
+                .filter_map(|&entity| states.get(entity).ok())
+                .map(
+                    |(entity, position, rotation, _, health)| ResyncKeyframeEntry {
+                        entity,
+                        state: critical_state(position, rotation, health),
+                    },
+                )
+                .collect();
+            if entries.is_empty() {
+                continue;
+            }
+
+            debug!(
+                "Resync: sending a keyframe of {} entities to client {:?}",
+                entries.len(),
+                remote_id.0
+            );
+            sender
+                .send::<ResyncKeyframe, LobbyControlChannel>(
+                    &ResyncKeyframe { entries },
+                    server,
+                    &NetworkTarget::Single(remote_id.0),
+                )
+                .unwrap_or_else(|e| {
+                    error!("Failed to send message: {:?}", e);
+                });
+        }
+    }
+}
@@ -0,0 +1,252 @@
+//! Messages held back until a tick or a state entry.
+//!
+//! A system that changes the server state and tells clients about it in the same frame
+//! sends the message before the transition has run: clients can react to a
+//! `StartLoadingGameEvent` before the server is loading. Scheduling the message for
+//! [`SendWhen::StateEntered`] instead sends it once the transition is done.
+//!
+//! What the scheduler guarantees: a message is never sent before its condition holds, it is
+//! sent at most once, and messages that become due in the same frame go out in the order
+//! they were scheduled. Every scheduled message ends in a [`ScheduledMessageReport`] that
+//! carries the channel's [`Delivery`], so a sender can tell whether `Sent` means the
+//! message will arrive.
+
+use bevy::prelude::{
+    App, IntoScheduleConfigs, Message, MessageWriter, Plugin, PostUpdate, Query, Res, ResMut,
+    Resource, State, error,
+};
+use lightyear::prelude::{
+    Channel, LocalTimeline, NetworkTarget, NetworkTimeline, Server, ServerMultiMessageSender, Tick,
+    TransportSystems,
+};
+use shared::protocol::{ChannelDelivery, Delivery};
+
+use crate::ServerGameState;
+
+/// When a scheduled message may go out.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SendWhen {
+    /// The next time scheduled messages are sent, at the end of this frame.
+    Now,
+    /// At or after this server tick.
+    Tick(Tick),
+    /// Once the server is in this state: after its transition ran, or right away when the
+    /// server is already there.
+    StateEntered(ServerGameState),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ScheduledMessageId(u64);
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScheduledOutcome {
+    /// Handed to the channel at this tick; see [`ScheduledMessageReport::delivery`] for
+    /// whether it will arrive.
+    Sent { tick: Option<Tick> },
+    /// The send failed and is not retried.
+    Failed(String),
+    /// Cancelled before it was due.
+    Cancelled,
+}
+
+/// Written once for every scheduled message, when it is sent, fails or is cancelled.
+#[derive(Message, Clone, Debug)]
+pub struct ScheduledMessageReport {
+    pub id: ScheduledMessageId,
+    /// Type name of the message.
+    pub message: &'static str,
+    pub delivery: Delivery,
+    pub outcome: ScheduledOutcome,
+}
+
+type SendFn =
+    Box<dyn FnOnce(&mut ServerMultiMessageSender, &Server) -> Result<(), String> + Send + Sync>;
+
+struct ScheduledMessage {
+    id: ScheduledMessageId,
+    when: SendWhen,
+    message: &'static str,
+    delivery: Delivery,
+    send: SendFn,
+}
+
+/// Messages waiting for their [`SendWhen`], in the order they were scheduled.
+#[derive(Resource, Default)]
+pub struct ScheduledMessages {
+    pending: Vec<ScheduledMessage>,
+    cancelled: Vec<(ScheduledMessageId, &'static str, Delivery)>,
+    next_id: u64,
+}
+
+impl ScheduledMessages {
+    /// Queues `message` on channel `C` for `target`, to be sent `when`.
+    pub fn schedule<M, C>(
+        &mut self,
+        message: M,
+        when: SendWhen,
+        target: NetworkTarget,
+    ) -> ScheduledMessageId
+    where
+        M: lightyear::prelude::Message + Send + Sync + 'static,
+        C: Channel + ChannelDelivery,
+    {
+        let id = ScheduledMessageId(self.next_id);
+        self.next_id += 1;
+        self.pending.push(ScheduledMessage {
+            id,
+            when,
+            message: std::any::type_name::<M>(),
+            delivery: C::DELIVERY,
+            send: Box::new(move |sender, server| {
+                sender
+                    .send::<M, C>(&message, server, &target)
+                    .map_err(|e| format!("{:?}", e))
+            }),
+        });
+        id
+    }
+
+    /// Drops a message that was not sent yet. Returns whether it was still pending.
+    pub fn cancel(&mut self, id: ScheduledMessageId) -> bool {
+        let Some(index) = self.pending.iter().position(|scheduled| scheduled.id == id) else {
+            return false;
+        };
+        let scheduled = self.pending.remove(index);
+        self.cancelled
+            .push((scheduled.id, scheduled.message, scheduled.delivery));
+        true
+    }
+
+    pub fn is_pending(&self, id: ScheduledMessageId) -> bool {
+        self.pending.iter().any(|scheduled| scheduled.id == id)
+    }
+
+    /// Takes out, in order, the messages due at `tick` in `state`.
+    fn take_due(&mut self, tick: Option<Tick>, state: &ServerGameState) -> Vec<ScheduledMessage> {
+        let (due, pending) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|scheduled| is_due(&scheduled.when, tick, state));
+        self.pending = pending;
+        due
+    }
+}
+
+fn is_due(when: &SendWhen, tick: Option<Tick>, state: &ServerGameState) -> bool {
+    match when {
+        SendWhen::Now => true,
+        // Ticks wrap around; the difference tells which one comes first.
+        SendWhen::Tick(at) => tick.is_some_and(|tick| tick - *at >= 0),
+        SendWhen::StateEntered(entered) => entered == state,
+    }
+}
+
+pub struct ServerScheduledMessagesPlugin;
+
+impl Plugin for ServerScheduledMessagesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScheduledMessages>();
+        app.add_message::<ScheduledMessageReport>();
+        // State transitions of the frame have run by now, and the messages still leave
+        // with this frame's packets.
+        app.add_systems(
+            PostUpdate,
+            send_scheduled_messages.before(TransportSystems::Send),
+        );
+    }
+}
+
+/// Adds [`ServerScheduledMessagesPlugin`] unless the app already has it, for plugins whose
+/// systems schedule messages.
+pub fn ensure_scheduled_messages(app: &mut App) {
+    if !app.is_plugin_added::<ServerScheduledMessagesPlugin>() {
+        app.add_plugins(ServerScheduledMessagesPlugin);
+    }
+}
+
+fn send_scheduled_messages(
+    mut scheduled: ResMut<ScheduledMessages>,
+    mut sender: ServerMultiMessageSender,
+    servers: Query<(&Server, Option<&LocalTimeline>)>,
+    state: Res<State<ServerGameState>>,
+    mut reports: MessageWriter<ScheduledMessageReport>,
+) {
+    for (id, message, delivery) in std::mem::take(&mut scheduled.cancelled) {
+        reports.write(ScheduledMessageReport {
+            id,
+            message,
+            delivery,
+            outcome: ScheduledOutcome::Cancelled,
+        });
+    }
+
+    let Some((server, timeline)) = servers.iter().next() else {
+        return;
+    };
+    let tick = timeline.map(|timeline| timeline.tick());
+    for due in scheduled.take_due(tick, state.get()) {
+        let outcome = match (due.send)(&mut sender, server) {
+            Ok(()) => ScheduledOutcome::Sent { tick },
+            Err(e) => {
+                error!("Failed to send scheduled {}: {}", due.message, e);
+                ScheduledOutcome::Failed(e)
+            }
+        };
+        reports.write(ScheduledMessageReport {
+            id: due.id,
+            message: due.message,
+            delivery: due.delivery,
+            outcome,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ScheduledMessages, SendWhen, is_due};
+    use crate::ServerGameState;
+    use lightyear::prelude::{NetworkTarget, Tick};
+    use shared::protocol::{LobbyControlChannel, ReturnToLobbyEvent, StartLoadingGameEvent};
+
+    #[test]
+    fn messages_wait_for_their_state_and_keep_their_order() {
+        let mut scheduled = ScheduledMessages::default();
+        let loading = scheduled.schedule::<_, LobbyControlChannel>(
+            StartLoadingGameEvent { start: true },
+            SendWhen::StateEntered(ServerGameState::Loading),
+            NetworkTarget::All,
+        );
+        let later = scheduled.schedule::<_, LobbyControlChannel>(
+            ReturnToLobbyEvent,
+            SendWhen::Tick(Tick(110)),
+            NetworkTarget::All,
+        );
+        let now = scheduled.schedule::<_, LobbyControlChannel>(
+            ReturnToLobbyEvent,
+            SendWhen::Now,
+            NetworkTarget::All,
+        );
+
+        let due = scheduled.take_due(Some(Tick(100)), &ServerGameState::Lobby);
+        assert_eq!(due.iter().map(|due| due.id).collect::<Vec<_>>(), [now]);
+
+        let due = scheduled.take_due(Some(Tick(110)), &ServerGameState::Loading);
+        assert_eq!(
+            due.iter().map(|due| due.id).collect::<Vec<_>>(),
+            [loading, later]
+        );
+        assert!(!scheduled.is_pending(loading));
+        assert!(!scheduled.cancel(loading));
+
+        // Tick numbers wrap around.
+        assert!(is_due(
+            &SendWhen::Tick(Tick(u16::MAX)),
+            Some(Tick(2)),
+            &ServerGameState::Lobby
+        ));
+        assert!(!is_due(
+            &SendWhen::Tick(Tick(2)),
+            Some(Tick(u16::MAX)),
+            &ServerGameState::Lobby
+        ));
+    }
+}
@@ -0,0 +1,226 @@
+use bevy::prelude::{
+    App, Commands, Entity, IntoScheduleConfigs, MessageReader, Name, OnEnter, Plugin, Query, Res,
+    ResMut, Resource, Single, Time, Update, With, error, in_state, info,
+};
+use std::collections::HashMap;
+
+use avian3d::prelude::Position;
+use lightyear::prelude::{NetworkTarget, Replicate, Server, ServerMultiMessageSender};
+use shared::components::destructible::Destructible;
+use shared::components::health::{DamageEvent, DeathEvent, process_damage_events};
+use shared::components::score::{ASSIST_WINDOW_SECS, MatchScore};
+use shared::components::team::{Team, TeamRules, is_friendly_fire};
+use shared::protocol::{
+    CharacterMarker, HitConfirmation, KillFeedEvent, LobbyControlChannel, PlayerId,
+};
+
+use crate::ServerGameState;
+use crate::match_events::{MatchEvent, MatchEventStream};
+use crate::match_recap::MatchTelemetryRecorder;
+
+/// Players that recently damaged each entity, with the time of their last hit.
+#[derive(Resource, Default)]
+struct RecentDamage(HashMap<Entity, Vec<(u64, f32)>>);
+
+impl RecentDamage {
+    fn assisters(&self, victim: Entity, now: f32) -> Vec<u64> {
+        self.0
+            .get(&victim)
+            .into_iter()
+            .flatten()
+            .filter(|(_, time)| now - time <= ASSIST_WINDOW_SECS)
+            .map(|(player_id, _)| *player_id)
+            .collect()
+    }
+}
+
+pub struct ServerScorePlugin;
+
+impl Plugin for ServerScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RecentDamage>();
+        app.add_systems(OnEnter(ServerGameState::Playing), spawn_match_score);
+        app.add_systems(
+            Update,
+            // Read deaths in the frame they happen, before dead players are despawned.
+            (track_player_damage, record_kills, send_hit_confirmations)
+                .chain()
+                .after(process_damage_events)
+                .run_if(in_state(ServerGameState::Playing)),
+        );
+    }
+}
+
+fn spawn_match_score(mut commands: Commands, existing: Query<&MatchScore>) {
+    if existing.is_empty() {
+        commands.spawn((
+            MatchScore::default(),
+            Replicate::to_clients(NetworkTarget::All),
+            Name::from("MatchScore"),
+        ));
+    }
+}
+
+fn player_bits(players: &Query<&PlayerId>, entity: Entity) -> Option<u64> {
+    players
+        .get(entity)
+        .ok()
+        .map(|player_id| player_id.0.to_bits())
+}
+
+fn track_player_damage(
+    time: Res<Time>,
+    mut damage_events: MessageReader<DamageEvent>,
+    players: Query<&PlayerId>,
+    characters: Query<&Position, With<CharacterMarker>>,
+    mut recent_damage: ResMut<RecentDamage>,
+    mut telemetry: Option<ResMut<MatchTelemetryRecorder>>,
+) {
+    let now = time.elapsed_secs();
+    recent_damage.0.retain(|_, hits| {
+        hits.retain(|(_, time)| now - time <= ASSIST_WINDOW_SECS);
+        !hits.is_empty()
+    });
+
+    for event in damage_events.read() {
+        if let (Some(telemetry), Ok(position)) = (telemetry.as_mut(), characters.get(event.target))
+        {
+            telemetry.record_damage(now, position.0, event.amount);
+        }
+
+        let Some(attacker) = event
+            .source
+            .and_then(|source| player_bits(&players, source))
+        else {
+            continue;
+        };
+
+        let hits = recent_damage.0.entry(event.target).or_default();
+        hits.retain(|(player_id, _)| *player_id != attacker);
+        hits.push((attacker, now));
+    }
+}
+
+/// Update the scoreboard for every death and announce it in the kill feed. Destroyed props
+/// are not kills.
+#[allow(clippy::too_many_arguments)]
+fn record_kills(
+    time: Res<Time>,
+    mut death_events: MessageReader<DeathEvent>,
+    players: Query<&PlayerId>,
+    names: Query<&Name>,
+    positions: Query<&Position>,
+    props: Query<(), With<Destructible>>,
+    mut recent_damage: ResMut<RecentDamage>,
+    mut match_score: Query<&mut MatchScore>,
+    match_events: Option<Res<MatchEventStream>>,
+    mut telemetry: Option<ResMut<MatchTelemetryRecorder>>,
+    mut sender: ServerMultiMessageSender,
+    server: Single<&Server>,
+) {
+    let now = time.elapsed_secs();
+    let server = server.into_inner();
+
+    for event in death_events.read() {
+        if props.contains(event.target) {
+            continue;
+        }
+
+        let killer = event
+            .source
+            .and_then(|source| player_bits(&players, source));
+        let victim = player_bits(&players, event.target);
+        let assisters = recent_damage.assisters(event.target, now);
+        recent_damage.0.remove(&event.target);
+
+        if let Some(mut score) = match_score.iter_mut().next() {
+            score.record_kill(killer, victim, &assisters);
+        }
+
+        let name_of = |entity: Entity| {
+            names
+                .get(entity)
+                .map(|name| name.as_str().to_string())
+                .unwrap_or_else(|_| format!("{:?}", entity))
+        };
+        let kill_feed = KillFeedEvent {
+            killer: event.source.map(name_of),
+            victim: name_of(event.target),
+            headshot: event.headshot,
+        };
+        info!(
+            "💀 {} killed {}{}",
+            kill_feed.killer.as_deref().unwrap_or("world"),
+            kill_feed.victim,
+            if kill_feed.headshot {
+                " (headshot)"
+            } else {
+                ""
+            }
+        );
+
+        if let Some(telemetry) = telemetry.as_mut() {
+            let position = positions.get(event.target).ok().map(|position| position.0);
+            telemetry.record_kill(now, &kill_feed, position);
+        }
+
+        if let Some(stream) = &match_events {
+            stream.publish(&MatchEvent::Kill {
+                killer: kill_feed.killer.clone(),
+                victim: kill_feed.victim.clone(),
+                headshot: kill_feed.headshot,
+            });
+        }
+
+        sender
+            .send::<KillFeedEvent, LobbyControlChannel>(&kill_feed, server, &NetworkTarget::All)
+            .unwrap_or_else(|e| {
+                error!("Failed to send message: {:?}", e);
+            });
+    }
+}
+
+/// Tell shooters their damage landed on a character, and whether it killed. Hits on props,
+/// self-damage and teammate hits dropped by the team rules are not confirmed.
+fn send_hit_confirmations(
+    mut damage_events: MessageReader<DamageEvent>,
+    mut death_events: MessageReader<DeathEvent>,
+    players: Query<&PlayerId>,
+    characters: Query<Option<&Team>, With<CharacterMarker>>,
+    team_rules: Option<Res<TeamRules>>,
+    mut sender: ServerMultiMessageSender,
+    server: Single<&Server>,
+) {
+    let server = server.into_inner();
+    let friendly_fire = team_rules.is_some_and(|rules| rules.friendly_fire);
+
+    let hits = damage_events
+        .read()
+        .map(|event| (event.source, event.target, false, event.headshot));
+    let kills = death_events
+        .read()
+        .map(|event| (event.source, event.target, true, event.headshot));
+
+    for (source, target, kill, headshot) in hits.chain(kills) {
+        let Some(source) = source.filter(|source| *source != target) else {
+            continue;
+        };
+        let (Ok(shooter), Ok(target_team)) = (players.get(source), characters.get(target)) else {
+            continue;
+        };
+        let source_team = characters.get(source).ok().flatten().copied();
+        if !friendly_fire && is_friendly_fire(source_team, target_team.copied()) {
+            continue;
+        }
+
+        sender
+            .send::<HitConfirmation, LobbyControlChannel>(
+                &HitConfirmation { kill, headshot },
+                server,
+                &NetworkTarget::Single(shooter.0),
+            )
+            .unwrap_or_else(|e| {
+                error!("Failed to send message: {:?}", e);
+            });
+    }
+}
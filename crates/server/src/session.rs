@@ -0,0 +1,321 @@
+use avian3d::prelude::LinearVelocity;
+use bevy::prelude::{
+    Added, App, Commands, Component, Entity, IntoScheduleConfigs, Plugin, Query, Res, ResMut,
+    Resource, Single, Time, Update, Vec3, With, error, in_state, info, warn,
+};
+use leafwing_input_manager::prelude::ActionState;
+use std::collections::HashMap;
+
+use lightyear::prelude::{
+    Connected, ControlledBy, InterpolationTarget, Lifetime, MessageReceiver, NetworkTarget,
+    PredictionTarget, RemoteId, Server, ServerMultiMessageSender, server::ClientOf,
+};
+use shared::inputs::input::PlayerAction;
+use shared::protocol::{LobbyControlChannel, PlayerId, ResumeSessionRequest, SessionGranted};
+
+use crate::ServerGameState;
+
+/// How long a dropped player's character waits, frozen, for its client to come back.
+pub const RECONNECT_GRACE_SECS: f32 = 30.0;
+/// How long a client that came back has to resume its session before it starts over as a
+/// new player.
+pub const RESUME_TIMEOUT_SECS: f32 = 5.0;
+
+/// Session tokens and reconnects. A client whose connection drops mid-match keeps its
+/// character for `RECONNECT_GRACE_SECS`; reconnecting with the same client id and the token
+/// it was granted hands the character back instead of spawning a fresh one.
+pub struct ServerSessionPlugin;
+
+impl Plugin for ServerSessionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SessionRegistry>();
+        app.add_systems(
+            Update,
+            (
+                grant_session_tokens,
+                freeze_disconnected_players,
+                (handle_resume_requests, expire_abandoned_players)
+                    .run_if(in_state(ServerGameState::Playing)),
+                start_unresumed_sessions,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// The current session token of every player, keyed by player id. The client id already
+/// identifies the player; the token proves the reconnecting client is the one that left.
+#[derive(Resource, Default, Debug)]
+pub struct SessionRegistry {
+    tokens: HashMap<u64, u64>,
+}
+
+impl SessionRegistry {
+    /// Issue a fresh token for `player_id`, replacing any previous one.
+    pub fn issue(&mut self, player_id: u64) -> u64 {
+        let token = rand::random::<u64>();
+        self.tokens.insert(player_id, token);
+        token
+    }
+
+    pub fn is_valid(&self, player_id: u64, token: u64) -> bool {
+        self.tokens.get(&player_id) == Some(&token)
+    }
+
+    pub fn revoke(&mut self, player_id: u64) {
+        self.tokens.remove(&player_id);
+    }
+}
+
+/// A character whose client dropped mid-match. It stays in the world, frozen, until the
+/// client resumes its session or the grace period runs out.
+#[derive(Component, Debug, Default)]
+pub struct AwaitingReconnect {
+    pub elapsed_secs: f32,
+}
+
+/// A client that connected while its character was waiting for it, and has not resumed
+/// its session yet. It holds no token until it does, or starts over.
+#[derive(Component, Debug, Default)]
+pub struct AwaitingResume {
+    pub elapsed_secs: f32,
+}
+
+/// Give every newly connected client a token, unless its character is waiting for it: that
+/// client has to present the token it already holds.
+fn grant_session_tokens(
+    new_clients: Query<(Entity, &RemoteId), (With<ClientOf>, Added<Connected>)>,
+    frozen_players: Query<&PlayerId, With<AwaitingReconnect>>,
+    mut registry: ResMut<SessionRegistry>,
+    mut sender: ServerMultiMessageSender,
+    server: Single<&Server>,
+    mut commands: Commands,
+) {
+    let server = server.into_inner();
+    for (client_entity, remote_id) in new_clients.iter() {
+        let player_id = remote_id.0.to_bits();
+        if frozen_players
+            .iter()
+            .any(|frozen| frozen.0.to_bits() == player_id)
+        {
+            commands
+                .entity(client_entity)
+                .insert(AwaitingResume::default());
+            continue;
+        }
+
+        let token = registry.issue(player_id);
+        send_session_grant(&mut sender, server, remote_id, token);
+    }
+}
+
+/// Stop a dropped player's character where it stands, so it neither drifts nor acts on the
+/// last input it received.
+fn freeze_disconnected_players(
+    mut frozen_players: Query<
+        (&mut LinearVelocity, Option<&mut ActionState<PlayerAction>>),
+        Added<AwaitingReconnect>,
+    >,
+) {
+    for (mut velocity, action_state) in frozen_players.iter_mut() {
+        velocity.0 = Vec3::ZERO;
+        if let Some(mut action_state) = action_state {
+            action_state.disable();
+        }
+    }
+}
+
+fn handle_resume_requests(
+    mut receivers: Query<
+        (
+            Entity,
+            &RemoteId,
+            &mut MessageReceiver<ResumeSessionRequest>,
+        ),
+        (With<ClientOf>, With<Connected>),
+    >,
+    mut frozen_players: Query<
+        (Entity, &PlayerId, Option<&mut ActionState<PlayerAction>>),
+        With<AwaitingReconnect>,
+    >,
+    mut registry: ResMut<SessionRegistry>,
+    mut sender: ServerMultiMessageSender,
+    server: Single<&Server>,
+    mut commands: Commands,
+) {
+    let server = server.into_inner();
+    for (client_entity, remote_id, mut receiver) in receivers.iter_mut() {
+        let Some(request) = receiver.receive().last() else {
+            continue;
+        };
+
+        let player_id = remote_id.0.to_bits();
+        let Some((player_entity, _, action_state)) = frozen_players
+            .iter_mut()
+            .find(|(_, frozen, _)| frozen.0.to_bits() == player_id)
+        else {
+            // Nothing to reclaim: the client got a fresh token when it connected, or gets
+            // one from `start_unresumed_sessions`.
+            continue;
+        };
+
+        if !registry.is_valid(player_id, request.token) {
+            // Someone reusing the client id without the token starts over; the fresh
+            // character comes from the late-join spawn.
+            warn!(
+                "Client {} presented an invalid session token, dropping its old character",
+                player_id
+            );
+            commands.entity(player_entity).despawn();
+        } else {
+            info!("🔌 Client {} resumed its session", player_id);
+            commands
+                .entity(player_entity)
+                .remove::<AwaitingReconnect>()
+                .insert((
+                    ControlledBy {
+                        owner: client_entity,
+                        lifetime: Lifetime::Persistent,
+                    },
+                    PredictionTarget::to_clients(NetworkTarget::Single(remote_id.0)),
+                    InterpolationTarget::to_clients(NetworkTarget::AllExceptSingle(remote_id.0)),
+                ));
+            if let Some(mut action_state) = action_state {
+                action_state.enable();
+            }
+        }
+
+        // Tokens are single use.
+        commands.entity(client_entity).remove::<AwaitingResume>();
+        let token = registry.issue(player_id);
+        send_session_grant(&mut sender, server, remote_id, token);
+    }
+}
+
+/// Start over the clients that came back but never resumed: once their character is gone
+/// (expired, or removed with the match) or `RESUME_TIMEOUT_SECS` have passed, they get a
+/// fresh token, and the character still waiting for them is removed for the late-join
+/// spawn to replace.
+fn start_unresumed_sessions(
+    time: Res<Time>,
+    mut clients: Query<(Entity, &RemoteId, &mut AwaitingResume), With<Connected>>,
+    frozen_players: Query<(Entity, &PlayerId), With<AwaitingReconnect>>,
+    mut registry: ResMut<SessionRegistry>,
+    mut sender: ServerMultiMessageSender,
+    server: Single<&Server>,
+    mut commands: Commands,
+) {
+    let server = server.into_inner();
+    for (client_entity, remote_id, mut awaiting) in clients.iter_mut() {
+        awaiting.elapsed_secs += time.delta_secs();
+        let player_id = remote_id.0.to_bits();
+        let frozen_player = frozen_players
+            .iter()
+            .find(|(_, frozen)| frozen.0.to_bits() == player_id);
+        if frozen_player.is_some() && awaiting.elapsed_secs < RESUME_TIMEOUT_SECS {
+            continue;
+        }
+
+        if let Some((player_entity, _)) = frozen_player {
+            warn!(
+                "Client {} did not resume its session in time, dropping its old character",
+                player_id
+            );
+            commands.entity(player_entity).despawn();
+        }
+        commands.entity(client_entity).remove::<AwaitingResume>();
+        let token = registry.issue(player_id);
+        send_session_grant(&mut sender, server, remote_id, token);
+    }
+}
+
+fn expire_abandoned_players(
+    time: Res<Time>,
+    mut frozen_players: Query<(Entity, &PlayerId, &mut AwaitingReconnect)>,
+    mut registry: ResMut<SessionRegistry>,
+    mut commands: Commands,
+) {
+    for (entity, player_id, mut awaiting) in frozen_players.iter_mut() {
+        awaiting.elapsed_secs += time.delta_secs();
+        if awaiting.elapsed_secs < RECONNECT_GRACE_SECS {
+            continue;
+        }
+
+        info!(
+            "Client {} did not reconnect in time, removing its character",
+            player_id.0.to_bits()
+        );
+        registry.revoke(player_id.0.to_bits());
+        commands.entity(entity).despawn();
+    }
+}
+
+fn send_session_grant(
+    sender: &mut ServerMultiMessageSender,
+    server: &Server,
+    remote_id: &RemoteId,
+    token: u64,
+) {
+    sender
+        .send::<SessionGranted, LobbyControlChannel>(
+            &SessionGranted { token },
+            server,
+            &NetworkTarget::Single(remote_id.0),
+        )
+        .unwrap_or_else(|e| {
+            error!("Failed to send message: {:?}", e);
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AwaitingReconnect, RECONNECT_GRACE_SECS, SessionRegistry, expire_abandoned_players,
+    };
+    use bevy::prelude::{App, MinimalPlugins, Update};
+    use lightyear::prelude::PeerId;
+    use shared::protocol::PlayerId;
+
+    #[test]
+    fn tokens_are_per_player_and_replaced_on_reissue() {
+        let mut registry = SessionRegistry::default();
+        let first = registry.issue(1);
+        let other = registry.issue(2);
+
+        assert!(registry.is_valid(1, first));
+        assert!(!registry.is_valid(1, other));
+
+        let second = registry.issue(1);
+        assert!(registry.is_valid(1, second));
+
+        registry.revoke(1);
+        assert!(!registry.is_valid(1, second));
+    }
+
+    #[test]
+    fn frozen_players_are_removed_after_the_grace_period() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.init_resource::<SessionRegistry>();
+        app.add_systems(Update, expire_abandoned_players);
+
+        let waiting = app
+            .world_mut()
+            .spawn((PlayerId(PeerId::Netcode(1)), AwaitingReconnect::default()))
+            .id();
+        let abandoned = app
+            .world_mut()
+            .spawn((
+                PlayerId(PeerId::Netcode(2)),
+                AwaitingReconnect {
+                    elapsed_secs: RECONNECT_GRACE_SECS,
+                },
+            ))
+            .id();
+
+        app.update();
+
+        assert!(app.world().get_entity(waiting).is_ok());
+        assert!(app.world().get_entity(abandoned).is_err());
+    }
+}
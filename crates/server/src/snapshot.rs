@@ -0,0 +1,342 @@
+use avian3d::prelude::{LinearVelocity, Position, Rotation};
+use bevy::prelude::{
+    App, Entity, IntoScheduleConfigs, Name, Plugin, Query, Res, ResMut, Resource, Single, Time,
+    Timer, TimerMode, Update, With, info, warn,
+};
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+use lightyear::connection::client_of::ClientOf;
+use lightyear::prelude::{
+    Connected, MessageReceiver, NetworkTarget, RemoteId, Replicate, Server,
+    ServerMultiMessageSender,
+};
+use shared::components::{
+    attachments::WeaponAttachments, flashlight::PlayerFlashlight, health::Health, weapons::Gun,
+};
+use shared::inputs::movement::GroundState;
+use shared::navigation::PatrolState;
+use shared::protocol::{
+    DebugChannel, EntitySnapshot, EntitySnapshotEntry, EntitySnapshotSubscribe, LobbyState,
+    PlayerId,
+};
+
+use crate::network::secrets_match;
+
+/// How often subscribed debug clients receive a fresh snapshot.
+const SNAPSHOT_INTERVAL_SECS: f32 = 0.5;
+
+/// Entity snapshots show every replicated entity, players included, so they are off unless
+/// the server has an inspector password, and only clients sending it may subscribe.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct SnapshotSettings {
+    pub password: Option<String>,
+}
+
+impl SnapshotSettings {
+    fn is_enabled(&self) -> bool {
+        self.password
+            .as_deref()
+            .is_some_and(|password| !password.is_empty())
+    }
+
+    fn accepts(&self, subscription: &EntitySnapshotSubscribe) -> bool {
+        self.is_enabled()
+            && self
+                .password
+                .as_deref()
+                .is_some_and(|password| secrets_match(&subscription.password, password))
+    }
+}
+
+/// Debug clients that asked for entity snapshots, keyed by their `ClientOf` entity.
+#[derive(Resource, Default)]
+pub struct SnapshotSubscribers(pub HashMap<Entity, EntitySnapshotSubscribe>);
+
+#[derive(Resource)]
+struct SnapshotTimer(Timer);
+
+impl Default for SnapshotTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(
+            SNAPSHOT_INTERVAL_SECS,
+            TimerMode::Repeating,
+        ))
+    }
+}
+
+pub struct ServerSnapshotPlugin;
+
+impl Plugin for ServerSnapshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SnapshotSettings>();
+        app.init_resource::<SnapshotSubscribers>();
+        app.init_resource::<SnapshotTimer>();
+        app.add_systems(
+            Update,
+            (receive_snapshot_subscriptions, broadcast_entity_snapshots)
+                .run_if(|settings: Res<SnapshotSettings>| settings.is_enabled()),
+        );
+    }
+}
+
+type SnapshotQueryData<'a> = (
+    Entity,
+    Option<&'a Name>,
+    Option<&'a PlayerId>,
+    Option<&'a Position>,
+    Option<&'a Rotation>,
+    Option<&'a LinearVelocity>,
+    Option<&'a GroundState>,
+    Option<&'a Health>,
+    Option<&'a Gun>,
+    Option<&'a WeaponAttachments>,
+    Option<&'a PlayerFlashlight>,
+    Option<&'a PatrolState>,
+    Option<&'a LobbyState>,
+);
+
+fn push_component<T: Debug>(
+    components: &mut Vec<(String, String)>,
+    label: &str,
+    component: Option<&T>,
+) {
+    if let Some(component) = component {
+        components.push((label.to_string(), format!("{:?}", component)));
+    }
+}
+
+fn describe_entity(row: SnapshotQueryData) -> EntitySnapshotEntry {
+    let (
+        entity,
+        name,
+        player_id,
+        position,
+        rotation,
+        linear_velocity,
+        ground_state,
+        health,
+        gun,
+        attachments,
+        flashlight,
+        patrol_state,
+        lobby_state,
+    ) = row;
+
+    let mut components = Vec::new();
+    if let Some(name) = name {
+        components.push(("Name".to_string(), name.as_str().to_string()));
+    }
+    push_component(&mut components, "PlayerId", player_id);
+    if let Some(position) = position {
+        components.push(("Position".to_string(), format!("{:.2}", position.0)));
+    }
+    if let Some(rotation) = rotation {
+        components.push(("Rotation".to_string(), format!("{:.3}", rotation.0)));
+    }
+    if let Some(linear_velocity) = linear_velocity {
+        components.push((
+            "LinearVelocity".to_string(),
+            format!("{:.2}", linear_velocity.0),
+        ));
+    }
+    push_component(&mut components, "GroundState", ground_state);
+    push_component(&mut components, "Health", health);
+    push_component(&mut components, "Gun", gun);
+    push_component(&mut components, "WeaponAttachments", attachments);
+    push_component(&mut components, "PlayerFlashlight", flashlight);
+    push_component(&mut components, "PatrolState", patrol_state);
+    push_component(&mut components, "LobbyState", lobby_state);
+
+    EntitySnapshotEntry {
+        entity: entity.to_bits(),
+        components,
+    }
+}
+
+/// Applies a subscription's filters to a full entity entry. Entities left without any
+/// matching component are dropped.
+pub fn filter_snapshot_entry(
+    entry: &EntitySnapshotEntry,
+    filter: &EntitySnapshotSubscribe,
+) -> Option<EntitySnapshotEntry> {
+    if filter
+        .entity_filter
+        .is_some_and(|entity| entity != entry.entity)
+    {
+        return None;
+    }
+
+    let Some(component_filter) = filter.component_filter.as_deref() else {
+        return Some(entry.clone());
+    };
+
+    let component_filter = component_filter.to_lowercase();
+    let components: Vec<(String, String)> = entry
+        .components
+        .iter()
+        .filter(|(label, _)| label.to_lowercase().contains(&component_filter))
+        .cloned()
+        .collect();
+
+    (!components.is_empty()).then(|| EntitySnapshotEntry {
+        entity: entry.entity,
+        components,
+    })
+}
+
+/// Registers debug subscribers that sent the inspector password and removes them from the
+/// lobby so they never get a player. Anyone else is left alone.
+fn receive_snapshot_subscriptions(
+    settings: Res<SnapshotSettings>,
+    mut receivers: Query<
+        (
+            Entity,
+            &RemoteId,
+            &mut MessageReceiver<EntitySnapshotSubscribe>,
+        ),
+        (With<ClientOf>, With<Connected>),
+    >,
+    mut lobby_query: Query<&mut LobbyState>,
+    mut subscribers: ResMut<SnapshotSubscribers>,
+) {
+    for (client_entity, remote_id, mut receiver) in receivers.iter_mut() {
+        let Some(subscription) = receiver.receive().last() else {
+            continue;
+        };
+        if !settings.accepts(&subscription) {
+            warn!(
+                "Rejected entity snapshot subscription from client {}: wrong inspector password",
+                remote_id.0
+            );
+            continue;
+        }
+
+        info!(
+            "🔍 Client {} subscribed to entity snapshots ({:?})",
+            remote_id.0, subscription
+        );
+        subscribers.0.insert(client_entity, subscription);
+
+        let client_id_bits = remote_id.0.to_bits();
+        for mut lobby_state in lobby_query.iter_mut() {
+            lobby_state.players.retain(|&id| id != client_id_bits);
+            if lobby_state.host_id == client_id_bits {
+                lobby_state.host_id = lobby_state.players.first().copied().unwrap_or(0);
+            }
+        }
+    }
+}
+
+fn broadcast_entity_snapshots(
+    time: Res<Time>,
+    mut timer: ResMut<SnapshotTimer>,
+    mut subscribers: ResMut<SnapshotSubscribers>,
+    clients: Query<&RemoteId, (With<ClientOf>, With<Connected>)>,
+    replicated: Query<SnapshotQueryData, With<Replicate>>,
+    mut sender: ServerMultiMessageSender,
+    server: Single<&Server>,
+) {
+    subscribers
+        .0
+        .retain(|client_entity, _| clients.get(*client_entity).is_ok());
+
+    if !timer.0.tick(time.delta()).just_finished() || subscribers.0.is_empty() {
+        return;
+    }
+
+    let mut entries: Vec<EntitySnapshotEntry> = replicated.iter().map(describe_entity).collect();
+    entries.sort_by_key(|entry| entry.entity);
+
+    let server = server.into_inner();
+    for (client_entity, filter) in subscribers.0.iter() {
+        let Ok(remote_id) = clients.get(*client_entity) else {
+            continue;
+        };
+
+        let snapshot = EntitySnapshot {
+            entities: entries
+                .iter()
+                .filter_map(|entry| filter_snapshot_entry(entry, filter))
+                .collect(),
+        };
+
+        sender
+            .send::<EntitySnapshot, DebugChannel>(
+                &snapshot,
+                server,
+                &NetworkTarget::Single(remote_id.0),
+            )
+            .unwrap_or_else(|e| {
+                bevy::log::error!("Failed to send EntitySnapshot: {:?}", e);
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SnapshotSettings, filter_snapshot_entry};
+    use shared::protocol::{EntitySnapshotEntry, EntitySnapshotSubscribe};
+
+    fn entry(entity: u64) -> EntitySnapshotEntry {
+        EntitySnapshotEntry {
+            entity,
+            components: vec![
+                ("Name".to_string(), "Player_1".to_string()),
+                ("Position".to_string(), "[0.00, 1.00, 0.00]".to_string()),
+                (
+                    "Health".to_string(),
+                    "Health { current: 100.0 }".to_string(),
+                ),
+            ],
+        }
+    }
+
+    #[test]
+    fn component_filter_is_case_insensitive_and_drops_empty_entities() {
+        let filter = EntitySnapshotSubscribe {
+            component_filter: Some("health".to_string()),
+            entity_filter: None,
+            ..Default::default()
+        };
+
+        let filtered = filter_snapshot_entry(&entry(7), &filter).expect("Health should match");
+        assert_eq!(filtered.components.len(), 1);
+        assert_eq!(filtered.components[0].0, "Health");
+
+        let no_match = EntitySnapshotSubscribe {
+            component_filter: Some("Gun".to_string()),
+            entity_filter: None,
+            ..Default::default()
+        };
+        assert!(filter_snapshot_entry(&entry(7), &no_match).is_none());
+    }
+
+    #[test]
+    fn subscriptions_need_the_inspector_password() {
+        let subscription = |password: &str| EntitySnapshotSubscribe {
+            password: password.to_string(),
+            ..Default::default()
+        };
+        assert!(!SnapshotSettings::default().accepts(&subscription("")));
+
+        let settings = SnapshotSettings {
+            password: Some("letmein".to_string()),
+        };
+        assert!(settings.accepts(&subscription("letmein")));
+        assert!(!settings.accepts(&subscription("letme")));
+        assert!(!settings.accepts(&subscription("")));
+    }
+
+    #[test]
+    fn entity_filter_only_keeps_requested_entity() {
+        let filter = EntitySnapshotSubscribe {
+            component_filter: None,
+            entity_filter: Some(7),
+            ..Default::default()
+        };
+
+        assert_eq!(filter_snapshot_entry(&entry(7), &filter), Some(entry(7)));
+        assert!(filter_snapshot_entry(&entry(8), &filter).is_none());
+    }
+}
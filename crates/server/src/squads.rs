@@ -0,0 +1,256 @@
+use avian3d::prelude::{LinearVelocity, Position};
+use bevy::prelude::{
+    App, Commands, Entity, IntoScheduleConfigs, Name, Plugin, Quat, Query, Res, ResMut, Resource,
+    Update, Vec3, With, Without, debug, in_state,
+};
+use std::collections::HashMap;
+
+use lightyear::prelude::{InterpolationTarget, NetworkTarget, Replicate};
+use shared::bots::BotProfile;
+use shared::clock::{GameClock, ensure_game_clock};
+use shared::components::grenade::{Grenade, GrenadeFuse, grenade_body, throw_velocity};
+use shared::components::health::Health;
+use shared::cover::CoverPoints;
+use shared::inputs::input::PLAYER_CAPSULE_HEIGHT;
+use shared::perception::BotPerception;
+use shared::squads::{
+    COVER_SEARCH_RADIUS, SQUAD_SIZE, SquadBlackboard, SquadMember, SquadRole, cover_candidates,
+    formation_position, grenade_is_safe,
+};
+
+use crate::ServerGameState;
+use crate::perception::update_bot_awareness;
+use crate::visibility::LineOfSight;
+
+/// Support members only pick cover again once their formation position moved this far.
+const SQUAD_REPLAN_DISTANCE: f32 = 2.0;
+
+/// Groups bots into squads that share what they perceive, give each member its position
+/// around the enemy they focus, with support in cover, and take turns with grenades.
+/// Bots go to their position when their behavior tree says so (`ServerBotBehaviorPlugin`). Bots spawned in a
+/// squad (`bots squad` in the admin console) keep it; the others are grouped here.
+pub struct ServerSquadPlugin;
+
+impl Plugin for ServerSquadPlugin {
+    fn build(&self, app: &mut App) {
+        ensure_game_clock(app);
+        app.init_resource::<SquadBlackboards>();
+        app.add_systems(
+            Update,
+            (
+                form_squads,
+                update_squad_blackboards,
+                pick_cover_positions,
+                coordinate_squad_grenades,
+            )
+                .chain()
+                .after(update_bot_awareness)
+                .run_if(in_state(ServerGameState::Playing)),
+        );
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct SquadBlackboards {
+    pub squads: HashMap<u32, SquadBlackboard>,
+    next_squad: u32,
+}
+
+impl SquadBlackboards {
+    /// Start a squad with an empty blackboard and return its id.
+    pub fn new_squad(&mut self) -> u32 {
+        let squad = self.next_squad;
+        self.next_squad += 1;
+        self.squads.insert(squad, SquadBlackboard::default());
+        squad
+    }
+
+    /// Where the squad wants `bot` while it engages: the cover it picked, else its
+    /// formation position around the focus. `None` while the squad engages nobody.
+    pub fn position_for(&self, bot: Entity, member: &SquadMember, center: Vec3) -> Option<Vec3> {
+        let blackboard = self.squads.get(&member.squad)?;
+        let enemy = blackboard.focus()?;
+        let cover = blackboard.cover.get(&bot).map(|&(_, cover)| cover);
+        Some(cover.unwrap_or_else(|| formation_position(member, center, enemy.position)))
+    }
+}
+
+type SquadMemberData<'a> = (Entity, &'a SquadMember, &'a Position, &'a Health);
+
+/// Living squad members' positions, averaged per squad.
+pub fn squad_centers<'a>(
+    members: impl Iterator<Item = (&'a SquadMember, &'a Position, &'a Health)>,
+) -> HashMap<u32, Vec3> {
+    let mut sums: HashMap<u32, (Vec3, f32)> = HashMap::new();
+    for (member, position, health) in members {
+        if health.is_dead {
+            continue;
+        }
+        let sum = sums.entry(member.squad).or_default();
+        sum.0 += position.0;
+        sum.1 += 1.0;
+    }
+    sums.into_iter()
+        .map(|(squad, (sum, count))| (squad, sum / count))
+        .collect()
+}
+
+fn form_squads(
+    mut commands: Commands,
+    mut blackboards: ResMut<SquadBlackboards>,
+    bots: Query<Entity, (With<BotProfile>, Without<SquadMember>)>,
+) {
+    let unassigned: Vec<Entity> = bots.iter().collect();
+    for squad_bots in unassigned.chunks(SQUAD_SIZE) {
+        let squad = blackboards.new_squad();
+        for (index, &bot) in squad_bots.iter().enumerate() {
+            commands.entity(bot).insert(SquadMember::new(squad, index));
+        }
+        debug!("Formed squad {} of {} bots", squad, squad_bots.len());
+    }
+}
+
+/// Members share what they saw or heard this frame with their squad, which then settles
+/// on the enemy it focuses.
+fn update_squad_blackboards(
+    clock: Res<GameClock>,
+    mut blackboards: ResMut<SquadBlackboards>,
+    members: Query<SquadMemberData>,
+    perceptions: Query<&BotPerception>,
+) {
+    let now = clock.elapsed_secs();
+    for (bot, member, _, health) in members.iter() {
+        if health.is_dead {
+            continue;
+        }
+        let Ok(perception) = perceptions.get(bot) else {
+            continue;
+        };
+        let blackboard = blackboards.squads.entry(member.squad).or_default();
+        for known in perception.sensed_at(now) {
+            blackboard.spot(known.enemy, known.position, now);
+        }
+    }
+
+    let squads = squad_centers(members.iter().map(|(_, m, p, h)| (m, p, h)));
+    blackboards
+        .squads
+        .retain(|squad, _| squads.contains_key(squad));
+    for (squad, blackboard) in blackboards.squads.iter_mut() {
+        blackboard.forget_stale(now);
+        blackboard.update_focus(squads[squad]);
+    }
+}
+
+/// Support members pick a spot near their formation position that something stands
+/// between and the squad's focus: a baked cover point facing it if there is one, else a
+/// spot they can walk to in a straight line from the formation position. Spots are only
+/// picked again once the formation position moved.
+pub fn pick_cover_positions(
+    mut blackboards: ResMut<SquadBlackboards>,
+    line_of_sight: LineOfSight,
+    cover_points: Option<Res<CoverPoints>>,
+    members: Query<SquadMemberData>,
+) {
+    let eye = Vec3::Y * (PLAYER_CAPSULE_HEIGHT * 0.5);
+    let centers = squad_centers(members.iter().map(|(_, m, p, h)| (m, p, h)));
+    for blackboard in blackboards.squads.values_mut() {
+        if blackboard.focus().is_none() {
+            blackboard.cover.clear();
+        }
+        blackboard
+            .cover
+            .retain(|bot, _| members.get(*bot).is_ok_and(|(.., health)| !health.is_dead));
+    }
+
+    for (bot, member, _, health) in members.iter() {
+        if health.is_dead || member.role != SquadRole::Support {
+            continue;
+        }
+        let (Some(&center), Some(blackboard)) = (
+            centers.get(&member.squad),
+            blackboards.squads.get_mut(&member.squad),
+        ) else {
+            continue;
+        };
+        let Some(enemy) = blackboard.focus() else {
+            continue;
+        };
+        let spot = formation_position(member, center, enemy.position);
+        let picked = blackboard.cover.get(&bot);
+        if picked.is_some_and(|(picked_for, _)| picked_for.distance(spot) <= SQUAD_REPLAN_DISTANCE)
+        {
+            continue;
+        }
+
+        let covered = |candidate: Vec3| {
+            line_of_sight.occluders(bot, candidate + eye, enemy.enemy, enemy.position + eye, 1) > 0
+        };
+        let baked = cover_points.as_deref().and_then(|cover_points| {
+            cover_points
+                .nearest_against(spot, enemy.position, COVER_SEARCH_RADIUS, |point| {
+                    covered(point.position)
+                })
+                .map(|point| point.position)
+        });
+        let cover = baked
+            .or_else(|| {
+                cover_candidates(spot).find(|&candidate| {
+                    line_of_sight.occluders(bot, spot + eye, bot, candidate + eye, 1) == 0
+                        && covered(candidate)
+                })
+            })
+            .unwrap_or(spot);
+        blackboard.cover.insert(bot, (spot, cover));
+    }
+}
+
+/// Support members throw the squad's grenade at its focus, one per squad cooldown, and
+/// only where the blast will not catch a squadmate.
+fn coordinate_squad_grenades(
+    mut commands: Commands,
+    clock: Res<GameClock>,
+    mut blackboards: ResMut<SquadBlackboards>,
+    members: Query<SquadMemberData>,
+    velocities: Query<&LinearVelocity>,
+) {
+    let now = clock.elapsed_secs();
+    for (bot, member, position, health) in members.iter() {
+        if health.is_dead || member.role != SquadRole::Support {
+            continue;
+        }
+        let Some(blackboard) = blackboards.squads.get_mut(&member.squad) else {
+            continue;
+        };
+        let Some(enemy) = blackboard.focus().map(|known| known.position) else {
+            continue;
+        };
+
+        let squadmates: Vec<Vec3> = members
+            .iter()
+            .filter(|(other, other_member, _, other_health)| {
+                *other != bot && other_member.squad == member.squad && !other_health.is_dead
+            })
+            .map(|(_, _, other_position, _)| other_position.0)
+            .collect();
+        if !grenade_is_safe(position.0, enemy, &squadmates) || !blackboard.try_claim_grenade(now) {
+            continue;
+        }
+
+        let aim = (enemy - position.0).with_y(0.0).normalize_or(Vec3::NEG_Z);
+        let rotation = Quat::from_rotation_arc(Vec3::NEG_Z, aim);
+        let carrier_velocity = velocities
+            .get(bot)
+            .map_or(Vec3::ZERO, |velocity| velocity.0);
+        let origin = position.0 + Vec3::Y * (PLAYER_CAPSULE_HEIGHT * 0.5) + aim * 0.8;
+        commands.spawn((
+            Name::new("Grenade"),
+            Grenade,
+            GrenadeFuse::new(bot),
+            grenade_body(origin, throw_velocity(rotation, carrier_velocity)),
+            Replicate::to_clients(NetworkTarget::All),
+            InterpolationTarget::to_clients(NetworkTarget::All),
+        ));
+        debug!("Squad {} support {:?} threw a grenade", member.squad, bot);
+    }
+}
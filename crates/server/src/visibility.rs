@@ -0,0 +1,217 @@
+use avian3d::prelude::{SpatialQuery, SpatialQueryFilter};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::{App, Dir3, Entity, First, Plugin, Res, ResMut, Resource, Time, Vec3};
+use std::collections::HashMap;
+
+/// How far either end of a sight line may move before a cached answer is cast again.
+const LOS_POSITION_TOLERANCE: f32 = 0.25;
+/// Cached answers are dropped after this long even if nothing moved, so doors and
+/// destroyed cover are picked up.
+const LOS_MAX_AGE_SECS: f32 = 0.1;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct CachedSightLine {
+    from: Vec3,
+    to: Vec3,
+    visible: bool,
+    cast_at_secs: f32,
+}
+
+/// Line-of-sight answers of recent ticks, keyed by the pair of entities looking at each
+/// other. Sight lines are symmetric, so `(a, b)` and `(b, a)` share an entry.
+#[derive(Resource, Default, Debug)]
+pub struct LineOfSightCache {
+    lines: HashMap<(Entity, Entity), CachedSightLine>,
+}
+
+impl LineOfSightCache {
+    fn key(a: Entity, from: Vec3, b: Entity, to: Vec3) -> ((Entity, Entity), Vec3, Vec3) {
+        if a <= b {
+            ((a, b), from, to)
+        } else {
+            ((b, a), to, from)
+        }
+    }
+
+    /// The cached answer for this pair, if both ends are still close to where it was cast.
+    pub fn get(&self, a: Entity, from: Vec3, b: Entity, to: Vec3) -> Option<bool> {
+        let (key, from, to) = Self::key(a, from, b, to);
+        let line = self.lines.get(&key)?;
+        (line.from.distance(from) <= LOS_POSITION_TOLERANCE
+            && line.to.distance(to) <= LOS_POSITION_TOLERANCE)
+            .then_some(line.visible)
+    }
+
+    pub fn insert(&mut self, a: Entity, from: Vec3, b: Entity, to: Vec3, visible: bool, now: f32) {
+        let (key, from, to) = Self::key(a, from, b, to);
+        self.lines.insert(
+            key,
+            CachedSightLine {
+                from,
+                to,
+                visible,
+                cast_at_secs: now,
+            },
+        );
+    }
+
+    fn forget_older_than(&mut self, now: f32, max_age: f32) {
+        self.lines
+            .retain(|_, line| now - line.cast_at_secs <= max_age);
+    }
+}
+
+/// Cache effectiveness, for the admin console.
+#[derive(Resource, Clone, Debug, Default, PartialEq)]
+pub struct LineOfSightMetrics {
+    pub hits: u64,
+    pub misses: u64,
+    pub last_tick_hits: u32,
+    pub last_tick_misses: u32,
+    tick_hits: u32,
+    tick_misses: u32,
+}
+
+impl LineOfSightMetrics {
+    /// Share of sight checks answered from the cache, 0 before any check.
+    pub fn hit_rate(&self) -> f32 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f32 / total as f32
+        }
+    }
+
+    fn record(&mut self, hit: bool) {
+        if hit {
+            self.hits += 1;
+            self.tick_hits += 1;
+        } else {
+            self.misses += 1;
+            self.tick_misses += 1;
+        }
+    }
+
+    fn end_tick(&mut self) {
+        self.last_tick_hits = std::mem::take(&mut self.tick_hits);
+        self.last_tick_misses = std::mem::take(&mut self.tick_misses);
+    }
+}
+
+/// Line-of-sight checks for server systems (bot vision, fog of war, hit validation).
+/// Repeated checks between the same entities reuse the answer instead of casting again.
+#[derive(SystemParam)]
+pub struct LineOfSight<'w, 's> {
+    spatial_query: SpatialQuery<'w, 's>,
+    cache: ResMut<'w, LineOfSightCache>,
+    metrics: ResMut<'w, LineOfSightMetrics>,
+    time: Res<'w, Time>,
+}
+
+impl LineOfSight<'_, '_> {
+    /// Whether `viewer` at `from` can see `target` at `to`: nothing but the two of them in
+    /// between.
+    pub fn can_see(&mut self, viewer: Entity, from: Vec3, target: Entity, to: Vec3) -> bool {
+        if let Some(visible) = self.cache.get(viewer, from, target, to) {
+            self.metrics.record(true);
+            return visible;
+        }
+        self.metrics.record(false);
+
+        let visible = match Dir3::new(to - from) {
+            Ok(direction) => {
+                let filter = SpatialQueryFilter::default().with_excluded_entities([viewer, target]);
+                self.spatial_query
+                    .cast_ray(from, direction, from.distance(to), true, &filter)
+                    .is_none()
+            }
+            // Same spot: nothing can be in between.
+            Err(_) => true,
+        };
+        let now = self.time.elapsed_secs();
+        self.cache.insert(viewer, from, target, to, visible, now);
+        visible
+    }
+
+    /// Obstacles between `listener` at `from` and `source` at `to`, counting at most
+    /// `max`. Not cached: hearing checks are rarer than sight checks.
+    pub fn occluders(
+        &self,
+        listener: Entity,
+        from: Vec3,
+        source: Entity,
+        to: Vec3,
+        max: u32,
+    ) -> usize {
+        let Ok(direction) = Dir3::new(to - from) else {
+            return 0;
+        };
+        let filter = SpatialQueryFilter::default().with_excluded_entities([listener, source]);
+        self.spatial_query
+            .ray_hits(from, direction, from.distance(to), max, true, &filter)
+            .len()
+    }
+}
+
+/// Shared line-of-sight cache for server systems, aged out at the start of every tick.
+pub struct ServerVisibilityPlugin;
+
+impl Plugin for ServerVisibilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LineOfSightCache>();
+        app.init_resource::<LineOfSightMetrics>();
+        app.add_systems(First, age_line_of_sight_cache);
+    }
+}
+
+fn age_line_of_sight_cache(
+    time: Res<Time>,
+    mut cache: ResMut<LineOfSightCache>,
+    mut metrics: ResMut<LineOfSightMetrics>,
+) {
+    cache.forget_older_than(time.elapsed_secs(), LOS_MAX_AGE_SECS);
+    metrics.end_tick();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LineOfSightCache, LineOfSightMetrics};
+    use bevy::prelude::{Vec3, World};
+
+    #[test]
+    fn cached_sight_lines_are_shared_by_both_ends_within_tolerance() {
+        let mut cache = LineOfSightCache::default();
+        let mut world = World::new();
+        let bot = world.spawn_empty().id();
+        let player = world.spawn_empty().id();
+        let eye = Vec3::new(0.0, 1.5, 0.0);
+        let target = Vec3::new(10.0, 1.5, 0.0);
+        cache.insert(bot, eye, player, target, false, 0.0);
+
+        assert_eq!(cache.get(bot, eye, player, target), Some(false));
+        assert_eq!(cache.get(player, target, bot, eye), Some(false));
+        assert_eq!(
+            cache.get(bot, eye + Vec3::X * 0.1, player, target),
+            Some(false)
+        );
+        assert_eq!(cache.get(bot, eye + Vec3::X, player, target), None);
+
+        cache.forget_older_than(0.5, 0.1);
+        assert_eq!(cache.get(bot, eye, player, target), None);
+    }
+
+    #[test]
+    fn hit_rate_counts_every_check() {
+        let mut metrics = LineOfSightMetrics::default();
+        assert_eq!(metrics.hit_rate(), 0.0);
+        metrics.record(false);
+        metrics.record(true);
+        metrics.record(true);
+        metrics.record(true);
+        metrics.end_tick();
+
+        assert_eq!(metrics.hit_rate(), 0.75);
+        assert_eq!((metrics.last_tick_hits, metrics.last_tick_misses), (3, 1));
+    }
+}
@@ -0,0 +1,123 @@
+use avian3d::prelude::Position;
+use bevy::prelude::{
+    App, IntoScheduleConfigs, Plugin, Query, Single, Update, Vec3, With, in_state, warn,
+};
+use lightyear::prelude::{
+    Connected, MessageReceiver, NetworkTarget, PeerId, RemoteId, Server, ServerMultiMessageSender,
+    server::ClientOf,
+};
+use shared::components::health::Health;
+use shared::protocol::{
+    MAX_VOICE_FRAME_BYTES, PlayerId, VOICE_PROXIMITY_RANGE, VoiceChannel, VoiceFrame,
+};
+
+use crate::ServerGameState;
+
+/// Proximity voice chat: relays every client's voice frames to the living players around
+/// their character. Clients without a living character (spectators, dead players) are not
+/// heard.
+pub struct ServerVoicePlugin;
+
+impl Plugin for ServerVoicePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            relay_voice_frames.run_if(in_state(ServerGameState::Playing)),
+        );
+    }
+}
+
+/// Living players other than `speaker` within `range` of `origin`.
+pub fn proximity_listeners<'a>(
+    speaker: PeerId,
+    origin: Vec3,
+    range: f32,
+    players: impl Iterator<Item = (PeerId, Vec3, &'a Health)>,
+) -> Vec<PeerId> {
+    players
+        .filter(|(peer, position, health)| {
+            *peer != speaker && !health.is_dead && position.distance(origin) <= range
+        })
+        .map(|(peer, _, _)| peer)
+        .collect()
+}
+
+fn relay_voice_frames(
+    mut receivers: Query<
+        (&RemoteId, &mut MessageReceiver<VoiceFrame>),
+        (With<ClientOf>, With<Connected>),
+    >,
+    players: Query<(&PlayerId, &Position, &Health)>,
+    mut sender: ServerMultiMessageSender,
+    server: Single<&Server>,
+) {
+    let server = server.into_inner();
+
+    for (remote_id, mut receiver) in receivers.iter_mut() {
+        let speaker = remote_id.0;
+        let origin = players
+            .iter()
+            .find(|(player_id, _, health)| player_id.0 == speaker && !health.is_dead)
+            .map(|(_, position, _)| position.0);
+
+        for mut frame in receiver.receive() {
+            let Some(origin) = origin else {
+                continue;
+            };
+            if frame.data.len() > MAX_VOICE_FRAME_BYTES {
+                warn!(
+                    "Dropping {} byte voice frame from client {}",
+                    frame.data.len(),
+                    speaker
+                );
+                continue;
+            }
+
+            let listeners = proximity_listeners(
+                speaker,
+                origin,
+                VOICE_PROXIMITY_RANGE,
+                players
+                    .iter()
+                    .map(|(player_id, position, health)| (player_id.0, position.0, health)),
+            );
+            if listeners.is_empty() {
+                continue;
+            }
+
+            frame.speaker = speaker.to_bits();
+            sender
+                .send::<VoiceFrame, VoiceChannel>(&frame, server, &NetworkTarget::Only(listeners))
+                .unwrap_or_else(|e| {
+                    bevy::log::error!("Failed to relay voice frame: {:?}", e);
+                });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::proximity_listeners;
+    use bevy::prelude::Vec3;
+    use lightyear::prelude::PeerId;
+    use shared::components::health::Health;
+
+    #[test]
+    fn only_living_players_in_range_hear_the_speaker() {
+        let alive = Health::basic();
+        let dead = Health {
+            is_dead: true,
+            ..Health::basic()
+        };
+        let players = [
+            (PeerId::Netcode(1), Vec3::ZERO, &alive),
+            (PeerId::Netcode(2), Vec3::new(10.0, 0.0, 0.0), &alive),
+            (PeerId::Netcode(3), Vec3::new(0.0, 0.0, 50.0), &alive),
+            (PeerId::Netcode(4), Vec3::new(5.0, 0.0, 0.0), &dead),
+        ];
+
+        let listeners =
+            proximity_listeners(PeerId::Netcode(1), Vec3::ZERO, 30.0, players.into_iter());
+        assert_eq!(listeners, vec![PeerId::Netcode(2)]);
+    }
+}
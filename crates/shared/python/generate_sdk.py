@@ -0,0 +1,438 @@
+"""
+Generate the `yolo_protocol` Python package from the protocol manifest.
+
+The manifest (`cargo run -- manifest`) lists every channel, message and input
+the protocol registers and the serde types they are made of. This script turns
+it into `yolo_protocol/messages.py`: a dataclass or enum per type, each with
+its bincode codec, and tables giving the network index, channel and direction
+of every message. The rest of the package (codec, netcode, lightyear packets,
+asyncio client) is hand-written and does not depend on the protocol.
+
+    cargo run -- manifest | python3 crates/shared/python/generate_sdk.py
+    python3 crates/shared/python/generate_sdk.py manifest.json -o sdk/
+
+Regenerate after any protocol change: `PROTOCOL_HASH` is the hash of the exact
+manifest, and servers refuse clients whose hash differs.
+"""
+
+import argparse
+import json
+import keyword
+import shutil
+import sys
+from pathlib import Path
+from typing import Dict, List, Optional, Tuple
+
+PACKAGE_DIR = Path(__file__).resolve().parent / "yolo_protocol"
+
+# Rust primitives, as (codec, annotation).
+PRIMITIVES: Dict[str, Tuple[str, str]] = {
+    "bool": ("bincode.Bool()", "bool"),
+    "u8": ("bincode.UInt(8)", "int"),
+    "u16": ("bincode.UInt(16)", "int"),
+    "u32": ("bincode.UInt(32)", "int"),
+    "u64": ("bincode.UInt(64)", "int"),
+    "u128": ("bincode.UInt(128)", "int"),
+    # Serde writes `usize` and `isize` as 64-bit integers.
+    "usize": ("bincode.UInt(64)", "int"),
+    "i8": ("bincode.SInt(8)", "int"),
+    "i16": ("bincode.SInt(16)", "int"),
+    "i32": ("bincode.SInt(32)", "int"),
+    "i64": ("bincode.SInt(64)", "int"),
+    "i128": ("bincode.SInt(128)", "int"),
+    "isize": ("bincode.SInt(64)", "int"),
+    "f32": ('bincode.Float("<f")', "float"),
+    "f64": ('bincode.Float("<d")', "float"),
+    "String": ("bincode.Str()", "str"),
+    # Bevy writes entities as their 64-bit `Entity::to_bits`.
+    "Entity": ("bincode.UInt(64)", "int"),
+}
+
+# Glam vectors and quaternions serialize as tuples of f32.
+VECTORS = {"Vec2": 2, "Vec3": 3, "Vec4": 4, "Quat": 4}
+
+SEQUENCES = {"Vec", "VecDeque", "HashSet", "BTreeSet"}
+MAPS = {"HashMap", "BTreeMap"}
+# Serde encodes these as the value they hold.
+WRAPPERS = {"Box", "Arc", "Rc"}
+
+# A parsed Rust type: a name with its generic arguments, a tuple (name
+# "()"), or an array (name "[]" with the item and its length).
+TypeNode = Tuple[str, List["TypeNode"], Optional[int]]
+
+
+def fnv1a_64(data: bytes) -> int:
+    """
+    Hash bytes the way `shared::manifest::fnv1a_64` does, giving the
+    protocol id the server expects.
+    """
+    value = 0xCBF29CE484222325
+    for byte in data:
+        value ^= byte
+        value = (value * 0x100000001B3) & 0xFFFFFFFFFFFFFFFF
+    return value
+
+
+def tokenize(rust_type: str) -> List[str]:
+    """Split a Rust type into identifiers and punctuation."""
+    tokens: List[str] = []
+    word = ""
+    for char in rust_type:
+        if char.isalnum() or char == "_":
+            word += char
+            continue
+        if word:
+            tokens.append(word)
+            word = ""
+        if char == ":" and tokens and tokens[-1] == ":":
+            tokens[-1] = "::"
+        elif not char.isspace():
+            tokens.append(char)
+    if word:
+        tokens.append(word)
+    return tokens
+
+
+def parse_type(rust_type: str) -> TypeNode:
+    """Parse a type as the manifest writes it, such as `Vec<(u8, Team)>`."""
+    tokens = tokenize(rust_type)
+    position = 0
+
+    def parse() -> TypeNode:
+        nonlocal position
+        token = tokens[position]
+        position += 1
+        if token == "(":
+            items = []
+            while tokens[position] != ")":
+                items.append(parse())
+                if tokens[position] == ",":
+                    position += 1
+            position += 1
+            return ("()", items, None)
+        if token == "[":
+            item = parse()
+            length = None
+            if tokens[position] == ";":
+                length = int(tokens[position + 1])
+                position += 2
+            position += 1
+            return ("[]", [item], length)
+        # Only the last segment of a path names the type.
+        while position < len(tokens) and tokens[position] == "::":
+            token = tokens[position + 1]
+            position += 2
+        args = []
+        if position < len(tokens) and tokens[position] == "<":
+            position += 1
+            while tokens[position] != ">":
+                args.append(parse())
+                if tokens[position] == ",":
+                    position += 1
+            position += 1
+        return (token, args, None)
+
+    node = parse()
+    if position != len(tokens):
+        raise ValueError(f"unexpected tokens in {rust_type!r}")
+    return node
+
+
+class Generator:
+    """Writes the Python source of the types and tables of a manifest."""
+
+    def __init__(self, manifest: dict) -> None:
+        """Index the manifest's types by name."""
+        self.manifest = manifest
+        self.types: Dict[str, dict] = {t["name"]: t for t in manifest["types"]}
+        self.unsupported: List[str] = []
+
+    def codec(self, node: TypeNode) -> str:
+        """Python expression building the codec of a parsed type."""
+        name, args, length = node
+        if name == "()":
+            if not args:
+                return "bincode.Unit()"
+            items = ", ".join(self.codec(arg) for arg in args)
+            return f"bincode.Tuple_([{items}])"
+        if name == "[]":
+            if length is None:
+                return f"bincode.Seq({self.codec(args[0])})"
+            return f"bincode.Tuple_([{self.codec(args[0])}] * {length})"
+        if name in PRIMITIVES:
+            return PRIMITIVES[name][0]
+        if name in VECTORS:
+            return f'bincode.Tuple_([bincode.Float("<f")] * {VECTORS[name]})'
+        if name == "Option" and len(args) == 1:
+            return f"bincode.Opt({self.codec(args[0])})"
+        if name in SEQUENCES and len(args) == 1:
+            return f"bincode.Seq({self.codec(args[0])})"
+        if name in MAPS and len(args) == 2:
+            key, value = (self.codec(arg) for arg in args)
+            return f"bincode.Map({key}, {value})"
+        if name in WRAPPERS and len(args) == 1:
+            return self.codec(args[0])
+        if name in self.types:
+            return f'bincode.Named(CODECS, "{name}")'
+        if name not in self.unsupported:
+            self.unsupported.append(name)
+        return f'bincode.Unsupported("{name}")'
+
+    def annotation(self, node: TypeNode) -> str:
+        """Python type hint of a parsed type."""
+        name, args, length = node
+        if name == "()":
+            if not args:
+                return "None"
+            items = ", ".join(self.annotation(arg) for arg in args)
+            return f"Tuple[{items}]"
+        if name == "[]":
+            return f"List[{self.annotation(args[0])}]"
+        if name in PRIMITIVES:
+            return PRIMITIVES[name][1]
+        if name in VECTORS:
+            return f"Tuple[{', '.join(['float'] * VECTORS[name])}]"
+        if name == "Option" and len(args) == 1:
+            return f"Optional[{self.annotation(args[0])}]"
+        if name in SEQUENCES and len(args) == 1:
+            return f"List[{self.annotation(args[0])}]"
+        if name in MAPS and len(args) == 2:
+            key, value = (self.annotation(arg) for arg in args)
+            return f"Dict[{key}, {value}]"
+        if name in WRAPPERS and len(args) == 1:
+            return self.annotation(args[0])
+        if name in self.types:
+            return f'"{name}"'
+        return "Any"
+
+    @staticmethod
+    def attribute(field_name: str) -> str:
+        """Python attribute for a Rust field; tuple fields become `_0`..."""
+        if field_name.isdigit() or keyword.iskeyword(field_name):
+            return f"_{field_name}"
+        return field_name
+
+    def dataclass(self, name: str, fields: List[dict], doc: str) -> str:
+        """Source of a dataclass with the given Rust fields."""
+        lines = ["@dataclass", f"class {name}:", f'    """{doc}"""', ""]
+        for field in fields:
+            hint = self.annotation(parse_type(field["type"]))
+            lines.append(f"    {self.attribute(field['name'])}: {hint}")
+        if not fields:
+            lines.pop()
+        return "\n".join(lines)
+
+    def struct_codec(self, cls: str, fields: List[dict]) -> str:
+        """Python expression building the codec of a dataclass."""
+        pairs = [
+            f'("{self.attribute(field["name"])}", '
+            f"{self.codec(parse_type(field['type']))})"
+            for field in fields
+        ]
+        return f"bincode.Struct({cls}, [{', '.join(pairs)}])"
+
+    def type_source(self, rust: dict) -> Tuple[str, str]:
+        """Source of a manifest type and of the statement registering its
+        codec in `CODECS`."""
+        name, kind = rust["name"], rust["kind"]
+        if kind == "struct":
+            source = self.dataclass(name, rust["fields"], f"`{name}`.")
+            codec = self.struct_codec(name, rust["fields"])
+            return source, f'CODECS["{name}"] = {codec}'
+        if kind == "alias":
+            # Written after the other types, so it can name them unquoted.
+            node = parse_type(rust["type"])
+            source = f"{name} = {self.annotation(node).replace(chr(34), '')}"
+            return source, f'CODECS["{name}"] = {self.codec(node)}'
+        variants = rust["variants"]
+        if all(not variant["fields"] for variant in variants):
+            lines = [f"class {name}(Enum):", f'    """`{name}`."""', ""]
+            lines += [
+                f'    {self.attribute(variant["name"])} = "{variant["name"]}"'
+                for variant in variants
+            ]
+            return "\n".join(lines), (
+                f'CODECS["{name}"] = bincode.UnitEnum({name})'
+            )
+        # One dataclass per variant, named after the enum and the variant.
+        classes = [
+            self.dataclass(
+                f"{name}{variant['name']}",
+                variant["fields"],
+                f"`{name}::{variant['name']}`.",
+            )
+            for variant in variants
+        ]
+        union = ", ".join(f"{name}{v['name']}" for v in variants)
+        classes.append(f"{name} = Union[{union}]")
+        codecs = ", ".join(
+            self.struct_codec(f"{name}{v['name']}", v["fields"])
+            for v in variants
+        )
+        return "\n\n\n".join(classes), (
+            f'CODECS["{name}"] = bincode.DataEnum([{codecs}])'
+        )
+
+    def module(self, protocol_hash: int) -> str:
+        """The whole `messages` module."""
+        sources, codecs = [], []
+        rust_types = sorted(
+            self.manifest["types"], key=lambda rust: rust["kind"] == "alias"
+        )
+        for rust in rust_types:
+            source, codec = self.type_source(rust)
+            sources.append(source)
+            codecs.append(codec)
+
+        channels = [
+            f'    ChannelInfo("{c["name"]}", {index}, "{c["mode"]}", '
+            f'"{c["direction"]}"),'
+            for index, c in enumerate(self.manifest["channels"])
+        ]
+        messages = []
+        for index, message in enumerate(self.manifest["messages"]):
+            name = message["name"]
+            if name in self.types:
+                cls, codec = name, f'bincode.Named(CODECS, "{name}")'
+            else:
+                cls, codec = "None", self.codec((name, [], None))
+            messages.append(
+                f'    MessageInfo("{name}", {index}, '
+                f'"{message["direction"]}", {cls}, {codec}),'
+            )
+        protocols = ", ".join(f'"{p}"' for p in self.manifest["protocols"])
+        return TEMPLATE.format(
+            protocol_hash=f"0x{protocol_hash:016X}",
+            protocols=protocols,
+            types="\n\n\n".join(sources),
+            codecs="\n".join(codecs),
+            channels="\n".join(channels),
+            messages="\n".join(messages),
+        )
+
+
+TEMPLATE = '''"""
+Protocol messages, generated by `generate_sdk.py` from the protocol manifest.
+Do not edit: regenerate after changing the protocol.
+"""
+
+from dataclasses import dataclass
+from enum import Enum
+from typing import Any, Dict, List, Optional, Tuple, Union
+
+from . import bincode
+
+# Netcode protocol id: the hash of the manifest this module was built from.
+PROTOCOL_HASH = {protocol_hash}
+PROTOCOLS = [{protocols}]
+
+# Codec of every manifest type, by Rust name.
+CODECS: Dict[str, bincode.Codec] = {{}}
+
+
+{types}
+
+
+{codecs}
+
+
+@dataclass(frozen=True)
+class ChannelInfo:
+    """A registered channel; `index` is its registration order."""
+
+    name: str
+    index: int
+    mode: str
+    direction: str
+
+
+@dataclass(frozen=True)
+class MessageInfo:
+    """A registered message; `index` is its registration order."""
+
+    name: str
+    index: int
+    direction: str
+    cls: Any
+    codec: bincode.Codec
+
+
+CHANNELS = [
+{channels}
+]
+
+MESSAGES = [
+{messages}
+]
+
+
+def message_info(cls: type) -> MessageInfo:
+    """The registration of the message class `cls`."""
+    for info in MESSAGES:
+        if info.cls is cls:
+            return info
+    raise KeyError(f"{{cls.__name__}} is not a registered message")
+
+
+def channel_info(name: str) -> ChannelInfo:
+    """The registration of the channel called `name`."""
+    for info in CHANNELS:
+        if info.name == name:
+            return info
+    raise KeyError(f"{{name}} is not a registered channel")
+'''
+
+
+def generate(manifest_text: str, package_dir: Path) -> List[str]:
+    """
+    Write `messages.py` into `package_dir` from the manifest JSON, returning
+    the Rust types the generated codecs cannot encode.
+    """
+    generator = Generator(json.loads(manifest_text))
+    source = generator.module(fnv1a_64(manifest_text.encode("utf-8")))
+    (package_dir / "messages.py").write_text(source, encoding="utf-8")
+    return generator.unsupported
+
+
+def main(argv: Optional[List[str]] = None) -> int:
+    """Command line entry point."""
+    parser = argparse.ArgumentParser(description=__doc__.split("\n\n")[0])
+    parser.add_argument(
+        "manifest",
+        nargs="?",
+        default="-",
+        help="manifest JSON file, or - for standard input (default)",
+    )
+    parser.add_argument(
+        "-o",
+        "--out",
+        type=Path,
+        help="copy the package into this directory instead of generating it "
+        "in place",
+    )
+    args = parser.parse_args(argv)
+
+    if args.manifest == "-":
+        # Bytes as they came, so the hash matches the server's.
+        manifest_text = sys.stdin.buffer.read().decode("utf-8")
+    else:
+        manifest_text = Path(args.manifest).read_bytes().decode("utf-8")
+
+    package_dir = PACKAGE_DIR
+    if args.out is not None:
+        package_dir = args.out / PACKAGE_DIR.name
+        shutil.copytree(
+            PACKAGE_DIR,
+            package_dir,
+            dirs_exist_ok=True,
+            ignore=shutil.ignore_patterns("__pycache__", "messages.py"),
+        )
+    unsupported = generate(manifest_text, package_dir)
+    for name in unsupported:
+        print(f"warning: no encoding for {name}", file=sys.stderr)
+    print(f"Generated {package_dir / 'messages.py'}", file=sys.stderr)
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
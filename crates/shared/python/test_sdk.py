@@ -0,0 +1,299 @@
+"""
+Tests of the Python SDK: the generated messages encode exactly like the Rust
+side (the expected bytes are what `bincode::serde::encode_to_vec` gives with
+the standard configuration), and the netcode client completes a handshake
+with a stand-in server.
+
+    python3 -m unittest discover crates/shared/python
+"""
+
+import asyncio
+import importlib
+import struct
+import sys
+import tempfile
+import unittest
+from pathlib import Path
+
+from cryptography.hazmat.primitives.ciphers.aead import ChaCha20Poly1305
+
+import generate_sdk
+
+# A manifest in the format `launcher manifest` prints, covering every kind of type.
+MANIFEST = """{
+  "protocols": ["core"],
+  "inputs": [
+    {"name": "PlayerAction"}
+  ],
+  "channels": [
+    {"name": "LobbyControlChannel", "mode": "OrderedReliable", \
+"direction": "Bidirectional"}
+  ],
+  "components": [],
+  "messages": [
+    {"name": "KillFeedEvent", "direction": "ServerToClient"},
+    {"name": "TeamSelectRequest", "direction": "ClientToServer"},
+    {"name": "LobbyRoster", "direction": "ServerToClient"}
+  ],
+  "types": [
+    {"name": "KillFeedEvent", "kind": "struct", "fields": [\
+{"name": "killer", "type": "Option<String>"}, \
+{"name": "victim", "type": "String"}, \
+{"name": "headshot", "type": "bool"}]},
+    {"name": "TeamSelectRequest", "kind": "struct", "fields": [\
+{"name": "team", "type": "Team"}]},
+    {"name": "LobbyRoster", "kind": "struct", "fields": [\
+{"name": "teams", "type": "TeamAssignments"}, \
+{"name": "spawn", "type": "Vec3"}, \
+{"name": "shape", "type": "Shape"}, \
+{"name": "offset", "type": "i32"}]},
+    {"name": "PlayerAction", "kind": "enum", "variants": [\
+{"name": "Move", "fields": []}, {"name": "Jump", "fields": []}]},
+    {"name": "Team", "kind": "enum", "variants": [\
+{"name": "Red", "fields": []}, {"name": "Blue", "fields": []}]},
+    {"name": "TeamAssignments", "kind": "alias", \
+"type": "HashMap<u64, Team>"},
+    {"name": "Shape", "kind": "enum", "variants": [\
+{"name": "Point", "fields": []}, \
+{"name": "Box", "fields": [{"name": "0", "type": "f32"}]}]}
+  ]
+}
+"""
+
+
+def setUpModule() -> None:
+    """Generate the package from `MANIFEST` and import it from there."""
+    global OUT, bincode, lightyear, messages, netcode, client
+    OUT = tempfile.TemporaryDirectory()
+    generate_sdk.main(["-o", OUT.name, str(_write_manifest())])
+    sys.path.insert(0, OUT.name)
+    bincode = importlib.import_module("yolo_protocol.bincode")
+    lightyear = importlib.import_module("yolo_protocol.lightyear")
+    messages = importlib.import_module("yolo_protocol.messages")
+    netcode = importlib.import_module("yolo_protocol.netcode")
+    client = importlib.import_module("yolo_protocol.client")
+
+
+def tearDownModule() -> None:
+    """Forget the generated package."""
+    sys.path.remove(OUT.name)
+    for name in list(sys.modules):
+        if name.startswith("yolo_protocol"):
+            del sys.modules[name]
+    OUT.cleanup()
+
+
+def _write_manifest() -> Path:
+    """Write `MANIFEST` to a file, as `cargo run -- manifest > file` would."""
+    path = Path(tempfile.mkdtemp()) / "manifest.json"
+    path.write_bytes(MANIFEST.encode("utf-8"))
+    return path
+
+
+class CodecTests(unittest.TestCase):
+    """Generated messages encode like the Rust types."""
+
+    def test_varints_switch_width_at_bincode_markers(self) -> None:
+        """Integers below 251 take one byte, larger ones a marker first."""
+        self.assertEqual(bincode.write_varint(250), b"\xfa")
+        self.assertEqual(bincode.write_varint(251), b"\xfb\xfb\x00")
+        self.assertEqual(
+            bincode.write_varint(1 << 16), b"\xfc\x00\x00\x01\x00"
+        )
+        for value in (0, 250, 251, 65535, 65536, 1 << 40):
+            encoded = bincode.write_varint(value)
+            reader = bincode.Reader(encoded)
+            self.assertEqual(bincode.read_varint(reader), value)
+        # Zigzag: -1 is 1, 1 is 2.
+        self.assertEqual(bincode.SInt(32).encode(-1), b"\x01")
+        self.assertEqual(bincode.SInt(32).encode(1), b"\x02")
+
+    def test_messages_round_trip_with_rust_bytes(self) -> None:
+        """Every kind of manifest type encodes to the bytes Rust writes."""
+        kill = messages.KillFeedEvent("a", "b", True)
+        info = messages.message_info(messages.KillFeedEvent)
+        self.assertEqual((info.index, info.direction), (0, "ServerToClient"))
+        self.assertEqual(
+            bincode.encode(info.codec, kill), b"\x01\x01a\x01b\x01"
+        )
+
+        roster = messages.LobbyRoster(
+            teams={7: messages.Team.Blue},
+            spawn=(1.0, 0.0, -2.0),
+            shape=messages.ShapeBox(0.5),
+            offset=-3,
+        )
+        expected = (
+            b"\x01\x07\x01"
+            + struct.pack("<3f", 1.0, 0.0, -2.0)
+            + b"\x01"
+            + struct.pack("<f", 0.5)
+            + b"\x05"
+        )
+        codec = messages.CODECS["LobbyRoster"]
+        self.assertEqual(bincode.encode(codec, roster), expected)
+        self.assertEqual(bincode.decode(codec, expected), roster)
+
+    def test_malformed_bytes_are_decode_errors(self) -> None:
+        """Truncated, trailing or out of range bytes raise `DecodeError`."""
+        codec = messages.CODECS["KillFeedEvent"]
+        for data in (b"", b"\x02", b"\x00\x05ab", b"\x00\x01b\x01\x00"):
+            with self.assertRaises(bincode.DecodeError):
+                bincode.decode(codec, data)
+        with self.assertRaises(bincode.DecodeError):
+            bincode.decode(messages.CODECS["Team"], b"\x02")
+
+    def test_protocol_hash_is_the_manifest_hash(self) -> None:
+        """The generated hash matches `shared::manifest::fnv1a_64`."""
+        self.assertEqual(generate_sdk.fnv1a_64(b""), 0xCBF29CE484222325)
+        self.assertEqual(generate_sdk.fnv1a_64(b"a"), 0xAF63DC4C8601EC8C)
+        self.assertEqual(
+            messages.PROTOCOL_HASH,
+            generate_sdk.fnv1a_64(MANIFEST.encode("utf-8")),
+        )
+
+
+class NetcodeTests(unittest.TestCase):
+    """The netcode client completes a handshake with a stand-in server."""
+
+    def test_hchacha20_matches_the_reference_vector(self) -> None:
+        """Subkey from the XChaCha20 draft, section 2.2.1."""
+        key = bytes(range(32))
+        nonce = bytes.fromhex("000000090000004a0000000031415927")
+        self.assertEqual(
+            netcode.hchacha20(key, nonce).hex(),
+            "82413b4227b27bfed30e42508a877d73"
+            "a0f9e4d58a74a853c12ec41326d3ecdc",
+        )
+
+    def test_client_joins_over_udp(self) -> None:
+        """
+        The server opens the connect token with the development key,
+        challenges the client and accepts its response; the client is then
+        connected and sends keep-alives.
+        """
+        asyncio.run(self._join())
+
+    async def _join(self) -> None:
+        """Run a stand-in server and a client on the loopback."""
+        loop = asyncio.get_running_loop()
+        server = StandInServer()
+        transport, _ = await loop.create_datagram_endpoint(
+            lambda: server, local_addr=("127.0.0.1", 0)
+        )
+        port = transport.get_extra_info("sockname")[1]
+        try:
+            yolo = await client.YoloClient._connect(
+                await client.UdpTransport.open("127.0.0.1", port),
+                "127.0.0.1",
+                port,
+                client_id=42,
+            )
+            self.assertTrue(yolo.connected)
+            self.assertEqual(server.protocol_id, messages.PROTOCOL_HASH)
+            self.assertEqual(server.client_id, 42)
+            self.assertEqual(yolo.netcode.client_index, 0)
+            await asyncio.wait_for(server.keep_alive, 1.0)
+            await yolo.close()
+        finally:
+            transport.close()
+
+
+class StandInServer(asyncio.DatagramProtocol):
+    """Just enough of a netcode server to accept one client."""
+
+    def __init__(self) -> None:
+        """Wait for a connection request."""
+        self.protocol_id = None
+        self.client_id = None
+        self.keys = None
+        self.sequence = 0
+        self.keep_alive = asyncio.get_running_loop().create_future()
+
+    def connection_made(self, transport: asyncio.BaseTransport) -> None:
+        """Keep the socket to answer on."""
+        self.transport = transport
+
+    def _seal(self, packet_type: int, body: bytes) -> bytes:
+        """Seal a packet with the server to client key."""
+        prefix = packet_type | (1 << 4)
+        associated = (
+            netcode.VERSION_INFO
+            + struct.pack("<Q", messages.PROTOCOL_HASH)
+            + bytes([prefix])
+        )
+        nonce = bytes(4) + struct.pack("<Q", self.sequence)
+        cipher = ChaCha20Poly1305(self.keys[1])
+        sealed = cipher.encrypt(nonce, body, associated)
+        packet = bytes([prefix, self.sequence]) + sealed
+        self.sequence += 1
+        return packet
+
+    def _open(self, packet: bytes) -> tuple:
+        """Open a packet sealed with the client to server key."""
+        prefix, width = packet[0], packet[0] >> 4
+        sequence = int.from_bytes(packet[1:1 + width], "little")
+        associated = (
+            netcode.VERSION_INFO
+            + struct.pack("<Q", messages.PROTOCOL_HASH)
+            + bytes([prefix])
+        )
+        body = ChaCha20Poly1305(self.keys[0]).decrypt(
+            bytes(4) + struct.pack("<Q", sequence),
+            packet[1 + width:],
+            associated,
+        )
+        return prefix & 0x0F, body
+
+    def datagram_received(self, data: bytes, addr: tuple) -> None:
+        """Answer the handshake and record keep-alives."""
+        if data[0] == netcode.CONNECTION_REQUEST:
+            self.protocol_id = struct.unpack_from("<Q", data, 14)[0]
+            associated = netcode.VERSION_INFO + data[14:30]
+            private = netcode.xchacha20poly1305_decrypt(
+                netcode.DEV_PRIVATE_KEY, data[30:54], data[54:], associated
+            )
+            self.client_id = struct.unpack_from("<Q", private)[0]
+            # id, timeout, one IPv4 address, then the two keys.
+            keys_at = 8 + 4 + 4 + 7
+            self.keys = (
+                private[keys_at:keys_at + 32],
+                private[keys_at + 32:keys_at + 64],
+            )
+            challenge = struct.pack("<Q", 0) + bytes(300)
+            packet = self._seal(netcode.CONNECTION_CHALLENGE, challenge)
+            self.transport.sendto(packet, addr)
+            return
+        packet_type, body = self._open(data)
+        if packet_type == netcode.CONNECTION_RESPONSE:
+            keep_alive = struct.pack("<II", 0, 8)
+            packet = self._seal(netcode.KEEP_ALIVE, keep_alive)
+            self.transport.sendto(packet, addr)
+        elif packet_type == netcode.KEEP_ALIVE and not self.keep_alive.done():
+            self.keep_alive.set_result(True)
+
+
+class LightyearTests(unittest.TestCase):
+    """Lightyear packets keep messages per channel and ack the server's."""
+
+    def test_packets_round_trip_and_ack(self) -> None:
+        """A packed message comes back out, and received ids are acked."""
+        packets = lightyear.Packets()
+        packets.add_channel(6, True)
+        message = lightyear.encode_message(3, b"\x01")
+        packet = packets.encode(10, [(6, message), (6, message)])
+        header, unpacked = packets.decode(packet, {6: True})
+        self.assertEqual(header.tick, 10)
+        self.assertEqual(unpacked, [(6, message), (6, message)])
+        self.assertEqual(lightyear.decode_message(message), (3, b"\x01"))
+
+        for packet_id in (1, 3):
+            received = lightyear.PacketHeader(0, packet_id, 0, 0, 0).encode()
+            packets.decode(received, {})
+        self.assertEqual(packets.last_received, 3)
+        # Packets 2 and 1 before 3: only 1 arrived.
+        self.assertEqual(packets.received_bits & 0b11, 0b10)
+
+
+if __name__ == "__main__":
+    unittest.main()
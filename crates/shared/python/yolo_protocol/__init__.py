@@ -0,0 +1,7 @@
+"""
+Python SDK for the Yolo network protocol: typed messages, their encoding and
+an asyncio client, for bot scripts and protocol tests.
+
+`messages` is generated from the protocol manifest by `generate_sdk.py`; the
+other modules are hand-written and shared by every protocol version.
+"""
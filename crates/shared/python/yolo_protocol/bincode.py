@@ -0,0 +1,410 @@
+"""
+Encoding of protocol values, byte for byte the way lightyear sends them.
+
+Lightyear serializes messages with bincode 2 and its standard configuration:
+integers wider than a byte are variable-length (see `write_varint`), signed
+ones zigzag-encoded first, floats little-endian, and sequences, maps and
+strings prefixed with their length. Enums start with the variant index.
+
+Codecs are small objects composed by the generated `messages` module, one per
+type in the manifest, so that every message class knows how to encode itself.
+"""
+
+import struct
+from typing import Any, Callable, Dict, List, Sequence, Tuple
+
+# First byte of a varint telling how many bytes follow.
+U16_MARKER = 251
+U32_MARKER = 252
+U64_MARKER = 253
+U128_MARKER = 254
+
+
+class DecodeError(ValueError):
+    """Raised when bytes do not hold a value of the expected type."""
+
+
+class Reader:
+    """Cursor over bytes being decoded."""
+
+    def __init__(self, data: bytes) -> None:
+        """Start reading `data` from its first byte."""
+        self.data = data
+        self.offset = 0
+
+    def take(self, count: int) -> bytes:
+        """Return the next `count` bytes, failing if there are fewer left."""
+        end = self.offset + count
+        if end > len(self.data):
+            raise DecodeError(
+                f"expected {count} more bytes at offset {self.offset}"
+            )
+        chunk = self.data[self.offset:end]
+        self.offset = end
+        return chunk
+
+    def remaining(self) -> int:
+        """Return how many bytes are left."""
+        return len(self.data) - self.offset
+
+
+def write_varint(value: int) -> bytes:
+    """
+    Encode an unsigned integer the way bincode's varint encoding does.
+
+    Values below 251 take a single byte. Larger ones are a marker byte
+    followed by the value as a little-endian u16, u32, u64 or u128.
+    """
+    if value < 0:
+        raise ValueError(f"{value} is negative")
+    if value < U16_MARKER:
+        return bytes([value])
+    if value < 1 << 16:
+        return bytes([U16_MARKER]) + struct.pack("<H", value)
+    if value < 1 << 32:
+        return bytes([U32_MARKER]) + struct.pack("<I", value)
+    if value < 1 << 64:
+        return bytes([U64_MARKER]) + struct.pack("<Q", value)
+    if value < 1 << 128:
+        return bytes([U128_MARKER]) + value.to_bytes(16, "little")
+    raise ValueError(f"{value} does not fit in 128 bits")
+
+
+def read_varint(reader: Reader) -> int:
+    """Decode an unsigned integer written by `write_varint`."""
+    first = reader.take(1)[0]
+    if first < U16_MARKER:
+        return first
+    widths = {U16_MARKER: 2, U32_MARKER: 4, U64_MARKER: 8, U128_MARKER: 16}
+    if first not in widths:
+        raise DecodeError(f"invalid varint marker {first}")
+    return int.from_bytes(reader.take(widths[first]), "little")
+
+
+class Codec:
+    """Encodes and decodes values of one type."""
+
+    def encode(self, value: Any) -> bytes:
+        """Return the bytes of `value`."""
+        raise NotImplementedError
+
+    def decode(self, reader: Reader) -> Any:
+        """Read a value from `reader`."""
+        raise NotImplementedError
+
+
+class UInt(Codec):
+    """Unsigned integer of `bits` bits; bytes are written as they are."""
+
+    def __init__(self, bits: int) -> None:
+        """Create a codec for integers in [0, 2**bits)."""
+        self.bits = bits
+
+    def encode(self, value: int) -> bytes:
+        """Encode `value`, checking it fits."""
+        if not 0 <= value < 1 << self.bits:
+            raise ValueError(f"{value} does not fit in u{self.bits}")
+        if self.bits == 8:
+            return bytes([value])
+        return write_varint(value)
+
+    def decode(self, reader: Reader) -> int:
+        """Decode a value, checking it fits."""
+        if self.bits == 8:
+            return reader.take(1)[0]
+        value = read_varint(reader)
+        if value >= 1 << self.bits:
+            raise DecodeError(f"{value} does not fit in u{self.bits}")
+        return value
+
+
+class SInt(Codec):
+    """Signed integer of `bits` bits, zigzag-encoded above a byte."""
+
+    def __init__(self, bits: int) -> None:
+        """Create a codec for integers in [-2**(bits-1), 2**(bits-1))."""
+        self.bits = bits
+
+    def encode(self, value: int) -> bytes:
+        """Encode `value`, checking it fits."""
+        limit = 1 << (self.bits - 1)
+        if not -limit <= value < limit:
+            raise ValueError(f"{value} does not fit in i{self.bits}")
+        if self.bits == 8:
+            return struct.pack("<b", value)
+        # Zigzag maps 0, -1, 1, -2, ... to 0, 1, 2, 3, ... so small
+        # magnitudes stay short whatever their sign.
+        return write_varint(value * 2 if value >= 0 else -value * 2 - 1)
+
+    def decode(self, reader: Reader) -> int:
+        """Decode a value, checking it fits."""
+        if self.bits == 8:
+            return struct.unpack("<b", reader.take(1))[0]
+        zigzag = read_varint(reader)
+        value = zigzag // 2 if zigzag % 2 == 0 else -(zigzag + 1) // 2
+        limit = 1 << (self.bits - 1)
+        if not -limit <= value < limit:
+            raise DecodeError(f"{value} does not fit in i{self.bits}")
+        return value
+
+
+class Float(Codec):
+    """IEEE float, little-endian; `fmt` is a `struct` format."""
+
+    def __init__(self, fmt: str) -> None:
+        """Create a codec for `<f` (f32) or `<d` (f64) floats."""
+        self.fmt = fmt
+
+    def encode(self, value: float) -> bytes:
+        """Encode `value`, rounding it to the format's precision."""
+        return struct.pack(self.fmt, value)
+
+    def decode(self, reader: Reader) -> float:
+        """Decode a value."""
+        size = struct.calcsize(self.fmt)
+        return struct.unpack(self.fmt, reader.take(size))[0]
+
+
+class Bool(Codec):
+    """A byte that is 0 or 1."""
+
+    def encode(self, value: bool) -> bytes:
+        """Encode `value`."""
+        return b"\x01" if value else b"\x00"
+
+    def decode(self, reader: Reader) -> bool:
+        """Decode a value, refusing bytes other than 0 and 1."""
+        byte = reader.take(1)[0]
+        if byte > 1:
+            raise DecodeError(f"invalid bool {byte}")
+        return byte == 1
+
+
+class Str(Codec):
+    """UTF-8 string prefixed with its length in bytes."""
+
+    def encode(self, value: str) -> bytes:
+        """Encode `value`."""
+        data = value.encode("utf-8")
+        return write_varint(len(data)) + data
+
+    def decode(self, reader: Reader) -> str:
+        """Decode a value, refusing invalid UTF-8."""
+        data = reader.take(read_varint(reader))
+        try:
+            return data.decode("utf-8")
+        except UnicodeDecodeError as error:
+            raise DecodeError(str(error)) from error
+
+
+class Opt(Codec):
+    """`Option<T>`: a 0 byte for `None`, or 1 followed by the value."""
+
+    def __init__(self, inner: Codec) -> None:
+        """Wrap the codec of the value."""
+        self.inner = inner
+
+    def encode(self, value: Any) -> bytes:
+        """Encode `value`, `None` included."""
+        if value is None:
+            return b"\x00"
+        return b"\x01" + self.inner.encode(value)
+
+    def decode(self, reader: Reader) -> Any:
+        """Decode a value, `None` included."""
+        tag = reader.take(1)[0]
+        if tag > 1:
+            raise DecodeError(f"invalid option tag {tag}")
+        return self.inner.decode(reader) if tag == 1 else None
+
+
+class Seq(Codec):
+    """`Vec<T>` and sets: the length, then every item."""
+
+    def __init__(self, item: Codec) -> None:
+        """Wrap the codec of the items."""
+        self.item = item
+
+    def encode(self, value: Sequence[Any]) -> bytes:
+        """Encode the items of `value` in order."""
+        items = list(value)
+        return write_varint(len(items)) + b"".join(
+            self.item.encode(item) for item in items
+        )
+
+    def decode(self, reader: Reader) -> List[Any]:
+        """Decode the items as a list."""
+        count = read_varint(reader)
+        # Every item takes at least one byte unless it is zero-sized, so a
+        # length beyond the data left is corrupt rather than a huge list.
+        if count > reader.remaining() and not isinstance(self.item, Unit):
+            raise DecodeError(f"{count} items in {reader.remaining()} bytes")
+        return [self.item.decode(reader) for _ in range(count)]
+
+
+class Map(Codec):
+    """`HashMap<K, V>`: the length, then every key and value."""
+
+    def __init__(self, key: Codec, value: Codec) -> None:
+        """Wrap the codecs of the keys and values."""
+        self.key = key
+        self.value = value
+
+    def encode(self, value: Dict[Any, Any]) -> bytes:
+        """Encode the entries of `value`."""
+        return write_varint(len(value)) + b"".join(
+            self.key.encode(k) + self.value.encode(v)
+            for k, v in value.items()
+        )
+
+    def decode(self, reader: Reader) -> Dict[Any, Any]:
+        """Decode the entries as a dict."""
+        count = read_varint(reader)
+        if count > reader.remaining():
+            raise DecodeError(f"{count} entries in {reader.remaining()} bytes")
+        return {
+            self.key.decode(reader): self.value.decode(reader)
+            for _ in range(count)
+        }
+
+
+class Tuple_(Codec):
+    """Tuples, arrays and glam vectors: every item, without a length."""
+
+    def __init__(self, items: Sequence[Codec]) -> None:
+        """Wrap the codec of each item."""
+        self.items = list(items)
+
+    def encode(self, value: Sequence[Any]) -> bytes:
+        """Encode the items of `value`, which must have the right count."""
+        items = list(value)
+        if len(items) != len(self.items):
+            raise ValueError(f"expected {len(self.items)} items: {value!r}")
+        return b"".join(c.encode(v) for c, v in zip(self.items, items))
+
+    def decode(self, reader: Reader) -> Tuple[Any, ...]:
+        """Decode the items as a tuple."""
+        return tuple(codec.decode(reader) for codec in self.items)
+
+
+class Unit(Codec):
+    """`()` and unit structs: no bytes at all."""
+
+    def __init__(self, make: Callable[[], Any] = lambda: None) -> None:
+        """Decode to `make()`."""
+        self.make = make
+
+    def encode(self, value: Any) -> bytes:
+        """Encode nothing."""
+        return b""
+
+    def decode(self, reader: Reader) -> Any:
+        """Decode nothing."""
+        return self.make()
+
+
+class Struct(Codec):
+    """A struct as its fields in declaration order, into a dataclass."""
+
+    def __init__(self, cls: type, fields: Sequence[Tuple[str, Codec]]):
+        """Encode instances of `cls` by the `(attribute, codec)` pairs."""
+        self.cls = cls
+        self.fields = list(fields)
+
+    def encode(self, value: Any) -> bytes:
+        """Encode every field of `value`."""
+        return b"".join(
+            codec.encode(getattr(value, name)) for name, codec in self.fields
+        )
+
+    def decode(self, reader: Reader) -> Any:
+        """Decode every field and build the dataclass."""
+        return self.cls(*(codec.decode(reader) for _, codec in self.fields))
+
+
+class UnitEnum(Codec):
+    """An enum without data, as a Python `enum.Enum` in variant order."""
+
+    def __init__(self, cls: Any) -> None:
+        """Encode members of `cls` by their position."""
+        self.members = list(cls)
+
+    def encode(self, value: Any) -> bytes:
+        """Encode the variant index of `value`."""
+        return write_varint(self.members.index(value))
+
+    def decode(self, reader: Reader) -> Any:
+        """Decode a variant index into its member."""
+        index = read_varint(reader)
+        if index >= len(self.members):
+            raise DecodeError(f"invalid variant {index}")
+        return self.members[index]
+
+
+class DataEnum(Codec):
+    """An enum with data: one dataclass per variant, in variant order."""
+
+    def __init__(self, variants: Sequence[Struct]) -> None:
+        """Encode instances of each variant's class with its codec."""
+        self.variants = list(variants)
+
+    def encode(self, value: Any) -> bytes:
+        """Encode the variant index of `value`, then its fields."""
+        for index, variant in enumerate(self.variants):
+            if type(value) is variant.cls:
+                return write_varint(index) + variant.encode(value)
+        raise ValueError(f"{value!r} is not a variant of this enum")
+
+    def decode(self, reader: Reader) -> Any:
+        """Decode a variant index and the variant's fields."""
+        index = read_varint(reader)
+        if index >= len(self.variants):
+            raise DecodeError(f"invalid variant {index}")
+        return self.variants[index].decode(reader)
+
+
+class Named(Codec):
+    """Codec of a manifest type looked up when used, for forward references."""
+
+    def __init__(self, codecs: Dict[str, Codec], name: str) -> None:
+        """Refer to `codecs[name]`, which may not be filled in yet."""
+        self.codecs = codecs
+        self.name = name
+
+    def encode(self, value: Any) -> bytes:
+        """Encode with the named codec."""
+        return self.codecs[self.name].encode(value)
+
+    def decode(self, reader: Reader) -> Any:
+        """Decode with the named codec."""
+        return self.codecs[self.name].decode(reader)
+
+
+class Unsupported(Codec):
+    """A type the generator has no encoding for; fails when used."""
+
+    def __init__(self, rust_type: str) -> None:
+        """Remember the Rust type for the error message."""
+        self.rust_type = rust_type
+
+    def encode(self, value: Any) -> bytes:
+        """Always fail."""
+        raise NotImplementedError(f"no encoding for {self.rust_type}")
+
+    def decode(self, reader: Reader) -> Any:
+        """Always fail."""
+        raise NotImplementedError(f"no encoding for {self.rust_type}")
+
+
+def encode(codec: Codec, value: Any) -> bytes:
+    """Encode `value` with `codec`."""
+    return codec.encode(value)
+
+
+def decode(codec: Codec, data: bytes) -> Any:
+    """Decode all of `data` with `codec`, refusing trailing bytes."""
+    reader = Reader(data)
+    value = codec.decode(reader)
+    if reader.remaining():
+        raise DecodeError(f"{reader.remaining()} trailing bytes")
+    return value
@@ -0,0 +1,389 @@
+"""
+Asyncio client for Yolo servers, for bot scripts and protocol tests.
+
+`YoloClient` connects over UDP (native servers) or WebTransport (the servers
+browser clients join), which joins the lobby, then sends protocol messages and
+inputs and hands out the messages the server sends. It does not replicate the
+world: scripts see what the messages tell them.
+
+    client = await YoloClient.connect_udp("127.0.0.1", 5000)
+    await client.send(messages.PlayerProfileRequest(
+        messages.PlayerProfile("script", None)))
+    async for message in client.messages():
+        print(message)
+
+WebTransport needs the `aioquic` package; UDP only needs `cryptography`.
+"""
+
+import asyncio
+import os
+import ssl
+import struct
+import time
+from contextlib import AsyncExitStack
+from typing import Any, AsyncIterator, Dict, List, Optional, Tuple
+
+from . import bincode, lightyear, messages, netcode
+
+# Seconds to wait for the server to accept the connection.
+CONNECT_TIMEOUT_SECS = 5.0
+# Ticks between the server tick a client last heard of and the tick its
+# inputs are for, so they arrive before the server simulates that tick.
+INPUT_DELAY_TICKS = 6
+
+
+class ServerConnectionError(ConnectionError):
+    """Raised when the server denies, drops or never answers a client."""
+
+
+class UdpTransport(asyncio.DatagramProtocol):
+    """Datagrams to and from a server over UDP."""
+
+    def __init__(self) -> None:
+        """Start with no socket; use `open`."""
+        self.received: asyncio.Queue = asyncio.Queue()
+        self.transport: Optional[asyncio.DatagramTransport] = None
+
+    @classmethod
+    async def open(cls, host: str, port: int) -> "UdpTransport":
+        """Open a UDP socket sending to `host:port`."""
+        loop = asyncio.get_running_loop()
+        _, protocol = await loop.create_datagram_endpoint(
+            cls, remote_addr=(host, port)
+        )
+        return protocol
+
+    def connection_made(self, transport: Any) -> None:
+        """Keep the socket asyncio opened."""
+        self.transport = transport
+
+    def datagram_received(self, data: bytes, addr: Any) -> None:
+        """Queue datagrams for `recv`."""
+        self.received.put_nowait(data)
+
+    def send(self, data: bytes) -> None:
+        """Send one datagram."""
+        if self.transport is not None:
+            self.transport.sendto(data)
+
+    async def recv(self) -> bytes:
+        """Wait for the next datagram."""
+        return await self.received.get()
+
+    async def close(self) -> None:
+        """Close the socket."""
+        if self.transport is not None:
+            self.transport.close()
+
+
+class WebTransportTransport:
+    """
+    Datagrams to and from a server over a WebTransport session, as browser
+    clients send them. Uses `aioquic` for QUIC and HTTP/3.
+    """
+
+    def __init__(self) -> None:
+        """Start with no session; use `open`."""
+        self.received: asyncio.Queue = asyncio.Queue()
+        self.stack = AsyncExitStack()
+        self.protocol: Any = None
+        self.http: Any = None
+        self.session_id = 0
+
+    @classmethod
+    async def open(
+        cls, host: str, port: int, path: str = "/", verify: bool = False
+    ) -> "WebTransportTransport":
+        """
+        Open a WebTransport session with `https://host:port/path`.
+
+        Yolo servers use self-signed certificates, so they are not verified
+        unless `verify` is set.
+        """
+        # aioquic is only needed for WebTransport, so it is imported here.
+        from aioquic.asyncio.client import connect
+        from aioquic.asyncio.protocol import QuicConnectionProtocol
+        from aioquic.h3.connection import H3_ALPN, H3Connection
+        from aioquic.h3.events import DatagramReceived, HeadersReceived
+        from aioquic.quic.configuration import QuicConfiguration
+
+        transport = cls()
+        session_ready: asyncio.Future = (
+            asyncio.get_running_loop().create_future()
+        )
+
+        class Protocol(QuicConnectionProtocol):
+            """Forwards HTTP/3 events of the session to the transport."""
+
+            def quic_event_received(self, event: Any) -> None:
+                """Route datagrams to the queue and the CONNECT answer."""
+                if transport.http is None:
+                    return
+                for http_event in transport.http.handle_event(event):
+                    if isinstance(http_event, DatagramReceived):
+                        transport.received.put_nowait(http_event.data)
+                    elif (
+                        isinstance(http_event, HeadersReceived)
+                        and not session_ready.done()
+                    ):
+                        status = dict(http_event.headers).get(b":status")
+                        session_ready.set_result(status == b"200")
+
+        configuration = QuicConfiguration(
+            is_client=True,
+            alpn_protocols=H3_ALPN,
+            max_datagram_frame_size=65536,
+            verify_mode=ssl.CERT_REQUIRED if verify else ssl.CERT_NONE,
+        )
+        transport.protocol = await transport.stack.enter_async_context(
+            connect(
+                host,
+                port,
+                configuration=configuration,
+                create_protocol=Protocol,
+            )
+        )
+        quic = transport.protocol._quic
+        transport.http = H3Connection(quic, enable_webtransport=True)
+        transport.session_id = quic.get_next_available_stream_id()
+        transport.http.send_headers(
+            transport.session_id,
+            [
+                (b":method", b"CONNECT"),
+                (b":scheme", b"https"),
+                (b":authority", f"{host}:{port}".encode()),
+                (b":path", path.encode()),
+                (b":protocol", b"webtransport"),
+            ],
+        )
+        transport.protocol.transmit()
+        if not await asyncio.wait_for(session_ready, CONNECT_TIMEOUT_SECS):
+            await transport.close()
+            raise ServerConnectionError("the server refused the session")
+        return transport
+
+    def send(self, data: bytes) -> None:
+        """Send one datagram on the session."""
+        self.http.send_datagram(self.session_id, data)
+        self.protocol.transmit()
+
+    async def recv(self) -> bytes:
+        """Wait for the next datagram."""
+        return await self.received.get()
+
+    async def close(self) -> None:
+        """Close the session and the QUIC connection."""
+        await self.stack.aclose()
+
+
+class YoloClient:
+    """
+    One connection to a server: a lobby player once `join` returns.
+
+    Messages are the generated classes of `messages`; `send` picks their
+    channel and network id from the manifest.
+    """
+
+    def __init__(
+        self,
+        transport: Any,
+        token: netcode.ConnectToken,
+        layout: Optional[lightyear.Layout] = None,
+    ) -> None:
+        """Prepare a connection over `transport`; use the `connect_*`."""
+        self.transport = transport
+        self.netcode = netcode.NetcodeClient(token)
+        self.layout = layout or lightyear.Layout()
+        self.packets = lightyear.Packets()
+        self.reliable: Dict[int, bool] = {}
+        for channel in messages.CHANNELS:
+            channel_id = self.layout.first_channel_id + channel.index
+            reliable = "Unreliable" not in channel.mode
+            self.packets.add_channel(channel_id, reliable)
+            self.reliable[channel_id] = reliable
+        self.packets.add_channel(self.layout.input_channel_id, False)
+        self.received: asyncio.Queue = asyncio.Queue()
+        self.server_tick = 0
+        self.connected = False
+        self.tasks: List[asyncio.Task] = []
+        self.last_sent = 0.0
+
+    @classmethod
+    async def connect_udp(
+        cls, host: str, port: int, client_id: Optional[int] = None
+    ) -> "YoloClient":
+        """Connect to a native server and join its lobby."""
+        transport = await UdpTransport.open(host, port)
+        return await cls._connect(transport, host, port, client_id)
+
+    @classmethod
+    async def connect_webtransport(
+        cls, host: str, port: int, client_id: Optional[int] = None
+    ) -> "YoloClient":
+        """Connect to a server over WebTransport and join its lobby."""
+        transport = await WebTransportTransport.open(host, port)
+        return await cls._connect(transport, host, port, client_id)
+
+    @classmethod
+    async def _connect(
+        cls, transport: Any, host: str, port: int, client_id: Optional[int]
+    ) -> "YoloClient":
+        """Run the handshake, closing the transport if it fails."""
+        if client_id is None:
+            # Random like the launcher's default, so scripts do not collide.
+            client_id = struct.unpack("<Q", os.urandom(8))[0]
+        token = netcode.ConnectToken.generate(
+            messages.PROTOCOL_HASH, client_id, (host, port)
+        )
+        client = cls(transport, token)
+        try:
+            await asyncio.wait_for(client.join(), CONNECT_TIMEOUT_SECS)
+        except BaseException:
+            await transport.close()
+            raise
+        return client
+
+    async def join(self) -> None:
+        """
+        Run the netcode handshake: send the connect token until challenged,
+        answer the challenge until the server sends a keep-alive. The server
+        adds the client to the lobby once connected.
+        """
+        packet = self.netcode.connection_request()
+        while True:
+            self.transport.send(packet)
+            reply = await self._next_packet(netcode.PACKET_SEND_INTERVAL)
+            if reply is None:
+                continue
+            packet_type, body = reply
+            if packet_type == netcode.CONNECTION_DENIED:
+                raise ServerConnectionError("the server is full or refused")
+            if packet_type == netcode.CONNECTION_CHALLENGE:
+                packet = self.netcode.challenge_response(body)
+            elif packet_type == netcode.KEEP_ALIVE:
+                self.netcode.record_keep_alive(body)
+                break
+        self.connected = True
+        self.last_sent = time.monotonic()
+        self.tasks = [
+            asyncio.create_task(self._receive_loop()),
+            asyncio.create_task(self._keep_alive_loop()),
+        ]
+
+    async def _next_packet(
+        self, timeout: float
+    ) -> Optional[Tuple[int, bytes]]:
+        """The next valid packet, or `None` after `timeout` seconds."""
+        try:
+            data = await asyncio.wait_for(self.transport.recv(), timeout)
+        except asyncio.TimeoutError:
+            return None
+        return self.netcode.open(data)
+
+    async def _receive_loop(self) -> None:
+        """Decode the server's messages until it disconnects the client."""
+        timeout = float(self.netcode.token.timeout_secs)
+        while self.connected:
+            packet = await self._next_packet(timeout)
+            if packet is None or packet[0] == netcode.DISCONNECT:
+                break
+            packet_type, body = packet
+            if packet_type == netcode.PAYLOAD:
+                self._receive_payload(body)
+        self.connected = False
+        # Wakes up `messages` so it ends.
+        self.received.put_nowait(None)
+
+    def _receive_payload(self, payload: bytes) -> None:
+        """Queue the protocol messages of one lightyear packet."""
+        try:
+            header, packed = self.packets.decode(payload, self.reliable)
+        except bincode.DecodeError:
+            return
+        self.server_tick = header.tick
+        for _, data in packed:
+            net_id, body = lightyear.decode_message(data)
+            index = net_id - self.layout.first_message_id
+            if not 0 <= index < len(messages.MESSAGES):
+                # Lightyear's own messages.
+                continue
+            info = messages.MESSAGES[index]
+            try:
+                self.received.put_nowait(bincode.decode(info.codec, body))
+            except (bincode.DecodeError, NotImplementedError):
+                continue
+
+    async def _keep_alive_loop(self) -> None:
+        """Send keep-alives while nothing else is sent."""
+        while self.connected:
+            await asyncio.sleep(netcode.PACKET_SEND_INTERVAL)
+            idle = time.monotonic() - self.last_sent
+            if idle >= netcode.PACKET_SEND_INTERVAL:
+                self._send_packet(self.netcode.keep_alive())
+
+    def _send_packet(self, packet: bytes) -> None:
+        """Send a sealed packet, remembering when."""
+        self.transport.send(packet)
+        self.last_sent = time.monotonic()
+
+    def _send_lightyear(self, channel_id: int, message: bytes) -> None:
+        """Send one message in its own lightyear packet."""
+        if not self.connected:
+            raise ServerConnectionError("not connected")
+        packet = self.packets.encode(self.server_tick, [(channel_id, message)])
+        self._send_packet(self.netcode.payload(packet))
+
+    async def send(
+        self, message: Any, channel: str = "LobbyControlChannel"
+    ) -> None:
+        """Send a protocol message on `channel`."""
+        info = messages.message_info(type(message))
+        if info.direction == "ServerToClient":
+            raise ValueError(f"{info.name} is only sent by the server")
+        channel_info = messages.channel_info(channel)
+        self._send_lightyear(
+            self.layout.first_channel_id + channel_info.index,
+            lightyear.encode_message(
+                self.layout.first_message_id + info.index,
+                bincode.encode(info.codec, message),
+            ),
+        )
+
+    async def send_input(
+        self, character: int, frame: lightyear.ActionFrame
+    ) -> None:
+        """
+        Send the inputs of `character` (its entity bits) for the next tick
+        the server will simulate.
+        """
+        actions = list(messages.PlayerAction)
+        body = lightyear.encode_input_message(
+            messages.CODECS["PlayerAction"],
+            actions,
+            character,
+            self.server_tick + INPUT_DELAY_TICKS,
+            [frame],
+        )
+        self._send_lightyear(
+            self.layout.input_channel_id,
+            lightyear.encode_message(self.layout.input_message_id, body),
+        )
+
+    async def messages(self) -> AsyncIterator[Any]:
+        """Yield the messages the server sends until it disconnects."""
+        while True:
+            message = await self.received.get()
+            if message is None:
+                return
+            yield message
+
+    async def close(self) -> None:
+        """Leave the server and close the transport."""
+        if self.connected:
+            # Netcode sends disconnects several times as they are not acked.
+            for _ in range(3):
+                self._send_packet(self.netcode.disconnect())
+        self.connected = False
+        for task in self.tasks:
+            task.cancel()
+        await self.transport.close()
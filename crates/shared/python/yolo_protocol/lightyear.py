@@ -0,0 +1,240 @@
+"""
+Lightyear packets, carried in netcode payloads.
+
+Lightyear does not document its wire format, so this module is the one place
+that knows it, as of lightyear 0.26: a packet header, then for each channel
+its network id and the messages sent on it. Each message is its network id
+followed by its bincode encoding. Inputs are messages too, sent on
+lightyear's own input channel.
+
+Network ids come from registration order. Lightyear registers its own
+channels and messages (time sync, replication) before the protocol's, and the
+input plugin registers the input message before the protocol's messages, so
+the protocol's ids start after them. `Layout` holds those offsets and is the
+thing to check first when a lightyear upgrade breaks the client.
+"""
+
+import struct
+from dataclasses import dataclass, field
+from typing import Any, Dict, Iterable, List, Optional, Tuple
+
+from . import bincode
+
+DATA_PACKET = 0
+FRAGMENTED_PACKET = 1
+HEADER = struct.Struct(">BHHIH")
+# Largest lightyear packet that fits a netcode payload unfragmented.
+MAX_PACKET_BYTES = 1200
+
+
+@dataclass
+class Layout:
+    """Where lightyear puts the protocol among its own registrations."""
+
+    # Id of the protocol's first channel, after lightyear's own.
+    first_channel_id: int = 6
+    # Id of the protocol's first message, after lightyear's own and the
+    # input message.
+    first_message_id: int = 3
+    # Id of the leafwing input message.
+    input_message_id: int = 2
+    # Channel lightyear sends inputs on.
+    input_channel_id: int = 3
+
+
+@dataclass
+class PacketHeader:
+    """Sequencing and acks of a lightyear packet, and the sender's tick."""
+
+    packet_type: int
+    packet_id: int
+    last_ack_packet_id: int
+    ack_bitfield: int
+    tick: int
+
+    def encode(self) -> bytes:
+        """The header's bytes, big-endian."""
+        return HEADER.pack(
+            self.packet_type,
+            self.packet_id,
+            self.last_ack_packet_id,
+            self.ack_bitfield,
+            self.tick,
+        )
+
+    @classmethod
+    def decode(cls, reader: bincode.Reader) -> "PacketHeader":
+        """Read a header."""
+        return cls(*HEADER.unpack(reader.take(HEADER.size)))
+
+
+@dataclass
+class ChannelState:
+    """Outgoing message ids of one reliable channel."""
+
+    reliable: bool
+    next_message_id: int = 0
+
+
+@dataclass
+class Packets:
+    """
+    Packet sequencing of one connection: numbers outgoing packets and acks
+    the server's, so reliable channels on the server side make progress.
+    """
+
+    channels: Dict[int, ChannelState] = field(default_factory=dict)
+    next_packet_id: int = 0
+    last_received: Optional[int] = None
+    received_bits: int = 0
+
+    def add_channel(self, channel_id: int, reliable: bool) -> None:
+        """Declare a channel messages will be sent on."""
+        self.channels[channel_id] = ChannelState(reliable)
+
+    def _header(self, tick: int) -> PacketHeader:
+        """Header of the next outgoing packet."""
+        header = PacketHeader(
+            DATA_PACKET,
+            self.next_packet_id,
+            self.last_received or 0,
+            self.received_bits,
+            tick & 0xFFFF,
+        )
+        self.next_packet_id = (self.next_packet_id + 1) & 0xFFFF
+        return header
+
+    def encode(
+        self, tick: int, messages: Iterable[Tuple[int, bytes]]
+    ) -> bytes:
+        """
+        Pack `(channel_id, message)` pairs into one packet.
+
+        Messages keep their order within a channel. Packets larger than
+        `MAX_PACKET_BYTES` are refused: the SDK only sends small requests and
+        inputs, and does not fragment.
+        """
+        by_channel: Dict[int, List[bytes]] = {}
+        for channel_id, message in messages:
+            by_channel.setdefault(channel_id, []).append(message)
+        body = b""
+        for channel_id, channel_messages in by_channel.items():
+            state = self.channels.get(channel_id)
+            if state is None:
+                raise KeyError(f"channel {channel_id} was not declared")
+            body += bincode.write_varint(channel_id)
+            body += bincode.write_varint(len(channel_messages))
+            for message in channel_messages:
+                if state.reliable:
+                    body += struct.pack(">H", state.next_message_id)
+                    state.next_message_id += 1
+                    state.next_message_id &= 0xFFFF
+                body += bincode.write_varint(len(message)) + message
+        packet = self._header(tick).encode() + body
+        if len(packet) > MAX_PACKET_BYTES:
+            raise ValueError(f"{len(packet)} byte packet needs fragmenting")
+        return packet
+
+    def decode(
+        self, packet: bytes, reliable: Dict[int, bool]
+    ) -> Tuple[PacketHeader, List[Tuple[int, bytes]]]:
+        """
+        Unpack a packet from the server into its header and its
+        `(channel_id, message)` pairs, recording it for acks.
+
+        `reliable` tells which channel ids carry message ids. Fragmented
+        packets are skipped: they only carry large replication updates.
+        """
+        reader = bincode.Reader(packet)
+        header = PacketHeader.decode(reader)
+        self._record(header.packet_id)
+        messages: List[Tuple[int, bytes]] = []
+        if header.packet_type != DATA_PACKET:
+            return header, messages
+        while reader.remaining():
+            channel_id = bincode.read_varint(reader)
+            for _ in range(bincode.read_varint(reader)):
+                if reliable.get(channel_id, False):
+                    reader.take(2)
+                messages.append(
+                    (channel_id, reader.take(bincode.read_varint(reader)))
+                )
+        return header, messages
+
+    def _record(self, packet_id: int) -> None:
+        """Ack `packet_id` in the next packets sent."""
+        if self.last_received is None:
+            self.last_received, self.received_bits = packet_id, 0
+            return
+        ahead = (packet_id - self.last_received) & 0xFFFF
+        if ahead == 0:
+            return
+        if ahead < 0x8000:
+            self.received_bits = (
+                (self.received_bits << ahead) | (1 << (ahead - 1))
+            ) & 0xFFFFFFFF
+            self.last_received = packet_id
+        else:
+            behind = (self.last_received - packet_id) & 0xFFFF
+            if behind <= 32:
+                self.received_bits |= 1 << (behind - 1)
+
+
+def encode_message(net_id: int, payload: bytes) -> bytes:
+    """A message as lightyear sends it: its network id, then its bytes."""
+    return bincode.write_varint(net_id) + payload
+
+
+def decode_message(data: bytes) -> Tuple[int, bytes]:
+    """Split a message into its network id and its bytes."""
+    reader = bincode.Reader(data)
+    net_id = bincode.read_varint(reader)
+    return net_id, reader.take(reader.remaining())
+
+
+@dataclass
+class ActionFrame:
+    """
+    Leafwing inputs of one tick: the buttons held and the value of every
+    dual-axis action (`Move`, `Look`).
+    """
+
+    pressed: List[Any] = field(default_factory=list)
+    axes: Dict[Any, Tuple[float, float]] = field(default_factory=dict)
+
+
+def encode_input_message(
+    action_codec: bincode.Codec,
+    actions: List[Any],
+    target_entity: int,
+    end_tick: int,
+    frames: List[ActionFrame],
+) -> bytes:
+    """
+    A leafwing input message for one character: the inputs of the ticks up
+    to `end_tick`, oldest first, as the state of every action each tick.
+
+    `actions` are all the action enum's members, `target_entity` the bits of
+    the character entity the server replicated to this client.
+    """
+    if not frames:
+        raise ValueError("an input message holds at least one tick")
+    f32 = bincode.Float("<f")
+    body = bincode.write_varint(end_tick & 0xFFFF)
+    # No interpolation delay: the SDK does not interpolate.
+    body += b"\x00"
+    body += bincode.write_varint(1)
+    # InputTarget::Entity, then the entity bits.
+    body += bincode.write_varint(0) + bincode.write_varint(target_entity)
+    body += bincode.write_varint(len(frames))
+    for frame in frames:
+        body += bincode.write_varint(len(actions))
+        for action in actions:
+            body += action_codec.encode(action)
+            if action in frame.axes:
+                x, y = frame.axes[action]
+                body += bincode.write_varint(1) + f32.encode(x) + f32.encode(y)
+            else:
+                body += bincode.write_varint(0)
+                body += bincode.Bool().encode(action in frame.pressed)
+    return body
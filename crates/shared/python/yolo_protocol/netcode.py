@@ -0,0 +1,305 @@
+"""
+Client side of netcode.io 1.02, the connection layer under lightyear.
+
+The server authenticates clients with connect tokens sealed by a private key.
+Yolo servers run with manual authentication and the all-zero development key
+(`SHARED_SETTINGS.private_key`), so a client seals its own token the way the
+Rust client does. Packets are built and read here without any I/O; `client`
+sends them over UDP or WebTransport.
+
+The `cryptography` package provides ChaCha20-Poly1305. Connect tokens use the
+extended-nonce XChaCha20-Poly1305, built on top of it with HChaCha20 as
+libsodium does.
+"""
+
+import ipaddress
+import os
+import struct
+import time
+from dataclasses import dataclass
+from typing import List, Optional, Tuple
+
+from cryptography.hazmat.primitives.ciphers.aead import ChaCha20Poly1305
+
+VERSION_INFO = b"NETCODE 1.02\x00"
+KEY_BYTES = 32
+MAC_BYTES = 16
+XNONCE_BYTES = 24
+CONNECT_TOKEN_PRIVATE_BYTES = 1024
+CHALLENGE_TOKEN_BYTES = 300
+USER_DATA_BYTES = 256
+MAX_PAYLOAD_BYTES = 1200
+# Seconds between two handshake or keep-alive packets.
+PACKET_SEND_INTERVAL = 0.1
+# Defaults of the Rust netcode connect token builder.
+TOKEN_EXPIRE_SECS = 30
+TIMEOUT_SECS = 15
+DEV_PRIVATE_KEY = bytes(KEY_BYTES)
+
+CONNECTION_REQUEST = 0
+CONNECTION_DENIED = 1
+CONNECTION_CHALLENGE = 2
+CONNECTION_RESPONSE = 3
+KEEP_ALIVE = 4
+PAYLOAD = 5
+DISCONNECT = 6
+
+ADDRESS_IPV4 = 1
+ADDRESS_IPV6 = 2
+
+
+def _rotl(value: int, bits: int) -> int:
+    """Rotate a 32-bit word left."""
+    return ((value << bits) | (value >> (32 - bits))) & 0xFFFFFFFF
+
+
+def hchacha20(key: bytes, nonce: bytes) -> bytes:
+    """
+    Derive a subkey from `key` and a 16-byte nonce.
+
+    HChaCha20 runs the 20 ChaCha rounds over the key and nonce and keeps the
+    first and last rows of the state, without the final addition.
+    """
+    state = list(struct.unpack("<4I", b"expand 32-byte k"))
+    state += list(struct.unpack("<8I", key))
+    state += list(struct.unpack("<4I", nonce))
+
+    def quarter(a: int, b: int, c: int, d: int) -> None:
+        state[a] = (state[a] + state[b]) & 0xFFFFFFFF
+        state[d] = _rotl(state[d] ^ state[a], 16)
+        state[c] = (state[c] + state[d]) & 0xFFFFFFFF
+        state[b] = _rotl(state[b] ^ state[c], 12)
+        state[a] = (state[a] + state[b]) & 0xFFFFFFFF
+        state[d] = _rotl(state[d] ^ state[a], 8)
+        state[c] = (state[c] + state[d]) & 0xFFFFFFFF
+        state[b] = _rotl(state[b] ^ state[c], 7)
+
+    for _ in range(10):
+        quarter(0, 4, 8, 12)
+        quarter(1, 5, 9, 13)
+        quarter(2, 6, 10, 14)
+        quarter(3, 7, 11, 15)
+        quarter(0, 5, 10, 15)
+        quarter(1, 6, 11, 12)
+        quarter(2, 7, 8, 13)
+        quarter(3, 4, 9, 14)
+    return struct.pack("<8I", *(state[0:4] + state[12:16]))
+
+
+def xchacha20poly1305_encrypt(
+    key: bytes, nonce: bytes, plaintext: bytes, associated: bytes
+) -> bytes:
+    """Seal `plaintext` with XChaCha20-Poly1305 and a 24-byte nonce."""
+    subkey = hchacha20(key, nonce[:16])
+    return ChaCha20Poly1305(subkey).encrypt(
+        bytes(4) + nonce[16:], plaintext, associated
+    )
+
+
+def xchacha20poly1305_decrypt(
+    key: bytes, nonce: bytes, sealed: bytes, associated: bytes
+) -> bytes:
+    """Open what `xchacha20poly1305_encrypt` sealed."""
+    subkey = hchacha20(key, nonce[:16])
+    return ChaCha20Poly1305(subkey).decrypt(
+        bytes(4) + nonce[16:], sealed, associated
+    )
+
+
+def _write_address(host: str, port: int) -> bytes:
+    """Encode a server address the way connect tokens list them."""
+    ip = ipaddress.ip_address(host)
+    if ip.version == 4:
+        return bytes([ADDRESS_IPV4]) + ip.packed + struct.pack("<H", port)
+    segments = struct.unpack(">8H", ip.packed)
+    return (
+        bytes([ADDRESS_IPV6])
+        + struct.pack("<8H", *segments)
+        + struct.pack("<H", port)
+    )
+
+
+@dataclass
+class ConnectToken:
+    """
+    What a client presents to connect: its id, the servers it may join and
+    the keys of the connection, sealed so only the servers can read them.
+    """
+
+    protocol_id: int
+    client_id: int
+    server_addresses: List[Tuple[str, int]]
+    client_to_server_key: bytes
+    server_to_client_key: bytes
+    expire_timestamp: int
+    nonce: bytes
+    timeout_secs: int = TIMEOUT_SECS
+    user_data: bytes = bytes(USER_DATA_BYTES)
+
+    @classmethod
+    def generate(
+        cls,
+        protocol_id: int,
+        client_id: int,
+        server_address: Tuple[str, int],
+        expire_secs: int = TOKEN_EXPIRE_SECS,
+    ) -> "ConnectToken":
+        """Create a token for one server with fresh random keys."""
+        return cls(
+            protocol_id=protocol_id,
+            client_id=client_id,
+            server_addresses=[server_address],
+            client_to_server_key=os.urandom(KEY_BYTES),
+            server_to_client_key=os.urandom(KEY_BYTES),
+            expire_timestamp=int(time.time()) + expire_secs,
+            nonce=os.urandom(XNONCE_BYTES),
+        )
+
+    def private_data(self) -> bytes:
+        """The part of the token only servers can read, before sealing."""
+        data = struct.pack(
+            "<QiI",
+            self.client_id,
+            self.timeout_secs,
+            len(self.server_addresses),
+        )
+        for host, port in self.server_addresses:
+            data += _write_address(host, port)
+        data += self.client_to_server_key + self.server_to_client_key
+        data += self.user_data
+        padded = CONNECT_TOKEN_PRIVATE_BYTES - MAC_BYTES
+        if len(data) > padded:
+            raise ValueError("too many server addresses for a connect token")
+        return data + bytes(padded - len(data))
+
+    def associated_data(self) -> bytes:
+        """Authenticated alongside the private data, but sent in the clear."""
+        return VERSION_INFO + struct.pack(
+            "<QQ", self.protocol_id, self.expire_timestamp
+        )
+
+    def sealed_private_data(self, private_key: bytes) -> bytes:
+        """The private data sealed with the servers' `private_key`."""
+        return xchacha20poly1305_encrypt(
+            private_key,
+            self.nonce,
+            self.private_data(),
+            self.associated_data(),
+        )
+
+
+def sequence_bytes(sequence: int) -> int:
+    """Bytes needed to write `sequence`, at least one."""
+    return max(1, (sequence.bit_length() + 7) // 8)
+
+
+class NetcodeClient:
+    """
+    Packet layer of one connection: seals outgoing packets with the client to
+    server key and opens incoming ones with the server to client key.
+    """
+
+    def __init__(
+        self,
+        token: ConnectToken,
+        private_key: bytes = DEV_PRIVATE_KEY,
+    ) -> None:
+        """Prepare to connect with `token`, sealed with `private_key`."""
+        self.token = token
+        self.private_key = private_key
+        self.sequence = 0
+        self.client_index: Optional[int] = None
+        self.max_clients: Optional[int] = None
+
+    def connection_request(self) -> bytes:
+        """The unencrypted request carrying the sealed connect token."""
+        return (
+            bytes([CONNECTION_REQUEST])
+            + VERSION_INFO
+            + struct.pack(
+                "<QQ", self.token.protocol_id, self.token.expire_timestamp
+            )
+            + self.token.nonce
+            + self.token.sealed_private_data(self.private_key)
+        )
+
+    def _associated_data(self, prefix: int) -> bytes:
+        """Authenticated with every encrypted packet."""
+        return (
+            VERSION_INFO
+            + struct.pack("<Q", self.token.protocol_id)
+            + bytes([prefix])
+        )
+
+    @staticmethod
+    def _nonce(sequence: int) -> bytes:
+        """Packets are sealed with their sequence number as nonce."""
+        return bytes(4) + struct.pack("<Q", sequence)
+
+    def seal(self, packet_type: int, body: bytes) -> bytes:
+        """Encrypt a packet of `packet_type` with the next sequence number."""
+        if packet_type == CONNECTION_REQUEST:
+            raise ValueError("connection requests are not encrypted")
+        sequence = self.sequence
+        self.sequence += 1
+        width = sequence_bytes(sequence)
+        prefix = packet_type | (width << 4)
+        sealed = ChaCha20Poly1305(self.token.client_to_server_key).encrypt(
+            self._nonce(sequence), body, self._associated_data(prefix)
+        )
+        return bytes([prefix]) + sequence.to_bytes(width, "little") + sealed
+
+    def open(self, packet: bytes) -> Optional[Tuple[int, bytes]]:
+        """
+        Decrypt a packet from the server into `(packet_type, body)`.
+
+        Returns `None` for anything that is not a well-formed packet sealed
+        with the server to client key; stray datagrams are dropped, as the
+        Rust client does.
+        """
+        if len(packet) < 2:
+            return None
+        prefix = packet[0]
+        packet_type, width = prefix & 0x0F, prefix >> 4
+        if not CONNECTION_DENIED <= packet_type <= DISCONNECT:
+            return None
+        if not 1 <= width <= 8 or len(packet) < 1 + width + MAC_BYTES:
+            return None
+        sequence = int.from_bytes(packet[1:1 + width], "little")
+        try:
+            body = ChaCha20Poly1305(self.token.server_to_client_key).decrypt(
+                self._nonce(sequence),
+                packet[1 + width:],
+                self._associated_data(prefix),
+            )
+        except Exception:
+            return None
+        return packet_type, body
+
+    def challenge_response(self, challenge: bytes) -> bytes:
+        """Echo a challenge back, proving this client holds the keys."""
+        if len(challenge) != 8 + CHALLENGE_TOKEN_BYTES:
+            raise ValueError("malformed connection challenge")
+        return self.seal(CONNECTION_RESPONSE, challenge)
+
+    def keep_alive(self) -> bytes:
+        """Tells the server the client is still there when idle."""
+        return self.seal(
+            KEEP_ALIVE,
+            struct.pack("<II", self.client_index or 0, self.max_clients or 0),
+        )
+
+    def payload(self, data: bytes) -> bytes:
+        """Carry `data` to the server."""
+        if not 0 < len(data) <= MAX_PAYLOAD_BYTES:
+            raise ValueError(f"payloads hold 1 to {MAX_PAYLOAD_BYTES} bytes")
+        return self.seal(PAYLOAD, data)
+
+    def disconnect(self) -> bytes:
+        """Tells the server the client is leaving."""
+        return self.seal(DISCONNECT, b"")
+
+    def record_keep_alive(self, body: bytes) -> None:
+        """Remember the slot the server gave this client."""
+        if len(body) == 8:
+            self.client_index, self.max_clients = struct.unpack("<II", body)
@@ -0,0 +1,30 @@
+//! Idle ("away from keyboard") detection shared by the server, which acts on it, and the
+//! client, which shows it on the scoreboard.
+
+use bevy::prelude::{Component, Vec2};
+use leafwing_input_manager::prelude::ActionState;
+use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
+
+use crate::inputs::input::PlayerAction;
+use crate::protocol::PlayerId;
+
+/// Player ids of headless bot clients (gym agents, load tests). They idle on purpose and
+/// are never treated as AFK.
+pub const BOT_PLAYER_IDS: RangeInclusive<u64> = 10_000..=19_999;
+
+/// Replicated on a player who has been idle long enough to be warned.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Afk;
+
+pub fn is_bot_player(player_id: &PlayerId) -> bool {
+    BOT_PLAYER_IDS.contains(&player_id.0.to_bits())
+}
+
+/// Whether this frame's input shows someone at the keyboard: a held button, movement or
+/// mouse look.
+pub fn has_meaningful_input(action_state: &ActionState<PlayerAction>) -> bool {
+    !action_state.get_pressed().is_empty()
+        || action_state.axis_pair(&PlayerAction::Move) != Vec2::ZERO
+        || action_state.axis_pair(&PlayerAction::Look) != Vec2::ZERO
+}
@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// What a node reports after a tick.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    Success,
+    Failure,
+    /// Still at it; ticked again next frame.
+    Running,
+}
+
+/// The conditions and actions a tree is made of, ticked against its blackboard.
+pub trait Leaf {
+    type Blackboard;
+
+    fn tick(&self, blackboard: &mut Self::Blackboard) -> Status;
+}
+
+/// A node of a behavior tree: composites tick their children in order, decorators change
+/// what their child reports, leaves do the work.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Node<L> {
+    /// Ticks children until one fails or is running, and reports that; succeeds when all
+    /// of them did.
+    Sequence(Vec<Node<L>>),
+    /// Ticks children until one succeeds or is running, and reports that; fails when all
+    /// of them did.
+    Selector(Vec<Node<L>>),
+    /// Swaps success and failure.
+    Invert(Box<Node<L>>),
+    /// Succeeds whether its child succeeded or failed.
+    Succeed(Box<Node<L>>),
+    Leaf(L),
+}
+
+impl<L: Leaf> Node<L> {
+    pub fn tick(&self, blackboard: &mut L::Blackboard) -> Status {
+        match self {
+            Node::Sequence(children) => children
+                .iter()
+                .map(|child| child.tick(blackboard))
+                .find(|status| *status != Status::Success)
+                .unwrap_or(Status::Success),
+            Node::Selector(children) => children
+                .iter()
+                .map(|child| child.tick(blackboard))
+                .find(|status| *status != Status::Failure)
+                .unwrap_or(Status::Failure),
+            Node::Invert(child) => match child.tick(blackboard) {
+                Status::Success => Status::Failure,
+                Status::Failure => Status::Success,
+                Status::Running => Status::Running,
+            },
+            Node::Succeed(child) => match child.tick(blackboard) {
+                Status::Running => Status::Running,
+                _ => Status::Success,
+            },
+            Node::Leaf(leaf) => leaf.tick(blackboard),
+        }
+    }
+}
+
+/// A behavior tree, ticked from its root every frame. Nothing is remembered between
+/// ticks: running nodes are reached again because the blackboard still leads to them,
+/// so a tree reacts as soon as what it reads changes.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct BehaviorTree<L> {
+    pub root: Node<L>,
+}
+
+impl<L: Leaf> BehaviorTree<L> {
+    pub fn tick(&self, blackboard: &mut L::Blackboard) -> Status {
+        self.root.tick(blackboard)
+    }
+}
+
+impl<L: Serialize + for<'de> Deserialize<'de>> BehaviorTree<L> {
+    pub fn to_ron(&self) -> Result<String, String> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, String> {
+        ron::from_str(text).map_err(|e| e.to_string())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Self::from_ron(&text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BehaviorTree, Leaf, Node, Status};
+    use serde::{Deserialize, Serialize};
+
+    /// Reports a fixed status and logs its name.
+    #[derive(Clone, Debug, PartialEq)]
+    struct Probe(&'static str, bool);
+
+    impl Leaf for Probe {
+        type Blackboard = Vec<&'static str>;
+
+        fn tick(&self, ticked: &mut Vec<&'static str>) -> Status {
+            ticked.push(self.0);
+            if self.1 {
+                Status::Success
+            } else {
+                Status::Failure
+            }
+        }
+    }
+
+    #[test]
+    fn composites_stop_at_the_first_deciding_child() {
+        let tree = Node::Selector(vec![
+            Node::Sequence(vec![
+                Node::Leaf(Probe("a", true)),
+                Node::Leaf(Probe("b", false)),
+                Node::Leaf(Probe("never", true)),
+            ]),
+            Node::Invert(Box::new(Node::Leaf(Probe("c", true)))),
+            Node::Succeed(Box::new(Node::Leaf(Probe("d", false)))),
+            Node::Leaf(Probe("never", true)),
+        ]);
+        let mut ticked = Vec::new();
+        assert_eq!(tree.tick(&mut ticked), Status::Success);
+        assert_eq!(ticked, vec!["a", "b", "c", "d"]);
+
+        let mut ticked = Vec::new();
+        assert_eq!(
+            Node::<Probe>::Sequence(vec![]).tick(&mut ticked),
+            Status::Success
+        );
+        assert_eq!(
+            Node::<Probe>::Selector(vec![]).tick(&mut ticked),
+            Status::Failure
+        );
+    }
+
+    #[test]
+    fn trees_round_trip_through_ron() {
+        #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+        enum Named {
+            Wait(f32),
+        }
+        let tree = BehaviorTree {
+            root: Node::Selector(vec![
+                Node::Leaf(Named::Wait(1.5)),
+                Node::Invert(Box::new(Node::Sequence(vec![]))),
+            ]),
+        };
+        let text = tree.to_ron().unwrap();
+        assert_eq!(BehaviorTree::from_ron(&text), Ok(tree));
+        assert!(BehaviorTree::<Named>::from_ron("Selector([Leaf(Run)])").is_err());
+    }
+}
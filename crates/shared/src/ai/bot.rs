@@ -0,0 +1,221 @@
+use bevy::prelude::{Component, Vec3};
+use serde::{Deserialize, Serialize};
+
+use super::behavior_tree::{BehaviorTree, Leaf, Node, Status};
+use crate::perception::Awareness;
+
+/// Move actions succeed once the bot is this close to where they send it.
+pub const ARRIVAL_DISTANCE: f32 = 1.0;
+/// Classic bots retreat to cover below this share of health.
+const CLASSIC_RETREAT_HEALTH: f32 = 0.35;
+
+/// What a bot's tree reads, and the intent it writes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BotBlackboard {
+    pub position: Vec3,
+    /// Health left, from 0.0 to 1.0.
+    pub health_fraction: f32,
+    pub awareness: Awareness,
+    /// Where the bot's squad wants it while the squad engages.
+    pub squad_position: Option<Vec3>,
+    /// Cover from the bot's current threat.
+    pub cover: Option<Vec3>,
+    pub intent: BotIntent,
+}
+
+impl BotBlackboard {
+    pub fn new(
+        position: Vec3,
+        health_fraction: f32,
+        awareness: Awareness,
+        squad_position: Option<Vec3>,
+        cover: Option<Vec3>,
+    ) -> Self {
+        Self {
+            position,
+            health_fraction,
+            awareness,
+            squad_position,
+            cover,
+            intent: BotIntent::Patrol,
+        }
+    }
+
+    fn move_to(&mut self, target: Vec3) -> Status {
+        self.intent = BotIntent::MoveTo(target);
+        if self.position.with_y(0.0).distance(target.with_y(0.0)) <= ARRIVAL_DISTANCE {
+            Status::Success
+        } else {
+            Status::Running
+        }
+    }
+}
+
+/// Where a bot's tree sends it.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum BotIntent {
+    /// Back to its patrol route.
+    #[default]
+    Patrol,
+    MoveTo(Vec3),
+}
+
+/// Conditions and actions of bot behavior trees.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum BotLeaf {
+    /// The bot's squad engages an enemy and has a position for the bot.
+    SquadEngaging,
+    /// An enemy is in sight.
+    EnemyInSight,
+    /// An enemy is remembered but out of sight.
+    EnemyRemembered,
+    /// Less than this share of health is left.
+    HealthBelow(f32),
+    /// Go to the squad's position for the bot.
+    GoToSquadPosition,
+    /// Go to cover from the bot's threat.
+    TakeCover,
+    /// Go at the enemy in sight.
+    Chase,
+    /// Check where the remembered enemy was.
+    Search,
+    /// Go back to the patrol route. Always running.
+    Patrol,
+}
+
+impl Leaf for BotLeaf {
+    type Blackboard = BotBlackboard;
+
+    fn tick(&self, blackboard: &mut BotBlackboard) -> Status {
+        let check = |condition: bool| {
+            if condition {
+                Status::Success
+            } else {
+                Status::Failure
+            }
+        };
+        match (*self, blackboard.awareness) {
+            (BotLeaf::SquadEngaging, _) => check(blackboard.squad_position.is_some()),
+            (BotLeaf::EnemyInSight, awareness) => {
+                check(matches!(awareness, Awareness::Engaging { .. }))
+            }
+            (BotLeaf::EnemyRemembered, awareness) => {
+                check(matches!(awareness, Awareness::Searching { .. }))
+            }
+            (BotLeaf::HealthBelow(share), _) => check(blackboard.health_fraction < share),
+            (BotLeaf::GoToSquadPosition, _) => match blackboard.squad_position {
+                Some(target) => blackboard.move_to(target),
+                None => Status::Failure,
+            },
+            (BotLeaf::TakeCover, _) => match blackboard.cover {
+                Some(cover) => blackboard.move_to(cover),
+                None => Status::Failure,
+            },
+            (BotLeaf::Chase, Awareness::Engaging { position, .. }) => blackboard.move_to(position),
+            (BotLeaf::Search, Awareness::Searching { last_known }) => {
+                blackboard.move_to(last_known)
+            }
+            (BotLeaf::Chase | BotLeaf::Search, _) => Status::Failure,
+            (BotLeaf::Patrol, _) => {
+                blackboard.intent = BotIntent::Patrol;
+                Status::Running
+            }
+        }
+    }
+}
+
+pub type BotBehaviorTree = BehaviorTree<BotLeaf>;
+
+impl BotBehaviorTree {
+    /// Classic bot behavior: retreat to cover when hurt, else take the squad's position
+    /// while it engages, else search for a remembered enemy, else patrol. `assets/ai/classic_bot.ron` is the same tree, to
+    /// start new ones from.
+    pub fn classic() -> Self {
+        BehaviorTree {
+            root: Node::Selector(vec![
+                Node::Sequence(vec![
+                    Node::Leaf(BotLeaf::HealthBelow(CLASSIC_RETREAT_HEALTH)),
+                    Node::Leaf(BotLeaf::TakeCover),
+                ]),
+                Node::Sequence(vec![
+                    Node::Leaf(BotLeaf::SquadEngaging),
+                    Node::Leaf(BotLeaf::GoToSquadPosition),
+                ]),
+                Node::Sequence(vec![
+                    Node::Leaf(BotLeaf::EnemyRemembered),
+                    Node::Leaf(BotLeaf::Search),
+                ]),
+                Node::Leaf(BotLeaf::Patrol),
+            ]),
+        }
+    }
+}
+
+/// Where a bot's tree last sent it, so it is only handed back to its patrol once.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+pub struct BehaviorTarget(pub Option<Vec3>);
+
+#[cfg(test)]
+mod tests {
+    use super::{BotBehaviorTree, BotBlackboard, BotIntent, CLASSIC_RETREAT_HEALTH};
+    use crate::perception::Awareness;
+    use bevy::prelude::{Entity, Vec3};
+
+    fn decide(awareness: Awareness, squad_position: Option<Vec3>) -> BotIntent {
+        let mut blackboard =
+            BotBlackboard::new(Vec3::ZERO, 1.0, awareness, squad_position, Some(Vec3::X));
+        BotBehaviorTree::classic().tick(&mut blackboard);
+        blackboard.intent
+    }
+
+    #[test]
+    fn classic_bots_engage_with_their_squad_then_search_then_patrol() {
+        let spot = Vec3::new(4.0, 0.0, 6.0);
+        let last_known = Vec3::new(-3.0, 0.0, 9.0);
+        let engaging = Awareness::Engaging {
+            enemy: Entity::PLACEHOLDER,
+            position: last_known,
+        };
+        assert_eq!(decide(engaging, Some(spot)), BotIntent::MoveTo(spot));
+        assert_eq!(
+            decide(Awareness::Searching { last_known }, Some(spot)),
+            BotIntent::MoveTo(spot)
+        );
+        assert_eq!(
+            decide(Awareness::Searching { last_known }, None),
+            BotIntent::MoveTo(last_known)
+        );
+        assert_eq!(decide(engaging, None), BotIntent::Patrol);
+        assert_eq!(decide(Awareness::Idle, None), BotIntent::Patrol);
+    }
+
+    #[test]
+    fn hurt_classic_bots_retreat_to_cover_when_there_is_some() {
+        let spot = Vec3::new(4.0, 0.0, 6.0);
+        let cover = Vec3::new(-5.0, 0.0, 2.0);
+        let awareness = Awareness::Searching {
+            last_known: Vec3::new(0.0, 0.0, 12.0),
+        };
+        let hurt = CLASSIC_RETREAT_HEALTH * 0.5;
+        let mut blackboard =
+            BotBlackboard::new(Vec3::ZERO, hurt, awareness, Some(spot), Some(cover));
+        BotBehaviorTree::classic().tick(&mut blackboard);
+        assert_eq!(blackboard.intent, BotIntent::MoveTo(cover));
+
+        let mut blackboard = BotBlackboard::new(Vec3::ZERO, hurt, awareness, Some(spot), None);
+        BotBehaviorTree::classic().tick(&mut blackboard);
+        assert_eq!(blackboard.intent, BotIntent::MoveTo(spot));
+    }
+
+    #[test]
+    fn the_classic_asset_is_the_classic_tree() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../assets/ai/classic_bot.ron"
+        );
+        assert_eq!(
+            BotBehaviorTree::load(std::path::Path::new(path)),
+            Ok(BotBehaviorTree::classic())
+        );
+    }
+}
@@ -0,0 +1,5 @@
+//! Data-defined bot behavior: a small behavior-tree engine, and the leaves and blackboard
+//! bots are driven by. Trees are RON files, so behavior is tweaked without recompiling.
+
+pub mod behavior_tree;
+pub mod bot;
@@ -0,0 +1,126 @@
+use bevy::prelude::{Component, Entity, Resource, Vec3};
+use serde::{Deserialize, Serialize};
+
+/// Widest angle between the crosshair and a target that still pulls the shot.
+pub const AIM_ASSIST_MAX_ANGLE_RADIANS: f32 = 0.07;
+/// Targets further away than this are never assisted.
+pub const AIM_ASSIST_MAX_RANGE: f32 = 30.0;
+/// Fraction of the remaining angle a shot is bent toward its target. Below 1 so assisted
+/// shots still need to be roughly on target.
+pub const AIM_ASSIST_STRENGTH: f32 = 0.5;
+/// Height above a character's origin that shots are pulled toward.
+const AIM_ASSIST_TARGET_HEIGHT: f32 = 1.0;
+
+/// Per-lobby aim assist switch. The server owns it as a resource and applies the assist;
+/// it mirrors the settings onto a replicated entity so the lobby can show them. Ranked
+/// matches never get assist, whatever the lobby asked for.
+#[derive(Resource, Component, Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AimAssistSettings {
+    pub enabled: bool,
+    pub ranked: bool,
+}
+
+impl AimAssistSettings {
+    pub fn is_active(&self) -> bool {
+        self.enabled && !self.ranked
+    }
+
+    pub fn label(&self) -> &'static str {
+        if self.ranked {
+            "Aim assist: off (ranked)"
+        } else if self.enabled {
+            "Aim assist: ON"
+        } else {
+            "Aim assist: off"
+        }
+    }
+}
+
+/// Bend `direction` slightly toward the closest living target near the crosshair. Returns
+/// `direction` unchanged when no target is within the angle and range caps.
+pub fn assist_aim(
+    shooter: Entity,
+    origin: Vec3,
+    direction: Vec3,
+    targets: impl IntoIterator<Item = (Entity, Vec3)>,
+) -> Vec3 {
+    let best = targets
+        .into_iter()
+        .filter(|(entity, _)| *entity != shooter)
+        .filter_map(|(_, position)| {
+            let to_target = position + Vec3::Y * AIM_ASSIST_TARGET_HEIGHT - origin;
+            let distance = to_target.length();
+            if distance <= f32::EPSILON || distance > AIM_ASSIST_MAX_RANGE {
+                return None;
+            }
+            let to_target = to_target / distance;
+            let angle = direction.angle_between(to_target);
+            (angle <= AIM_ASSIST_MAX_ANGLE_RADIANS).then_some((angle, to_target))
+        })
+        .min_by(|(a, _), (b, _)| a.total_cmp(b));
+
+    match best {
+        Some((_, to_target)) => direction
+            .lerp(to_target, AIM_ASSIST_STRENGTH)
+            .normalize_or(direction),
+        None => direction,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AIM_ASSIST_MAX_ANGLE_RADIANS, AIM_ASSIST_MAX_RANGE, AimAssistSettings, assist_aim,
+    };
+    use bevy::prelude::{Vec3, World};
+
+    #[test]
+    fn ranked_lobbies_never_get_assist() {
+        let settings = AimAssistSettings {
+            enabled: true,
+            ranked: true,
+        };
+        assert!(!settings.is_active());
+        assert!(
+            AimAssistSettings {
+                enabled: true,
+                ranked: false
+            }
+            .is_active()
+        );
+        assert!(!AimAssistSettings::default().is_active());
+    }
+
+    #[test]
+    fn shots_bend_toward_nearby_targets_within_caps() {
+        let mut world = World::new();
+        let shooter = world.spawn_empty().id();
+        let target = world.spawn_empty().id();
+        let origin = Vec3::ZERO;
+        let direction = Vec3::NEG_Z;
+        // Target chest sits just right of the crosshair.
+        let ahead = Vec3::new(0.5, -1.0, -15.0);
+
+        let assisted = assist_aim(shooter, origin, direction, [(target, ahead)]);
+        assert!(assisted.x > 0.0, "Shot should bend toward the target");
+        assert!(direction.angle_between(assisted) <= AIM_ASSIST_MAX_ANGLE_RADIANS);
+
+        let far = Vec3::new(0.1, -1.0, -(AIM_ASSIST_MAX_RANGE + 5.0));
+        assert_eq!(
+            assist_aim(shooter, origin, direction, [(target, far)]),
+            direction
+        );
+
+        let wide = Vec3::new(5.0, -1.0, -10.0);
+        assert_eq!(
+            assist_aim(shooter, origin, direction, [(target, wide)]),
+            direction
+        );
+
+        assert_eq!(
+            assist_aim(shooter, origin, direction, [(shooter, ahead)]),
+            direction,
+            "Shooters never assist toward themselves"
+        );
+    }
+}
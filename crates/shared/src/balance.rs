@@ -0,0 +1,162 @@
+use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::components::shield::Shield;
+use crate::components::stamina::{Stamina, StaminaConfig};
+use crate::hearing::HearingBalance;
+
+/// Tunable combat numbers shared by server simulation and client prediction. Balance files
+/// may leave any of them out to keep the default; unknown names are errors so a typo does
+/// not silently leave a default in place.
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BalanceConfig {
+    /// Damage multiplier applied to hits above `headshot_height`.
+    pub headshot_multiplier: f32,
+    /// Height above the target's origin (in meters) where a hit counts as a headshot.
+    pub headshot_height: f32,
+    pub shield: ShieldBalance,
+    pub stamina: StaminaBalance,
+    pub hearing: HearingBalance,
+}
+
+impl Default for BalanceConfig {
+    fn default() -> Self {
+        Self {
+            headshot_multiplier: 2.0,
+            headshot_height: 0.8,
+            shield: ShieldBalance::default(),
+            stamina: StaminaBalance::default(),
+            hearing: HearingBalance::default(),
+        }
+    }
+}
+
+/// Balance file loaded at startup and written back by the tuning tools.
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct BalanceConfigPath(pub PathBuf);
+
+impl Default for BalanceConfigPath {
+    fn default() -> Self {
+        Self(PathBuf::from("balance.ron"))
+    }
+}
+
+impl BalanceConfig {
+    pub fn is_headshot(&self, hit_height_above_origin: f32) -> bool {
+        hit_height_above_origin >= self.headshot_height
+    }
+
+    /// Shield given to players when they spawn.
+    pub fn player_shield(&self) -> Shield {
+        Shield::new(
+            self.shield.max,
+            self.shield.absorption,
+            self.shield.regeneration_rate,
+            self.shield.regeneration_delay,
+        )
+    }
+
+    /// Sprint stamina given to players when they spawn, with how it drains and refills.
+    pub fn player_stamina(&self) -> (Stamina, StaminaConfig) {
+        (
+            Stamina::new(self.stamina.max),
+            StaminaConfig {
+                drain_rate: self.stamina.drain_rate,
+                regeneration_rate: self.stamina.regeneration_rate,
+                recovery_fraction: self.stamina.recovery_fraction,
+                exhaustion_lockout_secs: self.stamina.exhaustion_lockout,
+            },
+        )
+    }
+
+    pub fn to_ron(&self) -> Result<String, String> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, String> {
+        ron::from_str(text).map_err(|e| e.to_string())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ShieldBalance {
+    pub max: f32,
+    /// Fraction of incoming damage the shield absorbs while it has charge.
+    pub absorption: f32,
+    pub regeneration_rate: f32,
+    pub regeneration_delay: f32,
+    /// Fraction of the absorption that headshots ignore.
+    pub headshot_bypass: f32,
+}
+
+impl Default for ShieldBalance {
+    fn default() -> Self {
+        Self {
+            max: 50.0,
+            absorption: 0.6,
+            regeneration_rate: 10.0,
+            regeneration_delay: 4.0,
+            headshot_bypass: 0.5,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StaminaBalance {
+    pub max: f32,
+    pub drain_rate: f32,
+    pub regeneration_rate: f32,
+    /// Fraction of stamina that has to be back before sprinting after running empty.
+    pub recovery_fraction: f32,
+    /// Seconds stamina waits before refilling after running empty.
+    pub exhaustion_lockout: f32,
+}
+
+impl Default for StaminaBalance {
+    fn default() -> Self {
+        let stamina = Stamina::default();
+        let config = StaminaConfig::default();
+        Self {
+            max: stamina.max,
+            drain_rate: config.drain_rate,
+            regeneration_rate: config.regeneration_rate,
+            recovery_fraction: config.recovery_fraction,
+            exhaustion_lockout: config.exhaustion_lockout_secs,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BalanceConfig;
+
+    #[test]
+    fn balance_files_round_trip_and_reject_typos() {
+        let mut config = BalanceConfig::default();
+        config.shield.max = 75.0;
+        config.hearing.threshold = 0.3;
+        config.stamina.exhaustion_lockout = 1.5;
+        assert_eq!(
+            BalanceConfig::from_ron(&config.to_ron().unwrap()),
+            Ok(config)
+        );
+
+        let partial =
+            BalanceConfig::from_ron("// tuned\n(hearing: (gunshot_range: 90.0))\n").unwrap();
+        assert_eq!(partial.hearing.gunshot_range, 90.0);
+        assert_eq!(
+            partial.hearing.threshold,
+            BalanceConfig::default().hearing.threshold
+        );
+        assert_eq!(partial.shield, BalanceConfig::default().shield);
+
+        assert!(BalanceConfig::from_ron("(hearing: (gunshot: 90.0))").is_err());
+        assert!(BalanceConfig::from_ron("(shield: (max: lots))").is_err());
+        assert!(BalanceConfig::from_ron("(shield: (max))").is_err());
+    }
+}
@@ -0,0 +1,530 @@
+use bevy::prelude::{
+    App, Component, Entity, IntoScheduleConfigs, MessageReader, MessageWriter, MinimalPlugins,
+    Query, Res, ResMut, Resource, Time, Update, Vec3,
+};
+use bevy::time::TimeUpdateStrategy;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::balance::BalanceConfig;
+use crate::bots::{BotDifficulty, BotProfile, DEFAULT_BOT_SPEED};
+use crate::components::health::{
+    DamageEvent, DeathEvent, Health, HealthPlugin, process_damage_events,
+};
+use crate::components::weapons::Gun;
+
+const SIM_TICK: Duration = Duration::from_micros(16_667);
+/// Range of hit heights relative to the character origin, feet to top of the head.
+const BODY_BOTTOM: f32 = -0.9;
+const BODY_TOP: f32 = 1.0;
+const TEAM_SPACING: f32 = 2.0;
+/// Engagement distance of a passive bot; reckless bots close in to `CLOSEST_ENGAGEMENT`.
+const FARTHEST_ENGAGEMENT: f32 = 25.0;
+const CLOSEST_ENGAGEMENT: f32 = 6.0;
+
+/// One set of balance numbers compared by a simulation run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BalanceVariant {
+    pub name: String,
+    pub balance: BalanceConfig,
+    pub gun: Gun,
+    /// Bot movement speed in m/s, before the aggression multiplier.
+    pub move_speed: f32,
+}
+
+impl BalanceVariant {
+    pub fn baseline() -> Self {
+        Self {
+            name: "baseline".to_string(),
+            balance: BalanceConfig::default(),
+            gun: Gun::default(),
+            move_speed: DEFAULT_BOT_SPEED,
+        }
+    }
+
+    /// The baseline and a few single-knob changes around it.
+    pub fn defaults() -> Vec<Self> {
+        let baseline = Self::baseline();
+        let mut damage = Self {
+            name: "damage +20%".to_string(),
+            ..baseline.clone()
+        };
+        damage.gun.damage *= 1.2;
+        let movement = Self {
+            name: "movement +30%".to_string(),
+            move_speed: baseline.move_speed * 1.3,
+            ..baseline.clone()
+        };
+        let mut headshots = Self {
+            name: "headshot x1.5".to_string(),
+            ..baseline.clone()
+        };
+        headshots.balance.headshot_multiplier = 1.5;
+
+        vec![baseline, damage, movement, headshots]
+    }
+}
+
+/// Two bot teams fighting each other.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Matchup {
+    pub team_a: BotDifficulty,
+    pub team_b: BotDifficulty,
+    pub team_size: usize,
+}
+
+impl Matchup {
+    pub fn defaults() -> Vec<Self> {
+        let team_size = 3;
+        vec![
+            Self {
+                team_a: BotDifficulty::Normal,
+                team_b: BotDifficulty::Normal,
+                team_size,
+            },
+            Self {
+                team_a: BotDifficulty::Easy,
+                team_b: BotDifficulty::Normal,
+                team_size,
+            },
+            Self {
+                team_a: BotDifficulty::Normal,
+                team_b: BotDifficulty::Hard,
+                team_size,
+            },
+        ]
+    }
+
+    pub fn label(&self) -> String {
+        format!(
+            "{}v{} {} vs {}",
+            self.team_size,
+            self.team_size,
+            self.team_a.label(),
+            self.team_b.label()
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimulationSettings {
+    /// Matches played per variant and matchup.
+    pub matches: usize,
+    /// Match `i` of every variant uses seed `seed + i`, so variants are compared on the
+    /// same dice rolls.
+    pub seed: u64,
+    /// Distance between the two teams when a match starts.
+    pub start_distance: f32,
+    /// Matches still running after this long are draws.
+    pub max_match_secs: f32,
+}
+
+impl Default for SimulationSettings {
+    fn default() -> Self {
+        Self {
+            matches: 100,
+            seed: 0,
+            start_distance: 40.0,
+            max_match_secs: 90.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MatchupStats {
+    pub matches: usize,
+    pub team_a_wins: usize,
+    pub team_b_wins: usize,
+    pub draws: usize,
+    /// Seconds between the first hit on a bot and its death, for every kill.
+    pub times_to_kill: Vec<f32>,
+    pub total_match_secs: f32,
+}
+
+impl MatchupStats {
+    pub fn team_a_win_rate(&self) -> f32 {
+        self.team_a_wins as f32 / self.matches.max(1) as f32
+    }
+
+    pub fn team_b_win_rate(&self) -> f32 {
+        self.team_b_wins as f32 / self.matches.max(1) as f32
+    }
+
+    pub fn average_time_to_kill(&self) -> Option<f32> {
+        (!self.times_to_kill.is_empty())
+            .then(|| self.times_to_kill.iter().sum::<f32>() / self.times_to_kill.len() as f32)
+    }
+
+    pub fn average_match_secs(&self) -> f32 {
+        self.total_match_secs / self.matches.max(1) as f32
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct VariantReport {
+    pub variant: String,
+    pub matchups: Vec<(Matchup, MatchupStats)>,
+}
+
+/// Play `settings.matches` headless matches for every variant and matchup.
+pub fn run_balance_simulation(
+    variants: &[BalanceVariant],
+    matchups: &[Matchup],
+    settings: &SimulationSettings,
+) -> Vec<VariantReport> {
+    variants
+        .iter()
+        .map(|variant| VariantReport {
+            variant: variant.name.clone(),
+            matchups: matchups
+                .iter()
+                .map(|matchup| {
+                    let mut stats = MatchupStats::default();
+                    for index in 0..settings.matches {
+                        let seed = settings.seed.wrapping_add(index as u64);
+                        let result = simulate_match(variant, matchup, settings, seed);
+                        stats.matches += 1;
+                        match result.winner {
+                            Some(Team::A) => stats.team_a_wins += 1,
+                            Some(Team::B) => stats.team_b_wins += 1,
+                            None => stats.draws += 1,
+                        }
+                        stats.times_to_kill.extend(result.times_to_kill);
+                        stats.total_match_secs += result.duration_secs;
+                    }
+                    (*matchup, stats)
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+/// Win rates and TTK per matchup, with each variant's change relative to the first one.
+pub fn format_balance_report(reports: &[VariantReport]) -> String {
+    let baseline = reports.first();
+    let mut lines = vec![format!(
+        "{:<16} {:<26} {:>7} {:>7} {:>6} {:>8} {:>8}",
+        "variant", "matchup", "A win", "B win", "draw", "TTK s", "match s"
+    )];
+
+    for (report_index, report) in reports.iter().enumerate() {
+        for (index, (matchup, stats)) in report.matchups.iter().enumerate() {
+            let delta = match baseline.and_then(|baseline| baseline.matchups.get(index)) {
+                Some((_, base)) if report_index > 0 => format!(
+                    "  (A win {:+.1} pts)",
+                    (stats.team_a_win_rate() - base.team_a_win_rate()) * 100.0
+                ),
+                _ => String::new(),
+            };
+            lines.push(format!(
+                "{:<16} {:<26} {:>6.1}% {:>6.1}% {:>6} {:>8} {:>8.1}{}",
+                report.variant,
+                matchup.label(),
+                stats.team_a_win_rate() * 100.0,
+                stats.team_b_win_rate() * 100.0,
+                stats.draws,
+                stats
+                    .average_time_to_kill()
+                    .map_or("-".to_string(), |ttk| format!("{:.2}", ttk)),
+                stats.average_match_secs(),
+                delta
+            ));
+        }
+    }
+
+    lines.join("\n")
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Team {
+    A,
+    B,
+}
+
+struct MatchResult {
+    winner: Option<Team>,
+    duration_secs: f32,
+    times_to_kill: Vec<f32>,
+}
+
+#[derive(Component)]
+struct SimCombatant {
+    team: Team,
+    profile: BotProfile,
+    gun: Gun,
+    position: Vec3,
+    target: Option<Entity>,
+    reaction_remaining: f32,
+}
+
+#[derive(Resource)]
+struct SimContext {
+    rng: StdRng,
+    move_speed: f32,
+    first_hits: HashMap<Entity, f32>,
+    times_to_kill: Vec<f32>,
+}
+
+fn simulate_match(
+    variant: &BalanceVariant,
+    matchup: &Matchup,
+    settings: &SimulationSettings,
+    seed: u64,
+) -> MatchResult {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins);
+    app.add_plugins(HealthPlugin);
+    app.insert_resource(variant.balance.clone());
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(SIM_TICK));
+    app.insert_resource(SimContext {
+        rng: StdRng::seed_from_u64(seed),
+        move_speed: variant.move_speed,
+        first_hits: HashMap::new(),
+        times_to_kill: Vec::new(),
+    });
+    app.add_systems(
+        Update,
+        (
+            simulate_combatants.before(process_damage_events),
+            record_times_to_kill.after(process_damage_events),
+        ),
+    );
+
+    for (team, difficulty, x) in [
+        (Team::A, matchup.team_a, 0.0),
+        (Team::B, matchup.team_b, settings.start_distance),
+    ] {
+        for slot in 0..matchup.team_size {
+            app.world_mut().spawn((
+                SimCombatant {
+                    team,
+                    profile: difficulty.profile(),
+                    gun: variant.gun.clone(),
+                    position: Vec3::new(x, 0.0, slot as f32 * TEAM_SPACING),
+                    target: None,
+                    reaction_remaining: 0.0,
+                },
+                Health {
+                    can_regenerate: false,
+                    ..Health::basic()
+                },
+                variant.balance.player_shield(),
+            ));
+        }
+    }
+
+    let mut winner = None;
+    let mut elapsed = 0.0;
+    while elapsed < settings.max_match_secs {
+        app.update();
+        elapsed = app.world().resource::<Time>().elapsed_secs();
+
+        let mut combatants = app.world_mut().query::<(&SimCombatant, &Health)>();
+        let mut alive = |team: Team| {
+            combatants
+                .iter(app.world())
+                .any(|(combatant, health)| combatant.team == team && !health.is_dead)
+        };
+        match (alive(Team::A), alive(Team::B)) {
+            (true, true) => continue,
+            (true, false) => winner = Some(Team::A),
+            (false, true) => winner = Some(Team::B),
+            (false, false) => {}
+        }
+        break;
+    }
+
+    MatchResult {
+        winner,
+        duration_secs: elapsed,
+        times_to_kill: std::mem::take(
+            &mut app.world_mut().resource_mut::<SimContext>().times_to_kill,
+        ),
+    }
+}
+
+/// Scripted bot behavior: pick the closest enemy, close in to a distance that depends on
+/// aggression, wait out the reaction time, then fire whenever the gun allows.
+fn simulate_combatants(
+    time: Res<Time>,
+    balance: Res<BalanceConfig>,
+    mut context: ResMut<SimContext>,
+    mut combatants: Query<(Entity, &mut SimCombatant, &Health)>,
+    mut damage_writer: MessageWriter<DamageEvent>,
+) {
+    let delta = time.delta();
+    let delta_secs = time.delta_secs();
+    let now = time.elapsed_secs();
+    let snapshot: Vec<(Entity, Team, Vec3)> = combatants
+        .iter()
+        .filter(|(_, _, health)| !health.is_dead)
+        .map(|(entity, combatant, _)| (entity, combatant.team, combatant.position))
+        .collect();
+
+    for (entity, mut combatant, health) in combatants.iter_mut() {
+        if health.is_dead {
+            continue;
+        }
+
+        let Some((target, target_position)) = snapshot
+            .iter()
+            .filter(|(_, team, _)| *team != combatant.team)
+            .map(|(enemy, _, position)| (*enemy, *position))
+            .min_by(|(_, a), (_, b)| {
+                a.distance_squared(combatant.position)
+                    .total_cmp(&b.distance_squared(combatant.position))
+            })
+        else {
+            continue;
+        };
+
+        if combatant.target != Some(target) {
+            combatant.target = Some(target);
+            combatant.reaction_remaining = combatant.profile.reaction_time;
+        }
+
+        let aggression = combatant.profile.aggression_level.clamp(0.0, 1.0);
+        let engagement =
+            FARTHEST_ENGAGEMENT + (CLOSEST_ENGAGEMENT - FARTHEST_ENGAGEMENT) * aggression;
+        let to_target = target_position - combatant.position;
+        let distance = to_target.length();
+        if distance > engagement {
+            let step =
+                context.move_speed * combatant.profile.patrol_speed_multiplier() * delta_secs;
+            combatant.position += to_target.normalize_or_zero() * step.min(distance - engagement);
+        }
+
+        combatant.reaction_remaining -= delta_secs;
+        combatant.gun.cooldown.tick(delta);
+        combatant.gun.tick_reload(delta);
+        if combatant.reaction_remaining > 0.0
+            || combatant.gun.is_reloading
+            || distance > combatant.gun.range
+            || !combatant.gun.cooldown.is_finished()
+        {
+            continue;
+        }
+        if combatant.gun.ammo_in_magazine == 0 {
+            combatant.gun.start_reload();
+            continue;
+        }
+
+        combatant.gun.ammo_in_magazine -= 1;
+        combatant.gun.cooldown.reset();
+
+        // Accuracy falls off to half at the gun's maximum range.
+        let hit_chance = combatant.profile.accuracy * (1.0 - 0.5 * distance / combatant.gun.range);
+        if context.rng.random::<f32>() >= hit_chance {
+            continue;
+        }
+
+        let hit_height = context.rng.random_range(BODY_BOTTOM..BODY_TOP);
+        context.first_hits.entry(target).or_insert(now);
+        damage_writer.write(DamageEvent {
+            target,
+            amount: combatant.gun.damage,
+            source: Some(entity),
+            penetration: combatant.gun.penetration,
+            headshot: balance.is_headshot(hit_height),
+        });
+    }
+}
+
+fn record_times_to_kill(
+    time: Res<Time>,
+    mut death_events: MessageReader<DeathEvent>,
+    mut context: ResMut<SimContext>,
+) {
+    let now = time.elapsed_secs();
+    for event in death_events.read() {
+        if let Some(first_hit) = context.first_hits.remove(&event.target) {
+            context.times_to_kill.push(now - first_hit);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BalanceVariant, Matchup, SimulationSettings, run_balance_simulation};
+    use crate::bots::{BotDifficulty, BotProfile};
+
+    fn settings(matches: usize) -> SimulationSettings {
+        SimulationSettings {
+            matches,
+            seed: 7,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn simulation_is_deterministic_per_seed() {
+        let variants = [BalanceVariant::baseline()];
+        let matchups = [Matchup {
+            team_a: BotDifficulty::Normal,
+            team_b: BotDifficulty::Normal,
+            team_size: 2,
+        }];
+
+        let first = run_balance_simulation(&variants, &matchups, &settings(5));
+        let second = run_balance_simulation(&variants, &matchups, &settings(5));
+        assert_eq!(first, second);
+
+        let stats = &first[0].matchups[0].1;
+        assert_eq!(stats.matches, 5);
+        assert_eq!(stats.team_a_wins + stats.team_b_wins + stats.draws, 5);
+        assert!(stats.average_time_to_kill().is_some());
+    }
+
+    #[test]
+    fn better_profiles_win_more() {
+        let sharpshooter = BotDifficulty::Custom(BotProfile {
+            reaction_time: 0.1,
+            aggression_level: 0.5,
+            accuracy: 1.0,
+        });
+        let matchups = [Matchup {
+            team_a: BotDifficulty::Easy,
+            team_b: sharpshooter,
+            team_size: 2,
+        }];
+
+        let reports =
+            run_balance_simulation(&[BalanceVariant::baseline()], &matchups, &settings(8));
+        let stats = &reports[0].matchups[0].1;
+        assert!(
+            stats.team_b_win_rate() > stats.team_a_win_rate(),
+            "sharpshooters should win most matches: {:?}",
+            stats
+        );
+    }
+
+    #[test]
+    fn more_damage_kills_faster() {
+        let variants = BalanceVariant::defaults();
+        let damage = variants
+            .iter()
+            .find(|variant| variant.name.starts_with("damage"))
+            .cloned()
+            .expect("default variants include a damage change");
+        let matchups = [Matchup {
+            team_a: BotDifficulty::Hard,
+            team_b: BotDifficulty::Hard,
+            team_size: 1,
+        }];
+
+        let reports = run_balance_simulation(
+            &[BalanceVariant::baseline(), damage],
+            &matchups,
+            &settings(10),
+        );
+        let ttk = |index: usize| {
+            reports[index].matchups[0]
+                .1
+                .average_time_to_kill()
+                .expect("hard bots should score kills")
+        };
+        assert!(ttk(1) < ttk(0), "baseline {} vs damage {}", ttk(0), ttk(1));
+    }
+}
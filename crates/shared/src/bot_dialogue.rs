@@ -0,0 +1,149 @@
+//! Bot taunts and callouts. Classic AI bots say a short line in chat when they get a kill
+//! or spot a player. The server asks a language model for lines, keeps the ones it gets
+//! and falls back on them, or on the templates here, when the model is slow or absent.
+//!
+//! Lines are reused across bots and targets, so prompts and templates name nobody.
+
+use std::collections::HashMap;
+
+/// Longest line said in chat, in characters.
+pub const BOT_LINE_MAX_CHARS: usize = 80;
+/// Lines kept per trigger. Once full, bots reuse them instead of asking the model.
+pub const DIALOGUE_CACHE_LINES: usize = 8;
+
+/// What makes a bot speak.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DialogueTrigger {
+    Kill,
+    Spotted,
+}
+
+impl DialogueTrigger {
+    /// Prompt for a language model, on a single line.
+    pub fn prompt(self) -> String {
+        let situation = match self {
+            DialogueTrigger::Kill => "You just eliminated an enemy player.",
+            DialogueTrigger::Spotted => "You just spotted an enemy player.",
+        };
+        format!(
+            "You are a cocky bot in a multiplayer shooter. {} Reply with one short line of \
+             trash talk for the match chat, under twelve words, naming nobody. Line:",
+            situation
+        )
+    }
+
+    pub fn template_lines(self) -> &'static [&'static str] {
+        match self {
+            DialogueTrigger::Kill => &[
+                "Too easy.",
+                "Respawn and try again.",
+                "Was that your best?",
+                "Another one for the scoreboard.",
+                "You should have stayed in the lobby.",
+            ],
+            DialogueTrigger::Spotted => &[
+                "I see you.",
+                "Contact! Moving in.",
+                "Nowhere to hide.",
+                "Found one.",
+                "You picked the wrong corridor.",
+            ],
+        }
+    }
+}
+
+/// The line in a model reply: its first non-empty line without surrounding quotes, cut to
+/// [`BOT_LINE_MAX_CHARS`]. `None` when there is nothing to say.
+pub fn clean_line(reply: &str) -> Option<String> {
+    let line = reply.lines().map(str::trim).find(|line| !line.is_empty())?;
+    let line = line.trim_matches(|c: char| c == '"' || c == '\'').trim();
+    if line.is_empty() {
+        return None;
+    }
+    if line.chars().count() <= BOT_LINE_MAX_CHARS {
+        return Some(line.to_string());
+    }
+    // Cut between words where possible.
+    let cut: String = line.chars().take(BOT_LINE_MAX_CHARS - 1).collect();
+    let cut = cut
+        .rsplit_once(' ')
+        .map_or(cut.as_str(), |(words, _)| words);
+    Some(format!("{}…", cut.trim_end()))
+}
+
+/// Lines said for each trigger, handed out in turn.
+#[derive(Clone, Debug, Default)]
+pub struct DialogueCache {
+    lines: HashMap<DialogueTrigger, Vec<String>>,
+    next: HashMap<DialogueTrigger, usize>,
+}
+
+impl DialogueCache {
+    /// Keeps `line` for `trigger`, replacing the oldest once full.
+    pub fn insert(&mut self, trigger: DialogueTrigger, line: String) {
+        let lines = self.lines.entry(trigger).or_default();
+        if lines.contains(&line) {
+            return;
+        }
+        if lines.len() >= DIALOGUE_CACHE_LINES {
+            lines.remove(0);
+        }
+        lines.push(line);
+    }
+
+    pub fn is_full(&self, trigger: DialogueTrigger) -> bool {
+        self.lines
+            .get(&trigger)
+            .is_some_and(|lines| lines.len() >= DIALOGUE_CACHE_LINES)
+    }
+
+    /// The next kept line for `trigger`, or the next template line before any is kept.
+    pub fn next_line(&mut self, trigger: DialogueTrigger) -> String {
+        let next = self.next.entry(trigger).or_default();
+        let line = match self.lines.get(&trigger).filter(|lines| !lines.is_empty()) {
+            Some(lines) => lines[*next % lines.len()].clone(),
+            None => {
+                let templates = trigger.template_lines();
+                templates[*next % templates.len()].to_string()
+            }
+        };
+        *next += 1;
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BOT_LINE_MAX_CHARS, DialogueCache, DialogueTrigger, clean_line};
+
+    #[test]
+    fn replies_are_cleaned_and_cached_lines_replace_templates() {
+        assert_eq!(
+            clean_line("\n  \"Too slow, rookie.\"\nExtra text"),
+            Some("Too slow, rookie.".to_string())
+        );
+        assert_eq!(clean_line(" \n\"\" "), None);
+        let long = clean_line(&"word ".repeat(40)).unwrap();
+        assert!(long.chars().count() <= BOT_LINE_MAX_CHARS);
+        assert!(long.ends_with("word…"));
+
+        let mut cache = DialogueCache::default();
+        let templates = DialogueTrigger::Kill.template_lines();
+        assert_eq!(cache.next_line(DialogueTrigger::Kill), templates[0]);
+        assert_eq!(cache.next_line(DialogueTrigger::Kill), templates[1]);
+        assert_eq!(
+            cache.next_line(DialogueTrigger::Spotted),
+            DialogueTrigger::Spotted.template_lines()[0]
+        );
+
+        cache.insert(DialogueTrigger::Kill, "Sit down.".to_string());
+        cache.insert(DialogueTrigger::Kill, "Sit down.".to_string());
+        assert_eq!(cache.next_line(DialogueTrigger::Kill), "Sit down.");
+        assert!(!cache.is_full(DialogueTrigger::Kill));
+        for index in 0..10 {
+            cache.insert(DialogueTrigger::Kill, format!("Line {}", index));
+        }
+        assert!(cache.is_full(DialogueTrigger::Kill));
+        assert!(!cache.is_full(DialogueTrigger::Spotted));
+    }
+}
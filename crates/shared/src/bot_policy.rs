@@ -0,0 +1,339 @@
+use bevy::prelude::{Component, Vec2, Vec3};
+
+/// Inputs fed to a bot policy each tick, see [`BotObservation::to_vector`].
+pub const OBSERVATION_SIZE: usize = 6;
+/// Planar movement direction (x, z) produced by a bot policy.
+pub const ACTION_SIZE: usize = 2;
+/// Distance used to normalize target offsets; targets further away saturate.
+pub const OBSERVATION_RANGE: f32 = 30.0;
+
+const CHECKPOINT_HEADER: &str = "bot_policy v1";
+const TANH_OUTPUT_LINE: &str = "output tanh";
+
+/// Marks a bot whose movement may be driven by a trained policy instead of its patrol.
+/// `active` is cleared whenever the policy could not run this tick, which hands the bot
+/// back to the scripted navigation systems.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+pub struct PolicyControlled {
+    pub active: bool,
+}
+
+/// What a policy-driven bot knows about the world.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BotObservation {
+    /// Offset from the bot to the closest living player, if any.
+    pub target_offset: Option<Vec3>,
+    pub health_fraction: f32,
+    /// Bot yaw in radians.
+    pub yaw: f32,
+}
+
+impl BotObservation {
+    pub fn to_vector(&self) -> [f32; OBSERVATION_SIZE] {
+        let (offset, has_target) = match self.target_offset {
+            Some(offset) => (
+                Vec2::new(offset.x, offset.z).clamp_length_max(OBSERVATION_RANGE)
+                    / OBSERVATION_RANGE,
+                1.0,
+            ),
+            None => (Vec2::ZERO, 0.0),
+        };
+
+        [
+            offset.x,
+            offset.y,
+            has_target,
+            self.health_fraction.clamp(0.0, 1.0),
+            self.yaw.sin(),
+            self.yaw.cos(),
+        ]
+    }
+}
+
+/// Fully connected layer. Weights are stored row-major, one row of inputs per output.
+#[derive(Clone, Debug, PartialEq)]
+struct DenseLayer {
+    inputs: usize,
+    weights: Vec<f32>,
+    bias: Vec<f32>,
+}
+
+impl DenseLayer {
+    fn forward(&self, input: &[f32]) -> Vec<f32> {
+        self.weights
+            .chunks(self.inputs)
+            .zip(&self.bias)
+            .map(|(row, bias)| row.iter().zip(input).map(|(w, x)| w * x).sum::<f32>() + bias)
+            .collect()
+    }
+}
+
+/// Perceptron with ReLU hidden layers, the shape trained by the `reinforcement_learning`
+/// crate: one hidden layer for its `SimpleNetwork`, any number for PPO policies, whose
+/// move stick also goes through `tanh`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BotPolicy {
+    layers: Vec<DenseLayer>,
+    tanh_output: bool,
+}
+
+impl BotPolicy {
+    /// A policy with one hidden layer of `hidden_size` units.
+    pub fn new(
+        hidden_size: usize,
+        weights1: Vec<f32>,
+        bias1: Vec<f32>,
+        weights2: Vec<f32>,
+        bias2: Vec<f32>,
+    ) -> Result<Self, String> {
+        Self::from_layers(
+            &[OBSERVATION_SIZE, hidden_size, ACTION_SIZE],
+            vec![(weights1, bias1), (weights2, bias2)],
+        )
+    }
+
+    /// A policy with layers of `sizes`, from `OBSERVATION_SIZE` inputs to `ACTION_SIZE`
+    /// outputs, and the weights and bias of each layer between them.
+    pub fn from_layers(sizes: &[usize], layers: Vec<(Vec<f32>, Vec<f32>)>) -> Result<Self, String> {
+        let (Some(&inputs), Some(&outputs)) = (sizes.first(), sizes.last()) else {
+            return Err("layers should list input, hidden and output sizes".to_string());
+        };
+        if inputs != OBSERVATION_SIZE || outputs != ACTION_SIZE {
+            return Err(format!(
+                "policy maps {} inputs to {} outputs, expected {} to {}",
+                inputs, outputs, OBSERVATION_SIZE, ACTION_SIZE
+            ));
+        }
+        if sizes.contains(&0) {
+            return Err("layers should not be empty".to_string());
+        }
+        if layers.len() + 1 != sizes.len() {
+            return Err(format!(
+                "{} layer sizes need {} layers, got {}",
+                sizes.len(),
+                sizes.len().saturating_sub(1),
+                layers.len()
+            ));
+        }
+
+        let layers = layers
+            .into_iter()
+            .zip(sizes.windows(2))
+            .enumerate()
+            .map(|(index, ((weights, bias), pair))| {
+                let [inputs, outputs] = [pair[0], pair[1]];
+                for (name, actual, expected) in [
+                    ("weights", weights.len(), inputs * outputs),
+                    ("bias", bias.len(), outputs),
+                ] {
+                    if actual != expected {
+                        return Err(format!(
+                            "{}{} has {} values, expected {}",
+                            name,
+                            index + 1,
+                            actual,
+                            expected
+                        ));
+                    }
+                }
+                Ok(DenseLayer {
+                    inputs,
+                    weights,
+                    bias,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+
+        Ok(Self {
+            layers,
+            tanh_output: false,
+        })
+    }
+
+    /// Squash the outputs with `tanh`, like the move stick of a PPO policy.
+    pub fn with_tanh_output(mut self) -> Self {
+        self.tanh_output = true;
+        self
+    }
+
+    /// Parse a checkpoint written by [`BotPolicy::to_checkpoint`]: a header line, the layer
+    /// sizes, an optional `output tanh` line, then one line of whitespace-separated values
+    /// per weight and bias.
+    pub fn from_checkpoint(text: &str) -> Result<Self, String> {
+        let mut lines = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .peekable();
+
+        if lines.next() != Some(CHECKPOINT_HEADER) {
+            return Err(format!("missing '{}' header", CHECKPOINT_HEADER));
+        }
+
+        let sizes = parse_values::<usize>(lines.next(), "layers")?;
+        let tanh_output = lines.next_if_eq(&TANH_OUTPUT_LINE).is_some();
+        let layers = (1..sizes.len())
+            .map(|index| {
+                Ok((
+                    parse_values(lines.next(), &format!("weights{}", index))?,
+                    parse_values(lines.next(), &format!("bias{}", index))?,
+                ))
+            })
+            .collect::<Result<_, String>>()?;
+
+        let policy = Self::from_layers(&sizes, layers)?;
+        Ok(Self {
+            tanh_output,
+            ..policy
+        })
+    }
+
+    pub fn to_checkpoint(&self) -> String {
+        let line = |name: String, values: &[f32]| {
+            let values: Vec<String> = values.iter().map(f32::to_string).collect();
+            format!("{} {}\n", name, values.join(" "))
+        };
+
+        let sizes: Vec<String> = std::iter::once(OBSERVATION_SIZE)
+            .chain(self.layers.iter().map(|layer| layer.bias.len()))
+            .map(|size| size.to_string())
+            .collect();
+        let mut text = format!("{}\nlayers {}\n", CHECKPOINT_HEADER, sizes.join(" "));
+        if self.tanh_output {
+            text.push_str(TANH_OUTPUT_LINE);
+            text.push('\n');
+        }
+        for (index, layer) in self.layers.iter().enumerate() {
+            text.push_str(&line(format!("weights{}", index + 1), &layer.weights));
+            text.push_str(&line(format!("bias{}", index + 1), &layer.bias));
+        }
+        text
+    }
+
+    pub fn forward(&self, input: &[f32; OBSERVATION_SIZE]) -> [f32; ACTION_SIZE] {
+        let mut values = input.to_vec();
+        let last = self.layers.len() - 1;
+        for (index, layer) in self.layers.iter().enumerate() {
+            values = layer.forward(&values);
+            if index < last {
+                values.iter_mut().for_each(|value| *value = value.max(0.0));
+            } else if self.tanh_output {
+                values.iter_mut().for_each(|value| *value = value.tanh());
+            }
+        }
+
+        let mut output = [0.0; ACTION_SIZE];
+        output.copy_from_slice(&values);
+        output
+    }
+
+    /// Planar direction the bot should move in, or `None` when the policy wants to stand
+    /// still.
+    pub fn act(&self, observation: &BotObservation) -> Option<Vec3> {
+        let [x, z] = self.forward(&observation.to_vector());
+        let direction = Vec3::new(x, 0.0, z);
+        (direction.is_finite() && direction.length_squared() > 1e-4).then(|| direction.normalize())
+    }
+}
+
+/// Values following `name` on a checkpoint line.
+fn parse_values<T: std::str::FromStr>(line: Option<&str>, name: &str) -> Result<Vec<T>, String> {
+    let mut tokens = line
+        .ok_or_else(|| format!("missing '{}' line", name))?
+        .split_whitespace();
+    if tokens.next() != Some(name) {
+        return Err(format!("expected '{}' line", name));
+    }
+
+    tokens
+        .map(|token| {
+            token
+                .parse()
+                .map_err(|_| format!("invalid value '{}' in '{}'", token, name))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ACTION_SIZE, BotObservation, BotPolicy, OBSERVATION_SIZE};
+    use bevy::prelude::Vec3;
+
+    /// Policy that walks straight toward the target: the hidden layer passes the
+    /// normalized offset through (split into positive and negative parts for the ReLU).
+    fn chase_policy() -> BotPolicy {
+        let hidden = 4;
+        let mut weights1 = vec![0.0; hidden * OBSERVATION_SIZE];
+        weights1[0] = 1.0;
+        weights1[OBSERVATION_SIZE] = -1.0;
+        weights1[2 * OBSERVATION_SIZE + 1] = 1.0;
+        weights1[3 * OBSERVATION_SIZE + 1] = -1.0;
+        let weights2 = vec![1.0, -1.0, 0.0, 0.0, 0.0, 0.0, 1.0, -1.0];
+
+        BotPolicy::new(
+            hidden,
+            weights1,
+            vec![0.0; hidden],
+            weights2,
+            vec![0.0; ACTION_SIZE],
+        )
+        .expect("chase policy should have consistent sizes")
+    }
+
+    #[test]
+    fn chase_policy_moves_toward_target_and_idles_without_one() {
+        let policy = chase_policy();
+        let toward = policy
+            .act(&BotObservation {
+                target_offset: Some(Vec3::new(-6.0, 0.0, 8.0)),
+                health_fraction: 1.0,
+                yaw: 0.0,
+            })
+            .expect("policy should move toward a visible target");
+
+        assert!((toward - Vec3::new(-0.6, 0.0, 0.8)).length() < 1e-5);
+        assert_eq!(policy.act(&BotObservation::default()), None);
+    }
+
+    #[test]
+    fn checkpoint_round_trips() {
+        let policy = chase_policy();
+        let restored = BotPolicy::from_checkpoint(&policy.to_checkpoint())
+            .expect("written checkpoint should parse");
+        assert_eq!(restored, policy);
+    }
+
+    #[test]
+    fn deeper_policies_with_tanh_outputs_round_trip() {
+        let sizes = [OBSERVATION_SIZE, 3, 4, ACTION_SIZE];
+        let layers = sizes
+            .windows(2)
+            .map(|pair| (vec![0.1; pair[0] * pair[1]], vec![0.0; pair[1]]))
+            .collect();
+        let policy = BotPolicy::from_layers(&sizes, layers)
+            .expect("sizes should match")
+            .with_tanh_output();
+
+        // 0.6 in each first hidden unit, 0.18 in each second one, then tanh(0.072).
+        let output = policy.forward(&[1.0; OBSERVATION_SIZE]);
+        assert!(
+            output
+                .iter()
+                .all(|value| (value - 0.072f32.tanh()).abs() < 1e-5)
+        );
+        let restored = BotPolicy::from_checkpoint(&policy.to_checkpoint())
+            .expect("written checkpoint should parse");
+        assert_eq!(restored, policy);
+    }
+
+    #[test]
+    fn malformed_checkpoints_are_rejected() {
+        assert!(BotPolicy::from_checkpoint("").is_err());
+        assert!(BotPolicy::from_checkpoint("bot_policy v1\nlayers 8 4 2\n").is_err());
+
+        let truncated = chase_policy()
+            .to_checkpoint()
+            .replace("bias2 0 0", "bias2 0");
+        assert!(BotPolicy::from_checkpoint(&truncated).is_err());
+    }
+}
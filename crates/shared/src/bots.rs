@@ -0,0 +1,227 @@
+use avian3d::prelude::{LinearVelocity, Position, Rotation};
+use bevy::prelude::{Commands, Component, Entity, Name, Quat, Resource, Vec3};
+use lightyear::prelude::{InterpolationTarget, NetworkTarget, Replicate};
+use serde::{Deserialize, Serialize};
+
+use crate::components::health::{Health, Respawnable};
+use crate::entities::NpcPhysicsBundle;
+use crate::navigation::{DEFAULT_NAVMESH_PATROL_RADIUS, NavMeshPatrol, setup_patrol};
+use crate::protocol::CharacterMarker;
+
+/// Bots spawned per match when nothing else is configured.
+pub const DEFAULT_BOT_COUNT: usize = 6;
+/// Most bots a match runs with, however they are added.
+pub const MAX_BOT_COUNT: usize = 32;
+pub const DEFAULT_BOT_SPEED: f32 = 3.0;
+const DEFAULT_BOT_RESPAWN_DELAY: f32 = 4.0;
+
+/// Combat tuning for a single bot.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BotProfile {
+    /// Seconds between spotting a target and reacting to it.
+    pub reaction_time: f32,
+    /// 0.0 (passive) to 1.0 (reckless).
+    pub aggression_level: f32,
+    /// 0.0 (never hits) to 1.0 (always hits).
+    pub accuracy: f32,
+}
+
+impl BotProfile {
+    /// Aggressive bots move faster between patrol points.
+    pub fn patrol_speed_multiplier(&self) -> f32 {
+        0.85 + 0.3 * self.aggression_level.clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum BotDifficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Custom(BotProfile),
+}
+
+impl BotDifficulty {
+    pub fn profile(&self) -> BotProfile {
+        match self {
+            BotDifficulty::Easy => BotProfile {
+                reaction_time: 0.6,
+                aggression_level: 0.3,
+                accuracy: 0.35,
+            },
+            BotDifficulty::Normal => BotProfile {
+                reaction_time: 0.35,
+                aggression_level: 0.5,
+                accuracy: 0.6,
+            },
+            BotDifficulty::Hard => BotProfile {
+                reaction_time: 0.18,
+                aggression_level: 0.8,
+                accuracy: 0.85,
+            },
+            BotDifficulty::Custom(profile) => *profile,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            BotDifficulty::Easy => "Easy",
+            BotDifficulty::Normal => "Normal",
+            BotDifficulty::Hard => "Hard",
+            BotDifficulty::Custom(_) => "Custom",
+        }
+    }
+}
+
+/// Per-match bot configuration. The server owns it as a resource and mirrors it onto a
+/// replicated entity so clients can display it in the lobby.
+#[derive(Resource, Component, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MatchBotSettings {
+    pub bot_count: usize,
+    pub difficulty: BotDifficulty,
+}
+
+impl Default for MatchBotSettings {
+    fn default() -> Self {
+        Self {
+            bot_count: DEFAULT_BOT_COUNT,
+            difficulty: BotDifficulty::default(),
+        }
+    }
+}
+
+/// Builder for a patrolling, server-replicated AI bot.
+pub struct ClassicAiBotBuilder {
+    name: String,
+    position: Vec3,
+    difficulty: BotDifficulty,
+    patrol_points: Vec<Vec3>,
+    speed: f32,
+    respawn_delay: f32,
+}
+
+/// Starts building a bot at `position`; finish with [`ClassicAiBotBuilder::spawn`].
+pub fn spawn_classic_ai_bot(name: impl Into<String>, position: Vec3) -> ClassicAiBotBuilder {
+    ClassicAiBotBuilder {
+        name: name.into(),
+        position,
+        difficulty: BotDifficulty::default(),
+        patrol_points: Vec::new(),
+        speed: DEFAULT_BOT_SPEED,
+        respawn_delay: DEFAULT_BOT_RESPAWN_DELAY,
+    }
+}
+
+impl ClassicAiBotBuilder {
+    pub fn difficulty(mut self, difficulty: BotDifficulty) -> Self {
+        self.difficulty = difficulty;
+        self
+    }
+
+    /// Patrol through `points`. Without one, the bot patrols points around its spawn
+    /// sampled on the navmesh once it is baked.
+    pub fn patrol(mut self, points: Vec<Vec3>) -> Self {
+        self.patrol_points = points;
+        self
+    }
+
+    /// Base patrol speed, scaled by the difficulty's aggression.
+    pub fn speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    pub fn respawn_delay(mut self, seconds: f32) -> Self {
+        self.respawn_delay = seconds;
+        self
+    }
+
+    pub fn spawn(self, commands: &mut Commands) -> Entity {
+        let profile = self.difficulty.profile();
+        let bot_entity = commands
+            .spawn((
+                Name::new(self.name),
+                Position::new(self.position),
+                Rotation::from(Quat::IDENTITY),
+                LinearVelocity::default(),
+                Health::basic(),
+                Respawnable::with_position(self.respawn_delay, self.position),
+                Replicate::to_clients(NetworkTarget::All),
+                InterpolationTarget::to_clients(NetworkTarget::All),
+                CharacterMarker,
+                NpcPhysicsBundle::default(),
+                profile,
+            ))
+            .id();
+
+        let speed = self.speed * profile.patrol_speed_multiplier();
+        if self.patrol_points.is_empty() {
+            commands.entity(bot_entity).insert(NavMeshPatrol {
+                radius: DEFAULT_NAVMESH_PATROL_RADIUS,
+                speed,
+            });
+        } else {
+            setup_patrol(commands, bot_entity, self.patrol_points, speed);
+        }
+
+        bot_entity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BotDifficulty, BotProfile, MatchBotSettings, spawn_classic_ai_bot};
+    use crate::navigation::SimpleNavigationAgent;
+    use bevy::prelude::{App, Commands, MinimalPlugins, Update, Vec3};
+    use lightyear::prelude::server::ServerPlugins;
+    use std::time::Duration;
+
+    #[test]
+    fn presets_scale_with_difficulty() {
+        let easy = BotDifficulty::Easy.profile();
+        let normal = BotDifficulty::Normal.profile();
+        let hard = BotDifficulty::Hard.profile();
+
+        assert!(easy.reaction_time > normal.reaction_time);
+        assert!(normal.reaction_time > hard.reaction_time);
+        assert!(easy.accuracy < normal.accuracy && normal.accuracy < hard.accuracy);
+        assert_eq!(
+            MatchBotSettings::default().difficulty,
+            BotDifficulty::Normal
+        );
+    }
+
+    #[test]
+    fn builder_applies_custom_profile_and_patrol_speed() {
+        let custom = BotProfile {
+            reaction_time: 0.1,
+            aggression_level: 1.0,
+            accuracy: 0.95,
+        };
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(ServerPlugins {
+            tick_duration: Duration::from_millis(16),
+        });
+        app.add_systems(Update, move |mut commands: Commands| {
+            spawn_classic_ai_bot("Bot", Vec3::new(0.0, 1.0, 0.0))
+                .difficulty(BotDifficulty::Custom(custom))
+                .patrol(vec![Vec3::new(5.0, 1.0, 0.0), Vec3::new(-5.0, 1.0, 0.0)])
+                .speed(2.0)
+                .spawn(&mut commands);
+        });
+        app.update();
+
+        let world = app.world_mut();
+        let (profile, agent) = world
+            .query::<(&BotProfile, &SimpleNavigationAgent)>()
+            .iter(world)
+            .next()
+            .expect("bot should be spawned with a profile and navigation agent");
+
+        assert_eq!(*profile, custom);
+        assert!((agent.speed - 2.0 * custom.patrol_speed_multiplier()).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,179 @@
+//! Game time for gameplay systems: timers, cooldowns, regeneration, respawns and bot
+//! behaviour read [`GameClock`] instead of `Time`, so tests and the gym can drive it.
+//!
+//! By default the clock follows the fixed tick inside the fixed loop and the frame outside
+//! of it, the same values `Res<Time>` gives in each schedule. A stepped clock advances by
+//! a set amount every frame instead, whatever the wall clock does, and a step of zero
+//! pauses gameplay time.
+
+use std::time::Duration;
+
+use bevy::app::{FixedFirst, RunFixedMainLoop, RunFixedMainLoopSystems};
+use bevy::prelude::{App, First, IntoScheduleConfigs, Plugin, Res, ResMut, Resource, Time};
+use bevy::time::TimeSystems;
+
+pub struct GameClockPlugin;
+
+impl Plugin for GameClockPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameClock>();
+        app.add_systems(First, begin_frame.after(TimeSystems));
+        app.add_systems(FixedFirst, begin_fixed_step);
+        app.add_systems(
+            RunFixedMainLoop,
+            end_fixed_steps.in_set(RunFixedMainLoopSystems::AfterFixedMainLoop),
+        );
+    }
+}
+
+/// Adds [`GameClockPlugin`] unless the app already has it, for plugins whose systems read
+/// the clock.
+pub fn ensure_game_clock(app: &mut App) {
+    if !app.is_plugin_added::<GameClockPlugin>() {
+        app.add_plugins(GameClockPlugin);
+    }
+}
+
+/// What moves the clock forward.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ClockDrive {
+    /// Bevy's time: the fixed tick in the fixed loop, the frame elsewhere.
+    #[default]
+    Time,
+    /// This much per frame, fixed steps included.
+    Stepped(Duration),
+}
+
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub struct GameClock {
+    drive: ClockDrive,
+    elapsed: Duration,
+    delta: Duration,
+    frame_elapsed: Duration,
+    frame_delta: Duration,
+}
+
+impl GameClock {
+    /// A clock that advances by `step` on every frame.
+    pub fn stepped(step: Duration) -> Self {
+        Self {
+            drive: ClockDrive::Stepped(step),
+            ..Default::default()
+        }
+    }
+
+    pub fn drive(&self) -> ClockDrive {
+        self.drive
+    }
+
+    /// Switches what moves the clock from the next frame on. Elapsed time carries over.
+    pub fn set_drive(&mut self, drive: ClockDrive) {
+        self.drive = drive;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.drive == ClockDrive::Stepped(Duration::ZERO)
+    }
+
+    /// Game time since startup.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn elapsed_secs(&self) -> f32 {
+        self.elapsed.as_secs_f32()
+    }
+
+    /// Game time since the previous frame, or the previous tick in the fixed loop.
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn delta_secs(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    fn begin_frame(&mut self, time_elapsed: Duration, time_delta: Duration) {
+        match self.drive {
+            ClockDrive::Time => {
+                self.frame_elapsed = time_elapsed;
+                self.frame_delta = time_delta;
+            }
+            ClockDrive::Stepped(step) => {
+                self.frame_elapsed += step;
+                self.frame_delta = step;
+            }
+        }
+        self.end_fixed_steps();
+    }
+
+    fn begin_fixed_step(&mut self, time_elapsed: Duration, time_delta: Duration) {
+        // A stepped clock shows its fixed steps the frame's step.
+        if self.drive == ClockDrive::Time {
+            self.elapsed = time_elapsed;
+            self.delta = time_delta;
+        }
+    }
+
+    fn end_fixed_steps(&mut self) {
+        self.elapsed = self.frame_elapsed;
+        self.delta = self.frame_delta;
+    }
+}
+
+fn begin_frame(time: Res<Time>, mut clock: ResMut<GameClock>) {
+    clock.begin_frame(time.elapsed(), time.delta());
+}
+
+fn begin_fixed_step(time: Res<Time>, mut clock: ResMut<GameClock>) {
+    clock.begin_fixed_step(time.elapsed(), time.delta());
+}
+
+fn end_fixed_steps(mut clock: ResMut<GameClock>) {
+    clock.end_fixed_steps();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClockDrive, GameClock, GameClockPlugin};
+    use bevy::prelude::{App, MinimalPlugins};
+    use std::time::Duration;
+
+    #[test]
+    fn stepped_clock_ignores_the_wall_clock_and_can_pause() {
+        let step = Duration::from_millis(100);
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, GameClockPlugin));
+        app.insert_resource(GameClock::stepped(step));
+
+        for _ in 0..3 {
+            app.update();
+        }
+        let clock = *app.world().resource::<GameClock>();
+        assert_eq!(clock.elapsed(), step * 3);
+        assert_eq!(clock.delta(), step);
+
+        app.world_mut()
+            .resource_mut::<GameClock>()
+            .set_drive(ClockDrive::Stepped(Duration::ZERO));
+        app.update();
+        let clock = *app.world().resource::<GameClock>();
+        assert!(clock.is_paused());
+        assert_eq!(clock.elapsed(), step * 3);
+        assert_eq!(clock.delta(), Duration::ZERO);
+    }
+
+    #[test]
+    fn time_driven_clock_matches_time() {
+        let mut clock = GameClock::default();
+        clock.begin_frame(Duration::from_secs(2), Duration::from_millis(16));
+        assert_eq!(clock.elapsed(), Duration::from_secs(2));
+
+        clock.begin_fixed_step(Duration::from_millis(1990), Duration::from_millis(10));
+        assert_eq!(clock.delta(), Duration::from_millis(10));
+
+        clock.end_fixed_steps();
+        assert_eq!(clock.elapsed(), Duration::from_secs(2));
+        assert_eq!(clock.delta(), Duration::from_millis(16));
+    }
+}
@@ -0,0 +1,288 @@
+use bevy::prelude::{Changed, Component, Or, Query};
+use serde::{Deserialize, Serialize};
+
+use crate::components::weapons::Gun;
+
+/// Camera zoom used when aiming without an optic.
+pub const DEFAULT_AIM_ZOOM: f32 = 1.25;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AttachmentSlot {
+    Optic,
+    Muzzle,
+    Magazine,
+}
+
+impl AttachmentSlot {
+    pub const ALL: [AttachmentSlot; 3] = [
+        AttachmentSlot::Optic,
+        AttachmentSlot::Muzzle,
+        AttachmentSlot::Magazine,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AttachmentSlot::Optic => "Optic",
+            AttachmentSlot::Muzzle => "Muzzle",
+            AttachmentSlot::Magazine => "Magazine",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Attachment {
+    RedDot,
+    Scope,
+    Suppressor,
+    Compensator,
+    ExtendedMagazine,
+}
+
+impl Attachment {
+    pub const ALL: [Attachment; 5] = [
+        Attachment::RedDot,
+        Attachment::Scope,
+        Attachment::Suppressor,
+        Attachment::Compensator,
+        Attachment::ExtendedMagazine,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Attachment::RedDot => "Red dot",
+            Attachment::Scope => "Scope",
+            Attachment::Suppressor => "Suppressor",
+            Attachment::Compensator => "Compensator",
+            Attachment::ExtendedMagazine => "Extended magazine",
+        }
+    }
+
+    pub fn slot(&self) -> AttachmentSlot {
+        match self {
+            Attachment::RedDot | Attachment::Scope => AttachmentSlot::Optic,
+            Attachment::Suppressor | Attachment::Compensator => AttachmentSlot::Muzzle,
+            Attachment::ExtendedMagazine => AttachmentSlot::Magazine,
+        }
+    }
+
+    pub fn modifiers(&self) -> AttachmentModifiers {
+        match self {
+            Attachment::RedDot => AttachmentModifiers {
+                aim_zoom: 1.5,
+                ..AttachmentModifiers::default()
+            },
+            Attachment::Scope => AttachmentModifiers {
+                aim_zoom: 4.0,
+                recoil_multiplier: 1.1,
+                ..AttachmentModifiers::default()
+            },
+            Attachment::Suppressor => AttachmentModifiers {
+                sound_range_multiplier: 0.35,
+                ..AttachmentModifiers::default()
+            },
+            Attachment::Compensator => AttachmentModifiers {
+                recoil_multiplier: 0.7,
+                sound_range_multiplier: 1.2,
+                ..AttachmentModifiers::default()
+            },
+            Attachment::ExtendedMagazine => AttachmentModifiers {
+                magazine_bonus: 4,
+                ..AttachmentModifiers::default()
+            },
+        }
+    }
+}
+
+/// Combined stat changes applied on top of the base weapon definition.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AttachmentModifiers {
+    pub aim_zoom: f32,
+    /// Scales how far bots hear the weapon's shots (see `HearingBalance::gunshot_range`).
+    pub sound_range_multiplier: f32,
+    pub magazine_bonus: u32,
+    /// Scales the kick of every shot (see `Gun::recoil`).
+    pub recoil_multiplier: f32,
+}
+
+impl Default for AttachmentModifiers {
+    fn default() -> Self {
+        Self {
+            aim_zoom: DEFAULT_AIM_ZOOM,
+            sound_range_multiplier: 1.0,
+            magazine_bonus: 0,
+            recoil_multiplier: 1.0,
+        }
+    }
+}
+
+impl AttachmentModifiers {
+    fn combine(self, other: AttachmentModifiers) -> Self {
+        Self {
+            aim_zoom: self.aim_zoom.max(other.aim_zoom),
+            sound_range_multiplier: self.sound_range_multiplier * other.sound_range_multiplier,
+            magazine_bonus: self.magazine_bonus + other.magazine_bonus,
+            recoil_multiplier: self.recoil_multiplier * other.recoil_multiplier,
+        }
+    }
+}
+
+/// Attachments mounted on the entity's weapon. Replicated so remote players render the same
+/// variant and the server can validate the effective stats.
+#[derive(Component, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct WeaponAttachments {
+    pub equipped: Vec<Attachment>,
+}
+
+impl WeaponAttachments {
+    /// Builds a valid attachment set from an untrusted list, keeping the last attachment
+    /// requested for each slot.
+    pub fn from_requested(requested: &[Attachment]) -> Self {
+        let mut attachments = Self::default();
+        for attachment in requested {
+            attachments.equip(*attachment);
+        }
+        attachments
+    }
+
+    /// Equips an attachment, replacing whatever occupied the same slot.
+    pub fn equip(&mut self, attachment: Attachment) {
+        self.equipped
+            .retain(|existing| existing.slot() != attachment.slot());
+        self.equipped.push(attachment);
+    }
+
+    pub fn unequip(&mut self, slot: AttachmentSlot) {
+        self.equipped.retain(|existing| existing.slot() != slot);
+    }
+
+    pub fn in_slot(&self, slot: AttachmentSlot) -> Option<Attachment> {
+        self.equipped
+            .iter()
+            .copied()
+            .find(|attachment| attachment.slot() == slot)
+    }
+
+    pub fn modifiers(&self) -> AttachmentModifiers {
+        self.equipped
+            .iter()
+            .map(Attachment::modifiers)
+            .fold(AttachmentModifiers::default(), AttachmentModifiers::combine)
+    }
+}
+
+/// Keeps gun stats in sync with the mounted attachments, clamping ammo when a larger
+/// magazine is removed. Attachments stay mounted across weapon switches, so the gun in hand
+/// is rechecked too.
+pub fn apply_attachment_stats(
+    mut query: Query<
+        (&mut Gun, &WeaponAttachments),
+        Or<(Changed<WeaponAttachments>, Changed<Gun>)>,
+    >,
+) {
+    for (mut gun, attachments) in query.iter_mut() {
+        let magazine_size = gun.base_magazine_size + attachments.modifiers().magazine_bonus;
+        if gun.magazine_size != magazine_size {
+            gun.magazine_size = magazine_size;
+            gun.ammo_in_magazine = gun.ammo_in_magazine.min(magazine_size);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Attachment, AttachmentSlot, DEFAULT_AIM_ZOOM, WeaponAttachments, apply_attachment_stats,
+    };
+    use crate::components::weapons::{BASE_MAGAZINE_SIZE, Gun};
+    use bevy::prelude::{App, MinimalPlugins, Update};
+
+    #[test]
+    fn equip_replaces_attachment_in_same_slot() {
+        let mut attachments = WeaponAttachments::default();
+        attachments.equip(Attachment::RedDot);
+        attachments.equip(Attachment::Suppressor);
+        attachments.equip(Attachment::Scope);
+
+        assert_eq!(attachments.equipped.len(), 2);
+        assert_eq!(
+            attachments.in_slot(AttachmentSlot::Optic),
+            Some(Attachment::Scope)
+        );
+        assert_eq!(
+            attachments.in_slot(AttachmentSlot::Muzzle),
+            Some(Attachment::Suppressor)
+        );
+    }
+
+    #[test]
+    fn requested_attachments_are_deduplicated_per_slot() {
+        let attachments = WeaponAttachments::from_requested(&[
+            Attachment::Suppressor,
+            Attachment::Compensator,
+            Attachment::ExtendedMagazine,
+            Attachment::ExtendedMagazine,
+        ]);
+
+        assert_eq!(
+            attachments.equipped,
+            vec![Attachment::Compensator, Attachment::ExtendedMagazine]
+        );
+    }
+
+    #[test]
+    fn modifiers_combine_across_slots() {
+        let attachments = WeaponAttachments::from_requested(&[
+            Attachment::Scope,
+            Attachment::Suppressor,
+            Attachment::ExtendedMagazine,
+        ]);
+        let modifiers = attachments.modifiers();
+
+        assert_eq!(modifiers.aim_zoom, 4.0);
+        assert_eq!(modifiers.magazine_bonus, 4);
+        assert!(modifiers.sound_range_multiplier < 0.5);
+        assert!(modifiers.recoil_multiplier > 1.0);
+        assert_eq!(
+            WeaponAttachments::default().modifiers().aim_zoom,
+            DEFAULT_AIM_ZOOM
+        );
+    }
+
+    #[test]
+    fn removing_extended_magazine_clamps_loaded_ammo() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_systems(Update, apply_attachment_stats);
+
+        let entity = app
+            .world_mut()
+            .spawn((
+                Gun::default(),
+                WeaponAttachments::from_requested(&[Attachment::ExtendedMagazine]),
+            ))
+            .id();
+        app.update();
+
+        {
+            let mut gun = app
+                .world_mut()
+                .get_mut::<Gun>(entity)
+                .expect("Entity should keep Gun");
+            assert_eq!(gun.magazine_size, BASE_MAGAZINE_SIZE + 4);
+            gun.ammo_in_magazine = gun.magazine_size;
+        }
+
+        app.world_mut()
+            .get_mut::<WeaponAttachments>(entity)
+            .expect("Entity should keep WeaponAttachments")
+            .unequip(AttachmentSlot::Magazine);
+        app.update();
+
+        let gun = app
+            .world()
+            .get::<Gun>(entity)
+            .expect("Entity should keep Gun");
+        assert_eq!(gun.magazine_size, BASE_MAGAZINE_SIZE);
+        assert_eq!(gun.ammo_in_magazine, BASE_MAGAZINE_SIZE);
+    }
+}
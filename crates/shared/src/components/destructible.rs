@@ -0,0 +1,221 @@
+use avian3d::prelude::{
+    AngularVelocity, Collider, LinearDamping, LinearVelocity, Mass, Position, RigidBody, Rotation,
+};
+use bevy::prelude::{
+    App, Bundle, Color, Commands, Component, Entity, Name, Plugin, Quat, Query, Res, Time, Timer,
+    TimerMode, Update, Vec3,
+};
+use lightyear::prelude::{NetworkTarget, Replicate};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::components::health::Health;
+use crate::navigation::NavigationObstacle;
+
+/// Debris pieces simulated by the server and replicated. The rest of a burst is spawned by
+/// each client as cosmetic, client-only bodies.
+pub const AUTHORITATIVE_DEBRIS_PIECES: usize = 3;
+pub const AUTHORITATIVE_DEBRIS_LIFETIME_SECS: f32 = 8.0;
+pub const COSMETIC_DEBRIS_LIFETIME_SECS: f32 = 4.0;
+const DEBRIS_SPEED: f32 = 4.0;
+
+pub const DESTRUCTIBLE_COLOR: Color = Color::srgb(0.55, 0.4, 0.25);
+
+/// A prop that shatters into debris when its `Health` runs out. Props are navigation
+/// obstacles, so breaking one rebakes the navmesh and bots path through the gap.
+#[derive(Component, Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Destructible {
+    pub size: Vec3,
+    pub debris_pieces: usize,
+}
+
+/// Replicated marker for a server-simulated debris piece, with its cube edge length.
+#[derive(Component, Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct DebrisPiece {
+    pub size: f32,
+}
+
+/// Despawns a debris piece once it runs out.
+#[derive(Component, Clone, Debug)]
+pub struct DebrisLifetime(pub Timer);
+
+impl DebrisLifetime {
+    pub fn from_seconds(seconds: f32) -> Self {
+        Self(Timer::from_seconds(seconds, TimerMode::Once))
+    }
+}
+
+/// Initial state of one piece of a shattered prop, relative to the prop's center.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DebrisSpec {
+    pub offset: Vec3,
+    pub velocity: Vec3,
+    pub angular_velocity: Vec3,
+    pub size: f32,
+}
+
+/// Pieces of a prop of `size` shattered with `seed`. Server and clients call this with the
+/// same seed, so cosmetic debris on every client starts out identical.
+pub fn debris_layout(seed: u64, size: Vec3, pieces: usize) -> Vec<DebrisSpec> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let piece_size = (size.min_element() * 0.35).max(0.05);
+    let half = size * 0.5;
+
+    (0..pieces)
+        .map(|_| {
+            let offset = Vec3::new(
+                rng.random_range(-half.x..=half.x),
+                rng.random_range(-half.y..=half.y),
+                rng.random_range(-half.z..=half.z),
+            );
+            let outward = offset.normalize_or(Vec3::Y);
+            let velocity = (outward + Vec3::Y * rng.random_range(0.5..1.5))
+                * DEBRIS_SPEED
+                * rng.random_range(0.6..1.2);
+            let angular_velocity = Vec3::new(
+                rng.random_range(-6.0..6.0),
+                rng.random_range(-6.0..6.0),
+                rng.random_range(-6.0..6.0),
+            );
+
+            DebrisSpec {
+                offset,
+                velocity,
+                angular_velocity,
+                size: piece_size * rng.random_range(0.7..1.3),
+            }
+        })
+        .collect()
+}
+
+/// Physics components of a debris piece launched from a prop centered on `origin`.
+pub fn debris_body(origin: Vec3, spec: &DebrisSpec) -> impl Bundle {
+    (
+        RigidBody::Dynamic,
+        Collider::cuboid(spec.size, spec.size, spec.size),
+        Mass(2.0),
+        LinearDamping(0.4),
+        Position::new(origin + spec.offset),
+        Rotation::default(),
+        LinearVelocity(spec.velocity),
+        AngularVelocity(spec.angular_velocity),
+    )
+}
+
+/// Spawn a server-replicated destructible crate resting on `position`.
+pub fn spawn_destructible_crate(
+    commands: &mut Commands,
+    name: impl Into<String>,
+    position: Vec3,
+) -> Entity {
+    let size = Vec3::splat(1.2);
+    spawn_destructible_prop(
+        commands,
+        name,
+        position + Vec3::Y * (size.y * 0.5),
+        Destructible {
+            size,
+            debris_pieces: 16,
+        },
+        50.0,
+    )
+}
+
+/// Spawn a server-replicated destructible prop centered on `center`, such as a barricade
+/// closing off a shortcut until someone shoots it down.
+pub fn spawn_destructible_prop(
+    commands: &mut Commands,
+    name: impl Into<String>,
+    center: Vec3,
+    destructible: Destructible,
+    health: f32,
+) -> Entity {
+    let size = destructible.size;
+    commands
+        .spawn((
+            Name::new(name.into()),
+            destructible,
+            Health {
+                current: health,
+                max: health,
+                can_regenerate: false,
+                ..Health::basic()
+            },
+            RigidBody::Static,
+            Collider::cuboid(size.x, size.y, size.z),
+            Position::new(center),
+            Rotation::from(Quat::IDENTITY),
+            NavigationObstacle,
+            Replicate::to_clients(NetworkTarget::All),
+        ))
+        .id()
+}
+
+pub struct DebrisPlugin;
+
+impl Plugin for DebrisPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, despawn_expired_debris);
+    }
+}
+
+fn despawn_expired_debris(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut debris: Query<(Entity, &mut DebrisLifetime)>,
+) {
+    for (entity, mut lifetime) in debris.iter_mut() {
+        lifetime.0.tick(time.delta());
+        if lifetime.0.is_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DebrisLifetime, DebrisPlugin, debris_layout};
+    use bevy::prelude::{App, MinimalPlugins, Vec3};
+    use std::time::Duration;
+
+    #[test]
+    fn layout_is_deterministic_per_seed() {
+        let size = Vec3::new(1.0, 2.0, 1.0);
+        let first = debris_layout(42, size, 12);
+
+        assert_eq!(first.len(), 12);
+        assert_eq!(first, debris_layout(42, size, 12));
+        assert_ne!(first, debris_layout(43, size, 12));
+        assert!(
+            first.iter().all(|piece| {
+                piece.offset.abs().cmple(size * 0.5).all() && piece.velocity.y > 0.0
+            })
+        );
+    }
+
+    #[test]
+    fn expired_debris_is_despawned() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(DebrisPlugin);
+        app.insert_resource(bevy::time::TimeUpdateStrategy::ManualDuration(
+            Duration::from_millis(100),
+        ));
+
+        let short = app
+            .world_mut()
+            .spawn(DebrisLifetime::from_seconds(0.15))
+            .id();
+        let long = app
+            .world_mut()
+            .spawn(DebrisLifetime::from_seconds(5.0))
+            .id();
+        for _ in 0..4 {
+            app.update();
+        }
+
+        assert!(app.world().get_entity(short).is_err());
+        assert!(app.world().get_entity(long).is_ok());
+    }
+}
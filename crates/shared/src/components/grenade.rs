@@ -0,0 +1,112 @@
+use avian3d::prelude::{
+    AngularDamping, Collider, Friction, LinearVelocity, Mass, Position, Restitution, RigidBody,
+    Rotation,
+};
+use bevy::prelude::{Bundle, Color, Component, Entity, Quat, Timer, TimerMode, Vec3};
+use serde::{Deserialize, Serialize};
+
+pub const GRENADE_RADIUS: f32 = 0.12;
+pub const GRENADE_COLOR: Color = Color::srgb(0.25, 0.35, 0.2);
+pub const GRENADE_FUSE_SECS: f32 = 2.5;
+pub const GRENADE_THROW_COOLDOWN_SECS: f32 = 1.5;
+/// Speed along the aim direction, plus an upward kick so flat throws still arc.
+pub const GRENADE_THROW_SPEED: f32 = 14.0;
+const GRENADE_THROW_LIFT: f32 = 3.0;
+/// Fraction of its speed a grenade keeps when it bounces.
+pub const GRENADE_RESTITUTION: f32 = 0.45;
+
+pub const GRENADE_BLAST_RADIUS: f32 = 6.0;
+/// Damage at the center of the blast; it falls off linearly to zero at the edge.
+pub const GRENADE_MAX_DAMAGE: f32 = 100.0;
+/// Fraction of shield absorption explosions ignore.
+pub const GRENADE_PENETRATION: f32 = 0.5;
+
+/// Replicated marker for a live grenade. The server simulates it; clients only draw it.
+#[derive(Component, Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Grenade;
+
+/// Server-side fuse of a live grenade, and who threw it for kill credit.
+#[derive(Component, Clone, Debug)]
+pub struct GrenadeFuse {
+    pub timer: Timer,
+    pub thrower: Entity,
+}
+
+impl GrenadeFuse {
+    pub fn new(thrower: Entity) -> Self {
+        Self {
+            timer: Timer::from_seconds(GRENADE_FUSE_SECS, TimerMode::Once),
+            thrower,
+        }
+    }
+}
+
+/// Lets a character throw grenades, at most one per cooldown.
+#[derive(Component, Clone, Debug)]
+pub struct GrenadeThrower {
+    pub cooldown: Timer,
+}
+
+impl Default for GrenadeThrower {
+    fn default() -> Self {
+        let mut cooldown = Timer::from_seconds(GRENADE_THROW_COOLDOWN_SECS, TimerMode::Once);
+        cooldown.finish();
+        Self { cooldown }
+    }
+}
+
+/// Launch velocity of a grenade thrown by a character looking along `rotation` while
+/// moving at `carrier_velocity`.
+pub fn throw_velocity(rotation: Quat, carrier_velocity: Vec3) -> Vec3 {
+    let aim = (rotation * Vec3::NEG_Z).normalize_or(Vec3::NEG_Z);
+    aim * GRENADE_THROW_SPEED + Vec3::Y * GRENADE_THROW_LIFT + carrier_velocity
+}
+
+/// Damage dealt by an explosion to something `distance` meters from its center.
+pub fn explosion_damage(distance: f32) -> f32 {
+    GRENADE_MAX_DAMAGE * (1.0 - distance / GRENADE_BLAST_RADIUS).clamp(0.0, 1.0)
+}
+
+/// Physics components of a grenade thrown from `origin`.
+pub fn grenade_body(origin: Vec3, velocity: Vec3) -> impl Bundle {
+    (
+        RigidBody::Dynamic,
+        Collider::sphere(GRENADE_RADIUS),
+        Restitution::new(GRENADE_RESTITUTION),
+        Friction::new(0.6),
+        Mass(0.4),
+        AngularDamping(1.0),
+        Position::new(origin),
+        Rotation::default(),
+        LinearVelocity(velocity),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GRENADE_BLAST_RADIUS, GRENADE_MAX_DAMAGE, explosion_damage, throw_velocity};
+    use bevy::prelude::{Quat, Vec3};
+
+    #[test]
+    fn damage_falls_off_to_zero_at_the_blast_edge() {
+        assert_eq!(explosion_damage(0.0), GRENADE_MAX_DAMAGE);
+        assert_eq!(
+            explosion_damage(GRENADE_BLAST_RADIUS * 0.5),
+            GRENADE_MAX_DAMAGE * 0.5
+        );
+        assert_eq!(explosion_damage(GRENADE_BLAST_RADIUS), 0.0);
+        assert_eq!(explosion_damage(GRENADE_BLAST_RADIUS * 2.0), 0.0);
+    }
+
+    #[test]
+    fn flat_throws_arc_upward_along_the_aim() {
+        let velocity = throw_velocity(
+            Quat::from_rotation_y(std::f32::consts::FRAC_PI_2),
+            Vec3::ZERO,
+        );
+
+        assert!(velocity.x < 0.0, "Looking along -X should throw along -X");
+        assert!(velocity.y > 0.0, "Flat throws should still go up first");
+        assert!(velocity.z.abs() < 1e-4);
+    }
+}
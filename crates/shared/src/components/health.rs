@@ -1,17 +1,33 @@
 use bevy::prelude::{
-    App, Component, Entity, Message, MessageReader, Plugin, Query, Reflect, ReflectComponent,
-    Res, Time, Update, Vec3, info,
+    App, Component, Entity, Message, MessageReader, MessageWriter, Plugin, Query, Reflect,
+    ReflectComponent, Res, Update, Vec3, info,
 };
 use serde::{Deserialize, Serialize};
 
+use crate::balance::BalanceConfig;
+use crate::clock::{GameClock, ensure_game_clock};
+use crate::components::shield::{Shield, shield_regeneration_system};
+use crate::components::team::{Team, TeamRules, is_friendly_fire};
+
 pub struct HealthPlugin;
 
 impl Plugin for HealthPlugin {
     fn build(&self, app: &mut App) {
+        ensure_game_clock(app);
         app.add_message::<DamageEvent>()
+            .add_message::<DeathEvent>()
+            .init_resource::<BalanceConfig>()
             .register_type::<Health>()
+            .register_type::<Shield>()
             .register_type::<Respawnable>()
-            .add_systems(Update, (process_damage_events, health_regeneration_system));
+            .add_systems(
+                Update,
+                (
+                    process_damage_events,
+                    health_regeneration_system,
+                    shield_regeneration_system,
+                ),
+            );
     }
 }
 
@@ -125,19 +141,89 @@ pub struct DamageEvent {
     pub target: Entity,
     pub amount: f32,
     pub source: Option<Entity>, // Who/what caused the damage
+    /// Fraction (0..1) of shield absorption this damage ignores.
+    pub penetration: f32,
+    pub headshot: bool,
 }
 
-fn process_damage_events(
-    mut damage_events: MessageReader<DamageEvent>,
-    mut health_query: Query<&mut Health>,
+impl DamageEvent {
+    pub fn new(target: Entity, amount: f32, source: Option<Entity>) -> Self {
+        Self {
+            target,
+            amount,
+            source,
+            penetration: 0.0,
+            headshot: false,
+        }
+    }
+}
 
-    time: Res<Time>,
+/// Written when a `DamageEvent` takes an entity's health to zero.
+#[derive(Message, Clone, Debug)]
+pub struct DeathEvent {
+    pub target: Entity,
+    pub source: Option<Entity>,
+    pub headshot: bool,
+}
+
+/// Damage left for `Health` after headshot scaling and shield absorption.
+pub fn resolve_damage(
+    event: &DamageEvent,
+    shield: Option<&mut Shield>,
+    balance: &BalanceConfig,
+    current_time: f32,
+) -> f32 {
+    let amount = if event.headshot {
+        event.amount * balance.headshot_multiplier
+    } else {
+        event.amount
+    };
+
+    let Some(shield) = shield else {
+        return amount;
+    };
+
+    let mut absorption_scale = 1.0 - event.penetration.clamp(0.0, 1.0);
+    if event.headshot {
+        absorption_scale *= 1.0 - balance.shield.headshot_bypass.clamp(0.0, 1.0);
+    }
+    shield.absorb(amount, absorption_scale, current_time)
+}
+
+/// Apply damage to health and shields. Damage between teammates is dropped unless the
+/// server's `TeamRules` allow friendly fire.
+pub fn process_damage_events(
+    mut damage_events: MessageReader<DamageEvent>,
+    mut health_query: Query<(&mut Health, Option<&mut Shield>)>,
+    team_query: Query<&Team>,
+    mut death_writer: MessageWriter<DeathEvent>,
+    balance: Res<BalanceConfig>,
+    team_rules: Option<Res<TeamRules>>,
+    clock: Res<GameClock>,
 ) {
-    let current_time = time.elapsed().as_secs_f32();
+    let current_time = clock.elapsed_secs();
+    let friendly_fire = team_rules.is_some_and(|rules| rules.friendly_fire);
 
     for damage_event in damage_events.read() {
-        if let Ok(mut health) = health_query.get_mut(damage_event.target) {
-            let actual_damage = health.take_damage(damage_event.amount, current_time);
+        let blocked = damage_event.source.is_some_and(|source| {
+            source != damage_event.target
+                && is_friendly_fire(
+                    team_query.get(source).ok().copied(),
+                    team_query.get(damage_event.target).ok().copied(),
+                )
+        });
+        if blocked && !friendly_fire {
+            continue;
+        }
+
+        if let Ok((mut health, mut shield)) = health_query.get_mut(damage_event.target) {
+            if health.is_dead {
+                continue;
+            }
+
+            let health_damage =
+                resolve_damage(damage_event, shield.as_deref_mut(), &balance, current_time);
+            let actual_damage = health.take_damage(health_damage, current_time);
 
             if actual_damage > 0.0 {
                 info!(
@@ -145,13 +231,21 @@ fn process_damage_events(
                     damage_event.target, actual_damage, health.current, health.max
                 );
             }
+
+            if health.is_dead {
+                death_writer.write(DeathEvent {
+                    target: damage_event.target,
+                    source: damage_event.source,
+                    headshot: damage_event.headshot,
+                });
+            }
         }
     }
 }
 
-fn health_regeneration_system(mut health_query: Query<&mut Health>, time: Res<Time>) {
-    let current_time = time.elapsed().as_secs_f32();
-    let delta_time = time.delta().as_secs_f32();
+fn health_regeneration_system(mut health_query: Query<&mut Health>, clock: Res<GameClock>) {
+    let current_time = clock.elapsed_secs();
+    let delta_time = clock.delta_secs();
 
     for mut health in health_query.iter_mut() {
         if health.can_regenerate_now(current_time) {
@@ -163,8 +257,10 @@ fn health_regeneration_system(mut health_query: Query<&mut Health>, time: Res<Ti
 
 #[cfg(test)]
 mod tests {
-    use super::{Health, Respawnable};
-    use bevy::prelude::Vec3;
+    use super::{DamageEvent, Health, Respawnable, resolve_damage};
+    use crate::balance::BalanceConfig;
+    use crate::components::shield::Shield;
+    use bevy::prelude::{Entity, Vec3};
 
     #[test]
     fn health_take_damage_and_death() {
@@ -226,4 +322,40 @@ mod tests {
         assert!(!delayed.can_respawn(12.4));
         assert!(delayed.can_respawn(12.5));
     }
+
+    #[test]
+    fn headshots_multiply_damage_and_bypass_part_of_the_shield() {
+        let balance = BalanceConfig::default();
+        let mut body_shield = balance.player_shield();
+        let mut head_shield = balance.player_shield();
+
+        let body = DamageEvent::new(Entity::PLACEHOLDER, 20.0, None);
+        let head = DamageEvent {
+            headshot: true,
+            ..body.clone()
+        };
+
+        let body_damage = resolve_damage(&body, Some(&mut body_shield), &balance, 1.0);
+        let head_damage = resolve_damage(&head, Some(&mut head_shield), &balance, 1.0);
+
+        assert!(body_damage < body.amount, "Shield should absorb body shots");
+        assert!(
+            head_damage > body_damage * balance.headshot_multiplier,
+            "Headshots should be multiplied and partially bypass the shield"
+        );
+    }
+
+    #[test]
+    fn full_penetration_ignores_shield() {
+        let balance = BalanceConfig::default();
+        let mut shield: Shield = balance.player_shield();
+        let event = DamageEvent {
+            penetration: 1.0,
+            ..DamageEvent::new(Entity::PLACEHOLDER, 30.0, None)
+        };
+
+        assert_eq!(resolve_damage(&event, Some(&mut shield), &balance, 1.0), 30.0);
+        assert_eq!(shield.current, shield.max);
+        assert_eq!(resolve_damage(&event, None, &balance, 1.0), 30.0);
+    }
 }
@@ -0,0 +1,280 @@
+use bevy::prelude::{Color, Component, Query, Timer, TimerMode};
+use leafwing_input_manager::prelude::ActionState;
+use serde::{Deserialize, Serialize};
+
+use crate::components::weapon_vfx::{MuzzleFlash, TracerStyle, WeaponVfx};
+use crate::components::weapons::{Gun, WeaponCharge, WeaponHeat};
+use crate::inputs::input::PlayerAction;
+
+/// Points a loadout may spend. The server rejects loadouts over budget.
+pub const LOADOUT_BUDGET: u32 = 5;
+/// Extra shield capacity granted by the armor plate.
+pub const ARMOR_PLATE_SHIELD_BONUS: f32 = 50.0;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PrimaryWeapon {
+    #[default]
+    Rifle,
+    Shotgun,
+    Marksman,
+    Railgun,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SecondaryWeapon {
+    #[default]
+    Pistol,
+    MachinePistol,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Equipment {
+    #[default]
+    Grenades,
+    ArmorPlate,
+}
+
+fn gun(
+    damage: f32,
+    fire_interval_secs: f32,
+    range: f32,
+    penetration: f32,
+    magazine_size: u32,
+    reload_secs: f32,
+) -> Gun {
+    Gun {
+        cooldown: Timer::from_seconds(fire_interval_secs, TimerMode::Once),
+        damage,
+        range,
+        penetration,
+        base_magazine_size: magazine_size,
+        magazine_size,
+        ammo_in_magazine: magazine_size,
+        reload_timer: Timer::from_seconds(reload_secs, TimerMode::Once),
+        is_reloading: false,
+        heat: None,
+        charge: None,
+        vfx: WeaponVfx::default(),
+        recoil: 0.0,
+    }
+}
+
+impl PrimaryWeapon {
+    pub const ALL: [PrimaryWeapon; 4] = [
+        PrimaryWeapon::Rifle,
+        PrimaryWeapon::Shotgun,
+        PrimaryWeapon::Marksman,
+        PrimaryWeapon::Railgun,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PrimaryWeapon::Rifle => "Rifle",
+            PrimaryWeapon::Shotgun => "Shotgun",
+            PrimaryWeapon::Marksman => "Marksman",
+            PrimaryWeapon::Railgun => "Railgun",
+        }
+    }
+
+    pub fn cost(&self) -> u32 {
+        match self {
+            PrimaryWeapon::Rifle | PrimaryWeapon::Shotgun => 2,
+            PrimaryWeapon::Marksman | PrimaryWeapon::Railgun => 3,
+        }
+    }
+
+    pub fn gun(&self) -> Gun {
+        match self {
+            PrimaryWeapon::Rifle => Gun::default(),
+            PrimaryWeapon::Shotgun => gun(60.0, 0.9, 20.0, 0.1, 4, 2.0).with_vfx(
+                WeaponVfx::default()
+                    .with_tracer(None)
+                    .with_muzzle_flash(MuzzleFlash {
+                        color: Color::srgb(1.0, 0.6, 0.25),
+                        size: 0.25,
+                        light_intensity: 90_000.0,
+                        lifetime_secs: 0.07,
+                    })
+                    .with_impact_scale(2.5),
+            ),
+            PrimaryWeapon::Marksman => gun(55.0, 0.8, 200.0, 0.5, 5, 1.8).with_vfx(
+                WeaponVfx::default()
+                    .with_tracer(Some(TracerStyle {
+                        color: Color::srgb(0.7, 0.9, 1.0),
+                        width: 0.035,
+                        lifetime_secs: 0.2,
+                    }))
+                    .with_impact_scale(1.5),
+            ),
+            // Fires on release: a tap does 30% of the damage, 1.2s of charge all of it.
+            PrimaryWeapon::Railgun => gun(90.0, 0.6, 150.0, 0.6, 4, 2.2)
+                .with_charge(WeaponCharge::new(1.2, 0.3))
+                .with_vfx(
+                    WeaponVfx::default()
+                        .with_tracer(Some(TracerStyle {
+                            color: Color::srgb(0.45, 0.7, 1.0),
+                            width: 0.05,
+                            lifetime_secs: 0.25,
+                        }))
+                        .with_impact_scale(1.8),
+                ),
+        }
+    }
+}
+
+impl SecondaryWeapon {
+    pub const ALL: [SecondaryWeapon; 2] = [SecondaryWeapon::Pistol, SecondaryWeapon::MachinePistol];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SecondaryWeapon::Pistol => "Pistol",
+            SecondaryWeapon::MachinePistol => "Machine pistol",
+        }
+    }
+
+    pub fn cost(&self) -> u32 {
+        match self {
+            SecondaryWeapon::Pistol => 1,
+            SecondaryWeapon::MachinePistol => 2,
+        }
+    }
+
+    pub fn gun(&self) -> Gun {
+        match self {
+            SecondaryWeapon::Pistol => gun(18.0, 0.25, 60.0, 0.1, 12, 1.0).with_vfx(
+                WeaponVfx::default()
+                    .with_tracer(None)
+                    .with_muzzle_flash(MuzzleFlash {
+                        color: Color::srgb(1.0, 0.8, 0.45),
+                        size: 0.08,
+                        light_intensity: 20_000.0,
+                        lifetime_secs: 0.04,
+                    })
+                    .with_impact_scale(0.6),
+            ),
+            // Runs on heat: 10 shots from cold overheat it.
+            SecondaryWeapon::MachinePistol => gun(10.0, 0.1, 40.0, 0.05, 20, 1.4)
+                .with_heat(WeaponHeat::new(0.1, 0.35, 1.6))
+                .with_vfx(
+                    WeaponVfx::default()
+                        .with_tracer(Some(TracerStyle {
+                            color: Color::srgb(1.0, 0.45, 0.2),
+                            width: 0.015,
+                            lifetime_secs: 0.05,
+                        }))
+                        .with_impact_scale(0.5),
+                ),
+        }
+    }
+}
+
+impl Equipment {
+    pub const ALL: [Equipment; 2] = [Equipment::Grenades, Equipment::ArmorPlate];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Equipment::Grenades => "Grenades",
+            Equipment::ArmorPlate => "Armor plate",
+        }
+    }
+
+    pub fn cost(&self) -> u32 {
+        match self {
+            Equipment::Grenades => 1,
+            Equipment::ArmorPlate => 2,
+        }
+    }
+}
+
+/// What a player spawns with. Chosen in the lobby, validated by the server and replicated
+/// on the player entity.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Loadout {
+    pub primary: PrimaryWeapon,
+    pub secondary: SecondaryWeapon,
+    pub equipment: Equipment,
+}
+
+impl Loadout {
+    pub fn cost(&self) -> u32 {
+        self.primary.cost() + self.secondary.cost() + self.equipment.cost()
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.cost() <= LOADOUT_BUDGET
+    }
+}
+
+/// The weapon not in hand. Switching weapons swaps it with the character's `Gun`.
+#[derive(Component, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HolsteredGun(pub Gun);
+
+/// Swap the gun in hand with the holstered one. A reload in progress is abandoned.
+pub fn switch_weapons(mut query: Query<(&mut Gun, &mut HolsteredGun, &ActionState<PlayerAction>)>) {
+    for (mut gun, mut holstered, action_state) in query.iter_mut() {
+        if action_state.disabled() || !action_state.just_pressed(&PlayerAction::SwitchWeapon) {
+            continue;
+        }
+
+        gun.is_reloading = false;
+        std::mem::swap(&mut *gun, &mut holstered.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Equipment, HolsteredGun, LOADOUT_BUDGET, Loadout, PrimaryWeapon, SecondaryWeapon,
+        switch_weapons,
+    };
+    use crate::components::weapons::Gun;
+    use crate::inputs::input::PlayerAction;
+    use bevy::prelude::{App, MinimalPlugins, Update};
+    use leafwing_input_manager::prelude::ActionState;
+
+    #[test]
+    fn default_loadout_keeps_the_standard_rifle_within_budget() {
+        let loadout = Loadout::default();
+        assert!(loadout.is_valid());
+        assert_eq!(loadout.primary.gun(), Gun::default());
+
+        let greedy = Loadout {
+            primary: PrimaryWeapon::Marksman,
+            secondary: SecondaryWeapon::MachinePistol,
+            equipment: Equipment::ArmorPlate,
+        };
+        assert!(greedy.cost() > LOADOUT_BUDGET);
+        assert!(!greedy.is_valid());
+    }
+
+    #[test]
+    fn switching_swaps_primary_and_secondary() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_systems(Update, switch_weapons);
+
+        let mut action_state = ActionState::<PlayerAction>::default();
+        action_state.enable();
+        action_state.press(&PlayerAction::SwitchWeapon);
+        let player = app
+            .world_mut()
+            .spawn((
+                PrimaryWeapon::Shotgun.gun(),
+                HolsteredGun(SecondaryWeapon::Pistol.gun()),
+                action_state,
+            ))
+            .id();
+
+        app.update();
+
+        let world = app.world();
+        assert_eq!(
+            world.get::<Gun>(player).unwrap(),
+            &SecondaryWeapon::Pistol.gun()
+        );
+        assert_eq!(
+            world.get::<HolsteredGun>(player).unwrap().0,
+            PrimaryWeapon::Shotgun.gun()
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use bevy::prelude::{Component, Resource};
+use serde::{Deserialize, Serialize};
+
+/// Length of a match unless the server is told otherwise.
+pub const DEFAULT_MATCH_DURATION_SECS: f32 = 600.0;
+/// How long final scores stay on screen before everyone is sent back to the lobby.
+pub const POST_GAME_SECS: f32 = 10.0;
+
+/// Server-side match length. Inserted by the launcher, defaults to
+/// [`DEFAULT_MATCH_DURATION_SECS`].
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct MatchTimerSettings {
+    pub duration_secs: f32,
+}
+
+impl Default for MatchTimerSettings {
+    fn default() -> Self {
+        Self {
+            duration_secs: DEFAULT_MATCH_DURATION_SECS,
+        }
+    }
+}
+
+/// Time left in the running match. Ticked by the server and replicated to all clients for
+/// the HUD clock.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MatchTimer {
+    pub remaining_secs: f32,
+}
+
+impl MatchTimer {
+    pub fn new(duration_secs: f32) -> Self {
+        Self {
+            remaining_secs: duration_secs.max(0.0),
+        }
+    }
+
+    /// Count `delta_secs` down. Returns true on the tick the match runs out.
+    pub fn tick(&mut self, delta_secs: f32) -> bool {
+        if self.is_finished() {
+            return false;
+        }
+        self.remaining_secs = (self.remaining_secs - delta_secs).max(0.0);
+        self.is_finished()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.remaining_secs <= 0.0
+    }
+}
+
+/// `mm:ss`, rounding up so the clock reads `00:00` only once the match is over.
+pub fn format_match_clock(remaining_secs: f32) -> String {
+    let total = remaining_secs.max(0.0).ceil() as u32;
+    format!("{:02}:{:02}", total / 60, total % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MatchTimer, format_match_clock};
+
+    #[test]
+    fn timer_reports_expiry_once() {
+        let mut timer = MatchTimer::new(1.0);
+        assert!(!timer.tick(0.6));
+        assert!(timer.tick(0.6));
+        assert!(timer.is_finished());
+        assert!(!timer.tick(0.6), "An expired match must not end twice");
+        assert_eq!(timer.remaining_secs, 0.0);
+    }
+
+    #[test]
+    fn clock_rounds_up_to_whole_seconds() {
+        assert_eq!(format_match_clock(600.0), "10:00");
+        assert_eq!(format_match_clock(59.2), "01:00");
+        assert_eq!(format_match_clock(0.4), "00:01");
+        assert_eq!(format_match_clock(0.0), "00:00");
+    }
+}
@@ -1,3 +1,16 @@
+pub mod attachments;
+pub mod destructible;
 pub mod flashlight;
+pub mod grenade;
 pub mod health;
+pub mod loadout;
+pub mod match_timer;
+pub mod pickup;
+pub mod profile;
+pub mod score;
+pub mod shield;
+pub mod stamina;
+pub mod team;
+pub mod weapon_vfx;
 pub mod weapons;
+pub mod world_items;
@@ -0,0 +1,182 @@
+use avian3d::prelude::{Collider, CollisionEventsEnabled, Position, RigidBody, Sensor};
+use bevy::prelude::{Color, Commands, Component, Entity, Name, Timer, TimerMode, Vec3};
+use lightyear::prelude::{NetworkTarget, Replicate};
+use serde::{Deserialize, Serialize};
+
+use crate::components::attachments::{Attachment, WeaponAttachments};
+use crate::components::health::Health;
+use crate::components::weapons::Gun;
+
+pub const HEALTH_PACK_AMOUNT: f32 = 50.0;
+pub const PICKUP_RESPAWN_SECS: f32 = 20.0;
+/// Radius of the trigger volume; the visible item is smaller.
+pub const PICKUP_TRIGGER_RADIUS: f32 = 0.8;
+/// Height of the item above the floor.
+pub const PICKUP_HOVER_HEIGHT: f32 = 0.6;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PickupKind {
+    HealthPack,
+    /// Refills the magazine and cancels a reload in progress.
+    Ammo,
+    /// Mounts the attachment on the collector's weapon.
+    Attachment(Attachment),
+}
+
+impl PickupKind {
+    pub fn color(&self) -> Color {
+        match self {
+            PickupKind::HealthPack => Color::srgb(0.9, 0.15, 0.15),
+            PickupKind::Ammo => Color::srgb(0.9, 0.75, 0.2),
+            PickupKind::Attachment(_) => Color::srgb(0.2, 0.6, 0.95),
+        }
+    }
+}
+
+/// Replicated pickup. `available` is false while it waits to respawn, so clients can hide
+/// it.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Pickup {
+    pub kind: PickupKind,
+    pub available: bool,
+}
+
+/// Server-side respawn timer, running while the pickup is taken.
+#[derive(Component, Clone, Debug)]
+pub struct PickupRespawn(pub Timer);
+
+impl Default for PickupRespawn {
+    fn default() -> Self {
+        Self(Timer::from_seconds(PICKUP_RESPAWN_SECS, TimerMode::Once))
+    }
+}
+
+/// Give the pickup to a character. Returns false, leaving the pickup in place, when it
+/// would have no effect (full health, full magazine, attachment already mounted).
+pub fn apply_pickup(
+    kind: PickupKind,
+    health: &mut Health,
+    gun: Option<&mut Gun>,
+    attachments: Option<&mut WeaponAttachments>,
+) -> bool {
+    match kind {
+        PickupKind::HealthPack => health.heal(HEALTH_PACK_AMOUNT) > 0.0,
+        PickupKind::Ammo => {
+            let Some(gun) = gun else {
+                return false;
+            };
+            if gun.ammo_in_magazine >= gun.magazine_size {
+                return false;
+            }
+            gun.ammo_in_magazine = gun.magazine_size;
+            gun.is_reloading = false;
+            true
+        }
+        PickupKind::Attachment(attachment) => {
+            let Some(attachments) = attachments else {
+                return false;
+            };
+            if attachments.equipped.contains(&attachment) {
+                return false;
+            }
+            attachments.equip(attachment);
+            true
+        }
+    }
+}
+
+/// Spawn a server-replicated pickup hovering above `floor`.
+pub fn spawn_pickup(
+    commands: &mut Commands,
+    name: impl Into<String>,
+    kind: PickupKind,
+    floor: Vec3,
+) -> Entity {
+    commands
+        .spawn((
+            Name::new(name.into()),
+            Pickup {
+                kind,
+                available: true,
+            },
+            PickupRespawn::default(),
+            RigidBody::Static,
+            Collider::sphere(PICKUP_TRIGGER_RADIUS),
+            Sensor,
+            CollisionEventsEnabled,
+            Position::new(floor + Vec3::Y * PICKUP_HOVER_HEIGHT),
+            Replicate::to_clients(NetworkTarget::All),
+        ))
+        .id()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HEALTH_PACK_AMOUNT, PickupKind, apply_pickup};
+    use crate::components::attachments::{Attachment, WeaponAttachments};
+    use crate::components::health::Health;
+    use crate::components::weapons::Gun;
+
+    #[test]
+    fn pickups_without_effect_stay_in_place() {
+        let mut health = Health::basic();
+        let mut gun = Gun::default();
+        let mut attachments = WeaponAttachments::from_requested(&[Attachment::Scope]);
+
+        assert!(!apply_pickup(
+            PickupKind::HealthPack,
+            &mut health,
+            None,
+            None
+        ));
+        assert!(!apply_pickup(
+            PickupKind::Ammo,
+            &mut health,
+            Some(&mut gun),
+            None
+        ));
+        assert!(!apply_pickup(
+            PickupKind::Attachment(Attachment::Scope),
+            &mut health,
+            None,
+            Some(&mut attachments)
+        ));
+    }
+
+    #[test]
+    fn pickups_heal_refill_and_equip() {
+        let mut health = Health::basic();
+        health.current = 10.0;
+        let mut gun = Gun {
+            ammo_in_magazine: 1,
+            is_reloading: true,
+            ..Gun::default()
+        };
+        let mut attachments = WeaponAttachments::from_requested(&[Attachment::Scope]);
+
+        assert!(apply_pickup(
+            PickupKind::HealthPack,
+            &mut health,
+            None,
+            None
+        ));
+        assert_eq!(health.current, 10.0 + HEALTH_PACK_AMOUNT);
+
+        assert!(apply_pickup(
+            PickupKind::Ammo,
+            &mut health,
+            Some(&mut gun),
+            None
+        ));
+        assert_eq!(gun.ammo_in_magazine, gun.magazine_size);
+        assert!(!gun.is_reloading);
+
+        assert!(apply_pickup(
+            PickupKind::Attachment(Attachment::RedDot),
+            &mut health,
+            None,
+            Some(&mut attachments)
+        ));
+        assert_eq!(attachments.equipped, vec![Attachment::RedDot]);
+    }
+}
@@ -0,0 +1,164 @@
+use bevy::prelude::Color;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::entities::color_from_id;
+
+/// Longest display name, in characters.
+pub const MAX_NAME_CHARS: usize = 16;
+
+/// Colors players can pick for their character.
+pub const PLAYER_COLORS: [Color; 8] = [
+    Color::srgb(0.9, 0.3, 0.3),
+    Color::srgb(0.95, 0.6, 0.2),
+    Color::srgb(0.95, 0.85, 0.3),
+    Color::srgb(0.4, 0.8, 0.35),
+    Color::srgb(0.3, 0.75, 0.8),
+    Color::srgb(0.35, 0.5, 0.95),
+    Color::srgb(0.65, 0.4, 0.9),
+    Color::srgb(0.9, 0.45, 0.7),
+];
+
+/// Display name and color of a player. Clients ask for one, the server keeps the validated
+/// version in the lobby.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlayerProfile {
+    pub name: String,
+    /// Index into [`PLAYER_COLORS`]; `None` keeps the color derived from the player id.
+    pub color: Option<u8>,
+}
+
+/// Profile of each lobby player, by player id.
+pub type PlayerProfiles = HashMap<u64, PlayerProfile>;
+
+/// Name shown for players that did not pick one.
+pub fn default_player_name(player: u64) -> String {
+    format!("Player_{}", player)
+}
+
+/// `raw` without control characters and runs of whitespace, cut to [`MAX_NAME_CHARS`].
+/// `None` if nothing is left.
+pub fn sanitize_name(raw: &str) -> Option<String> {
+    let name: String = raw
+        .split_whitespace()
+        .map(|word| word.chars().filter(|c| !c.is_control()).collect::<String>())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .take(MAX_NAME_CHARS)
+        .collect();
+    let name = name.trim_end().to_string();
+    (!name.is_empty()).then_some(name)
+}
+
+/// `name`, or `name (2)`, `name (3)`... if another player already goes by it, ignoring case.
+pub fn unique_name(name: &str, player: u64, profiles: &PlayerProfiles) -> String {
+    let taken = |candidate: &str| {
+        profiles.iter().any(|(other, profile)| {
+            *other != player && profile.name.eq_ignore_ascii_case(candidate)
+        })
+    };
+    if !taken(name) {
+        return name.to_string();
+    }
+    (2..)
+        .map(|suffix| format!("{} ({})", name, suffix))
+        .find(|candidate| !taken(candidate))
+        .expect("some suffix is free")
+}
+
+/// The profile `player` gets for asking for `requested`: a clean, unique name (the default
+/// one if nothing usable was sent) and a color from the palette.
+pub fn validate_profile(
+    requested: &PlayerProfile,
+    player: u64,
+    profiles: &PlayerProfiles,
+) -> PlayerProfile {
+    let name = sanitize_name(&requested.name).unwrap_or_else(|| default_player_name(player));
+    PlayerProfile {
+        name: unique_name(&name, player, profiles),
+        color: requested
+            .color
+            .filter(|index| usize::from(*index) < PLAYER_COLORS.len()),
+    }
+}
+
+/// Name of `player`, its default one without a profile.
+pub fn player_name(profiles: &PlayerProfiles, player: u64) -> String {
+    profiles.get(&player).map_or_else(
+        || default_player_name(player),
+        |profile| profile.name.clone(),
+    )
+}
+
+/// Color of `player`: the one picked, else the one derived from its id.
+pub fn player_color(profiles: &PlayerProfiles, player: u64) -> Color {
+    profiles
+        .get(&player)
+        .and_then(|profile| profile.color)
+        .and_then(|index| PLAYER_COLORS.get(usize::from(index)).copied())
+        .unwrap_or_else(|| color_from_id(player))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        MAX_NAME_CHARS, PLAYER_COLORS, PlayerProfile, PlayerProfiles, player_color, player_name,
+        sanitize_name, validate_profile,
+    };
+    use crate::entities::color_from_id;
+
+    fn request(name: &str, color: Option<u8>) -> PlayerProfile {
+        PlayerProfile {
+            name: name.to_string(),
+            color,
+        }
+    }
+
+    #[test]
+    fn names_are_cleaned_up_and_cut() {
+        assert_eq!(
+            sanitize_name("  Ada \t\n Lovelace\u{7} "),
+            Some("Ada Lovelace".to_string())
+        );
+        assert_eq!(sanitize_name(" \u{1b}\n "), None);
+
+        let long = sanitize_name(&"x".repeat(40)).unwrap();
+        assert_eq!(long.chars().count(), MAX_NAME_CHARS);
+        // A cut landing on a space does not leave it trailing.
+        assert_eq!(
+            sanitize_name("abcdefghijklmno pqr"),
+            Some("abcdefghijklmno".to_string())
+        );
+    }
+
+    #[test]
+    fn taken_names_get_a_suffix_and_bad_requests_fall_back() {
+        let mut profiles = PlayerProfiles::new();
+        profiles.insert(1, validate_profile(&request("Ada", Some(2)), 1, &profiles));
+        profiles.insert(2, validate_profile(&request("ada", None), 2, &profiles));
+        profiles.insert(
+            3,
+            validate_profile(&request("Ada", Some(200)), 3, &profiles),
+        );
+
+        assert_eq!(profiles[&1].name, "Ada");
+        assert_eq!(profiles[&2].name, "ada (2)");
+        assert_eq!(profiles[&3].name, "Ada (3)");
+        assert_eq!(profiles[&3].color, None);
+        // Keeping its own name is not a clash.
+        assert_eq!(
+            validate_profile(&request("Ada", None), 1, &profiles).name,
+            "Ada"
+        );
+
+        assert_eq!(
+            validate_profile(&request("", None), 4, &profiles).name,
+            "Player_4"
+        );
+        assert_eq!(player_name(&profiles, 5), "Player_5");
+        assert_eq!(player_color(&profiles, 1), PLAYER_COLORS[2]);
+        assert_eq!(player_color(&profiles, 3), color_from_id(3));
+    }
+}
@@ -0,0 +1,120 @@
+use bevy::prelude::Component;
+use serde::{Deserialize, Serialize};
+
+/// Damage dealt within this window before a kill earns an assist.
+pub const ASSIST_WINDOW_SECS: f32 = 10.0;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlayerScore {
+    /// `PlayerId` bits of the scoring player.
+    pub player_id: u64,
+    pub kills: u32,
+    pub deaths: u32,
+    pub assists: u32,
+}
+
+/// Kills, deaths and assists of every player in the match. Server authoritative and
+/// replicated to all clients for the scoreboard.
+#[derive(Component, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MatchScore {
+    pub players: Vec<PlayerScore>,
+}
+
+impl MatchScore {
+    pub fn get(&self, player_id: u64) -> Option<&PlayerScore> {
+        self.players
+            .iter()
+            .find(|score| score.player_id == player_id)
+    }
+
+    fn entry(&mut self, player_id: u64) -> &mut PlayerScore {
+        if let Some(index) = self
+            .players
+            .iter()
+            .position(|score| score.player_id == player_id)
+        {
+            return &mut self.players[index];
+        }
+
+        self.players.push(PlayerScore {
+            player_id,
+            ..Default::default()
+        });
+        self.players
+            .last_mut()
+            .expect("score entry was just pushed")
+    }
+
+    /// Record a kill. `killer` and `victim` are `None` for non-player entities (bots,
+    /// environment). Suicides count as a death only, and the killer never assists itself.
+    pub fn record_kill(&mut self, killer: Option<u64>, victim: Option<u64>, assisters: &[u64]) {
+        if let Some(victim) = victim {
+            self.entry(victim).deaths += 1;
+        }
+
+        let Some(killer) = killer.filter(|killer| Some(*killer) != victim) else {
+            return;
+        };
+        self.entry(killer).kills += 1;
+
+        for &assister in assisters {
+            if assister != killer && Some(assister) != victim {
+                self.entry(assister).assists += 1;
+            }
+        }
+    }
+
+    /// Scores ordered for display: most kills first, then fewest deaths.
+    pub fn ranked(&self) -> Vec<PlayerScore> {
+        let mut ranked = self.players.clone();
+        ranked.sort_by(|a, b| {
+            b.kills
+                .cmp(&a.kills)
+                .then(a.deaths.cmp(&b.deaths))
+                .then(b.assists.cmp(&a.assists))
+                .then(a.player_id.cmp(&b.player_id))
+        });
+        ranked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MatchScore;
+
+    #[test]
+    fn kill_updates_killer_victim_and_assisters() {
+        let mut score = MatchScore::default();
+        score.record_kill(Some(1), Some(2), &[1, 3, 2]);
+
+        let killer = score.get(1).expect("killer should be scored");
+        let victim = score.get(2).expect("victim should be scored");
+        let assister = score.get(3).expect("assister should be scored");
+
+        assert_eq!((killer.kills, killer.assists), (1, 0));
+        assert_eq!((victim.deaths, victim.assists), (1, 0));
+        assert_eq!(assister.assists, 1);
+    }
+
+    #[test]
+    fn suicide_and_bot_kills_only_count_deaths() {
+        let mut score = MatchScore::default();
+        score.record_kill(Some(1), Some(1), &[]);
+        score.record_kill(None, Some(1), &[2]);
+
+        let player = score.get(1).expect("player should be scored");
+        assert_eq!((player.kills, player.deaths), (0, 2));
+        assert!(score.get(2).is_none());
+    }
+
+    #[test]
+    fn ranking_orders_by_kills_then_deaths() {
+        let mut score = MatchScore::default();
+        score.record_kill(Some(1), None, &[]);
+        score.record_kill(Some(2), None, &[]);
+        score.record_kill(Some(2), Some(1), &[]);
+
+        let ranked: Vec<u64> = score.ranked().iter().map(|s| s.player_id).collect();
+        assert_eq!(ranked, vec![2, 1]);
+    }
+}
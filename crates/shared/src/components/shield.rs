@@ -0,0 +1,124 @@
+use bevy::prelude::{Component, Query, Reflect, ReflectComponent, Res};
+use serde::{Deserialize, Serialize};
+
+use crate::clock::GameClock;
+
+/// Energy shield layered on top of `Health`. Absorbs a fraction of incoming damage from its
+/// own pool and recharges after a delay without damage.
+#[derive(Component, Reflect, Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[reflect(Component)]
+pub struct Shield {
+    pub current: f32,
+    pub max: f32,
+    pub absorption: f32,
+    pub regeneration_rate: f32,
+    pub regeneration_delay: f32,
+    pub last_damage_time: f32,
+}
+
+impl Shield {
+    pub fn new(max: f32, absorption: f32, regeneration_rate: f32, regeneration_delay: f32) -> Self {
+        Self {
+            current: max,
+            max,
+            absorption: absorption.clamp(0.0, 1.0),
+            regeneration_rate,
+            regeneration_delay,
+            last_damage_time: 0.0,
+        }
+    }
+
+    pub fn percentage(&self) -> f32 {
+        if self.max <= 0.0 {
+            0.0
+        } else {
+            (self.current / self.max).clamp(0.0, 1.0)
+        }
+    }
+
+    /// Absorbs part of `amount` and returns the damage that goes through to health.
+    /// `absorption_scale` lowers the absorbed fraction (armor penetration, headshots).
+    pub fn absorb(&mut self, amount: f32, absorption_scale: f32, current_time: f32) -> f32 {
+        if amount <= 0.0 {
+            return 0.0;
+        }
+
+        self.last_damage_time = current_time;
+        if self.current <= 0.0 {
+            return amount;
+        }
+
+        let absorption = (self.absorption * absorption_scale).clamp(0.0, 1.0);
+        let absorbed = (amount * absorption).min(self.current);
+        self.current -= absorbed;
+        amount - absorbed
+    }
+
+    pub fn reset(&mut self) {
+        self.current = self.max;
+        self.last_damage_time = 0.0;
+    }
+
+    pub fn can_regenerate_now(&self, current_time: f32) -> bool {
+        self.current < self.max && (current_time - self.last_damage_time) >= self.regeneration_delay
+    }
+}
+
+pub(crate) fn shield_regeneration_system(
+    mut shield_query: Query<&mut Shield>,
+    clock: Res<GameClock>,
+) {
+    let current_time = clock.elapsed_secs();
+    let delta_time = clock.delta_secs();
+
+    for mut shield in shield_query.iter_mut() {
+        if shield.can_regenerate_now(current_time) {
+            shield.current =
+                (shield.current + shield.regeneration_rate * delta_time).min(shield.max);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Shield;
+
+    #[test]
+    fn shield_absorbs_fraction_until_depleted() {
+        let mut shield = Shield::new(20.0, 0.5, 10.0, 4.0);
+
+        let passed = shield.absorb(30.0, 1.0, 1.0);
+        assert_eq!(passed, 15.0);
+        assert_eq!(shield.current, 5.0);
+
+        let passed = shield.absorb(30.0, 1.0, 2.0);
+        assert_eq!(passed, 25.0);
+        assert_eq!(shield.current, 0.0);
+
+        let passed = shield.absorb(30.0, 1.0, 3.0);
+        assert_eq!(passed, 30.0);
+    }
+
+    #[test]
+    fn reduced_absorption_scale_lets_more_damage_through() {
+        let mut shield = Shield::new(100.0, 0.6, 10.0, 4.0);
+        let full = shield.clone().absorb(50.0, 1.0, 1.0);
+        let penetrated = shield.absorb(50.0, 0.5, 1.0);
+
+        assert!(penetrated > full);
+        assert_eq!(penetrated, 35.0);
+    }
+
+    #[test]
+    fn shield_regenerates_only_after_delay() {
+        let mut shield = Shield::new(50.0, 0.6, 10.0, 4.0);
+        shield.absorb(40.0, 1.0, 2.0);
+
+        assert!(!shield.can_regenerate_now(5.9));
+        assert!(shield.can_regenerate_now(6.0));
+
+        shield.reset();
+        assert!(!shield.can_regenerate_now(10.0));
+        assert_eq!(shield.percentage(), 1.0);
+    }
+}
@@ -0,0 +1,139 @@
+use bevy::prelude::{Component, Reflect, ReflectComponent};
+use serde::{Deserialize, Serialize};
+
+/// Sprint stamina: sprinting drains it and it refills while not sprinting, as its
+/// [`StaminaConfig`] says. Characters without it sprint for as long as they like.
+#[derive(Component, Reflect, Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[reflect(Component)]
+pub struct Stamina {
+    pub current: f32,
+    pub max: f32,
+    /// Ran empty and has not refilled to the recovery fraction yet.
+    pub exhausted: bool,
+    /// Time left before stamina that ran empty starts refilling.
+    pub lockout_secs: f32,
+}
+
+/// How a character's [`Stamina`] drains and refills. Replicated next to it, so game modes
+/// and training scenarios can tune it per character; characters without one use the
+/// default.
+#[derive(Component, Reflect, Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[reflect(Component)]
+pub struct StaminaConfig {
+    /// Spent per second of sprinting.
+    pub drain_rate: f32,
+    pub regeneration_rate: f32,
+    /// Once emptied, stamina has to refill to this fraction before sprinting works again.
+    pub recovery_fraction: f32,
+    /// Once emptied, stamina waits this long before it starts refilling.
+    pub exhaustion_lockout_secs: f32,
+}
+
+impl Default for StaminaConfig {
+    fn default() -> Self {
+        Self {
+            drain_rate: 25.0,
+            regeneration_rate: 20.0,
+            recovery_fraction: 0.25,
+            exhaustion_lockout_secs: 0.75,
+        }
+    }
+}
+
+impl Default for Stamina {
+    fn default() -> Self {
+        Self::new(100.0)
+    }
+}
+
+impl Stamina {
+    pub fn new(max: f32) -> Self {
+        Self {
+            current: max,
+            max,
+            exhausted: false,
+            lockout_secs: 0.0,
+        }
+    }
+
+    pub fn percentage(&self) -> f32 {
+        if self.max <= 0.0 {
+            0.0
+        } else {
+            (self.current / self.max).clamp(0.0, 1.0)
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.current >= self.max && !self.exhausted
+    }
+
+    /// Advances `delta_secs` and returns whether the character sprints: it drains while
+    /// `wants_sprint` and stamina lasts, and refills otherwise once any lockout is over.
+    pub fn sprint(&mut self, config: &StaminaConfig, wants_sprint: bool, delta_secs: f32) -> bool {
+        if self.lockout_secs > 0.0 {
+            self.lockout_secs = (self.lockout_secs - delta_secs).max(0.0);
+            return false;
+        }
+        if self.exhausted && self.current >= self.max * config.recovery_fraction {
+            self.exhausted = false;
+        }
+
+        let sprinting = wants_sprint && !self.exhausted && self.current > 0.0;
+        if sprinting {
+            self.current = (self.current - config.drain_rate * delta_secs).max(0.0);
+            if self.current <= 0.0 {
+                self.exhausted = true;
+                self.lockout_secs = config.exhaustion_lockout_secs;
+            }
+        } else {
+            self.current = (self.current + config.regeneration_rate * delta_secs).min(self.max);
+        }
+        sprinting
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Stamina, StaminaConfig};
+
+    fn config() -> StaminaConfig {
+        StaminaConfig {
+            drain_rate: 5.0,
+            regeneration_rate: 2.0,
+            recovery_fraction: 0.25,
+            exhaustion_lockout_secs: 0.5,
+        }
+    }
+
+    #[test]
+    fn sprinting_drains_until_empty_then_waits_for_recovery() {
+        let config = config();
+        let mut stamina = Stamina::new(10.0);
+        assert!(stamina.sprint(&config, true, 1.0));
+        assert_eq!(stamina.current, 5.0);
+        assert!(stamina.sprint(&config, true, 1.0));
+        assert!(stamina.exhausted);
+
+        // Still holding sprint, but empty: locked out for a moment, then refills until a
+        // quarter is back.
+        assert!(!stamina.sprint(&config, true, 0.5));
+        assert_eq!(stamina.current, 0.0);
+        assert!(!stamina.sprint(&config, true, 1.0));
+        assert_eq!(stamina.current, 2.0);
+        assert!(!stamina.sprint(&config, true, 0.25));
+        assert!(stamina.sprint(&config, true, 0.1));
+        assert!(!stamina.exhausted);
+    }
+
+    #[test]
+    fn resting_refills_up_to_max() {
+        let config = config();
+        let mut stamina = Stamina::new(10.0);
+        stamina.current = 9.0;
+        assert!(!stamina.sprint(&config, false, 1.0));
+        assert!(!stamina.sprint(&config, false, 1.0));
+        assert_eq!(stamina.current, 10.0);
+        assert!(stamina.is_full());
+    }
+}
@@ -0,0 +1,182 @@
+use bevy::prelude::{Color, Component, Resource};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Replicated team of a player. Characters without a team (bots, players in lobbies that
+/// predate teams) can hurt and be hurt by anyone.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Team {
+    Red,
+    Blue,
+}
+
+impl Team {
+    pub const ALL: [Team; 2] = [Team::Red, Team::Blue];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Team::Red => "Red",
+            Team::Blue => "Blue",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Team::Red => Color::srgb(0.85, 0.2, 0.2),
+            Team::Blue => Color::srgb(0.2, 0.4, 0.9),
+        }
+    }
+}
+
+/// Per-match team rules. The server owns them as a resource and mirrors them onto a
+/// replicated entity for the lobby UI.
+#[derive(Resource, Component, Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TeamRules {
+    pub friendly_fire: bool,
+}
+
+/// Team of each lobby player, by player id.
+pub type TeamAssignments = HashMap<u64, Team>;
+
+fn team_size(teams: &TeamAssignments, team: Team, except: Option<u64>) -> usize {
+    teams
+        .iter()
+        .filter(|(player, assigned)| **assigned == team && Some(**player) != except)
+        .count()
+}
+
+/// Team a newcomer should join: the smaller one, red on a tie.
+pub fn smallest_team(teams: &TeamAssignments) -> Team {
+    if team_size(teams, Team::Blue, None) < team_size(teams, Team::Red, None) {
+        Team::Blue
+    } else {
+        Team::Red
+    }
+}
+
+/// A player may pick a team as long as the teams end up at most one player apart.
+pub fn can_join_team(teams: &TeamAssignments, player: u64, team: Team) -> bool {
+    let size = team_size(teams, team, Some(player));
+    Team::ALL
+        .into_iter()
+        .filter(|other| *other != team)
+        .all(|other| size <= team_size(teams, other, Some(player)))
+}
+
+/// Drop players that left and put newcomers on the smallest team, in lobby order.
+pub fn assign_teams(players: &[u64], teams: &TeamAssignments) -> TeamAssignments {
+    let mut assigned: TeamAssignments = teams
+        .iter()
+        .filter(|(player, _)| players.contains(player))
+        .map(|(player, team)| (*player, *team))
+        .collect();
+    for player in players {
+        if !assigned.contains_key(player) {
+            let team = smallest_team(&assigned);
+            assigned.insert(*player, team);
+        }
+    }
+    assigned
+}
+
+/// [`assign_teams`], then move the most recent joiners off the larger team until the teams
+/// are at most one player apart. Only used in the lobby; teams are fixed once a match runs.
+pub fn rebalance_teams(players: &[u64], teams: &TeamAssignments) -> TeamAssignments {
+    let mut balanced = assign_teams(players, teams);
+    loop {
+        let red = team_size(&balanced, Team::Red, None);
+        let blue = team_size(&balanced, Team::Blue, None);
+        let (larger, smaller) = match red.abs_diff(blue) {
+            0 | 1 => return balanced,
+            _ if red > blue => (Team::Red, Team::Blue),
+            _ => (Team::Blue, Team::Red),
+        };
+        let Some(mover) = players
+            .iter()
+            .rev()
+            .find(|player| balanced.get(player) == Some(&larger))
+        else {
+            return balanced;
+        };
+        balanced.insert(*mover, smaller);
+    }
+}
+
+/// Team of `player`, its index among its teammates in lobby order and the team size. Used
+/// to spread each team around its own spawn point.
+pub fn team_slot(
+    players: &[u64],
+    teams: &TeamAssignments,
+    player: u64,
+) -> Option<(Team, usize, usize)> {
+    let team = *teams.get(&player)?;
+    let teammates: Vec<u64> = players
+        .iter()
+        .copied()
+        .filter(|id| teams.get(id) == Some(&team))
+        .collect();
+    let index = teammates.iter().position(|id| *id == player)?;
+    Some((team, index, teammates.len()))
+}
+
+/// Damage between two different teammates. Self-damage is not friendly fire.
+pub fn is_friendly_fire(source_team: Option<Team>, target_team: Option<Team>) -> bool {
+    matches!((source_team, target_team), (Some(source), Some(target)) if source == target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Team, TeamAssignments, assign_teams, can_join_team, is_friendly_fire, rebalance_teams,
+        team_slot,
+    };
+
+    #[test]
+    fn newcomers_alternate_and_leavers_are_rebalanced() {
+        let players = vec![1, 2, 3, 4];
+        let teams = assign_teams(&players, &TeamAssignments::new());
+        assert_eq!(teams[&1], Team::Red);
+        assert_eq!(teams[&2], Team::Blue);
+        assert_eq!(teams[&3], Team::Red);
+        assert_eq!(teams[&4], Team::Blue);
+
+        // Both blue players leave: the next newcomer joins blue.
+        let players = vec![1, 3, 5];
+        let teams = assign_teams(&players, &teams);
+        assert_eq!(teams[&5], Team::Blue);
+        // Lopsided teams: the latest red joiners move over.
+        let players = vec![1, 3, 5, 6];
+        let lopsided: TeamAssignments = [
+            (1, Team::Red),
+            (3, Team::Red),
+            (5, Team::Red),
+            (6, Team::Red),
+        ]
+        .into();
+        let teams = rebalance_teams(&players, &lopsided);
+        assert_eq!(teams[&6], Team::Blue);
+        assert_eq!(teams[&5], Team::Blue);
+        assert_eq!(teams[&1], Team::Red);
+
+        assert_eq!(team_slot(&players, &teams, 3), Some((Team::Red, 1, 2)));
+        assert_eq!(team_slot(&players, &teams, 7), None);
+    }
+
+    #[test]
+    fn team_switches_cannot_unbalance_teams() {
+        let teams: TeamAssignments = [(1, Team::Red), (2, Team::Blue), (3, Team::Red)].into();
+
+        assert!(can_join_team(&teams, 1, Team::Blue));
+        assert!(!can_join_team(&teams, 2, Team::Red));
+        assert!(can_join_team(&teams, 4, Team::Blue));
+        assert!(!can_join_team(&teams, 4, Team::Red));
+    }
+
+    #[test]
+    fn only_teammates_hitting_each_other_is_friendly_fire() {
+        assert!(is_friendly_fire(Some(Team::Red), Some(Team::Red)));
+        assert!(!is_friendly_fire(Some(Team::Red), Some(Team::Blue)));
+        assert!(!is_friendly_fire(None, Some(Team::Red)));
+        assert!(!is_friendly_fire(Some(Team::Blue), None));
+    }
+}
@@ -0,0 +1,155 @@
+use bevy::prelude::Color;
+use serde::{Deserialize, Serialize};
+
+/// What a shot hit, for picking its impact effect.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImpactSurface {
+    Character,
+    /// Destructible props.
+    Prop,
+    /// Level geometry and anything else.
+    Wall,
+}
+
+/// Beam from the barrel to where the shot ended; it thins out over its lifetime.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TracerStyle {
+    pub color: Color,
+    pub width: f32,
+    pub lifetime_secs: f32,
+}
+
+/// Flash at the barrel on every shot, with a point light when `light_intensity` is not 0.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MuzzleFlash {
+    pub color: Color,
+    pub size: f32,
+    pub light_intensity: f32,
+    pub lifetime_secs: f32,
+}
+
+/// Burst of `count` particles thrown back from the impact point.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ImpactParticles {
+    pub color: Color,
+    pub count: u32,
+    pub speed: f32,
+    pub size: f32,
+    pub lifetime_secs: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ImpactEffects {
+    pub character: ImpactParticles,
+    pub prop: ImpactParticles,
+    pub wall: ImpactParticles,
+}
+
+impl ImpactEffects {
+    pub fn for_surface(&self, surface: ImpactSurface) -> &ImpactParticles {
+        match surface {
+            ImpactSurface::Character => &self.character,
+            ImpactSurface::Prop => &self.prop,
+            ImpactSurface::Wall => &self.wall,
+        }
+    }
+}
+
+/// How a gun's shots look. Part of the weapon definition, so a new weapon brings its own
+/// effects and the client only plays them.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WeaponVfx {
+    /// `None` for weapons whose shots leave no visible trail.
+    pub tracer: Option<TracerStyle>,
+    pub muzzle_flash: MuzzleFlash,
+    pub impacts: ImpactEffects,
+}
+
+impl Default for WeaponVfx {
+    fn default() -> Self {
+        Self {
+            tracer: Some(TracerStyle {
+                color: Color::srgb(1.0, 0.85, 0.45),
+                width: 0.02,
+                lifetime_secs: 0.08,
+            }),
+            muzzle_flash: MuzzleFlash {
+                color: Color::srgb(1.0, 0.75, 0.35),
+                size: 0.12,
+                light_intensity: 40_000.0,
+                lifetime_secs: 0.05,
+            },
+            impacts: ImpactEffects {
+                character: ImpactParticles {
+                    color: Color::srgb(0.7, 0.05, 0.05),
+                    count: 6,
+                    speed: 2.5,
+                    size: 0.05,
+                    lifetime_secs: 0.3,
+                },
+                prop: ImpactParticles {
+                    color: Color::srgb(0.55, 0.4, 0.25),
+                    count: 8,
+                    speed: 3.5,
+                    size: 0.06,
+                    lifetime_secs: 0.45,
+                },
+                wall: ImpactParticles {
+                    color: Color::srgb(0.6, 0.6, 0.58),
+                    count: 5,
+                    speed: 3.0,
+                    size: 0.04,
+                    lifetime_secs: 0.35,
+                },
+            },
+        }
+    }
+}
+
+impl WeaponVfx {
+    pub fn with_tracer(mut self, tracer: Option<TracerStyle>) -> Self {
+        self.tracer = tracer;
+        self
+    }
+
+    pub fn with_muzzle_flash(mut self, muzzle_flash: MuzzleFlash) -> Self {
+        self.muzzle_flash = muzzle_flash;
+        self
+    }
+
+    /// Scales the particle count of every impact, for weapons that hit harder or softer.
+    pub fn with_impact_scale(mut self, scale: f32) -> Self {
+        for particles in [
+            &mut self.impacts.character,
+            &mut self.impacts.prop,
+            &mut self.impacts.wall,
+        ] {
+            particles.count = ((particles.count as f32) * scale).round().max(1.0) as u32;
+            particles.speed *= scale.sqrt();
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ImpactSurface, WeaponVfx};
+
+    #[test]
+    fn impact_scale_keeps_at_least_one_particle_per_surface() {
+        let vfx = WeaponVfx::default();
+        let heavy = vfx.with_impact_scale(2.0);
+        let light = vfx.with_impact_scale(0.01);
+
+        for surface in [
+            ImpactSurface::Character,
+            ImpactSurface::Prop,
+            ImpactSurface::Wall,
+        ] {
+            let base = vfx.impacts.for_surface(surface);
+            assert_eq!(heavy.impacts.for_surface(surface).count, base.count * 2);
+            assert!(heavy.impacts.for_surface(surface).speed > base.speed);
+            assert_eq!(light.impacts.for_surface(surface).count, 1);
+        }
+    }
+}
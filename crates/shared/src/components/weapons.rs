@@ -1,14 +1,21 @@
-use crate::components::health::DamageEvent;
+use crate::aim_assist::{AimAssistSettings, assist_aim};
+use crate::balance::BalanceConfig;
+use crate::clock::{GameClock, ensure_game_clock};
+use crate::components::attachments::{WeaponAttachments, apply_attachment_stats};
+use crate::components::health::{DamageEvent, Health};
+use crate::components::loadout::switch_weapons;
+use crate::components::weapon_vfx::WeaponVfx;
 use crate::inputs::input::PlayerAction;
 use crate::navigation::NavigationObstacle;
+use crate::protocol::CharacterMarker;
 use avian3d::prelude::{
     Collider, LinearVelocity, Position, RigidBody, Rotation, SpatialQueryFilter,
     SpatialQueryPipeline,
 };
 use bevy::ecs::query::With;
 use bevy::prelude::{
-    Commands, Component, Dir3, Entity, MessageWriter, Query, Res, Time, Timer, TimerMode, Vec3,
-    info,
+    Commands, Component, Dir3, Entity, Message, MessageWriter, Quat, Query, Res, Timer, TimerMode,
+    Vec3, info,
 };
 use leafwing_input_manager::prelude::ActionState;
 use lightyear::prelude::ControlledBy;
@@ -18,9 +25,13 @@ pub struct WeaponsPlugin;
 
 impl bevy::prelude::Plugin for WeaponsPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
+        ensure_game_clock(app);
+        app.add_message::<ShotFired>();
         app.add_systems(
             bevy::prelude::FixedUpdate,
             (
+                switch_weapons,
+                apply_attachment_stats,
                 fire_gun_system,
                 fire_projectile_gun_system,
                 update_simple_projectiles,
@@ -30,35 +41,82 @@ impl bevy::prelude::Plugin for WeaponsPlugin {
     }
 }
 
+pub const BASE_MAGAZINE_SIZE: u32 = 8;
+/// Upward kick (in radians) each shot adds to the next ones, before attachments.
+pub const RECOIL_PER_SHOT: f32 = 0.02;
+/// Most kick shots can build up to.
+pub const MAX_RECOIL: f32 = 0.12;
+/// Kick (in radians) a gun recovers per second.
+pub const RECOIL_RECOVERY_PER_SEC: f32 = 0.2;
+
 #[derive(Component, Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct Gun {
     pub cooldown: Timer,
     pub damage: f32,
     pub range: f32,
+    /// Fraction of shield absorption ignored by this gun's hits.
+    pub penetration: f32,
+    /// Magazine size before attachments.
+    pub base_magazine_size: u32,
     pub magazine_size: u32,
     pub ammo_in_magazine: u32,
     pub reload_timer: Timer,
     pub is_reloading: bool,
+    /// Heat-based firing instead of the magazine, for weapons defined with one.
+    pub heat: Option<WeaponHeat>,
+    /// Fires on release with the power charged while fire was held, for weapons defined
+    /// with one.
+    pub charge: Option<WeaponCharge>,
+    /// Tracer, muzzle flash and impacts of this gun's shots.
+    pub vfx: WeaponVfx,
+    /// How far above the aim (in radians) the next shot goes, from the kick of the ones
+    /// before it. Predicted with the rest of the gun, so rollbacks replay it.
+    pub recoil: f32,
 }
 
 impl Default for Gun {
     fn default() -> Self {
-        let magazine_size = 8;
+        let magazine_size = BASE_MAGAZINE_SIZE;
         Self {
             cooldown: Timer::from_seconds(0.3, TimerMode::Once), // ~3 shots/sec
             damage: 25.0,
             range: 100.0,
+            penetration: 0.2,
+            base_magazine_size: magazine_size,
             magazine_size,
             ammo_in_magazine: magazine_size,
             reload_timer: Timer::from_seconds(1.2, TimerMode::Once),
             is_reloading: false,
+            heat: None,
+            charge: None,
+            vfx: WeaponVfx::default(),
+            recoil: 0.0,
         }
     }
 }
 
 impl Gun {
+    pub fn with_heat(mut self, heat: WeaponHeat) -> Self {
+        self.heat = Some(heat);
+        self
+    }
+
+    pub fn with_charge(mut self, charge: WeaponCharge) -> Self {
+        self.charge = Some(charge);
+        self
+    }
+
+    pub fn with_vfx(mut self, vfx: WeaponVfx) -> Self {
+        self.vfx = vfx;
+        self
+    }
+
+    pub fn is_venting(&self) -> bool {
+        self.heat.as_ref().is_some_and(|heat| heat.venting)
+    }
+
     pub fn start_reload(&mut self) {
-        if self.is_reloading || self.ammo_in_magazine >= self.magazine_size {
+        if self.heat.is_some() || self.is_reloading || self.ammo_in_magazine >= self.magazine_size {
             return;
         }
 
@@ -77,6 +135,116 @@ impl Gun {
             self.ammo_in_magazine = self.magazine_size;
         }
     }
+
+    /// Kick the aim of the next shots up, scaled by the attachments' `recoil_multiplier`.
+    pub fn kick(&mut self, recoil_multiplier: f32) {
+        self.recoil = (self.recoil + RECOIL_PER_SHOT * recoil_multiplier).min(MAX_RECOIL);
+    }
+
+    pub fn recover_recoil(&mut self, delta: std::time::Duration) {
+        self.recoil = (self.recoil - RECOIL_RECOVERY_PER_SEC * delta.as_secs_f32()).max(0.0);
+    }
+
+    /// Pay for one shot: heat for heat weapons, a round otherwise.
+    pub fn consume_shot(&mut self) {
+        match self.heat.as_mut() {
+            Some(heat) => heat.add_shot(),
+            None => self.ammo_in_magazine = self.ammo_in_magazine.saturating_sub(1),
+        }
+    }
+}
+
+/// Heat of a weapon that never reloads. Every shot adds `heat_per_shot`; heat bleeds off at
+/// `cooling_per_sec` while the trigger rests. Reaching full heat overheats the weapon: it
+/// vents for the length of `vent_timer` and cannot fire until it is cold again.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct WeaponHeat {
+    pub heat_per_shot: f32,
+    pub cooling_per_sec: f32,
+    pub vent_timer: Timer,
+    /// 0 when cold, 1 when overheated.
+    pub level: f32,
+    pub venting: bool,
+}
+
+impl WeaponHeat {
+    pub fn new(heat_per_shot: f32, cooling_per_sec: f32, vent_secs: f32) -> Self {
+        Self {
+            heat_per_shot,
+            cooling_per_sec,
+            vent_timer: Timer::from_seconds(vent_secs, TimerMode::Once),
+            level: 0.0,
+            venting: false,
+        }
+    }
+
+    pub fn add_shot(&mut self) {
+        self.level = (self.level + self.heat_per_shot).min(1.0);
+        if self.level >= 1.0 {
+            self.venting = true;
+            self.vent_timer.reset();
+        }
+    }
+
+    /// Cool down by `delta`. While venting the heat drains evenly over the vent.
+    pub fn tick(&mut self, delta: std::time::Duration) {
+        if self.venting {
+            self.vent_timer.tick(delta);
+            self.level = 1.0 - self.vent_timer.fraction();
+            if self.vent_timer.is_finished() {
+                self.venting = false;
+                self.level = 0.0;
+            }
+        } else {
+            self.level = (self.level - self.cooling_per_sec * delta.as_secs_f32()).max(0.0);
+        }
+    }
+}
+
+/// Charge of a weapon that fires on release. Holding fire builds charge for up to
+/// `full_charge_secs`; letting go fires one shot whose damage and range scale from
+/// `min_power` at a tap up to 1 at full charge.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct WeaponCharge {
+    /// Must be positive.
+    pub full_charge_secs: f32,
+    pub min_power: f32,
+    /// Seconds fire has been held since the last release, capped at full charge.
+    pub held_secs: f32,
+}
+
+impl WeaponCharge {
+    pub fn new(full_charge_secs: f32, min_power: f32) -> Self {
+        Self {
+            full_charge_secs,
+            min_power,
+            held_secs: 0.0,
+        }
+    }
+
+    pub fn is_charging(&self) -> bool {
+        self.held_secs > 0.0
+    }
+
+    /// 0 at rest, 1 when fully charged.
+    pub fn fraction(&self) -> f32 {
+        (self.held_secs / self.full_charge_secs).clamp(0.0, 1.0)
+    }
+
+    pub fn power(&self) -> f32 {
+        self.min_power + (1.0 - self.min_power) * self.fraction()
+    }
+
+    pub fn hold(&mut self, delta: std::time::Duration) {
+        self.held_secs = (self.held_secs + delta.as_secs_f32()).min(self.full_charge_secs);
+    }
+
+    /// Power of the shot fired by letting go, if fire was held at all.
+    pub fn release(&mut self) -> Option<f32> {
+        let power = self.is_charging().then(|| self.power());
+        self.held_secs = 0.0;
+        power
+    }
 }
 
 #[derive(Component, Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -87,7 +255,19 @@ pub struct HitEvent {
     pub hit_point: Vec3,
 }
 
+/// A hitscan shot, hit or miss, for effects. Written wherever the shot is simulated: on the
+/// server, and on the client for its own predicted character.
+#[derive(Message, Clone, Debug, PartialEq)]
+pub struct ShotFired {
+    pub shooter: Entity,
+    pub origin: Vec3,
+    /// Where the shot stopped: the hit point, or the end of the gun's range.
+    pub end: Vec3,
+    pub hit: Option<Entity>,
+}
+
 // Gun use raycast to detect hits. ProjectileGun spawns projectile entities.
+#[allow(clippy::too_many_arguments)]
 pub fn fire_gun_system(
     mut commands: Commands,
     mut query: Query<
@@ -97,16 +277,26 @@ pub fn fire_gun_system(
             &Position,
             &Rotation,
             &ActionState<PlayerAction>,
+            Option<&WeaponAttachments>,
         ),
         With<ControlledBy>,
     >,
     spatial_query: Res<SpatialQueryPipeline>,
     obstacle_query: Query<(), With<NavigationObstacle>>,
+    target_query: Query<&Position>,
     mut damage_writer: MessageWriter<DamageEvent>,
-    time: Res<Time>,
+    mut shot_writer: MessageWriter<ShotFired>,
+    balance: Option<Res<BalanceConfig>>,
+    aim_assist: Option<Res<AimAssistSettings>>,
+    assist_targets: Query<(Entity, &Position, &Health), With<CharacterMarker>>,
+    clock: Res<GameClock>,
 ) {
-    for (shooter_entity, mut gun, pos, rot, action_state) in query.iter_mut() {
-        gun.cooldown.tick(time.delta());
+    let default_balance = BalanceConfig::default();
+    let balance = balance.as_deref().unwrap_or(&default_balance);
+
+    for (shooter_entity, mut gun, pos, rot, action_state, attachments) in query.iter_mut() {
+        gun.cooldown.tick(clock.delta());
+        gun.recover_recoil(clock.delta());
 
         if action_state.disabled() {
             continue;
@@ -116,32 +306,60 @@ pub fn fire_gun_system(
             gun.start_reload();
         }
 
-        gun.tick_reload(time.delta());
+        gun.tick_reload(clock.delta());
+        if let Some(heat) = gun.heat.as_mut() {
+            heat.tick(clock.delta());
+        }
 
-        if action_state.pressed(&PlayerAction::Shoot) && gun.cooldown.is_finished() {
-            if gun.is_reloading {
-                continue;
-            }
+        // Runs on the server too: an overheated gun fires there no matter what the client
+        // predicted.
+        let ready = gun.cooldown.is_finished() && !gun.is_reloading && !gun.is_venting();
+        let pressed = action_state.pressed(&PlayerAction::Shoot);
+        if pressed && ready && gun.heat.is_none() && gun.ammo_in_magazine == 0 {
+            gun.start_reload();
+            continue;
+        }
 
-            if gun.ammo_in_magazine == 0 {
-                gun.start_reload();
-                continue;
+        // Charge weapons build power while fire is held and shoot on release. Both sides
+        // time the hold from the inputs they simulate, so the server never takes the
+        // client's word for the charge and rollback corrects a mispredicted one.
+        let power = match gun.charge.as_mut() {
+            Some(charge) if pressed => {
+                if ready {
+                    charge.hold(clock.delta());
+                }
+                None
             }
+            Some(charge) => charge.release(),
+            None => pressed.then_some(1.0),
+        };
 
-            // Calculate shooting direction from current player look rotation.
-            let direction = shoot_direction(rot);
-
-            // Create raycast filter to exclude the shooter
-            let filter = SpatialQueryFilter::default().with_excluded_entities([shooter_entity]);
+        if let Some(power) = power
+            && ready
+        {
+            let damage = gun.damage * power;
+            let range = gun.range * power;
 
             // Perform raycast from camera position (eye level)
             let eye_height = 1.5; // Approximate player eye height
             let shoot_origin = pos.0 + Vec3::new(0.0, eye_height, 0.0);
 
+            // Calculate shooting direction from current player look rotation.
+            let direction = assisted_direction(
+                aim_assist.as_deref(),
+                shooter_entity,
+                shoot_origin,
+                recoiled_direction(rot, gun.recoil),
+                &assist_targets,
+            );
+
+            // Create raycast filter to exclude the shooter
+            let filter = SpatialQueryFilter::default().with_excluded_entities([shooter_entity]);
+
             let primary_hit = spatial_query.cast_ray(
                 shoot_origin,
                 Dir3::new(direction).unwrap_or(Dir3::NEG_Z),
-                gun.range,
+                range,
                 false, // also detect hits when the ray starts inside or very close to a collider
                 &filter,
             );
@@ -154,7 +372,7 @@ pub fn fire_gun_system(
                     .cast_ray(
                         assist_origin,
                         Dir3::new(direction).unwrap_or(Dir3::NEG_Z),
-                        gun.range,
+                        range,
                         false,
                         &filter,
                     )
@@ -171,25 +389,46 @@ pub fn fire_gun_system(
                     hit_entity, hit.distance, hit_point
                 );
 
+                let headshot = target_query
+                    .get(hit_entity)
+                    .is_ok_and(|target| balance.is_headshot(hit_point.y - target.0.y));
+
                 // Send damage event - the health system will handle it
                 damage_writer.write(DamageEvent {
                     target: hit_entity,
-                    amount: gun.damage,
+                    amount: damage,
                     source: Some(shooter_entity),
+                    penetration: gun.penetration,
+                    headshot,
                 });
 
                 // Spawn hit event for further processing (effects, sounds, etc.)
                 commands.spawn(HitEvent {
-                    damage: gun.damage,
+                    damage,
                     hit_entity,
                     shooter: shooter_entity,
                     hit_point,
                 });
+                shot_writer.write(ShotFired {
+                    shooter: shooter_entity,
+                    origin: ray_origin,
+                    end: hit_point,
+                    hit: Some(hit_entity),
+                });
             } else {
                 info!("🔫 Gun fired but missed (no hit detected)");
+                shot_writer.write(ShotFired {
+                    shooter: shooter_entity,
+                    origin: shoot_origin,
+                    end: shoot_origin + direction * range,
+                    hit: None,
+                });
             }
 
-            gun.ammo_in_magazine = gun.ammo_in_magazine.saturating_sub(1);
+            gun.consume_shot();
+            gun.kick(
+                attachments.map_or(1.0, |attachments| attachments.modifiers().recoil_multiplier),
+            );
             gun.cooldown.reset();
         }
     }
@@ -199,6 +438,36 @@ fn shoot_direction(rotation: &Rotation) -> Vec3 {
     (rotation.0 * Vec3::NEG_Z).normalize_or_zero()
 }
 
+/// [`shoot_direction`] pitched up by `recoil` radians.
+fn recoiled_direction(rotation: &Rotation, recoil: f32) -> Vec3 {
+    let right = rotation.0 * Vec3::X;
+    Quat::from_axis_angle(right, recoil) * shoot_direction(rotation)
+}
+
+/// Apply the lobby's aim assist, if any. Only the server holds `AimAssistSettings` as a
+/// resource, so assisted shots are decided there and clients never predict them.
+fn assisted_direction(
+    settings: Option<&AimAssistSettings>,
+    shooter: Entity,
+    origin: Vec3,
+    direction: Vec3,
+    targets: &Query<(Entity, &Position, &Health), With<CharacterMarker>>,
+) -> Vec3 {
+    if !settings.is_some_and(AimAssistSettings::is_active) {
+        return direction;
+    }
+
+    assist_aim(
+        shooter,
+        origin,
+        direction,
+        targets
+            .iter()
+            .filter(|(_, _, health)| !health.is_dead)
+            .map(|(entity, position, _)| (entity, position.0)),
+    )
+}
+
 #[derive(Component, Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct ProjectileGun {
     pub cooldown: Timer,
@@ -229,17 +498,25 @@ pub fn fire_projectile_gun_system(
         &Rotation,
         &ActionState<PlayerAction>,
     )>,
-    time: Res<Time>,
+    aim_assist: Option<Res<AimAssistSettings>>,
+    assist_targets: Query<(Entity, &Position, &Health), With<CharacterMarker>>,
+    clock: Res<GameClock>,
 ) {
     for (entity, mut gun, pos, rot, action_state) in query.iter_mut() {
-        gun.cooldown.tick(time.delta());
+        gun.cooldown.tick(clock.delta());
 
         if action_state.disabled() {
             continue;
         }
 
         if action_state.pressed(&PlayerAction::Shoot) && gun.cooldown.is_finished() {
-            let direction = rot.0 * Vec3::NEG_Z;
+            let direction = assisted_direction(
+                aim_assist.as_deref(),
+                entity,
+                pos.0,
+                rot.0 * Vec3::NEG_Z,
+                &assist_targets,
+            );
             commands.spawn((
                 Position(pos.0),
                 LinearVelocity(direction * 20.0),
@@ -260,10 +537,10 @@ pub fn fire_projectile_gun_system(
 pub fn update_simple_projectiles(
     mut commands: Commands,
     mut query: Query<(Entity, &mut Projectile)>,
-    time: Res<Time>,
+    clock: Res<GameClock>,
 ) {
     for (entity, mut proj) in query.iter_mut() {
-        proj.lifetime.tick(time.delta());
+        proj.lifetime.tick(clock.delta());
         if proj.lifetime.is_finished() || proj.has_hit {
             commands.entity(entity).despawn();
         }
@@ -287,7 +564,10 @@ pub fn process_hit_events(mut commands: Commands, hit_events: Query<(Entity, &Hi
 
 #[cfg(test)]
 mod tests {
-    use super::{Gun, HitEvent, fire_gun_system, shoot_direction};
+    use super::{
+        Gun, HitEvent, MAX_RECOIL, RECOIL_PER_SHOT, ShotFired, WeaponCharge, WeaponHeat,
+        fire_gun_system, recoiled_direction, shoot_direction,
+    };
     use avian3d::prelude::{Collider, Position, RigidBody, Rotation};
     use bevy::prelude::{App, MinimalPlugins, Quat, Timer, TimerMode, Vec3};
     use leafwing_input_manager::prelude::ActionState;
@@ -351,6 +631,73 @@ mod tests {
         assert_eq!(gun.ammo_in_magazine, gun.magazine_size);
     }
 
+    #[test]
+    fn heat_weapons_overheat_and_vent_instead_of_reloading() {
+        let mut gun = Gun {
+            ammo_in_magazine: 0,
+            ..Gun::default()
+        }
+        .with_heat(WeaponHeat::new(0.4, 0.5, 2.0));
+
+        gun.start_reload();
+        assert!(!gun.is_reloading);
+
+        gun.consume_shot();
+        let heat = gun.heat.as_mut().unwrap();
+        heat.tick(Duration::from_secs_f32(0.2));
+        assert!((heat.level - 0.3).abs() < 1e-5);
+
+        gun.consume_shot();
+        gun.consume_shot();
+        assert!(gun.is_venting());
+        assert_eq!(gun.ammo_in_magazine, 0);
+
+        let heat = gun.heat.as_mut().unwrap();
+        heat.tick(Duration::from_secs_f32(1.0));
+        assert!(heat.venting);
+        assert!((heat.level - 0.5).abs() < 1e-5);
+        heat.tick(Duration::from_secs_f32(1.0));
+        assert!(!heat.venting);
+        assert_eq!(heat.level, 0.0);
+    }
+
+    #[test]
+    fn charge_builds_while_held_and_is_spent_on_release() {
+        let mut charge = WeaponCharge::new(1.0, 0.25);
+        assert_eq!(charge.release(), None);
+
+        charge.hold(Duration::from_secs_f32(0.5));
+        assert!(charge.is_charging());
+        assert!((charge.power() - 0.625).abs() < 1e-5);
+
+        charge.hold(Duration::from_secs_f32(2.0));
+        assert_eq!(charge.fraction(), 1.0);
+        assert_eq!(charge.release(), Some(1.0));
+        assert!(!charge.is_charging());
+        assert_eq!(charge.release(), None);
+    }
+
+    #[test]
+    fn shots_kick_the_aim_up_and_it_recovers() {
+        let mut gun = Gun::default();
+        gun.kick(1.0);
+        gun.kick(0.5);
+        assert!((gun.recoil - RECOIL_PER_SHOT * 1.5).abs() < 1e-6);
+
+        for _ in 0..100 {
+            gun.kick(1.0);
+        }
+        assert_eq!(gun.recoil, MAX_RECOIL);
+
+        let rotation = Rotation::from(Quat::from_rotation_y(0.6));
+        let kicked = recoiled_direction(&rotation, gun.recoil);
+        assert!(kicked.y > 0.0 && (kicked.length() - 1.0).abs() < 1e-5);
+        assert!((kicked.with_y(0.0).normalize() - shoot_direction(&rotation)).length() < 1e-5);
+
+        gun.recover_recoil(Duration::from_secs(5));
+        assert_eq!(gun.recoil, 0.0);
+    }
+
     #[test]
     fn gun_raycast_hits_static_box_and_emits_hit_event() {
         let mut app = App::new();
@@ -365,6 +712,7 @@ mod tests {
         app.insert_resource(avian3d::spatial_query::SpatialQueryDiagnostics::default());
         app.add_plugins(avian3d::prelude::PhysicsPlugins::default());
         app.add_plugins(HealthPlugin);
+        app.add_message::<ShotFired>();
         app.add_systems(bevy::prelude::Update, fire_gun_system);
 
         let owner = app.world_mut().spawn_empty().id();
@@ -422,6 +770,19 @@ mod tests {
             obstacle,
             hit_events
         );
+        let shots: Vec<ShotFired> = app
+            .world()
+            .resource::<bevy::ecs::message::Messages<ShotFired>>()
+            .iter_current_update_messages()
+            .cloned()
+            .collect();
+        assert!(
+            shots
+                .iter()
+                .any(|shot| shot.shooter == shooter && shot.hit == Some(obstacle)),
+            "The hit should also be reported for effects, got {:?}",
+            shots
+        );
 
         let shooter_gun = app
             .world()
@@ -0,0 +1,160 @@
+use bevy::prelude::{
+    Added, App, Color, Commands, Component, Entity, Plugin, Query, Res, Resource, Time, Update,
+    Vec3, Without,
+};
+use serde::{Deserialize, Serialize};
+
+/// Size and color of dropped weapons.
+pub const LOOT_SIZE: Vec3 = Vec3::new(0.8, 0.15, 0.25);
+pub const LOOT_COLOR: Color = Color::srgb(0.85, 0.7, 0.2);
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum WorldItemKind {
+    Corpse,
+    Loot,
+}
+
+/// Replicated marker for things left behind in the world (corpses, dropped weapons). The
+/// server removes them once they expire, or oldest first when there are too many.
+#[derive(Component, Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct WorldItem {
+    pub kind: WorldItemKind,
+    pub color: Color,
+}
+
+/// Local time (`Time::elapsed_secs`) at which a world item appeared. Never replicated: the
+/// server and every client stamp items with their own clock.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct WorldItemSpawnedAt(pub f32);
+
+/// How long corpses and dropped items stay around, and how many may exist at once.
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WorldItemSettings {
+    pub corpse_lifetime_secs: f32,
+    pub loot_lifetime_secs: f32,
+    /// Cap on corpses and dropped items together; the oldest are evicted first.
+    pub max_world_items: usize,
+    /// Items fade out on clients over the last seconds of their lifetime.
+    pub fade_out_secs: f32,
+}
+
+impl Default for WorldItemSettings {
+    fn default() -> Self {
+        Self {
+            corpse_lifetime_secs: 45.0,
+            loot_lifetime_secs: 90.0,
+            max_world_items: 64,
+            fade_out_secs: 3.0,
+        }
+    }
+}
+
+impl WorldItemSettings {
+    pub fn lifetime(&self, kind: WorldItemKind) -> f32 {
+        match kind {
+            WorldItemKind::Corpse => self.corpse_lifetime_secs,
+            WorldItemKind::Loot => self.loot_lifetime_secs,
+        }
+    }
+
+    /// Opacity of an item of `kind` that is `age` seconds old: 1 until the fade starts,
+    /// then down to 0 at the end of its lifetime.
+    pub fn fade_alpha(&self, kind: WorldItemKind, age: f32) -> f32 {
+        let remaining = self.lifetime(kind) - age;
+        if self.fade_out_secs <= 0.0 {
+            return if remaining > 0.0 { 1.0 } else { 0.0 };
+        }
+        (remaining / self.fade_out_secs).clamp(0.0, 1.0)
+    }
+}
+
+/// Items to despawn at `now`: every expired item, then the oldest of the rest until at most
+/// `max_world_items` remain.
+pub fn world_items_to_evict(
+    items: impl Iterator<Item = (Entity, WorldItemKind, f32)>,
+    now: f32,
+    settings: &WorldItemSettings,
+) -> Vec<Entity> {
+    let mut evicted = Vec::new();
+    let mut alive = Vec::new();
+    for (entity, kind, spawned_at) in items {
+        if now - spawned_at >= settings.lifetime(kind) {
+            evicted.push(entity);
+        } else {
+            alive.push((entity, spawned_at));
+        }
+    }
+
+    if alive.len() > settings.max_world_items {
+        alive.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+        let excess = alive.len() - settings.max_world_items;
+        evicted.extend(alive.into_iter().take(excess).map(|(entity, _)| entity));
+    }
+    evicted
+}
+
+pub struct WorldItemsPlugin;
+
+impl Plugin for WorldItemsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WorldItemSettings>();
+        app.add_systems(Update, stamp_world_items);
+    }
+}
+
+fn stamp_world_items(
+    mut commands: Commands,
+    time: Res<Time>,
+    items: Query<Entity, (Added<WorldItem>, Without<WorldItemSpawnedAt>)>,
+) {
+    for entity in items.iter() {
+        commands
+            .entity(entity)
+            .insert(WorldItemSpawnedAt(time.elapsed_secs()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{WorldItemKind, WorldItemSettings, world_items_to_evict};
+    use bevy::prelude::World;
+
+    #[test]
+    fn expired_and_oldest_items_are_evicted() {
+        let settings = WorldItemSettings {
+            corpse_lifetime_secs: 10.0,
+            loot_lifetime_secs: 20.0,
+            max_world_items: 2,
+            fade_out_secs: 1.0,
+        };
+        let mut world = World::new();
+        let [old_corpse, old_loot, newer, newest] =
+            std::array::from_fn(|_| world.spawn_empty().id());
+        let items = [
+            (newest, WorldItemKind::Corpse, 11.0),
+            (old_corpse, WorldItemKind::Corpse, 0.0),
+            (old_loot, WorldItemKind::Loot, 1.0),
+            (newer, WorldItemKind::Loot, 5.0),
+        ];
+
+        let evicted = world_items_to_evict(items.into_iter(), 12.0, &settings);
+        assert_eq!(evicted, vec![old_corpse, old_loot]);
+
+        let evicted = world_items_to_evict(items.into_iter(), 30.0, &settings);
+        assert_eq!(evicted, vec![newest, old_corpse, old_loot, newer]);
+    }
+
+    #[test]
+    fn items_fade_out_at_the_end_of_their_lifetime() {
+        let settings = WorldItemSettings::default();
+        let lifetime = settings.corpse_lifetime_secs;
+
+        assert_eq!(settings.fade_alpha(WorldItemKind::Corpse, 0.0), 1.0);
+        let halfway = settings.fade_alpha(
+            WorldItemKind::Corpse,
+            lifetime - settings.fade_out_secs * 0.5,
+        );
+        assert!((halfway - 0.5).abs() < 1e-4);
+        assert_eq!(settings.fade_alpha(WorldItemKind::Corpse, lifetime), 0.0);
+    }
+}
@@ -0,0 +1,238 @@
+//! Checks of how an app is put together, run by debug builds once an app is built.
+//!
+//! Plugins, resources and states added through [`ComposeApp`] instead of the plain `App`
+//! methods remember where they were added. [`check_composition`] then reports anything
+//! added twice with every source, since a second resource or state silently replaces the
+//! first one and the app runs with whichever came last. Plugins are only ever built once.
+
+use bevy::app::{App, Plugin};
+use bevy::prelude::{AppExtStates, Resource, State};
+use bevy::state::state::FreelyMutableState;
+use std::any::type_name;
+use std::fmt;
+use std::panic::Location;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompositionKind {
+    Plugin,
+    Resource,
+    State,
+}
+
+impl fmt::Display for CompositionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CompositionKind::Plugin => "plugin",
+            CompositionKind::Resource => "resource",
+            CompositionKind::State => "state",
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+struct CompositionEntry {
+    kind: CompositionKind,
+    type_name: &'static str,
+    source: &'static Location<'static>,
+    /// Already in the app when this source added it.
+    already_present: bool,
+}
+
+/// Everything added through [`ComposeApp`], in order.
+#[derive(Resource, Clone, Debug, Default)]
+struct AppComposition {
+    entries: Vec<CompositionEntry>,
+}
+
+/// Something added to an app more than once.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompositionConflict {
+    pub kind: CompositionKind,
+    pub type_name: &'static str,
+    /// Every recorded place that added it, in order.
+    pub sources: Vec<&'static Location<'static>>,
+    /// Whether something unrecorded, like a plugin's `build`, added it before the first
+    /// recorded source.
+    pub added_elsewhere: bool,
+}
+
+impl fmt::Display for CompositionConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} added", self.kind, self.type_name)?;
+        if self.added_elsewhere {
+            f.write_str(" before the app was composed, then again")?;
+        }
+        f.write_str(" at")?;
+        for (index, source) in self.sources.iter().enumerate() {
+            let separator = if index == 0 { " " } else { ", " };
+            write!(f, "{}{}", separator, source)?;
+        }
+        Ok(())
+    }
+}
+
+/// `App` methods that record where things were added, for [`check_composition`].
+pub trait ComposeApp {
+    /// Adds `plugin` unless one of the same type is already there.
+    fn compose_plugin<P: Plugin>(&mut self, plugin: P) -> &mut Self;
+    fn compose_resource<R: Resource>(&mut self, resource: R) -> &mut Self;
+    /// Sets up the state `S`, starting at `state`.
+    fn compose_state<S: FreelyMutableState>(&mut self, state: S) -> &mut Self;
+}
+
+impl ComposeApp for App {
+    #[track_caller]
+    fn compose_plugin<P: Plugin>(&mut self, plugin: P) -> &mut Self {
+        let already_present = self.is_plugin_added::<P>();
+        record(
+            self,
+            CompositionKind::Plugin,
+            type_name::<P>(),
+            already_present,
+        );
+        if already_present {
+            return self;
+        }
+        self.add_plugins(plugin)
+    }
+
+    #[track_caller]
+    fn compose_resource<R: Resource>(&mut self, resource: R) -> &mut Self {
+        let already_present = self.world().contains_resource::<R>();
+        record(
+            self,
+            CompositionKind::Resource,
+            type_name::<R>(),
+            already_present,
+        );
+        self.insert_resource(resource)
+    }
+
+    #[track_caller]
+    fn compose_state<S: FreelyMutableState>(&mut self, state: S) -> &mut Self {
+        let already_present = self.world().contains_resource::<State<S>>();
+        record(
+            self,
+            CompositionKind::State,
+            type_name::<S>(),
+            already_present,
+        );
+        self.insert_state(state)
+    }
+}
+
+#[track_caller]
+fn record(app: &mut App, kind: CompositionKind, type_name: &'static str, already_present: bool) {
+    app.world_mut()
+        .get_resource_or_init::<AppComposition>()
+        .entries
+        .push(CompositionEntry {
+            kind,
+            type_name,
+            source: Location::caller(),
+            already_present,
+        });
+}
+
+/// Everything added to `app` more than once through [`ComposeApp`].
+pub fn composition_conflicts(app: &App) -> Vec<CompositionConflict> {
+    let Some(composition) = app.world().get_resource::<AppComposition>() else {
+        return Vec::new();
+    };
+
+    let mut conflicts: Vec<CompositionConflict> = Vec::new();
+    for entry in &composition.entries {
+        match conflicts
+            .iter_mut()
+            .find(|seen| seen.kind == entry.kind && seen.type_name == entry.type_name)
+        {
+            Some(seen) => seen.sources.push(entry.source),
+            None => conflicts.push(CompositionConflict {
+                kind: entry.kind,
+                type_name: entry.type_name,
+                sources: vec![entry.source],
+                added_elsewhere: entry.already_present,
+            }),
+        }
+    }
+    conflicts.retain(|conflict| conflict.sources.len() > 1 || conflict.added_elsewhere);
+    conflicts
+}
+
+/// Panics in debug builds when `app` was composed with duplicates, listing all of them.
+/// Release builds skip the check.
+pub fn check_composition(app: &App) {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+
+    let conflicts = composition_conflicts(app);
+    if conflicts.is_empty() {
+        return;
+    }
+    let report = conflicts
+        .iter()
+        .map(|conflict| format!("  - {}", conflict))
+        .collect::<Vec<_>>()
+        .join("\n");
+    panic!("the app was composed with duplicates:\n{}", report);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ComposeApp, CompositionKind, composition_conflicts};
+    use bevy::prelude::{App, AppExtStates, Plugin, Resource, States};
+    use bevy::state::app::StatesPlugin;
+
+    #[derive(Resource, Default)]
+    struct Builds(u32);
+
+    #[derive(Resource)]
+    struct Mode(u32);
+
+    #[derive(States, Clone, Debug, Default, PartialEq, Eq, Hash)]
+    enum Phase {
+        #[default]
+        Menu,
+        Playing,
+    }
+
+    struct CountingPlugin;
+
+    impl Plugin for CountingPlugin {
+        fn build(&self, app: &mut App) {
+            app.world_mut().get_resource_or_init::<Builds>().0 += 1;
+        }
+    }
+
+    #[test]
+    fn duplicates_are_reported_with_every_source() {
+        let mut app = App::new();
+        app.add_plugins(StatesPlugin);
+        app.compose_resource(Mode(1));
+        app.compose_plugin(CountingPlugin);
+        app.init_state::<Phase>();
+        assert!(composition_conflicts(&app).is_empty());
+
+        app.compose_resource(Mode(2));
+        app.compose_plugin(CountingPlugin);
+        app.compose_state(Phase::Playing);
+
+        assert_eq!(app.world().resource::<Builds>().0, 1);
+        assert_eq!(app.world().resource::<Mode>().0, 2);
+        let conflicts = composition_conflicts(&app);
+        let kinds: Vec<_> = conflicts.iter().map(|conflict| conflict.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                CompositionKind::Resource,
+                CompositionKind::Plugin,
+                CompositionKind::State
+            ]
+        );
+        assert_eq!(conflicts[0].sources.len(), 2);
+        assert!(conflicts[0].sources[0].file().ends_with("composition.rs"));
+        assert!(conflicts[2].added_elsewhere);
+        assert!(conflicts[0].to_string().contains("Mode added at"));
+    }
+}
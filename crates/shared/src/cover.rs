@@ -0,0 +1,147 @@
+//! Cover points: spots next to level geometry tall enough to hide a bot, each protecting
+//! from the directions its obstacle faces. Candidates are spaced along the sides of static
+//! colliders (`face_candidates`); the server keeps those with ground under them and their
+//! obstacle right behind them, once per level.
+
+use bevy::prelude::{Resource, Vec3};
+
+/// Obstacles lower than this hide nobody.
+pub const COVER_MIN_HEIGHT: f32 = 1.2;
+/// Distance between a cover point and the side of its obstacle.
+pub const COVER_OFFSET: f32 = 0.7;
+/// Distance between two cover points along a side.
+pub const COVER_SPACING: f32 = 2.0;
+/// Threats up to this angle off the direction a point faces are still covered.
+pub const COVER_HALF_ARC: f32 = std::f32::consts::FRAC_PI_3;
+/// Cover this close to the threat is no use.
+pub const MIN_THREAT_DISTANCE: f32 = 4.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CoverPoint {
+    /// On the ground, next to the obstacle.
+    pub position: Vec3,
+    /// Horizontal direction from the point into its obstacle. Threats that way are covered.
+    pub facing: Vec3,
+    /// Height of the obstacle above the point.
+    pub height: f32,
+}
+
+impl CoverPoint {
+    pub fn protects_from(&self, threat: Vec3) -> bool {
+        (threat - self.position)
+            .with_y(0.0)
+            .try_normalize()
+            .is_some_and(|direction| direction.dot(self.facing) >= COVER_HALF_ARC.cos())
+    }
+}
+
+/// Cover points of the current level, baked by the server.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct CoverPoints {
+    pub points: Vec<CoverPoint>,
+}
+
+impl CoverPoints {
+    /// The cover against `threat` nearest to `from`, at most `max_distance` away, that
+    /// `usable` accepts. `usable` is only asked about points that would do otherwise,
+    /// nearest first, so it can afford a raycast.
+    pub fn nearest_against(
+        &self,
+        from: Vec3,
+        threat: Vec3,
+        max_distance: f32,
+        usable: impl FnMut(&CoverPoint) -> bool,
+    ) -> Option<CoverPoint> {
+        let mut candidates: Vec<&CoverPoint> = self
+            .points
+            .iter()
+            .filter(|point| {
+                point.position.distance(from) <= max_distance
+                    && point.position.distance(threat) >= MIN_THREAT_DISTANCE
+                    && point.protects_from(threat)
+            })
+            .collect();
+        candidates.sort_by(|a, b| {
+            a.position
+                .distance_squared(from)
+                .total_cmp(&b.position.distance_squared(from))
+        });
+        candidates.into_iter().copied().find(usable)
+    }
+}
+
+/// Candidate cover points around a box obstacle spanning `min` to `max`: spaced along
+/// each of its four sides, `COVER_OFFSET` out, at the height of its foot, with the
+/// direction each faces.
+pub fn face_candidates(min: Vec3, max: Vec3) -> Vec<(Vec3, Vec3)> {
+    let spread = |from: f32, to: f32| {
+        let count = ((to - from) / COVER_SPACING).floor().max(1.0) as usize;
+        let step = (to - from) / count as f32;
+        (0..count).map(move |i| from + step * (i as f32 + 0.5))
+    };
+    let mut candidates = Vec::new();
+    for z in spread(min.z, max.z) {
+        candidates.push((Vec3::new(max.x + COVER_OFFSET, min.y, z), Vec3::NEG_X));
+        candidates.push((Vec3::new(min.x - COVER_OFFSET, min.y, z), Vec3::X));
+    }
+    for x in spread(min.x, max.x) {
+        candidates.push((Vec3::new(x, min.y, max.z + COVER_OFFSET), Vec3::NEG_Z));
+        candidates.push((Vec3::new(x, min.y, min.z - COVER_OFFSET), Vec3::Z));
+    }
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{COVER_OFFSET, CoverPoint, CoverPoints, MIN_THREAT_DISTANCE, face_candidates};
+    use bevy::prelude::Vec3;
+
+    fn point(x: f32, facing: Vec3) -> CoverPoint {
+        CoverPoint {
+            position: Vec3::new(x, 0.0, 0.0),
+            facing,
+            height: 1.5,
+        }
+    }
+
+    #[test]
+    fn candidates_line_every_side_facing_the_obstacle() {
+        let min = Vec3::new(0.0, 0.0, 0.0);
+        let max = Vec3::new(6.0, 2.0, 1.0);
+        let candidates = face_candidates(min, max);
+        // Three along each long side, one at each end.
+        assert_eq!(candidates.len(), 8);
+        for (position, facing) in candidates {
+            assert_eq!(position.y, min.y);
+            let center = (min + max) * 0.5;
+            assert!((center - position).with_y(0.0).dot(facing) > 0.0);
+            let outside_x = (position.x - center.x).abs() - (max.x - min.x) * 0.5;
+            let outside_z = (position.z - center.z).abs() - (max.z - min.z) * 0.5;
+            assert!((outside_x.max(outside_z) - COVER_OFFSET).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn bots_pick_the_nearest_cover_facing_the_threat() {
+        let threat = Vec3::new(20.0, 0.0, 0.0);
+        let cover = CoverPoints {
+            points: vec![
+                point(1.0, Vec3::NEG_X),
+                point(3.0, Vec3::X),
+                point(6.0, Vec3::X),
+                point(threat.x - MIN_THREAT_DISTANCE * 0.5, Vec3::X),
+            ],
+        };
+        assert!(cover.points[1].protects_from(threat));
+        assert!(!cover.points[0].protects_from(threat));
+
+        let nearest = cover.nearest_against(Vec3::ZERO, threat, 30.0, |_| true);
+        assert_eq!(nearest, Some(cover.points[1]));
+        let usable = cover.nearest_against(Vec3::ZERO, threat, 30.0, |p| p.position.x > 4.0);
+        assert_eq!(usable, Some(cover.points[2]));
+        assert_eq!(
+            cover.nearest_against(Vec3::ZERO, threat, 2.0, |_| true),
+            None
+        );
+    }
+}
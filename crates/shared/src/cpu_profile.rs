@@ -0,0 +1,321 @@
+//! CPU time per module, for attributing a tick's cost when optimizing. Each section is
+//! timed from just before its first system set starts to just after its last one ends, and
+//! published every frame as a `cpu/<section>` Bevy diagnostic, so the usual diagnostics
+//! store keeps its history.
+//!
+//! Sections are wall time: on the multithreaded executor, systems of other modules running
+//! alongside a section count towards it too. They show where a frame goes, not how much
+//! CPU each system burns.
+
+use std::fmt::Write as _;
+use std::time::Instant;
+
+use avian3d::prelude::PhysicsSystems;
+use bevy::diagnostic::{
+    Diagnostic, DiagnosticPath, Diagnostics, DiagnosticsStore, RegisterDiagnostic,
+};
+use bevy::ecs::schedule::{ScheduleLabel, SystemSet};
+use bevy::prelude::{
+    App, First, FixedPostUpdate, IntoScheduleConfigs, Last, Plugin, PostUpdate, PreUpdate, ResMut,
+    Resource,
+};
+use lightyear::prelude::{LinkSystems, MessageSystems, TransportSystems};
+
+/// Frames of history kept per section, ~10 s at 60 FPS.
+pub const CPU_HISTORY_LEN: usize = 600;
+const PATH_PREFIX: &str = "cpu/";
+/// The whole frame, which the other sections are a share of.
+pub const FRAME_SECTION: &str = "frame";
+
+/// Times the whole frame and the network and physics phases every app has. Apps add their
+/// own modules with [`CpuProfileAppExt`].
+pub struct CpuProfilePlugin;
+
+impl Plugin for CpuProfilePlugin {
+    fn build(&self, app: &mut App) {
+        add_section(app, FRAME_SECTION);
+        app.add_systems(First, |mut sections: ResMut<CpuSections>| {
+            sections.begin(FRAME_SECTION)
+        });
+        app.add_systems(
+            Last,
+            (
+                |mut sections: ResMut<CpuSections>| sections.end(FRAME_SECTION),
+                publish_cpu_sections,
+            )
+                .chain(),
+        );
+
+        app.profile_span(
+            "network_receive",
+            PreUpdate,
+            LinkSystems::Receive,
+            MessageSystems::Receive,
+        );
+        app.profile_set("physics", FixedPostUpdate, PhysicsSystems::StepSimulation);
+        // Replication is packed early in PostUpdate, ahead of the transport and link sends.
+        app.profile_span(
+            "network_send",
+            PostUpdate,
+            TransportSystems::Send,
+            LinkSystems::Send,
+        );
+    }
+}
+
+pub trait CpuProfileAppExt {
+    /// Time from just before `first` starts in `schedule` to just after `last` ends, as
+    /// section `label`. A section timed in several schedules, or in a schedule that runs
+    /// several times per frame, adds them up.
+    fn profile_span(
+        &mut self,
+        label: &'static str,
+        schedule: impl ScheduleLabel + Clone,
+        first: impl SystemSet + Clone,
+        last: impl SystemSet + Clone,
+    ) -> &mut Self;
+
+    /// Time `set` in `schedule` as section `label`.
+    fn profile_set(
+        &mut self,
+        label: &'static str,
+        schedule: impl ScheduleLabel + Clone,
+        set: impl SystemSet + Clone,
+    ) -> &mut Self {
+        self.profile_span(label, schedule, set.clone(), set)
+    }
+}
+
+impl CpuProfileAppExt for App {
+    fn profile_span(
+        &mut self,
+        label: &'static str,
+        schedule: impl ScheduleLabel + Clone,
+        first: impl SystemSet + Clone,
+        last: impl SystemSet + Clone,
+    ) -> &mut Self {
+        add_section(self, label);
+        self.add_systems(
+            schedule.clone(),
+            (move |mut sections: ResMut<CpuSections>| sections.begin(label)).before(first),
+        );
+        self.add_systems(
+            schedule,
+            (move |mut sections: ResMut<CpuSections>| sections.end(label)).after(last),
+        );
+        self
+    }
+}
+
+/// Registers section `label` and its diagnostic, once.
+fn add_section(app: &mut App, label: &'static str) {
+    app.init_resource::<CpuSections>();
+    let mut sections = app.world_mut().resource_mut::<CpuSections>();
+    if sections.get(label).is_some() {
+        return;
+    }
+    sections.sections.push(CpuSection::new(label));
+    app.register_diagnostic(
+        Diagnostic::new(section_path(label))
+            .with_suffix(" ms")
+            .with_max_history_length(CPU_HISTORY_LEN),
+    );
+}
+
+fn section_path(label: &str) -> DiagnosticPath {
+    DiagnosticPath::new(format!("{}{}", PATH_PREFIX, label))
+}
+
+struct CpuSection {
+    label: &'static str,
+    path: DiagnosticPath,
+    started: Option<Instant>,
+    frame_secs: f64,
+}
+
+impl CpuSection {
+    fn new(label: &'static str) -> Self {
+        Self {
+            label,
+            path: section_path(label),
+            started: None,
+            frame_secs: 0.0,
+        }
+    }
+}
+
+/// Running timings of the sections for the current frame.
+#[derive(Resource, Default)]
+pub struct CpuSections {
+    sections: Vec<CpuSection>,
+}
+
+impl CpuSections {
+    fn get(&mut self, label: &str) -> Option<&mut CpuSection> {
+        self.sections
+            .iter_mut()
+            .find(|section| section.label == label)
+    }
+
+    fn begin(&mut self, label: &str) {
+        if let Some(section) = self.get(label) {
+            section.started = Some(Instant::now());
+        }
+    }
+
+    fn end(&mut self, label: &str) {
+        if let Some(section) = self.get(label)
+            && let Some(started) = section.started.take()
+        {
+            section.frame_secs += started.elapsed().as_secs_f64();
+        }
+    }
+}
+
+fn publish_cpu_sections(mut sections: ResMut<CpuSections>, mut diagnostics: Diagnostics) {
+    for section in sections.sections.iter_mut() {
+        let millis = section.frame_secs * 1000.0;
+        diagnostics.add_measurement(&section.path, || millis);
+        section.frame_secs = 0.0;
+        section.started = None;
+    }
+}
+
+/// Recent timings of one section, in milliseconds per frame.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CpuSectionStats {
+    pub label: String,
+    pub average_ms: f64,
+    pub max_ms: f64,
+    /// Share of the average frame.
+    pub share: f64,
+    /// Oldest first.
+    pub history_ms: Vec<f64>,
+}
+
+/// Every section in `store`, the frame first and then the costliest first.
+pub fn cpu_breakdown(store: &DiagnosticsStore) -> Vec<CpuSectionStats> {
+    let mut stats: Vec<CpuSectionStats> = store
+        .iter()
+        .filter_map(|diagnostic| {
+            let label = diagnostic.path().as_str().strip_prefix(PATH_PREFIX)?;
+            let history_ms: Vec<f64> = diagnostic.values().copied().collect();
+            Some(CpuSectionStats {
+                label: label.to_string(),
+                average_ms: diagnostic.average().unwrap_or_default(),
+                max_ms: history_ms.iter().copied().fold(0.0, f64::max),
+                share: 0.0,
+                history_ms,
+            })
+        })
+        .collect();
+
+    let frame_ms = stats
+        .iter()
+        .find(|section| section.label == FRAME_SECTION)
+        .map_or(0.0, |frame| frame.average_ms);
+    for section in stats.iter_mut() {
+        section.share = if frame_ms > 0.0 {
+            section.average_ms / frame_ms
+        } else {
+            0.0
+        };
+    }
+    stats.sort_by(|a, b| {
+        (b.label == FRAME_SECTION)
+            .cmp(&(a.label == FRAME_SECTION))
+            .then(b.average_ms.total_cmp(&a.average_ms))
+    });
+    stats
+}
+
+/// One line per section: average and worst frame, and share of the frame.
+pub fn format_cpu_breakdown(stats: &[CpuSectionStats]) -> String {
+    if stats.is_empty() {
+        return "cpu: no sections recorded".to_string();
+    }
+    let mut out = String::new();
+    for section in stats {
+        let _ = writeln!(
+            out,
+            "{:<16} {:>6.2} ms avg {:>6.2} ms max {:>5.1} %",
+            section.label,
+            section.average_ms,
+            section.max_ms,
+            section.share * 100.0
+        );
+    }
+    out.truncate(out.trim_end().len());
+    out
+}
+
+/// The recorded history as CSV, one row per frame, oldest first, and one column per
+/// section in milliseconds. Sections with a shorter history leave their first cells empty.
+pub fn cpu_profile_csv(stats: &[CpuSectionStats]) -> String {
+    let mut out = String::from("frame");
+    for section in stats {
+        let _ = write!(out, ",{}_ms", section.label);
+    }
+    out.push('\n');
+
+    let rows = stats
+        .iter()
+        .map(|section| section.history_ms.len())
+        .max()
+        .unwrap_or(0);
+    for row in 0..rows {
+        let _ = write!(out, "{}", row);
+        for section in stats {
+            let missing = rows - section.history_ms.len();
+            match row.checked_sub(missing) {
+                Some(index) => {
+                    let _ = write!(out, ",{:.4}", section.history_ms[index]);
+                }
+                None => out.push(','),
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CpuSectionStats, cpu_profile_csv, format_cpu_breakdown};
+
+    fn section(label: &str, history_ms: Vec<f64>, share: f64) -> CpuSectionStats {
+        let average_ms = history_ms.iter().sum::<f64>() / history_ms.len() as f64;
+        CpuSectionStats {
+            label: label.to_string(),
+            average_ms,
+            max_ms: history_ms.iter().copied().fold(0.0, f64::max),
+            share,
+            history_ms,
+        }
+    }
+
+    #[test]
+    fn breakdown_lists_sections_and_csv_aligns_histories() {
+        let stats = vec![
+            section("frame", vec![4.0, 6.0, 5.0], 1.0),
+            section("physics", vec![1.0, 2.0], 0.3),
+        ];
+
+        let text = format_cpu_breakdown(&stats);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("frame"));
+        assert!(lines[0].contains("5.00 ms avg"));
+        assert!(lines[1].contains("2.00 ms max"));
+        assert!(lines[1].ends_with("30.0 %"));
+
+        assert_eq!(
+            cpu_profile_csv(&stats),
+            "frame,frame_ms,physics_ms\n\
+             0,4.0000,\n\
+             1,6.0000,1.0000\n\
+             2,5.0000,2.0000\n"
+        );
+        assert_eq!(format_cpu_breakdown(&[]), "cpu: no sections recorded");
+    }
+}
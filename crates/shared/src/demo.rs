@@ -0,0 +1,190 @@
+//! Demonstration files: a player's observations and actions, tick by tick, for
+//! pretraining policies on human play.
+//!
+//! A file starts with [`DEMO_MAGIC`], the format version and the observation length, as
+//! little-endian `u32`s. Then come frames until the end of the file, each the tick index,
+//! a `u32`, followed by the observation (see [`crate::observation`]) and the action taken
+//! on that tick as little-endian `f32`s. Tick indices count every fixed tick of the
+//! recording client, so a jump in them marks time the player was not recorded, such as
+//! between a death and the respawn.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::observation::{LAST_ACTION_SIZE, OBSERVATION_LEN};
+
+pub const DEMO_MAGIC: [u8; 4] = *b"DEMO";
+pub const DEMO_FORMAT_VERSION: u32 = 1;
+
+/// One recorded tick.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DemoFrame {
+    pub tick: u32,
+    pub observation: Vec<f32>,
+    /// The action taken after seeing `observation`.
+    pub action: [f32; LAST_ACTION_SIZE],
+}
+
+/// Appends frames to a demonstration file.
+pub struct DemoWriter {
+    path: PathBuf,
+    file: BufWriter<File>,
+}
+
+impl DemoWriter {
+    /// Creates the file, and its directory if needed, and writes the header.
+    pub fn create(path: &Path) -> io::Result<Self> {
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = BufWriter::new(File::create(path)?);
+        write_header(&mut file)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn write_frame(&mut self, frame: &DemoFrame) -> io::Result<()> {
+        write_frame(&mut self.file, frame)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+fn write_header(writer: &mut impl Write) -> io::Result<()> {
+    writer.write_all(&DEMO_MAGIC)?;
+    writer.write_all(&DEMO_FORMAT_VERSION.to_le_bytes())?;
+    writer.write_all(&(OBSERVATION_LEN as u32).to_le_bytes())
+}
+
+fn write_frame(writer: &mut impl Write, frame: &DemoFrame) -> io::Result<()> {
+    if frame.observation.len() != OBSERVATION_LEN {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "observation has {} values, expected {}",
+                frame.observation.len(),
+                OBSERVATION_LEN
+            ),
+        ));
+    }
+    writer.write_all(&frame.tick.to_le_bytes())?;
+    for value in frame.observation.iter().chain(&frame.action) {
+        writer.write_all(&value.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// Reads every frame of a demonstration file.
+pub fn read_demo(path: &Path) -> io::Result<Vec<DemoFrame>> {
+    read_frames(&mut BufReader::new(File::open(path)?))
+}
+
+fn read_frames(reader: &mut impl Read) -> io::Result<Vec<DemoFrame>> {
+    let invalid = |message: String| io::Error::new(ErrorKind::InvalidData, message);
+
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if magic != DEMO_MAGIC {
+        return Err(invalid("not a demonstration file".to_string()));
+    }
+    let version = read_u32(reader)?;
+    if version != DEMO_FORMAT_VERSION {
+        return Err(invalid(format!(
+            "demonstration format {}, expected {}",
+            version, DEMO_FORMAT_VERSION
+        )));
+    }
+    let observation_len = read_u32(reader)? as usize;
+    if observation_len != OBSERVATION_LEN {
+        return Err(invalid(format!(
+            "observations have {} values, expected {}",
+            observation_len, OBSERVATION_LEN
+        )));
+    }
+
+    let mut frames = Vec::new();
+    let mut tick = [0; 4];
+    loop {
+        // The file may end between frames, not inside one.
+        match reader.read_exact(&mut tick) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e),
+        }
+        let mut values = vec![0.0; OBSERVATION_LEN + LAST_ACTION_SIZE];
+        for value in &mut values {
+            *value = read_f32(reader)
+                .map_err(|_| invalid(format!("frame {} is cut short", frames.len())))?;
+        }
+        let mut action = [0.0; LAST_ACTION_SIZE];
+        action.copy_from_slice(&values[OBSERVATION_LEN..]);
+        values.truncate(OBSERVATION_LEN);
+        frames.push(DemoFrame {
+            tick: u32::from_le_bytes(tick),
+            observation: values,
+            action,
+        });
+    }
+    Ok(frames)
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_f32(reader: &mut impl Read) -> io::Result<f32> {
+    read_u32(reader).map(f32::from_bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DemoFrame, read_frames, write_frame, write_header};
+    use crate::observation::{OBSERVATION_LEN, RAY_COUNT, observation_buffer};
+    use bevy::prelude::Vec3;
+
+    #[test]
+    fn frames_read_back_and_partial_frames_are_rejected() {
+        let frames: Vec<_> = (0..3)
+            .map(|tick| DemoFrame {
+                tick,
+                observation: observation_buffer(
+                    Vec3::new(tick as f32, 0.0, 1.0),
+                    Vec3::X,
+                    80.0,
+                    100.0,
+                    &[20.0; RAY_COUNT],
+                    &[0.0, 1.0, 0.0, 0.0, 0.0, 1.0],
+                ),
+                action: [1.0, 0.0, 0.5, 0.0, 1.0, 0.0],
+            })
+            .collect();
+        assert_eq!(frames[0].observation.len(), OBSERVATION_LEN);
+
+        let mut bytes = Vec::new();
+        write_header(&mut bytes).unwrap();
+        for frame in &frames {
+            write_frame(&mut bytes, frame).unwrap();
+        }
+        assert_eq!(read_frames(&mut bytes.as_slice()).unwrap(), frames);
+
+        let short = &bytes[..bytes.len() - 4];
+        assert!(read_frames(&mut &short[..]).is_err());
+        assert!(read_frames(&mut &bytes[4..]).is_err());
+        let mut wrong_size = frames[0].clone();
+        wrong_size.observation.pop();
+        assert!(write_frame(&mut Vec::new(), &wrong_size).is_err());
+    }
+}
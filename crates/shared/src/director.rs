@@ -0,0 +1,322 @@
+//! The AI director: how each player has been doing over the last minute, and how hard the
+//! bots should push back so matches stay close. The server feeds it kills, deaths, damage
+//! and shots and applies its decisions to the bots (`server::director`).
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::bots::BotProfile;
+
+/// Seconds of play each player is judged on.
+pub const PERFORMANCE_WINDOW_SECS: f32 = 60.0;
+/// Bot intensity bounds; 1.0 is the match's configured difficulty.
+pub const MIN_INTENSITY: f32 = 0.6;
+pub const MAX_INTENSITY: f32 = 1.5;
+/// Most the intensity moves in one decision, so bots never jump from harmless to deadly.
+pub const MAX_INTENSITY_STEP: f32 = 0.1;
+/// Events a player needs in the window before their performance counts.
+const MIN_JUDGED_EVENTS: usize = 3;
+/// Accuracy of a player holding their own.
+const EVEN_ACCURACY: f32 = 0.3;
+/// Damage taken per minute by a player holding their own.
+const EVEN_DAMAGE_TAKEN_PER_MIN: f32 = 150.0;
+/// K/D ratio judged as fully dominating (or, inverted, fully outmatched).
+const DOMINANT_KD: f32 = 3.0;
+
+/// Something a player did or suffered.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PerformanceEvent {
+    Kill,
+    Death,
+    DamageTaken(f32),
+    Shot { hit: bool },
+}
+
+/// A player's events over the window.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PerformanceSummary {
+    pub kills: u32,
+    pub deaths: u32,
+    pub damage_taken: f32,
+    pub shots: u32,
+    pub hits: u32,
+    pub events: usize,
+}
+
+impl PerformanceSummary {
+    pub fn kd(&self) -> f32 {
+        self.kills as f32 / self.deaths.max(1) as f32
+    }
+
+    pub fn accuracy(&self) -> Option<f32> {
+        (self.shots > 0).then(|| self.hits as f32 / self.shots as f32)
+    }
+
+    /// -1.0 (outmatched) to 1.0 (dominating), 0.0 for a player holding their own, from
+    /// their K/D, accuracy and the damage they took over `covered_secs` of play.
+    pub fn skill(&self, covered_secs: f32) -> f32 {
+        // One kill and one death added, so a single early kill is not a perfect K/D.
+        let kd = (self.kills as f32 + 1.0) / (self.deaths as f32 + 1.0);
+        let kd_term = (kd.ln() / DOMINANT_KD.ln()).clamp(-1.0, 1.0);
+        let accuracy_term = self.accuracy().map_or(0.0, |accuracy| {
+            ((accuracy - EVEN_ACCURACY) / EVEN_ACCURACY).clamp(-1.0, 1.0)
+        });
+        let damage_per_min = self.damage_taken * 60.0 / covered_secs.max(1.0);
+        let damage_term = ((EVEN_DAMAGE_TAKEN_PER_MIN - damage_per_min)
+            / EVEN_DAMAGE_TAKEN_PER_MIN)
+            .clamp(-1.0, 1.0);
+        0.5 * kd_term + 0.3 * accuracy_term + 0.2 * damage_term
+    }
+}
+
+/// Timestamped events per player, forgotten once they leave the window.
+#[derive(Clone, Debug, Default)]
+pub struct PerformanceTracker {
+    started: f32,
+    players: HashMap<u64, VecDeque<(f32, PerformanceEvent)>>,
+}
+
+impl PerformanceTracker {
+    /// An empty tracker for a match starting at `now`.
+    pub fn new(now: f32) -> Self {
+        Self {
+            started: now,
+            players: HashMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, player: u64, now: f32, event: PerformanceEvent) {
+        self.players
+            .entry(player)
+            .or_default()
+            .push_back((now, event));
+    }
+
+    /// Drop events older than the window, and players left with none.
+    pub fn forget_old(&mut self, now: f32) {
+        let cutoff = now - PERFORMANCE_WINDOW_SECS;
+        self.players.retain(|_, events| {
+            while events.front().is_some_and(|(time, _)| *time < cutoff) {
+                events.pop_front();
+            }
+            !events.is_empty()
+        });
+    }
+
+    /// Seconds of play the window covers: less than a full window early in a match.
+    pub fn covered_secs(&self, now: f32) -> f32 {
+        (now - self.started).clamp(1.0, PERFORMANCE_WINDOW_SECS)
+    }
+
+    /// Every tracked player's summary, by player id.
+    pub fn summaries(&self) -> Vec<(u64, PerformanceSummary)> {
+        let mut summaries: Vec<_> = self
+            .players
+            .iter()
+            .map(|(player, events)| {
+                let mut summary = PerformanceSummary {
+                    events: events.len(),
+                    ..Default::default()
+                };
+                for (_, event) in events {
+                    match *event {
+                        PerformanceEvent::Kill => summary.kills += 1,
+                        PerformanceEvent::Death => summary.deaths += 1,
+                        PerformanceEvent::DamageTaken(amount) => summary.damage_taken += amount,
+                        PerformanceEvent::Shot { hit } => {
+                            summary.shots += 1;
+                            summary.hits += u32::from(hit);
+                        }
+                    }
+                }
+                (*player, summary)
+            })
+            .collect();
+        summaries.sort_by_key(|(player, _)| *player);
+        summaries
+    }
+}
+
+/// What the director settled on at one point of the match.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DirectorDecision {
+    pub time: f32,
+    /// Mean skill of the judged players, -1.0 to 1.0.
+    pub player_skill: f32,
+    pub players_judged: usize,
+    pub intensity: f32,
+    /// Bots allowed alive at once.
+    pub active_bots: usize,
+    pub bot_profile: BotProfile,
+}
+
+/// The director's state for one match.
+#[derive(Clone, Debug)]
+pub struct Director {
+    pub tracker: PerformanceTracker,
+    pub intensity: f32,
+    pub last_decision: Option<DirectorDecision>,
+}
+
+impl Director {
+    pub fn new(now: f32) -> Self {
+        Self {
+            tracker: PerformanceTracker::new(now),
+            intensity: 1.0,
+            last_decision: None,
+        }
+    }
+
+    /// Judge the players and move the intensity toward what keeps them challenged. With
+    /// no one to judge, the bots drift back to the configured difficulty.
+    pub fn decide(&mut self, now: f32, base: BotProfile, bot_count: usize) -> DirectorDecision {
+        self.tracker.forget_old(now);
+        let covered_secs = self.tracker.covered_secs(now);
+        let skills: Vec<f32> = self
+            .tracker
+            .summaries()
+            .into_iter()
+            .filter(|(_, summary)| summary.events >= MIN_JUDGED_EVENTS)
+            .map(|(_, summary)| summary.skill(covered_secs))
+            .collect();
+        let player_skill = if skills.is_empty() {
+            0.0
+        } else {
+            skills.iter().sum::<f32>() / skills.len() as f32
+        };
+
+        self.intensity = next_intensity(self.intensity, player_skill);
+        let decision = DirectorDecision {
+            time: now,
+            player_skill,
+            players_judged: skills.len(),
+            intensity: self.intensity,
+            active_bots: active_bots(bot_count, self.intensity),
+            bot_profile: scale_profile(base, self.intensity),
+        };
+        self.last_decision = Some(decision.clone());
+        decision
+    }
+}
+
+/// `current` moved toward the intensity matching `player_skill`, by at most
+/// [`MAX_INTENSITY_STEP`].
+pub fn next_intensity(current: f32, player_skill: f32) -> f32 {
+    let target = (1.0 + player_skill * 0.5).clamp(MIN_INTENSITY, MAX_INTENSITY);
+    (current + (target - current).clamp(-MAX_INTENSITY_STEP, MAX_INTENSITY_STEP))
+        .clamp(MIN_INTENSITY, MAX_INTENSITY)
+}
+
+/// Bots allowed alive at once out of `bot_count`: down to half of them while players
+/// struggle, all of them from an even match up.
+pub fn active_bots(bot_count: usize, intensity: f32) -> usize {
+    let minimum = bot_count.div_ceil(2);
+    let share = ((intensity - MIN_INTENSITY) / (1.0 - MIN_INTENSITY)).clamp(0.0, 1.0);
+    minimum + ((bot_count - minimum) as f32 * share).round() as usize
+}
+
+/// `base` scaled by `intensity`: sharper aim, more aggression and faster reactions above
+/// 1.0, the other way below.
+pub fn scale_profile(base: BotProfile, intensity: f32) -> BotProfile {
+    BotProfile {
+        reaction_time: (base.reaction_time / intensity).clamp(0.08, 1.5),
+        aggression_level: (base.aggression_level * intensity).clamp(0.0, 1.0),
+        accuracy: (base.accuracy * intensity).clamp(0.05, 0.95),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Director, MAX_INTENSITY, MAX_INTENSITY_STEP, MIN_INTENSITY, PERFORMANCE_WINDOW_SECS,
+        PerformanceEvent, PerformanceTracker, active_bots, next_intensity, scale_profile,
+    };
+    use crate::bots::BotDifficulty;
+
+    fn dominate(director: &mut Director, player: u64, now: f32) {
+        for _ in 0..4 {
+            director.tracker.record(player, now, PerformanceEvent::Kill);
+            director
+                .tracker
+                .record(player, now, PerformanceEvent::Shot { hit: true });
+        }
+    }
+
+    #[test]
+    fn old_events_leave_the_window() {
+        let mut tracker = PerformanceTracker::new(0.0);
+        tracker.record(1, 5.0, PerformanceEvent::Death);
+        tracker.record(1, 50.0, PerformanceEvent::Kill);
+        tracker.record(2, 10.0, PerformanceEvent::Shot { hit: false });
+
+        tracker.forget_old(10.0 + PERFORMANCE_WINDOW_SECS + 1.0);
+        let summaries = tracker.summaries();
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].0, 1);
+        assert_eq!((summaries[0].1.kills, summaries[0].1.deaths), (1, 0));
+    }
+
+    #[test]
+    fn skill_follows_kd_accuracy_and_damage_taken() {
+        let mut tracker = PerformanceTracker::new(0.0);
+        for _ in 0..5 {
+            tracker.record(1, 30.0, PerformanceEvent::Kill);
+            tracker.record(1, 30.0, PerformanceEvent::Shot { hit: true });
+            tracker.record(2, 30.0, PerformanceEvent::Death);
+            tracker.record(2, 30.0, PerformanceEvent::DamageTaken(100.0));
+            tracker.record(2, 30.0, PerformanceEvent::Shot { hit: false });
+        }
+        let summaries = tracker.summaries();
+        let covered = tracker.covered_secs(60.0);
+        assert!(summaries[0].1.skill(covered) > 0.5);
+        assert!(summaries[1].1.skill(covered) < -0.5);
+    }
+
+    #[test]
+    fn intensity_moves_in_bounded_steps() {
+        assert_eq!(next_intensity(1.0, 0.0), 1.0);
+        assert!((next_intensity(1.0, 1.0) - (1.0 + MAX_INTENSITY_STEP)).abs() < 1e-6);
+        assert!((next_intensity(1.0, -1.0) - (1.0 - MAX_INTENSITY_STEP)).abs() < 1e-6);
+        assert_eq!(next_intensity(MAX_INTENSITY, 1.0), MAX_INTENSITY);
+        assert_eq!(next_intensity(MIN_INTENSITY, -1.0), MIN_INTENSITY);
+    }
+
+    #[test]
+    fn fewer_and_weaker_bots_while_players_struggle() {
+        assert_eq!(active_bots(6, 1.0), 6);
+        assert_eq!(active_bots(6, MAX_INTENSITY), 6);
+        assert_eq!(active_bots(6, MIN_INTENSITY), 3);
+        assert_eq!(active_bots(0, MIN_INTENSITY), 0);
+
+        let base = BotDifficulty::Normal.profile();
+        let easier = scale_profile(base, 0.8);
+        let harder = scale_profile(base, 1.2);
+        assert!(easier.accuracy < base.accuracy && base.accuracy < harder.accuracy);
+        assert!(easier.reaction_time > base.reaction_time);
+        assert!(harder.reaction_time < base.reaction_time);
+        assert_eq!(scale_profile(base, 1.0), base);
+    }
+
+    #[test]
+    fn director_pushes_back_on_dominant_players_and_relaxes_without_them() {
+        let base = BotDifficulty::Normal.profile();
+        let mut director = Director::new(0.0);
+        let mut now = 0.0;
+        for _ in 0..5 {
+            now += 10.0;
+            dominate(&mut director, 1, now);
+            director.decide(now, base, 6);
+        }
+        let decision = director.last_decision.clone().unwrap();
+        assert_eq!(decision.players_judged, 1);
+        assert!(decision.intensity > 1.3);
+        assert!(decision.bot_profile.accuracy > base.accuracy);
+
+        // The player leaves: their events age out and the bots settle back.
+        for _ in 0..20 {
+            now += 10.0;
+            director.decide(now, base, 6);
+        }
+        assert_eq!(director.intensity, 1.0);
+        assert_eq!(director.last_decision.unwrap().players_judged, 0);
+    }
+}
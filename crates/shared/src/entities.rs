@@ -2,6 +2,7 @@ use avian3d::prelude::{
     AngularDamping, Collider, Friction, LinearDamping, LockedAxes, Mass, Restitution, RigidBody,
 };
 
+use crate::inputs::crouch::player_collider;
 use crate::inputs::input::{PLAYER_CAPSULE_HEIGHT, PLAYER_CAPSULE_RADIUS};
 use bevy::prelude::{Bundle, Color};
 
@@ -21,7 +22,7 @@ impl Default for PlayerPhysicsBundle {
     fn default() -> Self {
         Self {
             rigid_body: RigidBody::Dynamic,
-            collider: Collider::capsule(PLAYER_CAPSULE_HEIGHT, PLAYER_CAPSULE_RADIUS),
+            collider: player_collider(false),
             mass: Mass(80.0),
             restitution: Restitution::ZERO,
             friction: Friction::new(0.5),
@@ -0,0 +1,217 @@
+use bevy::prelude::{EulerRot, Quat, Vec3, Vec3Swizzles};
+
+/// Grid cells per unit `snap_vec3` rounds to, fine enough to hide float noise without moving
+/// anything visibly.
+pub const SNAP_SCALE: f32 = 1_000_000.0;
+
+/// Wrap an angle into `[-π, π]`.
+pub fn wrap_angle(radians: f32) -> f32 {
+    (radians + std::f32::consts::PI).rem_euclid(std::f32::consts::TAU) - std::f32::consts::PI
+}
+
+/// Signed turn from `from` to `to` the shortest way around, so crossing ±π does not read as
+/// a full turn.
+pub fn shortest_angle_delta(from: f32, to: f32) -> f32 {
+    wrap_angle(to - from)
+}
+
+/// Heading of a rotation around the vertical axis.
+pub fn yaw_of(rotation: Quat) -> f32 {
+    rotation.to_euler(EulerRot::YXZ).0
+}
+
+/// Yaw that points a model's +Z axis along `direction` on the ground plane.
+pub fn yaw_facing(direction: Vec3) -> f32 {
+    direction.x.atan2(direction.z)
+}
+
+/// Round every component to the `SNAP_SCALE` grid, so positions compared across apps or runs
+/// are not told apart by float noise.
+pub fn snap_vec3(value: Vec3) -> Vec3 {
+    (value * SNAP_SCALE).round() / SNAP_SCALE
+}
+
+/// Velocity to add to reach `wish_speed` along `wish_direction`. Never pushes the speed
+/// along `wish_direction` past `wish_speed`, and never slows down.
+pub fn calculate_acceleration(
+    wish_direction: Vec3,
+    wish_speed: f32,
+    acceleration: f32,
+    current_velocity: Vec3,
+    dt: f32,
+) -> Vec3 {
+    let velocity_projection = Vec3::dot(current_velocity, wish_direction);
+    let add_speed = wish_speed - velocity_projection;
+
+    if add_speed <= 0.0 {
+        return Vec3::ZERO;
+    }
+
+    let acceleration_speed = (acceleration * wish_speed * dt).min(add_speed);
+    wish_direction * acceleration_speed
+}
+
+/// Factor the lateral speed is scaled by after `dt` of friction. Speeds below `stop_speed`
+/// lose speed as if they were moving at `stop_speed`, so characters come to a full stop.
+pub fn friction_speed_scale(lateral_speed: f32, friction: f32, stop_speed: f32, dt: f32) -> f32 {
+    if lateral_speed <= 0.0 {
+        return 0.0;
+    }
+
+    let control = lateral_speed.max(stop_speed);
+    let drop = control * friction * dt;
+    ((lateral_speed - drop) / lateral_speed).max(0.0)
+}
+
+/// Scale the horizontal part of `velocity` down to `max_speed`, keeping the vertical part.
+pub fn clamp_planar_speed(velocity: Vec3, max_speed: f32) -> Vec3 {
+    let planar_speed = velocity.xz().length();
+    if planar_speed <= max_speed {
+        return velocity;
+    }
+
+    let ratio = max_speed / planar_speed;
+    Vec3::new(velocity.x * ratio, velocity.y, velocity.z * ratio)
+}
+
+#[cfg(test)]
+mod tests {
+    // Property tests: each one checks an invariant over many seeded random inputs.
+    use super::{
+        calculate_acceleration, clamp_planar_speed, friction_speed_scale, shortest_angle_delta,
+        snap_vec3, wrap_angle, yaw_facing, yaw_of,
+    };
+    use bevy::prelude::{Quat, Vec3, Vec3Swizzles};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::f32::consts::{PI, TAU};
+
+    const CASES: usize = 2_000;
+
+    fn random_vec3(rng: &mut StdRng, extent: f32) -> Vec3 {
+        Vec3::new(
+            rng.random_range(-extent..extent),
+            rng.random_range(-extent..extent),
+            rng.random_range(-extent..extent),
+        )
+    }
+
+    #[test]
+    fn wrapped_angles_stay_in_range_and_keep_their_direction() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..CASES {
+            let angle = rng.random_range(-100.0..100.0_f32);
+            let wrapped = wrap_angle(angle);
+
+            assert!(
+                (-PI..=PI).contains(&wrapped),
+                "{angle} wrapped to {wrapped}"
+            );
+            let turns = (angle - wrapped) / TAU;
+            assert!(
+                (turns - turns.round()).abs() < 1.0e-3,
+                "{angle} and {wrapped} differ by {turns} turns"
+            );
+            assert!((wrap_angle(wrapped) - wrapped).abs() < 1.0e-5);
+        }
+    }
+
+    #[test]
+    fn shortest_delta_never_exceeds_half_a_turn() {
+        let mut rng = StdRng::seed_from_u64(2);
+        for _ in 0..CASES {
+            let from = rng.random_range(-PI..PI);
+            let to = rng.random_range(-PI..PI);
+            let delta = shortest_angle_delta(from, to);
+
+            assert!(delta.abs() <= PI);
+            assert!((wrap_angle(from + delta) - wrap_angle(to)).abs() < 1.0e-4);
+        }
+    }
+
+    #[test]
+    fn yaw_facing_round_trips_through_a_rotation() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..CASES {
+            let direction = random_vec3(&mut rng, 10.0).with_y(0.0);
+            if direction.length() < 0.01 {
+                continue;
+            }
+
+            let yaw = yaw_facing(direction);
+            let rotation = Quat::from_rotation_y(yaw);
+            assert!((rotation * Vec3::Z).dot(direction.normalize()) > 0.999);
+            assert!(shortest_angle_delta(yaw_of(rotation), yaw).abs() < 1.0e-4);
+        }
+    }
+
+    #[test]
+    fn snapping_is_idempotent_and_stays_close() {
+        let mut rng = StdRng::seed_from_u64(4);
+        for _ in 0..CASES {
+            let value = random_vec3(&mut rng, 10.0);
+            let snapped = snap_vec3(value);
+
+            assert!((snapped - value).abs().max_element() <= 1.0e-5);
+            assert_eq!(snap_vec3(snapped), snapped);
+        }
+    }
+
+    #[test]
+    fn acceleration_is_clamped_to_the_wish_speed() {
+        let mut rng = StdRng::seed_from_u64(5);
+        for _ in 0..CASES {
+            let wish_direction = random_vec3(&mut rng, 1.0).with_y(0.0).normalize_or_zero();
+            if wish_direction == Vec3::ZERO {
+                continue;
+            }
+            let wish_speed = rng.random_range(0.0..60.0);
+            let acceleration = rng.random_range(0.0..20.0);
+            let velocity = random_vec3(&mut rng, 60.0);
+            let dt = rng.random_range(0.0..0.1);
+
+            let add =
+                calculate_acceleration(wish_direction, wish_speed, acceleration, velocity, dt);
+            let speed_before = velocity.dot(wish_direction);
+            let speed_after = (velocity + add).dot(wish_direction);
+
+            assert!(add.dot(wish_direction) >= 0.0, "acceleration slowed down");
+            assert!(add.length() <= acceleration * wish_speed * dt + 1.0e-3);
+            assert!(speed_after <= speed_before.max(wish_speed) + 1.0e-3);
+        }
+    }
+
+    #[test]
+    fn friction_is_monotonic_and_never_reverses() {
+        let mut rng = StdRng::seed_from_u64(6);
+        for _ in 0..CASES {
+            let speed = rng.random_range(0.0..60.0_f32);
+            let faster = speed + rng.random_range(0.0..20.0_f32);
+            let dt = rng.random_range(0.0..0.1);
+            let longer_dt = dt + rng.random_range(0.0..0.1);
+
+            let scale = friction_speed_scale(speed, 10.0, 1.0, dt);
+            assert!((0.0..=1.0).contains(&scale));
+            // More friction time never leaves more speed.
+            assert!(friction_speed_scale(speed, 10.0, 1.0, longer_dt) <= scale + 1.0e-6);
+            // A faster character never ends slower than a slower one.
+            assert!(faster * friction_speed_scale(faster, 10.0, 1.0, dt) >= speed * scale - 1.0e-4);
+        }
+    }
+
+    #[test]
+    fn planar_clamp_caps_horizontal_speed_only() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..CASES {
+            let velocity = random_vec3(&mut rng, 100.0);
+            let max_speed = rng.random_range(0.0..50.0);
+            let clamped = clamp_planar_speed(velocity, max_speed);
+
+            assert!(clamped.xz().length() <= max_speed + 1.0e-3);
+            assert_eq!(clamped.y, velocity.y);
+            if velocity.xz().length() <= max_speed {
+                assert_eq!(clamped, velocity);
+            }
+        }
+    }
+}
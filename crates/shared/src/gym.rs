@@ -2,6 +2,7 @@ use crate::GymMode;
 use crate::components::health::{Health, Respawnable};
 use crate::debug::{GymWanderDiagnostics, gym_debug_info, gym_debug_warn};
 use crate::entities::NpcPhysicsBundle;
+use crate::level::generation::LevelGeometry;
 use crate::navigation::{
     NavigationObstacle, NavigationPathState, SimpleNavigationAgent, validate_spawn_position,
 };
@@ -9,8 +10,8 @@ use crate::protocol::{CharacterMarker, PlayerId};
 use avian3d::prelude::{Collider, LinearVelocity, Position, RigidBody, Rotation};
 use bevy::prelude::Color;
 use bevy::prelude::{
-    Assets, Commands, Component, Cuboid, Dir3, Mesh, Mesh3d, MeshMaterial3d, Name, Plane3d, Query,
-    Ref, Res, ResMut, StandardMaterial, Vec2, Vec3, With, Without, default,
+    Assets, Commands, Component, Cuboid, Dir3, Entity, Mesh, Mesh3d, MeshMaterial3d, Name, Plane3d,
+    Query, Ref, Res, ResMut, StandardMaterial, Vec2, Vec3, With, Without, default,
 };
 use rand::Rng;
 use std::ops::Deref;
@@ -36,6 +37,10 @@ pub struct LevelDoneMarker;
 #[derive(Component, Clone, Debug, Default)]
 pub struct GymRandomWanderer;
 
+/// One of the crates scattered in the gym room, as opposed to its walls.
+#[derive(Component, Clone, Debug, Default)]
+pub struct GymObstacle;
+
 fn random_gym_floor_point(rng: &mut impl rand::Rng) -> Vec3 {
     let sample_extent = ROOM_HALF_EXTENT - GYM_TARGET_MARGIN;
     Vec3::new(
@@ -52,6 +57,7 @@ pub fn setup_gym_level(
 ) {
     let mut floor_entity = commands.spawn((
         Name::new("Floor"),
+        LevelGeometry,
         Position::from(Vec3::new(0.0, -FLOOR_THICKNESS / 2.0, 0.0)),
         Mesh3d(meshes.add(Plane3d {
             normal: Dir3::Y,
@@ -67,6 +73,7 @@ pub fn setup_gym_level(
 
     let mut ceiling_entity = commands.spawn((
         Name::new("Ceiling"),
+        LevelGeometry,
         Position::from(Vec3::new(0.0, WALL_HEIGHT + FLOOR_THICKNESS / 2.0, 0.0)),
         Mesh3d(meshes.add(Plane3d {
             normal: Dir3::NEG_Y,
@@ -106,6 +113,7 @@ pub fn setup_gym_level(
     for (position, size, name) in walls {
         let mut wall_entity = commands.spawn((
             Name::new(name),
+            LevelGeometry,
             Position::from(position),
             Mesh3d(meshes.add(Cuboid {
                 half_size: size / 2.0,
@@ -120,6 +128,39 @@ pub fn setup_gym_level(
         }
     }
 
+    spawn_gym_obstacles(&mut commands, &mut meshes, materials.as_deref_mut());
+
+    let nav_area = ROOM_HALF_EXTENT - 2.0;
+    commands.spawn((
+        ManagedNavMesh::single(),
+        NavMeshSettings {
+            fixed: Triangulation::from_outer_edges(&[
+                Vec2::new(-nav_area, -nav_area),
+                Vec2::new(nav_area, -nav_area),
+                Vec2::new(nav_area, nav_area),
+                Vec2::new(-nav_area, nav_area),
+            ]),
+            simplify: 0.1,
+            merge_steps: 1,
+            build_timeout: Some(10.0),
+            agent_radius: 1.0,
+            ..default()
+        },
+        NavMeshUpdateMode::Direct,
+        Name::new("NavMesh"),
+        LevelGeometry,
+    ));
+
+    commands.spawn((LevelDoneMarker, Name::new("Gym"), LevelGeometry));
+}
+
+/// Spawns the crates of the gym room. Training curricula despawn them (see
+/// [`GymObstacle`]) for stages in an empty room and call this to bring them back.
+pub fn spawn_gym_obstacles(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    mut materials: Option<&mut Assets<StandardMaterial>>,
+) {
     let obstacle_positions = [
         Vec3::new(15.0, 1.5, 10.0),
         Vec3::new(-10.0, 1.5, -15.0),
@@ -130,6 +171,8 @@ pub fn setup_gym_level(
     for (i, pos) in obstacle_positions.iter().enumerate() {
         let mut obstacle_entity = commands.spawn((
             Name::new(format!("Obstacle_{}", i + 1)),
+            LevelGeometry,
+            GymObstacle,
             Position::from(*pos),
             Mesh3d(meshes.add(Cuboid::new(OBSTACLE_SIZE, OBSTACLE_SIZE, OBSTACLE_SIZE))),
             RigidBody::Static,
@@ -148,28 +191,6 @@ pub fn setup_gym_level(
             })));
         }
     }
-
-    let nav_area = ROOM_HALF_EXTENT - 2.0;
-    commands.spawn((
-        ManagedNavMesh::single(),
-        NavMeshSettings {
-            fixed: Triangulation::from_outer_edges(&[
-                Vec2::new(-nav_area, -nav_area),
-                Vec2::new(nav_area, -nav_area),
-                Vec2::new(nav_area, nav_area),
-                Vec2::new(-nav_area, nav_area),
-            ]),
-            simplify: 0.1,
-            merge_steps: 1,
-            build_timeout: Some(10.0),
-            agent_radius: 1.0,
-            ..default()
-        },
-        NavMeshUpdateMode::Direct,
-        Name::new("NavMesh"),
-    ));
-
-    commands.spawn((LevelDoneMarker, Name::new("Gym")));
 }
 
 pub fn spawn_gym_patrolling_npc_entities(
@@ -191,39 +212,57 @@ pub fn spawn_gym_patrolling_npc_entities(
         npc_specs.len()
     ));
 
-    let mut rng = rand::rng();
-
     for (name, spawn_position, speed) in npc_specs {
-        let validated_spawn = validate_spawn_position(spawn_position, &obstacles, 0.5);
-        let mut nav_agent = SimpleNavigationAgent::new(speed);
-        nav_agent.arrival_threshold = 2.0;
-        nav_agent.current_target = Some(random_gym_floor_point(&mut rng));
-
-        let enemy = commands
-            .spawn((
-                Name::new(name),
-                Position::new(validated_spawn),
-                Rotation::default(),
-                LinearVelocity::default(),
-                Health::basic(),
-                Respawnable::with_position(2.0, validated_spawn),
-                Replicate::to_clients(NetworkTarget::All),
-                InterpolationTarget::to_clients(NetworkTarget::All),
-                CharacterMarker,
-                NpcPhysicsBundle::default(),
-                nav_agent,
-                NavigationPathState::default(),
-                GymRandomWanderer,
-                GymWanderDiagnostics::new(validated_spawn),
-            ))
-            .id();
-
-        // Gym NPC movement is driven directly by nav Position updates.
-        // Keep body kinematic to avoid dynamic solver jitter/fighting.
-        commands.entity(enemy).insert(RigidBody::Kinematic);
+        spawn_gym_wanderer(
+            &mut commands,
+            &obstacles,
+            name,
+            spawn_position,
+            speed,
+            Health::basic(),
+        );
     }
 }
 
+/// Spawns an NPC wandering between random points of the gym room.
+pub fn spawn_gym_wanderer(
+    commands: &mut Commands,
+    obstacles: &Query<&Position, With<NavigationObstacle>>,
+    name: &str,
+    spawn_position: Vec3,
+    speed: f32,
+    health: Health,
+) -> Entity {
+    let validated_spawn = validate_spawn_position(spawn_position, obstacles, 0.5);
+    let mut nav_agent = SimpleNavigationAgent::new(speed);
+    nav_agent.arrival_threshold = 2.0;
+    nav_agent.current_target = Some(random_gym_floor_point(&mut rand::rng()));
+
+    let enemy = commands
+        .spawn((
+            Name::new(name.to_string()),
+            Position::new(validated_spawn),
+            Rotation::default(),
+            LinearVelocity::default(),
+            health,
+            Respawnable::with_position(2.0, validated_spawn),
+            Replicate::to_clients(NetworkTarget::All),
+            InterpolationTarget::to_clients(NetworkTarget::All),
+            CharacterMarker,
+            NpcPhysicsBundle::default(),
+            nav_agent,
+            NavigationPathState::default(),
+            GymRandomWanderer,
+            GymWanderDiagnostics::new(validated_spawn),
+        ))
+        .id();
+
+    // Gym NPC movement is driven directly by nav Position updates.
+    // Keep body kinematic to avoid dynamic solver jitter/fighting.
+    commands.entity(enemy).insert(RigidBody::Kinematic);
+    enemy
+}
+
 pub fn update_gym_wandering_npc_targets(
     gym_mode: Option<Res<GymMode>>,
     navmesh_query: Query<(&ManagedNavMesh, Ref<NavMeshStatus>)>,
@@ -0,0 +1,157 @@
+//! Bot hearing. Characters make sounds that carry up to a range and lose loudness through
+//! every obstacle in between; bots notice the ones still louder than their threshold and
+//! learn where they came from.
+
+use bevy::prelude::Vec3;
+use serde::{Deserialize, Serialize};
+
+use crate::inputs::movement::WALK_SPEED;
+
+/// Slower than this on the ground a character makes no noise.
+const SILENT_SPEED: f32 = 0.5;
+/// Faster than this on the ground is sprinting, which carries farther than walking.
+const SPRINT_SPEED: f32 = WALK_SPEED * 1.15;
+
+/// Sounds bots can hear, quietest first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SoundKind {
+    Footstep,
+    Sprint,
+    Gunshot,
+}
+
+impl SoundKind {
+    pub const ALL: [SoundKind; 3] = [SoundKind::Footstep, SoundKind::Sprint, SoundKind::Gunshot];
+
+    /// Loudest sound a character makes this frame: a shot drowns its footsteps.
+    pub fn made_by(velocity: Vec3, grounded: bool, fired: bool) -> Option<Self> {
+        if fired {
+            return Some(SoundKind::Gunshot);
+        }
+        let speed = velocity.with_y(0.0).length();
+        if !grounded || speed < SILENT_SPEED {
+            None
+        } else if speed < SPRINT_SPEED {
+            Some(SoundKind::Footstep)
+        } else {
+            Some(SoundKind::Sprint)
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SoundKind::Footstep => "Footstep",
+            SoundKind::Sprint => "Sprint",
+            SoundKind::Gunshot => "Gunshot",
+        }
+    }
+}
+
+/// How far sounds carry and how much bots need to hear, part of the balance config.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HearingBalance {
+    pub footstep_range: f32,
+    pub sprint_range: f32,
+    pub gunshot_range: f32,
+    /// Fraction of the loudness that makes it through each obstacle.
+    pub occlusion_transmission: f32,
+    /// Quietest loudness, from 0.0 to 1.0, a bot notices.
+    pub threshold: f32,
+}
+
+impl Default for HearingBalance {
+    fn default() -> Self {
+        Self {
+            footstep_range: 12.0,
+            sprint_range: 25.0,
+            gunshot_range: 70.0,
+            occlusion_transmission: 0.4,
+            threshold: 0.15,
+        }
+    }
+}
+
+impl HearingBalance {
+    pub fn range(&self, kind: SoundKind) -> f32 {
+        match kind {
+            SoundKind::Footstep => self.footstep_range,
+            SoundKind::Sprint => self.sprint_range,
+            SoundKind::Gunshot => self.gunshot_range,
+        }
+    }
+
+    /// Loudness from 1.0 at the source, falling linearly to nothing at the range, of
+    /// `kind` heard `distance` away through `occluders` obstacles.
+    pub fn loudness(&self, kind: SoundKind, distance: f32, occluders: usize) -> f32 {
+        let range = self.range(kind);
+        if range <= 0.0 {
+            return 0.0;
+        }
+        let falloff = (1.0 - distance / range).clamp(0.0, 1.0);
+        falloff * self.transmission(occluders)
+    }
+
+    pub fn is_heard(&self, kind: SoundKind, distance: f32, occluders: usize) -> bool {
+        let loudness = self.loudness(kind, distance, occluders);
+        loudness > 0.0 && loudness >= self.threshold
+    }
+
+    /// Farthest a bot hears `kind` through `occluders` obstacles.
+    pub fn audible_radius(&self, kind: SoundKind, occluders: usize) -> f32 {
+        let transmission = self.transmission(occluders);
+        if transmission <= self.threshold {
+            return 0.0;
+        }
+        self.range(kind).max(0.0) * (1.0 - self.threshold / transmission)
+    }
+
+    fn transmission(&self, occluders: usize) -> f32 {
+        self.occlusion_transmission
+            .clamp(0.0, 1.0)
+            .powi(occluders as i32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HearingBalance, SoundKind};
+    use bevy::prelude::Vec3;
+
+    #[test]
+    fn sounds_fade_with_distance_and_walls() {
+        let hearing = HearingBalance::default();
+        assert_eq!(hearing.loudness(SoundKind::Footstep, 0.0, 0), 1.0);
+        assert!((hearing.loudness(SoundKind::Footstep, 6.0, 0) - 0.5).abs() < 1e-6);
+        assert!((hearing.loudness(SoundKind::Footstep, 6.0, 1) - 0.2).abs() < 1e-6);
+        assert_eq!(hearing.loudness(SoundKind::Footstep, 20.0, 0), 0.0);
+
+        assert!(hearing.is_heard(SoundKind::Footstep, 6.0, 1));
+        assert!(!hearing.is_heard(SoundKind::Footstep, 6.0, 2));
+        assert!(hearing.is_heard(SoundKind::Gunshot, 30.0, 1));
+
+        let radius = hearing.audible_radius(SoundKind::Footstep, 1);
+        assert!(hearing.is_heard(SoundKind::Footstep, radius - 0.01, 1));
+        assert!(!hearing.is_heard(SoundKind::Footstep, radius + 0.01, 1));
+        assert_eq!(hearing.audible_radius(SoundKind::Footstep, 3), 0.0);
+    }
+
+    #[test]
+    fn shots_drown_footsteps_and_standing_still_is_silent() {
+        let walking = Vec3::new(5.0, 0.0, 0.0);
+        assert_eq!(
+            SoundKind::made_by(walking, true, false),
+            Some(SoundKind::Footstep)
+        );
+        assert_eq!(
+            SoundKind::made_by(walking * 10.0, true, false),
+            Some(SoundKind::Sprint)
+        );
+        assert_eq!(
+            SoundKind::made_by(Vec3::ZERO, true, true),
+            Some(SoundKind::Gunshot)
+        );
+        assert_eq!(SoundKind::made_by(walking, false, false), None);
+        assert_eq!(SoundKind::made_by(Vec3::ZERO, true, false), None);
+    }
+}
@@ -0,0 +1,312 @@
+//! Ladders and mantling, on top of ground and air movement. Both only override the
+//! velocity `apply_movement` left, from the character's inputs and the level around it, so
+//! predicting clients climb exactly as the server does and rollbacks replay it.
+//!
+//! Characters in a ladder volume pressing forward climb it; forward and back move them up
+//! and down, jump pushes them off. Characters pressing forward into a ledge between
+//! [`MANTLE_MIN_HEIGHT`] and [`MANTLE_MAX_HEIGHT`] above them vault onto it on their own,
+//! which is also how they get off the top of a ladder.
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+use serde::{Deserialize, Serialize};
+
+use crate::clock::GameClock;
+use crate::game_math::yaw_of;
+use crate::inputs::input::{PLAYER_CAPSULE_RADIUS, PlayerAction};
+use crate::inputs::movement::{GroundState, TRACTION_NORMAL_CUTOFF};
+
+pub const CLIMB_SPEED: f32 = 3.0;
+/// Move input past this counts as pressing forward or back.
+pub const CLIMB_INPUT_THRESHOLD: f32 = 0.3;
+/// Speed a jump pushes a climbing character off its ladder, and up.
+pub const LADDER_JUMP_PUSH: f32 = 4.0;
+pub const LADDER_JUMP_SPEED: f32 = 4.0;
+/// Speed climbers are pulled against the wall at, so they reach the ledge at the top.
+const LADDER_PULL_SPEED: f32 = 1.0;
+/// Ledges lower than this are stepped onto as usual.
+pub const MANTLE_MIN_HEIGHT: f32 = 0.5;
+/// Ledges higher than this are out of reach.
+pub const MANTLE_MAX_HEIGHT: f32 = 1.4;
+/// Furthest in front of a character a ledge is noticed.
+pub const MANTLE_REACH: f32 = 0.6;
+/// How far past the edge of a ledge a mantle lands.
+pub const MANTLE_DEPTH: f32 = 0.6;
+pub const MANTLE_RISE_SPEED: f32 = 5.0;
+pub const MANTLE_FORWARD_SPEED: f32 = 4.0;
+/// A mantle ends once the character is this close to where it lands.
+const MANTLE_ARRIVAL: f32 = 0.1;
+/// Mantles blocked on the way are abandoned after this long.
+const MANTLE_MAX_SECS: f32 = 1.0;
+
+/// Climbable volume in front of a wall. Its local +X points into the wall.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Ladder {
+    pub size: Vec3,
+}
+
+impl Ladder {
+    /// Whether a character centered on `point` touches the volume of the ladder at
+    /// `center`, turned by `rotation`.
+    pub fn contains(&self, center: Vec3, rotation: Quat, point: Vec3) -> bool {
+        let local = rotation.inverse() * (point - center);
+        local
+            .abs()
+            .cmple(self.size * 0.5 + Vec3::splat(PLAYER_CAPSULE_RADIUS))
+            .all()
+    }
+}
+
+/// Climb state, next to [`GroundState`]. Predicted, so the local player climbs at once.
+#[derive(Component, Reflect, Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct ClimbState {
+    /// Horizontal direction into the wall while on a ladder.
+    pub ladder_facing: Option<Vec3>,
+    /// Where the character lands while mantling onto a ledge.
+    pub mantle_target: Option<Vec3>,
+    /// Time spent on the current mantle.
+    pub mantle_secs: f32,
+}
+
+/// Spawn the volume of a ladder centered on `center`.
+pub fn spawn_ladder(
+    commands: &mut Commands,
+    name: impl Into<String>,
+    center: Vec3,
+    rotation: Quat,
+    size: Vec3,
+) -> Entity {
+    commands
+        .spawn((
+            Name::new(name.into()),
+            Ladder { size },
+            Position::new(center),
+            Rotation::from(rotation),
+        ))
+        .id()
+}
+
+/// Velocity of a character climbing a ladder into `facing`: forward input climbs, back
+/// input descends.
+pub fn ladder_velocity(move_input: Vec2, facing: Vec3) -> Vec3 {
+    Vec3::Y * (move_input.y.clamp(-1.0, 1.0) * CLIMB_SPEED) + facing * LADDER_PULL_SPEED
+}
+
+/// Velocity carrying a mantling character at `position` to `target` within a `dt` tick,
+/// or `None` once it is there. It rises all the way first so it clears the edge, then
+/// moves over.
+pub fn mantle_velocity(position: Vec3, target: Vec3, dt: f32) -> Option<Vec3> {
+    let rise = target.y - position.y;
+    let planar = (target - position).with_y(0.0);
+    if rise > 1e-3 {
+        Some(Vec3::Y * MANTLE_RISE_SPEED.min(rise / dt.max(f32::EPSILON)))
+    } else if planar.length() > MANTLE_ARRIVAL {
+        let speed = MANTLE_FORWARD_SPEED.min(planar.length() / dt.max(f32::EPSILON));
+        Some(planar.normalize() * speed)
+    } else {
+        None
+    }
+}
+
+/// Where a character at `position` moving towards `forward` lands if it mantles: on top
+/// of a steep obstacle within [`MANTLE_REACH`], between [`MANTLE_MIN_HEIGHT`] and
+/// [`MANTLE_MAX_HEIGHT`] above it, with room for its collider over the edge.
+pub fn find_mantle_target(
+    spatial_query: &SpatialQueryPipeline,
+    entity: Entity,
+    collider: &Collider,
+    position: Vec3,
+    rotation: Quat,
+    forward: Vec3,
+) -> Option<Vec3> {
+    let filter = SpatialQueryFilter::default().with_excluded_entities([entity]);
+    let forward = Dir3::new(forward.with_y(0.0)).ok()?;
+    let cast = |origin: Vec3, direction: Dir3, distance: f32| {
+        spatial_query.cast_shape(
+            collider,
+            origin,
+            rotation,
+            direction,
+            &ShapeCastConfig::from_max_distance(distance),
+            &filter,
+        )
+    };
+
+    let wall = cast(position, forward, MANTLE_REACH)?;
+    if wall.normal1.y.abs() >= TRACTION_NORMAL_CUTOFF {
+        return None;
+    }
+    // Head room to rise, then room to move over the edge.
+    if cast(position, Dir3::Y, MANTLE_MAX_HEIGHT).is_some() {
+        return None;
+    }
+    let raised = position + Vec3::Y * MANTLE_MAX_HEIGHT;
+    let over = wall.distance + MANTLE_DEPTH;
+    if cast(raised, forward, over).is_some() {
+        return None;
+    }
+
+    let above_ledge = raised + forward * over;
+    let top = cast(above_ledge, Dir3::NEG_Y, MANTLE_MAX_HEIGHT)?;
+    if top.normal1.y < TRACTION_NORMAL_CUTOFF {
+        return None;
+    }
+    let height = MANTLE_MAX_HEIGHT - top.distance;
+    (height >= MANTLE_MIN_HEIGHT).then(|| above_ledge - Vec3::Y * (top.distance - 0.05))
+}
+
+/// System: mantle, climb ladders or push off them, overriding the velocity from
+/// `apply_movement`. Climbing characters hold against gravity.
+pub fn apply_climbing(
+    clock: Res<GameClock>,
+    gravity: Option<Res<Gravity>>,
+    spatial_query: Res<SpatialQueryPipeline>,
+    ladders: Query<(&Ladder, &Position, &Rotation)>,
+    mut characters: Query<(
+        Entity,
+        &ActionState<PlayerAction>,
+        &GroundState,
+        &Collider,
+        &Position,
+        &Rotation,
+        &mut LinearVelocity,
+        &mut ClimbState,
+    )>,
+) {
+    let dt = clock.delta_secs();
+    if dt <= 0.0 {
+        return;
+    }
+    // Cancels what avian's gravity takes off this step.
+    let hold = -gravity.map_or(Vec3::ZERO, |gravity| gravity.0) * dt;
+
+    for (
+        entity,
+        action_state,
+        ground_state,
+        collider,
+        position,
+        rotation,
+        mut velocity,
+        mut climb,
+    ) in characters.iter_mut()
+    {
+        let (move_input, jumping) = if action_state.disabled() {
+            (Vec2::ZERO, false)
+        } else {
+            (
+                action_state.axis_pair(&PlayerAction::Move),
+                action_state.just_pressed(&PlayerAction::Jump),
+            )
+        };
+        let mut next = climb.clone();
+
+        if let Some(target) = climb.mantle_target {
+            next.mantle_secs += dt;
+            match mantle_velocity(position.0, target, dt) {
+                Some(mantle) if next.mantle_secs < MANTLE_MAX_SECS => velocity.0 = mantle + hold,
+                _ => {
+                    next = ClimbState::default();
+                    velocity.0 = Vec3::ZERO;
+                }
+            }
+            climb.set_if_neq(next);
+            continue;
+        }
+
+        let forward = Quat::from_rotation_y(yaw_of(rotation.0)) * Vec3::NEG_Z;
+        let pressing_forward = move_input.y > CLIMB_INPUT_THRESHOLD;
+        let mantle = pressing_forward
+            .then(|| {
+                find_mantle_target(
+                    &spatial_query,
+                    entity,
+                    collider,
+                    position.0,
+                    rotation.0,
+                    forward,
+                )
+            })
+            .flatten();
+        if let Some(target) = mantle {
+            next.ladder_facing = None;
+            next.mantle_target = Some(target);
+            next.mantle_secs = 0.0;
+            velocity.0 = mantle_velocity(position.0, target, dt).unwrap_or(Vec3::ZERO) + hold;
+            climb.set_if_neq(next);
+            continue;
+        }
+
+        let ladder = ladders
+            .iter()
+            .find(|(ladder, center, turn)| ladder.contains(center.0, turn.0, position.0))
+            .map(|(_, _, turn)| turn.0 * Vec3::X);
+        next.ladder_facing = match ladder {
+            Some(facing) if climb.ladder_facing.is_some() || pressing_forward => {
+                let stepping_off =
+                    ground_state.is_grounded && move_input.y < -CLIMB_INPUT_THRESHOLD;
+                if jumping {
+                    velocity.0 = -facing * LADDER_JUMP_PUSH + Vec3::Y * LADDER_JUMP_SPEED;
+                    None
+                } else if stepping_off {
+                    None
+                } else {
+                    velocity.0 = ladder_velocity(move_input, facing) + hold;
+                    Some(facing)
+                }
+            }
+            _ => None,
+        };
+        climb.set_if_neq(next);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CLIMB_SPEED, Ladder, MANTLE_RISE_SPEED, ladder_velocity, mantle_velocity};
+    use bevy::prelude::{Quat, Vec2, Vec3};
+
+    #[test]
+    fn ladders_are_reached_from_in_front_in_their_own_frame() {
+        let ladder = Ladder {
+            size: Vec3::new(0.8, 5.0, 1.0),
+        };
+        let center = Vec3::new(10.0, 2.5, 0.0);
+        let turned = Quat::from_rotation_y(std::f32::consts::FRAC_PI_2);
+
+        assert!(ladder.contains(center, Quat::IDENTITY, Vec3::new(9.2, 1.0, 0.0)));
+        assert!(!ladder.contains(center, Quat::IDENTITY, Vec3::new(10.0, 1.0, 1.8)));
+        // Turned a quarter, the ladder's depth lies along Z.
+        assert!(ladder.contains(center, turned, Vec3::new(10.0, 1.0, 0.8)));
+        assert!(!ladder.contains(center, turned, Vec3::new(8.8, 1.0, 0.0)));
+
+        let up = ladder_velocity(Vec2::new(0.0, 1.0), Vec3::X);
+        let down = ladder_velocity(Vec2::new(0.0, -1.0), Vec3::X);
+        assert_eq!(up.y, CLIMB_SPEED);
+        assert_eq!(down.y, -CLIMB_SPEED);
+        assert!(
+            up.x > 0.0 && up.z == 0.0,
+            "Climbers are pulled against the wall"
+        );
+    }
+
+    #[test]
+    fn mantles_rise_then_move_over_the_edge() {
+        let target = Vec3::new(1.0, 2.0, 0.0);
+        let dt = 1.0 / 60.0;
+        let mut position = Vec3::ZERO;
+
+        let first = mantle_velocity(position, target, dt).unwrap();
+        assert_eq!(first, Vec3::Y * MANTLE_RISE_SPEED);
+
+        let mut steps = 0;
+        while let Some(velocity) = mantle_velocity(position, target, dt) {
+            assert!(velocity.y == 0.0 || velocity.x == 0.0, "{velocity:?}");
+            position += velocity * dt;
+            steps += 1;
+            assert!(steps < 120, "The mantle should finish");
+        }
+        assert!(position.distance(target) < 0.2);
+    }
+}
@@ -0,0 +1,293 @@
+//! Crouching and sliding. Crouched characters get a shorter capsule and move slower;
+//! crouching while sprinting on the ground slides on the momentum instead. Only the
+//! character's inputs, position and the level around it decide any of it, so predicting
+//! clients crouch exactly as the server does and rollbacks replay it.
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+use serde::{Deserialize, Serialize};
+
+use crate::clock::GameClock;
+use crate::inputs::input::{PLAYER_CAPSULE_HEIGHT, PLAYER_CAPSULE_RADIUS, PlayerAction};
+use crate::inputs::movement::{FRICTION, GroundState};
+
+/// Capsule height while crouched, in place of [`PLAYER_CAPSULE_HEIGHT`].
+pub const PLAYER_CROUCH_HEIGHT: f32 = 0.75;
+/// Top ground speed while crouched.
+pub const CROUCH_SPEED: f32 = 8.0;
+/// Planar speed needed to slide when crouching during a sprint, and to keep sliding.
+pub const SLIDE_MIN_SPEED: f32 = 6.0;
+/// Speed a slide adds on top of the sprint's.
+pub const SLIDE_BOOST: f32 = 3.0;
+pub const SLIDE_SECS: f32 = 0.8;
+/// Ground friction while sliding.
+pub const SLIDE_FRICTION: f32 = FRICTION * 0.15;
+
+/// Crouch state, next to [`GroundState`]. Predicted, so the local player crouches at once.
+#[derive(Component, Reflect, Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct CrouchState {
+    pub crouched: bool,
+    /// Whether `ToggleCrouch` holds the crouch, with or without `Crouch` held.
+    pub toggled: bool,
+    /// Time left on the current slide.
+    pub slide_secs: f32,
+}
+
+impl CrouchState {
+    pub fn is_sliding(&self) -> bool {
+        self.slide_secs > 0.0
+    }
+}
+
+/// Crouch inputs of a character for one tick.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CrouchInput {
+    pub held: bool,
+    pub toggle_pressed: bool,
+    pub sprinting: bool,
+}
+
+impl CrouchInput {
+    pub fn from_action_state(action_state: &ActionState<PlayerAction>) -> Self {
+        if action_state.disabled() {
+            return Self::default();
+        }
+        Self {
+            held: action_state.pressed(&PlayerAction::Crouch),
+            toggle_pressed: action_state.just_pressed(&PlayerAction::ToggleCrouch),
+            sprinting: action_state.pressed(&PlayerAction::Sprint),
+        }
+    }
+}
+
+/// Collider of a standing or crouched player.
+pub fn player_collider(crouched: bool) -> Collider {
+    let height = if crouched {
+        PLAYER_CROUCH_HEIGHT
+    } else {
+        PLAYER_CAPSULE_HEIGHT
+    };
+    Collider::capsule(height, PLAYER_CAPSULE_RADIUS)
+}
+
+/// Distance from the center of a capsule collider to its top or bottom.
+fn capsule_half_extent(collider: &Collider) -> Option<f32> {
+    let capsule = collider.shape().as_capsule()?;
+    Some(capsule.half_height() + capsule.radius)
+}
+
+/// How much lower a crouched player's center is than a standing one's, with their feet
+/// in the same place.
+pub fn crouch_drop() -> f32 {
+    let standing = capsule_half_extent(&player_collider(false)).unwrap_or_default();
+    let crouched = capsule_half_extent(&player_collider(true)).unwrap_or_default();
+    standing - crouched
+}
+
+/// The crouch state after one `dt` tick from `state`. `has_headroom` is only asked when
+/// the character tries to stand, and keeps it crouched under anything too low.
+pub fn next_crouch_state(
+    state: &CrouchState,
+    input: CrouchInput,
+    grounded: bool,
+    planar_speed: f32,
+    has_headroom: impl FnOnce() -> bool,
+    dt: f32,
+) -> CrouchState {
+    let mut next = state.clone();
+    if input.toggle_pressed {
+        next.toggled = !next.toggled;
+    }
+    let wants_crouch = input.held || next.toggled;
+
+    if wants_crouch && !state.crouched {
+        next.crouched = true;
+        if grounded && input.sprinting && planar_speed >= SLIDE_MIN_SPEED {
+            next.slide_secs = SLIDE_SECS;
+            return next;
+        }
+    } else if !wants_crouch && state.crouched && has_headroom() {
+        next.crouched = false;
+        next.slide_secs = 0.0;
+    }
+
+    // Slides end in the air, once slowed down or once their time is up.
+    if next.is_sliding() {
+        next.slide_secs = if grounded && planar_speed >= SLIDE_MIN_SPEED {
+            (next.slide_secs - dt).max(0.0)
+        } else {
+            0.0
+        };
+    }
+    next
+}
+
+/// System: crouch, stand up where there is room, and start slides. Grounded characters
+/// keep their feet where they are; the collider itself follows in `fit_crouch_collider`.
+pub fn update_crouch(
+    clock: Res<GameClock>,
+    spatial_query: Res<SpatialQueryPipeline>,
+    mut query: Query<(
+        Entity,
+        &ActionState<PlayerAction>,
+        &GroundState,
+        &Collider,
+        &Rotation,
+        &mut Position,
+        &mut LinearVelocity,
+        &mut CrouchState,
+    )>,
+) {
+    let dt = clock.delta_secs();
+    let drop = crouch_drop();
+
+    for (
+        entity,
+        action_state,
+        ground_state,
+        collider,
+        rotation,
+        mut position,
+        mut velocity,
+        mut crouch,
+    ) in query.iter_mut()
+    {
+        let planar = (velocity.0 - ground_state.ground_velocity).with_y(0.0);
+        let has_headroom = || {
+            // Room for the standing capsule: what is above the crouched one, up to the
+            // height it grows by.
+            let filter = SpatialQueryFilter::default().with_excluded_entities([entity]);
+            let rise = if ground_state.is_grounded {
+                2.0 * drop
+            } else {
+                drop
+            };
+            spatial_query
+                .cast_shape(
+                    collider,
+                    position.0,
+                    rotation.0,
+                    Dir3::Y,
+                    &ShapeCastConfig::from_max_distance(rise),
+                    &filter,
+                )
+                .is_none()
+        };
+        let next = next_crouch_state(
+            &crouch,
+            CrouchInput::from_action_state(action_state),
+            ground_state.is_grounded,
+            planar.length(),
+            has_headroom,
+            dt,
+        );
+
+        if next.crouched != crouch.crouched && ground_state.is_grounded {
+            position.0.y += if next.crouched { -drop } else { drop };
+        }
+        if next.is_sliding() && !crouch.is_sliding() {
+            velocity.0 += planar.normalize_or_zero() * SLIDE_BOOST;
+        }
+        crouch.set_if_neq(next);
+    }
+}
+
+/// System: give every character the collider of its crouch state, the local player and
+/// interpolated remote ones alike.
+pub fn fit_crouch_collider(mut query: Query<(&CrouchState, &mut Collider)>) {
+    let crouched_extent = capsule_half_extent(&player_collider(true)).unwrap_or_default();
+
+    for (crouch, mut collider) in query.iter_mut() {
+        let is_crouched_shape = capsule_half_extent(&collider)
+            .is_some_and(|extent| (extent - crouched_extent).abs() < 1e-3);
+        if is_crouched_shape != crouch.crouched {
+            *collider = player_collider(crouch.crouched);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CrouchInput, CrouchState, SLIDE_MIN_SPEED, SLIDE_SECS, next_crouch_state};
+
+    const DT: f32 = 1.0 / 60.0;
+
+    #[test]
+    fn crouch_holds_or_toggles_and_only_stands_with_headroom() {
+        let standing = CrouchState::default();
+        let held = CrouchInput {
+            held: true,
+            ..Default::default()
+        };
+        let crouched = next_crouch_state(&standing, held, true, 0.0, || true, DT);
+        assert!(crouched.crouched && !crouched.toggled);
+
+        // Letting go under a low ceiling stays crouched until there is room.
+        let released = CrouchInput::default();
+        let blocked = next_crouch_state(&crouched, released, true, 0.0, || false, DT);
+        assert!(blocked.crouched);
+        let stood = next_crouch_state(&blocked, released, true, 0.0, || true, DT);
+        assert!(!stood.crouched);
+
+        let toggle = CrouchInput {
+            toggle_pressed: true,
+            ..Default::default()
+        };
+        let toggled = next_crouch_state(&standing, toggle, true, 0.0, || true, DT);
+        assert!(toggled.crouched && toggled.toggled);
+        let still = next_crouch_state(&toggled, released, true, 0.0, || true, DT);
+        assert!(still.crouched, "A toggled crouch holds without input");
+        let untoggled = next_crouch_state(&still, toggle, true, 0.0, || true, DT);
+        assert!(!untoggled.crouched && !untoggled.toggled);
+    }
+
+    #[test]
+    fn crouching_during_a_grounded_sprint_slides_until_slowed_down() {
+        let sprint_crouch = CrouchInput {
+            held: true,
+            sprinting: true,
+            ..Default::default()
+        };
+        let standing = CrouchState::default();
+        let fast = SLIDE_MIN_SPEED + 1.0;
+
+        let walking = CrouchInput {
+            sprinting: false,
+            ..sprint_crouch
+        };
+        assert!(!next_crouch_state(&standing, walking, true, fast, || true, DT).is_sliding());
+        assert!(
+            !next_crouch_state(&standing, sprint_crouch, false, fast, || true, DT).is_sliding()
+        );
+        assert!(
+            !next_crouch_state(
+                &standing,
+                sprint_crouch,
+                true,
+                SLIDE_MIN_SPEED * 0.5,
+                || true,
+                DT
+            )
+            .is_sliding()
+        );
+
+        let sliding = next_crouch_state(&standing, sprint_crouch, true, fast, || true, DT);
+        assert!(sliding.crouched);
+        assert_eq!(sliding.slide_secs, SLIDE_SECS);
+
+        let going = next_crouch_state(&sliding, sprint_crouch, true, fast, || true, DT);
+        assert!(going.is_sliding() && going.slide_secs < SLIDE_SECS);
+        let slowed = next_crouch_state(
+            &going,
+            sprint_crouch,
+            true,
+            SLIDE_MIN_SPEED * 0.5,
+            || true,
+            DT,
+        );
+        assert!(!slowed.is_sliding() && slowed.crouched);
+        let airborne = next_crouch_state(&going, sprint_crouch, false, fast, || true, DT);
+        assert!(!airborne.is_sliding());
+    }
+}
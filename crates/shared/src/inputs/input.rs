@@ -32,6 +32,24 @@ pub enum PlayerAction {
 
     #[actionlike(Button)]
     ToggleFlashlight,
+
+    #[actionlike(Button)]
+    Throw,
+
+    #[actionlike(Button)]
+    SwitchWeapon,
+
+    /// Open or close a door, press a button.
+    #[actionlike(Button)]
+    Use,
+
+    /// Crouch while held.
+    #[actionlike(Button)]
+    Crouch,
+
+    /// Crouch until pressed again.
+    #[actionlike(Button)]
+    ToggleCrouch,
 }
 
 pub const PLAYER_CAPSULE_RADIUS: f32 = 0.5;
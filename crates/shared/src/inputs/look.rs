@@ -1,13 +1,71 @@
 use avian3d::prelude::Rotation;
-use bevy::prelude::{EulerRot, Quat, Query, Vec2, With};
+use bevy::prelude::{Component, EulerRot, Quat, Query, Vec2, With};
 use leafwing_input_manager::prelude::ActionState;
+use serde::{Deserialize, Serialize};
 
 use crate::{
+    game_math::shortest_angle_delta,
     inputs::input::{PITCH_LIMIT_RADIANS, PlayerAction},
     protocol::{CharacterMarker, PlayerId},
 };
 const LOOK_DEADZONE_SQUARED: f32 = 0.000001;
 pub const MOUSE_SENSIVITY: f32 = 0.0007;
+/// How far ahead remote look is extrapolated, roughly one replication interval.
+pub const LOOK_EXTRAPOLATION_HORIZON_SECS: f32 = 0.1;
+/// Largest visual look offset allowed, so a stale velocity can never spin a remote model.
+pub const MAX_LOOK_EXTRAPOLATION_RADIANS: f32 = 0.35;
+/// Rate at which the visual offset converges to its target. Easing instead of jumping is
+/// what hides the snap-back when a remote player stops turning.
+pub const LOOK_EXTRAPOLATION_SMOOTHING: f32 = 12.0;
+
+/// Server-measured yaw/pitch angular velocity (rad/s), replicated so clients can
+/// extrapolate the aim of remote players between updates.
+#[derive(Component, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct LookVelocity {
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+impl LookVelocity {
+    /// Angular velocity between two rotations taken `delta_secs` apart. Yaw takes the
+    /// shortest way around so crossing ±π does not read as a full turn.
+    pub fn between(previous: Quat, current: Quat, delta_secs: f32) -> Self {
+        if delta_secs <= 0.0 {
+            return Self::default();
+        }
+
+        let (previous_yaw, previous_pitch, _) = previous.to_euler(EulerRot::YXZ);
+        let (current_yaw, current_pitch, _) = current.to_euler(EulerRot::YXZ);
+        let yaw_delta = shortest_angle_delta(previous_yaw, current_yaw);
+
+        Self {
+            yaw: yaw_delta / delta_secs,
+            pitch: (current_pitch - previous_pitch) / delta_secs,
+        }
+    }
+
+    /// Target yaw/pitch offset for a visual extrapolated `horizon_secs` ahead, clamped to
+    /// `MAX_LOOK_EXTRAPOLATION_RADIANS` on each axis.
+    pub fn extrapolated_offset(&self, horizon_secs: f32) -> Vec2 {
+        (Vec2::new(self.yaw, self.pitch) * horizon_secs).clamp(
+            Vec2::splat(-MAX_LOOK_EXTRAPOLATION_RADIANS),
+            Vec2::splat(MAX_LOOK_EXTRAPOLATION_RADIANS),
+        )
+    }
+}
+
+/// Move `current` toward `target` with exponential smoothing over `delta_secs`.
+pub fn smooth_look_offset(current: Vec2, target: Vec2, delta_secs: f32) -> Vec2 {
+    let blend = 1.0 - (-LOOK_EXTRAPOLATION_SMOOTHING * delta_secs).exp();
+    current.lerp(target, blend)
+}
+
+/// Apply a yaw/pitch offset on top of `rotation`, keeping pitch within the look limits.
+pub fn apply_look_offset(rotation: Quat, offset: Vec2) -> Quat {
+    let (yaw, pitch, _) = rotation.to_euler(EulerRot::YXZ);
+    let pitch = (pitch + offset.y).clamp(-PITCH_LIMIT_RADIANS, PITCH_LIMIT_RADIANS);
+    Quat::from_euler(EulerRot::YXZ, yaw + offset.x, pitch, 0.0)
+}
 
 pub fn get_mouse_look_delta(action_state: &ActionState<PlayerAction>) -> Vec2 {
     let look_input = action_state.axis_pair(&PlayerAction::Look);
@@ -48,7 +106,10 @@ pub fn update_player_rotation_from_input(
 
 #[cfg(test)]
 mod tests {
-    use super::{apply_look_delta, get_mouse_look_delta};
+    use super::{
+        LookVelocity, MAX_LOOK_EXTRAPOLATION_RADIANS, apply_look_delta, get_mouse_look_delta,
+        smooth_look_offset,
+    };
     use crate::inputs::input::{PITCH_LIMIT_RADIANS, PlayerAction};
     use crate::protocol::{CharacterMarker, PlayerId};
     use avian3d::prelude::Rotation;
@@ -116,6 +177,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn look_velocity_wraps_yaw_across_pi() {
+        let previous = bevy::prelude::Quat::from_rotation_y(std::f32::consts::PI - 0.05);
+        let current = bevy::prelude::Quat::from_rotation_y(-std::f32::consts::PI + 0.05);
+
+        let velocity = LookVelocity::between(previous, current, 0.1);
+        assert!(
+            (velocity.yaw - 1.0).abs() < 0.001,
+            "Crossing ±π should be a small positive turn, got {}",
+            velocity.yaw
+        );
+    }
+
+    #[test]
+    fn extrapolated_offset_is_clamped() {
+        let velocity = LookVelocity {
+            yaw: 100.0,
+            pitch: -100.0,
+        };
+        let offset = velocity.extrapolated_offset(0.1);
+
+        assert_eq!(offset.x, MAX_LOOK_EXTRAPOLATION_RADIANS);
+        assert_eq!(offset.y, -MAX_LOOK_EXTRAPOLATION_RADIANS);
+    }
+
+    #[test]
+    fn look_offset_eases_back_instead_of_snapping() {
+        let offset = smooth_look_offset(Vec2::new(0.3, 0.0), Vec2::ZERO, 1.0 / 60.0);
+
+        assert!(offset.x > 0.0 && offset.x < 0.3);
+    }
+
     #[test]
     fn look_updates_server_style_entity_without_predicted_controlled_markers() {
         let mut app = App::new();
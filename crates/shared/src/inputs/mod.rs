@@ -1,22 +1,42 @@
 use bevy::prelude::{FixedUpdate, IntoScheduleConfigs, Plugin, Update};
 
+use crate::clock::ensure_game_clock;
+use crate::components::stamina::{Stamina, StaminaConfig};
 use crate::inputs::{
+    climbing::apply_climbing,
+    crouch::{fit_crouch_collider, update_crouch},
     look::update_player_rotation_from_input,
     movement::{apply_movement, update_ground_detection},
+    substeps::sweep_fast_characters,
 };
 
+pub mod climbing;
+pub mod crouch;
 pub mod input;
 pub mod look;
 pub mod movement;
+pub mod substeps;
 
 pub struct SharedInputPlugin;
 
 impl Plugin for SharedInputPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
+        ensure_game_clock(app);
+        app.register_type::<Stamina>();
+        app.register_type::<StaminaConfig>();
+
         // Movement systems (FixedUpdate for physics)
         app.add_systems(
             FixedUpdate,
-            (update_ground_detection, apply_movement).chain(),
+            (
+                update_ground_detection,
+                update_crouch,
+                fit_crouch_collider,
+                apply_movement,
+                apply_climbing,
+                sweep_fast_characters,
+            )
+                .chain(),
         );
 
         app.add_systems(Update, update_player_rotation_from_input);
@@ -3,7 +3,12 @@ use bevy::prelude::*;
 use leafwing_input_manager::prelude::ActionState;
 use serde::{Deserialize, Serialize};
 
+use crate::clock::GameClock;
+use crate::components::stamina::{Stamina, StaminaConfig};
+use crate::game_math::{calculate_acceleration, clamp_planar_speed, friction_speed_scale, yaw_of};
+use crate::inputs::crouch::{CROUCH_SPEED, CrouchState, SLIDE_FRICTION};
 use crate::inputs::input::PlayerAction;
+use crate::level::platforms::MovingPlatform;
 
 pub const WALK_SPEED: f32 = 20.0;
 pub const RUN_SPEED: f32 = 40.0;
@@ -26,6 +31,8 @@ pub struct GroundState {
     pub ground_normal: Vec3,
     pub ground_distance: f32,
     pub ground_tick: u8,
+    /// Velocity of the ground itself, non-zero on moving platforms.
+    pub ground_velocity: Vec3,
 }
 
 pub fn detect_ground(
@@ -34,6 +41,7 @@ pub fn detect_ground(
     position: Vec3,
     rotation: Quat,
     spatial_query: &SpatialQueryPipeline,
+    ground_velocity: impl Fn(Entity) -> Option<Vec3>,
 ) -> GroundState {
     let filter = SpatialQueryFilter::default().with_excluded_entities([entity]);
     let detection_distance = GROUNDED_DISTANCE.max(2.0);
@@ -54,39 +62,24 @@ pub fn detect_ground(
             ground_normal: hit.normal1,
             ground_distance: hit.distance,
             ground_tick: 0, // Will be updated by caller
+            ground_velocity: ground_velocity(hit.entity).unwrap_or(Vec3::ZERO),
         }
     } else {
         GroundState::default()
     }
 }
 
-/// Calculate acceleration for desired movement direction
-pub fn calculate_acceleration(
-    wish_direction: Vec3,
-    wish_speed: f32,
-    acceleration: f32,
-    current_velocity: Vec3,
-    dt: f32,
-) -> Vec3 {
-    let velocity_projection = Vec3::dot(current_velocity, wish_direction);
-    let add_speed = wish_speed - velocity_projection;
-
-    if add_speed <= 0.0 {
-        return Vec3::ZERO;
-    }
-
-    let acceleration_speed = (acceleration * wish_speed * dt).min(add_speed);
-    wish_direction * acceleration_speed
-}
-
 /// Apply friction to ground movement
 pub fn apply_ground_friction(velocity: &mut LinearVelocity, dt: f32) {
+    apply_friction(velocity, FRICTION, dt);
+}
+
+/// Slow lateral movement down by `friction`.
+pub fn apply_friction(velocity: &mut LinearVelocity, friction: f32, dt: f32) {
     let lateral_speed = velocity.0.xz().length();
 
     if lateral_speed > FRICTION_SPEED_CUTOFF {
-        let control = lateral_speed.max(STOP_SPEED);
-        let drop = control * FRICTION * dt;
-        let new_speed = ((lateral_speed - drop) / lateral_speed).max(0.0);
+        let new_speed = friction_speed_scale(lateral_speed, friction, STOP_SPEED, dt);
         velocity.0.x *= new_speed;
         velocity.0.z *= new_speed;
     } else {
@@ -135,13 +128,22 @@ pub fn get_wish_direction(
 pub fn update_ground_detection(
     spatial_query: Res<SpatialQueryPipeline>,
     mut query: Query<(Entity, &Position, &Rotation, &Collider, &mut GroundState)>,
+    platforms: Query<&LinearVelocity, With<MovingPlatform>>,
 ) {
     for (entity, position, rotation, collider, mut ground_state) in query.iter_mut() {
-        let detected = detect_ground(entity, collider, position.0, rotation.0, &spatial_query);
+        let detected = detect_ground(
+            entity,
+            collider,
+            position.0,
+            rotation.0,
+            &spatial_query,
+            |ground| platforms.get(ground).ok().map(|velocity| velocity.0),
+        );
 
         ground_state.is_grounded = detected.is_grounded;
         ground_state.ground_normal = detected.ground_normal;
         ground_state.ground_distance = detected.ground_distance;
+        ground_state.ground_velocity = detected.ground_velocity;
 
         if detected.is_grounded {
             ground_state.ground_tick = ground_state.ground_tick.saturating_add(1);
@@ -153,24 +155,30 @@ pub fn update_ground_detection(
 
 /// System: Apply movement based on input and ground state
 pub fn apply_movement(
-    time: Res<Time>,
+    clock: Res<GameClock>,
     mut query: Query<(
         &ActionState<PlayerAction>,
         &GroundState,
         &Rotation,
         &mut LinearVelocity,
+        Option<&mut Stamina>,
+        Option<&StaminaConfig>,
+        Option<&CrouchState>,
     )>,
 ) {
-    let dt = time.delta_secs();
+    let dt = clock.delta_secs();
+    let default_stamina = StaminaConfig::default();
 
-    for (action_state, ground_state, rotation, mut velocity) in query.iter_mut() {
+    for (action_state, ground_state, rotation, mut velocity, stamina, stamina_config, crouch) in
+        query.iter_mut()
+    {
         // Get input
         let move_input = if action_state.disabled() {
             Vec2::ZERO
         } else {
             action_state.axis_pair(&PlayerAction::Move)
         };
-        let (yaw, _, _) = rotation.0.to_euler(EulerRot::YXZ);
+        let yaw = yaw_of(rotation.0);
 
         // DEBUG: Log when movement is applied
         if move_input.length() > 0.1 {
@@ -182,29 +190,62 @@ pub fn apply_movement(
                 velocity.0
             );
         }
-        let is_sprinting = !action_state.disabled() && action_state.pressed(&PlayerAction::Sprint);
+        let crouch = crouch.cloned().unwrap_or_default();
+        // Crouched characters cannot sprint.
+        let sprint_pressed = !action_state.disabled()
+            && action_state.pressed(&PlayerAction::Sprint)
+            && !crouch.crouched;
+        // Only sprinting on the ground costs stamina; a full bar is left untouched so it
+        // does not register as changed every tick. Exhausted characters cannot sprint until
+        // it recovers.
+        let wants_sprint = sprint_pressed && ground_state.is_grounded && move_input.length() > 0.1;
+        let is_sprinting = match stamina {
+            Some(mut stamina) if wants_sprint || !stamina.is_full() => {
+                stamina.sprint(stamina_config.unwrap_or(&default_stamina), wants_sprint, dt)
+            }
+            Some(_) => false,
+            None => sprint_pressed,
+        };
         let is_jumping = !action_state.disabled() && action_state.pressed(&PlayerAction::Jump);
 
         // Calculate wish direction using camera yaw for camera-relative movement
         let (wish_direction, mut wish_speed) = get_wish_direction(move_input, yaw, 100.0, 60.0);
 
         // Apply speed limits
-        let max_speed = if is_sprinting { RUN_SPEED } else { WALK_SPEED };
+        let max_speed = if crouch.crouched {
+            CROUCH_SPEED
+        } else if is_sprinting {
+            RUN_SPEED
+        } else {
+            WALK_SPEED
+        };
         wish_speed = wish_speed.min(max_speed);
 
-        // Ground movement
+        // Ground movement, relative to the ground so characters ride moving platforms
         if ground_state.is_grounded {
-            apply_ground_friction(&mut velocity, dt);
-
-            let add =
-                calculate_acceleration(wish_direction, wish_speed, ACCELERATION, velocity.0, dt);
-            velocity.0 += add;
+            velocity.0 -= ground_state.ground_velocity;
+            if crouch.is_sliding() {
+                // Slides carry their momentum and do not steer.
+                apply_friction(&mut velocity, SLIDE_FRICTION, dt);
+            } else {
+                apply_ground_friction(&mut velocity, dt);
+
+                let add = calculate_acceleration(
+                    wish_direction,
+                    wish_speed,
+                    ACCELERATION,
+                    velocity.0,
+                    dt,
+                );
+                velocity.0 += add;
+            }
 
             remove_ground_penetration(&mut velocity, ground_state.ground_normal);
 
             if is_jumping {
                 velocity.0.y = JUMP_SPEED;
             }
+            velocity.0 += ground_state.ground_velocity;
         } else {
             // Air movement
             wish_speed = wish_speed.min(AIR_SPEED_CAP);
@@ -219,12 +260,7 @@ pub fn apply_movement(
             add.y = -GRAVITY * dt;
             velocity.0 += add;
 
-            let air_speed = velocity.0.xz().length();
-            if air_speed > MAX_AIR_SPEED {
-                let ratio = MAX_AIR_SPEED / air_speed;
-                velocity.0.x *= ratio;
-                velocity.0.z *= ratio;
-            }
+            velocity.0 = clamp_planar_speed(velocity.0, MAX_AIR_SPEED);
         }
 
         clamp_max_velocity(&mut velocity, 50.0);
@@ -237,6 +273,7 @@ mod tests {
         GroundState, LinearVelocity, apply_ground_friction, calculate_acceleration,
         clamp_max_velocity, get_wish_direction,
     };
+    use crate::clock::GameClockPlugin;
     use crate::inputs::input::PlayerAction;
     use crate::inputs::look::update_player_rotation_from_input;
     use crate::protocol::{CharacterMarker, PlayerId};
@@ -287,6 +324,43 @@ mod tests {
         assert!((velocity.0.length() - 10.0).abs() < 0.001);
     }
 
+    #[test]
+    fn grounded_characters_ride_moving_platforms() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, GameClockPlugin));
+        app.add_systems(FixedUpdate, super::apply_movement);
+
+        let platform_velocity = Vec3::new(0.0, 1.0, 2.0);
+        let mut action_state = ActionState::<PlayerAction>::default();
+        action_state.enable();
+        let player = app
+            .world_mut()
+            .spawn((
+                action_state,
+                GroundState {
+                    is_grounded: true,
+                    ground_normal: Vec3::Y,
+                    ground_distance: 0.0,
+                    ground_tick: 1,
+                    ground_velocity: platform_velocity,
+                },
+                LinearVelocity(platform_velocity),
+                Rotation::default(),
+            ))
+            .id();
+
+        for _ in 0..10 {
+            step(&mut app, std::time::Duration::from_millis(16));
+        }
+
+        let velocity = app.world().get::<LinearVelocity>(player).unwrap().0;
+        assert!(
+            velocity.distance(platform_velocity) < 1e-4,
+            "Standing still on a platform should keep its velocity, got {:?}",
+            velocity
+        );
+    }
+
     #[test]
     fn wish_direction_uses_yaw_rotation() {
         let (dir, speed) = get_wish_direction(
@@ -306,7 +380,7 @@ mod tests {
     #[test]
     fn keyboard_forward_then_mouse_turn_then_forward_changes_path() {
         let mut app = App::new();
-        app.add_plugins(MinimalPlugins);
+        app.add_plugins((MinimalPlugins, GameClockPlugin));
         app.add_systems(Update, update_player_rotation_from_input);
         app.add_systems(
             FixedUpdate,
@@ -330,6 +404,7 @@ mod tests {
                     ground_normal: Vec3::Y,
                     ground_distance: 0.0,
                     ground_tick: 1,
+                    ground_velocity: Vec3::ZERO,
                 },
                 LinearVelocity(Vec3::ZERO),
                 Position::new(Vec3::ZERO),
@@ -0,0 +1,124 @@
+//! Substepped sweeps for fast characters. Dashes, knockback and jump pads can carry a
+//! character further in one 60 Hz tick than its collider is thick, and a single physics
+//! step then lets it through thin walls or into the floor. Characters moving further than
+//! [`MAX_SUBSTEP_TRAVEL`] in a tick have that motion swept against the level in substeps,
+//! and their speed into anything in the way is cut so they reach the surface instead of
+//! crossing it; avian resolves the contact on the next step as usual.
+//!
+//! The substep count only depends on the character's own velocity and the tick length,
+//! and the sweeps only see the level, so the server and predicting clients compute the
+//! same thing and rollbacks replay it exactly. The global tick rate and avian's substep
+//! count are left alone.
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+use crate::clock::GameClock;
+use crate::inputs::input::PLAYER_CAPSULE_RADIUS;
+use crate::inputs::movement::GroundState;
+
+/// Furthest a character moves in one substep. Up to this, the physics step alone keeps it
+/// out of walls.
+pub const MAX_SUBSTEP_TRAVEL: f32 = PLAYER_CAPSULE_RADIUS;
+pub const MAX_SUBSTEPS: u32 = 8;
+/// Gap left between a swept character and the surface it is stopped at.
+const SKIN: f32 = 0.01;
+
+/// Substeps needed to move at `velocity` for `dt` seconds; 1 when a plain physics step is
+/// enough.
+pub fn substeps_for(velocity: Vec3, dt: f32) -> u32 {
+    let travel = velocity.length() * dt;
+    ((travel / MAX_SUBSTEP_TRAVEL).ceil() as u32).clamp(1, MAX_SUBSTEPS)
+}
+
+/// `velocity` with its speed into a surface of normal `normal` cut so that, over the
+/// `remaining` seconds of the tick, it closes no more than `gap`. Motion along or away
+/// from the surface is kept.
+pub fn limit_approach(velocity: Vec3, normal: Vec3, gap: f32, remaining: f32) -> Vec3 {
+    let approach = -velocity.dot(normal);
+    if approach <= 0.0 || remaining <= 0.0 {
+        return velocity;
+    }
+    let allowed = (gap.max(0.0) / remaining).min(approach);
+    velocity + normal * (approach - allowed)
+}
+
+/// System: sweep the characters too fast for one physics step and cut their velocity
+/// before what they would hit.
+pub fn sweep_fast_characters(
+    clock: Res<GameClock>,
+    spatial_query: Res<SpatialQueryPipeline>,
+    mut characters: Query<
+        (Entity, &Collider, &Position, &Rotation, &mut LinearVelocity),
+        With<GroundState>,
+    >,
+) {
+    let dt = clock.delta_secs();
+    // Other characters are interpolated on clients, so only the level is swept against.
+    let filter = SpatialQueryFilter::default()
+        .with_excluded_entities(characters.iter().map(|(entity, ..)| entity));
+
+    for (_, collider, position, rotation, mut velocity) in characters.iter_mut() {
+        let substeps = substeps_for(velocity.0, dt);
+        if substeps == 1 {
+            continue;
+        }
+
+        let step = dt / substeps as f32;
+        let mut origin = position.0;
+        let mut swept = velocity.0;
+        for substep in 0..substeps {
+            let Ok((direction, speed)) = Dir3::new_and_length(swept) else {
+                break;
+            };
+            if let Some(hit) = spatial_query.cast_shape(
+                collider,
+                origin,
+                rotation.0,
+                direction,
+                &ShapeCastConfig::from_max_distance(speed * step + SKIN),
+                &filter,
+            ) {
+                let remaining = dt - step * substep as f32;
+                swept = limit_approach(swept, hit.normal1, hit.distance - SKIN, remaining);
+            }
+            origin += swept * step;
+        }
+
+        if swept != velocity.0 {
+            velocity.0 = swept;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MAX_SUBSTEPS, limit_approach, substeps_for};
+    use crate::inputs::movement::{RUN_SPEED, WALK_SPEED};
+    use bevy::prelude::Vec3;
+
+    const TICK: f32 = 1.0 / 60.0;
+
+    #[test]
+    fn only_fast_characters_are_substepped() {
+        assert_eq!(substeps_for(Vec3::ZERO, TICK), 1);
+        assert_eq!(substeps_for(Vec3::X * WALK_SPEED, TICK), 1);
+        assert_eq!(substeps_for(Vec3::X * RUN_SPEED, TICK), 2);
+        assert_eq!(substeps_for(Vec3::NEG_Y * 1000.0, TICK), MAX_SUBSTEPS);
+    }
+
+    #[test]
+    fn approach_is_cut_to_reach_the_surface_and_sliding_is_kept() {
+        // Dashing at a wall 0.2 m away, with some sideways speed.
+        let velocity = Vec3::new(5.0, 0.0, -50.0);
+        let limited = limit_approach(velocity, Vec3::Z, 0.2, TICK);
+        assert!((limited.z * TICK + 0.2).abs() < 1e-4);
+        assert_eq!(limited.x, 5.0);
+
+        // Already far enough, or moving away: untouched.
+        assert_eq!(limit_approach(velocity, Vec3::Z, 10.0, TICK), velocity);
+        assert_eq!(limit_approach(-velocity, Vec3::Z, 0.0, TICK), -velocity);
+        // Touching the surface: no speed into it at all.
+        assert_eq!(limit_approach(velocity, Vec3::Z, -0.01, TICK).z, 0.0);
+    }
+}
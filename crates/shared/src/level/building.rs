@@ -1,15 +1,15 @@
-use avian3d::prelude::{LinearVelocity, Position, Rotation};
-use bevy::prelude::{
-	Color, Commands, Component, Name, PointLight, Quat, Vec2, Vec3, default, info,
-};
-use lightyear::prelude::{InterpolationTarget, NetworkTarget, Replicate};
-use vleue_navigator::prelude::{ManagedNavMesh, NavMeshSettings, NavMeshUpdateMode, Triangulation};
-
-use crate::components::health::{Health, Respawnable};
-use crate::entities::NpcPhysicsBundle;
-use crate::level::generation::{LevelGraph, Zone, ZoneType};
-use crate::navigation::setup_patrol;
-use crate::protocol::CharacterMarker;
+use bevy::prelude::{Color, Commands, Component, Name, PointLight, Vec3, default, info};
+use vleue_navigator::prelude::{ManagedNavMesh, NavMeshSettings, NavMeshUpdateMode};
+
+use crate::bots::{MatchBotSettings, spawn_classic_ai_bot};
+use crate::components::attachments::Attachment;
+use crate::components::destructible::spawn_destructible_crate;
+use crate::components::pickup::{PickupKind, spawn_pickup};
+use crate::level::generation::{LevelGeometry, LevelGraph, Zone, ZoneType};
+use crate::level::platforms::spawn_moving_platform;
+use crate::level::transition::spawn_level_exit;
+use crate::navigation::NavigationLink;
+use crate::navigation_pathfinding::{level_bounds, level_triangulation};
 
 #[derive(Component, Debug)]
 pub struct ProceduralNavMeshMarker;
@@ -20,37 +20,27 @@ pub struct ProceduralEnemyMarker;
 #[derive(Component, Debug)]
 pub struct ProceduralConnectionLightMarker;
 
-pub fn setup_procedural_navmesh(commands: &mut Commands, level_graph: &LevelGraph) {
-	let mut min_x = f32::INFINITY;
-	let mut max_x = f32::NEG_INFINITY;
-	let mut min_z = f32::INFINITY;
-	let mut max_z = f32::NEG_INFINITY;
-
-	for zone in level_graph.zones.values() {
-		let half_x = zone.size.x * 0.5;
-		let half_z = zone.size.z * 0.5;
-		min_x = min_x.min(zone.position.x - half_x);
-		max_x = max_x.max(zone.position.x + half_x);
-		min_z = min_z.min(zone.position.z - half_z);
-		max_z = max_z.max(zone.position.z + half_z);
-	}
+#[derive(Component, Debug)]
+pub struct ProceduralPropMarker;
 
-	if !min_x.is_finite() || !max_x.is_finite() || !min_z.is_finite() || !max_z.is_finite() {
-		return;
-	}
+#[derive(Component, Debug)]
+pub struct ProceduralPickupMarker;
 
-	let margin = 2.0;
-	let edges = [
-		Vec2::new(min_x + margin, min_z + margin),
-		Vec2::new(max_x - margin, min_z + margin),
-		Vec2::new(max_x - margin, max_z - margin),
-		Vec2::new(min_x + margin, max_z - margin),
-	];
+#[derive(Component, Debug)]
+pub struct ProceduralPlatformMarker;
+
+pub fn setup_procedural_navmesh(commands: &mut Commands, level_graph: &LevelGraph) {
+	let Some(triangulation) = level_triangulation(level_graph) else {
+		return;
+	};
+	let Some((min, max)) = level_bounds(level_graph) else {
+		return;
+	};
 
 	commands.spawn((
 		ManagedNavMesh::single(),
 		NavMeshSettings {
-			fixed: Triangulation::from_outer_edges(&edges),
+			fixed: triangulation,
 			simplify: 0.1,
 			merge_steps: 2,
 			build_timeout: Some(20.0),
@@ -59,15 +49,16 @@ pub fn setup_procedural_navmesh(commands: &mut Commands, level_graph: &LevelGrap
 		},
 		NavMeshUpdateMode::Direct,
 		ProceduralNavMeshMarker,
+		LevelGeometry,
 		Name::new("ProceduralNavMesh"),
 	));
 
 	info!(
-		"🗺️ Procedural navmesh built with bounds x:[{:.1}, {:.1}] z:[{:.1}, {:.1}]",
-		min_x,
-		max_x,
-		min_z,
-		max_z
+		"🗺️ Procedural navmesh baking with bounds x:[{:.1}, {:.1}] z:[{:.1}, {:.1}]",
+		min.x,
+		max.x,
+		min.y,
+		max.y
 	);
 }
 
@@ -86,6 +77,7 @@ pub fn spawn_procedural_connection_lights(commands: &mut Commands, level_graph:
 				connection.door_position + Vec3::new(0.0, 2.5, 0.0),
 			),
 			ProceduralConnectionLightMarker,
+			LevelGeometry,
 			Name::new(format!("ProceduralDoorLight_{}", index)),
 		));
 	}
@@ -96,7 +88,8 @@ pub fn spawn_procedural_connection_lights(commands: &mut Commands, level_graph:
 	);
 }
 
-fn patrol_points_for_zone(zone: &Zone) -> Vec<Vec3> {
+/// Patrol the corners of the zone, and its elevator ledge if it has one.
+fn patrol_points_for_zone(zone: &Zone, level_graph: &LevelGraph) -> Vec<Vec3> {
 	let half_x = (zone.size.x * 0.30).min(12.0);
 	let half_z = (zone.size.z * 0.30).min(12.0);
 	let offsets = [
@@ -109,6 +102,13 @@ fn patrol_points_for_zone(zone: &Zone) -> Vec<Vec3> {
 	offsets
 		.iter()
 		.map(|offset| zone.position + zone.rotation * *offset)
+		.chain(
+			level_graph
+				.elevators
+				.iter()
+				.filter(|elevator| elevator.zone == zone.id)
+				.map(|elevator| elevator.upper_stop),
+		)
 		.collect()
 }
 
@@ -123,7 +123,11 @@ fn enemy_speed_for_zone(zone_type: ZoneType) -> f32 {
 	}
 }
 
-pub fn spawn_procedural_enemies(commands: &mut Commands, level_graph: &LevelGraph) {
+pub fn spawn_procedural_enemies(
+	commands: &mut Commands,
+	level_graph: &LevelGraph,
+	bot_settings: &MatchBotSettings,
+) {
 	let mut candidate_zones: Vec<&Zone> = level_graph
 		.zones
 		.values()
@@ -132,46 +136,142 @@ pub fn spawn_procedural_enemies(commands: &mut Commands, level_graph: &LevelGrap
 
 	candidate_zones.sort_by_key(|zone| zone.id.0);
 
-	let max_npcs = candidate_zones.len().clamp(2, 6);
+	let max_npcs = bot_settings.bot_count.min(candidate_zones.len());
 	let selected = candidate_zones.into_iter().take(max_npcs);
 
 	let mut spawned = 0usize;
 	for zone in selected {
 		let spawn_position = zone.position + Vec3::new(0.0, 1.0, 0.0);
-		let patrol_points = patrol_points_for_zone(zone);
-
-		let enemy_entity = commands
-			.spawn((
-				Name::new(format!("ProceduralEnemy_{}", zone.id.0)),
-				Position::new(spawn_position),
-				Rotation::from(Quat::IDENTITY),
-				LinearVelocity::default(),
-				Health::basic(),
-				Respawnable::with_position(4.0, spawn_position),
-				Replicate::to_clients(NetworkTarget::All),
-				InterpolationTarget::to_clients(NetworkTarget::All),
-				CharacterMarker,
-				ProceduralEnemyMarker,
-				NpcPhysicsBundle::default(),
-			))
-			.id();
-
-		setup_patrol(
+
+		let enemy_entity =
+			spawn_classic_ai_bot(format!("ProceduralEnemy_{}", zone.id.0), spawn_position)
+				.difficulty(bot_settings.difficulty)
+				.patrol(patrol_points_for_zone(zone, level_graph))
+				.speed(enemy_speed_for_zone(zone.zone_type))
+				.respawn_delay(4.0)
+				.spawn(commands);
+		commands
+			.entity(enemy_entity)
+			.insert((ProceduralEnemyMarker, LevelGeometry));
+		spawned += 1;
+	}
+
+	info!(
+		"🤖 Spawned {} procedural patrolling enemies ({})",
+		spawned,
+		bot_settings.difficulty.label()
+	);
+}
+
+/// Destructible crates along the walls of storage and industrial zones.
+pub fn spawn_procedural_props(commands: &mut Commands, level_graph: &LevelGraph) {
+	let mut zones: Vec<&Zone> = level_graph
+		.zones
+		.values()
+		.filter(|zone| matches!(zone.zone_type, ZoneType::Storage | ZoneType::Industrial))
+		.collect();
+	zones.sort_by_key(|zone| zone.id.0);
+
+	let mut spawned = 0usize;
+	for zone in zones {
+		let half_x = zone.size.x * 0.35;
+		let half_z = zone.size.z * 0.35;
+		let offsets = [
+			Vec3::new(-half_x, 0.0, -half_z),
+			Vec3::new(-half_x + 1.4, 0.0, -half_z),
+			Vec3::new(half_x, 0.0, half_z),
+		];
+
+		for (index, offset) in offsets.iter().enumerate() {
+			let prop = spawn_destructible_crate(
+				commands,
+				format!("ProceduralCrate_{}_{}", zone.id.0, index),
+				zone.position + zone.rotation * *offset,
+			);
+			commands
+				.entity(prop)
+				.insert((ProceduralPropMarker, LevelGeometry));
+			spawned += 1;
+		}
+	}
+
+	info!("📦 Spawned {} destructible props", spawned);
+}
+
+/// Elevators are server-simulated and replicated; their ledges are built with the level
+/// physics on both sides.
+pub fn spawn_procedural_platforms(commands: &mut Commands, level_graph: &LevelGraph) {
+	for elevator in &level_graph.elevators {
+		let platform = spawn_moving_platform(
 			commands,
-			enemy_entity,
-			patrol_points,
-			enemy_speed_for_zone(zone.zone_type),
+			format!("ProceduralElevator_{}", elevator.zone.0),
+			elevator.path,
 		);
-		spawned += 1;
+		commands.entity(platform).insert((
+			NavigationLink {
+				lower: elevator.lower_stop,
+				upper: elevator.upper_stop,
+			},
+			ProceduralPlatformMarker,
+			LevelGeometry,
+		));
 	}
 
-	info!("🤖 Spawned {} procedural patrolling enemies", spawned);
+	info!("🛗 Spawned {} elevators", level_graph.elevators.len());
 }
 
-pub fn build_procedural_runtime_content(commands: &mut Commands, level_graph: &LevelGraph) {
+/// Health and ammo in utility rooms, and one attachment per objective room.
+pub fn spawn_procedural_pickups(commands: &mut Commands, level_graph: &LevelGraph) {
+	let mut zones: Vec<&Zone> = level_graph
+		.zones
+		.values()
+		.filter(|zone| matches!(zone.zone_type, ZoneType::Utility | ZoneType::Objective))
+		.collect();
+	zones.sort_by_key(|zone| zone.id.0);
+
+	let mut spawned = 0usize;
+	for zone in zones {
+		let half_x = zone.size.x * 0.3;
+		let pickups = match zone.zone_type {
+			ZoneType::Objective => vec![(
+				PickupKind::Attachment(Attachment::ALL[zone.id.0 as usize % Attachment::ALL.len()]),
+				Vec3::ZERO,
+			)],
+			_ => vec![
+				(PickupKind::HealthPack, Vec3::new(-half_x, 0.0, 0.0)),
+				(PickupKind::Ammo, Vec3::new(half_x, 0.0, 0.0)),
+			],
+		};
+
+		for (index, (kind, offset)) in pickups.into_iter().enumerate() {
+			let pickup = spawn_pickup(
+				commands,
+				format!("ProceduralPickup_{}_{}", zone.id.0, index),
+				kind,
+				zone.position + zone.rotation * offset,
+			);
+			commands
+				.entity(pickup)
+				.insert((ProceduralPickupMarker, LevelGeometry));
+			spawned += 1;
+		}
+	}
+
+	info!("🎁 Spawned {} pickups", spawned);
+}
+
+pub fn build_procedural_runtime_content(
+	commands: &mut Commands,
+	level_graph: &LevelGraph,
+	bot_settings: &MatchBotSettings,
+) {
 	setup_procedural_navmesh(commands, level_graph);
 	spawn_procedural_connection_lights(commands, level_graph);
-	spawn_procedural_enemies(commands, level_graph);
+	spawn_procedural_enemies(commands, level_graph, bot_settings);
+	spawn_procedural_props(commands, level_graph);
+	spawn_procedural_pickups(commands, level_graph);
+	spawn_procedural_platforms(commands, level_graph);
+	spawn_level_exit(commands, level_graph);
 }
 
 #[cfg(test)]
@@ -180,6 +280,7 @@ mod tests {
 		ProceduralConnectionLightMarker, ProceduralEnemyMarker, ProceduralNavMeshMarker,
 		build_procedural_runtime_content,
 	};
+	use crate::bots::MatchBotSettings;
 	use crate::level::generation::{LevelConfig, LevelGraph, generate_level};
 	use crate::navigation::{PatrolRoute, SimpleNavigationAgent};
 	use bevy::prelude::{App, Commands, MinimalPlugins, Res, Resource, Update};
@@ -190,7 +291,11 @@ mod tests {
 	struct TestLevelGraph(LevelGraph);
 
 	fn build_runtime_content_system(mut commands: Commands, level_graph: Res<TestLevelGraph>) {
-		build_procedural_runtime_content(&mut commands, &level_graph.0);
+		build_procedural_runtime_content(
+			&mut commands,
+			&level_graph.0,
+			&MatchBotSettings::default(),
+		);
 	}
 
 	#[test]
@@ -0,0 +1,315 @@
+//! Hand-made levels, as built in the client's level editor and saved as RON.
+
+use avian3d::prelude::{Collider, Position, RigidBody};
+use bevy::prelude::{
+    Assets, Color, Commands, Cuboid, Mesh, Mesh3d, MeshMaterial3d, Message, Name, PointLight,
+    StandardMaterial, Transform, Vec3, default,
+};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::bots::spawn_classic_ai_bot;
+use crate::components::destructible::{Destructible, spawn_destructible_prop};
+use crate::level::generation::LevelGeometry;
+use crate::level::interactive::{Door, Wired, spawn_door, spawn_level_button};
+use crate::level::platforms::{MovingPlatform, PlatformPath, spawn_moving_platform};
+use crate::navigation::{DoorLink, NavigationObstacle};
+
+/// Everything a hand-made level is made of. Positions are in world space, in meters.
+/// Sections left out of a file are empty.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LevelFile {
+    pub blocks: Vec<LevelBlock>,
+    pub spawn_points: Vec<Vec3>,
+    /// Each route gets a bot patrolling it, starting at its first point.
+    pub patrol_routes: Vec<Vec<Vec3>>,
+    pub lights: Vec<LevelLight>,
+    pub doors: Vec<DoorDefinition>,
+    pub buttons: Vec<ButtonDefinition>,
+    pub platforms: Vec<PlatformDefinition>,
+    pub destructibles: Vec<DestructibleDefinition>,
+}
+
+/// Static box of level geometry.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LevelBlock {
+    pub center: Vec3,
+    pub size: Vec3,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LevelLight {
+    pub position: Vec3,
+    /// Linear RGB, 0..1.
+    pub color: [f32; 3],
+    pub intensity: f32,
+    pub range: f32,
+}
+
+/// Sliding door, worked with `Use` or by the buttons of its channel. Bots work doors on a
+/// channel from its button nearest the door.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DoorDefinition {
+    /// Center of the door when closed.
+    pub center: Vec3,
+    pub size: Vec3,
+    #[serde(default)]
+    pub open: bool,
+    #[serde(default)]
+    pub channel: Option<u32>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ButtonDefinition {
+    pub position: Vec3,
+    pub channel: u32,
+}
+
+/// Moving platform. Platforms on a channel wait for one of its buttons; the others run
+/// from the start.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PlatformDefinition {
+    pub path: PlatformPath,
+    #[serde(default)]
+    pub channel: Option<u32>,
+}
+
+/// Box that shatters once shot enough, like a barricade over a shortcut. Bots path around
+/// it until it breaks.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DestructibleDefinition {
+    pub center: Vec3,
+    pub size: Vec3,
+    pub health: f32,
+    pub debris_pieces: usize,
+}
+
+impl LevelLight {
+    pub fn new(position: Vec3) -> Self {
+        Self {
+            position,
+            color: [1.0, 0.95, 0.85],
+            intensity: 20000.0,
+            range: 16.0,
+        }
+    }
+}
+
+impl LevelFile {
+    pub fn to_ron(&self) -> Result<String, String> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, String> {
+        ron::from_str(text).map_err(|e| e.to_string())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Self::from_ron(&text)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        std::fs::write(path, self.to_ron()?).map_err(|e| e.to_string())
+    }
+
+    /// Spawn point of the `index`th player, cycling through the level's spawn points.
+    pub fn spawn_point(&self, index: usize) -> Option<Vec3> {
+        (!self.spawn_points.is_empty()).then(|| self.spawn_points[index % self.spawn_points.len()])
+    }
+}
+
+/// Asks the server to replace the current level with a hand-made one. Only the host sends
+/// it, from the level editor, so it never goes over the network.
+#[derive(Message, Clone, Debug)]
+pub struct LoadLevelFile(pub LevelFile);
+
+/// Spawns the blocks, patrolling bots, lights, doors, buttons, platforms and destructible
+/// props of `level`, all tagged [`LevelGeometry`] so the usual level teardown removes them.
+/// Meshes and materials are skipped when the app has no renderer; clients dress the
+/// replicated props themselves.
+pub fn spawn_level_file(
+    commands: &mut Commands,
+    level: &LevelFile,
+    mut meshes: Option<&mut Assets<Mesh>>,
+    mut materials: Option<&mut Assets<StandardMaterial>>,
+) {
+    for (index, block) in level.blocks.iter().enumerate() {
+        let mut block_entity = commands.spawn((
+            Name::new(format!("LevelBlock_{}", index + 1)),
+            LevelGeometry,
+            Position::from(block.center),
+            RigidBody::Static,
+            Collider::cuboid(block.size.x, block.size.y, block.size.z),
+            NavigationObstacle,
+        ));
+        if let Some(meshes) = meshes.as_deref_mut() {
+            block_entity.insert(Mesh3d(meshes.add(Cuboid {
+                half_size: block.size / 2.0,
+            })));
+        }
+        if let Some(materials) = materials.as_deref_mut() {
+            block_entity.insert(MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: Color::srgb(0.55, 0.55, 0.6),
+                ..default()
+            })));
+        }
+    }
+
+    for (index, route) in level.patrol_routes.iter().enumerate() {
+        let Some(start) = route.first() else {
+            continue;
+        };
+        let bot = spawn_classic_ai_bot(format!("LevelPatrol_{}", index + 1), *start)
+            .patrol(route.clone())
+            .spawn(commands);
+        commands.entity(bot).insert(LevelGeometry);
+    }
+
+    for (index, light) in level.lights.iter().enumerate() {
+        let [red, green, blue] = light.color;
+        commands.spawn((
+            Name::new(format!("LevelLight_{}", index + 1)),
+            LevelGeometry,
+            PointLight {
+                color: Color::linear_rgb(red, green, blue),
+                intensity: light.intensity,
+                range: light.range,
+                shadows_enabled: false,
+                ..default()
+            },
+            Transform::from_translation(light.position),
+        ));
+    }
+
+    for (index, door) in level.doors.iter().enumerate() {
+        let entity = spawn_door(
+            commands,
+            format!("LevelDoor_{}", index + 1),
+            door.center,
+            door.size,
+            door.open,
+        );
+        commands.entity(entity).insert(LevelGeometry);
+        if let Some(channel) = door.channel {
+            commands.entity(entity).insert(Wired(channel));
+        }
+        let button = door.channel.and_then(|channel| {
+            level
+                .buttons
+                .iter()
+                .filter(|button| button.channel == channel)
+                .map(|button| button.position)
+                .min_by(|a, b| a.distance(door.center).total_cmp(&b.distance(door.center)))
+        });
+        let link = DoorLink::through(&Door::new(door.center, door.size, door.open), button);
+        commands.entity(entity).insert(link);
+    }
+
+    for (index, button) in level.buttons.iter().enumerate() {
+        let entity = spawn_level_button(
+            commands,
+            format!("LevelButton_{}", index + 1),
+            button.position,
+            button.channel,
+        );
+        commands.entity(entity).insert(LevelGeometry);
+    }
+
+    for (index, platform) in level.platforms.iter().enumerate() {
+        let entity = spawn_moving_platform(
+            commands,
+            format!("LevelPlatform_{}", index + 1),
+            platform.path,
+        );
+        commands.entity(entity).insert(LevelGeometry);
+        if let Some(channel) = platform.channel {
+            commands.entity(entity).insert((
+                Wired(channel),
+                MovingPlatform {
+                    path: platform.path,
+                    phase: 0.0,
+                    running: false,
+                },
+            ));
+        }
+    }
+
+    for (index, prop) in level.destructibles.iter().enumerate() {
+        let entity = spawn_destructible_prop(
+            commands,
+            format!("LevelDestructible_{}", index + 1),
+            prop.center,
+            Destructible {
+                size: prop.size,
+                debris_pieces: prop.debris_pieces,
+            },
+            prop.health,
+        );
+        commands.entity(entity).insert(LevelGeometry);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ButtonDefinition, DestructibleDefinition, DoorDefinition, LevelBlock, LevelFile,
+        LevelLight, PlatformDefinition,
+    };
+    use crate::level::platforms::PlatformPath;
+    use bevy::prelude::Vec3;
+
+    #[test]
+    fn level_files_round_trip_through_ron() {
+        let level = LevelFile {
+            blocks: vec![LevelBlock {
+                center: Vec3::new(0.0, -0.5, 0.0),
+                size: Vec3::new(40.0, 1.0, 40.0),
+            }],
+            spawn_points: vec![Vec3::new(0.0, 2.0, 0.0), Vec3::new(4.0, 2.0, 0.0)],
+            patrol_routes: vec![vec![Vec3::new(-5.0, 1.0, 5.0), Vec3::new(5.0, 1.0, 5.0)]],
+            lights: vec![LevelLight::new(Vec3::new(0.0, 4.0, 0.0))],
+            doors: vec![DoorDefinition {
+                center: Vec3::new(0.0, 1.5, 10.0),
+                size: Vec3::new(2.0, 3.0, 0.2),
+                open: false,
+                channel: Some(1),
+            }],
+            buttons: vec![ButtonDefinition {
+                position: Vec3::new(2.0, 1.2, 9.5),
+                channel: 1,
+            }],
+            platforms: vec![PlatformDefinition {
+                path: PlatformPath {
+                    start: Vec3::new(8.0, 0.0, 0.0),
+                    end: Vec3::new(8.0, 4.0, 0.0),
+                    travel_secs: 4.0,
+                    dwell_secs: 2.0,
+                },
+                channel: None,
+            }],
+            destructibles: vec![DestructibleDefinition {
+                center: Vec3::new(-6.0, 1.25, 0.0),
+                size: Vec3::new(3.0, 2.5, 0.3),
+                health: 120.0,
+                debris_pieces: 24,
+            }],
+        };
+
+        let text = level.to_ron().unwrap();
+        assert_eq!(LevelFile::from_ron(&text).unwrap(), level);
+        assert!(LevelFile::from_ron("(blocks: [oops])").is_err());
+        let door =
+            LevelFile::from_ron("(doors: [(center: (0.0, 1.5, 0.0), size: (2.0, 3.0, 0.2))])");
+        assert_eq!(door.unwrap().doors[0].channel, None);
+        assert_eq!(level.spawn_point(3), Some(Vec3::new(4.0, 2.0, 0.0)));
+        assert_eq!(LevelFile::default().spawn_point(0), None);
+    }
+}
@@ -5,10 +5,12 @@ use rand::{Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::inputs::climbing::spawn_ladder;
+use crate::level::platforms::{ElevatorDefinition, place_elevators};
 use crate::navigation::NavigationObstacle;
 
 pub(crate) const WALL_THICKNESS: f32 = 0.5;
-const DOOR_OPENING_WIDTH: f32 = 6.0;
+pub(crate) const DOOR_OPENING_WIDTH: f32 = 6.0;
 const DOOR_EDGE_MARGIN: f32 = 1.0;
 const MIN_WALL_SEGMENT_LENGTH: f32 = 0.5;
 pub(crate) const WALL_SIDE_EAST: usize = 0;
@@ -16,6 +18,10 @@ pub(crate) const WALL_SIDE_WEST: usize = 1;
 pub(crate) const WALL_SIDE_NORTH: usize = 2;
 pub(crate) const WALL_SIDE_SOUTH: usize = 3;
 
+/// Tags every entity built from a `LevelGraph` so the level can be torn down in place.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct LevelGeometry;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum WallSide {
     East,
@@ -218,6 +224,7 @@ fn spawn_wall_segments_for_side(
             Position::new(world_position),
             Rotation::from(zone.rotation),
             Transform::from_translation(world_position).with_rotation(zone.rotation),
+            LevelGeometry,
             Name::new(format!(
                 "Physics_Wall_{:?}_{}_Zone_{}",
                 side, segment_index, zone.id.0
@@ -312,6 +319,19 @@ pub struct LevelConfig {
     pub max_depth: u32,
 }
 
+impl LevelConfig {
+    /// Layout used for match levels; server and clients must agree on it to generate the
+    /// same level from a replicated seed.
+    pub fn for_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            target_zone_count: 12,
+            min_zone_spacing: 35.0,
+            max_depth: 8,
+        }
+    }
+}
+
 impl Default for LevelConfig {
     fn default() -> Self {
         Self {
@@ -330,6 +350,7 @@ pub struct LevelGraph {
     pub connections: Vec<ZoneConnection>,
     pub spawn_zone: ZoneId,
     pub objective_zones: Vec<ZoneId>,
+    pub elevators: Vec<ElevatorDefinition>,
 }
 
 impl LevelGraph {
@@ -340,6 +361,7 @@ impl LevelGraph {
             connections: Vec::new(),
             spawn_zone: ZoneId(0),
             objective_zones: Vec::new(),
+            elevators: Vec::new(),
         }
     }
 
@@ -473,10 +495,13 @@ pub fn generate_level(config: LevelConfig) -> LevelGraph {
         }
     }
 
+    graph.elevators = place_elevators(&graph);
+
     info!(
-        "Generated level with {} zones and {} connections",
+        "Generated level with {} zones, {} connections and {} elevators",
         graph.zones.len(),
-        graph.connections.len()
+        graph.connections.len(),
+        graph.elevators.len()
     );
 
     graph
@@ -562,6 +587,7 @@ pub fn build_level_physics(mut commands: Commands, level_graph: &LevelGraph) {
             Position::new(floor_position),
             Rotation::from(zone.rotation),
             Transform::from_translation(floor_position).with_rotation(zone.rotation),
+            LevelGeometry,
             Name::new(format!("Physics_Floor_Zone_{}", zone.id.0)),
         ));
 
@@ -594,6 +620,29 @@ pub fn build_level_physics(mut commands: Commands, level_graph: &LevelGraph) {
         );
     }
 
+    for elevator in &level_graph.elevators {
+        let size = elevator.ledge_size;
+        commands.spawn((
+            RigidBody::Static,
+            Collider::cuboid(size.x, size.y, size.z),
+            Position::new(elevator.ledge_center),
+            Rotation::from(elevator.rotation),
+            Transform::from_translation(elevator.ledge_center).with_rotation(elevator.rotation),
+            LevelGeometry,
+            Name::new(format!("Physics_Ledge_Zone_{}", elevator.zone.0)),
+        ));
+
+        let (ladder_center, ladder_size) = elevator.ladder();
+        let ladder = spawn_ladder(
+            &mut commands,
+            format!("Physics_Ladder_Zone_{}", elevator.zone.0),
+            ladder_center,
+            elevator.rotation,
+            ladder_size,
+        );
+        commands.entity(ladder).insert(LevelGeometry);
+    }
+
     if min_x.is_finite() && max_x.is_finite() && min_z.is_finite() && max_z.is_finite() {
         let safety_margin = 20.0;
         let safety_width = (max_x - min_x) + safety_margin;
@@ -606,6 +655,7 @@ pub fn build_level_physics(mut commands: Commands, level_graph: &LevelGraph) {
             Position::new(safety_center),
             Rotation::default(),
             Transform::from_translation(safety_center),
+            LevelGeometry,
             Name::new("Physics_SafetyFloor"),
         ));
     }
@@ -689,7 +739,11 @@ mod tests {
     fn wall_segments_split_around_single_opening() {
         let segments = build_wall_segments(10.0, &[0.0], 6.0);
 
-        assert_eq!(segments.len(), 2, "Expected two wall segments around one opening");
+        assert_eq!(
+            segments.len(),
+            2,
+            "Expected two wall segments around one opening"
+        );
         assert!(
             (segments[0].1 - 7.0).abs() < 0.001,
             "First segment length should be 7.0, got {:?}",
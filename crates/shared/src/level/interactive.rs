@@ -0,0 +1,358 @@
+//! Doors and buttons players work with `Use`, and bots with [`UseRequest`]. Both are
+//! server-simulated and replicated; the `Use` interaction runs on the client too, so the
+//! local player's door opens at once and is rolled back like the rest of its predicted
+//! state.
+
+use avian3d::prelude::{Collider, LinearVelocity, Position, RigidBody};
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+use lightyear::prelude::{NetworkTarget, PredictionTarget, Replicate};
+use serde::{Deserialize, Serialize};
+
+use crate::inputs::input::PlayerAction;
+use crate::inputs::movement::update_ground_detection;
+use crate::level::platforms::{MovingPlatform, advance_platforms};
+
+/// Players reach doors and buttons this far from their center.
+pub const USE_RANGE: f32 = 2.5;
+pub const DOOR_COLOR: Color = Color::srgb(0.35, 0.4, 0.45);
+pub const BUTTON_SIZE: Vec3 = Vec3::new(0.3, 0.3, 0.3);
+pub const BUTTON_COLOR: Color = Color::srgb(0.8, 0.2, 0.15);
+const DOOR_SLIDE_SECS: f32 = 0.8;
+
+/// Replicated, predicted sliding door. It slides up by its own height to open, driven by
+/// its velocity like moving platforms.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Door {
+    /// Center of the door when closed.
+    pub closed_at: Vec3,
+    pub size: Vec3,
+    /// Whether the door is opening or open, rather than closing or closed.
+    pub open: bool,
+    /// From 0 when closed to 1 when open.
+    pub progress: f32,
+}
+
+impl Door {
+    pub fn new(closed_at: Vec3, size: Vec3, open: bool) -> Self {
+        Self {
+            closed_at,
+            size,
+            open,
+            progress: if open { 1.0 } else { 0.0 },
+        }
+    }
+
+    pub fn position(&self) -> Vec3 {
+        self.closed_at + Vec3::Y * (self.size.y * self.progress)
+    }
+
+    pub fn is_closed(&self) -> bool {
+        !self.open && self.progress <= 0.0
+    }
+
+    /// Slide `dt` seconds towards open or closed.
+    pub fn advance(&mut self, dt: f32) {
+        let target = if self.open { 1.0 } else { 0.0 };
+        let step = dt / DOOR_SLIDE_SECS;
+        self.progress = if self.progress < target {
+            (self.progress + step).min(target)
+        } else {
+            (self.progress - step).max(target)
+        };
+    }
+}
+
+/// Button that toggles every door and moving platform [`Wired`] to its channel.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LevelButton {
+    pub channel: u32,
+}
+
+/// Connects a door or moving platform to the buttons of a channel. Channels are plain
+/// numbers so buttons need no entity mapping on the client.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Wired(pub u32);
+
+/// `Use` worked from `from` by something without inputs, such as a bot. Requests working
+/// the same door or button in one tick work it once, so bots waiting at a door together
+/// do not close it on each other.
+#[derive(Message, Clone, Copy, Debug, PartialEq)]
+pub struct UseRequest {
+    pub from: Vec3,
+}
+
+/// Spawn a server-replicated kinematic door, closed unless `open`.
+pub fn spawn_door(
+    commands: &mut Commands,
+    name: impl Into<String>,
+    closed_at: Vec3,
+    size: Vec3,
+    open: bool,
+) -> Entity {
+    let door = Door::new(closed_at, size, open);
+    commands
+        .spawn((
+            Name::new(name.into()),
+            door,
+            RigidBody::Kinematic,
+            Collider::cuboid(size.x, size.y, size.z),
+            Position::new(door.position()),
+            LinearVelocity::ZERO,
+            Replicate::to_clients(NetworkTarget::All),
+            PredictionTarget::to_clients(NetworkTarget::All),
+        ))
+        .id()
+}
+
+/// Spawn a server-replicated button on `channel`.
+pub fn spawn_level_button(
+    commands: &mut Commands,
+    name: impl Into<String>,
+    position: Vec3,
+    channel: u32,
+) -> Entity {
+    commands
+        .spawn((
+            Name::new(name.into()),
+            LevelButton { channel },
+            Position::new(position),
+            Replicate::to_clients(NetworkTarget::All),
+        ))
+        .id()
+}
+
+pub struct InteractivePlugin;
+
+impl Plugin for InteractivePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<UseRequest>();
+        app.add_systems(
+            FixedUpdate,
+            (use_interactables, move_doors)
+                .chain()
+                .before(advance_platforms)
+                .before(update_ground_detection),
+        );
+    }
+}
+
+/// What a player's `Use` works.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum UseTarget {
+    Door(Entity),
+    Button(u32),
+}
+
+/// Distance from `point` to a box centered on `center`.
+pub fn distance_to_box(point: Vec3, center: Vec3, half_size: Vec3) -> f32 {
+    ((point - center).abs() - half_size)
+        .max(Vec3::ZERO)
+        .length()
+}
+
+/// The nearest door or button within reach of `from`.
+fn use_target(
+    from: Vec3,
+    buttons: &Query<(&LevelButton, &Position)>,
+    doors: &Query<(Entity, &mut Door, &Position, Option<&Wired>)>,
+) -> Option<UseTarget> {
+    let door_targets = doors.iter().map(|(entity, door, position, _)| {
+        (
+            UseTarget::Door(entity),
+            distance_to_box(from, position.0, door.size * 0.5),
+        )
+    });
+    let button_targets = buttons
+        .iter()
+        .map(|(button, position)| (UseTarget::Button(button.channel), position.0.distance(from)));
+    door_targets
+        .chain(button_targets)
+        .filter(|(_, distance)| *distance <= USE_RANGE)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(target, _)| target)
+}
+
+/// Each player pressing `Use`, and each [`UseRequest`], works the nearest door or button in
+/// reach: doors open or close, buttons toggle everything wired to them.
+pub fn use_interactables(
+    players: Query<(&Position, &ActionState<PlayerAction>)>,
+    mut requests: MessageReader<UseRequest>,
+    buttons: Query<(&LevelButton, &Position)>,
+    mut doors: Query<(Entity, &mut Door, &Position, Option<&Wired>)>,
+    mut platforms: Query<(&mut MovingPlatform, &Wired)>,
+) {
+    let mut targets: Vec<UseTarget> = players
+        .iter()
+        .filter(|(_, action_state)| {
+            !action_state.disabled() && action_state.just_pressed(&PlayerAction::Use)
+        })
+        .filter_map(|(player, _)| use_target(player.0, &buttons, &doors))
+        .collect();
+    let mut requested = Vec::new();
+    for request in requests.read() {
+        if let Some(target) = use_target(request.from, &buttons, &doors)
+            && !requested.contains(&target)
+        {
+            requested.push(target);
+        }
+    }
+    targets.extend(requested);
+
+    for target in targets {
+        match target {
+            UseTarget::Door(entity) => {
+                if let Ok((_, mut door, ..)) = doors.get_mut(entity) {
+                    door.open = !door.open;
+                }
+            }
+            UseTarget::Button(channel) => {
+                for (_, mut door, _, wired) in doors.iter_mut() {
+                    if wired == Some(&Wired(channel)) {
+                        door.open = !door.open;
+                    }
+                }
+                for (mut platform, wired) in platforms.iter_mut() {
+                    if *wired == Wired(channel) {
+                        platform.running = !platform.running;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Slide each door towards open or closed through its velocity, so the physics step
+/// pushes whatever it meets instead of tunnelling through it.
+pub fn move_doors(time: Res<Time>, mut doors: Query<(&mut Door, &Position, &mut LinearVelocity)>) {
+    let dt = time.delta_secs();
+    if dt <= 0.0 {
+        return;
+    }
+
+    for (mut door, position, mut velocity) in doors.iter_mut() {
+        door.advance(dt);
+        velocity.0 = (door.position() - position.0) / dt;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Door, LevelButton, USE_RANGE, UseRequest, Wired, use_interactables};
+    use crate::inputs::input::PlayerAction;
+    use crate::level::platforms::{MovingPlatform, PlatformPath};
+    use avian3d::prelude::Position;
+    use bevy::prelude::{App, MinimalPlugins, Update, Vec3};
+    use leafwing_input_manager::prelude::ActionState;
+
+    #[test]
+    fn doors_slide_up_by_their_height_and_back() {
+        let mut door = Door::new(Vec3::new(0.0, 1.5, 0.0), Vec3::new(2.0, 3.0, 0.2), false);
+        assert!(door.is_closed());
+        door.open = true;
+        door.advance(0.4);
+        assert!((door.progress - 0.5).abs() < 1e-4);
+        door.advance(10.0);
+        assert_eq!(door.position(), Vec3::new(0.0, 4.5, 0.0));
+
+        door.open = false;
+        door.advance(10.0);
+        assert!(door.is_closed());
+        assert_eq!(door.position(), door.closed_at);
+    }
+
+    #[test]
+    fn use_works_the_nearest_door_or_button_in_reach() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_message::<UseRequest>();
+        app.add_systems(Update, use_interactables);
+
+        let size = Vec3::new(2.0, 3.0, 0.2);
+        let near_door = Door::new(Vec3::new(0.0, 1.5, 1.0), size, false);
+        let far_door = Door::new(Vec3::new(0.0, 1.5, USE_RANGE + 5.0), size, false);
+        let near = app
+            .world_mut()
+            .spawn((near_door, Position::new(near_door.closed_at)))
+            .id();
+        let far = app
+            .world_mut()
+            .spawn((far_door, Position::new(far_door.closed_at), Wired(3)))
+            .id();
+        let platform = app
+            .world_mut()
+            .spawn((
+                MovingPlatform {
+                    path: PlatformPath {
+                        start: Vec3::ZERO,
+                        end: Vec3::Y * 4.0,
+                        travel_secs: 4.0,
+                        dwell_secs: 2.0,
+                    },
+                    phase: 0.0,
+                    running: false,
+                },
+                Wired(3),
+            ))
+            .id();
+
+        let pressing_use = || {
+            let mut action_state = ActionState::<PlayerAction>::default();
+            action_state.enable();
+            action_state.press(&PlayerAction::Use);
+            action_state
+        };
+        let player = app
+            .world_mut()
+            .spawn((Position::new(Vec3::new(0.0, 1.0, -0.5)), pressing_use()))
+            .id();
+        app.update();
+
+        let world = app.world();
+        assert!(world.get::<Door>(near).unwrap().open);
+        assert!(!world.get::<Door>(far).unwrap().open);
+
+        // Walk up to a button on channel 3: it works the wired door and platform.
+        app.world_mut().spawn((
+            LevelButton { channel: 3 },
+            Position::new(Vec3::new(-10.0, 1.0, 0.0)),
+        ));
+        app.world_mut()
+            .entity_mut(player)
+            .insert((Position::new(Vec3::new(-9.0, 1.0, 0.0)), pressing_use()));
+        app.update();
+
+        let world = app.world();
+        assert!(world.get::<Door>(near).unwrap().open);
+        assert!(world.get::<Door>(far).unwrap().open);
+        assert!(world.get::<MovingPlatform>(platform).unwrap().running);
+    }
+
+    #[test]
+    fn use_requests_work_each_door_once_per_tick() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_message::<UseRequest>();
+        app.add_systems(Update, use_interactables);
+
+        let door = Door::new(Vec3::new(0.0, 1.5, 0.0), Vec3::new(2.0, 3.0, 0.2), false);
+        let entity = app
+            .world_mut()
+            .spawn((door, Position::new(door.closed_at)))
+            .id();
+
+        // Two bots on either side of the door ask at once: it opens, and stays open.
+        for from in [Vec3::new(0.0, 1.0, -1.0), Vec3::new(0.5, 1.0, 1.0)] {
+            app.world_mut().write_message(UseRequest { from });
+        }
+        app.update();
+        assert!(app.world().get::<Door>(entity).unwrap().open);
+
+        // Out of reach, nothing happens.
+        app.world_mut().write_message(UseRequest {
+            from: Vec3::new(0.0, 1.0, USE_RANGE + 3.0),
+        });
+        app.update();
+        assert!(app.world().get::<Door>(entity).unwrap().open);
+    }
+}
@@ -1,3 +1,8 @@
 pub mod building;
+pub mod file;
 pub mod generation;
+pub mod interactive;
+pub mod platforms;
+pub mod preload;
+pub mod transition;
 pub mod visuals;
@@ -0,0 +1,271 @@
+use avian3d::prelude::{Collider, LinearVelocity, Position, RigidBody, Rotation};
+use bevy::prelude::*;
+use lightyear::prelude::{NetworkTarget, PredictionTarget, Replicate};
+use serde::{Deserialize, Serialize};
+
+use crate::inputs::movement::update_ground_detection;
+use crate::level::generation::{LevelGraph, WALL_THICKNESS, Zone, ZoneId, ZoneType};
+
+pub const PLATFORM_SIZE: Vec3 = Vec3::new(3.0, 0.4, 3.0);
+pub const PLATFORM_COLOR: Color = Color::srgb(0.55, 0.45, 0.15);
+pub const LADDER_COLOR: Color = Color::srgb(0.3, 0.3, 0.32);
+/// Height of the walkable top of elevator ledges above the zone floor.
+pub const LEDGE_HEIGHT: f32 = 4.0;
+const LEDGE_DEPTH: f32 = 6.0;
+const LEDGE_THICKNESS: f32 = 0.5;
+const LADDER_DEPTH: f32 = 0.8;
+const LADDER_WIDTH: f32 = 1.0;
+/// Ladder volumes reach this far above their ledge, so climbers get high enough to mantle.
+const LADDER_OVERHANG: f32 = 1.0;
+const ELEVATOR_TRAVEL_SECS: f32 = 4.0;
+const ELEVATOR_DWELL_SECS: f32 = 3.0;
+
+/// Back-and-forth route of a moving platform. It waits `dwell_secs` at each end and travels
+/// between them at constant speed.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PlatformPath {
+    pub start: Vec3,
+    pub end: Vec3,
+    pub travel_secs: f32,
+    pub dwell_secs: f32,
+}
+
+impl PlatformPath {
+    pub fn period(&self) -> f32 {
+        2.0 * (self.travel_secs + self.dwell_secs)
+    }
+
+    /// How far along the path the platform is `phase` seconds into its cycle, from 0 at
+    /// `start` to 1 at `end`.
+    pub fn progress(&self, phase: f32) -> f32 {
+        let t = phase.rem_euclid(self.period());
+        if t < self.dwell_secs {
+            0.0
+        } else if t < self.dwell_secs + self.travel_secs {
+            (t - self.dwell_secs) / self.travel_secs
+        } else if t < 2.0 * self.dwell_secs + self.travel_secs {
+            1.0
+        } else {
+            1.0 - (t - 2.0 * self.dwell_secs - self.travel_secs) / self.travel_secs
+        }
+    }
+
+    pub fn position_at(&self, phase: f32) -> Vec3 {
+        self.start.lerp(self.end, self.progress(phase))
+    }
+
+    pub fn is_docked_at_start(&self, phase: f32) -> bool {
+        phase.rem_euclid(self.period()) < self.dwell_secs
+    }
+
+    pub fn is_docked_at_end(&self, phase: f32) -> bool {
+        let t = phase.rem_euclid(self.period());
+        t >= self.dwell_secs + self.travel_secs && t < 2.0 * self.dwell_secs + self.travel_secs
+    }
+}
+
+/// Elevator from the floor of an industrial zone up to a ledge along its east wall. Part of
+/// the level data, so server and clients build the same ledge from the level seed.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ElevatorDefinition {
+    pub zone: ZoneId,
+    pub path: PlatformPath,
+    pub ledge_center: Vec3,
+    pub ledge_size: Vec3,
+    pub rotation: Quat,
+    /// Where agents wait for the platform on the floor.
+    pub lower_stop: Vec3,
+    /// Where agents step off on the ledge.
+    pub upper_stop: Vec3,
+}
+
+impl ElevatorDefinition {
+    fn for_zone(zone: &Zone) -> Self {
+        let half_x = zone.size.x * 0.5;
+        let platform_x = half_x - LEDGE_DEPTH - PLATFORM_SIZE.x * 0.5 - 0.1;
+        let local = |offset: Vec3| zone.position + zone.rotation * offset;
+
+        Self {
+            zone: zone.id,
+            path: PlatformPath {
+                // Flush with the floor at the bottom and with the ledge at the top.
+                start: local(Vec3::new(platform_x, -PLATFORM_SIZE.y * 0.5 + 0.02, 0.0)),
+                end: local(Vec3::new(
+                    platform_x,
+                    LEDGE_HEIGHT - PLATFORM_SIZE.y * 0.5,
+                    0.0,
+                )),
+                travel_secs: ELEVATOR_TRAVEL_SECS,
+                dwell_secs: ELEVATOR_DWELL_SECS,
+            },
+            ledge_center: local(Vec3::new(
+                half_x - LEDGE_DEPTH * 0.5,
+                LEDGE_HEIGHT - LEDGE_THICKNESS * 0.5,
+                0.0,
+            )),
+            ledge_size: Vec3::new(
+                LEDGE_DEPTH,
+                LEDGE_THICKNESS,
+                zone.size.z - 2.0 * WALL_THICKNESS,
+            ),
+            rotation: zone.rotation,
+            lower_stop: local(Vec3::new(platform_x - PLATFORM_SIZE.x - 1.0, 1.0, 0.0)),
+            upper_stop: local(Vec3::new(
+                half_x - LEDGE_DEPTH * 0.5,
+                LEDGE_HEIGHT + 1.0,
+                0.0,
+            )),
+        }
+    }
+
+    /// Ladder volume up the front of the ledge, off to the side of the platform, for when
+    /// the platform is away: its center and size. Turned like the ledge, its local +X
+    /// points into the ledge.
+    pub fn ladder(&self) -> (Vec3, Vec3) {
+        let top = self.ledge_center.y + self.ledge_size.y * 0.5;
+        let size = Vec3::new(LADDER_DEPTH, LEDGE_HEIGHT + LADDER_OVERHANG, LADDER_WIDTH);
+        let front = self.ledge_center
+            + self.rotation
+                * Vec3::new(
+                    -(self.ledge_size.x + LADDER_DEPTH) * 0.5,
+                    0.0,
+                    self.ledge_size.z * 0.3,
+                );
+        (front.with_y(top - LEDGE_HEIGHT + size.y * 0.5), size)
+    }
+}
+
+/// One elevator per industrial zone. Derived from the zones alone so adding elevators does
+/// not change the rest of the generated level.
+pub fn place_elevators(level_graph: &LevelGraph) -> Vec<ElevatorDefinition> {
+    let mut zones: Vec<&Zone> = level_graph
+        .zones
+        .values()
+        .filter(|zone| zone.zone_type == ZoneType::Industrial)
+        .collect();
+    zones.sort_by_key(|zone| zone.id.0);
+
+    zones
+        .into_iter()
+        .map(ElevatorDefinition::for_zone)
+        .collect()
+}
+
+/// Replicated, predicted moving platform. Its phase drives its position on both sides, so
+/// predicted characters standing on it see it where the server will have it.
+#[derive(Component, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MovingPlatform {
+    pub path: PlatformPath,
+    pub phase: f32,
+    /// Stopped platforms hold where they are until a button starts them again.
+    pub running: bool,
+}
+
+/// Spawn a server-replicated kinematic platform at the start of `path`.
+pub fn spawn_moving_platform(
+    commands: &mut Commands,
+    name: impl Into<String>,
+    path: PlatformPath,
+) -> Entity {
+    commands
+        .spawn((
+            Name::new(name.into()),
+            MovingPlatform {
+                path,
+                phase: 0.0,
+                running: true,
+            },
+            RigidBody::Kinematic,
+            Collider::cuboid(PLATFORM_SIZE.x, PLATFORM_SIZE.y, PLATFORM_SIZE.z),
+            Position::new(path.start),
+            Rotation::default(),
+            LinearVelocity::ZERO,
+            Replicate::to_clients(NetworkTarget::All),
+            PredictionTarget::to_clients(NetworkTarget::All),
+        ))
+        .id()
+}
+
+pub struct MovingPlatformPlugin;
+
+impl Plugin for MovingPlatformPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            advance_platforms.before(update_ground_detection),
+        );
+    }
+}
+
+/// Advance each platform along its path. Platforms move through their velocity rather than
+/// by teleporting, so the physics step carries bodies resting on them and characters can
+/// read the velocity to ride along.
+pub fn advance_platforms(
+    time: Res<Time>,
+    mut platforms: Query<(&mut MovingPlatform, &Position, &mut LinearVelocity)>,
+) {
+    let dt = time.delta_secs();
+    if dt <= 0.0 {
+        return;
+    }
+
+    for (mut platform, position, mut velocity) in platforms.iter_mut() {
+        if platform.running {
+            platform.phase = (platform.phase + dt).rem_euclid(platform.path.period());
+        }
+        velocity.0 = (platform.path.position_at(platform.phase) - position.0) / dt;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LEDGE_HEIGHT, PLATFORM_SIZE, PlatformPath, place_elevators};
+    use crate::level::generation::{LevelConfig, ZoneType, generate_level};
+    use bevy::prelude::Vec3;
+
+    #[test]
+    fn platform_dwells_at_both_ends_and_travels_between() {
+        let path = PlatformPath {
+            start: Vec3::ZERO,
+            end: Vec3::Y * 4.0,
+            travel_secs: 4.0,
+            dwell_secs: 2.0,
+        };
+
+        assert_eq!(path.period(), 12.0);
+        assert!(path.is_docked_at_start(1.0));
+        assert_eq!(path.position_at(1.0), Vec3::ZERO);
+        assert_eq!(path.position_at(4.0), Vec3::Y * 2.0);
+        assert!(path.is_docked_at_end(7.0));
+        assert_eq!(path.position_at(7.0), Vec3::Y * 4.0);
+        assert_eq!(path.position_at(10.0), Vec3::Y * 2.0);
+        assert!(path.is_docked_at_start(13.0), "The cycle should repeat");
+    }
+
+    #[test]
+    fn every_industrial_zone_gets_an_elevator_up_to_its_ledge() {
+        let level = generate_level(LevelConfig::for_seed(7));
+        let industrial = level
+            .zones
+            .values()
+            .filter(|zone| zone.zone_type == ZoneType::Industrial)
+            .count();
+
+        assert_eq!(level.elevators.len(), industrial);
+        assert_eq!(place_elevators(&level), level.elevators);
+        for elevator in &level.elevators {
+            let top = elevator.path.end.y + PLATFORM_SIZE.y * 0.5;
+            assert!((top - LEDGE_HEIGHT).abs() < 1e-4);
+            assert!(elevator.upper_stop.y > LEDGE_HEIGHT);
+            assert!(elevator.lower_stop.y < LEDGE_HEIGHT);
+
+            // The ladder stands on the floor in front of the ledge and reaches past it.
+            let (ladder_center, ladder_size) = elevator.ladder();
+            let floor = ladder_center.y - ladder_size.y * 0.5;
+            assert!((floor - (top - LEDGE_HEIGHT)).abs() < 1e-4);
+            assert!(floor + ladder_size.y > LEDGE_HEIGHT);
+            let local = elevator.rotation.inverse() * (ladder_center - elevator.ledge_center);
+            assert!(local.x < -elevator.ledge_size.x * 0.5);
+        }
+    }
+}
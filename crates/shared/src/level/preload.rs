@@ -0,0 +1,124 @@
+use bevy::prelude::{App, Plugin, ResMut, Resource, Update, info};
+use bevy::tasks::{AsyncComputeTaskPool, Task, TaskPool, block_on, futures_lite::future};
+
+use crate::level::generation::{LevelConfig, LevelGraph, generate_level};
+
+/// Generates the next level in the background while the current one is still up, so that
+/// entering `Loading` only has to spawn it. The level has no file assets: its meshes,
+/// colliders and navmesh are all built from the [`LevelGraph`], which is the slow part.
+pub struct LevelPreloadPlugin;
+
+impl Plugin for LevelPreloadPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LevelPreloader>();
+        app.add_systems(Update, poll_level_preload);
+    }
+}
+
+/// The level being generated ahead of time, or already generated, with its seed.
+#[derive(Resource, Default)]
+pub struct LevelPreloader {
+    pending: Option<(u64, Task<LevelGraph>)>,
+    ready: Option<LevelGraph>,
+}
+
+impl LevelPreloader {
+    /// Seed of the level preloaded or being preloaded.
+    pub fn seed(&self) -> Option<u64> {
+        self.pending
+            .as_ref()
+            .map(|(seed, _)| *seed)
+            .or_else(|| self.ready.as_ref().map(|level| level.config.seed))
+    }
+
+    pub fn is_ready(&self, seed: u64) -> bool {
+        self.ready
+            .as_ref()
+            .is_some_and(|level| level.config.seed == seed)
+    }
+
+    /// Starts generating the level for `seed`, dropping any other preload. Nothing happens
+    /// if that level is already on its way.
+    pub fn preload(&mut self, seed: u64) {
+        if self.seed() == Some(seed) {
+            return;
+        }
+        self.cancel();
+        info!("🧱 Preloading level with seed: {}", seed);
+        let task = AsyncComputeTaskPool::get_or_init(TaskPool::new)
+            .spawn(async move { generate_level(LevelConfig::for_seed(seed)) });
+        self.pending = Some((seed, task));
+    }
+
+    /// Drops the preload. A generation already running finishes on its thread, but its
+    /// result is thrown away.
+    pub fn cancel(&mut self) {
+        self.pending = None;
+        self.ready = None;
+    }
+
+    fn poll(&mut self) {
+        let Some((seed, task)) = self.pending.as_mut() else {
+            return;
+        };
+        if let Some(level) = block_on(future::poll_once(task)) {
+            info!("🧱 Level with seed {} is preloaded", seed);
+            self.ready = Some(level);
+            self.pending = None;
+        }
+    }
+
+    /// The level for `seed`: the preloaded one, waiting for it if it is not done yet, or
+    /// generated now if a different level was preloaded.
+    pub fn take(&mut self, seed: u64) -> LevelGraph {
+        self.poll();
+        if self.is_ready(seed) {
+            return self.ready.take().expect("ready level was just checked");
+        }
+        match self.pending.take() {
+            Some((pending_seed, task)) if pending_seed == seed => block_on(task),
+            _ => {
+                self.cancel();
+                generate_level(LevelConfig::for_seed(seed))
+            }
+        }
+    }
+}
+
+fn poll_level_preload(mut preloader: ResMut<LevelPreloader>) {
+    if preloader.pending.is_some() {
+        preloader.poll();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LevelPreloader;
+    use crate::level::generation::{LevelConfig, generate_level};
+
+    #[test]
+    fn preloaded_level_matches_a_fresh_generation() {
+        let mut preloader = LevelPreloader::default();
+        preloader.preload(7);
+        assert_eq!(preloader.seed(), Some(7));
+
+        let level = preloader.take(7);
+        let fresh = generate_level(LevelConfig::for_seed(7));
+        assert_eq!(level.config.seed, 7);
+        assert_eq!(level.zones.len(), fresh.zones.len());
+        assert_eq!(level.spawn_zone, fresh.spawn_zone);
+        assert_eq!(preloader.seed(), None);
+    }
+
+    #[test]
+    fn changing_the_map_replaces_the_preload() {
+        let mut preloader = LevelPreloader::default();
+        preloader.preload(7);
+        preloader.preload(8);
+        assert_eq!(preloader.seed(), Some(8));
+
+        // A level that was not preloaded is still generated.
+        assert_eq!(preloader.take(9).config.seed, 9);
+        assert_eq!(preloader.seed(), None);
+    }
+}
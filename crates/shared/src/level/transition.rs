@@ -0,0 +1,211 @@
+use bevy::prelude::{Commands, Component, Entity, Name, Vec2, Vec3};
+use lightyear::prelude::{NetworkTarget, Replicate};
+use serde::{Deserialize, Serialize};
+
+use crate::components::team::Team;
+#[cfg(feature = "lobby")]
+use crate::components::team::team_slot;
+use crate::level::generation::{LevelGeometry, LevelGraph};
+#[cfg(feature = "lobby")]
+use crate::protocol::LobbyState;
+
+/// Radius (in meters) around the exit that counts as "reached".
+pub const LEVEL_EXIT_RADIUS: f32 = 4.0;
+const PLAYER_SPAWN_RADIUS: f32 = 3.0;
+const PLAYER_SPAWN_HEIGHT: f32 = 3.5;
+/// Distance of each team's spawn ring from the center of the spawn zone.
+const TEAM_SPAWN_OFFSET: f32 = 8.0;
+
+/// Campaign exit objective. When every living player stands inside it, the server streams
+/// the next level in place instead of returning to the lobby.
+#[derive(Component, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LevelExit {
+    pub position: Vec3,
+    pub radius: f32,
+}
+
+impl LevelExit {
+    pub fn contains(&self, point: Vec3) -> bool {
+        Vec2::new(point.x, point.z).distance(Vec2::new(self.position.x, self.position.z))
+            <= self.radius
+    }
+}
+
+/// The exit sits in the objective zone farthest from spawn, or the farthest zone when the
+/// level has no objective.
+pub fn level_exit_position(level_graph: &LevelGraph) -> Option<Vec3> {
+    let spawn_position = level_graph.get_zone(level_graph.spawn_zone)?.position;
+    let farthest = |zones: &mut dyn Iterator<Item = Vec3>| {
+        zones.max_by(|a, b| {
+            a.distance_squared(spawn_position)
+                .total_cmp(&b.distance_squared(spawn_position))
+        })
+    };
+
+    farthest(
+        &mut level_graph
+            .objective_zones
+            .iter()
+            .filter_map(|zone_id| level_graph.get_zone(*zone_id))
+            .map(|zone| zone.position),
+    )
+    .or_else(|| {
+        farthest(
+            &mut level_graph
+                .zones
+                .values()
+                .filter(|zone| zone.id != level_graph.spawn_zone)
+                .map(|zone| zone.position),
+        )
+    })
+}
+
+pub fn spawn_level_exit(commands: &mut Commands, level_graph: &LevelGraph) -> Option<Entity> {
+    let position = level_exit_position(level_graph)?;
+
+    Some(
+        commands
+            .spawn((
+                LevelExit {
+                    position,
+                    radius: LEVEL_EXIT_RADIUS,
+                },
+                Replicate::to_clients(NetworkTarget::All),
+                LevelGeometry,
+                Name::new("LevelExit"),
+            ))
+            .id(),
+    )
+}
+
+/// True when there is at least one player and all of them are inside the exit.
+pub fn team_reached_exit(
+    exit: &LevelExit,
+    player_positions: impl IntoIterator<Item = Vec3>,
+) -> bool {
+    let mut any_player = false;
+    for position in player_positions {
+        if !exit.contains(position) {
+            return false;
+        }
+        any_player = true;
+    }
+    any_player
+}
+
+/// Deterministic seed of the level that follows `seed` in a campaign.
+pub fn next_level_seed(seed: u64) -> u64 {
+    seed.wrapping_mul(6364136223846793005)
+        .wrapping_add(1442695040888963407)
+}
+
+/// Spawn position for player `index` out of `player_count`, on a ring around the spawn zone.
+pub fn player_spawn_position(index: usize, player_count: usize) -> Vec3 {
+    let angle = (index as f32) * std::f32::consts::TAU / player_count.max(1) as f32;
+    Vec3::new(
+        PLAYER_SPAWN_RADIUS * angle.cos(),
+        PLAYER_SPAWN_HEIGHT,
+        PLAYER_SPAWN_RADIUS * angle.sin(),
+    )
+}
+
+/// Spawn position for member `index` out of `team_size` of `team`. Each team gets its own
+/// ring, on opposite sides of the spawn zone.
+pub fn team_spawn_position(team: Team, index: usize, team_size: usize) -> Vec3 {
+    let side = match team {
+        Team::Red => -1.0,
+        Team::Blue => 1.0,
+    };
+    player_spawn_position(index, team_size) + Vec3::X * side * TEAM_SPAWN_OFFSET
+}
+
+/// Where a lobby player spawns: on its team's ring when it has a team, else on the shared
+/// ring around the spawn zone. The server spawns players there, and clients show their own
+/// player there while waiting for it.
+#[cfg(feature = "lobby")]
+pub fn lobby_spawn_point(lobby: &LobbyState, player_id: u64) -> (Vec3, Option<Team>) {
+    if let Some((team, index, team_size)) = team_slot(&lobby.players, &lobby.teams, player_id) {
+        return (team_spawn_position(team, index, team_size), Some(team));
+    }
+
+    let index = lobby
+        .players
+        .iter()
+        .position(|&id| id == player_id)
+        .unwrap_or(0);
+    (player_spawn_position(index, lobby.players.len()), None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        LEVEL_EXIT_RADIUS, LevelExit, level_exit_position, next_level_seed, player_spawn_position,
+        team_reached_exit, team_spawn_position,
+    };
+    use crate::components::team::Team;
+    use crate::level::generation::{LevelConfig, generate_level};
+    use bevy::prelude::Vec3;
+
+    #[test]
+    fn exit_is_placed_away_from_spawn_zone() {
+        let level = generate_level(LevelConfig::for_seed(42));
+        let exit = level_exit_position(&level).expect("generated level should have an exit");
+        let spawn = level
+            .get_zone(level.spawn_zone)
+            .expect("spawn zone should exist")
+            .position;
+
+        assert!(exit.distance(spawn) > LEVEL_EXIT_RADIUS);
+    }
+
+    #[test]
+    fn team_must_be_fully_inside_exit() {
+        let exit = LevelExit {
+            position: Vec3::new(10.0, 0.0, 10.0),
+            radius: LEVEL_EXIT_RADIUS,
+        };
+        let inside = Vec3::new(11.0, 1.0, 9.0);
+        let outside = Vec3::new(30.0, 1.0, 10.0);
+
+        assert!(team_reached_exit(&exit, [inside, inside]));
+        assert!(!team_reached_exit(&exit, [inside, outside]));
+        assert!(!team_reached_exit(&exit, []));
+    }
+
+    #[test]
+    fn next_level_seed_is_deterministic_and_changes() {
+        assert_eq!(next_level_seed(42), next_level_seed(42));
+        assert_ne!(next_level_seed(42), 42);
+    }
+
+    #[test]
+    fn teams_spawn_on_opposite_sides() {
+        for index in 0..3 {
+            assert!(team_spawn_position(Team::Red, index, 3).x < 0.0);
+            assert!(team_spawn_position(Team::Blue, index, 3).x > 0.0);
+        }
+    }
+
+    #[cfg(feature = "lobby")]
+    #[test]
+    fn lobby_players_spawn_on_their_team_ring_or_the_shared_one() {
+        use super::lobby_spawn_point;
+        use crate::protocol::LobbyState;
+
+        let lobby = LobbyState {
+            players: vec![1, 2, 3],
+            host_id: 1,
+            teams: [(1, Team::Red), (2, Team::Blue)].into_iter().collect(),
+            profiles: Default::default(),
+        };
+
+        assert_eq!(
+            lobby_spawn_point(&lobby, 2),
+            (team_spawn_position(Team::Blue, 0, 1), Some(Team::Blue))
+        );
+        assert_eq!(
+            lobby_spawn_point(&lobby, 3),
+            (player_spawn_position(2, 3), None)
+        );
+    }
+}
@@ -1,9 +1,10 @@
 use bevy::prelude::*;
 
 use crate::level::generation::{
-    LevelGraph, WALL_SIDE_EAST, WALL_SIDE_NORTH, WALL_SIDE_SOUTH, WALL_SIDE_WEST, WALL_THICKNESS,
-    Zone, ZoneId, ZoneType, collect_zone_wall_segments,
+    LevelGeometry, LevelGraph, WALL_SIDE_EAST, WALL_SIDE_NORTH, WALL_SIDE_SOUTH, WALL_SIDE_WEST,
+    WALL_THICKNESS, Zone, ZoneId, ZoneType, collect_zone_wall_segments,
 };
+use crate::level::platforms::{LADDER_COLOR, LEDGE_HEIGHT};
 
 #[derive(Component, Debug)]
 pub struct ZoneVisual {
@@ -34,6 +35,7 @@ fn spawn_zone_lighting(commands: &mut Commands, zone: &Zone) {
             ..default()
         },
         Transform::from_translation(zone.position + Vec3::new(0.0, zone.size.y * 0.4, 0.0)),
+        LevelGeometry,
         Name::new(format!("Light_Zone_{}", zone.id.0)),
     ));
 
@@ -60,6 +62,7 @@ fn spawn_zone_lighting(commands: &mut Commands, zone: &Zone) {
                     ..default()
                 },
                 Transform::from_translation(light_position),
+                LevelGeometry,
                 Name::new(format!("Haze_{}_Zone_{}", index, zone.id.0)),
             ));
         }
@@ -128,6 +131,7 @@ pub fn build_zone_visual(
         MeshMaterial3d(materials.add(base_material.clone())),
         Transform::from_translation(floor_position).with_rotation(zone.rotation),
         ZoneVisual { zone_id: zone.id },
+        LevelGeometry,
         Name::new(format!("Floor_Zone_{}", zone.id.0)),
     ));
 
@@ -137,6 +141,7 @@ pub fn build_zone_visual(
         Transform::from_translation(zone.position + Vec3::new(0.0, zone.size.y, 0.0))
             .with_rotation(zone.rotation),
         ZoneVisual { zone_id: zone.id },
+        LevelGeometry,
         Name::new(format!("Ceiling_Zone_{}", zone.id.0)),
     ));
 
@@ -193,6 +198,7 @@ pub fn build_zone_visual(
                 MeshMaterial3d(materials.add(wall_material.clone())),
                 Transform::from_translation(wall_position).with_rotation(zone.rotation),
                 ZoneVisual { zone_id: zone.id },
+                LevelGeometry,
                 Name::new(format!(
                     "Wall_{}_{}_Zone_{}",
                     side_name, segment_index, zone.id.0
@@ -229,6 +235,7 @@ pub fn build_level_visuals(
             brightness: 24.0,
             ..default()
         },
+        LevelGeometry,
         Name::new("ProceduralAmbientLight"),
     ));
 
@@ -242,5 +249,40 @@ pub fn build_level_visuals(
         );
     }
 
+    for elevator in &level_graph.elevators {
+        let material = level_graph
+            .get_zone(elevator.zone)
+            .map_or_else(StandardMaterial::default, |zone| {
+                zone_surface_material(zone.zone_type)
+            });
+        commands.spawn((
+            Mesh3d(meshes.add(Cuboid::from_size(elevator.ledge_size))),
+            MeshMaterial3d(materials.add(material)),
+            Transform::from_translation(elevator.ledge_center).with_rotation(elevator.rotation),
+            ZoneVisual {
+                zone_id: elevator.zone,
+            },
+            LevelGeometry,
+            Name::new(format!("Ledge_Zone_{}", elevator.zone.0)),
+        ));
+
+        // Rungs flat against the ledge, at the back of the ladder volume.
+        let (ladder_center, ladder_size) = elevator.ladder();
+        let rungs = Vec3::new(0.1, LEDGE_HEIGHT, ladder_size.z * 0.6);
+        let rungs_center = (ladder_center
+            + elevator.rotation * (Vec3::X * (ladder_size.x - rungs.x) * 0.5))
+            .with_y(ladder_center.y - ladder_size.y * 0.5 + LEDGE_HEIGHT * 0.5);
+        commands.spawn((
+            Mesh3d(meshes.add(Cuboid::from_size(rungs))),
+            MeshMaterial3d(materials.add(LADDER_COLOR)),
+            Transform::from_translation(rungs_center).with_rotation(elevator.rotation),
+            ZoneVisual {
+                zone_id: elevator.zone,
+            },
+            LevelGeometry,
+            Name::new(format!("Ladder_Zone_{}", elevator.zone.0)),
+        ));
+    }
+
     info!("Level visuals built successfully");
 }
@@ -1,12 +1,37 @@
+pub mod afk;
+pub mod ai;
+pub mod aim_assist;
+pub mod balance;
+pub mod balance_sim;
+pub mod bot_dialogue;
+pub mod bot_policy;
+pub mod bots;
+pub mod clock;
 pub mod components;
+pub mod composition;
+pub mod cover;
+pub mod cpu_profile;
 pub mod debug;
+pub mod demo;
+pub mod director;
 pub mod entities;
+pub mod game_math;
 pub mod gym;
+pub mod hearing;
 pub mod inputs;
 pub mod level;
+pub mod manifest;
+pub mod match_analytics;
+pub mod match_recap;
 pub mod navigation;
+pub mod navigation_pathfinding;
+pub mod observation;
+pub mod perception;
 pub mod protocol;
 pub mod render;
+pub mod resync;
+pub mod squads;
+pub mod tick_trace;
 
 use avian3d::collision::CollisionDiagnostics;
 use avian3d::dynamics::solver::SolverDiagnostics;
@@ -28,11 +53,9 @@ pub const SERVER_BIND_ADDR: SocketAddr = SocketAddr::new(
 );
 pub struct SharedSettings {
     pub private_key: [u8; 32],
-    pub protocol_id: u64,
 }
 pub const SHARED_SETTINGS: SharedSettings = SharedSettings {
     private_key: [0u8; 32], // dummy 32-byte key
-    protocol_id: 42,
 };
 pub const SERVER_ADDR: SocketAddr = SocketAddr::new(
     std::net::IpAddr::V4(std::net::Ipv4Addr::new(127, 0, 0, 1)),
@@ -54,6 +77,7 @@ pub struct GymMode(pub bool);
 pub struct SharedPlugin;
 impl Plugin for SharedPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
+        clock::ensure_game_clock(app);
         app.add_plugins(SharedInputPlugin);
         app.add_plugins(protocol::ProtocolPlugin);
         // Add diagnostics plugin and resource first so required resources exist
@@ -64,8 +88,14 @@ impl Plugin for SharedPlugin {
         app.add_plugins(PhysicsPlugins::default());
         app.add_plugins(VleueNavigatorPlugin);
         app.add_plugins(NavmeshUpdaterPlugin::<Collider, NavigationObstacle>::default());
+        app.add_plugins(level::platforms::MovingPlatformPlugin);
+        app.add_plugins(level::interactive::InteractivePlugin);
+        app.add_plugins(level::preload::LevelPreloadPlugin);
         app.add_plugins(navigation::NavigationPlugin);
+        app.add_plugins(navigation_pathfinding::NavMeshBakingPlugin);
         app.add_plugins(components::health::HealthPlugin);
         app.add_plugins(components::weapons::WeaponsPlugin);
+        app.add_plugins(components::destructible::DebrisPlugin);
+        app.add_plugins(components::world_items::WorldItemsPlugin);
     }
 }
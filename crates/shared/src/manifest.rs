@@ -0,0 +1,376 @@
+//! Machine-readable description of the network protocol, collected from the registrations
+//! in `protocol/` themselves (see `sub_protocol!`) and the serde formats of the types their
+//! messages and inputs are made of. External tools (the Python SDK in `python/`,
+//! dashboards) can read it with `launcher manifest`.
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_reflection::{ContainerFormat, Format, Named, Tracer, TracerConfig, VariantFormat};
+use std::sync::OnceLock;
+
+use crate::protocol::{Delivery, describe_protocol};
+
+/// Every input, channel, component and message registered by `ProtocolPlugin`, in
+/// registration order, and the fields of the types messages and inputs are made of.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct ProtocolManifest {
+    pub protocols: Vec<&'static str>,
+    pub inputs: Vec<InputEntry>,
+    pub channels: Vec<ChannelEntry>,
+    pub components: Vec<ComponentEntry>,
+    pub messages: Vec<MessageEntry>,
+    /// By name, types from other crates (`Vec3`, `PeerId`, ...) included.
+    pub types: Vec<TypeEntry>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct InputEntry {
+    pub name: &'static str,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ChannelEntry {
+    pub name: &'static str,
+    pub mode: String,
+    pub direction: &'static str,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ComponentEntry {
+    pub name: &'static str,
+    pub prediction: bool,
+    pub interpolation: bool,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct MessageEntry {
+    pub name: &'static str,
+    pub direction: &'static str,
+}
+
+/// A serde type, with its fields in the order they are encoded.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct TypeEntry {
+    pub name: String,
+    #[serde(flatten)]
+    pub def: TypeDef,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TypeDef {
+    Struct { fields: Vec<FieldEntry> },
+    Enum { variants: Vec<VariantEntry> },
+}
+
+/// A field; tuple fields are named `0`, `1`, ... Types are written the Rust way, such as
+/// `Option<String>`, `HashMap<u64, Team>` or `[f32; 3]`.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct FieldEntry {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct VariantEntry {
+    pub name: String,
+    pub fields: Vec<FieldEntry>,
+}
+
+/// Collects a [`ProtocolManifest`] as the sub-protocols describe what they register.
+pub struct ManifestBuilder {
+    manifest: ProtocolManifest,
+    tracer: Tracer,
+}
+
+impl ManifestBuilder {
+    fn new() -> Self {
+        Self {
+            manifest: ProtocolManifest::default(),
+            tracer: Tracer::new(TracerConfig::default()),
+        }
+    }
+
+    pub fn protocol(&mut self, name: &'static str) {
+        self.manifest.protocols.push(name);
+    }
+
+    pub fn input<A: DeserializeOwned>(&mut self, name: &'static str) {
+        self.manifest.inputs.push(InputEntry { name });
+        self.trace::<A>(name);
+    }
+
+    /// `calls` are the registration calls chained on the component, such as
+    /// `add_prediction`.
+    pub fn component(&mut self, name: &'static str, calls: &[&str]) {
+        self.manifest.components.push(ComponentEntry {
+            name,
+            prediction: calls.contains(&"add_prediction"),
+            interpolation: calls.iter().any(|call| call.contains("interpolation")),
+        });
+    }
+
+    pub fn channel(&mut self, name: &'static str, delivery: Delivery, direction: &'static str) {
+        self.manifest.channels.push(ChannelEntry {
+            name,
+            mode: format!("{:?}", delivery),
+            direction,
+        });
+    }
+
+    pub fn message<M: DeserializeOwned>(&mut self, name: &'static str, direction: &'static str) {
+        self.manifest
+            .messages
+            .push(MessageEntry { name, direction });
+        self.trace::<M>(name);
+    }
+
+    fn trace<T: DeserializeOwned>(&mut self, name: &str) {
+        if let Err(e) = self.tracer.trace_simple_type::<T>() {
+            panic!("failed to trace the serde format of {}: {}", name, e);
+        }
+    }
+
+    fn finish(self) -> ProtocolManifest {
+        let registry = self
+            .tracer
+            .registry()
+            .unwrap_or_else(|e| panic!("incomplete serde formats in the protocol: {}", e));
+        ProtocolManifest {
+            types: registry
+                .into_iter()
+                .map(|(name, container)| TypeEntry {
+                    name,
+                    def: type_def(&container),
+                })
+                .collect(),
+            ..self.manifest
+        }
+    }
+}
+
+fn type_def(container: &ContainerFormat) -> TypeDef {
+    let fields = match container {
+        ContainerFormat::UnitStruct => Vec::new(),
+        ContainerFormat::NewTypeStruct(format) => tuple_fields(std::slice::from_ref(format)),
+        ContainerFormat::TupleStruct(formats) => tuple_fields(formats),
+        ContainerFormat::Struct(fields) => named_fields(fields),
+        ContainerFormat::Enum(variants) => {
+            return TypeDef::Enum {
+                variants: variants
+                    .values()
+                    .map(|variant| VariantEntry {
+                        name: variant.name.clone(),
+                        fields: variant_fields(&variant.value),
+                    })
+                    .collect(),
+            };
+        }
+    };
+    TypeDef::Struct { fields }
+}
+
+fn variant_fields(variant: &VariantFormat) -> Vec<FieldEntry> {
+    match variant {
+        VariantFormat::Variable(_) | VariantFormat::Unit => Vec::new(),
+        VariantFormat::NewType(format) => tuple_fields(std::slice::from_ref(format)),
+        VariantFormat::Tuple(formats) => tuple_fields(formats),
+        VariantFormat::Struct(fields) => named_fields(fields),
+    }
+}
+
+fn tuple_fields(formats: &[Format]) -> Vec<FieldEntry> {
+    formats
+        .iter()
+        .enumerate()
+        .map(|(index, format)| FieldEntry {
+            name: index.to_string(),
+            ty: rust_type(format),
+        })
+        .collect()
+}
+
+fn named_fields(fields: &[Named<Format>]) -> Vec<FieldEntry> {
+    fields
+        .iter()
+        .map(|field| FieldEntry {
+            name: field.name.clone(),
+            ty: rust_type(&field.value),
+        })
+        .collect()
+}
+
+/// `format` as a Rust type that encodes the same way.
+fn rust_type(format: &Format) -> String {
+    let list = |formats: &[Format]| formats.iter().map(rust_type).collect::<Vec<_>>().join(", ");
+    match format {
+        Format::Variable(_) => "_".to_string(),
+        Format::TypeName(name) => name.clone(),
+        Format::Unit => "()".to_string(),
+        Format::Bool => "bool".to_string(),
+        Format::I8 => "i8".to_string(),
+        Format::I16 => "i16".to_string(),
+        Format::I32 => "i32".to_string(),
+        Format::I64 => "i64".to_string(),
+        Format::I128 => "i128".to_string(),
+        Format::U8 => "u8".to_string(),
+        Format::U16 => "u16".to_string(),
+        Format::U32 => "u32".to_string(),
+        Format::U64 => "u64".to_string(),
+        Format::U128 => "u128".to_string(),
+        Format::F32 => "f32".to_string(),
+        Format::F64 => "f64".to_string(),
+        Format::Char => "char".to_string(),
+        Format::Str => "String".to_string(),
+        Format::Bytes => "Vec<u8>".to_string(),
+        Format::Option(inner) => format!("Option<{}>", rust_type(inner)),
+        Format::Seq(inner) => format!("Vec<{}>", rust_type(inner)),
+        Format::Map { key, value } => {
+            format!("HashMap<{}, {}>", rust_type(key), rust_type(value))
+        }
+        Format::Tuple(formats) => format!("({})", list(formats)),
+        Format::TupleArray { content, size } => format!("[{}; {}]", rust_type(content), size),
+    }
+}
+
+/// The manifest of the protocol this build registers, with its features.
+pub fn protocol_manifest() -> &'static ProtocolManifest {
+    static MANIFEST: OnceLock<ProtocolManifest> = OnceLock::new();
+    MANIFEST.get_or_init(|| {
+        let mut builder = ManifestBuilder::new();
+        describe_protocol(&mut builder);
+        builder.finish()
+    })
+}
+
+/// The manifest as JSON, as `launcher manifest` prints it.
+pub fn protocol_manifest_json() -> &'static str {
+    static JSON: OnceLock<String> = OnceLock::new();
+    JSON.get_or_init(|| {
+        let mut json =
+            serde_json::to_string_pretty(protocol_manifest()).expect("the manifest is JSON");
+        json.push('\n');
+        json
+    })
+}
+
+/// Hash of the manifest, used as the netcode protocol id so a client and server built from
+/// different protocols, or sending messages with different fields, refuse to connect.
+pub fn protocol_hash() -> u64 {
+    fnv1a_64(protocol_manifest_json().as_bytes())
+}
+
+/// 64-bit FNV-1a, usable in const context and stable across platforms and Rust versions.
+pub const fn fnv1a_64(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut index = 0;
+    while index < bytes.len() {
+        hash ^= bytes[index] as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        index += 1;
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ChannelEntry, ComponentEntry, FieldEntry, InputEntry, MessageEntry, TypeDef, fnv1a_64,
+        protocol_hash, protocol_manifest, protocol_manifest_json,
+    };
+
+    fn type_def(name: &str) -> &'static TypeDef {
+        &protocol_manifest()
+            .types
+            .iter()
+            .find(|entry| entry.name == name)
+            .unwrap_or_else(|| panic!("{} is not described", name))
+            .def
+    }
+
+    fn field(name: &str, ty: &str) -> FieldEntry {
+        FieldEntry {
+            name: name.to_string(),
+            ty: ty.to_string(),
+        }
+    }
+
+    #[test]
+    fn manifest_lists_registered_protocol_items() {
+        let manifest = protocol_manifest();
+        assert_eq!(manifest.protocols[0], "core");
+        assert!(manifest.inputs.contains(&InputEntry {
+            name: "PlayerAction"
+        }));
+        assert!(manifest.components.contains(&ComponentEntry {
+            name: "Position",
+            prediction: true,
+            interpolation: true,
+        }));
+        assert!(manifest.components.contains(&ComponentEntry {
+            name: "GroundState",
+            prediction: false,
+            interpolation: false,
+        }));
+        assert!(manifest.channels.contains(&ChannelEntry {
+            name: "LobbyControlChannel",
+            mode: "OrderedReliable".to_string(),
+            direction: "Bidirectional",
+        }));
+        assert!(manifest.messages.contains(&MessageEntry {
+            name: "ResyncRequest",
+            direction: "ClientToServer",
+        }));
+    }
+
+    #[cfg(feature = "lobby")]
+    #[test]
+    fn manifest_lists_lobby_messages() {
+        assert!(protocol_manifest().messages.contains(&MessageEntry {
+            name: "StartLoadingGameEvent",
+            direction: "ServerToClient",
+        }));
+    }
+
+    #[cfg(all(feature = "combat", feature = "lobby"))]
+    #[test]
+    fn manifest_describes_message_types() {
+        assert_eq!(
+            *type_def("KillFeedEvent"),
+            TypeDef::Struct {
+                fields: vec![
+                    field("killer", "Option<String>"),
+                    field("victim", "String"),
+                    field("headshot", "bool"),
+                ],
+            }
+        );
+        // Types messages are made of are described too, wherever they are declared.
+        let TypeDef::Enum { variants } = type_def("Team") else {
+            panic!("Team is an enum");
+        };
+        let names: Vec<&str> = variants.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(names, ["Red", "Blue"]);
+        assert!(matches!(type_def("PlayerAction"), TypeDef::Enum { .. }));
+        assert!(protocol_manifest().channels.contains(&ChannelEntry {
+            name: "VoiceChannel",
+            mode: "UnorderedUnreliable".to_string(),
+            direction: "Bidirectional",
+        }));
+        assert!(
+            protocol_manifest_json().contains(r#""kind": "struct""#),
+            "types are tagged by kind"
+        );
+    }
+
+    #[test]
+    fn protocol_hash_matches_manifest() {
+        assert_eq!(fnv1a_64(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a_64(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(
+            protocol_hash(),
+            fnv1a_64(protocol_manifest_json().as_bytes())
+        );
+    }
+}
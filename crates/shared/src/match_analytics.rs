@@ -0,0 +1,196 @@
+//! Heatmaps over recorded match telemetry: where players die, where they take damage and
+//! which ways they walk. Level designers compare them across generator settings to see
+//! what a layout does to the fights.
+
+use bevy::prelude::Vec3;
+use std::collections::HashMap;
+
+use crate::match_recap::MatchTelemetry;
+
+/// Side of a heatmap cell, in meters.
+pub const HEATMAP_CELL_SIZE: f32 = 2.0;
+
+/// A cell of a heatmap and what accumulated in it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HeatCell {
+    /// Middle of the cell, at the mean height of what was added to it.
+    pub center: Vec3,
+    pub value: f32,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct CellTotals {
+    value: f32,
+    height_sum: f32,
+    samples: u32,
+}
+
+/// Values summed on a grid of square cells over the floor.
+#[derive(Clone, Debug)]
+pub struct Heatmap {
+    pub cell_size: f32,
+    cells: HashMap<(i32, i32), CellTotals>,
+}
+
+impl Heatmap {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+        }
+    }
+
+    fn cell_of(&self, position: Vec3) -> (i32, i32) {
+        (
+            (position.x / self.cell_size).floor() as i32,
+            (position.z / self.cell_size).floor() as i32,
+        )
+    }
+
+    pub fn add(&mut self, position: Vec3, value: f32) {
+        let cell = self.cells.entry(self.cell_of(position)).or_default();
+        cell.value += value;
+        cell.height_sum += position.y;
+        cell.samples += 1;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Value of the hottest cell, 0 when empty.
+    pub fn max(&self) -> f32 {
+        self.cells
+            .values()
+            .map(|cell| cell.value)
+            .fold(0.0, f32::max)
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = HeatCell> + '_ {
+        self.cells.iter().map(|(&(x, z), cell)| HeatCell {
+            center: Vec3::new(
+                (x as f32 + 0.5) * self.cell_size,
+                cell.height_sum / cell.samples as f32,
+                (z as f32 + 0.5) * self.cell_size,
+            ),
+            value: cell.value,
+        })
+    }
+
+    /// The `count` hottest cells, hottest first.
+    pub fn hottest(&self, count: usize) -> Vec<HeatCell> {
+        let mut cells: Vec<_> = self.cells().collect();
+        cells.sort_by(|a, b| b.value.total_cmp(&a.value));
+        cells.truncate(count);
+        cells
+    }
+}
+
+/// The heatmaps of one or more matches.
+#[derive(Clone, Debug)]
+pub struct MatchHeatmaps {
+    /// One per kill, where the victim died.
+    pub deaths: Heatmap,
+    /// Damage taken, in hit points.
+    pub damage: Heatmap,
+    /// One per player walking into a cell, so common paths stand out.
+    pub traffic: Heatmap,
+}
+
+impl MatchHeatmaps {
+    pub fn from_telemetry<'a>(
+        matches: impl IntoIterator<Item = &'a MatchTelemetry>,
+        cell_size: f32,
+    ) -> Self {
+        let mut heatmaps = Self {
+            deaths: Heatmap::new(cell_size),
+            damage: Heatmap::new(cell_size),
+            traffic: Heatmap::new(cell_size),
+        };
+        for telemetry in matches {
+            for position in telemetry.kills.iter().filter_map(|kill| kill.position) {
+                heatmaps.deaths.add(position, 1.0);
+            }
+            for damage in &telemetry.damage {
+                heatmaps.damage.add(damage.position, damage.amount);
+            }
+            for segment in telemetry.tracks.iter().flat_map(|track| &track.segments) {
+                heatmaps.add_walk(segment);
+            }
+        }
+        heatmaps
+    }
+
+    /// Counts every cell a walk through `points` enters, including the cells crossed
+    /// between two samples.
+    fn add_walk(&mut self, points: &[Vec3]) {
+        let step = self.traffic.cell_size * 0.5;
+        let mut last_cell = None;
+        let mut enter = |traffic: &mut Heatmap, position: Vec3| {
+            let cell = traffic.cell_of(position);
+            if last_cell != Some(cell) {
+                last_cell = Some(cell);
+                traffic.add(position, 1.0);
+            }
+        };
+
+        if let Some(first) = points.first() {
+            enter(&mut self.traffic, *first);
+        }
+        for pair in points.windows(2) {
+            let steps = (pair[0].distance(pair[1]) / step).ceil().max(1.0) as usize;
+            for index in 1..=steps {
+                enter(
+                    &mut self.traffic,
+                    pair[0].lerp(pair[1], index as f32 / steps as f32),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MatchHeatmaps;
+    use crate::match_recap::{DamageRecord, KillRecord, MatchTelemetry, PlayerTrack};
+    use bevy::prelude::Vec3;
+
+    #[test]
+    fn heatmaps_sum_events_per_cell_and_count_walks_once_per_cell() {
+        let kill = |x: f32| KillRecord {
+            at_secs: 0.0,
+            killer: None,
+            victim: "Bot_1".to_string(),
+            headshot: false,
+            position: Some(Vec3::new(x, 0.0, 1.0)),
+        };
+        let telemetry = MatchTelemetry {
+            kills: vec![kill(0.5), kill(1.5), kill(9.0)],
+            damage: vec![DamageRecord {
+                at_secs: 0.0,
+                position: Vec3::new(0.5, 2.0, 0.5),
+                amount: 30.0,
+            }],
+            tracks: vec![PlayerTrack {
+                name: "Player_1".to_string(),
+                // Lingering in the first cell, then one long stride across four more.
+                segments: vec![vec![
+                    Vec3::new(0.5, 0.0, 0.5),
+                    Vec3::new(1.0, 0.0, 0.5),
+                    Vec3::new(9.5, 0.0, 0.5),
+                ]],
+            }],
+            ..Default::default()
+        };
+
+        let heatmaps = MatchHeatmaps::from_telemetry([&telemetry, &telemetry], 2.0);
+        let hottest = heatmaps.deaths.hottest(1)[0];
+        assert_eq!(hottest.value, 4.0);
+        assert_eq!(hottest.center, Vec3::new(1.0, 0.0, 1.0));
+        assert_eq!(heatmaps.deaths.max(), 4.0);
+        assert_eq!(heatmaps.damage.hottest(1)[0].center.y, 2.0);
+
+        assert_eq!(heatmaps.traffic.cells().count(), 5);
+        assert!(heatmaps.traffic.cells().all(|cell| cell.value == 2.0));
+    }
+}
@@ -0,0 +1,335 @@
+//! Post-match narrative recap. The server records a light telemetry of the match (kills with
+//! their time, final standings) and turns it into a couple of sentences for the results
+//! screen, either with a language model or with the template here.
+//!
+//! The telemetry also keeps where things happened: kill and damage positions, and each
+//! player's path. Saved match records carry it as RON for the analytics viewer.
+
+use bevy::prelude::Vec3;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Seconds between two samples of a player's position.
+pub const TRACK_SAMPLE_SECS: f32 = 0.5;
+/// Kills this close together count as one streak.
+pub const STREAK_WINDOW_SECS: f32 = 10.0;
+/// Shortest streak worth a sentence.
+pub const STREAK_MIN_KILLS: usize = 3;
+
+/// One kill, `at_secs` after the match started. Names are display names only.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct KillRecord {
+    pub at_secs: f32,
+    pub killer: Option<String>,
+    pub victim: String,
+    pub headshot: bool,
+    /// Where the victim died.
+    #[serde(default)]
+    pub position: Option<Vec3>,
+}
+
+/// Damage a character took, `at_secs` after the match started, where it stood.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DamageRecord {
+    pub at_secs: f32,
+    pub position: Vec3,
+    pub amount: f32,
+}
+
+/// Where a player was, sampled every `TRACK_SAMPLE_SECS` while alive.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct PlayerTrack {
+    pub name: String,
+    /// Runs of consecutive samples; a new one starts after every respawn.
+    pub segments: Vec<Vec<Vec3>>,
+}
+
+/// A player's final line on the scoreboard.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Standing {
+    pub name: String,
+    pub kills: u32,
+    pub deaths: u32,
+    pub assists: u32,
+}
+
+/// Everything the recap is written from.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct MatchTelemetry {
+    pub seed: u64,
+    pub duration_secs: f32,
+    pub kills: Vec<KillRecord>,
+    /// Best first.
+    pub standings: Vec<Standing>,
+    #[serde(default)]
+    pub damage: Vec<DamageRecord>,
+    #[serde(default)]
+    pub tracks: Vec<PlayerTrack>,
+}
+
+/// Sent once the recap of the finished match is ready.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MatchRecapEvent {
+    pub text: String,
+}
+
+/// A run of kills by one player, each within `STREAK_WINDOW_SECS` of the previous one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KillStreak {
+    pub killer: String,
+    pub kills: usize,
+    pub started_at_secs: f32,
+}
+
+impl MatchTelemetry {
+    pub fn to_ron(&self) -> Result<String, String> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, String> {
+        ron::from_str(text).map_err(|e| e.to_string())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Self::from_ron(&text)
+    }
+
+    /// The longest kill streak of the match, the earliest one on ties.
+    pub fn best_streak(&self) -> Option<KillStreak> {
+        let mut best: Option<KillStreak> = None;
+        let mut current: Option<(KillStreak, f32)> = None;
+        for kill in &self.kills {
+            let Some(killer) = &kill.killer else {
+                continue;
+            };
+            current = match current {
+                Some((mut streak, last_at))
+                    if streak.killer == *killer && kill.at_secs - last_at <= STREAK_WINDOW_SECS =>
+                {
+                    streak.kills += 1;
+                    Some((streak, kill.at_secs))
+                }
+                _ => Some((
+                    KillStreak {
+                        killer: killer.clone(),
+                        kills: 1,
+                        started_at_secs: kill.at_secs,
+                    },
+                    kill.at_secs,
+                )),
+            };
+            if let Some((streak, _)) = &current
+                && best.as_ref().is_none_or(|best| streak.kills > best.kills)
+            {
+                best = Some(streak.clone());
+            }
+        }
+        best
+    }
+
+    /// "early", "mid-round" or "late" for a time in the match.
+    pub fn phase_of(&self, at_secs: f32) -> &'static str {
+        let progress = if self.duration_secs > 0.0 {
+            at_secs / self.duration_secs
+        } else {
+            0.0
+        };
+        if progress < 1.0 / 3.0 {
+            "early"
+        } else if progress < 2.0 / 3.0 {
+            "mid-round"
+        } else {
+            "late"
+        }
+    }
+
+    /// A recap from the numbers alone: the winner, the best streak and the sharpest shooter.
+    pub fn template_recap(&self) -> String {
+        let Some(winner) = self.standings.first() else {
+            return "Nobody showed up for this one.".to_string();
+        };
+        if self.kills.is_empty() {
+            return "A tense standoff: nobody landed a kill.".to_string();
+        }
+
+        let mut sentences = vec![format!(
+            "{} took the match with {} {} and {} {}.",
+            winner.name,
+            winner.kills,
+            plural(winner.kills as usize, "kill", "kills"),
+            winner.deaths,
+            plural(winner.deaths as usize, "death", "deaths"),
+        )];
+
+        if let Some(streak) = self.best_streak()
+            && streak.kills >= STREAK_MIN_KILLS
+        {
+            sentences.push(format!(
+                "{} dominated {} with {} quick kills.",
+                streak.killer,
+                match self.phase_of(streak.started_at_secs) {
+                    "early" => "the opening minutes",
+                    "mid-round" => "mid-round",
+                    _ => "the closing stretch",
+                },
+                streak.kills
+            ));
+        }
+
+        let headshots = self.kills.iter().filter(|kill| kill.headshot).count();
+        if headshots > 0 {
+            sentences.push(format!(
+                "{} of {} kills were headshots.",
+                headshots,
+                self.kills.len()
+            ));
+        }
+        sentences.join(" ")
+    }
+
+    /// Prompt for a language model: the facts, then the kind of recap wanted.
+    pub fn recap_prompt(&self) -> String {
+        let mut prompt = String::from(
+            "You are a sports commentator. Write a two sentence recap of this multiplayer \
+             shooter match. Only use the facts below; do not invent events.\n\n",
+        );
+        prompt.push_str(&format!(
+            "Match length: {:.0} seconds.\n",
+            self.duration_secs
+        ));
+        prompt.push_str("Final standings (kills/deaths/assists):\n");
+        for standing in &self.standings {
+            prompt.push_str(&format!(
+                "- {}: {}/{}/{}\n",
+                standing.name, standing.kills, standing.deaths, standing.assists
+            ));
+        }
+        prompt.push_str("Kills:\n");
+        for kill in &self.kills {
+            prompt.push_str(&format!(
+                "- {:.0}s ({}): {} killed {}{}\n",
+                kill.at_secs,
+                self.phase_of(kill.at_secs),
+                kill.killer.as_deref().unwrap_or("the environment"),
+                kill.victim,
+                if kill.headshot {
+                    " with a headshot"
+                } else {
+                    ""
+                }
+            ));
+        }
+        prompt.push_str("\nRecap:");
+        prompt
+    }
+}
+
+fn plural(count: usize, one: &'static str, many: &'static str) -> &'static str {
+    if count == 1 { one } else { many }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DamageRecord, KillRecord, MatchTelemetry, PlayerTrack, Standing};
+    use bevy::prelude::Vec3;
+
+    fn kill(at_secs: f32, killer: &str, victim: &str, headshot: bool) -> KillRecord {
+        KillRecord {
+            at_secs,
+            killer: Some(killer.to_string()),
+            victim: victim.to_string(),
+            headshot,
+            position: None,
+        }
+    }
+
+    fn standing(name: &str, kills: u32, deaths: u32) -> Standing {
+        Standing {
+            name: name.to_string(),
+            kills,
+            deaths,
+            assists: 0,
+        }
+    }
+
+    fn telemetry() -> MatchTelemetry {
+        MatchTelemetry {
+            seed: 42,
+            duration_secs: 300.0,
+            kills: vec![
+                kill(20.0, "Player_1", "Player_2", false),
+                kill(130.0, "Player_2", "Player_1", true),
+                kill(134.0, "Player_2", "Bot_3", false),
+                kill(141.0, "Player_2", "Player_1", true),
+            ],
+            standings: vec![standing("Player_2", 3, 1), standing("Player_1", 1, 2)],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn streaks_need_kills_close_together() {
+        let streak = telemetry().best_streak().unwrap();
+        assert_eq!(streak.killer, "Player_2");
+        assert_eq!(streak.kills, 3);
+        assert_eq!(streak.started_at_secs, 130.0);
+
+        let mut spread = telemetry();
+        spread.kills[3].at_secs = 200.0;
+        assert_eq!(spread.best_streak().unwrap().kills, 2);
+    }
+
+    #[test]
+    fn template_recap_tells_the_match_from_the_numbers() {
+        assert_eq!(
+            telemetry().template_recap(),
+            "Player_2 took the match with 3 kills and 1 death. \
+             Player_2 dominated mid-round with 3 quick kills. 2 of 4 kills were headshots."
+        );
+
+        let mut quiet = telemetry();
+        quiet.kills.clear();
+        assert_eq!(
+            quiet.template_recap(),
+            "A tense standoff: nobody landed a kill."
+        );
+        assert_eq!(
+            MatchTelemetry::default().template_recap(),
+            "Nobody showed up for this one."
+        );
+    }
+
+    #[test]
+    fn prompt_lists_only_recorded_facts() {
+        let prompt = telemetry().recap_prompt();
+        assert!(prompt.contains("- Player_2: 3/1/0"));
+        assert!(prompt.contains("- 130s (mid-round): Player_2 killed Player_1 with a headshot"));
+        assert!(prompt.ends_with("Recap:"));
+    }
+
+    #[test]
+    fn telemetry_round_trips_through_ron_and_old_records_still_load() {
+        let mut telemetry = telemetry();
+        telemetry.kills[0].position = Some(Vec3::new(4.0, 1.0, -2.0));
+        telemetry.damage.push(DamageRecord {
+            at_secs: 19.5,
+            position: Vec3::new(4.0, 1.0, -2.0),
+            amount: 25.0,
+        });
+        telemetry.tracks.push(PlayerTrack {
+            name: "Player_1".to_string(),
+            segments: vec![vec![Vec3::ZERO, Vec3::X]],
+        });
+        let text = telemetry.to_ron().unwrap();
+        assert_eq!(MatchTelemetry::from_ron(&text).unwrap(), telemetry);
+
+        let old = MatchTelemetry::from_ron(
+            "(seed: 3, duration_secs: 10.0, kills: [(at_secs: 1.0, killer: None, victim: \"Bot_1\", headshot: false)], standings: [])",
+        )
+        .unwrap();
+        assert_eq!(old.kills[0].position, None);
+        assert!(old.damage.is_empty() && old.tracks.is_empty());
+    }
+}
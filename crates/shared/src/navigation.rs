@@ -6,16 +6,124 @@ use serde::{Deserialize, Serialize};
 use std::ops::Deref;
 use vleue_navigator::prelude::{ManagedNavMesh, NavMesh, NavMeshStatus};
 
+use crate::bot_policy::PolicyControlled;
+use crate::clock::{GameClock, ensure_game_clock};
+use crate::game_math::yaw_facing;
+use crate::level::interactive::Door;
+use crate::level::platforms::{MovingPlatform, PLATFORM_SIZE};
+
 #[derive(Component, Clone, Debug)]
 pub struct NavigationObstacle;
 
+/// Targets this much higher or lower than an agent are on another floor, which the flat
+/// navmesh cannot reach.
+const NAVIGATION_LEVEL_STEP: f32 = 2.0;
+/// An agent that moves less than this toward its waypoint in [`STUCK_SECS`] is stuck.
+const STUCK_DISTANCE: f32 = 0.5;
+const STUCK_SECS: f32 = 1.5;
+/// Path refreshes a stuck agent gets before it gives up on its target.
+const MAX_STUCK_REPATHS: u32 = 2;
+/// Points sampled around an agent for a patrol generated on the navmesh.
+const NAVMESH_PATROL_SAMPLES: usize = 8;
+/// Most points kept in a generated patrol.
+const NAVMESH_PATROL_POINTS: usize = 4;
+/// Radius of the patrols generated for bots spawned without a route.
+pub const DEFAULT_NAVMESH_PATROL_RADIUS: f32 = 12.0;
+/// How far in front of a door's face agents stand to work it.
+const DOOR_STANDOFF: f32 = 0.75;
+
+/// Timed off-mesh link on a moving platform, carrying agents between `lower` and `upper`.
+#[derive(Component, Clone, Debug)]
+pub struct NavigationLink {
+    pub lower: Vec3,
+    pub upper: Vec3,
+}
+
+/// Off-mesh link through a door, which the navmesh does not know about. Agents whose path
+/// runs through the door while it is closed work it from the side they come from, or from
+/// `button` for doors worked from a button, and wait for it to open.
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct DoorLink {
+    /// Where agents stand in front of either face of the door.
+    pub sides: [Vec3; 2],
+    pub button: Option<Vec3>,
+}
+
+impl DoorLink {
+    pub fn through(door: &Door, button: Option<Vec3>) -> Self {
+        let half_size = door.size * 0.5;
+        // Doors are walked through along their thinner side.
+        let offset = if door.size.x < door.size.z {
+            Vec3::X * (half_size.x + DOOR_STANDOFF)
+        } else {
+            Vec3::Z * (half_size.z + DOOR_STANDOFF)
+        };
+        Self {
+            sides: [door.closed_at - offset, door.closed_at + offset],
+            button,
+        }
+    }
+
+    /// Where an agent at `from` works the door.
+    pub fn use_point(&self, from: Vec3) -> Vec3 {
+        self.button.unwrap_or_else(|| {
+            let [a, b] = self.sides;
+            if planar_distance(from, a) <= planar_distance(from, b) {
+                a
+            } else {
+                b
+            }
+        })
+    }
+}
+
+/// Walks an agent straight to a point and keeps it there, ahead of its path, while
+/// something has to happen first, such as a door opening. Its target and path are kept for
+/// when the detour is removed, and it is not counted as stuck meanwhile.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct NavigationDetour(pub Vec3);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TransitStage {
+    /// Walking to the stop the platform leaves from.
+    Approach,
+    /// Waiting at the stop for the platform to dock.
+    Wait,
+    /// Stepping onto the docked platform.
+    Board,
+    /// Standing on the platform until it docks at the other end.
+    Ride,
+    /// Walking off to the stop at the other end.
+    Alight,
+}
+
+/// An agent on its way to another floor through a `NavigationLink`. Its navigation target
+/// is borrowed for each leg of the trip and restored at the end.
+#[derive(Component, Clone, Debug)]
+pub struct PlatformTransit {
+    link: Entity,
+    final_target: Vec3,
+    going_up: bool,
+    stage: TransitStage,
+}
+
 pub struct NavigationPlugin;
 
 impl Plugin for NavigationPlugin {
     fn build(&self, app: &mut App) {
+        ensure_game_clock(app);
         app.add_systems(
             Update,
-            (patrol_system, refresh_navigation_paths, movement_system).chain(),
+            (
+                generate_navmesh_patrols,
+                patrol_system,
+                plan_platform_transits,
+                drive_platform_transits,
+                refresh_navigation_paths,
+                movement_system,
+                detect_stuck_agents,
+            )
+                .chain(),
         );
     }
 }
@@ -32,6 +140,11 @@ pub struct NavigationPathState {
     pub target: Option<Vec3>,
     pub current_waypoint: Option<Vec3>,
     pub remaining_waypoints: Vec<Vec3>,
+    /// Where the agent last made progress, and how long ago.
+    pub progress_anchor: Option<Vec3>,
+    pub stalled_secs: f32,
+    /// Paths refreshed since the agent last made progress.
+    pub stuck_repaths: u32,
 }
 
 impl NavigationPathState {
@@ -39,6 +152,13 @@ impl NavigationPathState {
         self.target = None;
         self.current_waypoint = None;
         self.remaining_waypoints.clear();
+        self.reset_progress(None);
+    }
+
+    fn reset_progress(&mut self, anchor: Option<Vec3>) {
+        self.progress_anchor = anchor;
+        self.stalled_secs = 0.0;
+        self.stuck_repaths = 0;
     }
 
     pub fn assign_path(&mut self, target: Vec3, waypoints: Vec<Vec3>) {
@@ -66,6 +186,14 @@ impl SimpleNavigationAgent {
     }
 }
 
+/// Asks for a patrol sampled on the navmesh around the agent, for agents spawned without
+/// a route. Replaced by a `PatrolRoute` once the navmesh is baked.
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct NavMeshPatrol {
+    pub radius: f32,
+    pub speed: f32,
+}
+
 /// Simple patrol state
 #[derive(Component, Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct PatrolState {
@@ -141,10 +269,17 @@ fn patrol_system(
         &mut PatrolState,
         &PatrolRoute,
         &Position,
+        Option<&PolicyControlled>,
     )>,
-    time: Res<Time>,
+    clock: Res<GameClock>,
 ) {
-    for (entity, mut nav_agent, mut patrol_state, patrol_route, position) in agents.iter_mut() {
+    for (entity, mut nav_agent, mut patrol_state, patrol_route, position, policy) in
+        agents.iter_mut()
+    {
+        if policy.is_some_and(|policy| policy.active) {
+            continue;
+        }
+
         if nav_agent.current_target.is_none() {
             if let Some((next_target, next_index)) = patrol_route
                 .get_next_target(patrol_state.current_target_index, &mut patrol_state.forward)
@@ -167,7 +302,7 @@ fn patrol_system(
         };
 
         if reached_target {
-            patrol_state.wait_timer += time.delta_secs();
+            patrol_state.wait_timer += clock.delta_secs();
 
             if patrol_state.wait_timer >= patrol_state.wait_duration
                 && let Some((next_target, next_index)) = patrol_route
@@ -188,6 +323,192 @@ fn patrol_system(
     }
 }
 
+/// Give agents waiting for a `NavMeshPatrol` a route of points they can reach on the baked
+/// navmesh.
+fn generate_navmesh_patrols(
+    mut commands: Commands,
+    agents: Query<(Entity, &Position, &NavMeshPatrol)>,
+    navmesh: Query<(&ManagedNavMesh, &NavMeshStatus)>,
+    navmeshes: Res<Assets<NavMesh>>,
+) {
+    if agents.is_empty() {
+        return;
+    }
+    let Ok((navmesh_handle, status)) = navmesh.single() else {
+        return;
+    };
+    if *status != NavMeshStatus::Built {
+        return;
+    }
+    let Some(navmesh) = navmeshes.get(navmesh_handle.deref()) else {
+        return;
+    };
+
+    for (entity, position, patrol) in agents.iter() {
+        let origin = to_navmesh_plane(position.0);
+        let route = navmesh_patrol_route(position.0, patrol.radius, |point| {
+            let point = to_navmesh_plane(point);
+            navmesh.transformed_is_in_mesh(point)
+                && navmesh
+                    .transformed_path(origin, point)
+                    .is_some_and(|path| path.length <= patrol.radius * 2.0)
+        });
+
+        commands.entity(entity).remove::<NavMeshPatrol>();
+        if route.len() < 2 {
+            warn!(
+                "Entity {:?}: nowhere to patrol on the navmesh around {:?}",
+                entity, position.0
+            );
+            continue;
+        }
+        setup_patrol(&mut commands, entity, route, patrol.speed);
+    }
+}
+
+/// A patrol starting at `origin` through up to [`NAVMESH_PATROL_POINTS`] points sampled
+/// around it within `radius`, keeping those `reachable` accepts. Samples alternate between
+/// the full and half radius so routes cover more than a ring.
+pub fn navmesh_patrol_route(
+    origin: Vec3,
+    radius: f32,
+    mut reachable: impl FnMut(Vec3) -> bool,
+) -> Vec<Vec3> {
+    let mut route = vec![origin];
+    // Every other sample first, so the kept points spread around the origin.
+    let order = (0..NAVMESH_PATROL_SAMPLES)
+        .step_by(2)
+        .chain((1..NAVMESH_PATROL_SAMPLES).step_by(2));
+    for sample in order {
+        if route.len() > NAVMESH_PATROL_POINTS {
+            break;
+        }
+        let angle = std::f32::consts::TAU * sample as f32 / NAVMESH_PATROL_SAMPLES as f32;
+        let distance = if sample % 2 == 0 {
+            radius
+        } else {
+            radius * 0.5
+        };
+        let point = origin + Vec3::new(angle.cos(), 0.0, angle.sin()) * distance;
+        if reachable(point) {
+            route.push(point);
+        }
+    }
+    route
+}
+
+/// Route agents whose target is on another floor through the nearest navigation link.
+fn plan_platform_transits(
+    mut commands: Commands,
+    agents: Query<
+        (
+            Entity,
+            &Position,
+            &SimpleNavigationAgent,
+            Option<&PolicyControlled>,
+        ),
+        Without<PlatformTransit>,
+    >,
+    links: Query<(Entity, &NavigationLink)>,
+) {
+    for (entity, position, nav_agent, policy) in agents.iter() {
+        if policy.is_some_and(|policy| policy.active) {
+            continue;
+        }
+        let Some(target) = nav_agent.current_target else {
+            continue;
+        };
+        if (target.y - position.0.y).abs() <= NAVIGATION_LEVEL_STEP {
+            continue;
+        }
+
+        let going_up = target.y > position.0.y;
+        let nearest = links.iter().min_by(|(_, a), (_, b)| {
+            let trip = |link: &NavigationLink| {
+                let (entry, exit) = transit_stops(link, going_up);
+                planar_distance(position.0, entry) + planar_distance(exit, target)
+            };
+            trip(a).total_cmp(&trip(b))
+        });
+        let Some((link, _)) = nearest else {
+            continue;
+        };
+
+        commands.entity(entity).insert(PlatformTransit {
+            link,
+            final_target: target,
+            going_up,
+            stage: TransitStage::Approach,
+        });
+    }
+}
+
+/// Walk transiting agents through each leg of the trip, timed on the platform's phase.
+fn drive_platform_transits(
+    mut commands: Commands,
+    mut agents: Query<(
+        Entity,
+        &Position,
+        &mut SimpleNavigationAgent,
+        &mut PlatformTransit,
+    )>,
+    links: Query<(&NavigationLink, &MovingPlatform, &Position)>,
+) {
+    for (entity, position, mut nav_agent, mut transit) in agents.iter_mut() {
+        let Ok((link, platform, platform_position)) = links.get(transit.link) else {
+            nav_agent.current_target = Some(transit.final_target);
+            commands.entity(entity).remove::<PlatformTransit>();
+            continue;
+        };
+
+        let (entry, exit) = transit_stops(link, transit.going_up);
+        let (docked_at_entry, docked_at_exit) = if transit.going_up {
+            (
+                platform.path.is_docked_at_start(platform.phase),
+                platform.path.is_docked_at_end(platform.phase),
+            )
+        } else {
+            (
+                platform.path.is_docked_at_end(platform.phase),
+                platform.path.is_docked_at_start(platform.phase),
+            )
+        };
+        let arrival_threshold = nav_agent.arrival_threshold;
+        let arrived = |point: Vec3| planar_distance(position.0, point) <= arrival_threshold;
+        let on_platform =
+            planar_distance(position.0, platform_position.0) <= PLATFORM_SIZE.x * 0.25;
+
+        transit.stage = match transit.stage {
+            TransitStage::Approach if arrived(entry) => TransitStage::Wait,
+            TransitStage::Wait if docked_at_entry => TransitStage::Board,
+            TransitStage::Board if on_platform => TransitStage::Ride,
+            // Missed it, wait for the next one.
+            TransitStage::Board if !docked_at_entry => TransitStage::Wait,
+            TransitStage::Ride if docked_at_exit => TransitStage::Alight,
+            TransitStage::Alight if arrived(exit) => {
+                nav_agent.current_target = Some(transit.final_target);
+                commands.entity(entity).remove::<PlatformTransit>();
+                continue;
+            }
+            stage => stage,
+        };
+
+        nav_agent.current_target = Some(match transit.stage {
+            TransitStage::Approach | TransitStage::Wait => entry,
+            TransitStage::Board | TransitStage::Ride => platform_position.0,
+            TransitStage::Alight => exit,
+        });
+    }
+}
+
+fn transit_stops(link: &NavigationLink, going_up: bool) -> (Vec3, Vec3) {
+    if going_up {
+        (link.lower, link.upper)
+    } else {
+        (link.upper, link.lower)
+    }
+}
+
 fn refresh_navigation_paths(
     mut agents: Query<(
         Entity,
@@ -268,13 +589,22 @@ fn movement_system(
         &mut Rotation,
         &SimpleNavigationAgent,
         Option<&mut NavigationPathState>,
+        Option<&NavigationDetour>,
+        Option<&PolicyControlled>,
     )>,
-    time: Res<Time>,
+    clock: Res<GameClock>,
 ) {
-    for (mut position, mut rotation, nav_agent, mut path_state) in agents.iter_mut() {
+    for (mut position, mut rotation, nav_agent, mut path_state, detour, policy) in agents.iter_mut()
+    {
+        if policy.is_some_and(|policy| policy.active) {
+            continue;
+        }
+
         let current_pos = position.0;
 
-        let movement_target = if let Some(path_state) = path_state.as_deref_mut() {
+        let movement_target = if let Some(detour) = detour {
+            Some(detour.0)
+        } else if let Some(path_state) = path_state.as_deref_mut() {
             if nav_agent.current_target != path_state.target {
                 path_state.clear();
             }
@@ -305,19 +635,76 @@ fn movement_system(
                 continue;
             }
 
-            let step = nav_agent.speed * time.delta_secs();
+            let step = nav_agent.speed * clock.delta_secs();
             let movement = direction * step.min(distance);
 
             position.0.x = current_pos.x + movement.x;
             position.0.z = current_pos.z + movement.z;
 
-            let target_rotation = Quat::from_rotation_y(direction.x.atan2(direction.z));
+            let target_rotation = Quat::from_rotation_y(yaw_facing(direction));
             rotation.0 = target_rotation;
         }
     }
 }
 
-fn planar_distance(a: Vec3, b: Vec3) -> f32 {
+/// Agents that stop making progress toward their waypoint get a fresh path; after a few
+/// fruitless refreshes they give up on the target, so their patrol or squad picks another.
+fn detect_stuck_agents(
+    clock: Res<GameClock>,
+    mut agents: Query<
+        (
+            Entity,
+            &Position,
+            &mut SimpleNavigationAgent,
+            &mut NavigationPathState,
+            Option<&PolicyControlled>,
+        ),
+        Without<NavigationDetour>,
+    >,
+) {
+    for (entity, position, mut nav_agent, mut path_state, policy) in agents.iter_mut() {
+        if policy.is_some_and(|policy| policy.active) {
+            continue;
+        }
+        let Some(goal) = path_state.current_waypoint.or(nav_agent.current_target) else {
+            continue;
+        };
+        if planar_distance(position.0, goal) <= nav_agent.arrival_threshold {
+            path_state.reset_progress(Some(position.0));
+            continue;
+        }
+        let anchor = *path_state.progress_anchor.get_or_insert(position.0);
+        if planar_distance(position.0, anchor) >= STUCK_DISTANCE {
+            path_state.reset_progress(Some(position.0));
+            continue;
+        }
+
+        path_state.stalled_secs += clock.delta_secs();
+        if path_state.stalled_secs < STUCK_SECS {
+            continue;
+        }
+        path_state.stalled_secs = 0.0;
+        if path_state.stuck_repaths < MAX_STUCK_REPATHS {
+            debug!(
+                "Entity {:?}: stuck at {:?}, refreshing its path",
+                entity, position.0
+            );
+            path_state.stuck_repaths += 1;
+            // `refresh_navigation_paths` rebuilds paths without a waypoint.
+            path_state.current_waypoint = None;
+            path_state.remaining_waypoints.clear();
+        } else {
+            debug!(
+                "Entity {:?}: stuck at {:?}, giving up on {:?}",
+                entity, position.0, nav_agent.current_target
+            );
+            nav_agent.current_target = None;
+            path_state.clear();
+        }
+    }
+}
+
+pub fn planar_distance(a: Vec3, b: Vec3) -> f32 {
     Vec2::new(a.x, a.z).distance(Vec2::new(b.x, b.z))
 }
 
@@ -381,9 +768,12 @@ pub fn validate_spawn_position<F: QueryFilter>(
 #[cfg(test)]
 mod tests {
     use super::{
-        NavigationObstacle, NavigationPathState, PatrolRoute, SimpleNavigationAgent,
-        from_navmesh_plane, movement_system, to_navmesh_plane, validate_spawn_position,
+        DoorLink, NAVMESH_PATROL_POINTS, NavigationDetour, NavigationObstacle, NavigationPathState,
+        PatrolRoute, SimpleNavigationAgent, detect_stuck_agents, from_navmesh_plane,
+        movement_system, navmesh_patrol_route, to_navmesh_plane, validate_spawn_position,
     };
+    use crate::clock::GameClockPlugin;
+    use crate::level::interactive::Door;
     use avian3d::prelude::Position;
     use avian3d::prelude::Rotation;
     use bevy::prelude::{App, Query, Resource, Update, Vec3, With};
@@ -459,7 +849,7 @@ mod tests {
     #[test]
     fn movement_falls_back_to_current_target_when_waypoint_missing() {
         let mut app = App::new();
-        app.add_plugins(bevy::MinimalPlugins);
+        app.add_plugins((bevy::MinimalPlugins, GameClockPlugin));
         app.add_systems(Update, movement_system);
 
         let start = Vec3::new(0.0, 1.0, 0.0);
@@ -500,6 +890,115 @@ mod tests {
         );
     }
 
+    #[test]
+    fn detours_hold_agents_off_their_path_in_front_of_doors() {
+        // A door across the x axis: it is walked through along x.
+        let door = Door::new(Vec3::new(5.0, 1.5, 0.0), Vec3::new(0.2, 3.0, 2.0), false);
+        let link = DoorLink::through(&door, None);
+        let near_side = link.use_point(Vec3::new(0.0, 1.0, 0.0));
+        assert!(near_side.x < 5.0 - 0.1 && near_side.z == 0.0);
+        assert!(link.use_point(Vec3::new(9.0, 1.0, 0.0)).x > 5.1);
+        let button = Vec3::new(3.0, 1.0, 2.0);
+        assert_eq!(
+            DoorLink::through(&door, Some(button)).use_point(near_side),
+            button
+        );
+
+        let mut app = App::new();
+        app.add_plugins((bevy::MinimalPlugins, GameClockPlugin));
+        app.add_systems(Update, (movement_system, detect_stuck_agents));
+        app.insert_resource(bevy::time::TimeUpdateStrategy::ManualDuration(
+            std::time::Duration::from_millis(100),
+        ));
+
+        let target = Vec3::new(10.0, 1.0, 0.0);
+        let mut path_state = NavigationPathState::default();
+        path_state.assign_path(target, vec![target]);
+        let entity = app
+            .world_mut()
+            .spawn((
+                Position::new(Vec3::new(0.0, 1.0, 0.0)),
+                Rotation::default(),
+                SimpleNavigationAgent {
+                    speed: 4.0,
+                    arrival_threshold: 0.5,
+                    current_target: Some(target),
+                },
+                path_state,
+                NavigationDetour(near_side),
+            ))
+            .id();
+        for _ in 0..50 {
+            app.update();
+        }
+
+        // It waits in front of the door for as long as it takes, path and target intact.
+        let position = app.world().get::<Position>(entity).unwrap().0;
+        assert!(position.distance(Vec3::new(near_side.x, 1.0, 0.0)) < 0.01);
+        let path_state = app.world().get::<NavigationPathState>(entity).unwrap();
+        assert_eq!(path_state.current_waypoint, Some(target));
+        assert_eq!(path_state.stuck_repaths, 0);
+        let agent = app.world().get::<SimpleNavigationAgent>(entity).unwrap();
+        assert_eq!(agent.current_target, Some(target));
+    }
+
+    #[test]
+    fn navmesh_patrols_keep_only_reachable_points() {
+        let origin = Vec3::new(3.0, 1.0, -2.0);
+        let route = navmesh_patrol_route(origin, 10.0, |_| true);
+        assert_eq!(route.len(), NAVMESH_PATROL_POINTS + 1);
+        assert_eq!(route[0], origin);
+        assert!(route.iter().all(|point| point.distance(origin) <= 10.001));
+        assert!(route.iter().all(|point| point.y == origin.y));
+
+        // A wall along x = origin.x: only the side in front of it is reachable.
+        let route = navmesh_patrol_route(origin, 10.0, |point| point.x > origin.x + 0.1);
+        assert!(route.len() > 1);
+        assert!(route[1..].iter().all(|point| point.x > origin.x));
+
+        assert_eq!(navmesh_patrol_route(origin, 10.0, |_| false), vec![origin]);
+    }
+
+    #[test]
+    fn stuck_agents_refresh_their_path_then_give_up() {
+        let mut app = App::new();
+        app.add_plugins((bevy::MinimalPlugins, GameClockPlugin));
+        app.add_systems(Update, detect_stuck_agents);
+
+        let target = Vec3::new(10.0, 1.0, 0.0);
+        let mut path_state = NavigationPathState::default();
+        path_state.assign_path(target, vec![Vec3::new(5.0, 1.0, 0.0), target]);
+        // Never moves: pinned against something the navmesh does not know about.
+        let entity = app
+            .world_mut()
+            .spawn((
+                Position::new(Vec3::new(0.0, 1.0, 0.0)),
+                SimpleNavigationAgent {
+                    speed: 4.0,
+                    arrival_threshold: 0.5,
+                    current_target: Some(target),
+                },
+                path_state,
+            ))
+            .id();
+        app.insert_resource(bevy::time::TimeUpdateStrategy::ManualDuration(
+            std::time::Duration::from_millis(100),
+        ));
+
+        for _ in 0..20 {
+            app.update();
+        }
+        let path_state = app.world().get::<NavigationPathState>(entity).unwrap();
+        assert_eq!(path_state.stuck_repaths, 1);
+        assert_eq!(path_state.current_waypoint, None);
+
+        for _ in 0..40 {
+            app.update();
+        }
+        let agent = app.world().get::<SimpleNavigationAgent>(entity).unwrap();
+        assert_eq!(agent.current_target, None);
+    }
+
     #[test]
     fn navmesh_plane_projection_preserves_world_xz() {
         let world = Vec3::new(-18.999_315, 1.0, 21.413_212);
@@ -0,0 +1,278 @@
+use bevy::prelude::{
+    Added, App, Entity, Message, MessageWriter, Plugin, Query, Ref, RemovedComponents, Update,
+    Vec2, Vec3, With, debug, info,
+};
+use vleue_navigator::prelude::{ManagedNavMesh, NavMeshSettings, NavMeshStatus, Triangulation};
+
+use crate::level::generation::{DOOR_OPENING_WIDTH, LevelGraph, Zone};
+use crate::navigation::NavigationObstacle;
+
+/// How far inside the level bounds the outer edge of the baked navmesh stays, so agents
+/// keep clear of the outer walls.
+const NAVMESH_BOUNDS_MARGIN: f32 = 2.0;
+/// Resolution of the walkable-area sampling grid used to carve out the void between zones.
+const NAVMESH_VOID_CELL_SIZE: f32 = 4.0;
+
+/// Emitted every time a managed navmesh finishes (re)baking.
+#[derive(Message, Clone, Debug)]
+pub struct NavMeshReady {
+    pub navmesh: Entity,
+}
+
+pub struct NavMeshBakingPlugin;
+
+impl Plugin for NavMeshBakingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<NavMeshReady>();
+        app.add_systems(
+            Update,
+            (rebake_navmesh_on_obstacle_changes, emit_navmesh_ready),
+        );
+    }
+}
+
+fn point_in_zone(zone: &Zone, point: Vec2) -> bool {
+    let local = zone.rotation.inverse() * (Vec3::new(point.x, 0.0, point.y) - zone.position);
+    local.x.abs() <= zone.size.x * 0.5 && local.z.abs() <= zone.size.z * 0.5
+}
+
+fn point_in_connection(from: Vec3, to: Vec3, point: Vec2) -> bool {
+    let start = Vec2::new(from.x, from.z);
+    let segment = Vec2::new(to.x, to.z) - start;
+    let length_squared = segment.length_squared();
+    if length_squared <= f32::EPSILON {
+        return false;
+    }
+
+    let t = ((point - start).dot(segment) / length_squared).clamp(0.0, 1.0);
+    let closest = start + segment * t;
+    closest.distance(point) <= DOOR_OPENING_WIDTH * 0.5
+}
+
+/// Whether a point on the XZ plane lies on a zone floor or on a connection between two zones.
+pub fn is_walkable(level_graph: &LevelGraph, point: Vec2) -> bool {
+    if level_graph
+        .zones
+        .values()
+        .any(|zone| point_in_zone(zone, point))
+    {
+        return true;
+    }
+
+    level_graph.connections.iter().any(|connection| {
+        match (
+            level_graph.get_zone(connection.from_zone),
+            level_graph.get_zone(connection.to_zone),
+        ) {
+            (Some(from), Some(to)) => point_in_connection(from.position, to.position, point),
+            _ => false,
+        }
+    })
+}
+
+/// XZ bounds `(min, max)` of every zone in the level, or `None` for an empty level.
+pub fn level_bounds(level_graph: &LevelGraph) -> Option<(Vec2, Vec2)> {
+    let mut min = Vec2::splat(f32::INFINITY);
+    let mut max = Vec2::splat(f32::NEG_INFINITY);
+
+    for zone in level_graph.zones.values() {
+        let half = Vec2::new(zone.size.x, zone.size.z) * 0.5;
+        let center = Vec2::new(zone.position.x, zone.position.z);
+        min = min.min(center - half);
+        max = max.max(center + half);
+    }
+
+    (min.is_finite() && max.is_finite()).then_some((min, max))
+}
+
+/// Rectangles (as navmesh-plane polygons) covering the parts of the level bounds that are
+/// neither zone floors nor connections. Adjacent void cells on the same row are merged.
+pub fn level_void_obstacles(level_graph: &LevelGraph, cell_size: f32) -> Vec<Vec<Vec2>> {
+    let Some((min, max)) = level_bounds(level_graph) else {
+        return Vec::new();
+    };
+
+    let columns = ((max.x - min.x) / cell_size).ceil().max(0.0) as usize;
+    let rows = ((max.y - min.y) / cell_size).ceil().max(0.0) as usize;
+    let mut obstacles = Vec::new();
+
+    for row in 0..rows {
+        let z0 = min.y + row as f32 * cell_size;
+        let z1 = (z0 + cell_size).min(max.y);
+        let mut run_start: Option<f32> = None;
+
+        for column in 0..=columns {
+            let x0 = min.x + column as f32 * cell_size;
+            let is_void = column < columns && {
+                let center = Vec2::new(x0 + cell_size * 0.5, (z0 + z1) * 0.5);
+                !is_walkable(level_graph, center)
+            };
+
+            match (is_void, run_start) {
+                (true, None) => run_start = Some(x0),
+                (false, Some(start)) => {
+                    let end = x0.min(max.x);
+                    obstacles.push(vec![
+                        Vec2::new(start, z0),
+                        Vec2::new(end, z0),
+                        Vec2::new(end, z1),
+                        Vec2::new(start, z1),
+                    ]);
+                    run_start = None;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    obstacles
+}
+
+/// Corners of the outer edge of the navmesh: the level bounds inset by
+/// `NAVMESH_BOUNDS_MARGIN`.
+pub fn navmesh_outline(level_graph: &LevelGraph) -> Option<[Vec2; 4]> {
+    let (min, max) = level_bounds(level_graph)?;
+    let min = min + Vec2::splat(NAVMESH_BOUNDS_MARGIN);
+    let max = max - Vec2::splat(NAVMESH_BOUNDS_MARGIN);
+
+    Some([
+        Vec2::new(min.x, min.y),
+        Vec2::new(max.x, min.y),
+        Vec2::new(max.x, max.y),
+        Vec2::new(min.x, max.y),
+    ])
+}
+
+/// Builds the fixed triangulation for a generated level: the level bounds minus the void
+/// between zones. Walls are carved afterwards by the `NavigationObstacle` updater.
+pub fn level_triangulation(level_graph: &LevelGraph) -> Option<Triangulation> {
+    let mut triangulation = Triangulation::from_outer_edges(&navmesh_outline(level_graph)?);
+    triangulation.add_obstacles(level_void_obstacles(level_graph, NAVMESH_VOID_CELL_SIZE));
+
+    Some(triangulation)
+}
+
+/// Navigation obstacles only trigger an update when they move; spawning or despawning one
+/// (doors, destructibles, props) must also rebake the navmesh.
+fn rebake_navmesh_on_obstacle_changes(
+    added_obstacles: Query<(), Added<NavigationObstacle>>,
+    mut removed_obstacles: RemovedComponents<NavigationObstacle>,
+    mut navmeshes: Query<&mut NavMeshSettings, With<ManagedNavMesh>>,
+) {
+    let added = added_obstacles.iter().count();
+    let removed = removed_obstacles.read().count();
+    if added == 0 && removed == 0 {
+        return;
+    }
+
+    for mut settings in navmeshes.iter_mut() {
+        settings.set_changed();
+    }
+
+    debug!(
+        "Navigation obstacles changed (+{} / -{}), requesting navmesh rebake",
+        added, removed
+    );
+}
+
+fn emit_navmesh_ready(
+    navmeshes: Query<(Entity, Ref<NavMeshStatus>), With<ManagedNavMesh>>,
+    mut ready_writer: MessageWriter<NavMeshReady>,
+) {
+    for (entity, status) in navmeshes.iter() {
+        if status.is_changed() && *status == NavMeshStatus::Built {
+            info!("🗺️ Navmesh {:?} baked and ready", entity);
+            ready_writer.write(NavMeshReady { navmesh: entity });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        NAVMESH_BOUNDS_MARGIN, NAVMESH_VOID_CELL_SIZE, is_walkable, level_bounds,
+        level_void_obstacles, navmesh_outline,
+    };
+    use crate::level::generation::{LevelConfig, generate_level};
+    use bevy::prelude::Vec2;
+
+    fn test_level() -> crate::level::generation::LevelGraph {
+        generate_level(LevelConfig {
+            seed: 77,
+            target_zone_count: 12,
+            min_zone_spacing: 32.0,
+            max_depth: 7,
+        })
+    }
+
+    #[test]
+    fn zone_centers_and_doors_are_walkable() {
+        let level = test_level();
+
+        for zone in level.zones.values() {
+            assert!(
+                is_walkable(&level, Vec2::new(zone.position.x, zone.position.z)),
+                "Zone {:?} center should be walkable",
+                zone.id
+            );
+        }
+        for connection in &level.connections {
+            let door = Vec2::new(connection.door_position.x, connection.door_position.z);
+            assert!(
+                is_walkable(&level, door),
+                "Door {:?} should be walkable",
+                door
+            );
+        }
+    }
+
+    #[test]
+    fn navmesh_outline_is_inset_from_level_bounds() {
+        let level = test_level();
+        let (min, max) = level_bounds(&level).expect("generated level should have bounds");
+        let outline = navmesh_outline(&level).expect("generated level should have an outline");
+
+        let outline_min = outline
+            .iter()
+            .fold(Vec2::MAX, |acc, corner| acc.min(*corner));
+        let outline_max = outline
+            .iter()
+            .fold(Vec2::MIN, |acc, corner| acc.max(*corner));
+        assert_eq!(outline_min, min + Vec2::splat(NAVMESH_BOUNDS_MARGIN));
+        assert_eq!(outline_max, max - Vec2::splat(NAVMESH_BOUNDS_MARGIN));
+    }
+
+    #[test]
+    fn void_obstacles_stay_inside_bounds_and_avoid_zone_centers() {
+        let level = test_level();
+        let (min, max) = level_bounds(&level).expect("generated level should have bounds");
+        let obstacles = level_void_obstacles(&level, NAVMESH_VOID_CELL_SIZE);
+
+        for obstacle in &obstacles {
+            assert_eq!(obstacle.len(), 4, "Void obstacles should be rectangles");
+            for corner in obstacle {
+                assert!(
+                    corner.x >= min.x - 0.001
+                        && corner.x <= max.x + 0.001
+                        && corner.y >= min.y - 0.001
+                        && corner.y <= max.y + 0.001,
+                    "Void corner {:?} outside level bounds {:?}..{:?}",
+                    corner,
+                    min,
+                    max
+                );
+            }
+        }
+
+        for zone in level.zones.values() {
+            let center = Vec2::new(zone.position.x, zone.position.z);
+            let covered = obstacles.iter().any(|obstacle| {
+                center.x > obstacle[0].x
+                    && center.x < obstacle[1].x
+                    && center.y > obstacle[0].y
+                    && center.y < obstacle[2].y
+            });
+            assert!(!covered, "Zone {:?} center is covered by a void", zone.id);
+        }
+    }
+}
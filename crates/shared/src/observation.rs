@@ -0,0 +1,85 @@
+//! What an agent observes in one tick, as a flat buffer of `f32`s. Trainers read it from
+//! RL environments and from demonstrations recorded by players, so both build it here.
+//!
+//! The layout is fixed for a given [`OBSERVATION_VERSION`]: the version itself, position,
+//! velocity, health, max health, [`RAY_COUNT`] ray distances and the last action. Any
+//! change to it bumps the version.
+
+use avian3d::prelude::{SpatialQueryFilter, SpatialQueryPipeline};
+use bevy::prelude::{Dir3, Entity, Quat, Vec3};
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::inputs::input::PlayerAction;
+
+pub const OBSERVATION_VERSION: u32 = 1;
+/// Horizontal rays cast around the agent, evenly spaced, starting straight ahead.
+pub const RAY_COUNT: usize = 8;
+/// Rays that hit nothing within this distance report it.
+pub const RAY_RANGE: f32 = 20.0;
+/// Rays start this far above the agent's origin, roughly at eye level.
+pub const RAY_HEIGHT: f32 = 0.8;
+/// Move(2) + Look(2) + Jump(1) + Shoot(1).
+pub const LAST_ACTION_SIZE: usize = 6;
+/// Values in an observation buffer.
+pub const OBSERVATION_LEN: usize = 1 + 3 + 3 + 1 + 1 + RAY_COUNT + LAST_ACTION_SIZE;
+
+/// Distances to the nearest obstacle along [`RAY_COUNT`] horizontal rays around the
+/// agent, starting from where it faces.
+pub fn ray_distances(
+    spatial_query: &SpatialQueryPipeline,
+    agent: Entity,
+    position: Vec3,
+    rotation: Quat,
+) -> [f32; RAY_COUNT] {
+    let origin = position + Vec3::Y * RAY_HEIGHT;
+    let filter = SpatialQueryFilter::default().with_excluded_entities([agent]);
+    std::array::from_fn(|index| {
+        let angle = index as f32 * std::f32::consts::TAU / RAY_COUNT as f32;
+        let direction =
+            Dir3::new(rotation * Quat::from_rotation_y(angle) * Vec3::NEG_Z).unwrap_or(Dir3::NEG_Z);
+        spatial_query
+            .cast_ray(origin, direction, RAY_RANGE, true, &filter)
+            .map_or(RAY_RANGE, |hit| hit.distance)
+    })
+}
+
+/// The observation buffer, version first.
+pub fn observation_buffer(
+    position: Vec3,
+    velocity: Vec3,
+    health: f32,
+    max_health: f32,
+    ray_distances: &[f32; RAY_COUNT],
+    last_action: &[f32; LAST_ACTION_SIZE],
+) -> Vec<f32> {
+    let mut buffer = Vec::with_capacity(OBSERVATION_LEN);
+    buffer.push(OBSERVATION_VERSION as f32);
+    buffer.extend_from_slice(&position.to_array());
+    buffer.extend_from_slice(&velocity.to_array());
+    buffer.push(health);
+    buffer.push(max_health);
+    buffer.extend_from_slice(ray_distances);
+    buffer.extend_from_slice(last_action);
+    buffer
+}
+
+/// A player's input as action values: move x y, look x y, then 1 or 0 for jump and shoot.
+pub fn action_values(action_state: &ActionState<PlayerAction>) -> [f32; LAST_ACTION_SIZE] {
+    let movement = action_state.axis_pair(&PlayerAction::Move);
+    let look = action_state.axis_pair(&PlayerAction::Look);
+    let button = |action: &PlayerAction| {
+        if action_state.pressed(action) {
+            1.0
+        } else {
+            0.0
+        }
+    };
+    [
+        movement.x,
+        movement.y,
+        look.x,
+        look.y,
+        button(&PlayerAction::Jump),
+        button(&PlayerAction::Shoot),
+    ]
+}
@@ -0,0 +1,249 @@
+//! Bot perception: what each bot has seen or heard of its enemies, and how sure it still is
+//! of where they are. Sight needs the enemy in the bot's vision cone, or right next to it,
+//! with nothing in between; what carries to a bot's ears is in `hearing`. Memories fade,
+//! and what is left decides whether the bot engages, searches or keeps to its patrol.
+
+use bevy::prelude::{Component, Entity, Vec3};
+
+/// Farthest a bot sees.
+pub const SIGHT_RANGE: f32 = 25.0;
+/// Half the angle of a bot's vision cone.
+pub const VISION_HALF_ANGLE: f32 = std::f32::consts::FRAC_PI_3;
+/// Enemies this close are noticed whichever way the bot faces.
+pub const PROXIMITY_RANGE: f32 = 3.0;
+/// How long a sighting and a sound are remembered; confidence fades to nothing over it.
+pub const SIGHT_MEMORY_SECS: f32 = 12.0;
+pub const HEARING_MEMORY_SECS: f32 = 8.0;
+/// An enemy seen this recently is still in sight.
+pub const IN_SIGHT_SECS: f32 = 0.5;
+/// Confidence of a fresh sound, against 1.0 for a fresh sighting.
+const HEARING_CONFIDENCE: f32 = 0.6;
+
+/// How a bot last sensed an enemy.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sense {
+    Sight,
+    Hearing,
+}
+
+impl Sense {
+    fn memory_secs(self) -> f32 {
+        match self {
+            Sense::Sight => SIGHT_MEMORY_SECS,
+            Sense::Hearing => HEARING_MEMORY_SECS,
+        }
+    }
+}
+
+/// An enemy a bot knows about.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PerceivedEnemy {
+    pub enemy: Entity,
+    /// Where the bot last sensed it.
+    pub position: Vec3,
+    pub sensed_secs: f32,
+    pub sense: Sense,
+}
+
+impl PerceivedEnemy {
+    /// 1.0 for an enemy in plain sight, fading to 0.0 once the memory is gone.
+    pub fn confidence(&self, now_secs: f32) -> f32 {
+        let fade = 1.0 - (now_secs - self.sensed_secs) / self.sense.memory_secs();
+        let certainty = match self.sense {
+            Sense::Sight => 1.0,
+            Sense::Hearing => HEARING_CONFIDENCE,
+        };
+        fade.clamp(0.0, 1.0) * certainty
+    }
+
+    pub fn in_sight(&self, now_secs: f32) -> bool {
+        self.sense == Sense::Sight && now_secs - self.sensed_secs <= IN_SIGHT_SECS
+    }
+}
+
+/// What a bot does about what it perceives.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+pub enum Awareness {
+    /// Nothing remembered: the bot keeps to its patrol.
+    #[default]
+    Idle,
+    /// An enemy is remembered but not in sight: the bot checks where it was.
+    Searching { last_known: Vec3 },
+    /// An enemy is in sight.
+    Engaging { enemy: Entity, position: Vec3 },
+}
+
+/// A bot's memory of its enemies.
+#[derive(Component, Clone, Debug, Default)]
+pub struct BotPerception {
+    pub enemies: Vec<PerceivedEnemy>,
+}
+
+impl BotPerception {
+    pub fn see(&mut self, enemy: Entity, position: Vec3, now_secs: f32) {
+        self.record(enemy, position, now_secs, Sense::Sight);
+    }
+
+    /// Record a sound. An enemy still in sight stays a sighting.
+    pub fn hear(&mut self, enemy: Entity, position: Vec3, now_secs: f32) {
+        let in_sight = self
+            .enemies
+            .iter()
+            .any(|known| known.enemy == enemy && known.in_sight(now_secs));
+        if !in_sight {
+            self.record(enemy, position, now_secs, Sense::Hearing);
+        }
+    }
+
+    fn record(&mut self, enemy: Entity, position: Vec3, now_secs: f32, sense: Sense) {
+        let perceived = PerceivedEnemy {
+            enemy,
+            position,
+            sensed_secs: now_secs,
+            sense,
+        };
+        match self.enemies.iter_mut().find(|known| known.enemy == enemy) {
+            Some(known) => *known = perceived,
+            None => self.enemies.push(perceived),
+        }
+    }
+
+    /// Drop enemies the bot no longer remembers.
+    pub fn forget_faded(&mut self, now_secs: f32) {
+        self.enemies
+            .retain(|known| known.confidence(now_secs) > 0.0);
+    }
+
+    /// Enemies sensed at `now_secs`, to share with squadmates.
+    pub fn sensed_at(&self, now_secs: f32) -> impl Iterator<Item = &PerceivedEnemy> {
+        self.enemies
+            .iter()
+            .filter(move |known| known.sensed_secs == now_secs)
+    }
+
+    /// Engaging the most recently seen enemy in sight, else searching where the enemy the
+    /// bot is surest about was.
+    pub fn awareness(&self, now_secs: f32) -> Awareness {
+        let in_sight = self
+            .enemies
+            .iter()
+            .filter(|known| known.in_sight(now_secs))
+            .max_by(|a, b| a.sensed_secs.total_cmp(&b.sensed_secs));
+        if let Some(known) = in_sight {
+            return Awareness::Engaging {
+                enemy: known.enemy,
+                position: known.position,
+            };
+        }
+        self.enemies
+            .iter()
+            .filter(|known| known.confidence(now_secs) > 0.0)
+            .max_by(|a, b| a.confidence(now_secs).total_cmp(&b.confidence(now_secs)))
+            .map_or(Awareness::Idle, |known| Awareness::Searching {
+                last_known: known.position,
+            })
+    }
+}
+
+/// Whether a bot with its eyes at `eye`, facing `facing`, would see `target` if nothing
+/// were in between: in range, and in its vision cone unless right next to it. The cone is
+/// only horizontal, so enemies above or below are not missed.
+pub fn in_view(eye: Vec3, facing: Vec3, target: Vec3) -> bool {
+    let offset = target - eye;
+    let distance = offset.length();
+    if distance > SIGHT_RANGE {
+        return false;
+    }
+    if distance <= PROXIMITY_RANGE {
+        return true;
+    }
+    let (Some(facing), Some(direction)) = (
+        facing.with_y(0.0).try_normalize(),
+        offset.with_y(0.0).try_normalize(),
+    ) else {
+        // Straight above or below: the horizontal cone says nothing.
+        return true;
+    };
+    facing.dot(direction) >= VISION_HALF_ANGLE.cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Awareness, BotPerception, HEARING_MEMORY_SECS, IN_SIGHT_SECS, PROXIMITY_RANGE,
+        SIGHT_MEMORY_SECS, SIGHT_RANGE, in_view,
+    };
+    use bevy::prelude::{Entity, Vec3};
+
+    #[test]
+    fn bots_only_see_ahead_unless_the_enemy_is_close() {
+        let eye = Vec3::new(0.0, 1.5, 0.0);
+        assert!(in_view(eye, Vec3::Z, Vec3::new(0.0, 1.5, 10.0)));
+        assert!(in_view(eye, Vec3::Z, Vec3::new(5.0, 0.0, 10.0)));
+        assert!(!in_view(eye, Vec3::Z, Vec3::new(10.0, 1.5, 0.0)));
+        assert!(!in_view(eye, Vec3::Z, Vec3::new(0.0, 1.5, -10.0)));
+        assert!(!in_view(
+            eye,
+            Vec3::Z,
+            Vec3::new(0.0, 1.5, SIGHT_RANGE + 1.0)
+        ));
+        assert!(in_view(
+            eye,
+            Vec3::Z,
+            Vec3::new(0.0, 1.5, -PROXIMITY_RANGE + 0.5)
+        ));
+    }
+
+    #[test]
+    fn awareness_goes_from_engaging_to_searching_to_idle() {
+        let enemy = Entity::PLACEHOLDER;
+        let seen_at = Vec3::new(4.0, 1.0, 8.0);
+        let mut perception = BotPerception::default();
+        assert_eq!(perception.awareness(0.0), Awareness::Idle);
+
+        perception.see(enemy, seen_at, 1.0);
+        assert_eq!(
+            perception.awareness(1.0),
+            Awareness::Engaging {
+                enemy,
+                position: seen_at
+            }
+        );
+        assert_eq!(perception.sensed_at(1.0).count(), 1);
+        assert_eq!(perception.sensed_at(1.1).count(), 0);
+
+        let later = 1.0 + IN_SIGHT_SECS + 1.0;
+        assert_eq!(
+            perception.awareness(later),
+            Awareness::Searching {
+                last_known: seen_at
+            }
+        );
+        assert!(perception.enemies[0].confidence(later) < 1.0);
+
+        perception.forget_faded(1.0 + SIGHT_MEMORY_SECS + 0.1);
+        assert!(perception.enemies.is_empty());
+        assert_eq!(perception.awareness(20.0), Awareness::Idle);
+    }
+
+    #[test]
+    fn sounds_are_less_certain_and_do_not_hide_a_sighting() {
+        let enemy = Entity::PLACEHOLDER;
+        let mut perception = BotPerception::default();
+        perception.see(enemy, Vec3::ZERO, 1.0);
+        perception.hear(enemy, Vec3::X, 1.2);
+        assert_eq!(perception.enemies[0].position, Vec3::ZERO);
+
+        perception.hear(enemy, Vec3::X, 5.0);
+        let heard = perception.enemies[0];
+        assert_eq!(heard.position, Vec3::X);
+        assert!(heard.confidence(5.0) < 1.0);
+        assert_eq!(heard.confidence(5.0 + HEARING_MEMORY_SECS), 0.0);
+        assert_eq!(
+            perception.awareness(5.0),
+            Awareness::Searching {
+                last_known: Vec3::X
+            }
+        );
+    }
+}
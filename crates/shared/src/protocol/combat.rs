@@ -0,0 +1,108 @@
+use crate::components::{
+    attachments::{Attachment, WeaponAttachments},
+    destructible::{DebrisPiece, Destructible},
+    flashlight::PlayerFlashlight,
+    grenade::Grenade,
+    health::{Health, Respawnable},
+    loadout::{HolsteredGun, Loadout},
+    pickup::Pickup,
+    score::MatchScore,
+    shield::Shield,
+    weapons::{Gun, Projectile, ProjectileGun},
+    world_items::WorldItem,
+};
+use crate::protocol::{ChannelDelivery, Delivery};
+use bevy::{prelude::Vec3, reflect::TypePath};
+
+use serde::{Deserialize, Serialize};
+
+/// Broadcast by the server for every kill, for the client kill feed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct KillFeedEvent {
+    pub killer: Option<String>,
+    pub victim: String,
+    pub headshot: bool,
+}
+
+/// Sent to a shooter when its damage lands on a character, for the crosshair hit marker.
+/// `kill` is set when that damage was fatal.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct HitConfirmation {
+    pub kill: bool,
+    pub headshot: bool,
+}
+
+/// Broadcast when a destructible prop shatters. The server simulates the first
+/// [`AUTHORITATIVE_DEBRIS_PIECES`](crate::components::destructible::AUTHORITATIVE_DEBRIS_PIECES)
+/// pieces itself; clients rebuild the rest from `seed` as cosmetic debris.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DebrisBurst {
+    pub origin: Vec3,
+    pub size: Vec3,
+    pub seed: u64,
+    pub pieces: usize,
+}
+
+/// Broadcast when a grenade explodes, for client effects. Damage is applied by the server.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GrenadeExplosion {
+    pub origin: Vec3,
+    pub radius: f32,
+}
+
+/// Client request to change the attachments mounted on its weapon. The server validates
+/// the list before applying it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EquipAttachmentsRequest {
+    pub attachments: Vec<Attachment>,
+}
+
+/// Client request to spawn with a different loadout. The server ignores loadouts over
+/// budget and applies accepted ones the next time the player spawns.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SubmitLoadoutRequest {
+    pub loadout: Loadout,
+}
+
+#[derive(TypePath)]
+pub struct LoadoutChannel;
+
+impl ChannelDelivery for LoadoutChannel {
+    const DELIVERY: Delivery = Delivery::OrderedReliable;
+}
+
+sub_protocol! {
+    /// Health, weapons, grenades, destructible props, corpses and loot, the kill feed and
+    /// hit confirmations.
+    name: "combat",
+    inputs: [],
+    components: [
+        Health.add_prediction(),
+        Shield.add_prediction(),
+        Respawnable,
+        MatchScore,
+        Gun.add_prediction(),
+        HolsteredGun.add_prediction(),
+        ProjectileGun.add_prediction(),
+        Loadout,
+        WeaponAttachments.add_prediction(),
+        Projectile.add_prediction(),
+        Grenade,
+        Destructible,
+        DebrisPiece,
+        WorldItem,
+        Pickup,
+        PlayerFlashlight.add_prediction(),
+    ],
+    channels: [
+        LoadoutChannel => ClientToServer,
+    ],
+    messages: [
+        KillFeedEvent => ServerToClient,
+        HitConfirmation => ServerToClient,
+        DebrisBurst => ServerToClient,
+        GrenadeExplosion => ServerToClient,
+        EquipAttachmentsRequest => ClientToServer,
+        SubmitLoadoutRequest => ClientToServer,
+    ],
+}
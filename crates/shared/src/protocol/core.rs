@@ -0,0 +1,155 @@
+use crate::{
+    afk::Afk,
+    components::stamina::{Stamina, StaminaConfig},
+    inputs::climbing::ClimbState,
+    inputs::crouch::CrouchState,
+    inputs::input::PlayerAction,
+    inputs::look::LookVelocity,
+    inputs::movement::GroundState,
+    level::interactive::{Door, LevelButton, Wired},
+    level::platforms::MovingPlatform,
+    level::transition::LevelExit,
+    navigation::{PatrolRoute, PatrolState, SimpleNavigationAgent},
+    resync::{ResyncChecksum, ResyncKeyframe, ResyncRequest},
+};
+use avian3d::prelude::{LinearVelocity, Position, Rotation};
+use bevy::{
+    prelude::{Color, Component, Name},
+    reflect::TypePath,
+};
+
+use lightyear::prelude::{ChannelMode, PeerId, ReliableSettings};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PlayerId(pub PeerId);
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PlayerColor(pub Color);
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CharacterMarker;
+
+#[derive(Component, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GameSeed {
+    pub seed: u64,
+}
+
+/// LevelSeed component - replicated from server to clients
+/// Used to synchronize procedural level generation across the network
+#[derive(Component, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LevelSeed {
+    pub seed: u64,
+}
+
+/// Sent when the team reached the level exit: clients tear down their local level and
+/// rebuild it from `seed` behind a loading overlay, keeping their player entities.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LevelTransitionEvent {
+    pub seed: u64,
+}
+
+/// Encoded voice audio from one speaker. Clients send their own frames with any `speaker`;
+/// the server overwrites it with the sender's id and relays the frame to players within
+/// [`VOICE_PROXIMITY_RANGE`]. The payload is opaque to the networking code.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct VoiceFrame {
+    pub speaker: u64,
+    pub sequence: u32,
+    pub data: Vec<u8>,
+}
+
+/// Players further than this (in meters) from a speaker do not hear them.
+pub const VOICE_PROXIMITY_RANGE: f32 = 30.0;
+/// Largest accepted [`VoiceFrame`] payload, the maximum size of an Opus packet.
+pub const MAX_VOICE_FRAME_BYTES: usize = 1275;
+
+/// What a channel promises about the messages sent on it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Delivery {
+    /// Every message arrives, in the order it was sent.
+    OrderedReliable,
+    /// Messages may be lost or arrive out of order.
+    UnorderedUnreliable,
+}
+
+impl Delivery {
+    pub fn mode(self) -> ChannelMode {
+        match self {
+            Delivery::OrderedReliable => ChannelMode::OrderedReliable(ReliableSettings::default()),
+            Delivery::UnorderedUnreliable => ChannelMode::UnorderedUnreliable,
+        }
+    }
+}
+
+/// A channel's [`Delivery`], which is also what it is registered with, so code sending on
+/// it can tell what a successful send guarantees.
+pub trait ChannelDelivery {
+    const DELIVERY: Delivery;
+}
+
+/// Reliable channel for game flow messages; the other sub-protocols send on it too.
+#[derive(TypePath)]
+pub struct LobbyControlChannel;
+
+impl ChannelDelivery for LobbyControlChannel {
+    const DELIVERY: Delivery = Delivery::OrderedReliable;
+}
+
+/// Unreliable: a late voice frame is worse than a dropped one.
+#[derive(TypePath)]
+pub struct VoiceChannel;
+
+impl ChannelDelivery for VoiceChannel {
+    const DELIVERY: Delivery = Delivery::UnorderedUnreliable;
+}
+
+sub_protocol! {
+    /// Inputs, player identity, movement, level flow, voice and the periodic resync:
+    /// everything a client needs to join and walk around a level.
+    name: "core",
+    inputs: [
+        PlayerAction {
+            rebroadcast_inputs: true,
+            lag_compensation: true,
+        },
+    ],
+    components: [
+        PlayerId,
+        Name,
+        PlayerColor,
+        GameSeed,
+        LevelSeed,
+        CharacterMarker,
+        LevelExit,
+        MovingPlatform.add_prediction(),
+        Door.add_prediction(),
+        LevelButton,
+        Wired,
+        Rotation.add_prediction().add_linear_interpolation(),
+        Position.add_prediction().add_linear_interpolation(),
+        LinearVelocity.add_prediction(),
+        Stamina.add_prediction(),
+        StaminaConfig.add_prediction(),
+        GroundState, // Server authoritative
+        ClimbState.add_prediction(),
+        CrouchState.add_prediction(),
+        LookVelocity, // Server authoritative, visuals only
+        Afk,          // Server authoritative, scoreboard only
+        SimpleNavigationAgent,
+        PatrolRoute,
+        PatrolState,
+    ],
+    channels: [
+        LobbyControlChannel => Bidirectional,
+        VoiceChannel => Bidirectional,
+    ],
+    messages: [
+        LevelTransitionEvent => ServerToClient,
+        VoiceFrame => Bidirectional,
+        ResyncChecksum.add_map_entities() => ServerToClient,
+        ResyncRequest => ClientToServer,
+        ResyncKeyframe.add_map_entities() => ServerToClient,
+    ],
+}
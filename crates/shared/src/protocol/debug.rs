@@ -0,0 +1,70 @@
+use bevy::reflect::TypePath;
+
+use crate::protocol::{ChannelDelivery, Delivery};
+
+use serde::{Deserialize, Serialize};
+
+/// Read-only debug subscription: the server stops treating the sender as a player and
+/// streams `EntitySnapshot`s of replicated entities matching the filters. Only honored
+/// when the server has an inspector password and `password` matches it.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct EntitySnapshotSubscribe {
+    pub password: String,
+    /// Only include components whose name contains this string (case-insensitive).
+    pub component_filter: Option<String>,
+    /// Only include the entity with these bits (`Entity::to_bits`).
+    pub entity_filter: Option<u64>,
+}
+
+/// Debug-formatted state of one replicated entity on the server.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EntitySnapshotEntry {
+    pub entity: u64,
+    pub components: Vec<(String, String)>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct EntitySnapshot {
+    pub entities: Vec<EntitySnapshotEntry>,
+}
+
+/// Numbered heartbeat the server streams to every client on [`NetProbeChannel`]. Gaps in
+/// the sequence measure packet loss for the netgraph.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct NetProbe {
+    pub sequence: u32,
+}
+
+/// Seconds between two [`NetProbe`]s.
+pub const NET_PROBE_INTERVAL_SECS: f32 = 0.05;
+
+#[derive(TypePath)]
+pub struct DebugChannel;
+
+impl ChannelDelivery for DebugChannel {
+    const DELIVERY: Delivery = Delivery::OrderedReliable;
+}
+
+/// Unreliable so that lost probes stay lost.
+#[derive(TypePath)]
+pub struct NetProbeChannel;
+
+impl ChannelDelivery for NetProbeChannel {
+    const DELIVERY: Delivery = Delivery::UnorderedUnreliable;
+}
+
+sub_protocol! {
+    /// Entity inspector snapshots and the netgraph probes.
+    name: "debug",
+    inputs: [],
+    components: [],
+    channels: [
+        DebugChannel => Bidirectional,
+        NetProbeChannel => ServerToClient,
+    ],
+    messages: [
+        EntitySnapshotSubscribe => ClientToServer,
+        EntitySnapshot => ServerToClient,
+        NetProbe => ServerToClient,
+    ],
+}
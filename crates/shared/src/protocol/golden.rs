@@ -0,0 +1,874 @@
+//! Golden-file tests for the wire format. Every component, message and input the protocol
+//! registers is encoded with representative values the way lightyear sends it (bincode,
+//! standard config) and compared byte for byte with a fixture in `tests/golden/`, so a
+//! change to a networked type that would break old clients shows up as a failing test
+//! instead of a desync. The protocol hash covers the fields of messages as declared, not
+//! component layouts or how types from other crates encode.
+//!
+//! The fixtures are committed: a missing one fails like a changed one. Run with
+//! `BLESS_GOLDEN=1` to write them after adding a type or an intended format change, and
+//! commit the result.
+//!
+//! The decoders are also fuzzed with random and mutated bytes: a peer can send anything,
+//! and it must come back as an error, never a panic.
+
+use std::{collections::BTreeSet, fmt::Debug, fs, path::PathBuf, time::Duration};
+
+use avian3d::prelude::{LinearVelocity, Position, Rotation};
+use bevy::prelude::{Color, Entity, Name, Quat, Timer, TimerMode, Vec3};
+use lightyear::prelude::PeerId;
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use serde::{Serialize, de::DeserializeOwned};
+
+use super::*;
+use crate::afk::Afk;
+use crate::aim_assist::AimAssistSettings;
+use crate::bots::{BotDifficulty, BotProfile, MatchBotSettings};
+use crate::components::{
+    attachments::{Attachment, WeaponAttachments},
+    destructible::{DebrisPiece, Destructible},
+    flashlight::PlayerFlashlight,
+    grenade::Grenade,
+    health::{Health, Respawnable},
+    loadout::{Equipment, HolsteredGun, Loadout, PrimaryWeapon, SecondaryWeapon},
+    match_timer::MatchTimer,
+    pickup::{Pickup, PickupKind},
+    profile::PlayerProfile,
+    score::{MatchScore, PlayerScore},
+    shield::Shield,
+    stamina::{Stamina, StaminaConfig},
+    team::{Team, TeamRules},
+    weapons::{Gun, Projectile, ProjectileGun, WeaponCharge, WeaponHeat},
+    world_items::{WorldItem, WorldItemKind},
+};
+use crate::inputs::{
+    climbing::ClimbState, crouch::CrouchState, input::PlayerAction, look::LookVelocity,
+    movement::GroundState,
+};
+use crate::level::{
+    interactive::{Door, LevelButton, Wired},
+    platforms::{MovingPlatform, PlatformPath},
+    transition::LevelExit,
+};
+use crate::match_recap::MatchRecapEvent;
+use crate::navigation::{PatrolRoute, PatrolState, SimpleNavigationAgent};
+use crate::resync::{
+    CriticalState, ResyncChecksum, ResyncChecksumEntry, ResyncKeyframe, ResyncKeyframeEntry,
+    ResyncRequest,
+};
+
+const BLESS_ENV: &str = "BLESS_GOLDEN";
+const FUZZ_SEED: u64 = 0x5eed_0f_90_1de5;
+const RANDOM_INPUTS_PER_TYPE: usize = 256;
+const MAX_RANDOM_INPUT_LEN: usize = 96;
+
+/// One encoded value of a registered type.
+struct Case {
+    /// Type name, as it appears in the protocol manifest.
+    name: &'static str,
+    /// Tells apart several values of the same type; empty for the first.
+    variant: &'static str,
+    bytes: Vec<u8>,
+    /// Decodes bytes as the case's type and encodes the result again.
+    reencode: fn(&[u8]) -> Result<Vec<u8>, String>,
+}
+
+impl Case {
+    fn fixture_path(&self) -> PathBuf {
+        let file = if self.variant.is_empty() {
+            format!("{}.bin", self.name)
+        } else {
+            format!("{}-{}.bin", self.name, self.variant)
+        };
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/golden")
+            .join(file)
+    }
+
+    fn label(&self) -> String {
+        if self.variant.is_empty() {
+            self.name.to_string()
+        } else {
+            format!("{} ({})", self.name, self.variant)
+        }
+    }
+}
+
+fn encode<T: Serialize>(value: &T) -> Vec<u8> {
+    bincode::serde::encode_to_vec(value, bincode::config::standard()).expect("encodable value")
+}
+
+fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, String> {
+    let (value, read) = bincode::serde::decode_from_slice(bytes, bincode::config::standard())
+        .map_err(|e| e.to_string())?;
+    if read != bytes.len() {
+        return Err(format!("{} trailing bytes", bytes.len() - read));
+    }
+    Ok(value)
+}
+
+fn reencode<T: Serialize + DeserializeOwned>(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    decode::<T>(bytes).map(|value| encode(&value))
+}
+
+/// Encodes `value` and checks it decodes back to itself.
+fn case<T>(name: &'static str, variant: &'static str, value: T) -> Case
+where
+    T: Serialize + DeserializeOwned + PartialEq + Debug,
+{
+    let bytes = encode(&value);
+    let decoded: T = decode(&bytes).unwrap_or_else(|e| panic!("{}: {}", name, e));
+    assert_eq!(decoded, value, "{} does not round-trip", name);
+    Case {
+        name,
+        variant,
+        bytes,
+        reencode: reencode::<T>,
+    }
+}
+
+fn entity(index: u64) -> Entity {
+    Entity::from_bits(index)
+}
+
+fn ticked_timer(secs: f32, mode: TimerMode, elapsed_ms: u64) -> Timer {
+    let mut timer = Timer::from_seconds(secs, mode);
+    timer.tick(Duration::from_millis(elapsed_ms));
+    timer
+}
+
+fn gun() -> Gun {
+    Gun {
+        cooldown: ticked_timer(0.3, TimerMode::Once, 120),
+        ammo_in_magazine: 17,
+        ..Gun::default()
+    }
+}
+
+fn health() -> Health {
+    Health {
+        current: 62.5,
+        last_damage_time: 12.25,
+        ..Health::basic()
+    }
+}
+
+fn bot_profile() -> BotProfile {
+    BotProfile {
+        reaction_time: 0.35,
+        aggression_level: 0.7,
+        accuracy: 0.55,
+    }
+}
+
+fn match_score() -> MatchScore {
+    MatchScore {
+        players: vec![
+            PlayerScore {
+                player_id: 1,
+                kills: 7,
+                deaths: 2,
+                assists: 3,
+            },
+            PlayerScore {
+                player_id: 42,
+                kills: 0,
+                deaths: 5,
+                assists: 1,
+            },
+        ],
+    }
+}
+
+fn player_profile() -> PlayerProfile {
+    PlayerProfile {
+        name: "Ada (2)".to_string(),
+        color: Some(3),
+    }
+}
+
+/// Every registered component.
+fn component_cases() -> Vec<Case> {
+    let mut heated_gun = gun();
+    heated_gun.heat = Some(WeaponHeat::new(0.1, 0.35, 1.6));
+    let mut reloading_gun = gun();
+    reloading_gun.start_reload();
+    let mut charge = WeaponCharge::new(1.2, 0.3);
+    charge.held_secs = 0.5;
+    let charging_gun = gun().with_charge(charge);
+
+    vec![
+        case("Afk", "", Afk),
+        case(
+            "AimAssistSettings",
+            "",
+            AimAssistSettings {
+                enabled: true,
+                ranked: false,
+            },
+        ),
+        case("BotProfile", "", bot_profile()),
+        case("CharacterMarker", "", CharacterMarker),
+        case(
+            "ClimbState",
+            "",
+            ClimbState {
+                ladder_facing: None,
+                mantle_target: Some(Vec3::new(2.0, 1.25, -3.5)),
+                mantle_secs: 0.25,
+            },
+        ),
+        case(
+            "CrouchState",
+            "",
+            CrouchState {
+                crouched: true,
+                toggled: false,
+                slide_secs: 0.5,
+            },
+        ),
+        case("DebrisPiece", "", DebrisPiece { size: 0.4 }),
+        case(
+            "Destructible",
+            "",
+            Destructible {
+                size: Vec3::new(2.0, 1.5, 0.5),
+                debris_pieces: 12,
+            },
+        ),
+        case(
+            "Door",
+            "",
+            Door {
+                closed_at: Vec3::new(4.0, 1.5, -2.0),
+                size: Vec3::new(2.0, 3.0, 0.2),
+                open: true,
+                progress: 0.25,
+            },
+        ),
+        case("GameSeed", "", GameSeed { seed: u64::MAX - 7 }),
+        case("Grenade", "", Grenade),
+        case(
+            "GroundState",
+            "",
+            GroundState {
+                is_grounded: true,
+                ground_normal: Vec3::new(0.0, 0.98, 0.2),
+                ground_distance: 0.05,
+                ground_tick: 3,
+                ground_velocity: Vec3::new(1.5, 0.0, -0.5),
+            },
+        ),
+        case("Gun", "", gun()),
+        case("Gun", "charging", charging_gun),
+        case("Gun", "heat", heated_gun),
+        case("Gun", "reloading", reloading_gun),
+        case("Health", "", health()),
+        case("HolsteredGun", "", HolsteredGun(gun())),
+        case(
+            "LevelExit",
+            "",
+            LevelExit {
+                position: Vec3::new(40.0, 0.5, -12.0),
+                radius: 2.5,
+            },
+        ),
+        case("LevelButton", "", LevelButton { channel: 3 }),
+        case("LevelSeed", "", LevelSeed { seed: 1234 }),
+        case(
+            "LinearVelocity",
+            "",
+            LinearVelocity(Vec3::new(3.0, -9.5, 0.25)),
+        ),
+        case(
+            "Loadout",
+            "",
+            Loadout {
+                primary: PrimaryWeapon::Marksman,
+                secondary: SecondaryWeapon::MachinePistol,
+                equipment: Equipment::ArmorPlate,
+            },
+        ),
+        case(
+            "LobbyState",
+            "",
+            LobbyState {
+                players: vec![1, 42, 7],
+                host_id: 1,
+                // One entry: the map iterates in random order, so more would not encode
+                // deterministically.
+                teams: [(42, Team::Blue)].into_iter().collect(),
+                profiles: [(7, player_profile())].into_iter().collect(),
+            },
+        ),
+        case(
+            "LookVelocity",
+            "",
+            LookVelocity {
+                yaw: 1.25,
+                pitch: -0.5,
+            },
+        ),
+        case(
+            "MatchBotSettings",
+            "",
+            MatchBotSettings {
+                bot_count: 4,
+                difficulty: BotDifficulty::Hard,
+            },
+        ),
+        case(
+            "MatchBotSettings",
+            "custom",
+            MatchBotSettings {
+                bot_count: 2,
+                difficulty: BotDifficulty::Custom(bot_profile()),
+            },
+        ),
+        case("MatchScore", "", match_score()),
+        case(
+            "MatchTimer",
+            "",
+            MatchTimer {
+                remaining_secs: 95.5,
+            },
+        ),
+        case(
+            "MovingPlatform",
+            "",
+            MovingPlatform {
+                path: PlatformPath {
+                    start: Vec3::new(0.0, 1.0, 0.0),
+                    end: Vec3::new(10.0, 1.0, 4.0),
+                    travel_secs: 3.0,
+                    dwell_secs: 1.5,
+                },
+                phase: 0.25,
+                running: true,
+            },
+        ),
+        case("Name", "", Name::new("Player 42")),
+        case(
+            "PatrolRoute",
+            "",
+            PatrolRoute::new(vec![
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(5.0, 0.0, 5.0),
+                Vec3::new(-3.0, 0.0, 8.0),
+            ]),
+        ),
+        case(
+            "PatrolState",
+            "",
+            PatrolState {
+                current_target_index: 2,
+                wait_timer: 0.5,
+                wait_duration: 2.0,
+                forward: false,
+            },
+        ),
+        case(
+            "Pickup",
+            "",
+            Pickup {
+                kind: PickupKind::HealthPack,
+                available: true,
+            },
+        ),
+        case(
+            "Pickup",
+            "attachment",
+            Pickup {
+                kind: PickupKind::Attachment(Attachment::Suppressor),
+                available: false,
+            },
+        ),
+        case("PlayerColor", "", PlayerColor(Color::srgb(0.9, 0.2, 0.1))),
+        case(
+            "PlayerFlashlight",
+            "",
+            PlayerFlashlight {
+                is_on: true,
+                ..PlayerFlashlight::new()
+            },
+        ),
+        case("PlayerId", "", PlayerId(PeerId::Netcode(42))),
+        case("Position", "", Position(Vec3::new(-4.0, 1.0, 18.5))),
+        case(
+            "Projectile",
+            "",
+            Projectile {
+                damage: 40.0,
+                shooter: entity(17),
+                lifetime: ticked_timer(3.0, TimerMode::Once, 500),
+                has_hit: false,
+            },
+        ),
+        case(
+            "ProjectileGun",
+            "",
+            ProjectileGun {
+                cooldown: ticked_timer(0.5, TimerMode::Repeating, 250),
+            },
+        ),
+        case(
+            "Respawnable",
+            "",
+            Respawnable {
+                death_time: 30.0,
+                respawn_position: Some(Vec3::new(1.0, 2.0, 3.0)),
+                ..Respawnable::new(5.0)
+            },
+        ),
+        case(
+            "Rotation",
+            "",
+            Rotation(Quat::from_rotation_y(std::f32::consts::FRAC_PI_3)),
+        ),
+        case(
+            "Shield",
+            "",
+            Shield {
+                current: 20.0,
+                ..Shield::new(50.0, 0.5, 5.0, 3.0)
+            },
+        ),
+        case(
+            "SimpleNavigationAgent",
+            "",
+            SimpleNavigationAgent {
+                current_target: Some(Vec3::new(6.0, 0.0, -2.0)),
+                ..SimpleNavigationAgent::new(4.5)
+            },
+        ),
+        case(
+            "Stamina",
+            "",
+            Stamina {
+                current: 12.5,
+                exhausted: true,
+                lockout_secs: 0.5,
+                ..Stamina::default()
+            },
+        ),
+        case(
+            "StaminaConfig",
+            "",
+            StaminaConfig {
+                drain_rate: 30.0,
+                ..StaminaConfig::default()
+            },
+        ),
+        case("Team", "", Team::Blue),
+        case(
+            "TeamRules",
+            "",
+            TeamRules {
+                friendly_fire: true,
+            },
+        ),
+        case(
+            "WeaponAttachments",
+            "",
+            WeaponAttachments {
+                equipped: vec![Attachment::Scope, Attachment::ExtendedMagazine],
+            },
+        ),
+        case("Wired", "", Wired(3)),
+        case(
+            "WorldItem",
+            "",
+            WorldItem {
+                kind: WorldItemKind::Loot,
+                color: Color::srgba(0.8, 0.7, 0.1, 0.9),
+            },
+        ),
+    ]
+}
+
+/// Every registered message.
+fn message_cases() -> Vec<Case> {
+    vec![
+        case(
+            "BotChatMessage",
+            "",
+            BotChatMessage {
+                speaker: "Bot_3".to_string(),
+                text: "Too easy.".to_string(),
+            },
+        ),
+        case(
+            "ClientWorldCreatedEvent",
+            "",
+            ClientWorldCreatedEvent { client_id: 42 },
+        ),
+        case(
+            "DebrisBurst",
+            "",
+            DebrisBurst {
+                origin: Vec3::new(3.0, 1.0, -7.0),
+                size: Vec3::new(2.0, 2.0, 0.5),
+                seed: 987_654_321,
+                pieces: 9,
+            },
+        ),
+        case(
+            "EntitySnapshot",
+            "",
+            EntitySnapshot {
+                entities: vec![EntitySnapshotEntry {
+                    entity: 4_294_967_338,
+                    components: vec![
+                        ("Health".to_string(), "62.5/100".to_string()),
+                        ("Name".to_string(), "Bot 3".to_string()),
+                    ],
+                }],
+            },
+        ),
+        case(
+            "EntitySnapshotSubscribe",
+            "",
+            EntitySnapshotSubscribe {
+                password: "hunter2".to_string(),
+                component_filter: Some("Health".to_string()),
+                entity_filter: Some(42),
+            },
+        ),
+        case(
+            "EquipAttachmentsRequest",
+            "",
+            EquipAttachmentsRequest {
+                attachments: vec![Attachment::RedDot, Attachment::Compensator],
+            },
+        ),
+        case(
+            "GrenadeExplosion",
+            "",
+            GrenadeExplosion {
+                origin: Vec3::new(10.0, 0.2, 4.0),
+                radius: 6.0,
+            },
+        ),
+        case(
+            "HitConfirmation",
+            "",
+            HitConfirmation {
+                kill: true,
+                headshot: false,
+            },
+        ),
+        case(
+            "HostStartGameEvent",
+            "",
+            HostStartGameEvent { requested: true },
+        ),
+        case(
+            "KillFeedEvent",
+            "",
+            KillFeedEvent {
+                killer: Some("Player 1".to_string()),
+                victim: "Bot 2".to_string(),
+                headshot: true,
+            },
+        ),
+        case(
+            "KillFeedEvent",
+            "no-killer",
+            KillFeedEvent {
+                killer: None,
+                victim: "Player 42".to_string(),
+                headshot: false,
+            },
+        ),
+        case(
+            "LevelTransitionEvent",
+            "",
+            LevelTransitionEvent { seed: 31_337 },
+        ),
+        case(
+            "MatchEndedEvent",
+            "",
+            MatchEndedEvent {
+                final_score: match_score(),
+            },
+        ),
+        case(
+            "MatchRecapEvent",
+            "",
+            MatchRecapEvent {
+                text: "Player 1 won with 7 kills".to_string(),
+            },
+        ),
+        case("NetProbe", "", NetProbe { sequence: 65_537 }),
+        case(
+            "PlayerProfileRequest",
+            "",
+            PlayerProfileRequest {
+                profile: player_profile(),
+            },
+        ),
+        case("PreloadLevelEvent", "", PreloadLevelEvent { seed: 1337 }),
+        case(
+            "ResumeSessionRequest",
+            "",
+            ResumeSessionRequest {
+                token: 0xdead_beef_cafe,
+            },
+        ),
+        case(
+            "ResyncChecksum",
+            "",
+            ResyncChecksum {
+                round: 12,
+                entries: vec![
+                    ResyncChecksumEntry {
+                        entity: entity(5),
+                        checksum: 0x0123_4567_89ab_cdef,
+                        at_rest: true,
+                    },
+                    ResyncChecksumEntry {
+                        entity: entity((1 << 32) | 9),
+                        checksum: 77,
+                        at_rest: false,
+                    },
+                ],
+            },
+        ),
+        case(
+            "ResyncKeyframe",
+            "",
+            ResyncKeyframe {
+                entries: vec![
+                    ResyncKeyframeEntry {
+                        entity: entity(5),
+                        state: CriticalState {
+                            position: Vec3::new(1.0, 2.0, 3.0),
+                            rotation: Quat::from_rotation_x(0.5),
+                            health: Some(health()),
+                        },
+                    },
+                    ResyncKeyframeEntry {
+                        entity: entity(6),
+                        state: CriticalState {
+                            position: Vec3::new(-8.0, 0.0, 2.5),
+                            rotation: Quat::IDENTITY,
+                            health: None,
+                        },
+                    },
+                ],
+            },
+        ),
+        case(
+            "ResyncRequest",
+            "",
+            ResyncRequest {
+                round: 12,
+                mismatched: vec![5, (1 << 31) | 9],
+            },
+        ),
+        case("ReturnToLobbyEvent", "", ReturnToLobbyEvent),
+        case(
+            "ServerAnnouncement",
+            "",
+            ServerAnnouncement {
+                text: "Server restarting in 5 minutes".to_string(),
+            },
+        ),
+        case("SessionGranted", "", SessionGranted { token: 99 }),
+        case("SpectateRequest", "", SpectateRequest { spectate: true }),
+        case(
+            "StartLoadingGameEvent",
+            "",
+            StartLoadingGameEvent { start: true },
+        ),
+        case(
+            "SubmitLoadoutRequest",
+            "",
+            SubmitLoadoutRequest {
+                loadout: Loadout {
+                    primary: PrimaryWeapon::Shotgun,
+                    ..Loadout::default()
+                },
+            },
+        ),
+        case(
+            "TeamSelectRequest",
+            "",
+            TeamSelectRequest { team: Team::Red },
+        ),
+        case(
+            "VoiceFrame",
+            "",
+            VoiceFrame {
+                speaker: 42,
+                sequence: 300,
+                data: (0..40).map(|i| (i * 37 % 256) as u8).collect(),
+            },
+        ),
+    ]
+}
+
+/// Every registered input.
+fn input_cases() -> Vec<Case> {
+    vec![
+        case("PlayerAction", "", PlayerAction::Move),
+        case("PlayerAction", "last", PlayerAction::ToggleCrouch),
+    ]
+}
+
+fn all_cases() -> Vec<Case> {
+    let mut cases = component_cases();
+    cases.extend(message_cases());
+    cases.extend(input_cases());
+    cases
+}
+
+/// Names of the components, messages and inputs in the protocol manifest, by list.
+fn manifest_names() -> [(&'static str, BTreeSet<String>); 3] {
+    let manifest = crate::manifest::protocol_manifest();
+    [
+        (
+            "components",
+            manifest
+                .components
+                .iter()
+                .map(|c| c.name.to_string())
+                .collect(),
+        ),
+        (
+            "messages",
+            manifest
+                .messages
+                .iter()
+                .map(|m| m.name.to_string())
+                .collect(),
+        ),
+        (
+            "inputs",
+            manifest.inputs.iter().map(|i| i.name.to_string()).collect(),
+        ),
+    ]
+}
+
+#[test]
+fn every_registered_type_has_a_golden_case() {
+    let covered: BTreeSet<String> = all_cases()
+        .iter()
+        .map(|case| case.name.to_string())
+        .collect();
+    for (list, names) in manifest_names() {
+        let missing: Vec<String> = names.difference(&covered).cloned().collect();
+        assert!(
+            missing.is_empty(),
+            "registered {} without a golden case: {:?}",
+            list,
+            missing
+        );
+    }
+}
+
+#[test]
+fn encodings_match_golden_files() {
+    let bless = std::env::var_os(BLESS_ENV).is_some_and(|value| value == "1");
+    for case in all_cases() {
+        let path = case.fixture_path();
+        match fs::read(&path) {
+            Ok(golden) if !bless => {
+                assert!(
+                    golden == case.bytes,
+                    "the wire format of {} changed, so builds before this one can no longer \
+                     talk to it. If that is intended, rerun with {}=1 and commit {}.",
+                    case.label(),
+                    BLESS_ENV,
+                    path.display()
+                );
+                // Old fixtures must still decode, to the same bytes.
+                assert_eq!(
+                    (case.reencode)(&golden).as_deref(),
+                    Ok(golden.as_slice()),
+                    "{}",
+                    case.label()
+                );
+            }
+            Err(error) if !bless => panic!(
+                "no golden file for {} at {} ({}). Rerun with {}=1 to write it, and commit it.",
+                case.label(),
+                path.display(),
+                error,
+                BLESS_ENV
+            ),
+            _ => {
+                fs::create_dir_all(path.parent().expect("fixture directory"))
+                    .expect("create fixture directory");
+                fs::write(&path, &case.bytes)
+                    .unwrap_or_else(|e| panic!("write {}: {}", path.display(), e));
+            }
+        }
+    }
+}
+
+/// Decoding must fail cleanly or produce a value that encodes back to stable bytes.
+fn check_decode(case: &Case, bytes: &[u8]) {
+    let result = std::panic::catch_unwind(|| (case.reencode)(bytes));
+    let Ok(result) = result else {
+        panic!("{} panicked decoding {:02x?}", case.label(), bytes);
+    };
+    if let Ok(reencoded) = result {
+        assert_eq!(
+            (case.reencode)(&reencoded).as_deref(),
+            Ok(reencoded.as_slice()),
+            "{} is unstable after decoding {:02x?}",
+            case.label(),
+            bytes
+        );
+    }
+}
+
+#[test]
+fn decoders_survive_arbitrary_bytes() {
+    let mut rng = StdRng::seed_from_u64(FUZZ_SEED);
+    for case in all_cases() {
+        for _ in 0..RANDOM_INPUTS_PER_TYPE {
+            let len = rng.random_range(0..=MAX_RANDOM_INPUT_LEN);
+            let bytes: Vec<u8> = (0..len).map(|_| rng.random()).collect();
+            check_decode(&case, &bytes);
+        }
+    }
+}
+
+#[test]
+fn decoders_survive_corrupted_encodings() {
+    let mut rng = StdRng::seed_from_u64(FUZZ_SEED);
+    for case in all_cases() {
+        let valid = &case.bytes;
+        for len in 0..valid.len() {
+            check_decode(&case, &valid[..len]);
+        }
+        for bit in 0..valid.len() * 8 {
+            let mut flipped = valid.clone();
+            flipped[bit / 8] ^= 1 << (bit % 8);
+            check_decode(&case, &flipped);
+        }
+        for _ in 0..RANDOM_INPUTS_PER_TYPE {
+            let mut mutated = valid.clone();
+            for _ in 0..rng.random_range(1..=4) {
+                match rng.random_range(0..3) {
+                    0 if !mutated.is_empty() => {
+                        let at = rng.random_range(0..mutated.len());
+                        mutated[at] = rng.random();
+                    }
+                    1 => {
+                        let at = rng.random_range(0..=mutated.len());
+                        mutated.insert(at, rng.random());
+                    }
+                    _ if !mutated.is_empty() => {
+                        let at = rng.random_range(0..mutated.len());
+                        mutated.remove(at);
+                    }
+                    _ => {}
+                }
+            }
+            check_decode(&case, &mutated);
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use crate::aim_assist::AimAssistSettings;
+use crate::bots::{BotProfile, MatchBotSettings};
+use crate::components::match_timer::MatchTimer;
+use crate::components::profile::{PlayerProfile, PlayerProfiles};
+use crate::components::score::MatchScore;
+use crate::components::team::{Team, TeamAssignments, TeamRules};
+use crate::match_recap::MatchRecapEvent;
+use bevy::prelude::Component;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Component, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LobbyState {
+    pub players: Vec<u64>,
+    pub host_id: u64,
+    pub teams: TeamAssignments,
+    /// Validated name and color of the players that sent one.
+    pub profiles: PlayerProfiles,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ClientWorldCreatedEvent {
+    pub client_id: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct HostStartGameEvent {
+    pub requested: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StartLoadingGameEvent {
+    pub start: bool,
+}
+
+/// Client request to stop (or resume) playing and watch the match as a spectator.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SpectateRequest {
+    pub spectate: bool,
+}
+
+/// Client request to play on another team. The server refuses switches that would leave
+/// the teams more than one player apart.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TeamSelectRequest {
+    pub team: Team,
+}
+
+/// Name and color a client wants, sent on connect and whenever the player edits them. The
+/// server cleans the name up and makes it unique before storing it in [`LobbyState`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PlayerProfileRequest {
+    pub profile: PlayerProfile,
+}
+
+/// Sent when the match timer runs out. Clients show `final_score` until the server sends
+/// everyone back to the lobby.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MatchEndedEvent {
+    pub final_score: MatchScore,
+}
+
+/// Seed of the next match's level, sent when the post-game screen starts and whenever the
+/// next map changes, so clients can generate it in the background.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PreloadLevelEvent {
+    pub seed: u64,
+}
+
+/// Sent once the post-game screen is over and the server has cleared the match.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ReturnToLobbyEvent;
+
+/// Sent to a client when it joins, and again after every successful resume. A client whose
+/// connection drops presents the token on reconnect to reclaim its character.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SessionGranted {
+    pub token: u64,
+}
+
+/// Client request to take back the character it left behind when its connection dropped.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ResumeSessionRequest {
+    pub token: u64,
+}
+
+/// Message from the server operator, shown to every player.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ServerAnnouncement {
+    pub text: String,
+}
+
+/// A line said by a bot, shown in chat under its name.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BotChatMessage {
+    pub speaker: String,
+    pub text: String,
+}
+
+sub_protocol! {
+    /// Lobby roster, teams and player profiles, match setup (bots, aim assist, team rules),
+    /// the start-of-game handshake, session tokens for reconnects, operator announcements,
+    /// bot chat and the end-of-match flow (final scores, recap, next level preload) back to
+    /// the lobby. Sent on the core `LobbyControlChannel`.
+    name: "lobby",
+    inputs: [],
+    components: [
+        LobbyState,
+        MatchBotSettings,
+        BotProfile,
+        AimAssistSettings,
+        Team,
+        TeamRules,
+        MatchTimer,
+    ],
+    channels: [],
+    messages: [
+        ClientWorldCreatedEvent => ClientToServer,
+        HostStartGameEvent => ClientToServer,
+        StartLoadingGameEvent => ServerToClient,
+        MatchEndedEvent => ServerToClient,
+        PreloadLevelEvent => ServerToClient,
+        ReturnToLobbyEvent => ServerToClient,
+        MatchRecapEvent => ServerToClient,
+        SessionGranted => ServerToClient,
+        ResumeSessionRequest => ClientToServer,
+        ServerAnnouncement => ServerToClient,
+        BotChatMessage => ServerToClient,
+        SpectateRequest => ClientToServer,
+        TeamSelectRequest => ClientToServer,
+        PlayerProfileRequest => ClientToServer,
+    ],
+}
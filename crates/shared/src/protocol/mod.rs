@@ -0,0 +1,142 @@
+//! Network protocol, split into sub-protocols that each register their own inputs,
+//! components, channels and messages:
+//!
+//! - `core`: player identity, movement, level flow, voice and resync. Always enabled.
+//! - `combat`: health, weapons, projectiles and destructibles (feature `combat`).
+//! - `lobby`: lobby state and match setup (feature `lobby`).
+//! - `debug`: entity inspector and netgraph probes (feature `debug`).
+//!
+//! Lightyear assigns network ids in registration order, so [`ProtocolPlugin`] always adds
+//! the sub-protocols in the order above. Client and server must be built with the same
+//! features; the protocol manifest (and so the protocol id) changes with them.
+
+/// Declares what a sub-protocol registers, in registration order. The one list both
+/// registers it with an app and describes it in the protocol manifest, so the two cannot
+/// drift apart. Component and message entries may chain registration calls, such as
+/// `Position.add_prediction().add_linear_interpolation()`.
+macro_rules! sub_protocol {
+    (
+        $(#[$meta:meta])*
+        name: $name:literal,
+        inputs: [$($input:ident { $($config:ident: $value:expr),* $(,)? }),* $(,)?],
+        components: [$($component:ident $(. $component_call:ident())*),* $(,)?],
+        channels: [$($channel:ident => $channel_direction:ident),* $(,)?],
+        messages: [
+            $($message:ident $(. $message_call:ident())* => $message_direction:ident),* $(,)?
+        ] $(,)?
+    ) => {
+        fn register(app: &mut ::bevy::prelude::App) {
+            #[allow(unused_imports)]
+            use ::lightyear::prelude::{
+                AppChannelExt, AppComponentExt, AppMessageExt, InterpolationRegistrationExt,
+                PredictionRegistrationExt,
+            };
+
+            $(app.add_plugins(::lightyear::prelude::input::leafwing::InputPlugin::<$input> {
+                config: ::lightyear::input::config::InputConfig::<$input> {
+                    $($config: $value,)*
+                    ..::bevy::prelude::default()
+                },
+            });)*
+            $(app.register_component::<$component>()$(.$component_call())*;)*
+            $(app.add_channel::<$channel>(::lightyear::prelude::ChannelSettings {
+                mode: <$channel as super::ChannelDelivery>::DELIVERY.mode(),
+                ..::bevy::prelude::default()
+            })
+            .add_direction(::lightyear::prelude::NetworkDirection::$channel_direction);)*
+            $(app.register_message::<$message>()$(.$message_call())*
+                .add_direction(::lightyear::prelude::NetworkDirection::$message_direction);)*
+        }
+
+        fn describe(manifest: &mut $crate::manifest::ManifestBuilder) {
+            manifest.protocol($name);
+            $(manifest.input::<$input>(stringify!($input));)*
+            $(manifest.component(
+                stringify!($component),
+                &[$(stringify!($component_call)),*],
+            );)*
+            $(manifest.channel(
+                stringify!($channel),
+                <$channel as super::ChannelDelivery>::DELIVERY,
+                stringify!($channel_direction),
+            );)*
+            $(manifest.message::<$message>(
+                stringify!($message),
+                stringify!($message_direction),
+            );)*
+        }
+
+        $(#[$meta])*
+        pub(super) const SUB_PROTOCOL: super::SubProtocol = super::SubProtocol {
+            register,
+            describe,
+        };
+    };
+}
+
+mod core;
+
+#[cfg(feature = "combat")]
+mod combat;
+#[cfg(feature = "debug")]
+mod debug;
+#[cfg(all(test, feature = "combat", feature = "lobby", feature = "debug"))]
+mod golden;
+#[cfg(feature = "lobby")]
+mod lobby;
+
+#[cfg(feature = "combat")]
+pub use self::combat::*;
+pub use self::core::*;
+#[cfg(feature = "debug")]
+pub use self::debug::*;
+#[cfg(feature = "lobby")]
+pub use self::lobby::*;
+
+use bevy::{
+    log::debug,
+    prelude::{App, Plugin, default},
+};
+
+use crate::manifest::ManifestBuilder;
+
+/// What a sub-protocol registers, see `sub_protocol!`.
+struct SubProtocol {
+    register: fn(&mut App),
+    describe: fn(&mut ManifestBuilder),
+}
+
+/// Keep this order: it decides the network ids of everything registered.
+const SUB_PROTOCOLS: &[SubProtocol] = &[
+    self::core::SUB_PROTOCOL,
+    #[cfg(feature = "combat")]
+    self::combat::SUB_PROTOCOL,
+    #[cfg(feature = "lobby")]
+    self::lobby::SUB_PROTOCOL,
+    #[cfg(feature = "debug")]
+    self::debug::SUB_PROTOCOL,
+];
+
+#[derive(Clone)]
+pub struct ProtocolPlugin;
+impl Plugin for ProtocolPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(avian3d::physics_transform::PhysicsTransformConfig {
+            transform_to_position: false,
+            position_to_transform: true,
+            ..default()
+        });
+        for sub_protocol in SUB_PROTOCOLS {
+            (sub_protocol.register)(app);
+        }
+
+        debug!("Protocol plugin initialized with components, messages, inputs, and events");
+    }
+}
+
+/// Describes everything [`ProtocolPlugin`] registers in `manifest`, in the same order.
+pub(crate) fn describe_protocol(manifest: &mut ManifestBuilder) {
+    for sub_protocol in SUB_PROTOCOLS {
+        (sub_protocol.describe)(manifest);
+    }
+}
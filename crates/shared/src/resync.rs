@@ -0,0 +1,163 @@
+//! Low-frequency authoritative resync, a safety net against replication drift. Every
+//! `RESYNC_INTERVAL_SECS` the server sends a checksum of the critical state of each
+//! replicated character; a client whose own copy keeps disagreeing asks for a keyframe and
+//! overwrites its state with the server's.
+
+use bevy::ecs::entity::{EntityMapper, MapEntities};
+use bevy::prelude::{Entity, Quat, Vec3};
+use serde::{Deserialize, Serialize};
+
+use crate::components::health::Health;
+use crate::game_math::yaw_of;
+
+/// Seconds between two checksum rounds.
+pub const RESYNC_INTERVAL_SECS: f32 = 5.0;
+/// Consecutive rounds an entity must mismatch before the client asks for a keyframe. A
+/// single mismatch is usually a replication update still in flight.
+pub const RESYNC_MISMATCH_ROUNDS: u32 = 2;
+/// Positions are compared on this grid, in meters.
+pub const RESYNC_POSITION_GRID: f32 = 0.25;
+/// Yaw is compared in steps of this many radians.
+pub const RESYNC_YAW_STEP: f32 = 0.05;
+/// Below this speed an entity is at rest and its pose is part of the checksum. Moving
+/// entities are always a little behind on clients, so only their health is compared.
+pub const RESYNC_AT_REST_SPEED: f32 = 0.05;
+
+/// The state the resync protects: pose and health.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CriticalState {
+    pub position: Vec3,
+    pub rotation: Quat,
+    pub health: Option<Health>,
+}
+
+impl CriticalState {
+    /// FNV-1a over the quantized state, so it is identical on every build and platform.
+    /// `include_pose` leaves position and rotation out for moving entities.
+    pub fn checksum(&self, include_pose: bool) -> u64 {
+        let mut hash = Fnv1a::default();
+        if include_pose {
+            let cell = (self.position / RESYNC_POSITION_GRID).round();
+            hash.write_i32(cell.x as i32);
+            hash.write_i32(cell.y as i32);
+            hash.write_i32(cell.z as i32);
+            hash.write_i32((yaw_of(self.rotation) / RESYNC_YAW_STEP).round() as i32);
+        }
+        match &self.health {
+            Some(health) => {
+                hash.write_i32(health.current.round() as i32);
+                hash.write_i32(health.max.round() as i32);
+                hash.write_i32(health.is_dead as i32);
+            }
+            None => hash.write_i32(-1),
+        }
+        hash.0
+    }
+}
+
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Fnv1a {
+    fn write_i32(&mut self, value: i32) {
+        for byte in value.to_le_bytes() {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ResyncChecksumEntry {
+    pub entity: Entity,
+    pub checksum: u64,
+    /// Whether the pose is part of `checksum`.
+    pub at_rest: bool,
+}
+
+/// One checksum round, sent to every client.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ResyncChecksum {
+    pub round: u32,
+    pub entries: Vec<ResyncChecksumEntry>,
+}
+
+impl MapEntities for ResyncChecksum {
+    fn map_entities<E: EntityMapper>(&mut self, entity_mapper: &mut E) {
+        for entry in &mut self.entries {
+            entry.entity = entity_mapper.get_mapped(entry.entity);
+        }
+    }
+}
+
+/// Client request for a keyframe of the entries of checksum `round` at `mismatched`
+/// indices. Indices avoid mapping entities back to the server's.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ResyncRequest {
+    pub round: u32,
+    pub mismatched: Vec<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ResyncKeyframeEntry {
+    pub entity: Entity,
+    pub state: CriticalState,
+}
+
+/// Authoritative critical state of the entities a client asked for.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ResyncKeyframe {
+    pub entries: Vec<ResyncKeyframeEntry>,
+}
+
+impl MapEntities for ResyncKeyframe {
+    fn map_entities<E: EntityMapper>(&mut self, entity_mapper: &mut E) {
+        for entry in &mut self.entries {
+            entry.entity = entity_mapper.get_mapped(entry.entity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CriticalState, RESYNC_POSITION_GRID};
+    use crate::components::health::Health;
+    use bevy::prelude::{Quat, Vec3};
+
+    fn state(position: Vec3, current_health: f32) -> CriticalState {
+        let mut health = Health::basic();
+        health.current = current_health;
+        CriticalState {
+            position,
+            rotation: Quat::from_rotation_y(0.5),
+            health: Some(health),
+        }
+    }
+
+    #[test]
+    fn checksum_ignores_noise_but_catches_drift() {
+        let server = state(Vec3::new(4.0, 1.0, -2.0), 80.0);
+        let noisy = state(Vec3::new(4.0 + RESYNC_POSITION_GRID * 0.1, 1.0, -2.0), 80.2);
+        let drifted = state(Vec3::new(9.0, 1.0, -2.0), 80.0);
+        let hurt = state(Vec3::new(4.0, 1.0, -2.0), 35.0);
+
+        assert_eq!(server.checksum(true), noisy.checksum(true));
+        assert_ne!(server.checksum(true), drifted.checksum(true));
+        assert_ne!(server.checksum(true), hurt.checksum(true));
+        assert_ne!(server.checksum(false), hurt.checksum(false));
+    }
+
+    #[test]
+    fn moving_entities_are_compared_on_health_only() {
+        let server = state(Vec3::new(4.0, 1.0, -2.0), 80.0);
+        let behind = state(Vec3::new(2.5, 1.0, -2.0), 80.0);
+
+        assert_ne!(server.checksum(true), behind.checksum(true));
+        assert_eq!(server.checksum(false), behind.checksum(false));
+    }
+}
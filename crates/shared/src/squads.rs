@@ -0,0 +1,394 @@
+use bevy::prelude::{Component, Entity, Quat, Vec3};
+use std::collections::HashMap;
+
+use crate::components::grenade::GRENADE_BLAST_RADIUS;
+
+/// Bots per squad.
+pub const SQUAD_SIZE: usize = 3;
+/// How long a spotted enemy stays on the blackboard after the squad loses sight of it.
+pub const SQUAD_MEMORY_SECS: f32 = 6.0;
+/// Distance the pointman closes to.
+pub const POINTMAN_DISTANCE: f32 = 8.0;
+/// Distance flankers keep, off to the side of the pointman's line of fire.
+pub const FLANK_DISTANCE: f32 = 10.0;
+/// Distance support holds, behind the pointman.
+pub const SUPPORT_DISTANCE: f32 = 16.0;
+/// Angle between the pointman's and a flanker's line of fire, so the enemy is caught in a
+/// crossfire.
+pub const CROSSFIRE_ANGLE: f32 = std::f32::consts::FRAC_PI_2;
+/// Angle each arm of a pincer swings out from the squad's approach. Short of a right angle,
+/// so the two arms do not fire straight at each other across the enemy.
+pub const PINCER_ANGLE: f32 = 3.0 * std::f32::consts::FRAC_PI_8;
+/// A closer enemy only takes the squad's focus when it is this much closer than the
+/// focused one, so the squad does not switch back and forth between two.
+pub const FOCUS_SWITCH_MARGIN: f32 = 5.0;
+/// Support members look for cover up to this far from their formation position.
+pub const COVER_SEARCH_RADIUS: f32 = 4.0;
+/// Grenades are thrown at enemies between these distances.
+pub const SQUAD_GRENADE_MIN_RANGE: f32 = 8.0;
+pub const SQUAD_GRENADE_MAX_RANGE: f32 = 18.0;
+/// One grenade per squad at a time: the squad waits this long between two throws.
+pub const SQUAD_GRENADE_COOLDOWN_SECS: f32 = 8.0;
+
+/// What a bot does for its squad.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SquadRole {
+    /// Leads straight at the enemy and draws its fire.
+    Pointman,
+    /// Swings wide to open a second line of fire.
+    Flanker,
+    /// Holds back, covers the pointman and throws the squad's grenades.
+    Support,
+}
+
+impl SquadRole {
+    /// Role of the squad member at `index`: one pointman, then flankers and support in turn.
+    pub fn for_member(index: usize) -> Self {
+        match index {
+            0 => SquadRole::Pointman,
+            i if i % 2 == 1 => SquadRole::Flanker,
+            _ => SquadRole::Support,
+        }
+    }
+}
+
+/// How a squad closes on the enemy it focuses.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SquadManeuver {
+    /// The pointman goes straight in while flankers open a second line of fire beside it.
+    #[default]
+    Crossfire,
+    /// The pointman and the flankers swing out to opposite sides while support holds the
+    /// middle.
+    Pincer,
+}
+
+impl SquadManeuver {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "crossfire" => Some(SquadManeuver::Crossfire),
+            "pincer" => Some(SquadManeuver::Pincer),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SquadManeuver::Crossfire => "crossfire",
+            SquadManeuver::Pincer => "pincer",
+        }
+    }
+}
+
+/// Membership of a bot in a squad.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct SquadMember {
+    pub squad: u32,
+    /// Position in the squad; picks the role and which side flankers take.
+    pub index: usize,
+    pub role: SquadRole,
+    pub maneuver: SquadManeuver,
+}
+
+impl SquadMember {
+    pub fn new(squad: u32, index: usize) -> Self {
+        Self {
+            squad,
+            index,
+            role: SquadRole::for_member(index),
+            maneuver: SquadManeuver::default(),
+        }
+    }
+
+    pub fn with_maneuver(mut self, maneuver: SquadManeuver) -> Self {
+        self.maneuver = maneuver;
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct KnownEnemy {
+    pub enemy: Entity,
+    pub position: Vec3,
+    pub last_seen_secs: f32,
+}
+
+/// What a squad knows and has agreed on, shared by all its members.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SquadBlackboard {
+    pub known_enemies: Vec<KnownEnemy>,
+    /// The enemy the whole squad focuses, see `update_focus`.
+    pub focus_enemy: Option<Entity>,
+    /// Cover each support member picked, with the formation position it was picked for.
+    pub cover: HashMap<Entity, (Vec3, Vec3)>,
+    /// Time the squad may throw its next grenade.
+    pub next_grenade_secs: f32,
+}
+
+impl SquadBlackboard {
+    /// Record a sighting, refreshing an enemy that is already known.
+    pub fn spot(&mut self, enemy: Entity, position: Vec3, now_secs: f32) {
+        match self
+            .known_enemies
+            .iter_mut()
+            .find(|known| known.enemy == enemy)
+        {
+            Some(known) => {
+                known.position = position;
+                known.last_seen_secs = now_secs;
+            }
+            None => self.known_enemies.push(KnownEnemy {
+                enemy,
+                position,
+                last_seen_secs: now_secs,
+            }),
+        }
+    }
+
+    /// Drop enemies nobody has seen for `SQUAD_MEMORY_SECS`.
+    pub fn forget_stale(&mut self, now_secs: f32) {
+        self.known_enemies
+            .retain(|known| now_secs - known.last_seen_secs <= SQUAD_MEMORY_SECS);
+    }
+
+    /// Focus the known enemy closest to `squad_center`. The squad sticks to the enemy it
+    /// already focuses while it is known, unless another is `FOCUS_SWITCH_MARGIN` closer.
+    pub fn update_focus(&mut self, squad_center: Vec3) {
+        let distance = |known: &KnownEnemy| known.position.distance(squad_center);
+        let closest = self
+            .known_enemies
+            .iter()
+            .min_by(|a, b| distance(a).total_cmp(&distance(b)));
+        let current = self.focus();
+        self.focus_enemy = match (current, closest) {
+            (Some(current), Some(closest))
+                if distance(closest) + FOCUS_SWITCH_MARGIN >= distance(&current) =>
+            {
+                Some(current.enemy)
+            }
+            (_, closest) => closest.map(|known| known.enemy),
+        };
+    }
+
+    /// The enemy the whole squad focuses, if it is still known.
+    pub fn focus(&self) -> Option<KnownEnemy> {
+        let enemy = self.focus_enemy?;
+        self.known_enemies
+            .iter()
+            .find(|known| known.enemy == enemy)
+            .copied()
+    }
+
+    /// Claim the squad's grenade. Only one member throws per cooldown.
+    pub fn try_claim_grenade(&mut self, now_secs: f32) -> bool {
+        if now_secs < self.next_grenade_secs {
+            return false;
+        }
+        self.next_grenade_secs = now_secs + SQUAD_GRENADE_COOLDOWN_SECS;
+        true
+    }
+}
+
+/// Where a member of the squad should stand while engaging `enemy`, given the direction
+/// the squad approaches from (`squad_center` toward `enemy`). In a crossfire, pointman and
+/// flankers keep `CROSSFIRE_ANGLE` between their lines of fire and flankers alternate sides
+/// by index; in a pincer, the pointman swings out `PINCER_ANGLE` to one side and the
+/// flankers to the other.
+pub fn formation_position(member: &SquadMember, squad_center: Vec3, enemy: Vec3) -> Vec3 {
+    let approach = (squad_center - enemy).with_y(0.0).normalize_or(Vec3::Z);
+    let offset = match (member.maneuver, member.role) {
+        (SquadManeuver::Crossfire, SquadRole::Pointman) => approach * POINTMAN_DISTANCE,
+        (SquadManeuver::Pincer, SquadRole::Pointman) => {
+            Quat::from_rotation_y(PINCER_ANGLE) * approach * POINTMAN_DISTANCE
+        }
+        (SquadManeuver::Pincer, SquadRole::Flanker) => {
+            Quat::from_rotation_y(-PINCER_ANGLE) * approach * FLANK_DISTANCE
+        }
+        (SquadManeuver::Crossfire, SquadRole::Flanker) => {
+            let side = if (member.index / 2) % 2 == 0 {
+                1.0
+            } else {
+                -1.0
+            };
+            Quat::from_rotation_y(side * CROSSFIRE_ANGLE) * approach * FLANK_DISTANCE
+        }
+        (_, SquadRole::Support) => {
+            let side = if (member.index / 2) % 2 == 0 {
+                -1.0
+            } else {
+                1.0
+            };
+            Quat::from_rotation_y(side * 0.3) * approach * SUPPORT_DISTANCE
+        }
+    };
+    Vec3::new(enemy.x + offset.x, squad_center.y, enemy.z + offset.z)
+}
+
+/// Spots a support member may take cover at around its formation position `spot`, nearest
+/// first: the spot itself, then rings at half and all of `COVER_SEARCH_RADIUS`.
+pub fn cover_candidates(spot: Vec3) -> impl Iterator<Item = Vec3> {
+    const PER_RING: usize = 8;
+    let ring = |radius: f32| {
+        (0..PER_RING).map(move |i| {
+            let angle = i as f32 * std::f32::consts::TAU / PER_RING as f32;
+            spot + Quat::from_rotation_y(angle) * Vec3::Z * radius
+        })
+    };
+    std::iter::once(spot)
+        .chain(ring(COVER_SEARCH_RADIUS * 0.5))
+        .chain(ring(COVER_SEARCH_RADIUS))
+}
+
+/// Whether a grenade thrown by `thrower` at `target` is worth it and will not catch any
+/// of the `squadmates`.
+pub fn grenade_is_safe(thrower: Vec3, target: Vec3, squadmates: &[Vec3]) -> bool {
+    let range = thrower.distance(target);
+    (SQUAD_GRENADE_MIN_RANGE..=SQUAD_GRENADE_MAX_RANGE).contains(&range)
+        && squadmates
+            .iter()
+            .all(|mate| mate.distance(target) > GRENADE_BLAST_RADIUS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        COVER_SEARCH_RADIUS, CROSSFIRE_ANGLE, FOCUS_SWITCH_MARGIN, PINCER_ANGLE, SQUAD_MEMORY_SECS,
+        SquadBlackboard, SquadManeuver, SquadMember, SquadRole, cover_candidates,
+        formation_position, grenade_is_safe,
+    };
+    use bevy::prelude::{Entity, Vec3};
+
+    #[test]
+    fn squads_get_one_pointman_then_flankers_and_support() {
+        let roles: Vec<SquadRole> = (0..4).map(SquadRole::for_member).collect();
+        assert_eq!(
+            roles,
+            vec![
+                SquadRole::Pointman,
+                SquadRole::Flanker,
+                SquadRole::Support,
+                SquadRole::Flanker
+            ]
+        );
+    }
+
+    #[test]
+    fn pointman_and_flanker_catch_the_enemy_in_a_crossfire() {
+        let squad_center = Vec3::new(0.0, 1.0, 20.0);
+        let enemy = Vec3::new(0.0, 1.0, 0.0);
+
+        let point = formation_position(&SquadMember::new(0, 0), squad_center, enemy);
+        let flank = formation_position(&SquadMember::new(0, 1), squad_center, enemy);
+        let other_flank = formation_position(&SquadMember::new(0, 3), squad_center, enemy);
+        let support = formation_position(&SquadMember::new(0, 2), squad_center, enemy);
+
+        let angle = (point - enemy).angle_between(flank - enemy);
+        assert!((angle - CROSSFIRE_ANGLE).abs() < 1e-3);
+        assert!(
+            (flank.x > 0.0) != (other_flank.x > 0.0),
+            "flankers split sides"
+        );
+        assert!(support.distance(enemy) > point.distance(enemy));
+        assert_eq!(point.y, squad_center.y);
+    }
+
+    #[test]
+    fn pincer_arms_close_from_opposite_sides() {
+        let squad_center = Vec3::new(0.0, 1.0, 20.0);
+        let enemy = Vec3::new(0.0, 1.0, 0.0);
+        let member = |index| SquadMember::new(0, index).with_maneuver(SquadManeuver::Pincer);
+
+        let point = formation_position(&member(0), squad_center, enemy);
+        let flank = formation_position(&member(1), squad_center, enemy);
+        let other_flank = formation_position(&member(3), squad_center, enemy);
+        let support = formation_position(&member(2), squad_center, enemy);
+
+        assert!((point.x > 0.0) != (flank.x > 0.0), "arms split sides");
+        assert_eq!(flank, other_flank, "flankers form one arm");
+        let angle = (point - enemy).angle_between(flank - enemy);
+        assert!((angle - 2.0 * PINCER_ANGLE).abs() < 1e-3);
+        assert_eq!(
+            support,
+            formation_position(&SquadMember::new(0, 2), squad_center, enemy),
+            "support holds the middle either way"
+        );
+        assert_eq!(SquadManeuver::parse("Pincer"), Some(SquadManeuver::Pincer));
+        assert_eq!(SquadManeuver::parse("wedge"), None);
+    }
+
+    #[test]
+    fn blackboard_focuses_the_closest_enemy_and_forgets_stale_ones() {
+        let mut blackboard = SquadBlackboard::default();
+        blackboard.spot(Entity::PLACEHOLDER, Vec3::new(30.0, 0.0, 0.0), 0.0);
+        blackboard.update_focus(Vec3::ZERO);
+        assert_eq!(
+            blackboard.focus().map(|known| known.position),
+            Some(Vec3::new(30.0, 0.0, 0.0))
+        );
+
+        blackboard.spot(Entity::PLACEHOLDER, Vec3::new(5.0, 0.0, 0.0), 1.0);
+        assert_eq!(
+            blackboard.known_enemies.len(),
+            1,
+            "sightings refresh the entry"
+        );
+
+        blackboard.forget_stale(1.0 + SQUAD_MEMORY_SECS + 0.1);
+        blackboard.update_focus(Vec3::ZERO);
+        assert_eq!(blackboard.focus(), None);
+    }
+
+    #[test]
+    fn focus_only_switches_to_a_clearly_closer_enemy() {
+        let first = Entity::from_raw_u32(1).unwrap();
+        let second = Entity::from_raw_u32(2).unwrap();
+        let mut blackboard = SquadBlackboard::default();
+        blackboard.spot(first, Vec3::new(10.0, 0.0, 0.0), 0.0);
+        blackboard.update_focus(Vec3::ZERO);
+
+        let slightly_closer = 10.0 - FOCUS_SWITCH_MARGIN * 0.5;
+        blackboard.spot(second, Vec3::new(0.0, 0.0, slightly_closer), 0.0);
+        blackboard.update_focus(Vec3::ZERO);
+        assert_eq!(blackboard.focus_enemy, Some(first));
+
+        blackboard.spot(second, Vec3::new(0.0, 0.0, 2.0), 1.0);
+        blackboard.update_focus(Vec3::ZERO);
+        assert_eq!(blackboard.focus_enemy, Some(second));
+    }
+
+    #[test]
+    fn cover_is_searched_nearest_first() {
+        let spot = Vec3::new(3.0, 1.0, -2.0);
+        let candidates: Vec<Vec3> = cover_candidates(spot).collect();
+        assert_eq!(candidates[0], spot);
+        assert!(
+            candidates
+                .windows(2)
+                .all(|pair| { pair[0].distance(spot) <= pair[1].distance(spot) + 1e-4 })
+        );
+        assert!(candidates.iter().all(|candidate| candidate.distance(spot)
+            <= COVER_SEARCH_RADIUS + 1e-4
+            && candidate.y == spot.y));
+    }
+
+    #[test]
+    fn one_grenade_per_squad_and_never_on_squadmates() {
+        let mut blackboard = SquadBlackboard::default();
+        assert!(blackboard.try_claim_grenade(0.0));
+        assert!(!blackboard.try_claim_grenade(1.0));
+
+        let thrower = Vec3::ZERO;
+        let target = Vec3::new(0.0, 0.0, -12.0);
+        assert!(grenade_is_safe(
+            thrower,
+            target,
+            &[Vec3::new(8.0, 0.0, 0.0)]
+        ));
+        assert!(!grenade_is_safe(
+            thrower,
+            target,
+            &[Vec3::new(0.0, 0.0, -10.0)]
+        ));
+        assert!(!grenade_is_safe(thrower, Vec3::new(0.0, 0.0, -3.0), &[]));
+    }
+}
@@ -0,0 +1,111 @@
+//! Tracing spans over the phases of a server frame, so a trace (see the launcher's
+//! `--trace-chrome`) shows where each tick's time goes.
+
+use avian3d::prelude::PhysicsSystems;
+use bevy::diagnostic::FrameCount;
+use bevy::log::info_span;
+use bevy::log::tracing::{Id, Span};
+use bevy::prelude::{
+    App, First, FixedPostUpdate, FixedUpdate, IntoScheduleConfigs, Last, Plugin, PostUpdate,
+    PreUpdate, Res, ResMut, Resource,
+};
+use lightyear::prelude::{LinkSystems, MessageSystems, TransportSystems};
+
+use crate::inputs::movement::{apply_movement, update_ground_detection};
+
+/// Open spans of the running frame. Phases are children of `tick`; dropping a span closes it.
+#[derive(Resource, Default)]
+pub struct TickSpans {
+    tick: Option<Span>,
+    network_receive: Option<Span>,
+    input_application: Option<Span>,
+    physics: Option<Span>,
+    replication_send: Option<Span>,
+}
+
+impl TickSpans {
+    fn tick_id(&self) -> Option<Id> {
+        self.tick.as_ref().and_then(Span::id)
+    }
+}
+
+/// One `tick` span per frame with a child span per pipeline phase: network receive, input
+/// application and physics (once per fixed step) and replication send.
+pub struct TickTracePlugin;
+
+impl Plugin for TickTracePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TickSpans>();
+        app.add_systems(First, open_tick_span);
+        app.add_systems(Last, close_tick_span);
+        app.add_systems(
+            PreUpdate,
+            (
+                open_network_receive_span.before(LinkSystems::Receive),
+                close_network_receive_span.after(MessageSystems::Receive),
+            ),
+        );
+        app.add_systems(
+            FixedUpdate,
+            (
+                open_input_application_span.before(update_ground_detection),
+                close_input_application_span.after(apply_movement),
+            ),
+        );
+        app.add_systems(
+            FixedPostUpdate,
+            (
+                open_physics_span.before(PhysicsSystems::StepSimulation),
+                close_physics_span.after(PhysicsSystems::StepSimulation),
+            ),
+        );
+        // Replication is packed early in PostUpdate, ahead of the transport and link sends.
+        app.add_systems(
+            PostUpdate,
+            (
+                open_replication_send_span.before(TransportSystems::Send),
+                close_replication_send_span.after(LinkSystems::Send),
+            ),
+        );
+    }
+}
+
+fn open_tick_span(frame: Res<FrameCount>, mut spans: ResMut<TickSpans>) {
+    spans.tick = Some(info_span!("tick", frame = frame.0));
+}
+
+fn close_tick_span(mut spans: ResMut<TickSpans>) {
+    *spans = TickSpans::default();
+}
+
+fn open_network_receive_span(mut spans: ResMut<TickSpans>) {
+    spans.network_receive = Some(info_span!(parent: spans.tick_id(), "network_receive"));
+}
+
+fn close_network_receive_span(mut spans: ResMut<TickSpans>) {
+    spans.network_receive = None;
+}
+
+fn open_input_application_span(mut spans: ResMut<TickSpans>) {
+    spans.input_application = Some(info_span!(parent: spans.tick_id(), "input_application"));
+}
+
+fn close_input_application_span(mut spans: ResMut<TickSpans>) {
+    spans.input_application = None;
+}
+
+fn open_physics_span(mut spans: ResMut<TickSpans>) {
+    spans.physics = Some(info_span!(parent: spans.tick_id(), "physics"));
+}
+
+fn close_physics_span(mut spans: ResMut<TickSpans>) {
+    spans.physics = None;
+}
+
+fn open_replication_send_span(mut spans: ResMut<TickSpans>) {
+    spans.replication_send = Some(info_span!(parent: spans.tick_id(), "replication_send"));
+}
+
+fn close_replication_send_span(mut spans: ResMut<TickSpans>) {
+    spans.replication_send = None;
+}